@@ -7,19 +7,27 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 // ANCHOR: book_main_cpp
-#include <QtGui/QGuiApplication>
 #include <QtQml/QQmlApplicationEngine>
+#include <QtWidgets/QApplication>
+
+#include "bevyqml/imageprovider.h"
 
 int
 main(int argc, char* argv[])
 {
-  QGuiApplication app(argc, argv);
+  // Lets Bevy render in an OpenGL context sharing textures with the scene
+  // graph, see src/render/interop/opengl.rs
+  QCoreApplication::setAttribute(Qt::AA_ShareOpenGLContexts);
+  // A QApplication rather than a QGuiApplication, for the file dialogs
+  QApplication app(argc, argv);
 
   QQmlApplicationEngine engine;
+  engine.addImageProvider(QStringLiteral("bevy"),
+                          new bevyqml::BevyImageProvider);
 
   // ANCHOR: book_qml_url
   const QUrl url(
-    QStringLiteral("qrc:/qt/qml/com/kdab/cxx_qt/demo/qml/main.qml"));
+    QStringLiteral("qrc:/qt/qml/BevyQml/qml/main.qml"));
   // ANCHOR_END: book_qml_url
   QObject::connect(
     &engine,
@@ -0,0 +1,290 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Derive macros for `qml_minimal`.
+//!
+//! cxx-qt-build generates the C++ side of a QObject from the bridge source
+//! files before the crate is compiled, so a macro cannot emit new QObjects.
+//! Instead [QmlComponent] generates the field accessors that the generic
+//! `BevyComponent` QML element uses to show and edit the component.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Expose the named fields of a Bevy component to QML
+///
+/// Every field has to implement `qml_minimal::variant::QmlValue`.
+///
+/// - `#[qml(name = "...")]` on the struct changes the name QML refers to it
+///   by, which defaults to the struct name.
+/// - `#[qml(rename = "...")]` on a field changes its name in QML, which no
+///   other exposed field may have.
+/// - `#[qml(skip)]` on a field hides it from QML.
+#[proc_macro_derive(QmlComponent, attributes(qml))]
+pub fn derive_qml_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct QmlField {
+    ident: syn::Ident,
+    name: String,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let mut qml_name = ident.to_string();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("qml"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                qml_name = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("expected `name = \"...\"`"))
+            }
+        })?;
+    }
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "QmlComponent can only be derived for structs",
+        ));
+    };
+    let Fields::Named(named) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "QmlComponent requires a struct with named fields",
+        ));
+    };
+
+    let mut fields: Vec<QmlField> = Vec::new();
+    for field in &named.named {
+        let field_ident = field.ident.clone().expect("named field");
+        let mut name = field_ident.to_string();
+        let mut skip = false;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("qml"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `skip` or `rename = \"...\"`"))
+                }
+            })?;
+        }
+        if skip {
+            continue;
+        }
+        if fields.iter().any(|other| other.name == name) {
+            return Err(syn::Error::new_spanned(
+                &field_ident,
+                format!("another field is already exposed to QML as `{name}`"),
+            ));
+        }
+        fields.push(QmlField {
+            ident: field_ident,
+            name,
+        });
+    }
+
+    let names: Vec<_> = fields.iter().map(|field| &field.name).collect();
+    let idents: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::qml_minimal::component::QmlComponent for #ident #ty_generics #where_clause {
+            const QML_NAME: &'static str = #qml_name;
+
+            fn qml_fields() -> &'static [&'static str] {
+                &[#(#names),*]
+            }
+
+            fn qml_read(&self) -> ::std::vec::Vec<(&'static str, ::qml_minimal::variant::QVariant)> {
+                ::std::vec![
+                    #((#names, ::qml_minimal::variant::QmlValue::to_variant(&self.#idents))),*
+                ]
+            }
+
+            fn qml_write(&mut self, field: &str, value: &::qml_minimal::variant::QVariant) -> bool {
+                match field {
+                    #(
+                        #names => match ::qml_minimal::variant::QmlValue::from_variant(value) {
+                            ::std::option::Option::Some(value) => {
+                                self.#idents = value;
+                                true
+                            }
+                            ::std::option::Option::None => false,
+                        },
+                    )*
+                    _ => false,
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_to_string(input: DeriveInput) -> String {
+        expand(input).unwrap().to_string()
+    }
+
+    fn expand_error(input: DeriveInput) -> String {
+        expand(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn exposes_named_fields() {
+        let expanded = expand_to_string(syn::parse_quote! {
+            #[qml(name = "Vitals")]
+            struct Health {
+                current: f32,
+                #[qml(rename = "maximum")]
+                max: f32,
+                #[qml(skip)]
+                regeneration: Timer,
+            }
+        });
+        let expected = quote! {
+            impl ::qml_minimal::component::QmlComponent for Health {
+                const QML_NAME: &'static str = "Vitals";
+
+                fn qml_fields() -> &'static [&'static str] {
+                    &["current", "maximum"]
+                }
+
+                fn qml_read(&self) -> ::std::vec::Vec<(&'static str, ::qml_minimal::variant::QVariant)> {
+                    ::std::vec![
+                        ("current", ::qml_minimal::variant::QmlValue::to_variant(&self.current)),
+                        ("maximum", ::qml_minimal::variant::QmlValue::to_variant(&self.max))
+                    ]
+                }
+
+                fn qml_write(&mut self, field: &str, value: &::qml_minimal::variant::QVariant) -> bool {
+                    match field {
+                        "current" => match ::qml_minimal::variant::QmlValue::from_variant(value) {
+                            ::std::option::Option::Some(value) => {
+                                self.current = value;
+                                true
+                            }
+                            ::std::option::Option::None => false,
+                        },
+                        "maximum" => match ::qml_minimal::variant::QmlValue::from_variant(value) {
+                            ::std::option::Option::Some(value) => {
+                                self.max = value;
+                                true
+                            }
+                            ::std::option::Option::None => false,
+                        },
+                        _ => false,
+                    }
+                }
+            }
+        };
+        assert_eq!(expanded, expected.to_string());
+    }
+
+    #[test]
+    fn names_the_component_after_the_struct() {
+        let expanded = expand_to_string(syn::parse_quote! {
+            struct Marker {}
+        });
+        let name = quote! { const QML_NAME: &'static str = "Marker"; };
+        assert!(expanded.contains(&name.to_string()));
+        let fields = quote! { &[] };
+        assert!(expanded.contains(&fields.to_string()));
+    }
+
+    #[test]
+    fn keeps_generics() {
+        let expanded = expand_to_string(syn::parse_quote! {
+            struct Tagged<T: Send> where T: Sync {
+                tag: T,
+            }
+        });
+        let head = quote! {
+            impl<T: Send> ::qml_minimal::component::QmlComponent for Tagged<T> where T: Sync
+        };
+        assert!(expanded.starts_with(&head.to_string()));
+    }
+
+    #[test]
+    fn rejects_enums() {
+        let error = expand_error(syn::parse_quote! {
+            enum Mode {
+                On,
+                Off,
+            }
+        });
+        assert_eq!(error, "QmlComponent can only be derived for structs");
+    }
+
+    #[test]
+    fn rejects_tuple_structs() {
+        let error = expand_error(syn::parse_quote! {
+            struct Speed(f32);
+        });
+        assert_eq!(error, "QmlComponent requires a struct with named fields");
+    }
+
+    #[test]
+    fn rejects_unknown_attributes() {
+        let error = expand_error(syn::parse_quote! {
+            #[qml(rename = "Other")]
+            struct Health {
+                current: f32,
+            }
+        });
+        assert_eq!(error, "expected `name = \"...\"`");
+
+        let error = expand_error(syn::parse_quote! {
+            struct Health {
+                #[qml(hidden)]
+                current: f32,
+            }
+        });
+        assert_eq!(error, "expected `skip` or `rename = \"...\"`");
+    }
+
+    #[test]
+    fn rejects_duplicate_names() {
+        let error = expand_error(syn::parse_quote! {
+            struct Health {
+                current: f32,
+                #[qml(rename = "current")]
+                max: f32,
+            }
+        });
+        assert_eq!(
+            error,
+            "another field is already exposed to QML as `current`"
+        );
+
+        // Skipped fields do not take a name
+        expand_to_string(syn::parse_quote! {
+            struct Health {
+                #[qml(skip)]
+                current: f32,
+                #[qml(rename = "current")]
+                max: f32,
+            }
+        });
+    }
+}
@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Turns the task ids returned by the async invokables of the BevyQml types
+// into promises, which settle when the object emits taskFinished or
+// taskFailed with the task id. onProgress, if given, is called with the
+// taskProgress of the task, for the objects which emit it.
+.pragma library
+
+function promise(target, taskId, onProgress) {
+    return new Promise(function (resolve, reject) {
+        if (!taskId) {
+            reject("The task was not started");
+            return;
+        }
+        var reportsProgress = onProgress && target.taskProgress !== undefined;
+        function disconnect() {
+            target.taskFinished.disconnect(onFinished);
+            target.taskFailed.disconnect(onFailed);
+            if (reportsProgress)
+                target.taskProgress.disconnect(onTaskProgress);
+        }
+        function onFinished(id, result) {
+            if (id === taskId) {
+                disconnect();
+                resolve(result);
+            }
+        }
+        function onFailed(id, error) {
+            if (id === taskId) {
+                disconnect();
+                reject(error);
+            }
+        }
+        function onTaskProgress(id, progress) {
+            if (id === taskId)
+                onProgress(progress);
+        }
+        target.taskFinished.connect(onFinished);
+        target.taskFailed.connect(onFailed);
+        if (reportsProgress)
+            target.taskProgress.connect(onTaskProgress);
+    });
+}
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+import QtQuick 2.12
+import QtQuick.Window 2.12
+
+import BevyQml 1.0
+
+// A minimap showing the world from above, with -Z at the top. It renders
+// through its own orthographic camera into a small image, see
+// BevyMiniViewCamera, and emits teleportRequested with the point of the world
+// that was clicked.
+//   BevyMiniView {
+//       name: "minimap"
+//       followEntity: player
+//       extent: 80
+//       width: 200; height: 200
+//       onTeleportRequested: position => commands.sendEvent("Teleport", { position: position })
+//   }
+// Every mini view needs a name of its own, as the image is published as
+// image://bevy/<name>.
+Item {
+    id: root
+
+    property alias name: camera.name
+    // The bits of the entity the view follows, or 0 to stay above center
+    property alias followEntity: camera.followEntity
+    property alias center: camera.center
+    // How much of the world the width of the view covers
+    property alias extent: camera.extent
+    property alias altitude: camera.altitude
+    // The bits of the camera entity
+    readonly property alias entity: camera.entity
+
+    // Emitted with the point of the world under a click
+    signal teleportRequested(vector3d position)
+
+    // The point of the world shown at a position of the view, or NaN
+    function mapToWorld(x, y) {
+        return camera.mapToWorld(x / Math.max(root.width, 1), y / Math.max(root.height, 1));
+    }
+
+    implicitHeight: 200
+    implicitWidth: 200
+
+    BevyMiniViewCamera {
+        id: camera
+
+        imageHeight: Math.max(1, Math.round(root.height * Screen.devicePixelRatio))
+        imageWidth: Math.max(1, Math.round(root.width * Screen.devicePixelRatio))
+    }
+
+    BevyTextureSource {
+        id: texture
+
+        name: camera.name
+    }
+
+    Image {
+        anchors.fill: parent
+        cache: false
+        smooth: true
+        source: texture.source
+    }
+
+    MouseArea {
+        anchors.fill: parent
+
+        onClicked: mouse => {
+            const position = root.mapToWorld(mouse.x, mouse.y);
+            if (!isNaN(position.x)) {
+                root.teleportRequested(position);
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+import QtQuick 2.12
+
+import BevyQml 1.0
+
+// A stats overlay for a BevyQuickItem, showing a graph of the recent frame
+// rates, frame time percentiles, the GPU time of the render passes where the
+// GPU can time them, draw calls and the number of entities. It ships as a
+// type of the module, so any QML file can use it after import BevyQml 1.0:
+//   BevyStatsOverlay { anchors.top: parent.top; anchors.right: parent.right }
+Rectangle {
+    id: root
+
+    // How many updates the graph and the percentiles cover
+    property alias capacity: history.capacity
+    property color textColor: "white"
+    property color graphColor: "#7fd87f"
+    property color gpuBoundColor: "#ffb347"
+
+    color: "#a0000000"
+    implicitHeight: column.implicitHeight + 16
+    implicitWidth: 220
+    radius: 4
+
+    DiagnosticsHistoryModel {
+        id: history
+    }
+
+    Column {
+        id: column
+
+        anchors.fill: parent
+        anchors.margins: 8
+        spacing: 4
+
+        Text {
+            color: root.textColor
+            font.bold: true
+            text: qsTr("%1 fps").arg(DiagnosticsBridge.fps.toFixed(0))
+        }
+
+        ListView {
+            id: graph
+
+            height: 40
+            interactive: false
+            model: history
+            orientation: ListView.Horizontal
+            width: parent.width
+
+            delegate: Item {
+                height: graph.height
+                width: graph.width / Math.max(1, history.capacity)
+
+                Rectangle {
+                    anchors.bottom: parent.bottom
+                    color: root.graphColor
+                    height: history.maxFps > 0 ? parent.height * model.fps / history.maxFps : 0
+                    width: Math.max(1, parent.width)
+                }
+            }
+        }
+
+        Text {
+            color: root.textColor
+            text: qsTr("Frame %1 ms, p50 %2, p95 %3, p99 %4")
+                .arg(DiagnosticsBridge.frameTime.toFixed(1))
+                .arg(history.frameTimeP50.toFixed(1))
+                .arg(history.frameTimeP95.toFixed(1))
+                .arg(history.frameTimeP99.toFixed(1))
+        }
+
+        Text {
+            // Highlighted while the frames wait for the GPU
+            color: DiagnosticsBridge.gpuBound ? root.gpuBoundColor : root.textColor
+            text: qsTr("GPU %1 ms: shadow %2, opaque %3, transparent %4, post %5")
+                .arg(DiagnosticsBridge.gpuTime.toFixed(1))
+                .arg(DiagnosticsBridge.gpuShadowTime.toFixed(1))
+                .arg(DiagnosticsBridge.gpuOpaqueTime.toFixed(1))
+                .arg(DiagnosticsBridge.gpuTransparentTime.toFixed(1))
+                .arg(DiagnosticsBridge.gpuPostTime.toFixed(1))
+            visible: DiagnosticsBridge.gpuTimed
+            width: parent.width
+            wrapMode: Text.Wrap
+        }
+
+        Text {
+            color: root.textColor
+            text: qsTr("%1 draw calls").arg(DiagnosticsBridge.drawCalls)
+        }
+
+        Text {
+            color: root.textColor
+            text: qsTr("%1 entities").arg(DiagnosticsBridge.entityCount)
+        }
+    }
+}
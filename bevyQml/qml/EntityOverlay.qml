@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+import QtQuick 2.12
+
+import BevyQml 1.0
+
+// Keeps its children centered over an entity shown by a BevyQuickItem, for
+// name tags, health bars and other plain QML items placed in the scene.
+//   BevyQuickItem {
+//       id: view
+//       EntityOverlay {
+//           entity: player
+//           offset: Qt.vector3d(0, 2, 0)
+//           occlusion: true
+//           fadeStart: 20; fadeEnd: 30
+//           Label { text: "Player" }
+//       }
+//   }
+// The overlay hides while the entity is behind the camera. With occlusion
+// set, it takes occludedOpacity while other meshes hide the entity. Between
+// fadeStart and fadeEnd from the camera it fades out, unless fadeEnd is 0.
+Item {
+    id: root
+
+    // The BevyQuickItem showing the entity, and the item the overlay moves in
+    property Item view: parent
+    // The bits of the entity
+    property var entity: 0
+    // Moves the anchor from the origin of the entity, in world space
+    property vector3d offset: Qt.vector3d(0, 0, 0)
+    property bool occlusion: false
+    property real occludedOpacity: 0
+    property real fadeStart: 0
+    property real fadeEnd: 0
+
+    readonly property bool onScreen: internal.onScreen
+    readonly property bool occluded: internal.occluded
+    // The distance of the anchor from the camera
+    readonly property real distance: internal.distance
+
+    function refresh() {
+        if (!root.view || !root.entity) {
+            internal.onScreen = false;
+            return;
+        }
+        const projection = root.view.projectEntity(root.entity, root.offset, root.occlusion);
+        internal.onScreen = projection.position !== undefined;
+        if (!internal.onScreen) {
+            return;
+        }
+        const position = root.view.mapToItem(root.parent, projection.position);
+        root.x = position.x - root.width / 2;
+        root.y = position.y - root.height / 2;
+        internal.distance = projection.distance;
+        internal.occluded = projection.occluded;
+    }
+
+    implicitHeight: childrenRect.height
+    implicitWidth: childrenRect.width
+    opacity: {
+        let opacity = internal.occluded ? root.occludedOpacity : 1;
+        if (root.fadeEnd > 0 && internal.distance > root.fadeStart) {
+            const range = Math.max(root.fadeEnd - root.fadeStart, 0.0001);
+            opacity *= Math.max(0, 1 - (internal.distance - root.fadeStart) / range);
+        }
+        return opacity;
+    }
+    visible: internal.onScreen && opacity > 0
+
+    Component.onCompleted: refresh()
+    onEntityChanged: refresh()
+    onOffsetChanged: refresh()
+    onOcclusionChanged: refresh()
+    onWidthChanged: refresh()
+    onHeightChanged: refresh()
+
+    QtObject {
+        id: internal
+
+        property bool onScreen: false
+        property bool occluded: false
+        property real distance: 0
+    }
+
+    Connections {
+        function onSceneUpdated() {
+            root.refresh();
+        }
+        function onWidthChanged() {
+            root.refresh();
+        }
+        function onHeightChanged() {
+            root.refresh();
+        }
+
+        target: root.view
+    }
+}
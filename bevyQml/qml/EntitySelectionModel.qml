@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+import QtQml 2.12
+import QtQml.Models 2.12
+
+import BevyQml 1.0
+
+// An ItemSelectionModel for an EntityTreeModel or QueryListModel which is
+// kept in step with the Bevy selection in both directions.
+ItemSelectionModel {
+    id: root
+
+    property bool syncing: false
+
+    function selectEntities() {
+        if (!root.model) {
+            return;
+        }
+        root.syncing = true;
+        root.clearSelection();
+        for (let i = 0; i < BevySelection.entities.length; ++i) {
+            const index = root.model.indexOf(BevySelection.entities[i]);
+            if (index.valid) {
+                root.select(index, ItemSelectionModel.Select | ItemSelectionModel.Rows);
+            }
+        }
+        root.syncing = false;
+    }
+
+    Component.onCompleted: selectEntities()
+    onModelChanged: selectEntities()
+    onSelectionChanged: {
+        if (!root.syncing) {
+            BevySelection.selectIndexes(root.selectedIndexes);
+        }
+    }
+
+    Connections {
+        function onEntitiesChanged() {
+            root.selectEntities();
+        }
+
+        target: BevySelection
+    }
+}
@@ -12,7 +12,7 @@ import QtQuick.Window 2.12
 // ANCHOR: book_qml_import
 // This must match the uri and version
 // specified in the qml_module in the build.rs script.
-import com.kdab.cxx_qt.demo 1.0
+import BevyQml 1.0
 // ANCHOR_END: book_qml_import
 
 Window {
@@ -27,6 +27,30 @@ Window {
         string: qsTr("My String with my number: %1").arg(myObject.number)
     }
 
+    MyBevyApp {
+        id: bevyApp
+
+        onBevyError: message => console.warn(message)
+    }
+
+    BevyResource {
+        id: demoSettings
+        name: "CurveDemoSettings"
+    }
+
+    BevyQuickItem {
+        anchors.fill: parent
+        selectOnClick: true
+
+        onDeviceLost: reason => console.warn(qsTr("The graphics device was lost: %1").arg(reason))
+    }
+
+    BevyStatsOverlay {
+        anchors.margins: 8
+        anchors.right: parent.right
+        anchors.top: parent.top
+    }
+
     Column {
         anchors.fill: parent
         anchors.margins: 10
@@ -52,10 +76,17 @@ Window {
             onClicked: myObject.sayHi(myObject.string, myObject.number)
         }
 
+        CheckBox {
+            checked: demoSettings.values.show_curve === true
+            text: qsTr("Show Curve")
+
+            onToggled: demoSettings.setValue("show_curve", checked)
+        }
+
         Button {
             text: qsTr("Quit")
 
-            onClicked: Qt.quit()
+            onClicked: bevyApp.quit()
         }
     }
 }
@@ -5,19 +5,261 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 // ANCHOR: book_build_rs
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
 use cxx_qt_build::{CxxQtBuilder, QmlModule};
 
+/// The URI of the QML module, imported as `import BevyQml 1.0`
+const QML_URI: &str = "BevyQml";
+
+/// The URI of the QObjects defined in Rust, which the QML module imports so
+/// that `import BevyQml 1.0` brings them along
+const QML_RUST_URI: &str = "BevyQml.Rust";
+
+/// The version of the QML modules, as major and minor version
+const QML_VERSION: (usize, usize) = (1, 0);
+
+/// The QML and JavaScript files of the QML module, those named like types
+/// are declared as types of the module
+const QML_FILES: &[&str] = &[
+    "../qml/main.qml",
+    "../qml/EntitySelectionModel.qml",
+    "../qml/BevyStatsOverlay.qml",
+    "../qml/EntityOverlay.qml",
+    "../qml/BevyMiniView.qml",
+    "../qml/BevyAsync.js",
+];
+
 fn main() {
+    println!("cargo:rerun-if-changed=cpp");
+    println!("cargo:rerun-if-changed=include");
+
+    let mut rust_files = vec![
+        "src/cxxqt_object.rs",
+        "src/cxxqt_bevy_action_model.rs",
+        "src/cxxqt_bevy_animation_player.rs",
+        "src/cxxqt_bevy_app.rs",
+        "src/cxxqt_bevy_asset_load.rs",
+        "src/cxxqt_bevy_assets.rs",
+        "src/cxxqt_bevy_benchmark.rs",
+        "src/cxxqt_bevy_camera.rs",
+        "src/cxxqt_bevy_camera_stats_model.rs",
+        "src/cxxqt_bevy_clipboard.rs",
+        "src/cxxqt_bevy_commands.rs",
+        "src/cxxqt_bevy_component.rs",
+        "src/cxxqt_bevy_diagnostics.rs",
+        "src/cxxqt_bevy_diagnostics_history.rs",
+        "src/cxxqt_bevy_document.rs",
+        "src/cxxqt_bevy_entity.rs",
+        "src/cxxqt_bevy_entity_tree_model.rs",
+        "src/cxxqt_bevy_event_listener.rs",
+        "src/cxxqt_bevy_gamepad_model.rs",
+        "src/cxxqt_bevy_gizmos.rs",
+        "src/cxxqt_bevy_gltf_model.rs",
+        "src/cxxqt_bevy_graphics_adapter.rs",
+        "src/cxxqt_bevy_input_map_model.rs",
+        "src/cxxqt_bevy_inspection_camera.rs",
+        "src/cxxqt_bevy_layer_group.rs",
+        "src/cxxqt_bevy_light.rs",
+        "src/cxxqt_bevy_light_bridge.rs",
+        "src/cxxqt_bevy_log_model.rs",
+        "src/cxxqt_bevy_material.rs",
+        "src/cxxqt_bevy_memory_diagnostics.rs",
+        "src/cxxqt_bevy_mesh.rs",
+        "src/cxxqt_bevy_mini_view_camera.rs",
+        "src/cxxqt_bevy_orbit_camera.rs",
+        "src/cxxqt_bevy_query_model.rs",
+        "src/cxxqt_bevy_quick_item.rs",
+        "src/cxxqt_bevy_recording.rs",
+        "src/cxxqt_bevy_render_settings.rs",
+        "src/cxxqt_bevy_resource.rs",
+        "src/cxxqt_bevy_selection.rs",
+        "src/cxxqt_bevy_state.rs",
+        "src/cxxqt_bevy_texture_source.rs",
+        "src/cxxqt_bevy_time.rs",
+        "src/cxxqt_bevy_trace.rs",
+        "src/cxxqt_bevy_transform.rs",
+        "src/cxxqt_bevy_transform_gizmo.rs",
+        "src/cxxqt_bevy_undo_stack.rs",
+        "src/cxxqt_bevy_windows.rs",
+        "src/app.rs",
+        "src/asset/dialog.rs",
+        "src/asset/qrc.rs",
+        "src/clipboard.rs",
+        "src/image.rs",
+        "src/input/raw_motion.rs",
+        "src/log.rs",
+        "src/qml_texture.rs",
+        "src/runtime.rs",
+        "src/settings.rs",
+        "src/theme.rs",
+        "src/variant.rs",
+        "src/window.rs",
+    ];
+    // Render targets shared with the Qt OpenGL, Direct3D and Metal backends,
+    // see src/render/interop
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let apple = matches!(target_os.as_str(), "macos" | "ios");
+    if target_os == "linux" {
+        rust_files.push("src/render/interop/opengl.rs");
+    }
+    if target_os == "windows" {
+        rust_files.push("src/render/interop/d3d12.rs");
+    }
+    if apple {
+        rust_files.push("src/render/interop/iosurface.rs");
+        for framework in ["Foundation", "IOSurface", "Metal"] {
+            println!("cargo:rustc-link-lib=framework={framework}");
+        }
+    }
+    // The QML type for reloading gameplay systems, see src/logic.rs
+    if env::var_os("CARGO_FEATURE_HOT_LOGIC").is_some() {
+        rust_files.push("src/cxxqt_bevy_logic.rs");
+    }
+    // The QML types for snapshot tests, see src/testing.rs
+    if env::var_os("CARGO_FEATURE_TESTING").is_some() {
+        rust_files.extend([
+            "src/cxxqt_bevy_golden_image.rs",
+            "src/cxxqt_bevy_test_app.rs",
+        ]);
+    }
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let module_dir = Path::new(&out_dir).join("bevyqml_module");
+    let qmldir = qmldir();
+
     CxxQtBuilder::new()
         // ANCHOR: book_qml_module
-        .qml_module(QmlModule {
-            uri: "com.kdab.cxx_qt.demo",
-            rust_files: &["src/cxxqt_object.rs","src/cxxqt_bevy_app.rs",],
-            qml_files: &["../qml/main.qml"],
+        .qml_module(QmlModule::<_, &str> {
+            uri: QML_RUST_URI,
+            version_major: QML_VERSION.0,
+            version_minor: QML_VERSION.1,
+            rust_files: &rust_files,
             ..Default::default()
         })
         // ANCHOR_END: book_qml_module
+        .qrc(write_module_resources(&module_dir, &qmldir))
+        .qt_module("Quick")
+        .qt_module("Widgets")
+        .cc_builder(move |cc| {
+            cc.include("include");
+            cc.file("cpp/imageprovider.cpp");
+            cc.file("cpp/interop.cpp");
+            cc.file("cpp/qmltexture.cpp");
+            cc.file("cpp/window.cpp");
+            if apple {
+                cc.file("cpp/metal.mm");
+            }
+        })
         .with_opts(cxx_qt_lib_headers::build_opts())
         .build();
+
+    export_qml_modules(&qmldir);
 }
 // ANCHOR_END: book_build_rs
+
+/// The qmldir of the QML module
+///
+/// cxx-qt-build writes the qmldir of the module holding the Rust types on its
+/// own, without the types of QML files. The QML module is therefore a module
+/// of its own, which declares its QML files as types and imports the Rust
+/// types, and whose qmldir is the same in the library and for QML tooling.
+fn qmldir() -> String {
+    let (major, minor) = QML_VERSION;
+    let mut qmldir = format!("module {QML_URI}\nimport {QML_RUST_URI} {major}.{minor}\n");
+    for file in QML_FILES {
+        let file = Path::new(file);
+        let file_name = file.file_name().unwrap().to_string_lossy();
+        let type_name = file.file_stem().unwrap().to_string_lossy();
+        if type_name.starts_with(|c: char| c.is_ascii_uppercase()) {
+            qmldir.push_str(&format!("{type_name} {major}.{minor} qml/{file_name}\n"));
+        }
+    }
+    qmldir
+}
+
+/// Write the qmldir and a resource file embedding it with the QML files under
+/// `qrc:/qt/qml/BevyQml/`, the default import path, returning the resource
+/// file
+fn write_module_resources(module_dir: &Path, qmldir: &str) -> PathBuf {
+    fs::create_dir_all(module_dir).expect("Could not create the QML module directory");
+    let qmldir_path = module_dir.join("qmldir");
+    write_if_changed(&qmldir_path, qmldir);
+
+    let mut files = format!(
+        "    <file alias=\"qmldir\">{}</file>\n",
+        qmldir_path.display()
+    );
+    for file in QML_FILES {
+        let path = fs::canonicalize(file).expect("Could not find a QML file");
+        let file_name = Path::new(file).file_name().unwrap().to_string_lossy();
+        files.push_str(&format!(
+            "    <file alias=\"qml/{file_name}\">{}</file>\n",
+            path.display()
+        ));
+    }
+    let module_path = QML_URI.replace('.', "/");
+    let qrc_path = module_dir.join("bevyqml_module.qrc");
+    write_if_changed(
+        &qrc_path,
+        &format!(
+            "<RCC>\n<qresource prefix=\"/qt/qml/{module_path}\">\n{files}</qresource>\n</RCC>\n"
+        ),
+    );
+    qrc_path
+}
+
+/// Write a generated file unless it is up to date, as the resource file is
+/// watched for changes and rewriting it would run the build script every time
+fn write_if_changed(path: &Path, contents: &str) {
+    if fs::read_to_string(path).is_ok_and(|current| current == contents) {
+        return;
+    }
+    fs::write(path, contents)
+        .unwrap_or_else(|error| panic!("Could not write {}: {error}", path.display()));
+}
+
+/// Copy the QML modules next to the generated headers, so that QML tooling
+/// such as Qt Creator and qmllint finds them with
+/// `${CXXQT_EXPORT_DIR}/qml_modules` as an import path
+///
+/// cxx-qt-build generates the qmldir and the plugin.qmltypes describing every
+/// `#[qml_element]`, and embeds them in the library. The copy refers to the
+/// QML files on disk rather than in the resources.
+fn export_qml_modules(qmldir: &str) {
+    let Ok(export_dir) = env::var("CXXQT_EXPORT_DIR") else {
+        return;
+    };
+    let exported = Path::new(&export_dir).join("qml_modules");
+
+    let module = exported.join(QML_URI.replace('.', "/"));
+    fs::create_dir_all(module.join("qml")).expect("Could not create the exported QML module");
+    fs::write(module.join("qmldir"), qmldir).expect("Could not write qmldir");
+    for file in QML_FILES {
+        let file = Path::new(file);
+        fs::copy(file, module.join("qml").join(file.file_name().unwrap()))
+            .expect("Could not copy a QML file");
+    }
+
+    let rust_module_path = QML_RUST_URI.replace('.', "/");
+    let generated = Path::new(&env::var("OUT_DIR").unwrap())
+        .join("qml_modules")
+        .join(&rust_module_path);
+    let rust_module = exported.join(&rust_module_path);
+    fs::create_dir_all(&rust_module).expect("Could not create the exported QML module");
+    let rust_qmldir = fs::read_to_string(generated.join("qmldir")).expect("Could not read qmldir");
+    let rust_qmldir: String = rust_qmldir
+        .lines()
+        .filter(|line| !line.starts_with("prefer "))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    fs::write(rust_module.join("qmldir"), rust_qmldir).expect("Could not write qmldir");
+    fs::copy(
+        generated.join("plugin.qmltypes"),
+        rust_module.join("plugin.qmltypes"),
+    )
+    .expect("Could not copy plugin.qmltypes");
+}
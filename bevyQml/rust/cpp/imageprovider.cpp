@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#include "bevyqml/imageprovider.h"
+
+#include "cxx-qt-gen/rust_cxx_qt_bevy_texture_source.cxx.h"
+
+namespace bevyqml {
+
+BevyImageProvider::BevyImageProvider()
+  : QQuickImageProvider(QQuickImageProvider::Image)
+{
+}
+
+QImage
+BevyImageProvider::requestImage(const QString& id,
+                                QSize* size,
+                                const QSize& requestedSize)
+{
+  const ImageFrame frame = latestFrame(id.section(QLatin1Char('/'), 0, 0));
+
+  QImage image;
+  if (!frame.data.empty()) {
+    image = QImage(frame.data.data(),
+                   static_cast<int>(frame.width),
+                   static_cast<int>(frame.height),
+                   static_cast<qsizetype>(frame.width) * 4,
+                   QImage::Format_RGBA8888)
+              .copy();
+  }
+
+  if (size != nullptr) {
+    *size = image.size();
+  }
+
+  if (requestedSize.isValid() && !image.isNull()) {
+    image = image.scaled(
+      requestedSize, Qt::IgnoreAspectRatio, Qt::SmoothTransformation);
+  }
+
+  return image;
+}
+
+}
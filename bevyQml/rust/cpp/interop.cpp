@@ -0,0 +1,930 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#include "bevyqml/interop.h"
+#include "cxx-qt-gen/rust_cxx_qt_bevy_quick_item.cxx.h"
+
+#include <algorithm>
+#include <cstring>
+
+#include <QtQuick/QSGRendererInterface>
+#include <QtQuick/QSGSimpleTextureNode>
+
+#if QT_CONFIG(vulkan) && defined(Q_OS_LINUX)
+#define BEVYQML_VULKAN_INTEROP
+#include <QtGui/QVulkanFunctions>
+#include <QtGui/QVulkanInstance>
+#include <QtQuick/QQuickGraphicsConfiguration>
+#include <QtQuick/QQuickGraphicsDevice>
+#include <QtQuick/qsgtexture_platform.h>
+#include <unistd.h>
+#endif
+
+#if QT_CONFIG(opengl) && defined(Q_OS_LINUX)
+#define BEVYQML_OPENGL_INTEROP
+#include <QtGui/QOpenGLContext>
+#include <QtGui/QOpenGLExtraFunctions>
+#include <QtQuick/qsgtexture_platform.h>
+#endif
+
+#ifdef Q_OS_DARWIN
+#define BEVYQML_METAL_INTEROP
+#include "bevyqml/metal.h"
+#endif
+
+#ifdef Q_OS_WIN
+#define BEVYQML_D3D_INTEROP
+#include <QtQuick/qsgtexture_platform.h>
+#include <d3d11_4.h>
+#include <d3d12.h>
+#include <dxgi.h>
+#endif
+
+namespace bevyqml {
+
+namespace {
+
+// Must match InteropBackend in src/render/interop/mod.rs
+enum class Backend
+{
+  Copy = 0,
+  VulkanExternalMemory = 1,
+  D3DSharedHandle = 2,
+  MetalIOSurface = 3,
+  OpenGLShareContext = 4,
+};
+
+// Must match VulkanHandshake in src/render/interop/mod.rs
+enum class Handshake
+{
+  None = 0,
+  PhysicalDevice = 1,
+  Device = 2,
+};
+
+// A texture node which owns the textures it imported, one for every buffer
+// of the target, and the native objects they wrap. It hands the buffer it
+// draws back to Bevy once the frame has ended.
+class SharedTextureNode : public QSGSimpleTextureNode
+{
+public:
+  static constexpr int MaxBuffers = 3;
+
+  using Release = void (*)(QQuickWindow*, void*);
+  using Acquire = void (*)(void*, ::std::uint64_t);
+  // Called with the key the buffer was taken under, and whether to wait for
+  // the GPU to finish the frame
+  using HandBack = void (*)(QQuickWindow*, void*, ::std::uint64_t, bool);
+
+  explicit SharedTextureNode(QQuickWindow* window)
+    : m_window(window)
+  {
+    setFiltering(QSGTexture::Linear);
+    m_frameEnd = QObject::connect(
+      window,
+      &QQuickWindow::afterFrameEnd,
+      [this] { handBack(); },
+      Qt::DirectConnection);
+  }
+
+  ~SharedTextureNode() override
+  {
+    QObject::disconnect(m_frameEnd);
+    // Bevy must not wait for a frame that is never drawn
+    if (m_pending >= 0) {
+      handBackToSlot(m_pending);
+    }
+    for (Buffer& buffer : m_buffers) {
+      release(buffer);
+    }
+    if (m_slot != 0) {
+      sharedTextureSlotRelease(m_slot);
+    }
+  }
+
+  // Hand buffers back to the slot, which must outlive the node
+  void setSlot(::std::size_t slot)
+  {
+    if (slot == m_slot) {
+      return;
+    }
+    if (m_slot != 0) {
+      sharedTextureSlotRelease(m_slot);
+    }
+    m_slot = slot;
+    if (m_slot != 0) {
+      sharedTextureSlotRetain(m_slot);
+    }
+  }
+
+  void setImported(int buffer,
+                   QSGTexture* texture,
+                   void* native,
+                   Release releaseNative,
+                   Acquire acquireNative = nullptr,
+                   HandBack handBackNative = nullptr)
+  {
+    if (m_pending == buffer) {
+      m_pending = -1;
+      handBackToSlot(buffer);
+    }
+    Buffer old = m_buffers[buffer];
+    m_buffers[buffer] = {
+      texture, native, releaseNative, acquireNative, handBackNative
+    };
+    // The old texture wraps the old native objects, so drop it first
+    if (old.texture != nullptr && old.texture == this->texture()) {
+      setTexture(texture);
+    }
+    release(old);
+  }
+
+  // Draw the buffer, waiting for the frame released under a non zero key
+  // first
+  bool show(int buffer, ::std::uint64_t key, bool waitIdle)
+  {
+    Buffer& shown = m_buffers[buffer];
+    if (shown.texture == nullptr) {
+      return false;
+    }
+    if (shown.texture != texture()) {
+      setTexture(shown.texture);
+    }
+    if (key != 0) {
+      if (shown.acquire != nullptr) {
+        shown.acquire(shown.native, key);
+      }
+      m_pending = buffer;
+      m_pendingKey = key;
+      m_waitIdle = waitIdle;
+    }
+    return true;
+  }
+
+private:
+  struct Buffer
+  {
+    QSGTexture* texture = nullptr;
+    void* native = nullptr;
+    Release release = nullptr;
+    Acquire acquire = nullptr;
+    HandBack handBack = nullptr;
+  };
+
+  void release(Buffer& buffer)
+  {
+    delete buffer.texture;
+    if (buffer.release != nullptr) {
+      buffer.release(m_window, buffer.native);
+    }
+    buffer = {};
+  }
+
+  void handBack()
+  {
+    if (m_pending < 0) {
+      return;
+    }
+    const int buffer = m_pending;
+    m_pending = -1;
+    const Buffer& drawn = m_buffers[buffer];
+    if (drawn.handBack != nullptr) {
+      drawn.handBack(m_window, drawn.native, m_pendingKey, m_waitIdle);
+    }
+    handBackToSlot(buffer);
+  }
+
+  void handBackToSlot(int buffer)
+  {
+    if (m_slot != 0) {
+      sharedTextureHandBack(m_slot, buffer);
+    }
+  }
+
+  QQuickWindow* m_window = nullptr;
+  Buffer m_buffers[MaxBuffers];
+  ::std::size_t m_slot = 0;
+  // The buffer drawn in this frame, and the key it was taken under
+  int m_pending = -1;
+  ::std::uint64_t m_pendingKey = 0;
+  bool m_waitIdle = false;
+  QMetaObject::Connection m_frameEnd;
+};
+
+#ifdef BEVYQML_VULKAN_INTEROP
+struct VulkanImport
+{
+  VkDevice device;
+  VkImage image;
+  VkDeviceMemory memory;
+};
+
+struct VulkanContext
+{
+  QVulkanInstance* instance;
+  VkPhysicalDevice physicalDevice;
+  VkDevice device;
+};
+
+bool
+vulkanContext(QQuickWindow* window, VulkanContext& context)
+{
+  QSGRendererInterface* ri = window->rendererInterface();
+  if (ri == nullptr || ri->graphicsApi() != QSGRendererInterface::Vulkan) {
+    return false;
+  }
+
+  auto* physicalDevice = static_cast<VkPhysicalDevice*>(
+    ri->getResource(window, QSGRendererInterface::PhysicalDeviceResource));
+  auto* device = static_cast<VkDevice*>(
+    ri->getResource(window, QSGRendererInterface::DeviceResource));
+  context.instance = window->vulkanInstance();
+  if (physicalDevice == nullptr || device == nullptr ||
+      context.instance == nullptr) {
+    return false;
+  }
+
+  context.physicalDevice = *physicalDevice;
+  context.device = *device;
+  return true;
+}
+
+void
+releaseVulkanImport(QQuickWindow* window, void* native)
+{
+  auto* imported = static_cast<VulkanImport*>(native);
+  QVulkanDeviceFunctions* df =
+    window->vulkanInstance()->deviceFunctions(imported->device);
+  df->vkDestroyImage(imported->device, imported->image, nullptr);
+  df->vkFreeMemory(imported->device, imported->memory, nullptr);
+  delete imported;
+}
+
+// Bevy cannot wait for the device of the scene graph, so the scene graph
+// waits for its own queue before handing the texture back
+void
+handBackVulkanFrame(QQuickWindow* window,
+                    void* native,
+                    ::std::uint64_t,
+                    bool waitIdle)
+{
+  if (!waitIdle) {
+    return;
+  }
+  auto* imported = static_cast<VulkanImport*>(native);
+  auto* queue = static_cast<VkQueue*>(window->rendererInterface()->getResource(
+    window, QSGRendererInterface::CommandQueueResource));
+  if (queue != nullptr) {
+    window->vulkanInstance()
+      ->deviceFunctions(imported->device)
+      ->vkQueueWaitIdle(*queue);
+  }
+}
+
+bool
+importVulkanTexture(QQuickWindow* window,
+                    SharedTextureNode& node,
+                    int buffer,
+                    int fd,
+                    ::std::uint64_t allocationSize,
+                    ::std::uint32_t width,
+                    ::std::uint32_t height)
+{
+  VulkanContext context;
+  if (!vulkanContext(window, context)) {
+    return false;
+  }
+
+  QVulkanFunctions* f = context.instance->functions();
+  QVulkanDeviceFunctions* df =
+    context.instance->deviceFunctions(context.device);
+
+  VkExternalMemoryImageCreateInfo externalInfo = {};
+  externalInfo.sType = VK_STRUCTURE_TYPE_EXTERNAL_MEMORY_IMAGE_CREATE_INFO;
+  externalInfo.handleTypes = VK_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD_BIT;
+
+  // Must match the image created by src/render/interop/vulkan.rs
+  VkImageCreateInfo imageInfo = {};
+  imageInfo.sType = VK_STRUCTURE_TYPE_IMAGE_CREATE_INFO;
+  imageInfo.pNext = &externalInfo;
+  imageInfo.imageType = VK_IMAGE_TYPE_2D;
+  imageInfo.format = VK_FORMAT_R8G8B8A8_SRGB;
+  imageInfo.extent = { width, height, 1 };
+  imageInfo.mipLevels = 1;
+  imageInfo.arrayLayers = 1;
+  imageInfo.samples = VK_SAMPLE_COUNT_1_BIT;
+  imageInfo.tiling = VK_IMAGE_TILING_OPTIMAL;
+  imageInfo.usage = VK_IMAGE_USAGE_SAMPLED_BIT |
+                    VK_IMAGE_USAGE_TRANSFER_SRC_BIT |
+                    VK_IMAGE_USAGE_TRANSFER_DST_BIT |
+                    VK_IMAGE_USAGE_COLOR_ATTACHMENT_BIT;
+  imageInfo.sharingMode = VK_SHARING_MODE_EXCLUSIVE;
+  imageInfo.initialLayout = VK_IMAGE_LAYOUT_UNDEFINED;
+
+  VkImage image = VK_NULL_HANDLE;
+  if (df->vkCreateImage(context.device, &imageInfo, nullptr, &image) !=
+      VK_SUCCESS) {
+    return false;
+  }
+
+  VkMemoryRequirements requirements;
+  df->vkGetImageMemoryRequirements(context.device, image, &requirements);
+  VkPhysicalDeviceMemoryProperties memoryProperties;
+  f->vkGetPhysicalDeviceMemoryProperties(context.physicalDevice,
+                                         &memoryProperties);
+
+  uint32_t memoryTypeIndex = memoryProperties.memoryTypeCount;
+  for (uint32_t i = 0; i < memoryProperties.memoryTypeCount; ++i) {
+    if ((requirements.memoryTypeBits & (1u << i)) != 0 &&
+        (memoryProperties.memoryTypes[i].propertyFlags &
+         VK_MEMORY_PROPERTY_DEVICE_LOCAL_BIT) != 0) {
+      memoryTypeIndex = i;
+      break;
+    }
+  }
+  if (memoryTypeIndex == memoryProperties.memoryTypeCount) {
+    df->vkDestroyImage(context.device, image, nullptr);
+    return false;
+  }
+
+  VkMemoryDedicatedAllocateInfo dedicatedInfo = {};
+  dedicatedInfo.sType = VK_STRUCTURE_TYPE_MEMORY_DEDICATED_ALLOCATE_INFO;
+  dedicatedInfo.image = image;
+
+  VkImportMemoryFdInfoKHR importInfo = {};
+  importInfo.sType = VK_STRUCTURE_TYPE_IMPORT_MEMORY_FD_INFO_KHR;
+  importInfo.pNext = &dedicatedInfo;
+  importInfo.handleType = VK_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD_BIT;
+  importInfo.fd = fd;
+
+  VkMemoryAllocateInfo allocateInfo = {};
+  allocateInfo.sType = VK_STRUCTURE_TYPE_MEMORY_ALLOCATE_INFO;
+  allocateInfo.pNext = &importInfo;
+  allocateInfo.allocationSize =
+    std::max<VkDeviceSize>(allocationSize, requirements.size);
+  allocateInfo.memoryTypeIndex = memoryTypeIndex;
+
+  // A successful import takes ownership of the file descriptor
+  VkDeviceMemory memory = VK_NULL_HANDLE;
+  if (df->vkAllocateMemory(context.device, &allocateInfo, nullptr, &memory) !=
+      VK_SUCCESS) {
+    df->vkDestroyImage(context.device, image, nullptr);
+    ::close(fd);
+    return false;
+  }
+
+  if (df->vkBindImageMemory(context.device, image, memory, 0) != VK_SUCCESS) {
+    df->vkDestroyImage(context.device, image, nullptr);
+    df->vkFreeMemory(context.device, memory, nullptr);
+    return false;
+  }
+
+  // wgpu leaves render targets as color attachments at the end of a frame
+  QSGTexture* texture = QNativeInterface::QSGVulkanTexture::fromNative(
+    image,
+    VK_IMAGE_LAYOUT_COLOR_ATTACHMENT_OPTIMAL,
+    window,
+    QSize(static_cast<int>(width), static_cast<int>(height)));
+  if (texture == nullptr) {
+    df->vkDestroyImage(context.device, image, nullptr);
+    df->vkFreeMemory(context.device, memory, nullptr);
+    return false;
+  }
+
+  node.setImported(buffer,
+                   texture,
+                   new VulkanImport{ context.device, image, memory },
+                   releaseVulkanImport,
+                   nullptr,
+                   handBackVulkanFrame);
+  return true;
+}
+
+// Wraps the instance of Bevy, which all windows share
+QVulkanInstance*
+adoptedVulkanInstance(::std::uint64_t instance)
+{
+  static QVulkanInstance* adopted = [instance]() -> QVulkanInstance* {
+    auto* vulkanInstance = new QVulkanInstance();
+    vulkanInstance->setVkInstance(
+      reinterpret_cast<VkInstance>(static_cast<quintptr>(instance)));
+    if (!vulkanInstance->create()) {
+      delete vulkanInstance;
+      return nullptr;
+    }
+    return vulkanInstance;
+  }();
+  return adopted;
+}
+#endif
+
+#ifdef BEVYQML_D3D_INTEROP
+// The texture and fence opened from the NT handles of Bevy, with the device
+// objects the fence is waited for and signaled on
+struct D3DImport
+{
+  IUnknown* texture = nullptr;
+  ID3D11Fence* fence11 = nullptr;
+  ID3D11DeviceContext4* context11 = nullptr;
+  ID3D12Fence* fence12 = nullptr;
+  ID3D12CommandQueue* queue12 = nullptr;
+};
+
+template<typename T>
+void
+releaseComObject(T*& object)
+{
+  if (object != nullptr) {
+    object->Release();
+    object = nullptr;
+  }
+}
+
+void
+releaseD3DImport(QQuickWindow*, void* native)
+{
+  auto* imported = static_cast<D3DImport*>(native);
+  releaseComObject(imported->texture);
+  releaseComObject(imported->fence11);
+  releaseComObject(imported->context11);
+  releaseComObject(imported->fence12);
+  releaseComObject(imported->queue12);
+  delete imported;
+}
+
+// Make the GPU wait for Bevy to release the frame before it is sampled
+void
+acquireD3DFrame(void* native, ::std::uint64_t key)
+{
+  auto* imported = static_cast<D3DImport*>(native);
+  if (imported->fence12 != nullptr) {
+    imported->queue12->Wait(imported->fence12, key);
+  } else {
+    imported->context11->Wait(imported->fence11, key);
+  }
+}
+
+// Hand the texture back to Bevy under the key after the one it was taken
+// under, once the GPU is done with the frame sampling it
+void
+handBackD3DFrame(QQuickWindow*, void* native, ::std::uint64_t taken, bool)
+{
+  auto* imported = static_cast<D3DImport*>(native);
+  const ::std::uint64_t key = taken + 1;
+  // Bevy moves on by itself when it has waited too long, and the fence must
+  // never go back
+  if (imported->fence12 != nullptr) {
+    if (imported->fence12->GetCompletedValue() < key) {
+      imported->queue12->Signal(imported->fence12, key);
+    }
+  } else if (imported->fence11->GetCompletedValue() < key) {
+    imported->context11->Signal(imported->fence11, key);
+  }
+}
+
+bool
+importD3DTexture(QQuickWindow* window,
+                 SharedTextureNode& node,
+                 int buffer,
+                 HANDLE handle,
+                 HANDLE fence,
+                 ::std::uint32_t width,
+                 ::std::uint32_t height)
+{
+  QSGRendererInterface* ri = window->rendererInterface();
+  const QSize size(static_cast<int>(width), static_cast<int>(height));
+  auto* imported = new D3DImport();
+  QSGTexture* texture = nullptr;
+
+  if (ri->graphicsApi() == QSGRendererInterface::Direct3D11) {
+    auto* device = static_cast<ID3D11Device*>(
+      ri->getResource(window, QSGRendererInterface::DeviceResource));
+    auto* context = static_cast<ID3D11DeviceContext*>(
+      ri->getResource(window, QSGRendererInterface::DeviceContextResource));
+    ID3D11Device5* device5 = nullptr;
+    ID3D11Texture2D* texture2d = nullptr;
+    // The fence needs ID3D11Device5, from the Windows 10 Creators Update on
+    const bool opened =
+      device != nullptr && context != nullptr &&
+      SUCCEEDED(device->QueryInterface(IID_PPV_ARGS(&device5))) &&
+      SUCCEEDED(context->QueryInterface(IID_PPV_ARGS(&imported->context11))) &&
+      SUCCEEDED(device5->OpenSharedResource1(handle, IID_PPV_ARGS(&texture2d))) &&
+      SUCCEEDED(
+        device5->OpenSharedFence(fence, IID_PPV_ARGS(&imported->fence11)));
+    imported->texture = texture2d;
+    if (opened) {
+      texture =
+        QNativeInterface::QSGD3D11Texture::fromNative(texture2d, window, size);
+    }
+    releaseComObject(device5);
+  }
+#if QT_VERSION >= QT_VERSION_CHECK(6, 6, 0)
+  else if (ri->graphicsApi() == QSGRendererInterface::Direct3D12) {
+    auto* device = static_cast<ID3D12Device*>(
+      ri->getResource(window, QSGRendererInterface::DeviceResource));
+    auto* queue = static_cast<ID3D12CommandQueue*>(
+      ri->getResource(window, QSGRendererInterface::CommandQueueResource));
+    ID3D12Resource* resource = nullptr;
+    const bool opened =
+      device != nullptr && queue != nullptr &&
+      SUCCEEDED(device->OpenSharedHandle(handle, IID_PPV_ARGS(&resource))) &&
+      SUCCEEDED(
+        device->OpenSharedHandle(fence, IID_PPV_ARGS(&imported->fence12)));
+    imported->texture = resource;
+    if (opened) {
+      queue->AddRef();
+      imported->queue12 = queue;
+      // Shared with simultaneous access, so it is always in the common state
+      texture = QNativeInterface::QSGD3D12Texture::fromNative(
+        resource, D3D12_RESOURCE_STATE_COMMON, window, size);
+    }
+  }
+#endif
+
+  // The handles are only needed to open the objects
+  CloseHandle(handle);
+  CloseHandle(fence);
+
+  if (texture == nullptr) {
+    releaseD3DImport(window, imported);
+    return false;
+  }
+
+  node.setImported(buffer,
+                   texture,
+                   imported,
+                   releaseD3DImport,
+                   acquireD3DFrame,
+                   handBackD3DFrame);
+  return true;
+}
+#endif
+
+#ifdef BEVYQML_OPENGL_INTEROP
+// Make the GPU wait for the commands of Bevy before the texture is sampled,
+// the key being the GLsync Bevy created after the frame
+void
+acquireOpenGLFrame(void*, ::std::uint64_t key)
+{
+  QOpenGLContext* context = QOpenGLContext::currentContext();
+  if (context != nullptr) {
+    context->extraFunctions()->glWaitSync(
+      reinterpret_cast<GLsync>(static_cast<quintptr>(key)),
+      0,
+      GL_TIMEOUT_IGNORED);
+  }
+}
+
+// Bevy only waits for the texture to be handed back, so the scene graph
+// finishes its own commands first
+void
+handBackOpenGLFrame(QQuickWindow*, void*, ::std::uint64_t, bool waitIdle)
+{
+  QOpenGLContext* context = QOpenGLContext::currentContext();
+  if (waitIdle && context != nullptr) {
+    context->functions()->glFinish();
+  }
+}
+
+// The texture belongs to the context of Bevy, which shares it with the
+// contexts of the scene graph, so there is nothing to release
+bool
+importOpenGLTexture(QQuickWindow* window,
+                    SharedTextureNode& node,
+                    int buffer,
+                    GLuint name,
+                    ::std::uint32_t width,
+                    ::std::uint32_t height)
+{
+  QSGRendererInterface* ri = window->rendererInterface();
+  if (ri == nullptr || ri->graphicsApi() != QSGRendererInterface::OpenGL) {
+    return false;
+  }
+
+  QSGTexture* texture = QNativeInterface::QSGOpenGLTexture::fromNative(
+    name, window, QSize(static_cast<int>(width), static_cast<int>(height)));
+  if (texture == nullptr) {
+    return false;
+  }
+
+  node.setImported(buffer,
+                   texture,
+                   nullptr,
+                   nullptr,
+                   acquireOpenGLFrame,
+                   handBackOpenGLFrame);
+  return true;
+}
+#endif
+
+#ifdef BEVYQML_METAL_INTEROP
+void
+releaseMetalImport(QQuickWindow*, void* native)
+{
+  metalReleaseTexture(native);
+}
+
+// Bevy cannot wait for the queue of the scene graph, so the scene graph
+// waits for it before handing the texture back
+void
+handBackMetalFrame(QQuickWindow* window, void*, ::std::uint64_t, bool waitIdle)
+{
+  if (waitIdle) {
+    metalWaitIdle(window);
+  }
+}
+
+bool
+importMetalTexture(QQuickWindow* window,
+                   SharedTextureNode& node,
+                   int buffer,
+                   ::std::int64_t surface,
+                   ::std::uint32_t width,
+                   ::std::uint32_t height)
+{
+  void* texture = nullptr;
+  QSGTexture* qsgTexture =
+    metalImportSurface(window, surface, width, height, texture);
+  if (qsgTexture == nullptr) {
+    return false;
+  }
+
+  node.setImported(buffer,
+                   qsgTexture,
+                   texture,
+                   releaseMetalImport,
+                   nullptr,
+                   handBackMetalFrame);
+  return true;
+}
+#endif
+
+void
+closeHandle(int backend, ::std::int64_t handle)
+{
+#if defined(BEVYQML_VULKAN_INTEROP)
+  if (backend == static_cast<int>(Backend::VulkanExternalMemory)) {
+    ::close(static_cast<int>(handle));
+  }
+#elif defined(BEVYQML_D3D_INTEROP)
+  if (backend == static_cast<int>(Backend::D3DSharedHandle) && handle >= 0) {
+    CloseHandle(reinterpret_cast<HANDLE>(handle));
+  }
+#elif defined(BEVYQML_METAL_INTEROP)
+  if (backend == static_cast<int>(Backend::MetalIOSurface) && handle >= 0) {
+    metalReleaseSurface(handle);
+  }
+#else
+  Q_UNUSED(backend);
+  Q_UNUSED(handle);
+#endif
+}
+
+// Import the texture of the frame as its buffer, taking ownership of its
+// handle and fence
+bool
+importSharedTexture(QQuickWindow* window,
+                    SharedTextureNode& node,
+                    const SharedTextureFrame& frame)
+{
+  const int backend = frame.backend;
+  bool imported = false;
+#ifdef BEVYQML_OPENGL_INTEROP
+  // Texture names need no closing, the other backends leave them alone
+  if (backend == static_cast<int>(Backend::OpenGLShareContext)) {
+    imported = importOpenGLTexture(window,
+                                   node,
+                                   frame.buffer,
+                                   static_cast<GLuint>(frame.handle),
+                                   frame.width,
+                                   frame.height);
+  }
+#endif
+#ifdef BEVYQML_VULKAN_INTEROP
+  if (backend == static_cast<int>(Backend::VulkanExternalMemory)) {
+    imported = importVulkanTexture(window,
+                                   node,
+                                   frame.buffer,
+                                   static_cast<int>(frame.handle),
+                                   frame.allocation_size,
+                                   frame.width,
+                                   frame.height);
+  } else {
+    closeHandle(backend, frame.handle);
+  }
+#elif defined(BEVYQML_D3D_INTEROP)
+  if (frame.fence >= 0 &&
+      backend == static_cast<int>(Backend::D3DSharedHandle)) {
+    imported = importD3DTexture(window,
+                                node,
+                                frame.buffer,
+                                reinterpret_cast<HANDLE>(frame.handle),
+                                reinterpret_cast<HANDLE>(frame.fence),
+                                frame.width,
+                                frame.height);
+  } else {
+    closeHandle(backend, frame.handle);
+    closeHandle(backend, frame.fence);
+  }
+#elif defined(BEVYQML_METAL_INTEROP)
+  if (backend == static_cast<int>(Backend::MetalIOSurface)) {
+    imported = importMetalTexture(
+      window, node, frame.buffer, frame.handle, frame.width, frame.height);
+  } else {
+    closeHandle(backend, frame.handle);
+  }
+#else
+  Q_UNUSED(window);
+  Q_UNUSED(node);
+  closeHandle(backend, frame.handle);
+#endif
+  return imported;
+}
+
+}
+
+int
+quickWindowGraphicsDevice(QQuickWindow* window,
+                          ::rust::Slice<::std::uint8_t> deviceUuid,
+                          bool& uuidValid)
+{
+  uuidValid = false;
+  if (window == nullptr || window->rendererInterface() == nullptr) {
+    return QSGRendererInterface::Unknown;
+  }
+
+  const auto api = window->rendererInterface()->graphicsApi();
+
+#ifdef BEVYQML_VULKAN_INTEROP
+  VulkanContext context;
+  if (api == QSGRendererInterface::Vulkan && deviceUuid.size() >= VK_UUID_SIZE &&
+      vulkanContext(window, context)) {
+    auto getProperties2 = reinterpret_cast<PFN_vkGetPhysicalDeviceProperties2>(
+      context.instance->getInstanceProcAddr("vkGetPhysicalDeviceProperties2"));
+    if (getProperties2 != nullptr) {
+      VkPhysicalDeviceIDProperties idProperties = {};
+      idProperties.sType = VK_STRUCTURE_TYPE_PHYSICAL_DEVICE_ID_PROPERTIES;
+      VkPhysicalDeviceProperties2 properties = {};
+      properties.sType = VK_STRUCTURE_TYPE_PHYSICAL_DEVICE_PROPERTIES_2;
+      properties.pNext = &idProperties;
+      getProperties2(context.physicalDevice, &properties);
+
+      std::copy(idProperties.deviceUUID,
+                idProperties.deviceUUID + VK_UUID_SIZE,
+                deviceUuid.begin());
+      uuidValid = true;
+    }
+  }
+#elif defined(BEVYQML_D3D_INTEROP)
+  // Direct3D tells adapters apart by their LUID, which Bevy writes into the
+  // first bytes of the UUID as well
+  QSGRendererInterface* ri = window->rendererInterface();
+  LUID luid = {};
+  if (api == QSGRendererInterface::Direct3D11) {
+    auto* device = static_cast<ID3D11Device*>(
+      ri->getResource(window, QSGRendererInterface::DeviceResource));
+    IDXGIDevice* dxgiDevice = nullptr;
+    IDXGIAdapter* adapter = nullptr;
+    DXGI_ADAPTER_DESC desc = {};
+    if (device != nullptr &&
+        SUCCEEDED(device->QueryInterface(IID_PPV_ARGS(&dxgiDevice))) &&
+        SUCCEEDED(dxgiDevice->GetAdapter(&adapter)) &&
+        SUCCEEDED(adapter->GetDesc(&desc))) {
+      luid = desc.AdapterLuid;
+      uuidValid = true;
+    }
+    releaseComObject(adapter);
+    releaseComObject(dxgiDevice);
+  } else if (api == QSGRendererInterface::Direct3D12) {
+    auto* device = static_cast<ID3D12Device*>(
+      ri->getResource(window, QSGRendererInterface::DeviceResource));
+    if (device != nullptr) {
+      luid = device->GetAdapterLuid();
+      uuidValid = true;
+    }
+  }
+  if (uuidValid && deviceUuid.size() >= sizeof(luid)) {
+    std::fill(deviceUuid.begin(), deviceUuid.end(), 0);
+    std::memcpy(deviceUuid.data(), &luid, sizeof(luid));
+  } else {
+    uuidValid = false;
+  }
+#elif defined(BEVYQML_METAL_INTEROP)
+  // Metal tells GPUs apart by their registry ID, which Bevy writes into the
+  // first bytes of the UUID as well
+  void* device = window->rendererInterface()->getResource(
+    window, QSGRendererInterface::DeviceResource);
+  if (api == QSGRendererInterface::Metal && device != nullptr &&
+      deviceUuid.size() >= sizeof(::std::uint64_t)) {
+    const ::std::uint64_t registryId =
+      metalDeviceRegistryId(reinterpret_cast<::std::size_t>(device));
+    std::fill(deviceUuid.begin(), deviceUuid.end(), 0);
+    std::memcpy(deviceUuid.data(), &registryId, sizeof(registryId));
+    uuidValid = true;
+  }
+#else
+  Q_UNUSED(deviceUuid);
+#endif
+
+  return api;
+}
+
+bool
+quickWindowAdoptVulkanDevice(QQuickWindow* window,
+                             int handshake,
+                             ::std::uint64_t instance,
+                             ::std::uint64_t physicalDevice,
+                             ::std::uint64_t device,
+                             ::std::uint32_t queueFamily,
+                             ::std::uint32_t queueIndex)
+{
+#ifdef BEVYQML_VULKAN_INTEROP
+  // The scene graph sets up its device when the window is created
+  if (handshake == static_cast<int>(Handshake::None) ||
+      QQuickWindow::graphicsApi() != QSGRendererInterface::Vulkan ||
+      window->handle() != nullptr) {
+    return false;
+  }
+
+  QVulkanInstance* vulkanInstance = adoptedVulkanInstance(instance);
+  if (vulkanInstance == nullptr) {
+    return false;
+  }
+  window->setVulkanInstance(vulkanInstance);
+
+  auto vkPhysicalDevice =
+    reinterpret_cast<VkPhysicalDevice>(static_cast<quintptr>(physicalDevice));
+  if (handshake == static_cast<int>(Handshake::Device)) {
+    // Memory allocated by the same device needs no import extensions
+    window->setGraphicsDevice(QQuickGraphicsDevice::fromDeviceObjects(
+      vkPhysicalDevice,
+      reinterpret_cast<VkDevice>(static_cast<quintptr>(device)),
+      static_cast<int>(queueFamily),
+      static_cast<int>(queueIndex)));
+  } else {
+    window->setGraphicsDevice(
+      QQuickGraphicsDevice::fromPhysicalDevice(vkPhysicalDevice));
+    QQuickGraphicsConfiguration configuration =
+      window->graphicsConfiguration();
+    configuration.setDeviceExtensions(
+      { QByteArrayLiteral("VK_KHR_external_memory"),
+        QByteArrayLiteral("VK_KHR_external_memory_fd") });
+    window->setGraphicsConfiguration(configuration);
+  }
+  return true;
+#else
+  Q_UNUSED(window);
+  Q_UNUSED(handshake);
+  Q_UNUSED(instance);
+  Q_UNUSED(physicalDevice);
+  Q_UNUSED(device);
+  Q_UNUSED(queueFamily);
+  Q_UNUSED(queueIndex);
+  return false;
+#endif
+}
+
+QSGNode*
+updateSharedTextureNode(QQuickItem& item,
+                        QSGNode* oldNode,
+                        const SharedTextureFrame& frame,
+                        const QRectF& rect)
+{
+  auto* node = dynamic_cast<SharedTextureNode*>(oldNode);
+  QQuickWindow* window = item.window();
+  const bool validBuffer =
+    frame.buffer >= 0 && frame.buffer < SharedTextureNode::MaxBuffers;
+
+  if (frame.handle >= 0) {
+    if (window == nullptr || !validBuffer) {
+      closeHandle(frame.backend, frame.handle);
+      closeHandle(frame.backend, frame.fence);
+      delete oldNode;
+      return nullptr;
+    }
+
+    // Switching over from the copy path, the old node cannot be reused
+    if (node == nullptr) {
+      delete oldNode;
+      node = new SharedTextureNode(window);
+    }
+    node->setSlot(frame.slot);
+    if (!importSharedTexture(window, *node, frame)) {
+      delete node;
+      return nullptr;
+    }
+    return node;
+  }
+
+  if (node == nullptr || !validBuffer) {
+    // Nothing has been exported yet, keep whatever was shown before
+    return oldNode;
+  }
+
+  node->setSlot(frame.slot);
+  node->show(frame.buffer, frame.key, frame.wait_idle);
+  // The shared memory is updated in place, so only the material is dirty
+  node->markDirty(QSGNode::DirtyMaterial);
+  node->setRect(rect);
+  return node;
+}
+
+}
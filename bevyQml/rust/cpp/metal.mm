@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#include "bevyqml/metal.h"
+
+#include <QtQuick/QQuickWindow>
+#include <QtQuick/QSGRendererInterface>
+#include <QtQuick/qsgtexture_platform.h>
+
+#include <IOSurface/IOSurfaceRef.h>
+#include <TargetConditionals.h>
+#import <Foundation/Foundation.h>
+#import <Metal/Metal.h>
+
+// Built without ARC, so references are counted by hand
+
+namespace bevyqml {
+
+namespace {
+
+// 'RGBA', which matches the sRGB RGBA8 targets of src/render/mod.rs
+constexpr OSType pixelFormatRgba = 0x52474241;
+
+MTLTextureDescriptor*
+surfaceTextureDescriptor(::std::uint32_t width, ::std::uint32_t height)
+{
+  MTLTextureDescriptor* descriptor = [MTLTextureDescriptor
+    texture2DDescriptorWithPixelFormat:MTLPixelFormatRGBA8Unorm_sRGB
+                                 width:width
+                                height:height
+                             mipmapped:NO];
+  descriptor.usage = MTLTextureUsageShaderRead | MTLTextureUsageRenderTarget;
+  // IOSurface backed textures cannot be private to the GPU
+#if TARGET_OS_OSX
+  descriptor.storageMode = MTLStorageModeManaged;
+#else
+  descriptor.storageMode = MTLStorageModeShared;
+#endif
+  return descriptor;
+}
+
+}
+
+::std::uint64_t
+metalDeviceRegistryId(::std::size_t device)
+{
+  id<MTLDevice> mtlDevice = reinterpret_cast<id<MTLDevice>>(device);
+  return mtlDevice.registryID;
+}
+
+bool
+metalCreateSharedTexture(::std::size_t device,
+                         ::std::uint32_t width,
+                         ::std::uint32_t height,
+                         ::std::size_t& texture,
+                         ::std::int64_t& surface,
+                         ::std::uint64_t& allocationSize)
+{
+  @autoreleasepool {
+    const size_t bytesPerRow =
+      IOSurfaceAlignProperty(kIOSurfaceBytesPerRow, size_t(width) * 4);
+    NSDictionary* properties = @{
+      (NSString*)kIOSurfaceWidth : @(width),
+      (NSString*)kIOSurfaceHeight : @(height),
+      (NSString*)kIOSurfaceBytesPerElement : @4,
+      (NSString*)kIOSurfaceBytesPerRow : @(bytesPerRow),
+      (NSString*)kIOSurfacePixelFormat : @(pixelFormatRgba),
+    };
+    IOSurfaceRef ioSurface =
+      IOSurfaceCreate(reinterpret_cast<CFDictionaryRef>(properties));
+    if (ioSurface == nullptr) {
+      return false;
+    }
+
+    id<MTLDevice> mtlDevice = reinterpret_cast<id<MTLDevice>>(device);
+    id<MTLTexture> mtlTexture =
+      [mtlDevice newTextureWithDescriptor:surfaceTextureDescriptor(width, height)
+                                iosurface:ioSurface
+                                    plane:0];
+    if (mtlTexture == nil) {
+      CFRelease(ioSurface);
+      return false;
+    }
+
+    texture = reinterpret_cast<::std::size_t>(mtlTexture);
+    surface = reinterpret_cast<::std::int64_t>(ioSurface);
+    allocationSize = IOSurfaceGetAllocSize(ioSurface);
+    return true;
+  }
+}
+
+QSGTexture*
+metalImportSurface(QQuickWindow* window,
+                   ::std::int64_t surface,
+                   ::std::uint32_t width,
+                   ::std::uint32_t height,
+                   void*& texture)
+{
+  @autoreleasepool {
+    auto ioSurface = reinterpret_cast<IOSurfaceRef>(surface);
+    QSGRendererInterface* ri = window->rendererInterface();
+    id<MTLDevice> mtlDevice = static_cast<id<MTLDevice>>(
+      ri->getResource(window, QSGRendererInterface::DeviceResource));
+    if (ri->graphicsApi() != QSGRendererInterface::Metal || mtlDevice == nil) {
+      CFRelease(ioSurface);
+      return nullptr;
+    }
+
+    // The texture keeps the surface alive on its own
+    id<MTLTexture> mtlTexture =
+      [mtlDevice newTextureWithDescriptor:surfaceTextureDescriptor(width, height)
+                                iosurface:ioSurface
+                                    plane:0];
+    CFRelease(ioSurface);
+    if (mtlTexture == nil) {
+      return nullptr;
+    }
+
+    // The size is in pixels, and the node draws it into the logical rect of
+    // the item, which makes up for the scale of Retina screens
+    QSGTexture* qsgTexture = QNativeInterface::QSGMetalTexture::fromNative(
+      mtlTexture,
+      window,
+      QSize(static_cast<int>(width), static_cast<int>(height)));
+    if (qsgTexture == nullptr) {
+      [mtlTexture release];
+      return nullptr;
+    }
+    texture = reinterpret_cast<void*>(mtlTexture);
+    return qsgTexture;
+  }
+}
+
+void
+metalReleaseTexture(void* texture)
+{
+  [reinterpret_cast<id<MTLTexture>>(texture) release];
+}
+
+void
+metalReleaseSurface(::std::int64_t surface)
+{
+  CFRelease(reinterpret_cast<IOSurfaceRef>(surface));
+}
+
+void
+metalWaitIdle(QQuickWindow* window)
+{
+  @autoreleasepool {
+    QSGRendererInterface* ri = window->rendererInterface();
+    id<MTLCommandQueue> queue = static_cast<id<MTLCommandQueue>>(
+      ri->getResource(window, QSGRendererInterface::CommandQueueResource));
+    if (queue == nil) {
+      return;
+    }
+    // Command buffers of a queue complete in order, so an empty one completes
+    // after everything committed before it
+    id<MTLCommandBuffer> commandBuffer = [queue commandBuffer];
+    [commandBuffer commit];
+    [commandBuffer waitUntilCompleted];
+  }
+}
+
+}
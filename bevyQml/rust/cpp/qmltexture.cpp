@@ -0,0 +1,267 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#include "bevyqml/qmltexture.h"
+
+#include <QtCore/QCoreApplication>
+#include <QtGui/QKeyEvent>
+#include <QtGui/QMouseEvent>
+#include <QtGui/QWheelEvent>
+#include <QtQml/QQmlComponent>
+#include <QtQml/QQmlEngine>
+#include <QtQuick/QQuickItem>
+#include <QtQuick/QQuickRenderControl>
+#include <QtQuick/QQuickRenderTarget>
+#include <QtQuick/QQuickWindow>
+#include <rhi/qrhi.h>
+
+namespace bevyqml {
+
+namespace {
+
+// Every texture shares one engine, which lives as long as the application
+QQmlEngine*
+sharedEngine()
+{
+  static QQmlEngine* engine = new QQmlEngine(QCoreApplication::instance());
+  return engine;
+}
+
+}
+
+QmlTextureRenderer::QmlTextureRenderer(const QUrl& source,
+                                       ::std::uint32_t width,
+                                       ::std::uint32_t height)
+  : m_renderControl(std::make_unique<QQuickRenderControl>())
+  , m_window(std::make_unique<QQuickWindow>(m_renderControl.get()))
+{
+  m_window->resize(static_cast<int>(width), static_cast<int>(height));
+  m_window->setColor(Qt::transparent);
+
+  const auto markDirty = [this] { m_dirty = true; };
+  QObject::connect(m_renderControl.get(),
+                   &QQuickRenderControl::renderRequested,
+                   m_renderControl.get(),
+                   markDirty);
+  QObject::connect(m_renderControl.get(),
+                   &QQuickRenderControl::sceneChanged,
+                   m_renderControl.get(),
+                   markDirty);
+
+  m_component = std::make_unique<QQmlComponent>(sharedEngine(), source);
+  if (m_component->isError()) {
+    m_error = m_component->errorString();
+    return;
+  }
+
+  QObject* object = m_component->create();
+  m_root = qobject_cast<QQuickItem*>(object);
+  if (m_root == nullptr) {
+    m_error = object != nullptr
+                ? QStringLiteral("The root object of %1 is not an Item")
+                    .arg(source.toString())
+                : m_component->errorString();
+    delete object;
+    return;
+  }
+  m_root->setParentItem(m_window->contentItem());
+  m_root->setSize(m_window->size());
+
+  if (!m_renderControl->initialize()) {
+    m_error = QStringLiteral("Failed to initialize the Qt Quick renderer");
+    return;
+  }
+  createRenderTarget();
+  m_initialized = m_renderTarget != nullptr;
+}
+
+QmlTextureRenderer::~QmlTextureRenderer()
+{
+  // The scene has to go before the renderer it was created for
+  delete m_root;
+  releaseRenderTarget();
+}
+
+QString
+QmlTextureRenderer::errorString() const
+{
+  return m_error;
+}
+
+void
+QmlTextureRenderer::resize(::std::uint32_t width, ::std::uint32_t height)
+{
+  const QSize size(static_cast<int>(width), static_cast<int>(height));
+  if (size == m_window->size()) {
+    return;
+  }
+
+  m_window->resize(size);
+  if (m_root != nullptr) {
+    m_root->setSize(size);
+  }
+  if (m_initialized) {
+    releaseRenderTarget();
+    createRenderTarget();
+    m_initialized = m_renderTarget != nullptr;
+  }
+  m_dirty = true;
+}
+
+bool
+QmlTextureRenderer::render(::rust::Vec<::std::uint8_t>& pixels)
+{
+  if (!m_initialized || !m_dirty) {
+    return false;
+  }
+  m_dirty = false;
+
+  QRhi* rhi = m_renderControl->rhi();
+  m_renderControl->polishItems();
+  m_renderControl->beginFrame();
+  m_renderControl->sync();
+  m_renderControl->render();
+
+  QRhiReadbackResult result;
+  QRhiResourceUpdateBatch* readback = rhi->nextResourceUpdateBatch();
+  readback->readBackTexture(QRhiReadbackDescription(m_texture), &result);
+  m_renderControl->commandBuffer()->resourceUpdate(readback);
+
+  // Ending an offscreen frame waits for the GPU, so the pixels are there
+  m_renderControl->endFrame();
+  if (result.data.isEmpty()) {
+    return false;
+  }
+
+  const qsizetype rowBytes = result.pixelSize.width() * 4;
+  const int rows = result.pixelSize.height();
+  pixels.reserve(static_cast<::std::size_t>(rowBytes * rows));
+  for (int row = 0; row < rows; ++row) {
+    // Bevy images start at the top, OpenGL framebuffers at the bottom
+    const int source = rhi->isYUpInFramebuffer() ? rows - 1 - row : row;
+    const char* line = result.data.constData() + source * rowBytes;
+    for (qsizetype i = 0; i < rowBytes; ++i) {
+      pixels.push_back(static_cast<::std::uint8_t>(line[i]));
+    }
+  }
+  return true;
+}
+
+void
+QmlTextureRenderer::sendMouse(::std::int32_t type,
+                              double x,
+                              double y,
+                              ::std::int32_t button,
+                              ::std::uint32_t buttons,
+                              ::std::uint32_t modifiers)
+{
+  const QPointF position(x, y);
+  QMouseEvent event(static_cast<QEvent::Type>(type),
+                    position,
+                    position,
+                    m_window->mapToGlobal(position),
+                    static_cast<Qt::MouseButton>(button),
+                    static_cast<Qt::MouseButtons>(buttons),
+                    static_cast<Qt::KeyboardModifiers>(modifiers));
+  QCoreApplication::sendEvent(m_window.get(), &event);
+}
+
+void
+QmlTextureRenderer::sendWheel(double x,
+                              double y,
+                              ::std::int32_t angleX,
+                              ::std::int32_t angleY,
+                              ::std::uint32_t modifiers)
+{
+  const QPointF position(x, y);
+  QWheelEvent event(position,
+                    m_window->mapToGlobal(position),
+                    QPoint(),
+                    QPoint(angleX, angleY),
+                    Qt::NoButton,
+                    static_cast<Qt::KeyboardModifiers>(modifiers),
+                    Qt::NoScrollPhase,
+                    false);
+  QCoreApplication::sendEvent(m_window.get(), &event);
+}
+
+void
+QmlTextureRenderer::sendKey(bool pressed,
+                            ::std::int32_t key,
+                            ::std::uint32_t modifiers,
+                            const QString& text,
+                            bool autoRepeat)
+{
+  QKeyEvent event(pressed ? QEvent::KeyPress : QEvent::KeyRelease,
+                  key,
+                  static_cast<Qt::KeyboardModifiers>(modifiers),
+                  text,
+                  autoRepeat);
+  QCoreApplication::sendEvent(m_window.get(), &event);
+}
+
+void
+QmlTextureRenderer::setFocused(bool focused)
+{
+  // The offscreen window never becomes the focus window of the
+  // application, so tell the scene directly
+  QFocusEvent event(focused ? QEvent::FocusIn : QEvent::FocusOut);
+  QCoreApplication::sendEvent(m_window.get(), &event);
+}
+
+void
+QmlTextureRenderer::createRenderTarget()
+{
+  QRhi* rhi = m_renderControl->rhi();
+  const QSize size = m_window->size().expandedTo(QSize(1, 1));
+
+  m_texture = rhi->newTexture(QRhiTexture::RGBA8,
+                              size,
+                              1,
+                              QRhiTexture::RenderTarget |
+                                QRhiTexture::UsedAsTransferSource);
+  m_depthStencil =
+    rhi->newRenderBuffer(QRhiRenderBuffer::DepthStencil, size, 1);
+  if (!m_texture->create() || !m_depthStencil->create()) {
+    m_error = QStringLiteral("Failed to create the texture for the QML scene");
+    releaseRenderTarget();
+    return;
+  }
+
+  QRhiTextureRenderTargetDescription description(
+    QRhiColorAttachment(m_texture));
+  description.setDepthStencilBuffer(m_depthStencil);
+  m_renderTarget = rhi->newTextureRenderTarget(description);
+  m_renderPass = m_renderTarget->newCompatibleRenderPassDescriptor();
+  m_renderTarget->setRenderPassDescriptor(m_renderPass);
+  if (!m_renderTarget->create()) {
+    m_error = QStringLiteral("Failed to create the texture for the QML scene");
+    releaseRenderTarget();
+    return;
+  }
+
+  m_window->setRenderTarget(
+    QQuickRenderTarget::fromRhiRenderTarget(m_renderTarget));
+}
+
+void
+QmlTextureRenderer::releaseRenderTarget()
+{
+  m_window->setRenderTarget(QQuickRenderTarget());
+  delete m_renderTarget;
+  delete m_renderPass;
+  delete m_depthStencil;
+  delete m_texture;
+  m_renderTarget = nullptr;
+  m_renderPass = nullptr;
+  m_depthStencil = nullptr;
+  m_texture = nullptr;
+}
+
+::std::unique_ptr<QmlTextureRenderer>
+qmlTextureRendererNew(const QUrl& source,
+                      ::std::uint32_t width,
+                      ::std::uint32_t height)
+{
+  return ::std::make_unique<QmlTextureRenderer>(source, width, height);
+}
+
+}
@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#include "bevyqml/window.h"
+
+#include <QtQml/QQmlApplicationEngine>
+#include <QtQml/QQmlContext>
+#include <QtQuick/QQuickItem>
+#include <QtQuick/QQuickWindow>
+
+#include "bevyqml/imageprovider.h"
+
+namespace bevyqml {
+
+QmlWindowHost::QmlWindowHost(const QUrl& source,
+                             const QString& name,
+                             const QString& world,
+                             const QString& title)
+  : m_engine(std::make_unique<QQmlApplicationEngine>())
+{
+  m_engine->addImageProvider(QStringLiteral("bevy"), new BevyImageProvider);
+  m_engine->rootContext()->setContextProperty(QStringLiteral("bevyWindow"),
+                                              name);
+  m_engine->rootContext()->setContextProperty(QStringLiteral("bevyWorld"),
+                                              world);
+
+  QStringList warnings;
+  const QMetaObject::Connection collect =
+    QObject::connect(m_engine.get(),
+                     &QQmlEngine::warnings,
+                     m_engine.get(),
+                     [&warnings](const QList<QQmlError>& errors) {
+                       for (const QQmlError& error : errors) {
+                         warnings.append(error.toString());
+                       }
+                     });
+  m_engine->load(source);
+  QObject::disconnect(collect);
+
+  const QList<QObject*> roots = m_engine->rootObjects();
+  if (roots.isEmpty()) {
+    m_error = warnings.isEmpty()
+                ? QStringLiteral("Failed to load %1").arg(source.toString())
+                : warnings.join(QLatin1Char('\n'));
+    return;
+  }
+
+  QObject* root = roots.first();
+  m_window = qobject_cast<QQuickWindow*>(root);
+  if (m_window == nullptr) {
+    auto* item = qobject_cast<QQuickItem*>(root);
+    if (item == nullptr) {
+      m_error = QStringLiteral("The root object of %1 is neither a Window nor "
+                               "an Item")
+                  .arg(source.toString());
+      return;
+    }
+    m_ownedWindow = std::make_unique<QQuickWindow>();
+    m_window = m_ownedWindow.get();
+    item->setParentItem(m_window->contentItem());
+    const QSizeF size(item->implicitWidth(), item->implicitHeight());
+    m_window->resize(size.isEmpty() ? QSize(800, 600) : size.toSize());
+    QObject::connect(m_window.data(),
+                     &QQuickWindow::widthChanged,
+                     item,
+                     [item](int width) { item->setWidth(width); });
+    QObject::connect(m_window.data(),
+                     &QQuickWindow::heightChanged,
+                     item,
+                     [item](int height) { item->setHeight(height); });
+    item->setSize(m_window->size());
+  }
+
+  if (!title.isEmpty()) {
+    m_window->setTitle(title);
+  }
+  m_window->show();
+}
+
+QmlWindowHost::~QmlWindowHost()
+{
+  // Item roots belong to the engine but are shown in the owned window
+  if (m_ownedWindow != nullptr) {
+    for (QQuickItem* item : m_ownedWindow->contentItem()->childItems()) {
+      item->setParentItem(nullptr);
+    }
+  }
+}
+
+QString
+QmlWindowHost::errorString() const
+{
+  return m_error;
+}
+
+bool
+QmlWindowHost::isVisible() const
+{
+  return m_window != nullptr && m_window->isVisible();
+}
+
+void
+QmlWindowHost::setTitle(const QString& title)
+{
+  if (m_window != nullptr) {
+    m_window->setTitle(title);
+  }
+}
+
+void
+QmlWindowHost::raise()
+{
+  if (m_window != nullptr) {
+    m_window->raise();
+    m_window->requestActivate();
+  }
+}
+
+::std::unique_ptr<QmlWindowHost>
+qmlWindowHostNew(const QUrl& source,
+                 const QString& name,
+                 const QString& world,
+                 const QString& title)
+{
+  return ::std::make_unique<QmlWindowHost>(source, name, world, title);
+}
+
+}
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <memory>
+#include <string>
+#include <vector>
+
+#include <QtCore/QDir>
+#include <QtCore/QDirIterator>
+#include <QtCore/QFileSystemWatcher>
+#include <QtCore/QString>
+#include <QtCore/QTimer>
+#include <QtCore/QUrl>
+#include <QtQml/QQmlApplicationEngine>
+#include <QtWidgets/QApplication>
+
+#include "bevyqml/imageprovider.h"
+#include "rust/cxx.h"
+
+namespace bevyqml {
+
+using QApplication = ::QApplication;
+using QQmlApplicationEngine = ::QQmlApplicationEngine;
+
+// A QApplication rather than a QGuiApplication, for the file dialogs. It
+// keeps referring to argc and argv, so they live as long as the process.
+inline ::std::unique_ptr<QApplication>
+applicationNew(const ::rust::Vec<::rust::String>& args)
+{
+  static ::std::vector<::std::string> storage;
+  static ::std::vector<char*> argv;
+  static int argc = 0;
+  for (const ::rust::String& arg : args) {
+    storage.emplace_back(arg);
+  }
+  for (::std::string& arg : storage) {
+    argv.push_back(arg.data());
+  }
+  argv.push_back(nullptr);
+  argc = static_cast<int>(storage.size());
+  // Lets Bevy render in an OpenGL context sharing textures with the scene
+  // graph, see src/render/interop/opengl.rs
+  QCoreApplication::setAttribute(Qt::AA_ShareOpenGLContexts);
+  return ::std::make_unique<QApplication>(argc, argv.data());
+}
+
+inline int
+applicationExec()
+{
+  return QApplication::exec();
+}
+
+// The engine serves the frames of Bevy cameras as image://bevy/<name>
+inline ::std::unique_ptr<QQmlApplicationEngine>
+qmlEngineNew()
+{
+  auto engine = ::std::make_unique<QQmlApplicationEngine>();
+  engine->addImageProvider(QStringLiteral("bevy"), new BevyImageProvider);
+  return engine;
+}
+
+inline void
+qmlEngineAddImportPath(QQmlApplicationEngine& engine, const QString& path)
+{
+  engine.addImportPath(path);
+}
+
+// Whether the file loaded and created a root object
+inline bool
+qmlEngineLoad(QQmlApplicationEngine& engine, const QUrl& url)
+{
+  engine.load(url);
+  return !engine.rootObjects().isEmpty();
+}
+
+
+// Watch the directory and the QML files below it
+inline void
+qmlWatchDirectory(QFileSystemWatcher& watcher, const QString& directory)
+{
+  QStringList found{ directory };
+  QDirIterator it(directory,
+                  { QStringLiteral("*.qml"), QStringLiteral("qmldir") },
+                  QDir::Files | QDir::AllDirs | QDir::NoDotAndDotDot,
+                  QDirIterator::Subdirectories);
+  while (it.hasNext()) {
+    found.append(it.next());
+  }
+  // Files which were replaced rather than written to left the watcher
+  const QStringList watched = watcher.files() + watcher.directories();
+  QStringList paths;
+  for (const QString& path : found) {
+    if (!watched.contains(path)) {
+      paths.append(path);
+    }
+  }
+  if (!paths.isEmpty()) {
+    watcher.addPaths(paths);
+  }
+}
+
+// Load the file from the directory on disk, and load it again whenever a
+// QML file below the directory changes. The root objects of the previous
+// load are destroyed, everything else, like the Bevy app, stays.
+inline bool
+qmlEngineLoadWatched(QQmlApplicationEngine& engine,
+                     const QString& directory,
+                     const QString& fileName)
+{
+  const QUrl url = QUrl::fromLocalFile(QDir(directory).filePath(fileName));
+  auto* watcher = new QFileSystemWatcher(&engine);
+  qmlWatchDirectory(*watcher, directory);
+
+  // Editors write a file in several steps, reload once they are done
+  auto* debounce = new QTimer(&engine);
+  debounce->setSingleShot(true);
+  debounce->setInterval(200);
+  QObject::connect(watcher,
+                   &QFileSystemWatcher::fileChanged,
+                   debounce,
+                   qOverload<>(&QTimer::start));
+  QObject::connect(watcher,
+                   &QFileSystemWatcher::directoryChanged,
+                   debounce,
+                   qOverload<>(&QTimer::start));
+  QObject::connect(
+    debounce, &QTimer::timeout, &engine, [&engine, watcher, directory, url] {
+      qmlWatchDirectory(*watcher, directory);
+      const QList<QObject*> roots = engine.rootObjects();
+      for (QObject* root : roots) {
+        delete root;
+      }
+      engine.clearComponentCache();
+      engine.load(url);
+      qInfo("Reloaded %s", qPrintable(url.toLocalFile()));
+    });
+
+  engine.load(url);
+  return !engine.rootObjects().isEmpty();
+}
+
+}
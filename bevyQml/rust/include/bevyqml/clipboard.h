@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstdint>
+
+#include <QtCore/QMimeData>
+#include <QtCore/QString>
+#include <QtGui/QClipboard>
+#include <QtGui/QGuiApplication>
+#include <QtGui/QImage>
+
+#include "bevyqml/runtime.h"
+
+namespace bevyqml {
+
+// The clipboard of the QGuiApplication, null in headless apps
+inline QClipboard*
+guiClipboard()
+{
+  return hasGuiApplication() ? QGuiApplication::clipboard() : nullptr;
+}
+
+// Counts up whenever the contents of the clipboard change, and asks for an
+// update of the Bevy app so it picks them up. Stays 0 in headless apps.
+inline ::std::uint64_t
+clipboardRevision()
+{
+  static ::std::uint64_t revision = 1;
+  static QMetaObject::Connection connection;
+  QClipboard* clipboard = guiClipboard();
+  if (clipboard == nullptr) {
+    return 0;
+  }
+  if (!connection) {
+    connection = QObject::connect(clipboard,
+                                  &QClipboard::dataChanged,
+                                  [] {
+                                    ++revision;
+                                    requestUpdate();
+                                  });
+  }
+  return revision;
+}
+
+inline QString
+clipboardText()
+{
+  QClipboard* clipboard = guiClipboard();
+  return clipboard != nullptr ? clipboard->text() : QString();
+}
+
+inline void
+setClipboardText(const QString& text)
+{
+  if (QClipboard* clipboard = guiClipboard()) {
+    clipboard->setText(text);
+  }
+}
+
+// A null image unless the clipboard holds one
+inline QImage
+clipboardImage()
+{
+  QClipboard* clipboard = guiClipboard();
+  if (clipboard == nullptr) {
+    return QImage();
+  }
+  const QMimeData* data = clipboard->mimeData();
+  if (data == nullptr || !data->hasImage()) {
+    return QImage();
+  }
+  return clipboard->image();
+}
+
+inline void
+setClipboardImage(const QImage& image)
+{
+  if (QClipboard* clipboard = guiClipboard()) {
+    clipboard->setImage(image);
+  }
+}
+
+}
@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <QtGui/QMatrix4x4>
+#include <QtGui/QQuaternion>
+
+// The Rust side mirrors these types field by field in src/convert.rs
+static_assert(sizeof(QQuaternion) == 4 * sizeof(float),
+              "QQuaternion must be four floats");
+static_assert(sizeof(QMatrix4x4) == 16 * sizeof(float) + sizeof(int),
+              "QMatrix4x4 must be sixteen floats and the flag bits");
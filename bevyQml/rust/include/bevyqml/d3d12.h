@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstddef>
+#include <cstdint>
+#include <cstring>
+
+#include <d3d12.h>
+#include <windows.h>
+
+#include "rust/cxx.h"
+
+// The Direct3D 12 side of sharing render targets with the scene graph, for
+// the device of Bevy. Pointers are passed as integers, and the objects that
+// are handed out carry a reference which the caller releases.
+
+namespace bevyqml {
+
+// Write the LUID of the adapter of the device into the first 8 bytes of luid
+inline void
+d3d12DeviceLuid(::std::size_t device, ::rust::Slice<::std::uint8_t> luid)
+{
+  const LUID adapterLuid =
+    reinterpret_cast<ID3D12Device*>(device)->GetAdapterLuid();
+  if (luid.size() >= sizeof(adapterLuid)) {
+    std::memcpy(luid.data(), &adapterLuid, sizeof(adapterLuid));
+  }
+}
+
+// Create a render target in a shared heap and an NT handle to it, which the
+// scene graph opens with its own device. Must match the texture the scene
+// graph expects in cpp/interop.cpp.
+inline bool
+d3d12CreateSharedTexture(::std::size_t device,
+                         ::std::uint32_t width,
+                         ::std::uint32_t height,
+                         ::std::size_t& resource,
+                         ::std::int64_t& handle,
+                         ::std::uint64_t& allocationSize)
+{
+  auto* d3dDevice = reinterpret_cast<ID3D12Device*>(device);
+
+  D3D12_HEAP_PROPERTIES heap = {};
+  heap.Type = D3D12_HEAP_TYPE_DEFAULT;
+
+  D3D12_RESOURCE_DESC desc = {};
+  desc.Dimension = D3D12_RESOURCE_DIMENSION_TEXTURE2D;
+  desc.Width = width;
+  desc.Height = height;
+  desc.DepthOrArraySize = 1;
+  desc.MipLevels = 1;
+  desc.Format = DXGI_FORMAT_R8G8B8A8_UNORM_SRGB;
+  desc.SampleDesc.Count = 1;
+  desc.Layout = D3D12_TEXTURE_LAYOUT_UNKNOWN;
+  // Both devices use the texture in turns, synchronized by the shared fence
+  desc.Flags = D3D12_RESOURCE_FLAG_ALLOW_RENDER_TARGET |
+               D3D12_RESOURCE_FLAG_ALLOW_SIMULTANEOUS_ACCESS;
+
+  ID3D12Resource* texture = nullptr;
+  if (FAILED(d3dDevice->CreateCommittedResource(&heap,
+                                                D3D12_HEAP_FLAG_SHARED,
+                                                &desc,
+                                                D3D12_RESOURCE_STATE_COMMON,
+                                                nullptr,
+                                                IID_PPV_ARGS(&texture)))) {
+    return false;
+  }
+
+  HANDLE shared = nullptr;
+  if (FAILED(d3dDevice->CreateSharedHandle(
+        texture, nullptr, GENERIC_ALL, nullptr, &shared))) {
+    texture->Release();
+    return false;
+  }
+
+  resource = reinterpret_cast<::std::size_t>(texture);
+  handle = reinterpret_cast<::std::int64_t>(shared);
+  allocationSize = d3dDevice->GetResourceAllocationInfo(0, 1, &desc).SizeInBytes;
+  return true;
+}
+
+// Create a fence the scene graph opens as well, and an NT handle to it
+inline bool
+d3d12CreateSharedFence(::std::size_t device,
+                       ::std::size_t& fence,
+                       ::std::int64_t& handle)
+{
+  auto* d3dDevice = reinterpret_cast<ID3D12Device*>(device);
+
+  ID3D12Fence* d3dFence = nullptr;
+  if (FAILED(d3dDevice->CreateFence(
+        0, D3D12_FENCE_FLAG_SHARED, IID_PPV_ARGS(&d3dFence)))) {
+    return false;
+  }
+
+  HANDLE shared = nullptr;
+  if (FAILED(d3dDevice->CreateSharedHandle(
+        d3dFence, nullptr, GENERIC_ALL, nullptr, &shared))) {
+    d3dFence->Release();
+    return false;
+  }
+
+  fence = reinterpret_cast<::std::size_t>(d3dFence);
+  handle = reinterpret_cast<::std::int64_t>(shared);
+  return true;
+}
+
+// Set the fence to value, once the GPU has finished with the frame
+inline void
+d3d12SignalFence(::std::size_t fence, ::std::uint64_t value)
+{
+  reinterpret_cast<ID3D12Fence*>(fence)->Signal(value);
+}
+
+// Block until the fence reaches value, returns false after the timeout
+inline bool
+d3d12WaitForFence(::std::size_t fence,
+                  ::std::uint64_t value,
+                  ::std::uint32_t timeoutMs)
+{
+  auto* d3dFence = reinterpret_cast<ID3D12Fence*>(fence);
+  if (d3dFence->GetCompletedValue() >= value) {
+    return true;
+  }
+
+  HANDLE event = CreateEventW(nullptr, FALSE, FALSE, nullptr);
+  if (event == nullptr) {
+    return false;
+  }
+  bool reached = false;
+  if (SUCCEEDED(d3dFence->SetEventOnCompletion(value, event))) {
+    reached = WaitForSingleObject(event, timeoutMs) == WAIT_OBJECT_0;
+  }
+  CloseHandle(event);
+  return reached;
+}
+
+inline void
+d3d12ReleaseFence(::std::size_t fence)
+{
+  reinterpret_cast<ID3D12Fence*>(fence)->Release();
+}
+
+// Close a handle that will not be imported after all
+inline void
+d3d12CloseHandle(::std::int64_t handle)
+{
+  CloseHandle(reinterpret_cast<HANDLE>(handle));
+}
+
+}
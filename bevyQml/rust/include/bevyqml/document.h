@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <QtCore/QString>
+#include <QtGui/QGuiApplication>
+
+namespace bevyqml {
+
+// The name of the application for window titles, which falls back to the
+// application name when no display name was set
+inline QString
+applicationDisplayName()
+{
+  return QGuiApplication::applicationDisplayName();
+}
+
+}
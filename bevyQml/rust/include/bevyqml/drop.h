@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <QtCore/QList>
+#include <QtCore/QMimeData>
+#include <QtCore/QPointF>
+#include <QtCore/QUrl>
+#include <QtGui/QDropEvent>
+#include <QtQuick/QQuickItem>
+
+namespace bevyqml {
+
+template<typename T>
+void
+quickItemAcceptDrops(T& item)
+{
+  item.setFlag(QQuickItem::ItemAcceptsDrops, true);
+}
+
+// Map a position of the item into the coordinates of its scene
+template<typename T>
+QPointF
+quickItemMapToScene(const T& item, const QPointF& position)
+{
+  return item.mapToScene(position);
+}
+
+// The drag enter and move events derive from QDropEvent, so these are used
+// for all three.
+inline QList<QUrl>
+dropEventUrls(const QDropEvent& event)
+{
+  const QMimeData* mimeData = event.mimeData();
+  return mimeData != nullptr ? mimeData->urls() : QList<QUrl>();
+}
+
+inline QPointF
+dropEventPosition(const QDropEvent& event)
+{
+#if QT_VERSION >= QT_VERSION_CHECK(6, 0, 0)
+  return event.position();
+#else
+  return event.posF();
+#endif
+}
+
+inline void
+dropEventSetAccepted(QDropEvent& event, bool accepted)
+{
+  if (accepted) {
+    event.acceptProposedAction();
+  } else {
+    event.ignore();
+  }
+}
+
+}
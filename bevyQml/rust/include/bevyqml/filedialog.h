@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <QtCore/QCoreApplication>
+#include <QtCore/QString>
+#include <QtCore/QtGlobal>
+#include <QtWidgets/QApplication>
+#include <QtWidgets/QFileDialog>
+
+namespace bevyqml {
+
+// QFileDialog is a widget, so the application must be a QApplication
+inline bool
+fileDialogsAvailable()
+{
+  if (qobject_cast<QApplication*>(QCoreApplication::instance()) != nullptr) {
+    return true;
+  }
+  qWarning("File dialogs need a QApplication rather than a QGuiApplication");
+  return false;
+}
+
+// The file picked in a modal dialog, empty if it was canceled. The filters
+// are given as in "Models (*.gltf *.glb);;Images (*.png *.jpg)".
+inline QString
+openFileDialog(const QString& filters)
+{
+  if (!fileDialogsAvailable()) {
+    return QString();
+  }
+  return QFileDialog::getOpenFileName(nullptr, QString(), QString(), filters);
+}
+
+inline QString
+saveFileDialog()
+{
+  if (!fileDialogsAvailable()) {
+    return QString();
+  }
+  return QFileDialog::getSaveFileName(nullptr);
+}
+
+}
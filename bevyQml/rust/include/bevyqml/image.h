@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstdint>
+
+#include <QtCore/QString>
+#include <QtGui/QColorSpace>
+#include <QtGui/QImage>
+
+#include "rust/cxx.h"
+
+namespace bevyqml {
+
+// How pixels are laid out in the tightly packed buffers shared with Rust
+enum class PixelLayout : ::std::uint8_t
+{
+  Rgba8,
+  Rgba32Float,
+  R8,
+};
+
+// Floating point images keep their precision, everything else becomes RGBA8
+inline PixelLayout
+qimagePixelLayout(const QImage& image)
+{
+  switch (image.format()) {
+    case QImage::Format_RGBX16FPx4:
+    case QImage::Format_RGBA16FPx4:
+    case QImage::Format_RGBA16FPx4_Premultiplied:
+    case QImage::Format_RGBX32FPx4:
+    case QImage::Format_RGBA32FPx4:
+    case QImage::Format_RGBA32FPx4_Premultiplied:
+      return PixelLayout::Rgba32Float;
+    default:
+      return PixelLayout::Rgba8;
+  }
+}
+
+// Whether the pixels of the image are stored without a transfer function
+inline bool
+qimageIsLinear(const QImage& image)
+{
+  const QColorSpace colorSpace = image.colorSpace();
+  return colorSpace.isValid() &&
+         colorSpace.transferFunction() == QColorSpace::TransferFunction::Linear;
+}
+
+inline bool
+qimageToPixels(const QImage& image,
+               PixelLayout layout,
+               ::rust::Vec<::std::uint8_t>& data,
+               ::std::uint32_t& width,
+               ::std::uint32_t& height)
+{
+  if (image.isNull()) {
+    return false;
+  }
+
+  // Converting also turns premultiplied alpha into the straight alpha Bevy
+  // expects
+  QImage::Format format = QImage::Format_RGBA8888;
+  qsizetype pixelBytes = 4;
+  switch (layout) {
+    case PixelLayout::Rgba32Float:
+      format = QImage::Format_RGBA32FPx4;
+      pixelBytes = 16;
+      break;
+    case PixelLayout::R8:
+      format = QImage::Format_Grayscale8;
+      pixelBytes = 1;
+      break;
+    case PixelLayout::Rgba8:
+      break;
+  }
+  const QImage converted = image.convertToFormat(format);
+
+  // Scan lines are padded to 32 bits, the buffer is not
+  const qsizetype rowBytes = converted.width() * pixelBytes;
+  data.reserve(static_cast<::std::size_t>(rowBytes * converted.height()));
+  for (int y = 0; y < converted.height(); ++y) {
+    const uchar* line = converted.constScanLine(y);
+    for (qsizetype i = 0; i < rowBytes; ++i) {
+      data.push_back(line[i]);
+    }
+  }
+
+  width = static_cast<::std::uint32_t>(converted.width());
+  height = static_cast<::std::uint32_t>(converted.height());
+  return true;
+}
+
+inline QImage
+qimageFromPixels(::std::uint32_t width,
+                 ::std::uint32_t height,
+                 PixelLayout layout,
+                 bool linear,
+                 ::rust::Slice<const ::std::uint8_t> data)
+{
+  QImage::Format format = QImage::Format_RGBA8888;
+  qsizetype pixelBytes = 4;
+  switch (layout) {
+    case PixelLayout::Rgba32Float:
+      format = QImage::Format_RGBA32FPx4;
+      pixelBytes = 16;
+      break;
+    case PixelLayout::R8:
+      format = QImage::Format_Grayscale8;
+      pixelBytes = 1;
+      break;
+    case PixelLayout::Rgba8:
+      break;
+  }
+
+  // The QImage does not own the Rust buffer, so detach a copy before the
+  // slice goes out of scope.
+  QImage image = QImage(data.data(),
+                        static_cast<int>(width),
+                        static_cast<int>(height),
+                        static_cast<qsizetype>(width) * pixelBytes,
+                        format)
+                   .copy();
+  if (layout != PixelLayout::R8) {
+    image.setColorSpace(linear ? QColorSpace::SRgbLinear : QColorSpace::SRgb);
+  }
+  return image;
+}
+
+// The format follows the suffix of the path
+inline bool
+qimageSave(const QImage& image, const QString& path)
+{
+  return image.save(path);
+}
+
+// The format follows the contents of the file, a missing file gives a null
+// image
+inline QImage
+qimageLoad(const QString& path)
+{
+  return QImage(path);
+}
+
+inline bool
+qimageIsNull(const QImage& image)
+{
+  return image.isNull();
+}
+
+}
@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <QtQuick/QQuickImageProvider>
+
+namespace bevyqml {
+
+// Serves the frames of Bevy cameras with a QmlImageTarget as
+// image://bevy/<name>, anything after a further slash is ignored so that a
+// frame counter can be appended to bypass the QML image cache.
+//
+// Register it with the engine before loading any QML:
+//   engine.addImageProvider(QStringLiteral("bevy"), new bevyqml::BevyImageProvider);
+class BevyImageProvider : public QQuickImageProvider
+{
+public:
+  BevyImageProvider();
+
+  QImage requestImage(const QString& id,
+                      QSize* size,
+                      const QSize& requestedSize) override;
+};
+
+}
@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstdint>
+
+#include <QtCore/QString>
+#include <QtGui/QGuiApplication>
+#include <QtGui/QInputMethod>
+#include <QtGui/QInputMethodEvent>
+#include <QtQuick/QQuickItem>
+
+namespace bevyqml {
+
+// Take input method events while a Bevy text field has the focus, and show
+// or hide the virtual keyboard if the item has the focus
+template<typename T>
+void
+quickItemSetAcceptsInputMethod(T& item, bool accepts)
+{
+  item.setFlag(QQuickItem::ItemAcceptsInputMethod, accepts);
+  if (!item.hasActiveFocus()) {
+    return;
+  }
+  QInputMethod* inputMethod = QGuiApplication::inputMethod();
+  inputMethod->update(Qt::ImEnabled);
+  if (accepts) {
+    inputMethod->show();
+  } else {
+    inputMethod->reset();
+    inputMethod->hide();
+  }
+}
+
+// Tell the input method the text cursor moved, so candidate windows follow
+template<typename T>
+void
+quickItemUpdateInputMethod(T& item)
+{
+  if (item.hasActiveFocus()) {
+    QGuiApplication::inputMethod()->update(Qt::ImCursorRectangle);
+  }
+}
+
+inline QString
+inputMethodEventPreeditString(const QInputMethodEvent& event)
+{
+  return event.preeditString();
+}
+
+inline QString
+inputMethodEventCommitString(const QInputMethodEvent& event)
+{
+  return event.commitString();
+}
+
+// Where the cursor is in the preedit string in UTF-16 code units, or -1 if
+// it is hidden
+inline ::std::int32_t
+inputMethodEventCursorPosition(const QInputMethodEvent& event)
+{
+  for (const QInputMethodEvent::Attribute& attribute : event.attributes()) {
+    if (attribute.type == QInputMethodEvent::Cursor) {
+      return attribute.length > 0 ? attribute.start : -1;
+    }
+  }
+  return -1;
+}
+
+}
@@ -0,0 +1,232 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstdint>
+
+#include <QtCore/QPoint>
+#include <QtCore/QPointF>
+#include <QtCore/QString>
+#include <QtGui/QCursor>
+#include <QtGui/QKeyEvent>
+#include <QtGui/QTouchEvent>
+#include <QtGui/QMouseEvent>
+#include <QtGui/QWheelEvent>
+#include <QtQuick/QQuickItem>
+#include <QtQuick/QQuickWindow>
+
+namespace bevyqml {
+
+template<typename T>
+void
+quickItemAcceptMouseInput(T& item)
+{
+  item.setAcceptedMouseButtons(Qt::AllButtons);
+  item.setAcceptHoverEvents(true);
+}
+
+template<typename T>
+void
+quickItemAcceptTouchInput(T& item)
+{
+  item.setAcceptTouchEvents(true);
+}
+
+// Take keyboard focus when the item is clicked or tabbed to
+template<typename T>
+void
+quickItemAcceptKeyboardInput(T& item)
+{
+  item.setActiveFocusOnTab(true);
+}
+
+template<typename T>
+void
+quickItemForceActiveFocus(T& item)
+{
+  item.forceActiveFocus(Qt::MouseFocusReason);
+}
+
+// Show a Qt::CursorShape while the cursor is over the item
+template<typename T>
+void
+quickItemSetCursorShape(T& item, ::std::int32_t shape)
+{
+  item.setCursor(QCursor(static_cast<Qt::CursorShape>(shape)));
+}
+
+// Move the cursor to a position of the item, which only works on platforms
+// that allow applications to warp the cursor
+template<typename T>
+void
+quickItemMoveCursor(T& item, const QPointF& position)
+{
+  const QQuickWindow* window = item.window();
+  if (window != nullptr) {
+    QCursor::setPos(window->screen(), item.mapToGlobal(position).toPoint());
+  }
+}
+
+// Keep receiving mouse moves while the pointer is locked to the item
+template<typename T>
+void
+quickItemGrabMouse(T& item, bool grab)
+{
+  if (grab) {
+    item.grabMouse();
+  } else {
+    item.ungrabMouse();
+  }
+}
+
+// The ratio between physical and logical pixels of the window showing the
+// item, or 1 while the item is not in a window.
+template<typename T>
+double
+quickItemDevicePixelRatio(const T& item)
+{
+  const QQuickWindow* window = item.window();
+  return window != nullptr ? window->effectiveDevicePixelRatio() : 1.0;
+}
+
+inline QPointF
+mouseEventPosition(const QMouseEvent& event)
+{
+#if QT_VERSION >= QT_VERSION_CHECK(6, 0, 0)
+  return event.position();
+#else
+  return event.localPos();
+#endif
+}
+
+inline int
+mouseEventButton(const QMouseEvent& event)
+{
+  return static_cast<int>(event.button());
+}
+
+inline ::std::uint32_t
+mouseEventButtons(const QMouseEvent& event)
+{
+  return static_cast<::std::uint32_t>(event.buttons());
+}
+
+inline ::std::uint32_t
+mouseEventModifiers(const QMouseEvent& event)
+{
+  return static_cast<::std::uint32_t>(event.modifiers());
+}
+
+inline QPointF
+hoverEventPosition(const QHoverEvent& event)
+{
+#if QT_VERSION >= QT_VERSION_CHECK(6, 0, 0)
+  return event.position();
+#else
+  return event.posF();
+#endif
+}
+
+inline QPointF
+wheelEventPosition(const QWheelEvent& event)
+{
+#if QT_VERSION >= QT_VERSION_CHECK(6, 0, 0)
+  return event.position();
+#else
+  return event.posF();
+#endif
+}
+
+inline ::std::uint32_t
+wheelEventModifiers(const QWheelEvent& event)
+{
+  return static_cast<::std::uint32_t>(event.modifiers());
+}
+
+inline QPoint
+wheelEventAngleDelta(const QWheelEvent& event)
+{
+  return event.angleDelta();
+}
+
+inline QPoint
+wheelEventPixelDelta(const QWheelEvent& event)
+{
+  return event.pixelDelta();
+}
+
+inline int
+keyEventKey(const QKeyEvent& event)
+{
+  return event.key();
+}
+
+inline ::std::uint32_t
+keyEventModifiers(const QKeyEvent& event)
+{
+  return static_cast<::std::uint32_t>(event.modifiers());
+}
+
+inline QString
+keyEventText(const QKeyEvent& event)
+{
+  return event.text();
+}
+
+inline bool
+keyEventIsAutoRepeat(const QKeyEvent& event)
+{
+  return event.isAutoRepeat();
+}
+
+inline int
+touchEventPointCount(const QTouchEvent& event)
+{
+#if QT_VERSION >= QT_VERSION_CHECK(6, 0, 0)
+  return static_cast<int>(event.pointCount());
+#else
+  return event.touchPoints().size();
+#endif
+}
+
+inline int
+touchEventPointId(const QTouchEvent& event, int index)
+{
+#if QT_VERSION >= QT_VERSION_CHECK(6, 0, 0)
+  return event.points().at(index).id();
+#else
+  return event.touchPoints().at(index).id();
+#endif
+}
+
+inline QPointF
+touchEventPointPosition(const QTouchEvent& event, int index)
+{
+#if QT_VERSION >= QT_VERSION_CHECK(6, 0, 0)
+  return event.points().at(index).position();
+#else
+  return event.touchPoints().at(index).pos();
+#endif
+}
+
+// The QEventPoint::State of the point, Qt 5 uses the same values
+inline int
+touchEventPointState(const QTouchEvent& event, int index)
+{
+#if QT_VERSION >= QT_VERSION_CHECK(6, 0, 0)
+  return static_cast<int>(event.points().at(index).state());
+#else
+  return static_cast<int>(event.touchPoints().at(index).state());
+#endif
+}
+
+inline double
+touchEventPointPressure(const QTouchEvent& event, int index)
+{
+#if QT_VERSION >= QT_VERSION_CHECK(6, 0, 0)
+  return event.points().at(index).pressure();
+#else
+  return event.touchPoints().at(index).pressure();
+#endif
+}
+
+}
@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstdint>
+
+#include <QtQuick/QQuickItem>
+#include <QtQuick/QQuickWindow>
+#include <QtQuick/QSGNode>
+
+#include "rust/cxx.h"
+
+namespace bevyqml {
+
+// Defined by the bridge in src/cxxqt_bevy_quick_item.rs
+struct SharedTextureFrame;
+
+// Returns the QSGRendererInterface::GraphicsApi the window renders with and
+// writes the 16 byte UUID of its physical device into deviceUuid where the
+// API exposes one. Returns whether the UUID was written via uuidValid.
+int
+quickWindowGraphicsDevice(QQuickWindow* window,
+                          ::rust::Slice<::std::uint8_t> deviceUuid,
+                          bool& uuidValid);
+
+// Render the window with the VkInstance and the physical device Bevy renders
+// with, and also with its VkDevice and queue when handshake is 2, see
+// VulkanHandshake in src/render/interop/mod.rs.
+//
+// This only has an effect while the scene graph renders with Vulkan and the
+// window has not been created yet. Returns whether the window adopted them.
+bool
+quickWindowAdoptVulkanDevice(QQuickWindow* window,
+                             int handshake,
+                             ::std::uint64_t instance,
+                             ::std::uint64_t physicalDevice,
+                             ::std::uint64_t device,
+                             ::std::uint32_t queueFamily,
+                             ::std::uint32_t queueIndex);
+
+// Import a texture exported by the Bevy render world as one of the buffers of
+// a texture node covering rect, or show one of its buffers.
+//
+// When the handle of the frame is valid, the texture is imported as the
+// buffer, replacing the one imported before. Ownership of a valid handle and
+// fence always passes to this function, they are closed when the import
+// fails. Otherwise the buffer is shown and only marked dirty, as its memory is
+// updated in place. The scene graph waits for the frame released under a non
+// zero key before sampling it: on the GPU with the fence of Direct3D, or the
+// GLsync the key is with OpenGL. Once the frame drawing it has ended, the
+// buffer is handed back to the slot of the frame, after waiting for the GPU
+// if the frame asks for it, and under the key after it with Direct3D.
+// Returns nullptr if the texture could not be imported, the caller should
+// then fall back to copying frames.
+QSGNode*
+updateSharedTextureNode(QQuickItem& item,
+                        QSGNode* oldNode,
+                        const SharedTextureFrame& frame,
+                        const QRectF& rect);
+
+template<typename T>
+int
+quickItemGraphicsDevice(T& item,
+                        ::rust::Slice<::std::uint8_t> deviceUuid,
+                        bool& uuidValid)
+{
+  return quickWindowGraphicsDevice(item.window(), deviceUuid, uuidValid);
+}
+
+// Let every window the item is shown in adopt the Vulkan device of Bevy, see
+// quickWindowAdoptVulkanDevice()
+template<typename T>
+void
+quickItemAdoptVulkanDevice(T& item,
+                           int handshake,
+                           ::std::uint64_t instance,
+                           ::std::uint64_t physicalDevice,
+                           ::std::uint64_t device,
+                           ::std::uint32_t queueFamily,
+                           ::std::uint32_t queueIndex)
+{
+  const auto adopt = [=](QQuickWindow* window) {
+    if (window != nullptr) {
+      quickWindowAdoptVulkanDevice(window,
+                                   handshake,
+                                   instance,
+                                   physicalDevice,
+                                   device,
+                                   queueFamily,
+                                   queueIndex);
+    }
+  };
+  QObject::connect(&item, &QQuickItem::windowChanged, &item, adopt);
+  adopt(item.window());
+}
+
+template<typename T>
+QSGNode*
+quickItemUpdateSharedTextureNode(T& item,
+                                 QSGNode* oldNode,
+                                 const SharedTextureFrame& frame,
+                                 const QRectF& rect)
+{
+  return updateSharedTextureNode(item, oldNode, frame, rect);
+}
+
+}
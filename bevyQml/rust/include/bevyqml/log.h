@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstdint>
+#include <map>
+#include <memory>
+#include <mutex>
+#include <string>
+
+#include <QtCore/QLoggingCategory>
+#include <QtCore/QString>
+
+#include "rust/cxx.h"
+
+namespace bevyqml {
+
+// Must match level_value in src/log.rs
+enum class LogLevel : ::std::uint8_t
+{
+  Trace = 0,
+  Debug = 1,
+  Info = 2,
+  Warn = 3,
+  Error = 4,
+};
+
+// The Qt category of a Bevy log target, bevy_render::renderer is logged as
+// bevy.bevy_render.renderer
+inline const QLoggingCategory&
+logCategory(::rust::Str target)
+{
+  static ::std::mutex mutex;
+  // Categories keep a pointer to their name, which the keys of the map own
+  static ::std::map<::std::string, ::std::unique_ptr<QLoggingCategory>>
+    categories;
+
+  const ::std::lock_guard<::std::mutex> lock(mutex);
+  auto name = ::std::string("bevy.") + ::std::string(target);
+  for (auto found = name.find("::"); found != ::std::string::npos;
+       found = name.find("::", found)) {
+    name.replace(found, 2, ".");
+  }
+
+  auto entry = categories.find(name);
+  if (entry == categories.end()) {
+    entry = categories.emplace(name, nullptr).first;
+    entry->second = ::std::make_unique<QLoggingCategory>(entry->first.c_str());
+  }
+  return *entry->second;
+}
+
+// Called from any thread logging through Bevy
+inline void
+qtLog(::std::uint8_t level, ::rust::Str target, ::rust::Str message)
+{
+  const auto& category = logCategory(target);
+  const auto text =
+    QString::fromUtf8(message.data(), static_cast<qsizetype>(message.size()));
+  switch (static_cast<LogLevel>(level)) {
+    case LogLevel::Trace:
+    case LogLevel::Debug:
+      qCDebug(category).noquote() << text;
+      break;
+    case LogLevel::Info:
+      qCInfo(category).noquote() << text;
+      break;
+    case LogLevel::Warn:
+      qCWarning(category).noquote() << text;
+      break;
+    case LogLevel::Error:
+      qCCritical(category).noquote() << text;
+      break;
+  }
+}
+
+}
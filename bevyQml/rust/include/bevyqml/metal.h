@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstddef>
+#include <cstdint>
+
+class QQuickWindow;
+class QSGTexture;
+
+// Metal is only reachable from Objective-C++, so these are implemented in
+// cpp/metal.mm. Devices and textures are passed as integers or untyped
+// pointers, and the objects that are handed out carry a reference.
+
+namespace bevyqml {
+
+// The registryID of the device, which tells GPUs apart
+::std::uint64_t
+metalDeviceRegistryId(::std::size_t device);
+
+// Create an IOSurface and a render target backed by it on the device of
+// Bevy. Returns the texture and the surface, whose reference passes to the
+// importer. Must match the texture the scene graph imports.
+bool
+metalCreateSharedTexture(::std::size_t device,
+                         ::std::uint32_t width,
+                         ::std::uint32_t height,
+                         ::std::size_t& texture,
+                         ::std::int64_t& surface,
+                         ::std::uint64_t& allocationSize);
+
+// Wrap the surface in a texture on the Metal device of the window, taking
+// over the reference to the surface. Returns nullptr on failure, and the
+// MTLTexture for metalReleaseTexture() via texture otherwise.
+QSGTexture*
+metalImportSurface(QQuickWindow* window,
+                   ::std::int64_t surface,
+                   ::std::uint32_t width,
+                   ::std::uint32_t height,
+                   void*& texture);
+
+void
+metalReleaseTexture(void* texture);
+
+// Drop a surface that will not be imported after all
+void
+metalReleaseSurface(::std::int64_t surface);
+
+// Wait for the command queue of the window to finish the frames it was given
+void
+metalWaitIdle(QQuickWindow* window);
+
+}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstdint>
+
+#include <QtCore/QModelIndex>
+
+namespace bevyqml {
+
+// The bits of the entity shown at an index of one of the entity models,
+// which all keep them in Qt::UserRole
+inline ::std::uint64_t
+modelIndexEntity(const QModelIndex& index)
+{
+  return index.data(Qt::UserRole).toULongLong();
+}
+
+}
@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstddef>
+#include <cstdint>
+#include <memory>
+
+#include <QtGui/QOffscreenSurface>
+#include <QtGui/QOpenGLContext>
+#include <QtGui/QOpenGLExtraFunctions>
+
+#include "rust/cxx.h"
+
+// The OpenGL context Bevy renders in through the GL backend of wgpu. It
+// shares its objects with the contexts of the scene graph, which needs
+// Qt::AA_ShareOpenGLContexts to be set before the application is created.
+// The context belongs to the GUI thread, where the render world runs.
+
+namespace bevyqml {
+
+struct OpenGLShareContext
+{
+  ::std::unique_ptr<QOpenGLContext> context;
+  ::std::unique_ptr<QOffscreenSurface> surface;
+};
+
+inline OpenGLShareContext&
+openglShareContext()
+{
+  static OpenGLShareContext shared;
+  return shared;
+}
+
+// Create the context sharing with QOpenGLContext::globalShareContext() and
+// make it current. Returns false if there is nothing to share with.
+inline bool
+openglShareContextCreate()
+{
+  OpenGLShareContext& shared = openglShareContext();
+  if (shared.context != nullptr) {
+    return true;
+  }
+
+  QOpenGLContext* globalShareContext = QOpenGLContext::globalShareContext();
+  if (globalShareContext == nullptr) {
+    return false;
+  }
+
+  auto context = ::std::make_unique<QOpenGLContext>();
+  context->setFormat(globalShareContext->format());
+  context->setShareContext(globalShareContext);
+  auto surface = ::std::make_unique<QOffscreenSurface>();
+  surface->setFormat(globalShareContext->format());
+  surface->create();
+  if (!context->create() || !context->shareContext() ||
+      !context->makeCurrent(surface.get())) {
+    return false;
+  }
+
+  shared.context = ::std::move(context);
+  shared.surface = ::std::move(surface);
+  return true;
+}
+
+// Make the context current again, after the scene graph made its own current
+// on the GUI thread
+inline bool
+openglShareContextMakeCurrent()
+{
+  OpenGLShareContext& shared = openglShareContext();
+  if (shared.context == nullptr) {
+    return false;
+  }
+  if (QOpenGLContext::currentContext() == shared.context.get()) {
+    return true;
+  }
+  return shared.context->makeCurrent(shared.surface.get());
+}
+
+inline ::std::size_t
+openglGetProcAddress(::rust::Str name)
+{
+  OpenGLShareContext& shared = openglShareContext();
+  if (shared.context == nullptr) {
+    return 0;
+  }
+  const QByteArray procName(name.data(), static_cast<int>(name.size()));
+  return reinterpret_cast<::std::size_t>(
+    shared.context->getProcAddress(procName));
+}
+
+// Insert a sync object after the commands Bevy submitted so far and flush
+// them, so that the scene graph can wait for them in its own context.
+// Returns 0 if no sync object could be created.
+inline ::std::int64_t
+openglFenceSync()
+{
+  if (!openglShareContextMakeCurrent()) {
+    return 0;
+  }
+  QOpenGLExtraFunctions* f = openglShareContext().context->extraFunctions();
+  GLsync sync = f->glFenceSync(GL_SYNC_GPU_COMMANDS_COMPLETE, 0);
+  f->glFlush();
+  return reinterpret_cast<::std::int64_t>(sync);
+}
+
+// Sync objects are shared as well, one the scene graph still waits for is
+// only deleted once the wait is over
+inline void
+openglDeleteSync(::std::int64_t sync)
+{
+  if (sync == 0 || !openglShareContextMakeCurrent()) {
+    return;
+  }
+  openglShareContext().context->extraFunctions()->glDeleteSync(
+    reinterpret_cast<GLsync>(sync));
+}
+
+}
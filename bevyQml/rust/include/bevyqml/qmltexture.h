@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstdint>
+#include <memory>
+
+#include <QtCore/QString>
+#include <QtCore/QUrl>
+
+#include "rust/cxx.h"
+
+class QQmlComponent;
+class QQuickItem;
+class QQuickRenderControl;
+class QQuickWindow;
+class QRhiRenderBuffer;
+class QRhiRenderPassDescriptor;
+class QRhiTexture;
+class QRhiTextureRenderTarget;
+
+namespace bevyqml {
+
+// Renders a QML scene offscreen with QQuickRenderControl and reads the
+// result back as RGBA8 pixels, so it can be used as a Bevy texture. Input is
+// delivered to the offscreen window as if it was shown on screen.
+class QmlTextureRenderer
+{
+public:
+  QmlTextureRenderer(const QUrl& source,
+                     ::std::uint32_t width,
+                     ::std::uint32_t height);
+  ~QmlTextureRenderer();
+
+  // Empty until loading the QML failed
+  QString errorString() const;
+
+  void resize(::std::uint32_t width, ::std::uint32_t height);
+
+  // Render the scene if it changed since the last frame, returns whether
+  // pixels holds a new frame
+  bool render(::rust::Vec<::std::uint8_t>& pixels);
+
+  // Positions are in pixels of the texture, the event type is a
+  // QEvent::Type and the buttons and modifiers are Qt flags
+  void sendMouse(::std::int32_t type,
+                 double x,
+                 double y,
+                 ::std::int32_t button,
+                 ::std::uint32_t buttons,
+                 ::std::uint32_t modifiers);
+  void sendWheel(double x,
+                 double y,
+                 ::std::int32_t angleX,
+                 ::std::int32_t angleY,
+                 ::std::uint32_t modifiers);
+  void sendKey(bool pressed,
+               ::std::int32_t key,
+               ::std::uint32_t modifiers,
+               const QString& text,
+               bool autoRepeat);
+  void setFocused(bool focused);
+
+private:
+  void createRenderTarget();
+  void releaseRenderTarget();
+
+  std::unique_ptr<QQuickRenderControl> m_renderControl;
+  std::unique_ptr<QQuickWindow> m_window;
+  std::unique_ptr<QQmlComponent> m_component;
+  QQuickItem* m_root = nullptr;
+  QRhiTexture* m_texture = nullptr;
+  QRhiRenderBuffer* m_depthStencil = nullptr;
+  QRhiTextureRenderTarget* m_renderTarget = nullptr;
+  QRhiRenderPassDescriptor* m_renderPass = nullptr;
+  QString m_error;
+  bool m_initialized = false;
+  bool m_dirty = true;
+};
+
+::std::unique_ptr<QmlTextureRenderer>
+qmlTextureRendererNew(const QUrl& source,
+                      ::std::uint32_t width,
+                      ::std::uint32_t height);
+
+}
@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstdint>
+
+#include <QtCore/QDir>
+#include <QtCore/QFile>
+#include <QtCore/QFileInfo>
+#include <QtCore/QString>
+#include <QtCore/QStringList>
+
+#include "rust/cxx.h"
+
+namespace bevyqml {
+
+// Paths are relative to the root of the resource system, without the
+// leading ":/". These only touch read-only resources, so they can be called
+// from the asset task threads.
+
+inline bool
+qrcRead(const QString& path, ::rust::Vec<::std::uint8_t>& data)
+{
+  QFile file(QStringLiteral(":/") + path);
+  if (!file.open(QIODevice::ReadOnly)) {
+    return false;
+  }
+
+  const QByteArray bytes = file.readAll();
+  data.reserve(static_cast<::std::size_t>(bytes.size()));
+  for (const char byte : bytes) {
+    data.push_back(static_cast<::std::uint8_t>(byte));
+  }
+  return true;
+}
+
+inline bool
+qrcExists(const QString& path)
+{
+  return QFileInfo::exists(QStringLiteral(":/") + path);
+}
+
+// The size of a file, or -1 if there is none
+inline ::std::int64_t
+qrcSize(const QString& path)
+{
+  const QFileInfo info(QStringLiteral(":/") + path);
+  return info.isFile() ? info.size() : -1;
+}
+
+inline bool
+qrcIsDirectory(const QString& path)
+{
+  return QFileInfo(QStringLiteral(":/") + path).isDir();
+}
+
+inline QStringList
+qrcEntries(const QString& path)
+{
+  return QDir(QStringLiteral(":/") + path)
+    .entryList(QDir::AllEntries | QDir::NoDotAndDotDot);
+}
+
+}
@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstddef>
+#include <cstdint>
+#include <memory>
+
+#include <QtGui/QImage>
+#include <QtGui/QScreen>
+#include <QtQuick/QQuickItem>
+#include <QtQuick/QQuickWindow>
+#include <QtQuick/QSGSimpleTextureNode>
+
+#include "bevyqml/runtime.h"
+#include "rust/cxx.h"
+
+// CXX cannot name nested C++ types, so give the paint node data a top level
+// name that the bridge can refer to.
+using QQuickItemUpdatePaintNodeData = QQuickItem::UpdatePaintNodeData;
+
+namespace bevyqml {
+
+template<typename T>
+void
+quickItemSetHasContents(T& item)
+{
+  item.setFlag(QQuickItem::ItemHasContents, true);
+}
+
+// Tell the runtime whenever the window showing the item is about to render
+// a frame, following the item into other windows
+template<typename T>
+void
+quickItemPaceFrames(T& item)
+{
+  auto connection = std::make_shared<QMetaObject::Connection>();
+  const auto follow = [&item, connection](QQuickWindow* window) {
+    QObject::disconnect(*connection);
+    if (window != nullptr) {
+      *connection =
+        QObject::connect(window, &QQuickWindow::afterAnimating, &item, [window] {
+          sceneGraphFrame(reinterpret_cast<::std::size_t>(window));
+        });
+    }
+  };
+  QObject::connect(&item, &QQuickItem::windowChanged, &item, follow);
+  follow(item.window());
+}
+
+// Tell the runtime whether the window showing the item is visible, counting
+// minimized windows as hidden
+template<typename T>
+void
+quickItemTrackWindowVisibility(T& item)
+{
+  const auto track = [&item](QQuickWindow* window) {
+    if (window == nullptr) {
+      return;
+    }
+    const auto id = reinterpret_cast<::std::size_t>(window);
+    const auto report = [id](QWindow::Visibility visibility) {
+      windowVisibilityChanged(id,
+                              visibility != QWindow::Hidden &&
+                                visibility != QWindow::Minimized);
+    };
+    QObject::connect(window, &QWindow::visibilityChanged, &item, report);
+    QObject::connect(
+      window, &QObject::destroyed, window, [id] { windowDestroyed(id); });
+    report(window->visibility());
+  };
+  QObject::connect(&item, &QQuickItem::windowChanged, &item, track);
+  track(item.window());
+}
+
+// Ask for an update when the item is resized, for apps updated on demand
+template<typename T>
+void
+quickItemRequestUpdateOnResize(T& item)
+{
+  QObject::connect(&item, &QQuickItem::widthChanged, &item, [] {
+    requestUpdate();
+  });
+  QObject::connect(&item, &QQuickItem::heightChanged, &item, [] {
+    requestUpdate();
+  });
+}
+
+// Ask for an update when the device pixel ratio of the item may change, as
+// its window moves to another screen or the scale of the screen changes
+template<typename T>
+void
+quickItemRequestUpdateOnScaleChange(T& item)
+{
+  auto windowConnection = std::make_shared<QMetaObject::Connection>();
+  auto screenConnection = std::make_shared<QMetaObject::Connection>();
+  const auto followScreen = [&item, screenConnection](QScreen* screen) {
+    QObject::disconnect(*screenConnection);
+    if (screen != nullptr) {
+      *screenConnection = QObject::connect(
+        screen, &QScreen::logicalDotsPerInchChanged, &item, [] {
+          requestUpdate();
+        });
+    }
+    requestUpdate();
+  };
+  const auto followWindow =
+    [&item, windowConnection, followScreen](QQuickWindow* window) {
+      QObject::disconnect(*windowConnection);
+      if (window != nullptr) {
+        *windowConnection = QObject::connect(
+          window, &QWindow::screenChanged, &item, followScreen);
+        followScreen(window->screen());
+      }
+    };
+  QObject::connect(&item, &QQuickItem::windowChanged, &item, followWindow);
+  followWindow(item.window());
+}
+
+// Emit sceneGraphReset on the item whenever the scene graph of its window is
+// torn down, taking the nodes and textures of the item with it. This happens
+// on the render thread, so the item hears of it on the GUI thread.
+template<typename T>
+void
+quickItemTrackSceneGraph(T& item)
+{
+  auto connection = std::make_shared<QMetaObject::Connection>();
+  const auto follow = [&item, connection](QQuickWindow* window) {
+    QObject::disconnect(*connection);
+    if (window != nullptr) {
+      *connection = QObject::connect(window,
+                                     &QQuickWindow::sceneGraphInvalidated,
+                                     &item,
+                                     [&item] { item.sceneGraphReset(); },
+                                     Qt::QueuedConnection);
+    }
+  };
+  QObject::connect(&item, &QQuickItem::windowChanged, &item, follow);
+  follow(item.window());
+}
+
+// Upload the given RGBA8 pixels into the texture node of the item, creating
+// the node if needed, and show them in rect. When pixels is empty the
+// previous texture is kept and only the geometry of the node is updated.
+template<typename T>
+QSGNode*
+quickItemUpdateTextureNode(T& item,
+                           QSGNode* oldNode,
+                           ::rust::Slice<const ::std::uint8_t> pixels,
+                           ::std::uint32_t width,
+                           ::std::uint32_t height,
+                           const QRectF& rect)
+{
+  auto* node = static_cast<QSGSimpleTextureNode*>(oldNode);
+  QQuickWindow* window = item.window();
+
+  if (!pixels.empty() && window != nullptr) {
+    // The QImage does not own the Rust buffer, so detach a copy before the
+    // slice goes out of scope.
+    const QImage image = QImage(pixels.data(),
+                                static_cast<int>(width),
+                                static_cast<int>(height),
+                                static_cast<qsizetype>(width) * 4,
+                                QImage::Format_RGBA8888)
+                           .copy();
+
+    if (node == nullptr) {
+      node = new QSGSimpleTextureNode();
+      node->setOwnsTexture(true);
+      node->setFiltering(QSGTexture::Linear);
+    }
+    node->setTexture(window->createTextureFromImage(image));
+  }
+
+  if (node != nullptr) {
+    node->setRect(rect);
+  }
+
+  return node;
+}
+
+}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <QtCore/QCoreApplication>
+#include <QtCore/QtGlobal>
+
+#ifdef Q_OS_WIN
+#include <QtCore/QAbstractNativeEventFilter>
+#include <QtCore/QByteArray>
+#include <windows.h>
+#endif
+
+namespace bevyqml {
+
+// Implemented in Rust by the raw motion bridge
+void
+rawMouseMotion(double x, double y) noexcept;
+
+#ifdef Q_OS_WIN
+// Reads the unaccelerated motion of mice from WM_INPUT, which Windows sends
+// to the window with the focus once mice are registered for raw input
+class RawMouseInputFilter : public QAbstractNativeEventFilter
+{
+public:
+#if QT_VERSION >= QT_VERSION_CHECK(6, 0, 0)
+  bool nativeEventFilter(const QByteArray& eventType,
+                         void* message,
+                         qintptr*) override
+#else
+  bool nativeEventFilter(const QByteArray& eventType,
+                         void* message,
+                         long*) override
+#endif
+  {
+    if (eventType != "windows_generic_MSG") {
+      return false;
+    }
+    const MSG* msg = static_cast<const MSG*>(message);
+    if (msg->message != WM_INPUT) {
+      return false;
+    }
+    RAWINPUT input;
+    UINT size = sizeof(input);
+    if (GetRawInputData(reinterpret_cast<HRAWINPUT>(msg->lParam),
+                        RID_INPUT,
+                        &input,
+                        &size,
+                        sizeof(RAWINPUTHEADER)) == static_cast<UINT>(-1)) {
+      return false;
+    }
+    // Tablets and remote desktops report absolute positions instead
+    if (input.header.dwType == RIM_TYPEMOUSE &&
+        (input.data.mouse.usFlags & MOUSE_MOVE_ABSOLUTE) == 0) {
+      rawMouseMotion(input.data.mouse.lLastX, input.data.mouse.lLastY);
+    }
+    // Let Windows clean up after the message
+    return false;
+  }
+};
+#endif
+
+// Start reading raw mouse motion, returns whether the platform supports it
+inline bool
+installRawMouseInput()
+{
+#ifdef Q_OS_WIN
+  static const bool installed = [] {
+    RAWINPUTDEVICE device{};
+    // HID_USAGE_PAGE_GENERIC and HID_USAGE_GENERIC_MOUSE
+    device.usUsagePage = 0x01;
+    device.usUsage = 0x02;
+    device.dwFlags = 0;
+    device.hwndTarget = nullptr;
+    if (!RegisterRawInputDevices(&device, 1, sizeof(device))) {
+      return false;
+    }
+    QCoreApplication::instance()->installNativeEventFilter(
+      new RawMouseInputFilter());
+    return true;
+  }();
+  return installed;
+#else
+  return false;
+#endif
+}
+
+}
@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstddef>
+#include <memory>
+
+#include <QtCore/QCoreApplication>
+#include <QtCore/QTimer>
+#include <QtGui/QGuiApplication>
+
+namespace bevyqml {
+
+// Implemented in Rust by the runtime bridge
+void
+sceneGraphFrame(::std::size_t window) noexcept;
+void
+requestUpdate() noexcept;
+void
+windowVisibilityChanged(::std::size_t window, bool visible) noexcept;
+void
+windowDestroyed(::std::size_t window) noexcept;
+void
+shutdown() noexcept;
+
+inline ::std::unique_ptr<QTimer>
+qtimerNew()
+{
+  return ::std::make_unique<QTimer>();
+}
+
+// False for a QCoreApplication, as in headless apps
+inline bool
+hasGuiApplication()
+{
+  return qobject_cast<QGuiApplication*>(QCoreApplication::instance()) !=
+         nullptr;
+}
+
+inline void
+coreApplicationExit(int code)
+{
+  QCoreApplication::exit(code);
+}
+
+// Tears down the Bevy apps before the windows and the graphics of Qt go away
+inline void
+shutdownOnAboutToQuit()
+{
+  static QMetaObject::Connection connection;
+  if (!connection) {
+    connection = QObject::connect(QCoreApplication::instance(),
+                                  &QCoreApplication::aboutToQuit,
+                                  [] { shutdown(); });
+  }
+}
+
+}
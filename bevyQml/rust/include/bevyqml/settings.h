@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <QtCore/QSettings>
+#include <QtCore/QString>
+
+namespace bevyqml {
+
+// The settings are those of the organization and application names set on
+// the QCoreApplication, and may be read and written from any thread
+
+// The text stored under the key, empty if there is none
+inline QString
+settingsValue(const QString& key)
+{
+  return QSettings().value(key).toString();
+}
+
+inline void
+setSettingsValue(const QString& key, const QString& value)
+{
+  QSettings settings;
+  settings.setValue(key, value);
+  settings.sync();
+}
+
+}
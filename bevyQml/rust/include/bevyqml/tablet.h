@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstdint>
+
+#include <QtCore/QCoreApplication>
+#include <QtCore/QEvent>
+#include <QtCore/QObject>
+#include <QtGui/QTabletEvent>
+#include <QtQuick/QQuickItem>
+
+namespace bevyqml {
+
+// The tool of a QTabletEvent as 0 for unknown, 1 for a pen, 2 for an eraser
+// and 3 for a puck
+inline ::std::int32_t
+tabletEventTool(const QTabletEvent& event)
+{
+#if QT_VERSION >= QT_VERSION_CHECK(6, 0, 0)
+  switch (event.pointerType()) {
+    case QPointingDevice::PointerType::Pen:
+      return 1;
+    case QPointingDevice::PointerType::Eraser:
+      return 2;
+    case QPointingDevice::PointerType::Cursor:
+      return 3;
+    default:
+      return 0;
+  }
+#else
+  switch (event.pointerType()) {
+    case QTabletEvent::Pen:
+      return 1;
+    case QTabletEvent::Eraser:
+      return 2;
+    case QTabletEvent::Cursor:
+      return 3;
+    default:
+      return 0;
+  }
+#endif
+}
+
+// Hands the tablet events of the item to it, and tells it when the stylus
+// leaves the proximity of the tablet, which Qt only reports to the
+// application. The events are left unaccepted, so Qt still turns them into
+// mouse events.
+template<typename T>
+class TabletEventForwarder : public QObject
+{
+public:
+  explicit TabletEventForwarder(T& item)
+    : QObject(&item)
+    , m_item(item)
+  {
+    item.installEventFilter(this);
+    QCoreApplication::instance()->installEventFilter(this);
+  }
+
+protected:
+  bool eventFilter(QObject* watched, QEvent* event) override
+  {
+    switch (event->type()) {
+      case QEvent::TabletMove:
+      case QEvent::TabletPress:
+      case QEvent::TabletRelease:
+        if (watched == &m_item) {
+          const auto& tablet = static_cast<const QTabletEvent&>(*event);
+#if QT_VERSION >= QT_VERSION_CHECK(6, 0, 0)
+          const QPointF position = tablet.position();
+          const qint64 toolId = tablet.pointingDevice()->uniqueId().numericId();
+#else
+          const QPointF position = tablet.posF();
+          const qint64 toolId = tablet.uniqueId();
+#endif
+          m_item.tabletEvent(static_cast<::std::int32_t>(event->type()),
+                             position,
+                             tablet.pressure(),
+                             tablet.xTilt(),
+                             tablet.yTilt(),
+                             tablet.rotation(),
+                             tabletEventTool(tablet),
+                             toolId);
+        }
+        break;
+      case QEvent::TabletLeaveProximity:
+        if (watched == QCoreApplication::instance()) {
+          m_item.tabletProximityLeft();
+        }
+        break;
+      default:
+        break;
+    }
+    return false;
+  }
+
+private:
+  T& m_item;
+};
+
+template<typename T>
+void
+quickItemForwardTabletEvents(T& item)
+{
+  // Owned by the item
+  new TabletEventForwarder<T>(item);
+}
+
+}
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstdint>
+
+#include <QtGui/QColor>
+#include <QtGui/QGuiApplication>
+#include <QtGui/QPalette>
+#include <QtGui/QStyleHints>
+
+namespace bevyqml {
+
+// The color of the active group of the application palette, the role is a
+// QPalette::ColorRole
+inline QColor
+paletteColor(::std::uint8_t role)
+{
+  return QGuiApplication::palette().color(
+    QPalette::Active, static_cast<QPalette::ColorRole>(role));
+}
+
+// Must match ColorScheme::from_qt in src/theme.rs, 0 when Qt does not know
+inline ::std::uint8_t
+colorScheme()
+{
+#if QT_VERSION >= QT_VERSION_CHECK(6, 5, 0)
+  switch (QGuiApplication::styleHints()->colorScheme()) {
+    case Qt::ColorScheme::Light:
+      return 1;
+    case Qt::ColorScheme::Dark:
+      return 2;
+    default:
+      break;
+  }
+#endif
+  return 0;
+}
+
+}
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <cstddef>
+
+#include <QtCore/QList>
+#include <QtCore/QMap>
+#include <QtCore/QString>
+#include <QtCore/QVariant>
+#include <QtGui/QQuaternion>
+#include <QtGui/QVector2D>
+#include <QtGui/QVector3D>
+#include <QtGui/QVector4D>
+#include <QtQml/QJSValue>
+
+#include "rust/cxx.h"
+
+namespace bevyqml {
+
+inline QVariant
+qvariantFromVector2D(float x, float y)
+{
+  return QVariant::fromValue(QVector2D(x, y));
+}
+
+inline QVariant
+qvariantFromVector3D(float x, float y, float z)
+{
+  return QVariant::fromValue(QVector3D(x, y, z));
+}
+
+inline QVariant
+qvariantFromVector4D(float x, float y, float z, float w)
+{
+  return QVariant::fromValue(QVector4D(x, y, z, w));
+}
+
+inline QVariant
+qvariantFromQuaternion(float x, float y, float z, float w)
+{
+  return QVariant::fromValue(QQuaternion(w, x, y, z));
+}
+
+// Copy the components of a vector or quaternion into out, x first and the
+// scalar of a quaternion last. Returns how many there are, 0 for anything
+// else.
+inline ::std::size_t
+qvariantToFloats(const QVariant& variant, ::rust::Slice<float> out)
+{
+  QVector4D components;
+  ::std::size_t count = 0;
+  switch (variant.userType()) {
+    case QMetaType::QVector2D:
+      components = QVector4D(variant.value<QVector2D>());
+      count = 2;
+      break;
+    case QMetaType::QVector3D:
+      components = QVector4D(variant.value<QVector3D>());
+      count = 3;
+      break;
+    case QMetaType::QVector4D:
+      components = variant.value<QVector4D>();
+      count = 4;
+      break;
+    case QMetaType::QQuaternion:
+      components = variant.value<QQuaternion>().toVector4D();
+      count = 4;
+      break;
+    default:
+      return 0;
+  }
+  for (::std::size_t i = 0; i < count && i < out.size(); ++i) {
+    out[i] = components[static_cast<int>(i)];
+  }
+  return count;
+}
+
+inline QVariant
+qvariantFromMap(const QMap<QString, QVariant>& map)
+{
+  return QVariant(map);
+}
+
+inline QVariant
+qvariantFromList(const QList<QVariant>& list)
+{
+  return QVariant(list);
+}
+
+// Whether the variant is null, undefined or missing altogether
+inline bool
+qvariantIsNull(const QVariant& variant)
+{
+  if (variant.userType() == qMetaTypeId<QJSValue>()) {
+    const QJSValue value = variant.value<QJSValue>();
+    return value.isNull() || value.isUndefined();
+  }
+  return !variant.isValid() || variant.isNull();
+}
+
+// Whether the variant holds a map or a JavaScript object other than an array
+inline bool
+qvariantIsMap(const QVariant& variant)
+{
+  if (variant.userType() == qMetaTypeId<QJSValue>()) {
+    const QJSValue value = variant.value<QJSValue>();
+    return value.isObject() && !value.isArray();
+  }
+  return variant.userType() == QMetaType::QVariantMap ||
+         variant.userType() == QMetaType::QVariantHash;
+}
+
+// Whether the variant holds a list or a JavaScript array
+inline bool
+qvariantIsList(const QVariant& variant)
+{
+  if (variant.userType() == qMetaTypeId<QJSValue>()) {
+    return variant.value<QJSValue>().isArray();
+  }
+  return variant.userType() == QMetaType::QVariantList ||
+         variant.userType() == QMetaType::QStringList;
+}
+
+// The entries of a variant holding a map or a JavaScript object, empty for
+// anything else
+inline QMap<QString, QVariant>
+qvariantToMap(const QVariant& variant)
+{
+  if (variant.userType() == qMetaTypeId<QJSValue>()) {
+    return variant.value<QJSValue>().toVariant().toMap();
+  }
+  return variant.toMap();
+}
+
+// The elements of a variant holding a list or a JavaScript array, empty for
+// anything else
+inline QList<QVariant>
+qvariantToList(const QVariant& variant)
+{
+  if (variant.userType() == qMetaTypeId<QJSValue>()) {
+    return variant.value<QJSValue>().toVariant().toList();
+  }
+  return variant.toList();
+}
+
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#pragma once
+
+#include <memory>
+
+#include <QtCore/QPointer>
+#include <QtCore/QString>
+#include <QtCore/QUrl>
+
+class QQmlApplicationEngine;
+class QQuickWindow;
+
+namespace bevyqml {
+
+// Loads a QML file in an engine of its own and shows it as a top level
+// window. The root object may be a Window, or an Item which is then shown
+// in a window of its own.
+//
+// The QML sees the name of the window as bevyWindow and the name of the
+// world it should show as bevyWorld, which is empty for the main world:
+//   BevyQuickItem { world: bevyWorld }
+class QmlWindowHost
+{
+public:
+  QmlWindowHost(const QUrl& source,
+                const QString& name,
+                const QString& world,
+                const QString& title);
+  ~QmlWindowHost();
+
+  // Empty until loading the QML failed
+  QString errorString() const;
+
+  // Closing a window hides it, which is how closing by the user is noticed
+  bool isVisible() const;
+
+  void setTitle(const QString& title);
+
+  void raise();
+
+private:
+  std::unique_ptr<QQmlApplicationEngine> m_engine;
+  // Only set for Item roots, Window roots belong to the engine
+  std::unique_ptr<QQuickWindow> m_ownedWindow;
+  QPointer<QQuickWindow> m_window;
+  QString m_error;
+};
+
+::std::unique_ptr<QmlWindowHost>
+qmlWindowHostNew(const QUrl& source,
+                 const QString& name,
+                 const QString& world,
+                 const QString& title);
+
+}
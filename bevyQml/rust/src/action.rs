@@ -0,0 +1,536 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Actions which are defined once by the app and triggered from a Qt
+//! `Shortcut` or menu, or by the keys Bevy receives from a `BevyQuickItem`.
+//!
+//! An action has a name, a text for menus, a default shortcut and an
+//! optional condition on the world for when it is enabled:
+//!
+//! ```ignore
+//! fn register_actions(mut actions: ResMut<QmlActions>) {
+//!     actions.register(QmlAction::new("edit.delete", "Delete").with_shortcut("Del"));
+//!     actions.register(
+//!         QmlAction::new("edit.focus", "Focus Selection")
+//!             .with_shortcut("F")
+//!             .enabled_if(|world| !world.resource::<Selection>().is_empty()),
+//!     );
+//! }
+//!
+//! fn delete_selection(mut triggered: EventReader<ActionTriggered>) {
+//!     for action in triggered.read().filter(|action| action.name == "edit.delete") {
+//!         // ...
+//!     }
+//! }
+//! ```
+//!
+//! Shortcuts are written as in QKeySequence::PortableText, such as
+//! `Ctrl+Shift+S`, so QML can hand them to a `Shortcut` as they are. An
+//! `ActionModel` lists the actions for a dialog customizing the keys, and
+//! the shortcuts picked there are kept in QSettings, see [crate::settings].
+//!
+//! Keys a Qt `Shortcut` takes never reach Bevy, so each press triggers an
+//! action once whichever side sees it. Bevy only matches shortcuts of a
+//! single key with modifiers, sequences like `Ctrl+K, Ctrl+C` need a Qt
+//! `Shortcut`.
+
+use std::{collections::VecDeque, sync::Arc};
+
+use bevy::{input::InputSystem, prelude::*, utils::HashMap};
+
+use crate::{input::keyboard::key_code_from_qt, settings};
+
+/// Where the custom shortcuts are stored, see [crate::settings]
+const SHORTCUTS_KEY: &str = "shortcuts";
+
+/// How many triggers are kept for the models to pick up
+const TRIGGER_CAPACITY: usize = 64;
+
+/// Whether an action can be triggered in the current state of the world
+pub type ActionCondition = Arc<dyn Fn(&World) -> bool + Send + Sync>;
+
+/// An action of the app, see [QmlActions::register]
+#[derive(Clone)]
+pub struct QmlAction {
+    /// Identifies the action, e.g. `file.save`
+    pub name: String,
+    /// What menus and the shortcut dialog show, e.g. `Save`
+    pub text: String,
+    /// The shortcut unless one was picked, empty for none
+    pub default_shortcut: String,
+    /// The action is always enabled without a condition
+    pub enabled: Option<ActionCondition>,
+}
+
+impl QmlAction {
+    pub fn new(name: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            text: text.into(),
+            default_shortcut: String::new(),
+            enabled: None,
+        }
+    }
+
+    pub fn with_shortcut(mut self, shortcut: impl Into<String>) -> Self {
+        self.default_shortcut = shortcut.into();
+        self
+    }
+
+    pub fn enabled_if(
+        mut self,
+        condition: impl Fn(&World) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.enabled = Some(Arc::new(condition));
+        self
+    }
+}
+
+/// Where an action was triggered from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActionSource {
+    /// A `Shortcut`, menu or button calling `ActionModel.trigger`
+    Qml,
+    /// A shortcut pressed while a `BevyQuickItem` had the keyboard focus
+    Input,
+}
+
+/// Sent when an enabled action is triggered
+#[derive(Event, Clone, Debug)]
+pub struct ActionTriggered {
+    pub name: String,
+    pub source: ActionSource,
+}
+
+/// An action as the models show it
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionState {
+    pub name: String,
+    pub text: String,
+    /// The shortcut in use
+    pub shortcut: String,
+    pub default_shortcut: String,
+    pub enabled: bool,
+}
+
+struct RegisteredAction {
+    action: QmlAction,
+    state: ActionState,
+    chord: Option<KeyChord>,
+}
+
+/// The actions of the app and the shortcuts picked for them
+#[derive(Resource)]
+pub struct QmlActions {
+    actions: Vec<RegisteredAction>,
+    /// The shortcuts picked in place of the defaults, by action name
+    custom: HashMap<String, String>,
+    /// Trigger actions from the keys Bevy receives
+    pub match_input: bool,
+    /// Bumped whenever the actions or their states change
+    revision: u64,
+    /// The latest triggers with their sequence numbers, oldest first
+    triggers: VecDeque<(u64, ActionTriggered)>,
+    sequence: u64,
+}
+
+impl Default for QmlActions {
+    fn default() -> Self {
+        Self {
+            actions: Vec::new(),
+            custom: HashMap::default(),
+            match_input: true,
+            revision: 0,
+            triggers: VecDeque::new(),
+            sequence: 0,
+        }
+    }
+}
+
+impl QmlActions {
+    /// Add an action, or replace the one with the same name
+    pub fn register(&mut self, action: QmlAction) {
+        let shortcut = self
+            .custom
+            .get(&action.name)
+            .cloned()
+            .unwrap_or_else(|| action.default_shortcut.clone());
+        let registered = RegisteredAction {
+            state: ActionState {
+                name: action.name.clone(),
+                text: action.text.clone(),
+                shortcut: shortcut.clone(),
+                default_shortcut: action.default_shortcut.clone(),
+                enabled: true,
+            },
+            chord: KeyChord::parse(&shortcut),
+            action,
+        };
+        match self.position(&registered.state.name) {
+            Some(index) => self.actions[index] = registered,
+            None => self.actions.push(registered),
+        }
+        self.revision += 1;
+    }
+
+    pub fn unregister(&mut self, name: &str) {
+        if let Some(index) = self.position(name) {
+            self.actions.remove(index);
+            self.revision += 1;
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ActionState> {
+        self.position(name).map(|index| &self.actions[index].state)
+    }
+
+    /// The actions in the order they were registered
+    pub fn iter(&self) -> impl Iterator<Item = &ActionState> {
+        self.actions.iter().map(|action| &action.state)
+    }
+
+    /// Changes whenever an action, its shortcut or whether it is enabled
+    /// changes
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Use another shortcut for the action, an empty one for none, and
+    /// store it in the settings
+    pub fn set_shortcut(&mut self, name: &str, shortcut: &str) -> Result<(), String> {
+        let index = self
+            .position(name)
+            .ok_or_else(|| format!("There is no action {name:?}"))?;
+        let shortcut = shortcut.trim().to_owned();
+        let action = &mut self.actions[index];
+        if shortcut == action.state.default_shortcut {
+            self.custom.remove(name);
+        } else {
+            self.custom.insert(name.to_owned(), shortcut.clone());
+        }
+        action.chord = KeyChord::parse(&shortcut);
+        action.state.shortcut = shortcut;
+        self.revision += 1;
+        settings::save(SHORTCUTS_KEY, &self.custom);
+        Ok(())
+    }
+
+    /// Go back to the default shortcut of the action
+    pub fn reset_shortcut(&mut self, name: &str) -> Result<(), String> {
+        let default = self
+            .get(name)
+            .map(|state| state.default_shortcut.clone())
+            .ok_or_else(|| format!("There is no action {name:?}"))?;
+        self.set_shortcut(name, &default)
+    }
+
+    /// Go back to the default shortcuts of every action
+    pub fn reset_all_shortcuts(&mut self) {
+        self.custom.clear();
+        for action in &mut self.actions {
+            action.state.shortcut = action.action.default_shortcut.clone();
+            action.chord = KeyChord::parse(&action.state.shortcut);
+        }
+        self.revision += 1;
+        settings::save(SHORTCUTS_KEY, &self.custom);
+    }
+
+    /// The triggers after the sequence number, with their sequence numbers
+    ///
+    /// Only the latest triggers are kept, so a reader which falls far
+    /// behind misses some.
+    pub fn triggers_since(&self, sequence: u64) -> impl Iterator<Item = &(u64, ActionTriggered)> {
+        self.triggers
+            .iter()
+            .skip_while(move |(triggered, _)| *triggered <= sequence)
+    }
+
+    /// The sequence number of the latest trigger
+    pub fn last_trigger(&self) -> u64 {
+        self.sequence
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.actions
+            .iter()
+            .position(|action| action.state.name == name)
+    }
+
+    fn record(&mut self, triggered: ActionTriggered) {
+        self.sequence += 1;
+        self.triggers.push_back((self.sequence, triggered));
+        if self.triggers.len() > TRIGGER_CAPACITY {
+            self.triggers.pop_front();
+        }
+    }
+}
+
+/// Trigger the action if it is enabled, returns whether it was
+pub fn trigger(world: &mut World, name: &str, source: ActionSource) -> Result<bool, String> {
+    let condition = {
+        let actions = world.resource::<QmlActions>();
+        let index = actions
+            .position(name)
+            .ok_or_else(|| format!("There is no action {name:?}"))?;
+        actions.actions[index].action.enabled.clone()
+    };
+    if condition.is_some_and(|enabled| !enabled(world)) {
+        return Ok(false);
+    }
+    let triggered = ActionTriggered {
+        name: name.to_owned(),
+        source,
+    };
+    world.resource_mut::<QmlActions>().record(triggered.clone());
+    world.send_event(triggered);
+    Ok(true)
+}
+
+/// A single key with modifiers, which Bevy can match
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct KeyChord {
+    key: KeyCode,
+    ctrl: bool,
+    shift: bool,
+    alt: bool,
+    meta: bool,
+}
+
+impl KeyChord {
+    /// Parse a shortcut in QKeySequence::PortableText, [None] for sequences
+    /// of several chords and keys without a [KeyCode]
+    fn parse(shortcut: &str) -> Option<Self> {
+        if shortcut.is_empty() || shortcut.contains(", ") {
+            return None;
+        }
+        // A trailing `+` is the plus key, as in `Ctrl++`
+        let (modifiers, key) = match shortcut.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None => shortcut.rsplit_once('+').unwrap_or(("", shortcut)),
+        };
+        let mut chord = Self {
+            key: key_code_from_qt(qt_key(key)?)?,
+            ctrl: false,
+            shift: false,
+            alt: false,
+            meta: false,
+        };
+        for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty()) {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => chord.ctrl = true,
+                "shift" => chord.shift = true,
+                "alt" => chord.alt = true,
+                "meta" => chord.meta = true,
+                _ => return None,
+            }
+        }
+        Some(chord)
+    }
+
+    fn just_pressed(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        let held = |left, right| keys.any_pressed([left, right]);
+        // Qt means the command key by Ctrl on macOS, and the control key by
+        // Meta
+        let (ctrl, meta) = if cfg!(target_os = "macos") {
+            (
+                held(KeyCode::SuperLeft, KeyCode::SuperRight),
+                held(KeyCode::ControlLeft, KeyCode::ControlRight),
+            )
+        } else {
+            (
+                held(KeyCode::ControlLeft, KeyCode::ControlRight),
+                held(KeyCode::SuperLeft, KeyCode::SuperRight),
+            )
+        };
+        keys.just_pressed(self.key)
+            && ctrl == self.ctrl
+            && meta == self.meta
+            && held(KeyCode::ShiftLeft, KeyCode::ShiftRight) == self.shift
+            && held(KeyCode::AltLeft, KeyCode::AltRight) == self.alt
+    }
+}
+
+/// The Qt::Key of a key in QKeySequence::PortableText
+fn qt_key(name: &str) -> Option<i32> {
+    let mut chars = name.chars();
+    if let (Some(char), None) = (chars.next(), chars.next()) {
+        // Letters, digits and punctuation are their upper case character
+        let char = char.to_ascii_uppercase();
+        return char.is_ascii_graphic().then_some(char as i32);
+    }
+    if let Some(number) = name.strip_prefix('F').and_then(|n| n.parse::<i32>().ok()) {
+        return (1..=35)
+            .contains(&number)
+            .then_some(0x0100_0030 + number - 1);
+    }
+    Some(match name {
+        "Space" => 0x20,
+        "Esc" => 0x0100_0000,
+        "Tab" => 0x0100_0001,
+        "Backspace" => 0x0100_0003,
+        "Return" => 0x0100_0004,
+        "Enter" => 0x0100_0005,
+        "Ins" => 0x0100_0006,
+        "Del" => 0x0100_0007,
+        "Pause" => 0x0100_0008,
+        "Print" => 0x0100_0009,
+        "Home" => 0x0100_0010,
+        "End" => 0x0100_0011,
+        "Left" => 0x0100_0012,
+        "Up" => 0x0100_0013,
+        "Right" => 0x0100_0014,
+        "Down" => 0x0100_0015,
+        "PgUp" => 0x0100_0016,
+        "PgDown" => 0x0100_0017,
+        _ => return None,
+    })
+}
+
+pub struct QmlActionPlugin;
+
+impl Plugin for QmlActionPlugin {
+    fn build(&self, app: &mut App) {
+        let custom = settings::load(SHORTCUTS_KEY).unwrap_or_default();
+        app.add_event::<ActionTriggered>()
+            .insert_resource(QmlActions {
+                custom,
+                ..default()
+            })
+            .add_systems(PreUpdate, trigger_from_input.after(InputSystem))
+            .add_systems(Last, update_enabled);
+    }
+}
+
+fn trigger_from_input(
+    keys: Option<Res<ButtonInput<KeyCode>>>,
+    mut actions: ResMut<QmlActions>,
+    mut events: EventWriter<ActionTriggered>,
+) {
+    let Some(keys) = keys.filter(|keys| keys.get_just_pressed().next().is_some()) else {
+        return;
+    };
+    if !actions.match_input {
+        return;
+    }
+    let pressed: Vec<String> = actions
+        .actions
+        .iter()
+        .filter(|action| action.state.enabled)
+        .filter(|action| action.chord.is_some_and(|chord| chord.just_pressed(&keys)))
+        .map(|action| action.state.name.clone())
+        .collect();
+    for name in pressed {
+        let triggered = ActionTriggered {
+            name,
+            source: ActionSource::Input,
+        };
+        actions.record(triggered.clone());
+        events.send(triggered);
+    }
+}
+
+/// Check the conditions of the actions after every update
+fn update_enabled(world: &mut World) {
+    world.resource_scope(|world, mut actions: Mut<QmlActions>| {
+        let mut changed = false;
+        for action in &mut actions.bypass_change_detection().actions {
+            let enabled = action
+                .action
+                .enabled
+                .as_ref()
+                .map_or(true, |enabled| enabled(world));
+            if action.state.enabled != enabled {
+                action.state.enabled = enabled;
+                changed = true;
+            }
+        }
+        if changed {
+            actions.revision += 1;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(key: KeyCode) -> KeyChord {
+        KeyChord {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+            meta: false,
+        }
+    }
+
+    #[test]
+    fn parses_chords() {
+        assert_eq!(KeyChord::parse("S"), Some(chord(KeyCode::KeyS)));
+        assert_eq!(
+            KeyChord::parse("Ctrl+Shift+S"),
+            Some(KeyChord {
+                ctrl: true,
+                shift: true,
+                ..chord(KeyCode::KeyS)
+            })
+        );
+        assert_eq!(
+            KeyChord::parse("Alt+Meta+F5"),
+            Some(KeyChord {
+                alt: true,
+                meta: true,
+                ..chord(KeyCode::F5)
+            })
+        );
+        assert_eq!(
+            KeyChord::parse("Ctrl+Del"),
+            Some(KeyChord {
+                ctrl: true,
+                ..chord(KeyCode::Delete)
+            })
+        );
+        assert_eq!(
+            KeyChord::parse("Ctrl++"),
+            Some(KeyChord {
+                ctrl: true,
+                ..chord(KeyCode::Equal)
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_case_and_modifier_order() {
+        let expected = KeyChord::parse("Ctrl+Shift+S");
+        assert_eq!(KeyChord::parse("Shift+Ctrl+S"), expected);
+        assert_eq!(KeyChord::parse("ctrl+SHIFT+s"), expected);
+        assert_eq!(KeyChord::parse("Ctrl+Ctrl+Shift+S"), expected);
+    }
+
+    #[test]
+    fn rejects_other_shortcuts() {
+        assert_eq!(KeyChord::parse(""), None);
+        assert_eq!(KeyChord::parse("Ctrl+"), None);
+        assert_eq!(KeyChord::parse("Ctrl+Shift+"), None);
+        assert_eq!(KeyChord::parse("Ctrl+Foo"), None);
+        assert_eq!(KeyChord::parse("Hyper+S"), None);
+        assert_eq!(KeyChord::parse("Ctrl+F36"), None);
+        // Sequences of several chords
+        assert_eq!(KeyChord::parse("Ctrl+K, Ctrl+C"), None);
+    }
+
+    #[test]
+    fn qt_keys() {
+        assert_eq!(qt_key("a"), Some(0x41));
+        assert_eq!(qt_key("A"), Some(0x41));
+        assert_eq!(qt_key("7"), Some(0x37));
+        assert_eq!(qt_key("/"), Some(0x2f));
+        assert_eq!(qt_key("F1"), Some(0x0100_0030));
+        assert_eq!(qt_key("F35"), Some(0x0100_0052));
+        assert_eq!(qt_key("Esc"), Some(0x0100_0000));
+        assert_eq!(qt_key("PgDown"), Some(0x0100_0017));
+        assert_eq!(qt_key("F0"), None);
+        assert_eq!(qt_key("esc"), None);
+        assert_eq!(qt_key(" "), None);
+        assert_eq!(qt_key(""), None);
+    }
+}
@@ -0,0 +1,235 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Playing the animation clips of an entity from QML, see
+//! [crate::cxxqt_bevy_animation_player] for the QML side.
+//!
+//! The clips are those in the [AnimationGraph] of the [AnimationPlayer] of
+//! the entity, or of its first descendant with one, which is where glTF
+//! scenes put it. Clips are named after the glTF animation they were loaded
+//! from, and otherwise after their node in the graph.
+
+use std::time::Duration;
+
+use bevy::{
+    animation::{ActiveAnimation, AnimationTransitions, RepeatAnimation},
+    gltf::Gltf,
+    prelude::*,
+    utils::HashMap,
+};
+
+/// A clip the animation player of an entity can play
+#[derive(Clone, Debug, PartialEq)]
+pub struct QmlAnimationClip {
+    pub name: String,
+    pub node: AnimationNodeIndex,
+    /// The length of the clip in seconds
+    pub duration: f32,
+}
+
+/// How [play] starts a clip
+#[derive(Clone, Copy, Debug)]
+pub struct PlayOptions {
+    /// Start over at the end instead of stopping
+    pub repeat: bool,
+    /// How fast the clip plays, where 1 is its own pace
+    pub speed: f32,
+    /// How long the clip takes to replace the animations playing before
+    pub crossfade: Duration,
+}
+
+impl Default for PlayOptions {
+    fn default() -> Self {
+        Self {
+            repeat: false,
+            speed: 1.0,
+            crossfade: Duration::ZERO,
+        }
+    }
+}
+
+/// What the animation player of an entity is doing
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlaybackStatus {
+    /// The clip which was started last, if it is still playing
+    pub clip: Option<String>,
+    pub playing: bool,
+    /// Where the clip is, in seconds
+    pub playhead: f32,
+    /// The length of the clip in seconds
+    pub duration: f32,
+}
+
+/// The entity with the animation player of an entity, which is the entity
+/// itself or its first descendant with one
+pub fn find_player(world: &World, entity: Entity) -> Option<Entity> {
+    if world.get::<AnimationPlayer>(entity).is_some() {
+        return Some(entity);
+    }
+    world
+        .get::<Children>(entity)?
+        .iter()
+        .find_map(|child| find_player(world, *child))
+}
+
+/// The clips the animation player of an entity can play, in the order of
+/// their nodes
+pub fn clips(world: &World, entity: Entity) -> Vec<QmlAnimationClip> {
+    let Some(graph) = find_player(world, entity)
+        .and_then(|player| world.get::<Handle<AnimationGraph>>(player))
+        .and_then(|graph| world.get_resource::<Assets<AnimationGraph>>()?.get(graph))
+    else {
+        return Vec::new();
+    };
+    let clip_assets = world.get_resource::<Assets<AnimationClip>>();
+
+    // Name the clips after the glTF animations they were loaded from
+    let mut names = HashMap::new();
+    if let Some(gltfs) = world.get_resource::<Assets<Gltf>>() {
+        for (_, gltf) in gltfs.iter() {
+            for (name, clip) in &gltf.named_animations {
+                names.insert(clip.id(), name.to_string());
+            }
+        }
+    }
+
+    graph
+        .graph
+        .node_indices()
+        .filter_map(|node| {
+            let clip = graph.get(node)?.clip.as_ref()?;
+            Some(QmlAnimationClip {
+                name: names
+                    .get(&clip.id())
+                    .cloned()
+                    .unwrap_or_else(|| format!("Animation{}", node.index())),
+                node,
+                duration: clip_assets
+                    .and_then(|assets| assets.get(clip))
+                    .map_or(0.0, AnimationClip::duration),
+            })
+        })
+        .collect()
+}
+
+/// Play the clip of an entity with the given name
+///
+/// Clips playing before fade out over the crossfade, or stop straight away
+/// without one. Returns false if the entity has no such clip.
+pub fn play(world: &mut World, entity: Entity, name: &str, options: PlayOptions) -> bool {
+    let Some(clip) = clips(world, entity)
+        .into_iter()
+        .find(|clip| clip.name == name)
+    else {
+        return false;
+    };
+    let Some(player) = find_player(world, entity) else {
+        return false;
+    };
+
+    // Transitions keep track of the main animation, so they stay on the
+    // player afterwards
+    let mut transitions = world
+        .entity_mut(player)
+        .take::<AnimationTransitions>()
+        .unwrap_or_else(AnimationTransitions::new);
+    if let Some(mut animation_player) = world.get_mut::<AnimationPlayer>(player) {
+        animation_player.resume_all();
+        let active = transitions.play(&mut animation_player, clip.node, options.crossfade);
+        active.set_speed(options.speed);
+        active.set_repeat(if options.repeat {
+            RepeatAnimation::Forever
+        } else {
+            RepeatAnimation::Never
+        });
+    }
+    world.entity_mut(player).insert(transitions);
+    true
+}
+
+/// Pause or resume every animation of the animation player of an entity
+pub fn set_paused(world: &mut World, entity: Entity, paused: bool) {
+    let Some(mut player) =
+        find_player(world, entity).and_then(|player| world.get_mut::<AnimationPlayer>(player))
+    else {
+        return;
+    };
+    if paused {
+        player.pause_all();
+    } else {
+        player.resume_all();
+    }
+}
+
+/// Stop every animation of the animation player of an entity
+pub fn stop(world: &mut World, entity: Entity) {
+    if let Some(mut player) =
+        find_player(world, entity).and_then(|player| world.get_mut::<AnimationPlayer>(player))
+    {
+        player.stop_all();
+    }
+}
+
+/// Move the clip which was started last to a time, in seconds
+pub fn seek(world: &mut World, entity: Entity, time: f32) {
+    let Some(player) = find_player(world, entity) else {
+        return;
+    };
+    let node = main_node(world, player);
+    let Some(mut animation_player) = world.get_mut::<AnimationPlayer>(player) else {
+        return;
+    };
+    if let Some(active) = node.and_then(|node| animation_player.animation_mut(node)) {
+        active.seek_to(time.max(0.0));
+    }
+}
+
+/// What the animation player of an entity is doing
+pub fn status(world: &World, entity: Entity) -> PlaybackStatus {
+    let Some(player) = find_player(world, entity) else {
+        return PlaybackStatus::default();
+    };
+    let node = main_node(world, player);
+    let Some((node, active)) = node.and_then(|node| {
+        let active = world.get::<AnimationPlayer>(player)?.animation(node)?;
+        Some((node, active))
+    }) else {
+        return PlaybackStatus::default();
+    };
+    let paused = world
+        .get::<AnimationPlayer>(player)
+        .is_some_and(AnimationPlayer::all_paused);
+    let clip = clips(world, entity)
+        .into_iter()
+        .find(|clip| clip.node == node);
+
+    PlaybackStatus {
+        clip: clip.as_ref().map(|clip| clip.name.clone()),
+        playing: !paused && !active.is_paused() && !active.is_finished(),
+        playhead: playhead(active, clip.as_ref().map_or(0.0, |clip| clip.duration)),
+        duration: clip.map_or(0.0, |clip| clip.duration),
+    }
+}
+
+/// The animation started last on the player, or any playing one
+fn main_node(world: &World, player: Entity) -> Option<AnimationNodeIndex> {
+    world
+        .get::<AnimationTransitions>(player)
+        .and_then(AnimationTransitions::get_main_animation)
+        .or_else(|| {
+            world
+                .get::<AnimationPlayer>(player)?
+                .playing_animations()
+                .next()
+                .map(|(node, _)| *node)
+        })
+}
+
+/// The time within the clip, as repeating clips keep counting up
+fn playhead(active: &ActiveAnimation, duration: f32) -> f32 {
+    let time = active.seek_time();
+    if duration > 0.0 && active.repeat_mode() != RepeatAnimation::Never {
+        time.rem_euclid(duration)
+    } else {
+        time
+    }
+}
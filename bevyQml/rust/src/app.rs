@@ -0,0 +1,237 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Launches the Qt application, the QML engine and the Bevy [App] from a
+//! Rust `main()`, instead of the C++ one in `cpp/main.cpp`.
+//!
+//! ```ignore
+//! fn main() {
+//!     let code = BevyQmlApp::new()
+//!         .qml_main("qrc:/qt/qml/BevyQml/qml/main.qml")
+//!         .import_path("qml")
+//!         .register_type::<CurveDemoSettings>()
+//!         .add_plugins(CurveDemoPlugin)
+//!         .run();
+//!     std::process::exit(code);
+//! }
+//! ```
+//!
+//! The QML types of the crate register themselves with `#[qml_element]`, so
+//! [BevyQmlApp::register_type] registers types with the Bevy app instead,
+//! for the reflection based elements such as `BevyComponent` and
+//! `BevyResource`. As the app is running before the QML loads, the QML
+//! should not create a `MyBevyApp` as well.
+//!
+//! With [BevyQmlApp::hot_reload] the QML is loaded from its source directory
+//! and loaded again whenever a QML file in there changes. Only the QML
+//! objects are created anew, the Bevy app keeps running with its world as it
+//! is, and the new bridge objects find their entities and resources in it
+//! just as the previous ones did. Entities spawned by the QML itself, such
+//! as with `BevyCommands`, are spawned again.
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_app")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qurl.h");
+        /// An alias to the QUrl type
+        type QUrl = cxx_qt_lib::QUrl;
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/app.h");
+        type QApplication;
+        type QQmlApplicationEngine;
+
+        #[doc(hidden)]
+        #[rust_name = "application_new"]
+        fn applicationNew(args: &Vec<String>) -> UniquePtr<QApplication>;
+
+        #[doc(hidden)]
+        #[rust_name = "application_exec"]
+        fn applicationExec() -> i32;
+
+        #[doc(hidden)]
+        #[rust_name = "qml_engine_new"]
+        fn qmlEngineNew() -> UniquePtr<QQmlApplicationEngine>;
+
+        #[doc(hidden)]
+        #[rust_name = "qml_engine_add_import_path"]
+        fn qmlEngineAddImportPath(engine: Pin<&mut QQmlApplicationEngine>, path: &QString);
+
+        #[doc(hidden)]
+        #[rust_name = "qml_engine_load"]
+        fn qmlEngineLoad(engine: Pin<&mut QQmlApplicationEngine>, url: &QUrl) -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "qml_engine_load_watched"]
+        fn qmlEngineLoadWatched(
+            engine: Pin<&mut QQmlApplicationEngine>,
+            directory: &QString,
+            file_name: &QString,
+        ) -> bool;
+    }
+}
+
+use bevy::{app::Plugins, prelude::*, reflect::GetTypeRegistration};
+use cxx_qt_lib::{QString, QUrl};
+
+#[cfg(target_os = "linux")]
+use crate::plugin::bevy_qml_opengl_plugins;
+use crate::{
+    plugin::{bevy_qml_plugins_with_adapter, BevyQmlPlugin},
+    render::adapter::AdapterSelection,
+};
+
+/// Something to do with the [App] before it runs
+type AppHook = Box<dyn FnOnce(&mut App)>;
+
+/// Builds and runs a Qt application showing a QML file, next to a Bevy [App]
+/// with [crate::plugin::bevy_qml_default_plugins] and [BevyQmlPlugin]
+///
+/// The hooks run in the order they were added, after the plugins of this
+/// crate.
+#[derive(Default)]
+#[must_use]
+pub struct BevyQmlApp {
+    qml_main: Option<String>,
+    hot_reload: Option<String>,
+    import_paths: Vec<String>,
+    plugin: BevyQmlPlugin,
+    hooks: Vec<AppHook>,
+    adapter: AdapterSelection,
+    #[cfg(target_os = "linux")]
+    opengl: bool,
+}
+
+impl BevyQmlApp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The URL of the QML file the engine loads, such as `qrc:/main.qml`
+    pub fn qml_main(mut self, url: impl Into<String>) -> Self {
+        self.qml_main = Some(url.into());
+        self
+    }
+
+    /// Load the QML main file from the directory rather than from the
+    /// resources, and reload it whenever a QML file in the directory changes
+    ///
+    /// The file name is the last part of [BevyQmlApp::qml_main]. This is
+    /// meant for development, e.g. only in debug builds:
+    ///
+    /// ```ignore
+    /// if cfg!(debug_assertions) {
+    ///     app = app.hot_reload(concat!(env!("CARGO_MANIFEST_DIR"), "/../qml"));
+    /// }
+    /// ```
+    pub fn hot_reload(mut self, directory: impl Into<String>) -> Self {
+        self.hot_reload = Some(directory.into());
+        self
+    }
+
+    /// A directory the engine looks for QML modules in
+    pub fn import_path(mut self, path: impl Into<String>) -> Self {
+        self.import_paths.push(path.into());
+        self
+    }
+
+    /// Register a type for reflection with the Bevy app
+    pub fn register_type<T: GetTypeRegistration>(self) -> Self {
+        self.with_app(|app| {
+            app.register_type::<T>();
+        })
+    }
+
+    /// Add Bevy plugins to the app
+    pub fn add_plugins<M>(self, plugins: impl Plugins<M> + 'static) -> Self {
+        self.with_app(|app| {
+            app.add_plugins(plugins);
+        })
+    }
+
+    /// Change the app in any other way before it runs
+    pub fn with_app(mut self, hook: impl FnOnce(&mut App) + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Render with OpenGL in a context shared with the scene graph, rather
+    /// than with the backend wgpu prefers, see [bevy_qml_opengl_plugins]
+    #[cfg(target_os = "linux")]
+    pub fn opengl(mut self) -> Self {
+        self.opengl = true;
+        self
+    }
+
+    /// Render with the GPU adapter of the selection, which the environment
+    /// can still override, see [AdapterSelection::with_env]
+    pub fn adapter(mut self, selection: AdapterSelection) -> Self {
+        self.adapter = selection;
+        self
+    }
+
+    /// Use these settings for the [BevyQmlPlugin]
+    pub fn plugin(mut self, plugin: BevyQmlPlugin) -> Self {
+        self.plugin = plugin;
+        self
+    }
+
+    /// Create the Qt application, run the Bevy app, load the QML and run
+    /// the Qt event loop until it quits
+    ///
+    /// Returns the exit code of the event loop, or -1 if the QML did not
+    /// load.
+    pub fn run(self) -> i32 {
+        let args: Vec<String> = std::env::args().collect();
+        // Dropped after the engine, in the reverse order of creation
+        let _application = ffi::application_new(&args);
+
+        let adapter = self.adapter.with_env();
+        #[cfg(target_os = "linux")]
+        let plugins = if self.opengl {
+            bevy_qml_opengl_plugins()
+        } else {
+            bevy_qml_plugins_with_adapter(&adapter)
+        };
+        #[cfg(not(target_os = "linux"))]
+        let plugins = bevy_qml_plugins_with_adapter(&adapter);
+
+        let mut app = App::new();
+        app.add_plugins((plugins, self.plugin));
+        for hook in self.hooks {
+            hook(&mut app);
+        }
+        app.run();
+
+        // Logged once the app forwards its log output to Qt
+        let Some(qml_main) = self.qml_main else {
+            error!("BevyQmlApp needs a QML file to load, set it with qml_main");
+            return -1;
+        };
+        let mut engine = ffi::qml_engine_new();
+        for path in &self.import_paths {
+            ffi::qml_engine_add_import_path(engine.pin_mut(), &QString::from(path.as_str()));
+        }
+        let loaded = match &self.hot_reload {
+            Some(directory) => {
+                let file_name = qml_main.rsplit('/').next().unwrap_or_default();
+                ffi::qml_engine_load_watched(
+                    engine.pin_mut(),
+                    &QString::from(directory.as_str()),
+                    &QString::from(file_name),
+                )
+            }
+            None => ffi::qml_engine_load(engine.pin_mut(), &QUrl::from(qml_main.as_str())),
+        };
+        if !loaded {
+            error!("BevyQmlApp failed to load {qml_main}");
+            return -1;
+        }
+
+        ffi::application_exec()
+    }
+}
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Native dialogs for picking the files to load and save.
+//!
+//! The picked paths are returned to the caller and sent to the main world
+//! as [FilePicked] as well, so a menu item in QML only has to open the
+//! dialog while a system reacts to the file:
+//!
+//! ```ignore
+//! fn open_models(mut picked: EventReader<FilePicked>, asset_server: Res<AssetServer>) {
+//!     for picked in picked.read().filter(|picked| picked.kind == FileDialogKind::Open) {
+//!         let scene: Handle<Scene> = asset_server.load(picked.path.clone());
+//!     }
+//! }
+//! ```
+//!
+//! The dialogs are QFileDialogs, which need the application to be a
+//! QApplication rather than a QGuiApplication.
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_file_dialog")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/filedialog.h");
+
+        #[doc(hidden)]
+        #[rust_name = "open_file_dialog"]
+        fn openFileDialog(filters: &QString) -> QString;
+
+        #[doc(hidden)]
+        #[rust_name = "save_file_dialog"]
+        fn saveFileDialog() -> QString;
+    }
+}
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use cxx_qt_lib::QString;
+
+use crate::runtime;
+
+/// The kind of dialog a file was picked in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileDialogKind {
+    Open,
+    Save,
+}
+
+/// A file was picked in a dialog
+#[derive(Event, Clone, Debug)]
+pub struct FilePicked {
+    pub kind: FileDialogKind,
+    pub path: PathBuf,
+}
+
+/// Ask for a file to open, [None] if the dialog was canceled
+///
+/// The filters are given as in `"Models (*.gltf *.glb);;Images (*.png)"`.
+/// This must be called from the GUI thread and blocks until the dialog is
+/// closed.
+pub fn open_file(filters: &str) -> Option<PathBuf> {
+    let path = ffi::open_file_dialog(&QString::from(filters));
+    picked(FileDialogKind::Open, path)
+}
+
+/// Ask for a file to save to, [None] if the dialog was canceled
+///
+/// This must be called from the GUI thread and blocks until the dialog is
+/// closed.
+pub fn save_file() -> Option<PathBuf> {
+    picked(FileDialogKind::Save, ffi::save_file_dialog())
+}
+
+fn picked(kind: FileDialogKind, path: QString) -> Option<PathBuf> {
+    if path.is_empty() {
+        return None;
+    }
+    let path = PathBuf::from(path.to_string());
+    let event = FilePicked {
+        kind,
+        path: path.clone(),
+    };
+    runtime::send(move |world| {
+        world.send_event(event);
+    });
+    Some(path)
+}
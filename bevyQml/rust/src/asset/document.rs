@@ -0,0 +1,539 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Saving the world, or a tree of entities in it, to a file and loading it
+//! again, for document-style apps. See `BevyAssets.saveScene` and
+//! `BevyAssets.loadScene` for the QML side.
+//!
+//! `.scn.ron` files keep every reflected component and load back as they
+//! were saved. `.gltf` files only take the meshes, placed where they are in
+//! the world, with the base colors of their [StandardMaterial]s, for other
+//! tools to open. Their vertex data goes to a `.bin` file next to them.
+//!
+//! Saving takes what is saved out of the world with [extract_scene], then
+//! encodes and writes it with [write_scene], which may run on another
+//! thread. Loading works the other way around with [read_scene] and
+//! [spawn_loaded_scene]. [save_scene_file] and [load_scene_file] do either
+//! from a task, see [crate::task].
+
+use std::{
+    any::TypeId,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::AssetId,
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+    scene::serde::SceneDeserializer,
+    utils::{HashMap, HashSet},
+};
+use serde::de::DeserializeSeed;
+use serde_json::{json, Value};
+
+use super::{
+    load::{local_path, resolve_url},
+    scene::{extract_trees, spawn_scene, trees, write_dynamic_scene},
+};
+use crate::{commands, label::EntityLabel, task};
+
+/// What [extract_scene] takes out of the world
+#[derive(Clone, Debug, Default)]
+pub struct SceneSaveOptions {
+    /// Save this entity and its descendants rather than the whole world
+    pub root: Option<Entity>,
+    /// Leave out the root itself, its children become the top-most
+    /// entities of the scene
+    pub descendants_only: bool,
+    /// Only save these components, by short or full type path, and the
+    /// hierarchy, or every reflected component if this is empty
+    pub components: Vec<String>,
+    /// Save the reflected resources as well, only to `.scn.ron` files
+    pub resources: bool,
+}
+
+/// The kind of file a scene is saved to, by its extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SceneFormat {
+    /// `.scn.ron` or `.scn`
+    Ron,
+    /// `.gltf`
+    Gltf,
+}
+
+impl SceneFormat {
+    pub fn of(path: &Path) -> Result<Self, String> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        if name.ends_with(".scn.ron") || name.ends_with(".scn") {
+            Ok(Self::Ron)
+        } else if name.ends_with(".gltf") {
+            Ok(Self::Gltf)
+        } else {
+            Err(format!(
+                "{} is neither a .scn.ron nor a .gltf file",
+                path.display()
+            ))
+        }
+    }
+}
+
+/// The file behind a `file:` URL or a path in the assets folder
+pub fn scene_file(url: &str) -> Result<PathBuf, String> {
+    let path = resolve_url(url)?;
+    local_path(&path).ok_or_else(|| format!("{url} is not a local file"))
+}
+
+/// What is saved, taken out of the world
+pub enum SceneExport {
+    Ron(DynamicScene),
+    Gltf(GltfExport),
+}
+
+/// Take what is saved out of the world
+pub fn extract_scene(
+    world: &World,
+    format: SceneFormat,
+    options: &SceneSaveOptions,
+) -> Result<SceneExport, String> {
+    let roots: Vec<Entity> = match options.root {
+        Some(root) if world.get_entity(root).is_none() => {
+            return Err(format!("{root:?} does not exist"));
+        }
+        Some(root) if options.descendants_only => world
+            .get::<Children>(root)
+            .map(|children| children.to_vec())
+            .unwrap_or_default(),
+        Some(root) => vec![root],
+        None => world
+            .iter_entities()
+            .filter(|entity| !entity.contains::<Parent>())
+            .map(|entity| entity.id())
+            .collect(),
+    };
+
+    match format {
+        SceneFormat::Ron => {
+            let (mut scene, _) = extract_trees(world, roots);
+            if !options.components.is_empty() {
+                filter_components(world, &mut scene, &options.components)?;
+            }
+            // Such as entities Bevy keeps for itself
+            scene
+                .entities
+                .retain(|entity| !entity.components.is_empty());
+            if options.resources {
+                scene.resources = DynamicSceneBuilder::from_world(world)
+                    .extract_resources()
+                    .build()
+                    .resources;
+            }
+            Ok(SceneExport::Ron(scene))
+        }
+        SceneFormat::Gltf => {
+            let origin = options
+                .root
+                .and_then(|root| world.get::<GlobalTransform>(root))
+                .map_or(Mat4::IDENTITY, |transform| {
+                    transform.compute_matrix().inverse()
+                });
+            Ok(SceneExport::Gltf(GltfExport::extract(
+                world,
+                trees(world, roots),
+                origin,
+            )))
+        }
+    }
+}
+
+/// Keep the named components and the hierarchy
+fn filter_components(
+    world: &World,
+    scene: &mut DynamicScene,
+    components: &[String],
+) -> Result<(), String> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let mut kept: HashSet<TypeId> = HashSet::default();
+    kept.insert(TypeId::of::<Parent>());
+    kept.insert(TypeId::of::<Children>());
+    for name in components {
+        kept.insert(commands::lookup(&registry, name)?.type_id());
+    }
+    for entity in &mut scene.entities {
+        entity.components.retain(|component| {
+            component
+                .get_represented_type_info()
+                .is_some_and(|info| kept.contains(&info.type_id()))
+        });
+    }
+    Ok(())
+}
+
+/// Encode what was saved and write it to the file, reporting the progress
+/// from 0 to 1
+pub fn write_scene(
+    export: SceneExport,
+    registry: &AppTypeRegistry,
+    path: &Path,
+    progress: &mut dyn FnMut(f32),
+) -> Result<(), String> {
+    match export {
+        SceneExport::Ron(scene) => {
+            let ron = scene
+                .serialize(&registry.read())
+                .map_err(|error| error.to_string())?;
+            progress(0.5);
+            write_file(path, ron.as_bytes())?;
+        }
+        SceneExport::Gltf(gltf) => gltf.write(path, progress)?,
+    }
+    progress(1.0);
+    Ok(())
+}
+
+/// Read a `.scn.ron` file
+pub fn read_scene(path: &Path, registry: &AppTypeRegistry) -> Result<DynamicScene, String> {
+    let ron = std::fs::read_to_string(path)
+        .map_err(|error| format!("Cannot read {}: {error}", path.display()))?;
+    let registry = registry.read();
+    let mut deserializer =
+        ron::de::Deserializer::from_str(&ron).map_err(|error| error.to_string())?;
+    SceneDeserializer {
+        type_registry: &registry,
+    }
+    .deserialize(&mut deserializer)
+    .map_err(|error| format!("{}: {error}", path.display()))
+}
+
+/// Spawn the entities of a scene which was read, returns the new top-most
+/// entities
+pub fn spawn_loaded_scene(world: &mut World, scene: &DynamicScene) -> Result<Vec<Entity>, String> {
+    let spawned = write_dynamic_scene(world, scene)?;
+    Ok(spawned
+        .into_iter()
+        .filter(|entity| world.get::<Parent>(*entity).is_none())
+        .collect())
+}
+
+/// Save to a `file:` URL or a path in the assets folder, the way
+/// `BevyAssets.saveScene` does
+///
+/// Only taking what is saved out of the world waits for an update, encoding
+/// and writing happen wherever the future runs. Returns the format the
+/// scene was saved in.
+pub async fn save_scene_file(
+    url: String,
+    options: SceneSaveOptions,
+    progress: &mut (dyn FnMut(f32) + Send),
+) -> Result<SceneFormat, String> {
+    let path = scene_file(&url)?;
+    let format = SceneFormat::of(&path)?;
+    let (export, registry) = task::world(move |world| {
+        let export = extract_scene(world, format, &options)?;
+        Ok::<_, String>((export, world.resource::<AppTypeRegistry>().clone()))
+    })
+    .await
+    .ok_or("The Bevy app is not running")??;
+    write_scene(export, &registry, &path, progress)?;
+    Ok(format)
+}
+
+/// Spawn the entities saved to a `.scn.ron` file, or the scene of any other
+/// URL below a new root entity, the way `BevyAssets.loadScene` does
+///
+/// Returns the new top-most entities.
+pub async fn load_scene_file(url: String) -> Result<Vec<Entity>, String> {
+    // Anything but a saved scene goes through the asset server
+    let saved = scene_file(&url)
+        .ok()
+        .filter(|path| SceneFormat::of(path) == Ok(SceneFormat::Ron));
+    let Some(path) = saved else {
+        return task::world(move |world| {
+            let root = world.spawn_empty().id();
+            spawn_scene(world, root, &url, None, Transform::IDENTITY)?;
+            Ok(vec![root])
+        })
+        .await
+        .ok_or("The Bevy app is not running")?;
+    };
+    let registry = task::world(|world| world.resource::<AppTypeRegistry>().clone())
+        .await
+        .ok_or("The Bevy app is not running")?;
+    // Reading and parsing stay off the GUI thread
+    let scene = read_scene(&path, &registry)?;
+    task::world(move |world| spawn_loaded_scene(world, &scene))
+        .await
+        .ok_or("The Bevy app is not running")?
+}
+
+/// Write the file as a whole, so a failed save leaves the previous one alone
+fn write_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    std::fs::write(&partial, contents)
+        .and_then(|()| std::fs::rename(&partial, path))
+        .map_err(|error| {
+            let _ = std::fs::remove_file(&partial);
+            format!("Cannot write {}: {error}", path.display())
+        })
+}
+
+/// The meshes of a tree of entities, for a `.gltf` file
+pub struct GltfExport {
+    nodes: Vec<GltfNode>,
+    meshes: Vec<GltfMesh>,
+}
+
+struct GltfNode {
+    name: String,
+    matrix: Mat4,
+    mesh: usize,
+}
+
+struct GltfMesh {
+    mesh: Mesh,
+    base_color: [f32; 4],
+    metallic: f32,
+    roughness: f32,
+}
+
+impl GltfExport {
+    /// Take the meshes of the entities, placed relative to `origin`
+    fn extract(world: &World, entities: Vec<Entity>, origin: Mat4) -> Self {
+        let (Some(mesh_assets), Some(materials)) = (
+            world.get_resource::<Assets<Mesh>>(),
+            world.get_resource::<Assets<StandardMaterial>>(),
+        ) else {
+            return Self {
+                nodes: Vec::new(),
+                meshes: Vec::new(),
+            };
+        };
+
+        let mut shared: HashMap<(AssetId<Mesh>, Option<AssetId<StandardMaterial>>), usize> =
+            HashMap::default();
+        let mut nodes = Vec::new();
+        let mut meshes = Vec::new();
+        for entity in entities {
+            let Some(handle) = world.get::<Handle<Mesh>>(entity) else {
+                continue;
+            };
+            let Some(mesh) = mesh_assets.get(handle) else {
+                continue;
+            };
+            if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+                warn!("Cannot save the mesh of {entity:?} to glTF, it is not a triangle list");
+                continue;
+            }
+            let material = world.get::<Handle<StandardMaterial>>(entity);
+            let key = (handle.id(), material.map(Handle::id));
+            let index = *shared.entry(key).or_insert_with(|| {
+                let material = material.and_then(|material| materials.get(material));
+                let defaults = StandardMaterial::default();
+                let material = material.unwrap_or(&defaults);
+                let color = material.base_color.to_linear();
+                meshes.push(GltfMesh {
+                    mesh: mesh.clone(),
+                    base_color: [color.red, color.green, color.blue, color.alpha],
+                    metallic: material.metallic,
+                    roughness: material.perceptual_roughness,
+                });
+                meshes.len() - 1
+            });
+            let global = world
+                .get::<GlobalTransform>(entity)
+                .map_or(Mat4::IDENTITY, GlobalTransform::compute_matrix);
+            nodes.push(GltfNode {
+                name: EntityLabel::of(world, entity).display_name,
+                matrix: origin * global,
+                mesh: index,
+            });
+        }
+        Self { nodes, meshes }
+    }
+
+    fn write(&self, path: &Path, progress: &mut dyn FnMut(f32)) -> Result<(), String> {
+        let mut buffer = GltfBuffer::default();
+        let mut meshes = Vec::with_capacity(self.meshes.len());
+        let mut materials = Vec::with_capacity(self.meshes.len());
+        for (index, exported) in self.meshes.iter().enumerate() {
+            meshes.push(buffer.add_mesh(&exported.mesh, index)?);
+            materials.push(json!({
+                "pbrMetallicRoughness": {
+                    "baseColorFactor": exported.base_color,
+                    "metallicFactor": exported.metallic,
+                    "roughnessFactor": exported.roughness,
+                },
+            }));
+            // Encoding takes the first half, writing the other
+            progress(0.5 * (index + 1) as f32 / self.meshes.len() as f32);
+        }
+
+        let nodes: Vec<Value> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                json!({
+                    "name": node.name,
+                    "matrix": node.matrix.to_cols_array(),
+                    "mesh": node.mesh,
+                })
+            })
+            .collect();
+        let bin_path = path.with_extension("bin");
+        let bin_name = bin_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let buffers = if buffer.data.is_empty() {
+            Vec::new()
+        } else {
+            vec![json!({ "uri": bin_name, "byteLength": buffer.data.len() })]
+        };
+        let document = json!({
+            "asset": { "version": "2.0", "generator": "BevyQml" },
+            "scene": 0,
+            "scenes": [{ "nodes": (0..nodes.len()).collect::<Vec<_>>() }],
+            "nodes": nodes,
+            "meshes": meshes,
+            "materials": materials,
+            "accessors": buffer.accessors,
+            "bufferViews": buffer.views,
+            "buffers": buffers,
+        });
+        let json = serde_json::to_vec_pretty(&document).map_err(|error| error.to_string())?;
+
+        if !buffer.data.is_empty() {
+            write_file(&bin_path, &buffer.data)?;
+        }
+        progress(0.75);
+        write_file(path, &json)
+    }
+}
+
+/// glTF component types
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+
+/// glTF buffer view targets
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// The vertex data of every mesh in one buffer, with the views and accessors
+/// pointing into it
+#[derive(Default)]
+struct GltfBuffer {
+    data: Vec<u8>,
+    views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl GltfBuffer {
+    /// Add the attributes glTF knows and the indices of the mesh, returns the
+    /// glTF mesh using the material of the same index
+    fn add_mesh(&mut self, mesh: &Mesh, index: usize) -> Result<Value, String> {
+        let positions = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+            .and_then(VertexAttributeValues::as_float3)
+            .ok_or("A mesh has no positions")?;
+        let mut attributes = serde_json::Map::new();
+        attributes.insert(String::from("POSITION"), self.add_vec3(positions, true));
+        if let Some(VertexAttributeValues::Float32x3(normals)) =
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+        {
+            attributes.insert(String::from("NORMAL"), self.add_vec3(normals, false));
+        }
+        if let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            let floats: Vec<f32> = uvs.iter().flatten().copied().collect();
+            let accessor = self.add_accessor(&floats, uvs.len(), "VEC2", FLOAT, ARRAY_BUFFER);
+            attributes.insert(String::from("TEXCOORD_0"), accessor.into());
+        }
+
+        let mut primitive = json!({
+            "attributes": attributes,
+            "material": index,
+        });
+        let indices: Option<Vec<u32>> = match mesh.indices() {
+            Some(Indices::U16(indices)) => Some(indices.iter().map(|&i| u32::from(i)).collect()),
+            Some(Indices::U32(indices)) => Some(indices.clone()),
+            None => None,
+        };
+        if let Some(indices) = indices {
+            let accessor = self.add_accessor(
+                &indices,
+                indices.len(),
+                "SCALAR",
+                UNSIGNED_INT,
+                ELEMENT_ARRAY_BUFFER,
+            );
+            primitive["indices"] = accessor.into();
+        }
+        Ok(json!({ "primitives": [primitive] }))
+    }
+
+    fn add_vec3(&mut self, values: &[[f32; 3]], bounds: bool) -> Value {
+        let floats: Vec<f32> = values.iter().flatten().copied().collect();
+        let accessor = self.add_accessor(&floats, values.len(), "VEC3", FLOAT, ARRAY_BUFFER);
+        // glTF asks for the bounds of the positions
+        if bounds {
+            let (min, max) = values.iter().fold(
+                (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+                |(min, max), value| {
+                    let value = Vec3::from_array(*value);
+                    (min.min(value), max.max(value))
+                },
+            );
+            let accessor = &mut self.accessors[accessor];
+            accessor["min"] = json!(min.to_array());
+            accessor["max"] = json!(max.to_array());
+        }
+        accessor.into()
+    }
+
+    /// Append 4 byte values, returns the index of the accessor
+    fn add_accessor<T: Copy + ToLeBytes>(
+        &mut self,
+        values: &[T],
+        count: usize,
+        kind: &str,
+        component_type: u32,
+        target: u32,
+    ) -> usize {
+        let offset = self.data.len();
+        for value in values {
+            self.data.extend_from_slice(&value.to_le_bytes());
+        }
+        self.views.push(json!({
+            "buffer": 0,
+            "byteOffset": offset,
+            "byteLength": self.data.len() - offset,
+            "target": target,
+        }));
+        self.accessors.push(json!({
+            "bufferView": self.views.len() - 1,
+            "componentType": component_type,
+            "count": count,
+            "type": kind,
+        }));
+        self.accessors.len() - 1
+    }
+}
+
+/// The values glTF buffers hold here
+trait ToLeBytes {
+    fn to_le_bytes(self) -> [u8; 4];
+}
+
+impl ToLeBytes for f32 {
+    fn to_le_bytes(self) -> [u8; 4] {
+        f32::to_le_bytes(self)
+    }
+}
+
+impl ToLeBytes for u32 {
+    fn to_le_bytes(self) -> [u8; 4] {
+        u32::to_le_bytes(self)
+    }
+}
@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! What a loaded glTF file holds, so QML can offer its parts by name, see
+//! [crate::cxxqt_bevy_gltf_model] for the QML side.
+
+use bevy::{asset::UntypedAssetId, gltf::Gltf, prelude::*, utils::HashMap};
+
+use super::QmlAssets;
+
+/// The kinds of parts of a glTF file listed by [gltf_contents]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GltfPartKind {
+    Scene,
+    Node,
+    Material,
+    Animation,
+}
+
+impl GltfPartKind {
+    /// The name QML knows the kind by
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Scene => "scene",
+            Self::Node => "node",
+            Self::Material => "material",
+            Self::Animation => "animation",
+        }
+    }
+}
+
+/// A part of a glTF file
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfPart {
+    pub kind: GltfPartKind,
+    /// The name given in the file, or the label without one
+    pub name: String,
+    /// The label of the part within the file, such as `Scene1`
+    pub label: String,
+    /// The asset path of the part, which loads it on its own
+    pub path: String,
+}
+
+/// The scenes, named nodes, materials and animations of a glTF file loaded
+/// by QML, in the order of the file
+///
+/// Returns [None] until the asset with the id has loaded, or if it is not a
+/// glTF file.
+pub fn gltf_contents(world: &World, id: u64) -> Option<Vec<GltfPart>> {
+    let handle = world.get_resource::<QmlAssets>()?.get(id)?;
+    let gltf = world
+        .get_resource::<Assets<Gltf>>()?
+        .get(handle.id().try_typed::<Gltf>().ok()?)?;
+
+    let mut parts = Vec::new();
+    add_parts(
+        &mut parts,
+        GltfPartKind::Scene,
+        &gltf.scenes,
+        &gltf.named_scenes,
+        true,
+    );
+    // Unnamed nodes are mostly the joints and pieces of meshes
+    add_parts(
+        &mut parts,
+        GltfPartKind::Node,
+        &gltf.nodes,
+        &gltf.named_nodes,
+        false,
+    );
+    add_parts(
+        &mut parts,
+        GltfPartKind::Material,
+        &gltf.materials,
+        &gltf.named_materials,
+        true,
+    );
+    add_parts(
+        &mut parts,
+        GltfPartKind::Animation,
+        &gltf.animations,
+        &gltf.named_animations,
+        true,
+    );
+    Some(parts)
+}
+
+fn add_parts<A: Asset>(
+    parts: &mut Vec<GltfPart>,
+    kind: GltfPartKind,
+    handles: &[Handle<A>],
+    named: &HashMap<Box<str>, Handle<A>>,
+    include_unnamed: bool,
+) {
+    let names: HashMap<UntypedAssetId, &str> = named
+        .iter()
+        .map(|(name, handle)| (handle.id().untyped(), &**name))
+        .collect();
+
+    for handle in handles {
+        let name = names.get(&handle.id().untyped()).copied();
+        if name.is_none() && !include_unnamed {
+            continue;
+        }
+        let Some(path) = handle.path() else {
+            continue;
+        };
+        let label = path.label().unwrap_or_default().to_owned();
+        parts.push(GltfPart {
+            kind,
+            name: name.map_or_else(|| label.clone(), str::to_owned),
+            label,
+            path: path.to_string(),
+        });
+    }
+}
@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Asset sources downloading from `http://` and `https://` URLs.
+
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::io::{
+        AssetReader, AssetReaderError, AssetSource, AssetSourceId, PathStream, Reader, VecReader,
+    },
+    prelude::*,
+};
+
+/// Registers the `http` and `https` asset sources
+///
+/// Like [super::QrcAssetPlugin] this has to go before the AssetPlugin.
+pub struct HttpAssetPlugin;
+
+impl Plugin for HttpAssetPlugin {
+    fn build(&self, app: &mut App) {
+        for scheme in ["http", "https"] {
+            app.register_asset_source(
+                AssetSourceId::from(scheme),
+                AssetSource::build().with_reader(move || Box::new(HttpAssetReader { scheme })),
+            );
+        }
+    }
+}
+
+/// Downloads assets, the path of an asset being the URL without its scheme
+///
+/// Downloads block the IO task they run on until they finish. There are no
+/// meta files or directories on the web, so asking for those fails.
+pub struct HttpAssetReader {
+    scheme: &'static str,
+}
+
+impl HttpAssetReader {
+    fn url(&self, path: &Path) -> String {
+        format!(
+            "{}://{}",
+            self.scheme,
+            path.to_string_lossy().replace('\\', "/")
+        )
+    }
+}
+
+impl AssetReader for HttpAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<Box<Reader<'a>>, AssetReaderError> {
+        let data = download(&self.url(path), path)?;
+        let reader: Box<Reader<'a>> = Box::new(VecReader::new(data));
+        Ok(reader)
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<Box<Reader<'a>>, AssetReaderError> {
+        Err(AssetReaderError::NotFound(path.to_owned()))
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        Err(AssetReaderError::NotFound(path.to_owned()))
+    }
+
+    async fn is_directory<'a>(&'a self, _path: &'a Path) -> Result<bool, AssetReaderError> {
+        Ok(false)
+    }
+}
+
+fn download(url: &str, path: &Path) -> Result<Vec<u8>, AssetReaderError> {
+    let response = match ureq::get(url).call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(404, _)) => {
+            return Err(AssetReaderError::NotFound(PathBuf::from(path)));
+        }
+        Err(ureq::Error::Status(status, _)) => {
+            return Err(AssetReaderError::HttpError(status));
+        }
+        Err(error) => {
+            return Err(AssetReaderError::Io(
+                std::io::Error::new(std::io::ErrorKind::Other, error).into(),
+            ));
+        }
+    };
+
+    let mut data = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut data)
+        .map_err(|error| AssetReaderError::Io(error.into()))?;
+    Ok(data)
+}
@@ -0,0 +1,546 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Assets loaded on behalf of QML, which refers to them by a numeric handle id.
+
+use std::path::PathBuf;
+
+use bevy::{
+    asset::{
+        io::file::FileAssetReader, AssetPath, LoadState, LoadedUntypedAsset,
+        RecursiveDependencyLoadState,
+    },
+    gltf::Gltf,
+    prelude::*,
+    utils::HashMap,
+};
+
+use super::qrc::{self, QRC_SOURCE};
+
+/// A load which finished, in either direction
+#[derive(Clone, Debug)]
+pub enum QmlAssetOutcome {
+    Loaded { id: u64 },
+    Failed { id: u64, error: String },
+}
+
+/// Where the load of a single asset stands
+#[derive(Clone, Debug, PartialEq)]
+pub enum QmlAssetStatus {
+    /// Bevy only reports whether the asset and then its dependencies are
+    /// loaded, so the progress moves from 0 to 0.5 and then to 1
+    Loading {
+        progress: f32,
+    },
+    Loaded,
+    Failed {
+        error: String,
+    },
+}
+
+impl QmlAssetStatus {
+    pub fn progress(&self) -> f32 {
+        match self {
+            Self::Loading { progress } => *progress,
+            Self::Loaded | Self::Failed { .. } => 1.0,
+        }
+    }
+}
+
+/// The progress of the loads started since the last time nothing was loading
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct QmlLoadProgress {
+    pub requested: u32,
+    pub loaded: u32,
+    pub failed: u32,
+    /// The size of the files of the requested assets, where it is known up
+    /// front, which is not the case for downloads or dependencies
+    pub bytes_total: u64,
+    pub bytes_loaded: u64,
+    /// How much of the batch is done, from 0 to 1
+    pub fraction: f32,
+}
+
+impl QmlLoadProgress {
+    pub fn is_loading(&self) -> bool {
+        self.loaded + self.failed < self.requested
+    }
+}
+
+struct PendingAsset {
+    handle: UntypedHandle,
+    bytes: u64,
+    progress: f32,
+}
+
+/// The assets QML asked for, kept alive until they are released
+///
+/// Handle ids start at one, zero meaning no asset. Outcomes pile up until
+/// they are taken by the `BevyAssets` singleton after an update. Failed
+/// loads are remembered until they are released or the asset is loaded
+/// again.
+#[derive(Resource, Default)]
+pub struct QmlAssets {
+    next_id: u64,
+    pending: HashMap<u64, PendingAsset>,
+    loaded: HashMap<u64, (UntypedHandle, u64)>,
+    /// The error and the path of every failed load
+    failed: HashMap<u64, (String, Option<AssetPath<'static>>)>,
+    outcomes: Vec<QmlAssetOutcome>,
+    batch: QmlLoadProgress,
+}
+
+impl QmlAssets {
+    /// The asset with the id, once it has finished loading
+    pub fn get(&self, id: u64) -> Option<&UntypedHandle> {
+        self.loaded.get(&id).map(|(handle, _)| handle)
+    }
+
+    /// Whether the asset with the id is still loading
+    pub fn is_pending(&self, id: u64) -> bool {
+        self.pending.contains_key(&id)
+    }
+
+    /// Where the load of an asset stands, [None] for unknown or released ids
+    pub fn status(&self, id: u64) -> Option<QmlAssetStatus> {
+        if let Some(pending) = self.pending.get(&id) {
+            Some(QmlAssetStatus::Loading {
+                progress: pending.progress,
+            })
+        } else if self.loaded.contains_key(&id) {
+            Some(QmlAssetStatus::Loaded)
+        } else {
+            self.failed
+                .get(&id)
+                .map(|(error, _)| QmlAssetStatus::Failed {
+                    error: error.clone(),
+                })
+        }
+    }
+
+    /// The size of the file of an asset, 0 if it is not known
+    pub fn bytes(&self, id: u64) -> u64 {
+        self.pending
+            .get(&id)
+            .map(|pending| pending.bytes)
+            .or_else(|| self.loaded.get(&id).map(|(_, bytes)| *bytes))
+            .unwrap_or(0)
+    }
+
+    pub fn progress(&self) -> QmlLoadProgress {
+        QmlLoadProgress {
+            fraction: if self.batch.requested == 0 {
+                1.0
+            } else {
+                self.batch.fraction
+            },
+            ..self.batch
+        }
+    }
+
+    /// Drop the handle of an asset, so it is unloaded unless used elsewhere
+    ///
+    /// Releasing an asset which is still loading counts as a failed load.
+    pub fn release(&mut self, id: u64) {
+        if let Some(pending) = self.pending.remove(&id) {
+            self.batch.failed += 1;
+            self.batch.bytes_loaded += pending.bytes;
+            self.update_fraction();
+        }
+        self.loaded.remove(&id);
+        self.failed.remove(&id);
+    }
+
+    pub fn take_outcomes(&mut self) -> Vec<QmlAssetOutcome> {
+        std::mem::take(&mut self.outcomes)
+    }
+
+    fn insert(&mut self, handle: UntypedHandle, bytes: u64) -> u64 {
+        if self.pending.is_empty() {
+            self.batch = QmlLoadProgress::default();
+        }
+        self.batch.requested += 1;
+        self.batch.bytes_total += bytes;
+        // A new load of an asset replaces its failed loads
+        if let Some(path) = handle.path() {
+            self.failed
+                .retain(|_, (_, failed)| failed.as_ref() != Some(path));
+        }
+
+        self.next_id += 1;
+        self.pending.insert(
+            self.next_id,
+            PendingAsset {
+                handle,
+                bytes,
+                progress: 0.0,
+            },
+        );
+        self.update_fraction();
+        self.next_id
+    }
+
+    fn finish(&mut self, id: u64, result: Result<UntypedHandle, String>) {
+        let Some(pending) = self.pending.remove(&id) else {
+            return;
+        };
+        self.batch.bytes_loaded += pending.bytes;
+        match result {
+            Ok(handle) => {
+                self.batch.loaded += 1;
+                self.loaded.insert(id, (handle, pending.bytes));
+                self.outcomes.push(QmlAssetOutcome::Loaded { id });
+            }
+            Err(error) => {
+                self.batch.failed += 1;
+                let path = pending.handle.path().cloned();
+                self.failed.insert(id, (error.clone(), path));
+                self.outcomes.push(QmlAssetOutcome::Failed { id, error });
+            }
+        }
+        self.update_fraction();
+    }
+
+    fn update_fraction(&mut self) {
+        let done = (self.batch.loaded + self.batch.failed) as f32;
+        let pending: f32 = self.pending.values().map(|pending| pending.progress).sum();
+        self.batch.fraction = ((done + pending) / self.batch.requested.max(1) as f32).min(1.0);
+    }
+}
+
+pub struct QmlAssetsPlugin;
+
+impl Plugin for QmlAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QmlAssets>()
+            .register_type::<super::QmlSceneRoot>()
+            .add_event::<super::FilePicked>()
+            .add_systems(Last, track_loads);
+    }
+}
+
+/// Turn a URL as used in QML into an asset path
+///
+/// `file:` URLs become absolute paths of the default source with their escaped
+/// characters decoded and their fragment as the label, `qrc:` URLs and
+/// `:/` paths use the [QRC_SOURCE] and `http(s):` URLs are downloaded by
+/// [super::HttpAssetPlugin]. Anything without a scheme is relative to the
+/// assets folder.
+pub fn resolve_url(url: &str) -> Result<AssetPath<'static>, String> {
+    let url = url.trim();
+    if url.is_empty() {
+        return Err("No asset URL given".to_owned());
+    }
+
+    if let Some(path) = url.strip_prefix("file:") {
+        return file_asset_path(path);
+    }
+    let path = if let Some(path) = url.strip_prefix("qrc:").or_else(|| url.strip_prefix(':')) {
+        format!("{QRC_SOURCE}://{}", path.trim_start_matches('/'))
+    } else if url.starts_with("http://") || url.starts_with("https://") {
+        url.to_owned()
+    } else if let Some((scheme, _)) = url.split_once("://") {
+        return Err(format!("Unsupported URL scheme {scheme}"));
+    } else {
+        url.to_owned()
+    };
+
+    AssetPath::try_parse(&path)
+        .map(AssetPath::into_owned)
+        .map_err(|error| format!("Invalid asset path {path}: {error}"))
+}
+
+/// The asset path of a `file:` URL, given the part after the scheme
+///
+/// Only the path is decoded, so an escaped `#` stays part of the file name
+/// while the fragment names a label of the asset.
+fn file_asset_path(url: &str) -> Result<AssetPath<'static>, String> {
+    let (path, label) = match url.split_once('#') {
+        Some((path, label)) => (path, Some(label)),
+        None => (url, None),
+    };
+    let path = match path.strip_prefix("//") {
+        // file:///C:/x on Windows, file:///x elsewhere
+        Some(path) => {
+            let path = path.strip_prefix("localhost").unwrap_or(path);
+            match path.get(1..3) {
+                Some(drive) if drive.ends_with(':') => &path[1..],
+                _ => path,
+            }
+        }
+        None => path,
+    };
+    let path = AssetPath::from(PathBuf::from(percent_decode(path)?));
+    Ok(match label {
+        Some(label) => path.with_label(label.to_owned()),
+        None => path,
+    })
+}
+
+/// Decode the escaped characters of a URL path, such as `%20` for a space
+fn percent_decode(path: &str) -> Result<String, String> {
+    let invalid = || format!("Invalid escape in {path}");
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail.get(..2).ok_or_else(invalid)?;
+            let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| format!("{path} is not valid UTF-8 once decoded"))
+}
+
+/// Start loading an asset from a URL and return its handle id
+///
+/// The type hint names the asset type, such as `image`, `mesh`, `scene` or
+/// `gltf`. Without one the type is picked from the file extension. Scenes
+/// from glTF files default to the first scene of the file.
+pub fn load_url(world: &mut World, url: &str, type_hint: &str) -> Result<u64, String> {
+    let path = resolve_url(url)?;
+    let bytes = file_size(&path).unwrap_or(0);
+    let asset_server = world
+        .get_resource::<AssetServer>()
+        .ok_or("There is no asset server")?;
+
+    let handle = match type_hint.trim().to_ascii_lowercase().as_str() {
+        "" => asset_server.load_untyped(path).untyped(),
+        "image" | "texture" => asset_server.load::<Image>(path).untyped(),
+        "mesh" => asset_server.load::<Mesh>(path).untyped(),
+        "scene" => asset_server.load::<Scene>(default_scene(path)).untyped(),
+        "gltf" => asset_server.load::<Gltf>(path).untyped(),
+        "animation" => asset_server.load::<AnimationClip>(path).untyped(),
+        "audio" => asset_server.load::<AudioSource>(path).untyped(),
+        "font" => asset_server.load::<Font>(path).untyped(),
+        "shader" => asset_server.load::<Shader>(path).untyped(),
+        other => return Err(format!("Unknown asset type {other}")),
+    };
+
+    Ok(world.resource_mut::<QmlAssets>().insert(handle, bytes))
+}
+
+/// Point a path to a glTF file without a label to the first scene of the file
+pub(crate) fn default_scene(path: AssetPath<'static>) -> AssetPath<'static> {
+    let is_gltf = path
+        .get_full_extension()
+        .is_some_and(|extension| extension == "gltf" || extension == "glb");
+    if is_gltf && path.label().is_none() {
+        path.with_label("Scene0")
+    } else {
+        path
+    }
+}
+
+/// The size of the file behind an asset path, for the sources which can tell
+fn file_size(path: &AssetPath) -> Option<u64> {
+    match path.source().as_str() {
+        None => {
+            let file = local_path(path)?;
+            std::fs::metadata(file).ok().map(|metadata| metadata.len())
+        }
+        Some(QRC_SOURCE) => qrc::resource_size(path.path()),
+        Some(_) => None,
+    }
+}
+
+/// Read the file behind a URL as used in QML, see [resolve_url], from the
+/// assets folder, the file system or the Qt resources
+pub fn read_url(url: &str) -> Result<Vec<u8>, String> {
+    let path = resolve_url(url)?;
+    if let Some(file) = local_path(&path) {
+        return std::fs::read(&file)
+            .map_err(|error| format!("Cannot read {}: {error}", file.display()));
+    }
+    match path.source().as_str() {
+        Some(QRC_SOURCE) => {
+            qrc::read_resource(path.path()).map_err(|error| format!("Cannot read {url}: {error}"))
+        }
+        _ => Err(format!("{url} is neither a local file nor a Qt resource")),
+    }
+}
+
+/// The file behind an asset path of the default source, which reads from the
+/// assets folder of the AssetPlugin
+pub(crate) fn local_path(path: &AssetPath) -> Option<PathBuf> {
+    path.source().as_str().is_none().then(|| {
+        FileAssetReader::get_base_path()
+            .join("assets")
+            .join(path.path())
+    })
+}
+
+fn track_loads(
+    mut assets: ResMut<QmlAssets>,
+    asset_server: Res<AssetServer>,
+    untyped: Res<Assets<LoadedUntypedAsset>>,
+) {
+    if assets.pending.is_empty() {
+        return;
+    }
+
+    let mut finished = Vec::new();
+    for (&id, pending) in &mut assets.pending {
+        let Some((state, _, recursive)) = asset_server.get_load_states(&pending.handle) else {
+            continue;
+        };
+        match (state, recursive) {
+            (LoadState::Failed(error), _) => finished.push((id, Err(error.to_string()))),
+            (_, RecursiveDependencyLoadState::Failed) => finished.push((
+                id,
+                Err("A dependency of the asset failed to load".to_owned()),
+            )),
+            (_, RecursiveDependencyLoadState::Loaded) => {
+                // Keep the asset itself rather than the wrapper of an untyped load
+                let handle = pending
+                    .handle
+                    .clone()
+                    .try_typed::<LoadedUntypedAsset>()
+                    .ok()
+                    .and_then(|wrapper| untyped.get(&wrapper))
+                    .map_or_else(|| pending.handle.clone(), |wrapper| wrapper.handle.clone());
+                finished.push((id, Ok(handle)));
+            }
+            (LoadState::Loaded, _) => pending.progress = 0.5,
+            _ => {}
+        }
+    }
+
+    for (id, result) in finished {
+        assets.finish(id, result);
+    }
+    assets.update_fraction();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forgets_failed_loads() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        let asset_server = app.world().resource::<AssetServer>();
+        let load = || asset_server.load_untyped("missing.png").untyped();
+        let mut assets = QmlAssets::default();
+
+        let first = assets.insert(load(), 0);
+        assets.finish(first, Err("not found".to_owned()));
+        let second = assets.insert(asset_server.load_untyped("other.png").untyped(), 0);
+        assets.finish(second, Err("not found".to_owned()));
+        assert_eq!(
+            assets.status(first),
+            Some(QmlAssetStatus::Failed {
+                error: "not found".to_owned()
+            })
+        );
+
+        // Loading the asset again forgets its failed load, but not the others
+        let retry = assets.insert(load(), 0);
+        assert_eq!(assets.status(first), None);
+        assert!(assets.status(second).is_some());
+        assert_eq!(
+            assets.status(retry),
+            Some(QmlAssetStatus::Loading { progress: 0.0 })
+        );
+
+        assets.release(second);
+        assert_eq!(assets.status(second), None);
+        assert!(assets.failed.is_empty());
+    }
+
+    #[test]
+    fn resolves_file_urls() {
+        assert_eq!(
+            resolve_url("file:///home/user/models/ship.glb").unwrap(),
+            AssetPath::from("/home/user/models/ship.glb")
+        );
+        assert_eq!(
+            resolve_url("file://localhost/home/user/ship.glb").unwrap(),
+            AssetPath::from("/home/user/ship.glb")
+        );
+        assert_eq!(
+            resolve_url("file:relative/ship.glb").unwrap(),
+            AssetPath::from("relative/ship.glb")
+        );
+    }
+
+    #[test]
+    fn decodes_file_urls() {
+        assert_eq!(
+            resolve_url("file:///home/user/My%20Models/sh%C3%AFp.glb").unwrap(),
+            AssetPath::from("/home/user/My Models/sh\u{ef}p.glb")
+        );
+        assert!(resolve_url("file:///home/user/ship%2.glb").is_err());
+        assert!(resolve_url("file:///home/user/ship%zz.glb").is_err());
+        assert!(resolve_url("file:///home/user/ship%ff.glb").is_err());
+    }
+
+    #[test]
+    fn decodes_only_the_path_of_file_urls() {
+        let path = resolve_url("file:///tmp/a%23b.gltf").unwrap();
+        assert_eq!(path, AssetPath::from(PathBuf::from("/tmp/a#b.gltf")));
+        assert_eq!(path.label(), None);
+        assert_eq!(
+            resolve_url("file:///tmp/a%23b.gltf#Scene0").unwrap(),
+            AssetPath::from(PathBuf::from("/tmp/a#b.gltf")).with_label("Scene0")
+        );
+        assert_eq!(
+            resolve_url("file:///tmp/ship.glb#Mesh0/Primitive0").unwrap(),
+            AssetPath::from("/tmp/ship.glb#Mesh0/Primitive0")
+        );
+    }
+
+    #[test]
+    fn resolves_drive_letters() {
+        assert_eq!(
+            resolve_url("file:///C:/Users/user/ship.glb").unwrap(),
+            AssetPath::from("C:/Users/user/ship.glb")
+        );
+        assert_eq!(
+            resolve_url("file:///D:/My%20Models/ship.glb").unwrap(),
+            AssetPath::from("D:/My Models/ship.glb")
+        );
+    }
+
+    #[test]
+    fn resolves_qrc_urls() {
+        let expected = AssetPath::parse("qrc://models/ship.glb").into_owned();
+        assert_eq!(resolve_url("qrc:/models/ship.glb").unwrap(), expected);
+        assert_eq!(resolve_url("qrc:///models/ship.glb").unwrap(), expected);
+        assert_eq!(resolve_url(":/models/ship.glb").unwrap(), expected);
+    }
+
+    #[test]
+    fn keeps_http_urls() {
+        assert_eq!(
+            resolve_url("https://example.com/ship.glb").unwrap(),
+            AssetPath::from("https://example.com/ship.glb")
+        );
+        assert_eq!(
+            resolve_url("http://example.com/ship.glb#Scene0").unwrap(),
+            AssetPath::from("http://example.com/ship.glb#Scene0")
+        );
+    }
+
+    #[test]
+    fn resolves_relative_paths() {
+        assert_eq!(
+            resolve_url(" models/ship.glb ").unwrap(),
+            AssetPath::from("models/ship.glb")
+        );
+        assert_eq!(
+            resolve_url("models/ship.glb#Mesh0/Primitive0").unwrap(),
+            AssetPath::from("models/ship.glb#Mesh0/Primitive0")
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_urls() {
+        assert!(resolve_url("").is_err());
+        assert!(resolve_url("ftp://example.com/ship.glb").is_err());
+    }
+}
@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Loading Bevy assets from the places Qt applications keep them.
+
+mod dialog;
+mod document;
+mod gltf;
+mod http;
+mod load;
+mod qrc;
+mod scene;
+
+pub use dialog::{open_file, save_file, FileDialogKind, FilePicked};
+pub use document::{
+    extract_scene, load_scene_file, read_scene, save_scene_file, scene_file, spawn_loaded_scene,
+    write_scene, SceneExport, SceneFormat, SceneSaveOptions,
+};
+pub use gltf::{gltf_contents, GltfPart, GltfPartKind};
+pub use http::{HttpAssetPlugin, HttpAssetReader};
+pub use load::{
+    load_url, read_url, resolve_url, QmlAssetOutcome, QmlAssetStatus, QmlAssets, QmlAssetsPlugin,
+    QmlLoadProgress,
+};
+pub use qrc::{QrcAssetPlugin, QrcAssetReader};
+pub use scene::{despawn_scene, spawn_scene, QmlSceneRoot};
+pub(crate) use scene::{extract_trees, write_dynamic_scene};
@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! An asset source reading from the Qt resource system.
+//!
+//! With [QrcAssetPlugin] added, assets compiled into the application with a
+//! `.qrc` file or `qt_add_resources` are loaded with paths such as
+//! `asset_server.load("qrc://models/ship.gltf")`, which reads
+//! `:/models/ship.gltf`.
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_qrc")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qstringlist.h");
+        /// An alias to the QStringList type
+        type QStringList = cxx_qt_lib::QStringList;
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/qrc.h");
+
+        #[doc(hidden)]
+        #[rust_name = "qrc_read"]
+        fn qrcRead(path: &QString, data: &mut Vec<u8>) -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "qrc_exists"]
+        fn qrcExists(path: &QString) -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "qrc_size"]
+        fn qrcSize(path: &QString) -> i64;
+
+        #[doc(hidden)]
+        #[rust_name = "qrc_is_directory"]
+        fn qrcIsDirectory(path: &QString) -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "qrc_entries"]
+        fn qrcEntries(path: &QString) -> QStringList;
+    }
+}
+
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    asset::io::{
+        get_meta_path, AssetReader, AssetReaderError, AssetSource, AssetSourceId, PathStream,
+        Reader, VecReader,
+    },
+    prelude::*,
+    tasks::futures_lite::stream,
+};
+use cxx_qt_lib::{QList, QString};
+
+/// The name of the asset source, as in `qrc://path/to/asset`
+pub const QRC_SOURCE: &str = "qrc";
+
+/// Registers the `qrc` asset source
+///
+/// Asset sources have to be registered before the AssetPlugin is built, so
+/// this plugin goes before the default plugins, which
+/// [crate::plugin::bevy_qml_default_plugins] already takes care of.
+pub struct QrcAssetPlugin;
+
+impl Plugin for QrcAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_asset_source(
+            AssetSourceId::from(QRC_SOURCE),
+            AssetSource::build().with_reader(|| Box::new(QrcAssetReader)),
+        );
+    }
+}
+
+/// Reads assets from the Qt resource system
+pub struct QrcAssetReader;
+
+impl AssetReader for QrcAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<Box<Reader<'a>>, AssetReaderError> {
+        let reader: Box<Reader<'a>> = Box::new(VecReader::new(read_resource(path)?));
+        Ok(reader)
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<Box<Reader<'a>>, AssetReaderError> {
+        let meta_path = get_meta_path(path);
+        let reader: Box<Reader<'a>> = Box::new(VecReader::new(read_resource(&meta_path)?));
+        Ok(reader)
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        let resource = resource_path(path);
+        if !ffi::qrc_is_directory(&resource) {
+            return Err(AssetReaderError::NotFound(path.to_owned()));
+        }
+
+        let entries: Vec<PathBuf> = QList::<QString>::from(&ffi::qrc_entries(&resource))
+            .iter()
+            .map(|entry| path.join(entry.to_string()))
+            .filter(|entry| {
+                // Meta files are read along with their assets
+                entry
+                    .extension()
+                    .map_or(true, |extension| extension != "meta")
+            })
+            .collect();
+        let stream: Box<PathStream> = Box::new(stream::iter(entries));
+        Ok(stream)
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        let resource = resource_path(path);
+        if !ffi::qrc_exists(&resource) {
+            return Err(AssetReaderError::NotFound(path.to_owned()));
+        }
+        Ok(ffi::qrc_is_directory(&resource))
+    }
+}
+
+/// The path of an asset in the resource system, relative to `:/`
+fn resource_path(path: &Path) -> QString {
+    let path = path.to_string_lossy().replace('\\', "/");
+    QString::from(path.trim_start_matches('/'))
+}
+
+/// The size of a resource in bytes, [None] if there is no such file
+pub(super) fn resource_size(path: &Path) -> Option<u64> {
+    u64::try_from(ffi::qrc_size(&resource_path(path))).ok()
+}
+
+/// The contents of a resource
+pub(super) fn read_resource(path: &Path) -> Result<Vec<u8>, AssetReaderError> {
+    let mut data = Vec::new();
+    if ffi::qrc_read(&resource_path(path), &mut data) {
+        Ok(data)
+    } else {
+        Err(AssetReaderError::NotFound(path.to_owned()))
+    }
+}
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Scenes placed from QML, such as prefabs dropped into a level.
+//!
+//! A scene is spawned below a root entity of its own, which carries the
+//! transform it was placed with and is despawned along with the scene.
+//!
+//! Trees of entities are also extracted into scenes here, for the clipboard
+//! and for saving documents, see [super::document].
+
+use std::any::TypeId;
+
+use bevy::{
+    ecs::entity::{EntityHashMap, EntityHashSet},
+    prelude::*,
+};
+
+use super::load::{default_scene, resolve_url};
+
+/// The root entity of a scene spawned with [spawn_scene]
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct QmlSceneRoot {
+    /// The URL the scene was loaded from
+    pub url: String,
+}
+
+/// Spawn the scene at a URL below the given root entity, which may have
+/// been reserved
+///
+/// `.scn` and `.scn.ron` files are loaded as [DynamicScene]s, anything else
+/// as a [Scene], where glTF files default to their first scene. The URL is
+/// resolved as by [resolve_url], and the scene appears once it has loaded.
+pub fn spawn_scene(
+    world: &mut World,
+    root: Entity,
+    url: &str,
+    parent: Option<Entity>,
+    transform: Transform,
+) -> Result<(), String> {
+    let path = resolve_url(url)?;
+    let asset_server = world
+        .get_resource::<AssetServer>()
+        .ok_or("There is no asset server")?
+        .clone();
+    let parent = parent.filter(|parent| *parent != root && world.get_entity(*parent).is_some());
+
+    // Reserved entities only exist once the world has been flushed
+    world.flush();
+    let mut root = world
+        .get_or_spawn(root)
+        .ok_or_else(|| format!("{root:?} was despawned"))?;
+    root.insert((
+        SpatialBundle::from_transform(transform),
+        QmlSceneRoot { url: url.to_owned() },
+    ));
+    let is_dynamic = path
+        .get_full_extension()
+        .is_some_and(|extension| extension == "scn" || extension == "scn.ron");
+    if is_dynamic {
+        root.insert(asset_server.load::<DynamicScene>(path));
+    } else {
+        root.insert(asset_server.load::<Scene>(default_scene(path)));
+    }
+    if let Some(parent) = parent {
+        root.set_parent(parent);
+    }
+    Ok(())
+}
+
+/// Despawn a scene spawned with [spawn_scene] along with its root
+pub fn despawn_scene(world: &mut World, root: Entity) -> Result<(), String> {
+    let root = world
+        .get_entity_mut(root)
+        .filter(|root| root.contains::<QmlSceneRoot>())
+        .ok_or_else(|| format!("{root:?} is not the root of a scene"))?;
+    root.despawn_recursive();
+    Ok(())
+}
+
+/// The entities and their descendants, depth first so the entities keep
+/// the order they were given in
+pub(crate) fn trees(world: &World, entities: impl IntoIterator<Item = Entity>) -> Vec<Entity> {
+    let mut pending: Vec<Entity> = entities.into_iter().collect();
+    pending.reverse();
+    let mut seen = EntityHashSet::default();
+    let mut found = Vec::new();
+    while let Some(entity) = pending.pop() {
+        if world.get_entity(entity).is_none() || !seen.insert(entity) {
+            continue;
+        }
+        found.push(entity);
+        if let Some(children) = world.get::<Children>(entity) {
+            pending.extend(children.iter().rev());
+        }
+    }
+    found
+}
+
+/// Extract the entities and their descendants into a scene, returns it with
+/// the top-most entities
+pub(crate) fn extract_trees(
+    world: &World,
+    entities: impl IntoIterator<Item = Entity>,
+) -> (DynamicScene, Vec<Entity>) {
+    let extracted = trees(world, entities);
+    let seen: EntityHashSet = extracted.iter().copied().collect();
+    let roots: Vec<Entity> = extracted
+        .iter()
+        .copied()
+        .filter(|entity| {
+            world
+                .get::<Parent>(*entity)
+                .map_or(true, |parent| !seen.contains(&parent.get()))
+        })
+        .collect();
+
+    let mut scene = DynamicSceneBuilder::from_world(world)
+        .extract_entities(extracted.into_iter())
+        .build();
+    // The parents of the top-most entities are not part of the scene
+    for entity in &mut scene.entities {
+        if roots.contains(&entity.entity) {
+            entity.components.retain(|component| {
+                component
+                    .get_represented_type_info()
+                    .map_or(true, |info| info.type_id() != TypeId::of::<Parent>())
+            });
+        }
+    }
+    (scene, roots)
+}
+
+/// Spawn the entities of the scene, returns them in the order of the scene
+pub(crate) fn write_dynamic_scene(
+    world: &mut World,
+    scene: &DynamicScene,
+) -> Result<Vec<Entity>, String> {
+    let mut entities = EntityHashMap::default();
+    scene
+        .write_to_world(world, &mut entities)
+        .map_err(|error| error.to_string())?;
+    Ok(scene
+        .entities
+        .iter()
+        .filter_map(|entity| entities.get(&entity.entity).copied())
+        .collect())
+}
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Coalesces the changes of components into one notification per entity and
+//! update, so bridge objects only refresh their properties when something
+//! they mirror changed, once, instead of polling the world after every
+//! update.
+//!
+//! [QmlBatchedComponent] collects the entities whose component `C` was
+//! changed, added or removed into the [QmlChangeBatch] at the end of every
+//! update, however often it was mutated. Right after the update, before the
+//! [crate::runtime::on_update] listeners run, the batch is taken from the
+//! world and the callbacks registered with [subscribe] for those entities
+//! are run, each once. Changes which are not taken before the next update
+//! are dropped. This is the sync point after which the properties of
+//! the bridge objects match the world.
+//!
+//! ```ignore
+//! app.add_plugins(QmlBatchedComponent::<Health>::default());
+//!
+//! let subscription = batch::subscribe::<Health>(entity, move || {
+//!     let _ = qt_thread.queue(|bar| bar.refresh());
+//! });
+//! ```
+//!
+//! Only the app of [crate::plugin::BevyQmlPlugin] is batched, which batches
+//! [Transform] for `TransformBridge`.
+
+use std::{any::TypeId, cell::RefCell, marker::PhantomData, rc::Rc};
+
+use bevy::{ecs::entity::EntityHashSet, prelude::*, utils::HashMap};
+
+use crate::{panic, runtime};
+
+type Callback = Rc<RefCell<Option<Box<dyn FnMut()>>>>;
+
+thread_local! {
+    static SUBSCRIBERS: RefCell<HashMap<(TypeId, Entity), Vec<Callback>>> =
+        RefCell::new(HashMap::default());
+}
+
+/// The entities whose batched components changed since the batch was last
+/// taken, by the type of the component
+#[derive(Resource, Default)]
+pub struct QmlChangeBatch {
+    changed: HashMap<TypeId, EntityHashSet>,
+}
+
+impl QmlChangeBatch {
+    /// Mark the component `C` of the entity as changed
+    pub fn mark<C: Component>(&mut self, entity: Entity) {
+        self.changed
+            .entry(TypeId::of::<C>())
+            .or_default()
+            .insert(entity);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changed.values().all(|entities| entities.is_empty())
+    }
+
+    /// Whether the component `C` of the entity changed since the batch was
+    /// last taken
+    pub fn contains<C: Component>(&self, entity: Entity) -> bool {
+        self.changed
+            .get(&TypeId::of::<C>())
+            .is_some_and(|entities| entities.contains(&entity))
+    }
+
+    fn take(&mut self) -> HashMap<TypeId, EntityHashSet> {
+        std::mem::take(&mut self.changed)
+    }
+}
+
+/// Batches the changes of the component `C` for [subscribe]
+pub struct QmlBatchedComponent<C>(PhantomData<fn() -> C>);
+
+impl<C> Default for QmlBatchedComponent<C> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<C: Component> Plugin for QmlBatchedComponent<C> {
+    fn build(&self, app: &mut App) {
+        if !app.world().contains_resource::<QmlChangeBatch>() {
+            app.init_resource::<QmlChangeBatch>()
+                .add_systems(First, clear_changes);
+        }
+        app.add_systems(Last, collect_changes::<C>);
+    }
+}
+
+/// Changes which were not taken right after the update they happened in,
+/// such as while the app runs on a thread of its own, are dropped
+fn clear_changes(mut batch: ResMut<QmlChangeBatch>) {
+    if !batch.is_empty() {
+        batch.take();
+    }
+}
+
+fn collect_changes<C: Component>(
+    changed: Query<Entity, Changed<C>>,
+    mut removed: RemovedComponents<C>,
+    mut batch: ResMut<QmlChangeBatch>,
+) {
+    for entity in changed.iter().chain(removed.read()) {
+        batch.mark::<C>(entity);
+    }
+}
+
+/// Keeps a callback registered with [subscribe] alive
+///
+/// The callback is removed when this is dropped.
+pub struct BatchSubscription(Callback);
+
+impl Drop for BatchSubscription {
+    fn drop(&mut self) {
+        if let Ok(mut callback) = self.0.try_borrow_mut() {
+            callback.take();
+        }
+    }
+}
+
+/// Register a callback which is run on the GUI thread after every update in
+/// which the component `C` of the entity changed, was added or was removed
+///
+/// The component needs a [QmlBatchedComponent] plugin.
+pub fn subscribe<C: Component>(
+    entity: Entity,
+    callback: impl FnMut() + 'static,
+) -> BatchSubscription {
+    let callback: Callback = Rc::new(RefCell::new(Some(Box::new(callback))));
+    SUBSCRIBERS.with(|subscribers| {
+        subscribers
+            .borrow_mut()
+            .entry((TypeId::of::<C>(), entity))
+            .or_default()
+            .push(callback.clone());
+    });
+    BatchSubscription(callback)
+}
+
+/// Take the batch of the app and run the callbacks of the changed entities
+pub(crate) fn flush() {
+    let changed = runtime::with_world(|world| {
+        world
+            .get_resource_mut::<QmlChangeBatch>()
+            .filter(|batch| !batch.is_empty())
+            .map(|mut batch| batch.take())
+    })
+    .flatten();
+    let Some(changed) = changed else {
+        return;
+    };
+
+    // Collected first so callbacks can subscribe while running
+    let callbacks: Vec<Callback> = SUBSCRIBERS.with(|subscribers| {
+        let mut subscribers = subscribers.borrow_mut();
+        subscribers.retain(|_, callbacks| {
+            callbacks.retain(|callback| callback.try_borrow().map_or(true, |cb| cb.is_some()));
+            !callbacks.is_empty()
+        });
+        changed
+            .iter()
+            .flat_map(|(type_id, entities)| entities.iter().map(|entity| (*type_id, *entity)))
+            .filter_map(|key| subscribers.get(&key))
+            .flatten()
+            .cloned()
+            .collect()
+    });
+
+    for callback in callbacks {
+        if let Ok(mut callback) = callback.try_borrow_mut() {
+            if let Some(callback) = callback.as_mut() {
+                panic::catch_panic("A change subscription", || callback());
+            }
+        }
+    }
+}
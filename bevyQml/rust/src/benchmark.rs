@@ -0,0 +1,603 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Benchmarks and soak tests of standard stress scenes, run from QML with
+//! the `BenchmarkController`, e.g. to qualify the hardware of embedded
+//! devices.
+//!
+//! A [BenchmarkScenario] is a JSON object naming one of the [StressScene]s,
+//! how many objects it spawns and how long it runs:
+//!
+//! ```json
+//! { "name": "10k cubes", "scene": "cubes", "count": 10000, "durationSeconds": 60 }
+//! ```
+//!
+//! [start_benchmark] spawns the scene with a camera looking at it, and lets
+//! it run through the warmup, while pipelines compile and meshes upload,
+//! before it records the time of every frame. Every sample interval the
+//! frame time percentiles of the interval are kept with the
+//! [MemoryDiagnostics] of the world, so soak tests running for hours show
+//! whether frames slow down or memory grows. Once the scenario is over the
+//! scene is despawned and the [BenchmarkReport] waits in [QmlBenchmark].
+
+use std::time::Duration;
+
+use bevy::{prelude::*, render::renderer::RenderAdapterInfo};
+use serde::{Deserialize, Serialize};
+
+use crate::{diagnostics::percentile, memory::MemoryDiagnostics, render::QmlView};
+
+/// The most objects a scenario can ask for
+const MAX_COUNT: u32 = 1_000_000;
+
+/// How fast the objects of the scenes spin, in radians per second
+const SPIN_SPEED: f32 = 0.5;
+
+/// A standard scene stressing one part of the renderer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StressScene {
+    /// Spinning opaque cubes sharing a few materials, lit by a directional
+    /// light, which stresses transforms, culling and batching
+    #[default]
+    Cubes,
+    /// Overlapping alpha blended spheres, which are sorted and drawn one by
+    /// one, and stress the fill rate
+    Transparent,
+    /// Point lights over a floor with a grid of cubes, which stresses
+    /// clustered lighting and, with shadows, the shadow passes
+    Lights,
+}
+
+/// What a benchmark runs, read from JSON with camel case keys
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BenchmarkScenario {
+    pub name: String,
+    pub scene: StressScene,
+    /// How many cubes, spheres or lights the scene spawns
+    pub count: u32,
+    /// Whether the lights cast shadows
+    pub shadows: bool,
+    /// How long the scene runs before frames are recorded
+    pub warmup_seconds: f64,
+    /// How long frames are recorded
+    pub duration_seconds: f64,
+    /// How often the frame times and the memory are summed up in the report
+    pub sample_interval_seconds: f64,
+    /// The view of the `BevyQuickItem` the camera renders to, the default
+    /// item without one
+    pub view: String,
+}
+
+impl Default for BenchmarkScenario {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            scene: StressScene::default(),
+            count: 1000,
+            shadows: true,
+            warmup_seconds: 2.0,
+            duration_seconds: 10.0,
+            sample_interval_seconds: 1.0,
+            view: String::new(),
+        }
+    }
+}
+
+impl BenchmarkScenario {
+    /// Read the scenario from JSON, leaving out keys keeps their defaults
+    pub fn from_json(json: &[u8]) -> Result<Self, String> {
+        let scenario: Self = serde_json::from_slice(json)
+            .map_err(|error| format!("Invalid benchmark scenario: {error}"))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let positive = |seconds: f64| seconds.is_finite() && seconds > 0.0;
+        if !positive(self.duration_seconds) || !positive(self.sample_interval_seconds) {
+            return Err("The duration and sample interval need to be positive".to_owned());
+        }
+        if !self.warmup_seconds.is_finite() || self.warmup_seconds < 0.0 {
+            return Err("The warmup cannot be negative".to_owned());
+        }
+        if self.count > MAX_COUNT {
+            return Err(format!("A scene holds at most {MAX_COUNT} objects"));
+        }
+        Ok(())
+    }
+}
+
+/// Percentiles and extremes of frame times in milliseconds
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct FrameTimeStats {
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl FrameTimeStats {
+    fn of(frame_times: &[f32]) -> Self {
+        let mut sorted: Vec<f64> = frame_times.iter().map(|&time| time.into()).collect();
+        if sorted.is_empty() {
+            return Self::default();
+        }
+        sorted.sort_by(f64::total_cmp);
+        Self {
+            min: sorted[0],
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p50: percentile(&sorted, 0.5),
+            p95: percentile(&sorted, 0.95),
+            p99: percentile(&sorted, 0.99),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+/// The frames of one sample interval of a benchmark
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkSample {
+    /// When the interval ended, since frames were first recorded
+    pub seconds: f64,
+    pub frames: usize,
+    pub frame_time: FrameTimeStats,
+    /// [None] without the [crate::memory::QmlMemoryDiagnosticsPlugin]
+    pub memory: Option<MemoryDiagnostics>,
+}
+
+/// The outcome of a benchmark, serialized to JSON for QML
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkReport {
+    pub scenario: BenchmarkScenario,
+    /// The name and backend of the graphics adapter
+    pub adapter: Option<String>,
+    pub frames: usize,
+    pub seconds: f64,
+    /// Frames per second over the whole run
+    pub fps: f64,
+    pub frame_time: FrameTimeStats,
+    pub samples: Vec<BenchmarkSample>,
+}
+
+impl BenchmarkReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("benchmark reports serialize to JSON")
+    }
+}
+
+/// The benchmark running in the world, and the report of the last one
+#[derive(Resource, Default)]
+pub struct QmlBenchmark {
+    run: Option<BenchmarkRun>,
+    report: Option<BenchmarkReport>,
+}
+
+impl QmlBenchmark {
+    pub fn is_running(&self) -> bool {
+        self.run.is_some()
+    }
+
+    /// How far the running benchmark got, from 0 to 1 including its warmup
+    pub fn progress(&self) -> f64 {
+        self.run.as_ref().map_or(0.0, |run| {
+            let total = run.scenario.warmup_seconds + run.scenario.duration_seconds;
+            (run.elapsed.as_secs_f64() / total).min(1.0)
+        })
+    }
+
+    /// The report of the benchmark which finished last, once
+    pub fn take_report(&mut self) -> Option<BenchmarkReport> {
+        self.report.take()
+    }
+}
+
+struct BenchmarkRun {
+    scenario: BenchmarkScenario,
+    /// The camera and everything else the scene spawned
+    entities: Vec<Entity>,
+    elapsed: Duration,
+    /// How long the frames since the warmup took
+    recorded: Duration,
+    /// The frame times in milliseconds since the warmup
+    frame_times: Vec<f32>,
+    /// Where the frames of the current sample interval start
+    sample_start: usize,
+    samples: Vec<BenchmarkSample>,
+}
+
+impl BenchmarkRun {
+    fn sample(&mut self, memory: Option<&MemoryDiagnostics>) {
+        let frames = &self.frame_times[self.sample_start..];
+        self.samples.push(BenchmarkSample {
+            seconds: self.recorded.as_secs_f64(),
+            frames: frames.len(),
+            frame_time: FrameTimeStats::of(frames),
+            memory: memory.cloned(),
+        });
+        self.sample_start = self.frame_times.len();
+    }
+
+    fn report(self, adapter: Option<String>) -> BenchmarkReport {
+        let seconds = self.recorded.as_secs_f64();
+        BenchmarkReport {
+            adapter,
+            frames: self.frame_times.len(),
+            seconds,
+            fps: self.frame_times.len() as f64 / seconds.max(f64::EPSILON),
+            frame_time: FrameTimeStats::of(&self.frame_times),
+            samples: self.samples,
+            scenario: self.scenario,
+        }
+    }
+}
+
+/// Spins the objects of the stress scenes
+#[derive(Component)]
+struct BenchmarkSpin;
+
+pub struct QmlBenchmarkPlugin;
+
+impl Plugin for QmlBenchmarkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QmlBenchmark>()
+            .add_systems(Update, run_benchmark);
+    }
+}
+
+/// Spawn the scene of the scenario and start recording once it warmed up,
+/// stopping a benchmark which is running already
+pub fn start_benchmark(world: &mut World, scenario: BenchmarkScenario) -> Result<(), String> {
+    scenario.validate()?;
+    if !world.contains_resource::<Assets<Mesh>>() {
+        return Err("Benchmarks need an app which renders".to_owned());
+    }
+    cancel_benchmark(world);
+
+    let entities = spawn_scene(world, &scenario);
+    world.resource_mut::<QmlBenchmark>().run = Some(BenchmarkRun {
+        scenario,
+        entities,
+        elapsed: Duration::ZERO,
+        recorded: Duration::ZERO,
+        frame_times: Vec::new(),
+        sample_start: 0,
+        samples: Vec::new(),
+    });
+    Ok(())
+}
+
+/// Stop the running benchmark without a report, returns whether one ran
+pub fn cancel_benchmark(world: &mut World) -> bool {
+    let Some(run) = world.resource_mut::<QmlBenchmark>().run.take() else {
+        return false;
+    };
+    despawn_scene(world, &run.entities);
+    true
+}
+
+fn despawn_scene(world: &mut World, entities: &[Entity]) {
+    for &entity in entities {
+        if let Some(entity) = world.get_entity_mut(entity) {
+            entity.despawn_recursive();
+        }
+    }
+}
+
+fn spawn_scene(world: &mut World, scenario: &BenchmarkScenario) -> Vec<Entity> {
+    let count = scenario.count as usize;
+    // The objects fill a cube, or a square on the floor, two units apart
+    let planar = scenario.scene == StressScene::Lights;
+    let side = if planar {
+        (count as f32).sqrt().ceil().max(1.0) as usize
+    } else {
+        (count as f32).cbrt().ceil().max(1.0) as usize
+    };
+    let extent = side as f32 * 2.0;
+    let center = (side as f32 - 1.0) / 2.0;
+    let grid = move |index: usize| {
+        let x = (index % side) as f32 - center;
+        let z = (index / side % side) as f32 - center;
+        let y = if planar {
+            0.0
+        } else {
+            (index / (side * side)) as f32 - center
+        };
+        Vec3::new(x, y, z) * 2.0
+    };
+
+    let mut meshes = world.resource_mut::<Assets<Mesh>>();
+    let cube = meshes.add(Cuboid::from_length(1.0));
+    let sphere = meshes.add(Sphere::new(0.75));
+    let floor = meshes.add(Plane3d::default().mesh().size(extent + 4.0, extent + 4.0));
+
+    let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+    // A few shared materials keep meshes batched
+    let opaque: Vec<_> = (0..8)
+        .map(|hue| materials.add(Color::hsl(hue as f32 * 45.0, 0.6, 0.5)))
+        .collect();
+    let transparent: Vec<_> = (0..8)
+        .map(|hue| {
+            materials.add(StandardMaterial {
+                base_color: Color::hsla(hue as f32 * 45.0, 0.6, 0.5, 0.3),
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            })
+        })
+        .collect();
+    let ground = materials.add(Color::srgb(0.4, 0.4, 0.4));
+
+    let mut camera = world.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, extent * 0.8, extent * 1.6)
+            .looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+    if !scenario.view.is_empty() {
+        camera.insert(QmlView(scenario.view.clone()));
+    }
+    let mut entities = vec![camera.id()];
+
+    match scenario.scene {
+        StressScene::Cubes => {
+            entities.push(world.spawn(directional_light(scenario.shadows)).id());
+            entities.extend((0..count).map(|index| {
+                world
+                    .spawn((
+                        PbrBundle {
+                            mesh: cube.clone(),
+                            material: opaque[index % opaque.len()].clone(),
+                            transform: Transform::from_translation(grid(index)),
+                            ..default()
+                        },
+                        BenchmarkSpin,
+                    ))
+                    .id()
+            }));
+        }
+        StressScene::Transparent => {
+            entities.push(world.spawn(directional_light(scenario.shadows)).id());
+            entities.extend((0..count).map(|index| {
+                world
+                    .spawn((
+                        PbrBundle {
+                            mesh: sphere.clone(),
+                            material: transparent[index % transparent.len()].clone(),
+                            transform: Transform::from_translation(grid(index)),
+                            ..default()
+                        },
+                        BenchmarkSpin,
+                    ))
+                    .id()
+            }));
+        }
+        StressScene::Lights => {
+            entities.push(
+                world
+                    .spawn(PbrBundle {
+                        mesh: floor,
+                        material: ground,
+                        ..default()
+                    })
+                    .id(),
+            );
+            entities.extend((0..count).map(|index| {
+                let position = grid(index);
+                world
+                    .spawn((
+                        PbrBundle {
+                            mesh: cube.clone(),
+                            material: opaque[index % opaque.len()].clone(),
+                            transform: Transform::from_translation(position.with_y(0.5)),
+                            ..default()
+                        },
+                        BenchmarkSpin,
+                    ))
+                    .with_children(|cube| {
+                        cube.spawn(PointLightBundle {
+                            point_light: PointLight {
+                                color: Color::hsl(index as f32 * 37.0 % 360.0, 0.8, 0.6),
+                                range: 4.0,
+                                shadows_enabled: scenario.shadows,
+                                ..default()
+                            },
+                            transform: Transform::from_xyz(0.0, 1.5, 0.0),
+                            ..default()
+                        });
+                    })
+                    .id()
+            }));
+        }
+    }
+    entities
+}
+
+fn directional_light(shadows: bool) -> DirectionalLightBundle {
+    DirectionalLightBundle {
+        directional_light: DirectionalLight {
+            shadows_enabled: shadows,
+            ..default()
+        },
+        transform: Transform::from_xyz(1.0, 2.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    }
+}
+
+fn run_benchmark(
+    mut commands: Commands,
+    mut benchmark: ResMut<QmlBenchmark>,
+    time: Res<Time<Real>>,
+    memory: Option<Res<MemoryDiagnostics>>,
+    adapter: Option<Res<RenderAdapterInfo>>,
+    mut spinning: Query<&mut Transform, With<BenchmarkSpin>>,
+) {
+    if !benchmark.is_running() {
+        return;
+    }
+    let Some(run) = benchmark.run.as_mut() else {
+        return;
+    };
+    let delta = time.delta();
+    for mut transform in &mut spinning {
+        transform.rotate_y(delta.as_secs_f32() * SPIN_SPEED);
+    }
+
+    let warmed_up = run.elapsed.as_secs_f64() > run.scenario.warmup_seconds;
+    run.elapsed += delta;
+    // The first frame after the warmup still waited on it
+    if !warmed_up {
+        return;
+    }
+    run.recorded += delta;
+    run.frame_times.push(delta.as_secs_f32() * 1000.0);
+
+    let recorded = run.recorded.as_secs_f64();
+    let interval = run.scenario.sample_interval_seconds;
+    if recorded - run.samples.len() as f64 * interval >= interval {
+        run.sample(memory.as_deref());
+    }
+    if recorded < run.scenario.duration_seconds {
+        return;
+    }
+
+    let Some(mut run) = benchmark.run.take() else {
+        return;
+    };
+    if run.sample_start < run.frame_times.len() {
+        run.sample(memory.as_deref());
+    }
+    for entity in run.entities.drain(..) {
+        if let Some(entity) = commands.get_entity(entity) {
+            entity.despawn_recursive();
+        }
+    }
+    let adapter = adapter.map(|info| format!("{} ({:?})", info.name, info.backend));
+    benchmark.report = Some(run.report(adapter));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_scenarios_with_defaults() {
+        let scenario =
+            BenchmarkScenario::from_json(br#"{"name": "lights", "scene": "lights", "count": 64}"#)
+                .unwrap();
+        assert_eq!(
+            scenario,
+            BenchmarkScenario {
+                name: "lights".to_owned(),
+                scene: StressScene::Lights,
+                count: 64,
+                ..default()
+            }
+        );
+        assert_eq!(
+            BenchmarkScenario::from_json(b"{}").unwrap(),
+            BenchmarkScenario::default()
+        );
+    }
+
+    #[test]
+    fn reads_camel_case_keys() {
+        let scenario = BenchmarkScenario::from_json(
+            br#"{"warmupSeconds": 0, "durationSeconds": 5, "sampleIntervalSeconds": 0.5}"#,
+        )
+        .unwrap();
+        assert_eq!(scenario.warmup_seconds, 0.0);
+        assert_eq!(scenario.duration_seconds, 5.0);
+        assert_eq!(scenario.sample_interval_seconds, 0.5);
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        assert!(BenchmarkScenario::from_json(b"{").is_err());
+        assert!(BenchmarkScenario::from_json(br#"{"scene": "fog"}"#).is_err());
+        assert!(BenchmarkScenario::from_json(br#"{"count": -1}"#).is_err());
+    }
+
+    #[test]
+    fn validates_durations() {
+        let valid = BenchmarkScenario::default();
+        assert_eq!(valid.validate(), Ok(()));
+        for duration_seconds in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let scenario = BenchmarkScenario {
+                duration_seconds,
+                ..valid.clone()
+            };
+            assert!(scenario.validate().is_err());
+            let scenario = BenchmarkScenario {
+                sample_interval_seconds: duration_seconds,
+                ..valid.clone()
+            };
+            assert!(scenario.validate().is_err());
+        }
+        for warmup_seconds in [-0.5, f64::NAN, f64::INFINITY] {
+            let scenario = BenchmarkScenario {
+                warmup_seconds,
+                ..valid.clone()
+            };
+            assert!(scenario.validate().is_err());
+        }
+        let scenario = BenchmarkScenario {
+            warmup_seconds: 0.0,
+            ..valid
+        };
+        assert_eq!(scenario.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validates_counts() {
+        let scenario = BenchmarkScenario {
+            count: MAX_COUNT,
+            ..default()
+        };
+        assert_eq!(scenario.validate(), Ok(()));
+        let scenario = BenchmarkScenario {
+            count: MAX_COUNT + 1,
+            ..default()
+        };
+        assert!(scenario.validate().is_err());
+    }
+
+    #[test]
+    fn stats_of_no_frames_are_zero() {
+        assert_eq!(FrameTimeStats::of(&[]), FrameTimeStats::default());
+    }
+
+    #[test]
+    fn stats_of_frame_times() {
+        let mut frame_times: Vec<f32> = (1..=100).map(|time| time as f32).collect();
+        frame_times.reverse();
+        assert_eq!(
+            FrameTimeStats::of(&frame_times),
+            FrameTimeStats {
+                min: 1.0,
+                mean: 50.5,
+                p50: 50.0,
+                p95: 95.0,
+                p99: 99.0,
+                max: 100.0,
+            }
+        );
+    }
+
+    #[test]
+    fn stats_of_a_single_frame() {
+        assert_eq!(
+            FrameTimeStats::of(&[16.5]),
+            FrameTimeStats {
+                min: 16.5,
+                mean: 16.5,
+                p50: 16.5,
+                p95: 16.5,
+                p99: 16.5,
+                max: 16.5,
+            }
+        );
+    }
+}
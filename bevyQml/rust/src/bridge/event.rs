@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Forwards Bevy events to QML, see [crate::cxxqt_bevy_event_listener] for
+//! the QML side.
+
+use std::{collections::VecDeque, marker::PhantomData};
+
+use bevy::{
+    prelude::*,
+    reflect::{GetTypeRegistration, TypePath},
+};
+use cxx_qt_lib::QVariant;
+
+use crate::variant;
+
+/// How many forwarded events are kept for listeners that have not caught up yet
+const RETAINED_EVENTS: usize = 1024;
+
+/// Emits a Qt signal for every event of type `E`
+///
+/// `BevyEventListener` elements whose `event` is the short type path of `E`
+/// emit `triggered` with the reflected fields of each event as the payload.
+///
+/// ```ignore
+/// #[derive(Event, Reflect, Clone)]
+/// struct EnemyDied {
+///     score: u32,
+/// }
+///
+/// app.add_event::<EnemyDied>()
+///     .add_plugins(EventSignalBridge::<EnemyDied>::default());
+/// ```
+///
+/// ```qml
+/// BevyEventListener {
+///     event: "EnemyDied"
+///     onTriggered: (payload) => score.text = payload.score
+/// }
+/// ```
+pub struct EventSignalBridge<E>(PhantomData<fn() -> E>);
+
+impl<E> Default for EventSignalBridge<E> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<E> Plugin for EventSignalBridge<E>
+where
+    E: Event + Reflect + GetTypeRegistration + TypePath,
+{
+    fn build(&self, app: &mut App) {
+        app.register_type::<E>()
+            .init_resource::<ForwardedEvents>()
+            .add_systems(Last, forward_events::<E>);
+    }
+}
+
+/// A Bevy event forwarded to QML
+#[derive(Clone)]
+pub struct ForwardedEvent {
+    /// Increases by one for every forwarded event
+    pub sequence: u64,
+    /// The short type path of the event
+    pub name: &'static str,
+    pub payload: Vec<(String, QVariant)>,
+}
+
+/// The most recent events forwarded by every [EventSignalBridge]
+#[derive(Resource, Default)]
+pub struct ForwardedEvents {
+    events: VecDeque<ForwardedEvent>,
+    next_sequence: u64,
+}
+
+impl ForwardedEvents {
+    /// The sequence number the next forwarded event will get
+    pub fn next_sequence(&self) -> u64 {
+        self.next_sequence
+    }
+
+    /// The retained events from the given sequence number onwards
+    pub fn since(&self, sequence: u64) -> impl Iterator<Item = &ForwardedEvent> {
+        self.events
+            .iter()
+            .skip_while(move |event| event.sequence < sequence)
+    }
+
+    fn push(&mut self, name: &'static str, payload: Vec<(String, QVariant)>) {
+        if self.events.len() == RETAINED_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(ForwardedEvent {
+            sequence: self.next_sequence,
+            name,
+            payload,
+        });
+        self.next_sequence += 1;
+    }
+}
+
+fn forward_events<E: Event + Reflect + TypePath>(
+    mut events: EventReader<E>,
+    mut forwarded: ResMut<ForwardedEvents>,
+) {
+    for event in events.read() {
+        forwarded.push(E::short_type_path(), variant::struct_fields(event));
+    }
+}
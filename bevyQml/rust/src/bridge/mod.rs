@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Generic bridges which expose parts of the Bevy world to QML without a
+//! hand-written QObject per type.
+
+mod event;
+mod resource;
+mod send_event;
+mod state;
+
+pub use event::{EventSignalBridge, ForwardedEvent, ForwardedEvents};
+pub use resource::{resource_fields, set_resource_field, BridgedResources, ResourceBridge};
+pub use send_event::{send_named_event, QmlEventRegistry, SendEventBridge};
+pub use state::{request_state, BridgedStates, QmlStateTransition, StateBridge};
@@ -0,0 +1,131 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Mirrors reflected resources into QML, see
+//! [crate::cxxqt_bevy_resource] for the QML side.
+
+use std::{any::TypeId, marker::PhantomData};
+
+use bevy::{
+    ecs::reflect::ReflectResource,
+    prelude::*,
+    reflect::{GetTypeRegistration, TypePath},
+    utils::HashMap,
+};
+use cxx_qt_lib::QVariant;
+
+use crate::variant;
+
+/// Exposes every field of the resource `T` to QML
+///
+/// The resource has to reflect `Resource`. QML then reads and writes its
+/// fields through a `BevyResource` element whose `name` is the short type
+/// path of `T`.
+///
+/// ```ignore
+/// #[derive(Resource, Reflect, Default)]
+/// #[reflect(Resource)]
+/// struct GameSettings {
+///     volume: f32,
+///     show_fps: bool,
+/// }
+///
+/// app.init_resource::<GameSettings>()
+///     .add_plugins(ResourceBridge::<GameSettings>::default());
+/// ```
+///
+/// ```qml
+/// BevyResource {
+///     id: settings
+///     name: "GameSettings"
+/// }
+/// Slider {
+///     value: settings.values.volume
+///     onMoved: settings.setValue("volume", value)
+/// }
+/// ```
+pub struct ResourceBridge<T>(PhantomData<fn() -> T>);
+
+impl<T> Default for ResourceBridge<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Plugin for ResourceBridge<T>
+where
+    T: Resource + Reflect + GetTypeRegistration + TypePath,
+{
+    fn build(&self, app: &mut App) {
+        app.register_type::<T>()
+            .init_resource::<BridgedResources>()
+            .add_systems(Last, track_changes::<T>);
+
+        let mut bridged = app.world_mut().resource_mut::<BridgedResources>();
+        let type_id = TypeId::of::<T>();
+        bridged
+            .names
+            .insert(T::short_type_path().to_owned(), type_id);
+        bridged.names.insert(T::type_path().to_owned(), type_id);
+    }
+}
+
+/// The resources exposed with a [ResourceBridge] and how often they changed
+#[derive(Resource, Default)]
+pub struct BridgedResources {
+    names: HashMap<String, TypeId>,
+    revisions: HashMap<TypeId, u64>,
+}
+
+impl BridgedResources {
+    /// Find a bridged resource by its short or full type path
+    pub fn lookup(&self, name: &str) -> Option<TypeId> {
+        self.names.get(name).copied()
+    }
+
+    /// Increases every time the resource changes
+    pub fn revision(&self, type_id: TypeId) -> u64 {
+        self.revisions.get(&type_id).copied().unwrap_or_default()
+    }
+}
+
+fn track_changes<T: Resource>(resource: Option<Res<T>>, mut bridged: ResMut<BridgedResources>) {
+    if resource.is_some_and(|resource| resource.is_changed()) {
+        *bridged.revisions.entry(TypeId::of::<T>()).or_default() += 1;
+    }
+}
+
+fn reflect_resource(world: &World, type_id: TypeId) -> Option<ReflectResource> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    registry.get_type_data::<ReflectResource>(type_id).cloned()
+}
+
+/// The current fields of a bridged resource
+///
+/// Returns [None] if the resource does not exist in the world.
+pub fn resource_fields(world: &World, type_id: TypeId) -> Option<Vec<(String, QVariant)>> {
+    let reflect = reflect_resource(world, type_id)?;
+    reflect.reflect(world).map(variant::struct_fields)
+}
+
+/// Change a field of a bridged resource
+///
+/// Returns false if the field does not exist or the value does not fit it.
+pub fn set_resource_field(
+    world: &mut World,
+    type_id: TypeId,
+    field: &str,
+    value: &QVariant,
+) -> bool {
+    let Some(reflect) = reflect_resource(world, type_id) else {
+        return false;
+    };
+    let Some(mut resource) = reflect.reflect_mut(world) else {
+        return false;
+    };
+    // Only mark the resource as changed when the field was actually written
+    let applied = variant::apply_struct_field(resource.bypass_change_detection(), field, value);
+    if applied {
+        resource.set_changed();
+    }
+    applied
+}
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Lets QML send Bevy events by name, through `BevyCommands.sendEvent()`.
+
+use std::marker::PhantomData;
+
+use bevy::{prelude::*, reflect::TypePath, utils::HashMap};
+use cxx_qt_lib::QVariant;
+
+use crate::variant;
+
+type SendFn = fn(&mut World, &[(String, QVariant)]) -> Result<(), String>;
+
+/// Allows QML to send events of type `E`, using its short type path as the name
+///
+/// The event starts from its `Default` and the fields given by QML are then
+/// set on it.
+///
+/// ```ignore
+/// #[derive(Event, Reflect, Default)]
+/// struct StartGame {
+///     level: u32,
+/// }
+///
+/// app.add_event::<StartGame>()
+///     .add_plugins(SendEventBridge::<StartGame>::default());
+/// ```
+///
+/// ```qml
+/// Button {
+///     onClicked: BevyCommands.sendEvent("StartGame", { "level": 2 })
+/// }
+/// ```
+pub struct SendEventBridge<E>(PhantomData<fn() -> E>);
+
+impl<E> Default for SendEventBridge<E> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<E> Plugin for SendEventBridge<E>
+where
+    E: Event + Reflect + TypePath + Default,
+{
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QmlEventRegistry>();
+        let mut registry = app.world_mut().resource_mut::<QmlEventRegistry>();
+        registry
+            .0
+            .insert(E::short_type_path().to_owned(), send_event::<E>);
+        registry
+            .0
+            .insert(E::type_path().to_owned(), send_event::<E>);
+    }
+}
+
+/// The events QML may send, by short and full type path
+#[derive(Resource, Default)]
+pub struct QmlEventRegistry(HashMap<String, SendFn>);
+
+impl QmlEventRegistry {
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+}
+
+/// Build the named event from the fields and send it
+pub fn send_named_event(
+    world: &mut World,
+    name: &str,
+    fields: &[(String, QVariant)],
+) -> Result<(), String> {
+    let send = world
+        .get_resource::<QmlEventRegistry>()
+        .and_then(|registry| registry.0.get(name).copied())
+        .ok_or_else(|| format!("{name} is not registered with a SendEventBridge"))?;
+    send(world, fields)
+}
+
+fn send_event<E: Event + Reflect + TypePath + Default>(
+    world: &mut World,
+    fields: &[(String, QVariant)],
+) -> Result<(), String> {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    let mut event = E::default();
+    for (field, value) in fields {
+        variant::apply_struct_field_with(&mut event, field, value, &registry)
+            .map_err(|error| format!("{}.{field}: {error}", E::short_type_path()))?;
+    }
+    world.send_event(event);
+    Ok(())
+}
@@ -0,0 +1,203 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Follows and changes Bevy [States] from QML, see
+//! [crate::cxxqt_bevy_state] for the QML side.
+
+use std::{collections::VecDeque, marker::PhantomData};
+
+use bevy::{
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant, ReflectRef, TypeInfo, TypePath, Typed},
+    state::state::{FreelyMutableState, StateTransitionEvent},
+    utils::HashMap,
+};
+
+type RequestFn = fn(&mut World, &str) -> bool;
+
+/// Exposes the state `S` to QML, using its short type path as the name
+///
+/// The states are named after the variants of `S`, which QML can only ask
+/// for when they are unit variants.
+///
+/// ```ignore
+/// #[derive(States, Reflect, Clone, Debug, Default, PartialEq, Eq, Hash)]
+/// enum GameState {
+///     #[default]
+///     Menu,
+///     Playing,
+///     Paused,
+/// }
+///
+/// app.init_state::<GameState>()
+///     .add_plugins(StateBridge::<GameState>::default());
+/// ```
+///
+/// ```qml
+/// BevyState {
+///     id: gameState
+///     name: "GameState"
+///     onEntered: state => stack.replace(pages[state])
+/// }
+/// Button {
+///     text: "Pause"
+///     onClicked: gameState.requestState("Paused")
+/// }
+/// ```
+pub struct StateBridge<S>(PhantomData<fn() -> S>);
+
+impl<S> Default for StateBridge<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S> Plugin for StateBridge<S>
+where
+    S: FreelyMutableState + Reflect + FromReflect + Typed + TypePath,
+{
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BridgedStates>()
+            .add_systems(Last, track_transitions::<S>);
+
+        let names = match S::type_info() {
+            TypeInfo::Enum(info) => info
+                .variant_names()
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            _ => Vec::new(),
+        };
+        let mut bridged = app.world_mut().resource_mut::<BridgedStates>();
+        let index = bridged.states.len();
+        bridged.states.push(BridgedState {
+            names,
+            current: None,
+            request: request_variant::<S>,
+        });
+        bridged.names.insert(S::short_type_path().to_owned(), index);
+        bridged.names.insert(S::type_path().to_owned(), index);
+    }
+}
+
+/// A change between two states, where [None] stands for the state not
+/// existing
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QmlStateTransition {
+    pub exited: Option<String>,
+    pub entered: Option<String>,
+}
+
+/// How many transitions of the bridged states are kept for QML to catch up
+/// with
+const RETAINED_TRANSITIONS: usize = 64;
+
+struct BridgedState {
+    /// The variants of the state
+    names: Vec<String>,
+    current: Option<String>,
+    request: RequestFn,
+}
+
+/// The states exposed with a [StateBridge] and their recent transitions
+#[derive(Resource, Default)]
+pub struct BridgedStates {
+    names: HashMap<String, usize>,
+    states: Vec<BridgedState>,
+    /// Transitions by the index of their state, with a sequence number that
+    /// increases with every transition
+    transitions: VecDeque<(u64, usize, QmlStateTransition)>,
+    sequence: u64,
+}
+
+impl BridgedStates {
+    /// Find a bridged state by its short or full type path
+    pub fn lookup(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    /// The names of the variants of the state
+    pub fn names(&self, index: usize) -> &[String] {
+        self.states.get(index).map_or(&[], |state| &state.names)
+    }
+
+    /// The variant the state is in, if it exists
+    pub fn current(&self, index: usize) -> Option<&str> {
+        self.states.get(index)?.current.as_deref()
+    }
+
+    /// The sequence number the next transition will get
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// The transitions of the state from a sequence number on
+    ///
+    /// Transitions which happened too long ago are left out.
+    pub fn transitions_since(&self, index: usize, since: u64) -> Vec<QmlStateTransition> {
+        self.transitions
+            .iter()
+            .filter(|(sequence, state, _)| *sequence >= since && *state == index)
+            .map(|(_, _, transition)| transition.clone())
+            .collect()
+    }
+}
+
+/// Ask for a bridged state to change to the variant with the given name in
+/// the next state transition
+///
+/// Returns false if the state is unknown or has no such unit variant.
+pub fn request_state(world: &mut World, index: usize, name: &str) -> bool {
+    let Some(request) = world
+        .get_resource::<BridgedStates>()
+        .and_then(|bridged| bridged.states.get(index))
+        .map(|state| state.request)
+    else {
+        return false;
+    };
+    request(world, name)
+}
+
+fn request_variant<S: FreelyMutableState + FromReflect>(world: &mut World, name: &str) -> bool {
+    let Some(state) = S::from_reflect(&DynamicEnum::new(name, DynamicVariant::Unit)) else {
+        return false;
+    };
+    let Some(mut next) = world.get_resource_mut::<NextState<S>>() else {
+        return false;
+    };
+    next.set(state);
+    true
+}
+
+fn variant_name<S: Reflect>(state: &S) -> Option<String> {
+    match state.reflect_ref() {
+        ReflectRef::Enum(state) => Some(state.variant_name().to_owned()),
+        _ => None,
+    }
+}
+
+fn track_transitions<S: States + Reflect + TypePath>(
+    mut transitions: EventReader<StateTransitionEvent<S>>,
+    state: Option<Res<State<S>>>,
+    mut bridged: ResMut<BridgedStates>,
+) {
+    let Some(index) = bridged.lookup(S::short_type_path()) else {
+        return;
+    };
+    let current = state.and_then(|state| variant_name(state.get()));
+    if bridged.states[index].current != current {
+        bridged.states[index].current = current;
+    }
+
+    for transition in transitions.read() {
+        let transition = QmlStateTransition {
+            exited: transition.exited.as_ref().and_then(variant_name),
+            entered: transition.entered.as_ref().and_then(variant_name),
+        };
+        let sequence = bridged.sequence;
+        bridged.sequence += 1;
+        bridged.transitions.push_back((sequence, index, transition));
+        if bridged.transitions.len() > RETAINED_TRANSITIONS {
+            bridged.transitions.pop_front();
+        }
+    }
+}
@@ -0,0 +1,303 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Camera controllers driven by the input forwarded from QML items.
+//!
+//! An [OrbitCamera] turns the camera it is added to around a target point.
+//! It follows the mouse and the [CameraGesture]s of the item the camera
+//! renders into, so several views each move their own camera. The
+//! `OrbitCameraController` QML element sets it up from QML.
+
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    render::camera::RenderTarget,
+    utils::HashMap,
+    window::CursorMoved,
+};
+
+use crate::{
+    input::touch::{CameraAction, CameraGesture},
+    render::QuickItemTarget,
+};
+
+/// Pixels of a scroll in [MouseScrollUnit::Pixel] that count as one line
+const PIXELS_PER_LINE: f32 = 40.0;
+
+/// Orbits the camera around `target` at `distance`
+///
+/// Yaw turns around the Y axis and pitch tilts the camera up or down, with
+/// positive pitch looking down onto the target. Angles are in radians.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default)]
+pub struct OrbitCamera {
+    pub target: Vec3,
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// How much of the remaining way to the goal the camera keeps every
+    /// sixtieth of a second, 0 moves it straight there
+    pub damping: f32,
+    /// Radians per logical pixel of cursor movement
+    pub orbit_speed: f32,
+    /// Fraction of the distance per logical pixel of cursor movement
+    pub pan_speed: f32,
+    /// Relative change of the distance per line scrolled
+    pub zoom_speed: f32,
+    pub orbit_button: Option<MouseButton>,
+    pub pan_button: Option<MouseButton>,
+    /// Whether the mouse wheel zooms
+    pub wheel_zoom: bool,
+    /// Whether the camera follows input at all, it still moves to its goal
+    pub enabled: bool,
+    /// Where the camera currently is on its way to the goal
+    #[reflect(ignore)]
+    current: Option<OrbitPose>,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self {
+            target: Vec3::ZERO,
+            distance: 10.0,
+            yaw: 0.0,
+            pitch: 0.3,
+            min_pitch: -1.5,
+            max_pitch: 1.5,
+            min_distance: 0.5,
+            max_distance: 500.0,
+            damping: 0.0,
+            orbit_speed: 0.01,
+            pan_speed: 0.002,
+            zoom_speed: 0.1,
+            orbit_button: Some(MouseButton::Left),
+            pan_button: Some(MouseButton::Right),
+            wheel_zoom: true,
+            enabled: true,
+            current: None,
+        }
+    }
+}
+
+impl OrbitCamera {
+    /// Orbit around the target from where the eye is
+    pub fn looking_at(eye: Vec3, target: Vec3) -> Self {
+        let offset = eye - target;
+        let distance = offset.length().max(f32::EPSILON);
+        Self {
+            target,
+            distance,
+            yaw: offset.x.atan2(offset.z),
+            pitch: (offset.y / distance).clamp(-1.0, 1.0).asin(),
+            ..default()
+        }
+    }
+
+    /// The rotation of the camera for the goal of the orbit
+    pub fn rotation(&self) -> Quat {
+        OrbitPose::from(self).rotation()
+    }
+
+    /// Take over the settings and goal of another orbit, keeping where the
+    /// camera currently is so it moves to the new goal smoothly
+    pub fn apply(&mut self, other: OrbitCamera) {
+        let current = self.current;
+        *self = OrbitCamera { current, ..other };
+        self.clamp();
+    }
+
+    /// Keep the angles and distance within their limits
+    pub fn clamp(&mut self) {
+        self.pitch = self.pitch.clamp(self.min_pitch, self.max_pitch);
+        self.distance = self
+            .distance
+            .clamp(self.min_distance, self.max_distance.max(self.min_distance));
+    }
+
+    fn orbit(&mut self, delta: Vec2) {
+        self.yaw -= delta.x * self.orbit_speed;
+        self.pitch += delta.y * self.orbit_speed;
+    }
+
+    fn pan(&mut self, delta: Vec2) {
+        let rotation = self.rotation();
+        let scale = self.distance * self.pan_speed;
+        self.target += (rotation * Vec3::NEG_X * delta.x + rotation * Vec3::Y * delta.y) * scale;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct OrbitPose {
+    target: Vec3,
+    distance: f32,
+    yaw: f32,
+    pitch: f32,
+}
+
+impl From<&OrbitCamera> for OrbitPose {
+    fn from(orbit: &OrbitCamera) -> Self {
+        Self {
+            target: orbit.target,
+            distance: orbit.distance,
+            yaw: orbit.yaw,
+            pitch: orbit.pitch,
+        }
+    }
+}
+
+impl OrbitPose {
+    fn rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.yaw) * Quat::from_rotation_x(-self.pitch)
+    }
+
+    fn transform(&self) -> Transform {
+        let rotation = self.rotation();
+        Transform {
+            translation: self.target + rotation * Vec3::Z * self.distance,
+            rotation,
+            ..default()
+        }
+    }
+
+    fn lerp(self, goal: Self, t: f32) -> Self {
+        Self {
+            target: self.target.lerp(goal.target, t),
+            distance: self.distance + (goal.distance - self.distance) * t,
+            yaw: self.yaw + (goal.yaw - self.yaw) * t,
+            pitch: self.pitch + (goal.pitch - self.pitch) * t,
+        }
+    }
+}
+
+pub struct QmlCameraPlugin;
+
+impl Plugin for QmlCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<OrbitCamera>().add_systems(
+            Update,
+            (orbit_camera_input, move_orbit_cameras)
+                .chain()
+                .in_set(QmlCameraSystems),
+        );
+    }
+}
+
+/// The systems moving the cameras, which run in [Update]
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct QmlCameraSystems;
+
+#[derive(Default)]
+struct WindowInput {
+    cursor_delta: Vec2,
+    scroll: f32,
+    gestures: Vec<(CameraAction, Vec2)>,
+}
+
+fn orbit_camera_input(
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut cursor_moved: EventReader<CursorMoved>,
+    mut wheel: EventReader<MouseWheel>,
+    mut gestures: EventReader<CameraGesture>,
+    mut last_cursor: Local<HashMap<Entity, Vec2>>,
+    targets: Query<(Entity, &QuickItemTarget)>,
+    mut cameras: Query<(&Camera, &mut OrbitCamera)>,
+) {
+    let mut input = HashMap::<Entity, WindowInput>::default();
+    for event in cursor_moved.read() {
+        if let Some(last) = last_cursor.insert(event.window, event.position) {
+            input.entry(event.window).or_default().cursor_delta += event.position - last;
+        }
+    }
+    for event in wheel.read() {
+        let lines = match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        };
+        input.entry(event.window).or_default().scroll += lines;
+    }
+    for gesture in gestures.read() {
+        input
+            .entry(gesture.window)
+            .or_default()
+            .gestures
+            .push((gesture.action, gesture.delta));
+    }
+    last_cursor.retain(|window, _| targets.contains(*window));
+    if input.is_empty() {
+        return;
+    }
+
+    for (camera, mut orbit) in &mut cameras {
+        if !orbit.enabled {
+            continue;
+        }
+        let RenderTarget::Image(image) = &camera.target else {
+            continue;
+        };
+        let Some(input) = targets
+            .iter()
+            .find(|(_, target)| target.image == *image)
+            .and_then(|(window, _)| input.get(&window))
+        else {
+            continue;
+        };
+
+        let orbit = &mut *orbit;
+        if orbit
+            .orbit_button
+            .is_some_and(|button| buttons.pressed(button))
+        {
+            orbit.orbit(input.cursor_delta);
+        } else if orbit
+            .pan_button
+            .is_some_and(|button| buttons.pressed(button))
+        {
+            orbit.pan(input.cursor_delta);
+        }
+        if orbit.wheel_zoom && input.scroll != 0.0 {
+            orbit.distance *= (-input.scroll * orbit.zoom_speed).exp();
+        }
+        for &(action, delta) in &input.gestures {
+            match action {
+                CameraAction::Orbit => orbit.orbit(delta),
+                CameraAction::Pan => orbit.pan(delta),
+                CameraAction::Zoom => orbit.distance /= (1.0 + delta.x).max(f32::EPSILON),
+                CameraAction::Roll | CameraAction::None => {}
+            }
+        }
+        orbit.clamp();
+    }
+}
+
+fn move_orbit_cameras(time: Res<Time>, mut cameras: Query<(&mut OrbitCamera, &mut Transform)>) {
+    for (mut orbit, mut transform) in &mut cameras {
+        let goal = OrbitPose::from(&*orbit);
+        let current = match orbit.current {
+            Some(current) if orbit.damping > 0.0 => {
+                let keep = orbit
+                    .damping
+                    .clamp(0.0, 0.999)
+                    .powf(time.delta_seconds() * 60.0);
+                let pose = current.lerp(goal, 1.0 - keep);
+                // Settle once the remaining way is too small to see
+                if pose.target.distance(goal.target) < 1e-4
+                    && (pose.distance - goal.distance).abs() < 1e-4
+                    && (pose.yaw - goal.yaw).abs() < 1e-5
+                    && (pose.pitch - goal.pitch).abs() < 1e-5
+                {
+                    goal
+                } else {
+                    pose
+                }
+            }
+            _ => goal,
+        };
+        if orbit.current != Some(current) {
+            orbit.current = Some(current);
+            *transform = current.transform();
+        }
+    }
+}
@@ -0,0 +1,264 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! The clipboard of Qt, shared by Bevy and QML.
+//!
+//! [QtClipboard] mirrors the text and image on the clipboard into the main
+//! world after every update in which they changed, and hands what systems
+//! put on it to Qt after the update:
+//!
+//! ```ignore
+//! fn copy_name(names: Query<&Name, With<Selected>>, mut clipboard: ResMut<QtClipboard>) {
+//!     if let Some(name) = names.iter().next() {
+//!         clipboard.set_text(name.as_str());
+//!     }
+//! }
+//! ```
+//!
+//! QML reaches the clipboard through the `ClipboardBridge` singleton of
+//! [crate::cxxqt_bevy_clipboard], which also copies entities along with
+//! their children as RON scenes with [entities_to_ron], pastes them with
+//! [spawn_ron] and duplicates them with [duplicate_entities]. Pasting and
+//! duplicating can be undone, see [crate::undo].
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_clipboard")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qimage.h");
+        /// An alias to the QImage type
+        type QImage = cxx_qt_lib::QImage;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/clipboard.h");
+
+        #[doc(hidden)]
+        #[rust_name = "clipboard_revision"]
+        fn clipboardRevision() -> u64;
+
+        #[doc(hidden)]
+        #[rust_name = "clipboard_text"]
+        fn clipboardText() -> QString;
+
+        #[doc(hidden)]
+        #[rust_name = "set_clipboard_text"]
+        fn setClipboardText(text: &QString);
+
+        #[doc(hidden)]
+        #[rust_name = "clipboard_image"]
+        fn clipboardImage() -> QImage;
+
+        #[doc(hidden)]
+        #[rust_name = "set_clipboard_image"]
+        fn setClipboardImage(image: &QImage);
+    }
+}
+
+use bevy::{prelude::*, render::render_asset::RenderAssetUsages, scene::serde::SceneDeserializer};
+use cxx_qt_lib::{QImage, QString};
+use serde::de::DeserializeSeed;
+
+use crate::{
+    asset, image,
+    runtime::{self, UpdateListener},
+    undo::{MacroCommand, QmlUndoStack, SpawnEntity},
+};
+
+/// The contents of the clipboard, as of the last update
+#[derive(Resource, Default)]
+pub struct QtClipboard {
+    text: String,
+    image: Option<Image>,
+    /// What to put on the clipboard after the update
+    pending: Option<ClipboardContent>,
+    /// The revision of the clipboard the contents were read at
+    revision: u64,
+}
+
+enum ClipboardContent {
+    Text(String),
+    Image(Image),
+}
+
+impl QtClipboard {
+    /// The text on the clipboard, empty if there is none
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The image on the clipboard, if there is one
+    pub fn image(&self) -> Option<&Image> {
+        self.image.as_ref()
+    }
+
+    /// Put text on the clipboard, replacing what was on it
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        let text = text.into();
+        self.text.clone_from(&text);
+        self.image = None;
+        self.pending = Some(ClipboardContent::Text(text));
+    }
+
+    /// Put an image on the clipboard, replacing what was on it
+    ///
+    /// Only images [image::qimage_from_image] converts make it to Qt.
+    pub fn set_image(&mut self, image: Image) {
+        self.text.clear();
+        self.image = Some(image.clone());
+        self.pending = Some(ClipboardContent::Image(image));
+    }
+}
+
+pub struct QmlClipboardPlugin;
+
+impl Plugin for QmlClipboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QtClipboard>()
+            .insert_non_send_resource(QmlClipboardListener(runtime::on_update(sync_clipboard)));
+    }
+}
+
+/// Syncs the clipboard after every update for as long as the app exists
+struct QmlClipboardListener(#[allow(dead_code)] UpdateListener);
+
+/// Hand what systems copied to Qt, and read the clipboard when it changed
+fn sync_clipboard() {
+    runtime::with_world(|world| {
+        let Some(mut clipboard) = world.get_resource_mut::<QtClipboard>() else {
+            return;
+        };
+        match clipboard.bypass_change_detection().pending.take() {
+            Some(ClipboardContent::Text(text)) => set_text(&text),
+            Some(ClipboardContent::Image(image)) => match image::qimage_from_image(&image) {
+                Some(qimage) => set_image(&qimage),
+                None => warn!("Cannot copy an image of {:?}", image.texture_descriptor.format),
+            },
+            None => {}
+        }
+
+        let revision = ffi::clipboard_revision();
+        if clipboard.revision != revision {
+            clipboard.text = text();
+            clipboard.image = image::image_from_qimage(&image(), RenderAssetUsages::default());
+            clipboard.revision = revision;
+        }
+    });
+}
+
+/// The text on the clipboard, this must be called from the GUI thread
+pub fn text() -> String {
+    ffi::clipboard_text().to_string()
+}
+
+pub fn set_text(text: &str) {
+    ffi::set_clipboard_text(&QString::from(text));
+}
+
+/// The image on the clipboard, a null image if there is none
+pub fn image() -> QImage {
+    ffi::clipboard_image()
+}
+
+pub fn set_image(image: &QImage) {
+    ffi::set_clipboard_image(image);
+}
+
+/// Serialize the entities, their descendants and their reflected components
+/// as a RON scene
+///
+/// The top-most of the entities leave their parents behind, so they can be
+/// pasted anywhere.
+pub fn entities_to_ron(
+    world: &World,
+    entities: impl IntoIterator<Item = Entity>,
+) -> Result<String, String> {
+    let (scene, _) = asset::extract_trees(world, entities);
+    let registry = world.resource::<AppTypeRegistry>().read();
+    scene
+        .serialize(&registry)
+        .map_err(|error| error.to_string())
+}
+
+/// Spawn the entities of a RON scene below the parent, or at the top level
+/// without one, returns the new top-most entities
+pub fn spawn_ron(
+    world: &mut World,
+    ron: &str,
+    parent: Option<Entity>,
+) -> Result<Vec<Entity>, String> {
+    if let Some(parent) = parent.filter(|parent| world.get_entity(*parent).is_none()) {
+        return Err(format!("{parent:?} does not exist"));
+    }
+    let scene = {
+        let registry = world.resource::<AppTypeRegistry>().read();
+        let mut deserializer =
+            ron::de::Deserializer::from_str(ron).map_err(|error| error.to_string())?;
+        SceneDeserializer {
+            type_registry: &registry,
+        }
+        .deserialize(&mut deserializer)
+        .map_err(|error| error.to_string())?
+    };
+    let spawned = asset::write_dynamic_scene(world, &scene)?;
+    let roots: Vec<Entity> = spawned
+        .iter()
+        .copied()
+        .filter(|entity| world.get::<Parent>(*entity).is_none())
+        .collect();
+    if let Some(parent) = parent {
+        for root in &roots {
+            world.entity_mut(*root).set_parent(parent);
+        }
+    }
+    record_spawns(world, "Paste", &spawned);
+    Ok(roots)
+}
+
+/// Spawn copies of the entities and their descendants below the parents of
+/// the entities, returns the new top-most entities
+pub fn duplicate_entities(
+    world: &mut World,
+    entities: impl IntoIterator<Item = Entity>,
+) -> Result<Vec<Entity>, String> {
+    let (scene, roots) = asset::extract_trees(world, entities);
+    let parents: Vec<Option<Entity>> = roots
+        .iter()
+        .map(|root| world.get::<Parent>(*root).map(Parent::get))
+        .collect();
+    let positions: Vec<usize> = roots
+        .iter()
+        .filter_map(|root| {
+            scene
+                .entities
+                .iter()
+                .position(|entity| entity.entity == *root)
+        })
+        .collect();
+    let spawned = asset::write_dynamic_scene(world, &scene)?;
+
+    let mut copies = Vec::with_capacity(roots.len());
+    for (position, parent) in positions.into_iter().zip(parents) {
+        let copy = spawned[position];
+        if let Some(parent) = parent {
+            world.entity_mut(copy).set_parent(parent);
+        }
+        copies.push(copy);
+    }
+    record_spawns(world, "Duplicate", &spawned);
+    Ok(copies)
+}
+
+/// Let the app undo spawning the entities, if it has an undo stack
+fn record_spawns(world: &mut World, text: &str, entities: &[Entity]) {
+    let Some(mut stack) = world.get_resource_mut::<QmlUndoStack>() else {
+        return;
+    };
+    let mut spawned = MacroCommand::new(text);
+    for entity in entities {
+        spawned.add(Box::new(SpawnEntity::new(*entity)));
+    }
+    stack.record(spawned);
+}
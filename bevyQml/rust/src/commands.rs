@@ -0,0 +1,323 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! A queue of world mutations requested from QML.
+//!
+//! QML never touches the world directly while a frame is in flight. Commands
+//! are pushed onto the [QmlCommandQueue] from any thread and applied in
+//! [First] by [ApplyQmlCommands], so systems always see them at the same point
+//! of the frame. See [crate::cxxqt_bevy_commands] for the QML side.
+
+use std::sync::{Arc, Mutex, OnceLock};
+
+use bevy::{
+    ecs::reflect::{ReflectComponent, ReflectResource},
+    prelude::*,
+    reflect::{ReflectMut, TypeRegistration, TypeRegistry},
+};
+use cxx_qt_lib::QVariant;
+
+use crate::{asset, bridge, component, runtime, variant};
+
+/// A world mutation requested from QML
+pub enum QmlCommand {
+    /// Spawn an entity which has already been reserved
+    Spawn(Entity),
+    /// Despawn an entity along with its children
+    Despawn(Entity),
+    /// Insert a reflected component, or change the fields of an existing one
+    InsertComponent {
+        entity: Entity,
+        type_name: String,
+        fields: Vec<(String, QVariant)>,
+    },
+    /// Insert a reflected component, or overwrite what the value mentions of
+    /// an existing one, see [crate::variant]
+    SetComponent {
+        entity: Entity,
+        type_name: String,
+        value: QVariant,
+    },
+    /// Change a field of a component registered with a
+    /// [crate::component::QmlComponentPlugin]
+    WriteField {
+        entity: Entity,
+        component: String,
+        field: String,
+        value: QVariant,
+    },
+    /// Insert a reflected resource, or change the fields of an existing one
+    SetResource {
+        type_name: String,
+        fields: Vec<(String, QVariant)>,
+    },
+    /// Send an event registered with a [crate::bridge::SendEventBridge]
+    SendEvent {
+        name: String,
+        fields: Vec<(String, QVariant)>,
+    },
+    /// Spawn a scene below a root entity which has already been reserved,
+    /// see [crate::asset::spawn_scene]
+    SpawnScene {
+        root: Entity,
+        url: String,
+        parent: Option<Entity>,
+        transform: Transform,
+    },
+    /// Despawn a scene along with its root
+    DespawnScene(Entity),
+}
+
+/// A thread safe queue of [QmlCommand]s
+///
+/// All clones share the same queue, and [QmlCommandQueue::global] is the one
+/// inserted as a resource by [QmlCommandsPlugin].
+#[derive(Resource, Clone, Default)]
+pub struct QmlCommandQueue(Arc<Mutex<Vec<QmlCommand>>>);
+
+impl QmlCommandQueue {
+    /// The queue shared by every QML element
+    pub fn global() -> Self {
+        static QUEUE: OnceLock<QmlCommandQueue> = OnceLock::new();
+        QUEUE.get_or_init(Self::default).clone()
+    }
+
+    pub fn push(&self, command: QmlCommand) {
+        self.0.lock().unwrap().push(command);
+        runtime::request_update();
+    }
+
+    fn take(&self) -> Vec<QmlCommand> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+/// The system set in [First] where queued QML commands are applied
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ApplyQmlCommands;
+
+pub struct QmlCommandsPlugin;
+
+impl Plugin for QmlCommandsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(QmlCommandQueue::global())
+            .add_systems(First, apply_qml_commands.in_set(ApplyQmlCommands));
+    }
+}
+
+/// Apply the queued commands, also used to flush the queue on shutdown
+pub(crate) fn apply_qml_commands(world: &mut World) {
+    let commands = world.resource::<QmlCommandQueue>().take();
+    if commands.is_empty() {
+        return;
+    }
+
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    for command in commands {
+        if let Err(error) = apply(world, &registry, command) {
+            warn!("Failed to apply a command from QML: {error}");
+        }
+    }
+}
+
+/// Apply a single command straight away rather than in [ApplyQmlCommands]
+pub(crate) fn apply_command(world: &mut World, command: QmlCommand) -> Result<(), String> {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    apply(world, &registry, command)
+}
+
+fn apply(world: &mut World, registry: &TypeRegistry, command: QmlCommand) -> Result<(), String> {
+    match command {
+        QmlCommand::Spawn(entity) => {
+            // Reserved entities only exist once the world has been flushed
+            world.flush();
+            world
+                .get_or_spawn(entity)
+                .ok_or("the entity was despawned")?;
+        }
+        QmlCommand::Despawn(entity) => {
+            let mut entity = world
+                .get_entity_mut(entity)
+                .ok_or_else(|| format!("{entity:?} does not exist"))?;
+            // Leave no dangling entry in the Children of the parent, nor
+            // orphaned children behind
+            entity.remove_parent();
+            entity.despawn_recursive();
+        }
+        QmlCommand::InsertComponent {
+            entity,
+            type_name,
+            fields,
+        } => {
+            world.flush();
+            let registration = lookup(registry, &type_name)?;
+            let reflect = registration
+                .data::<ReflectComponent>()
+                .ok_or_else(|| format!("{type_name} does not reflect Component"))?;
+            let mut entity = world
+                .get_entity_mut(entity)
+                .ok_or_else(|| format!("{entity:?} does not exist"))?;
+
+            if let Some(mut existing) = reflect.reflect_mut(&mut entity) {
+                apply_fields(&mut *existing, &type_name, &fields, registry)?;
+            } else {
+                let mut value = default_value(registration, &type_name)?;
+                apply_fields(&mut *value, &type_name, &fields, registry)?;
+                reflect.insert(&mut entity, &*value, registry);
+            }
+        }
+        QmlCommand::SetComponent {
+            entity,
+            type_name,
+            value,
+        } => {
+            world.flush();
+            let registration = lookup(registry, &type_name)?;
+            let reflect = registration
+                .data::<ReflectComponent>()
+                .ok_or_else(|| format!("{type_name} does not reflect Component"))?;
+            let mut entity = world
+                .get_entity_mut(entity)
+                .ok_or_else(|| format!("{entity:?} does not exist"))?;
+
+            if let Some(mut existing) = reflect.reflect_mut(&mut entity) {
+                variant::apply_variant_with(&mut *existing, &value, registry)
+                    .map_err(|error| format!("{type_name}: {error}"))?;
+            } else {
+                let value = variant::from_variant(registration, &value, registry)
+                    .map_err(|error| format!("{type_name}: {error}"))?;
+                reflect.insert(&mut entity, &*value, registry);
+            }
+        }
+        QmlCommand::WriteField {
+            entity,
+            component: name,
+            field,
+            value,
+        } => {
+            if !component::write_field(world, entity, &name, &field, &value) {
+                return Err(format!("cannot write {name}.{field} of {entity:?}"));
+            }
+        }
+        QmlCommand::SetResource { type_name, fields } => {
+            let registration = lookup(registry, &type_name)?;
+            let reflect = registration
+                .data::<ReflectResource>()
+                .ok_or_else(|| format!("{type_name} does not reflect Resource"))?;
+
+            if let Some(mut existing) = reflect.reflect_mut(world) {
+                apply_fields(&mut *existing, &type_name, &fields, registry)?;
+            } else {
+                let mut value = default_value(registration, &type_name)?;
+                apply_fields(&mut *value, &type_name, &fields, registry)?;
+                reflect.insert(world, &*value, registry);
+            }
+        }
+        QmlCommand::SendEvent { name, fields } => {
+            bridge::send_named_event(world, &name, &fields)?;
+        }
+        QmlCommand::SpawnScene {
+            root,
+            url,
+            parent,
+            transform,
+        } => {
+            asset::spawn_scene(world, root, &url, parent, transform)?;
+        }
+        QmlCommand::DespawnScene(root) => {
+            asset::despawn_scene(world, root)?;
+        }
+    }
+    Ok(())
+}
+
+/// Find a registered type by its short or full type path
+pub fn lookup<'a>(
+    registry: &'a TypeRegistry,
+    type_name: &str,
+) -> Result<&'a TypeRegistration, String> {
+    registry
+        .get_with_short_type_path(type_name)
+        .or_else(|| registry.get_with_type_path(type_name))
+        .ok_or_else(|| format!("{type_name} is not registered"))
+}
+
+/// Read a reflected component of an entity, see [crate::variant]
+pub fn read_component(
+    world: &World,
+    registry: &TypeRegistry,
+    entity: Entity,
+    type_name: &str,
+) -> Result<QVariant, String> {
+    let reflect = lookup(registry, type_name)?
+        .data::<ReflectComponent>()
+        .ok_or_else(|| format!("{type_name} does not reflect Component"))?;
+    let entity_ref = world
+        .get_entity(entity)
+        .ok_or_else(|| format!("{entity:?} does not exist"))?;
+    let value = reflect
+        .reflect(entity_ref)
+        .ok_or_else(|| format!("{entity:?} has no {type_name}"))?;
+    variant::to_variant(value).ok_or_else(|| format!("{type_name} cannot be converted"))
+}
+
+/// The type paths of the reflected components of an entity
+pub fn component_names(world: &World, registry: &TypeRegistry, entity: Entity) -> Vec<String> {
+    if world.get_entity(entity).is_none() {
+        return Vec::new();
+    }
+    world
+        .inspect_entity(entity)
+        .into_iter()
+        .filter_map(|info| registry.get(info.type_id()?))
+        .filter(|registration| registration.data::<ReflectComponent>().is_some())
+        .map(|registration| registration.type_info().type_path().to_owned())
+        .collect()
+}
+
+fn default_value(
+    registration: &TypeRegistration,
+    type_name: &str,
+) -> Result<Box<dyn Reflect>, String> {
+    Ok(registration
+        .data::<ReflectDefault>()
+        .ok_or_else(|| format!("{type_name} does not reflect Default"))?
+        .default())
+}
+
+fn apply_fields(
+    target: &mut dyn Reflect,
+    type_name: &str,
+    fields: &[(String, QVariant)],
+    registry: &TypeRegistry,
+) -> Result<(), String> {
+    if !fields.is_empty() && !matches!(target.reflect_mut(), ReflectMut::Struct(_)) {
+        return Err(format!("{type_name} is not a struct"));
+    }
+    for (field, value) in fields {
+        variant::apply_struct_field_with(target, field, value, registry)
+            .map_err(|error| format!("{type_name}.{field}: {error}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn despawn_detaches_from_parent() {
+        let mut world = World::new();
+        let registry = TypeRegistry::default();
+        let parent = world.spawn_empty().id();
+        let child = world.spawn_empty().set_parent(parent).id();
+        let grandchild = world.spawn_empty().set_parent(child).id();
+
+        apply(&mut world, &registry, QmlCommand::Despawn(child)).unwrap();
+        assert!(world.get_entity(child).is_none());
+        assert!(world.get_entity(grandchild).is_none());
+        assert!(world.get::<Children>(parent).is_none());
+        assert!(apply(&mut world, &registry, QmlCommand::Despawn(child)).is_err());
+    }
+}
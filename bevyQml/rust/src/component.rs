@@ -0,0 +1,269 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Exposes the fields of Bevy components to QML, see
+//! [crate::cxxqt_bevy_component] for the QML side.
+
+use std::marker::PhantomData;
+
+use bevy::{ecs::component::Tick, prelude::*, utils::HashMap};
+use cxx_qt_lib::QVariant;
+
+pub use bevy_qml_derive::QmlComponent;
+
+/// A component whose fields can be read and written from QML
+///
+/// Derive it with `#[derive(QmlComponent)]` and register the component with a
+/// [QmlComponentPlugin].
+///
+/// ```ignore
+/// #[derive(Component, QmlComponent)]
+/// struct Health {
+///     current: f32,
+///     max: f32,
+///     #[qml(skip)]
+///     regeneration: Timer,
+/// }
+///
+/// app.add_plugins(QmlComponentPlugin::<Health>::default());
+/// ```
+///
+/// ```qml
+/// BevyComponent {
+///     id: health
+///     entity: player.entity
+///     component: "Health"
+/// }
+/// ProgressBar {
+///     value: health.values.current / health.values.max
+/// }
+/// ```
+pub trait QmlComponent: Component {
+    /// The name QML refers to the component by
+    const QML_NAME: &'static str;
+
+    /// The names of the exposed fields
+    fn qml_fields() -> &'static [&'static str];
+
+    /// The current values of the exposed fields
+    fn qml_read(&self) -> Vec<(&'static str, QVariant)>;
+
+    /// Change a field, returns false if there is no such field or the value does not fit
+    fn qml_write(&mut self, field: &str, value: &QVariant) -> bool;
+}
+
+/// Registers a [QmlComponent] so `BevyComponent` elements can find it by name
+pub struct QmlComponentPlugin<T>(PhantomData<fn() -> T>);
+
+impl<T> Default for QmlComponentPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: QmlComponent> Plugin for QmlComponentPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QmlComponents>();
+        let mut components = app.world_mut().resource_mut::<QmlComponents>();
+        // QML could only reach one of them
+        if let Some(other) = components.0.get(T::QML_NAME) {
+            panic!(
+                "{} is exposed to QML as {:?}, which {} already is",
+                std::any::type_name::<T>(),
+                T::QML_NAME,
+                other.type_name
+            );
+        }
+        components.0.insert(
+            T::QML_NAME.to_owned(),
+            ComponentAccess {
+                type_name: std::any::type_name::<T>(),
+                fields: T::qml_fields(),
+                read: read_component::<T>,
+                write: write_component::<T>,
+            },
+        );
+    }
+}
+
+/// The field values of a component and when it last changed
+pub struct ComponentValues {
+    pub changed: Tick,
+    pub fields: Vec<(&'static str, QVariant)>,
+}
+
+#[derive(Clone, Copy)]
+struct ComponentAccess {
+    type_name: &'static str,
+    fields: &'static [&'static str],
+    read: fn(&World, Entity) -> Option<ComponentValues>,
+    write: fn(&mut World, Entity, &str, &QVariant) -> bool,
+}
+
+/// Every component registered with a [QmlComponentPlugin], by name
+#[derive(Resource, Default)]
+pub struct QmlComponents(HashMap<String, ComponentAccess>);
+
+impl QmlComponents {
+    /// Read the component with the given name from the entity
+    ///
+    /// Returns [None] if the component is unknown or the entity does not have it.
+    pub fn read(&self, world: &World, entity: Entity, component: &str) -> Option<ComponentValues> {
+        (self.0.get(component)?.read)(world, entity)
+    }
+
+    /// Whether a component with the given name has been registered
+    pub fn contains(&self, component: &str) -> bool {
+        self.0.contains_key(component)
+    }
+
+    /// Whether the named component has been registered and exposes the field
+    pub fn has_field(&self, component: &str, field: &str) -> bool {
+        self.0
+            .get(component)
+            .is_some_and(|access| access.fields.contains(&field))
+    }
+}
+
+/// Change a field of the named component on the entity
+///
+/// Returns false if the component is unknown, the entity does not have it, or
+/// the value does not fit the field.
+pub fn write_field(
+    world: &mut World,
+    entity: Entity,
+    component: &str,
+    field: &str,
+    value: &QVariant,
+) -> bool {
+    let Some(access) = world
+        .get_resource::<QmlComponents>()
+        .and_then(|components| components.0.get(component).copied())
+    else {
+        return false;
+    };
+    (access.write)(world, entity, field, value)
+}
+
+fn read_component<T: QmlComponent>(world: &World, entity: Entity) -> Option<ComponentValues> {
+    let component = world.get_entity(entity)?.get_ref::<T>()?;
+    Some(ComponentValues {
+        changed: component.last_changed(),
+        fields: component.qml_read(),
+    })
+}
+
+fn write_component<T: QmlComponent>(
+    world: &mut World,
+    entity: Entity,
+    field: &str,
+    value: &QVariant,
+) -> bool {
+    let Some(mut component) = world.get_mut::<T>(entity) else {
+        return false;
+    };
+    // Only mark the component as changed when the field was actually written
+    let applied = component.bypass_change_detection().qml_write(field, value);
+    if applied {
+        component.set_changed();
+    }
+    applied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Component)]
+    struct Health(f32);
+
+    #[derive(Component)]
+    struct Shield(f32);
+
+    impl QmlComponent for Health {
+        const QML_NAME: &'static str = "Health";
+
+        fn qml_fields() -> &'static [&'static str] {
+            &["current"]
+        }
+
+        fn qml_read(&self) -> Vec<(&'static str, QVariant)> {
+            vec![("current", QVariant::from(&self.0))]
+        }
+
+        fn qml_write(&mut self, field: &str, value: &QVariant) -> bool {
+            match (field, value.value::<f32>()) {
+                ("current", Some(value)) => {
+                    self.0 = value;
+                    true
+                }
+                _ => false,
+            }
+        }
+    }
+
+    impl QmlComponent for Shield {
+        const QML_NAME: &'static str = "Health";
+
+        fn qml_fields() -> &'static [&'static str] {
+            &[]
+        }
+
+        fn qml_read(&self) -> Vec<(&'static str, QVariant)> {
+            Vec::new()
+        }
+
+        fn qml_write(&mut self, _field: &str, _value: &QVariant) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn registers_fields() {
+        let mut app = App::new();
+        app.add_plugins(QmlComponentPlugin::<Health>::default());
+        let components = app.world().resource::<QmlComponents>();
+        assert!(components.contains("Health"));
+        assert!(components.has_field("Health", "current"));
+        assert!(!components.has_field("Health", "max"));
+        assert!(!components.has_field("Shield", "current"));
+    }
+
+    #[test]
+    fn writes_fields() {
+        let mut app = App::new();
+        app.add_plugins(QmlComponentPlugin::<Health>::default());
+        let world = app.world_mut();
+        let entity = world.spawn(Health(1.0)).id();
+        assert!(write_field(
+            world,
+            entity,
+            "Health",
+            "current",
+            &QVariant::from(&0.5_f32)
+        ));
+        assert_eq!(world.get::<Health>(entity).unwrap().0, 0.5);
+        assert!(!write_field(
+            world,
+            entity,
+            "Health",
+            "max",
+            &QVariant::from(&2.0_f32)
+        ));
+        assert!(!write_field(
+            world,
+            entity,
+            "Shield",
+            "current",
+            &QVariant::from(&2.0_f32)
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "is exposed to QML as \"Health\", which")]
+    fn rejects_duplicate_names() {
+        App::new().add_plugins((
+            QmlComponentPlugin::<Health>::default(),
+            QmlComponentPlugin::<Shield>::default(),
+        ));
+    }
+}
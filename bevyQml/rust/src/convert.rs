@@ -0,0 +1,361 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Conversions between the value types of Qt and the math types of Bevy.
+//!
+//! Neither side of most pairs is owned by this crate, so instead of [From]
+//! they convert with [FromQt] and [IntoQt]:
+//!
+//! ```ignore
+//! let position = Vec3::from_qt(&vector);
+//! let vector: QVector3D = position.into_qt();
+//! ```
+//!
+//! cxx-qt-lib has no QQuaternion or QMatrix4x4, so [QQuaternion] and
+//! [QMatrix4x4] stand in for them on the Rust side. They can be used in
+//! bridges like the cxx-qt-lib types:
+//!
+//! ```ignore
+//! unsafe extern "C++" {
+//!     include!("bevyqml/convert.h");
+//!     type QQuaternion = crate::convert::QQuaternion;
+//! }
+//! ```
+
+use bevy::{color::Srgba, prelude::*};
+use cxx::{type_id, ExternType};
+use cxx_qt_lib::{QColor, QPointF, QRectF, QSizeF, QVector2D, QVector3D, QVector4D};
+
+/// Conversion from a Qt value type
+pub trait FromQt<Q>: Sized {
+    fn from_qt(value: &Q) -> Self;
+}
+
+/// Conversion into a Qt value type
+pub trait IntoQt<Q> {
+    fn into_qt(self) -> Q;
+}
+
+/// QQuaternion, laid out like its C++ counterpart
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QQuaternion {
+    wp: f32,
+    xp: f32,
+    yp: f32,
+    zp: f32,
+}
+
+impl QQuaternion {
+    pub fn new(scalar: f32, x: f32, y: f32, z: f32) -> Self {
+        Self {
+            wp: scalar,
+            xp: x,
+            yp: y,
+            zp: z,
+        }
+    }
+
+    pub fn scalar(&self) -> f32 {
+        self.wp
+    }
+
+    pub fn x(&self) -> f32 {
+        self.xp
+    }
+
+    pub fn y(&self) -> f32 {
+        self.yp
+    }
+
+    pub fn z(&self) -> f32 {
+        self.zp
+    }
+}
+
+impl Default for QQuaternion {
+    /// The identity rotation, like the default QQuaternion
+    fn default() -> Self {
+        Self::new(1.0, 0.0, 0.0, 0.0)
+    }
+}
+
+unsafe impl ExternType for QQuaternion {
+    type Id = type_id!("QQuaternion");
+    type Kind = cxx::kind::Trivial;
+}
+
+impl From<Quat> for QQuaternion {
+    fn from(value: Quat) -> Self {
+        Self::new(value.w, value.x, value.y, value.z)
+    }
+}
+
+impl From<QQuaternion> for Quat {
+    fn from(value: QQuaternion) -> Self {
+        Quat::from_xyzw(value.xp, value.yp, value.zp, value.wp)
+    }
+}
+
+/// QMatrix4x4, laid out like its C++ counterpart
+///
+/// The elements are stored column by column, like in a [Mat4].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QMatrix4x4 {
+    m: [[f32; 4]; 4],
+    /// Which kinds of transformation the matrix holds, Qt uses these to
+    /// skip work for simple matrices
+    flag_bits: i32,
+}
+
+impl QMatrix4x4 {
+    /// QMatrix4x4::General, the matrix may be any transformation
+    const GENERAL: i32 = 0x001f;
+    /// QMatrix4x4::Identity
+    const IDENTITY: i32 = 0x0000;
+
+    /// The element at a row and column
+    pub fn get(&self, row: usize, column: usize) -> f32 {
+        self.m[column][row]
+    }
+}
+
+impl Default for QMatrix4x4 {
+    /// The identity matrix, like the default QMatrix4x4
+    fn default() -> Self {
+        Mat4::IDENTITY.into()
+    }
+}
+
+unsafe impl ExternType for QMatrix4x4 {
+    type Id = type_id!("QMatrix4x4");
+    type Kind = cxx::kind::Trivial;
+}
+
+impl From<Mat4> for QMatrix4x4 {
+    fn from(value: Mat4) -> Self {
+        Self {
+            m: value.to_cols_array_2d(),
+            flag_bits: if value == Mat4::IDENTITY {
+                Self::IDENTITY
+            } else {
+                Self::GENERAL
+            },
+        }
+    }
+}
+
+impl From<QMatrix4x4> for Mat4 {
+    fn from(value: QMatrix4x4) -> Self {
+        Mat4::from_cols_array_2d(&value.m)
+    }
+}
+
+impl FromQt<QQuaternion> for Quat {
+    fn from_qt(value: &QQuaternion) -> Self {
+        (*value).into()
+    }
+}
+
+impl IntoQt<QQuaternion> for Quat {
+    fn into_qt(self) -> QQuaternion {
+        self.into()
+    }
+}
+
+impl FromQt<QMatrix4x4> for Mat4 {
+    fn from_qt(value: &QMatrix4x4) -> Self {
+        (*value).into()
+    }
+}
+
+impl IntoQt<QMatrix4x4> for Mat4 {
+    fn into_qt(self) -> QMatrix4x4 {
+        self.into()
+    }
+}
+
+impl FromQt<QVector2D> for Vec2 {
+    fn from_qt(value: &QVector2D) -> Self {
+        Vec2::new(value.x(), value.y())
+    }
+}
+
+impl IntoQt<QVector2D> for Vec2 {
+    fn into_qt(self) -> QVector2D {
+        QVector2D::new(self.x, self.y)
+    }
+}
+
+impl FromQt<QVector3D> for Vec3 {
+    fn from_qt(value: &QVector3D) -> Self {
+        Vec3::new(value.x(), value.y(), value.z())
+    }
+}
+
+impl IntoQt<QVector3D> for Vec3 {
+    fn into_qt(self) -> QVector3D {
+        QVector3D::new(self.x, self.y, self.z)
+    }
+}
+
+impl FromQt<QVector4D> for Vec4 {
+    fn from_qt(value: &QVector4D) -> Self {
+        Vec4::new(value.x(), value.y(), value.z(), value.w())
+    }
+}
+
+impl IntoQt<QVector4D> for Vec4 {
+    fn into_qt(self) -> QVector4D {
+        QVector4D::new(self.x, self.y, self.z, self.w)
+    }
+}
+
+impl FromQt<QPointF> for Vec2 {
+    fn from_qt(value: &QPointF) -> Self {
+        Vec2::new(value.x() as f32, value.y() as f32)
+    }
+}
+
+impl IntoQt<QPointF> for Vec2 {
+    fn into_qt(self) -> QPointF {
+        QPointF::new(self.x.into(), self.y.into())
+    }
+}
+
+impl FromQt<QSizeF> for Vec2 {
+    fn from_qt(value: &QSizeF) -> Self {
+        Vec2::new(value.width() as f32, value.height() as f32)
+    }
+}
+
+impl IntoQt<QSizeF> for Vec2 {
+    fn into_qt(self) -> QSizeF {
+        QSizeF::new(self.x.into(), self.y.into())
+    }
+}
+
+/// Qt keeps the top left corner and the size, which may be negative
+impl FromQt<QRectF> for Rect {
+    fn from_qt(value: &QRectF) -> Self {
+        Rect::new(
+            value.x() as f32,
+            value.y() as f32,
+            (value.x() + value.width()) as f32,
+            (value.y() + value.height()) as f32,
+        )
+    }
+}
+
+impl IntoQt<QRectF> for Rect {
+    fn into_qt(self) -> QRectF {
+        QRectF::new(
+            self.min.x.into(),
+            self.min.y.into(),
+            self.width().into(),
+            self.height().into(),
+        )
+    }
+}
+
+/// QColor holds sRGB colors, so they become [Srgba] colors
+impl FromQt<QColor> for Color {
+    fn from_qt(value: &QColor) -> Self {
+        Color::srgba(
+            value.red_f(),
+            value.green_f(),
+            value.blue_f(),
+            value.alpha_f(),
+        )
+    }
+}
+
+impl IntoQt<QColor> for Color {
+    fn into_qt(self) -> QColor {
+        let Srgba {
+            red,
+            green,
+            blue,
+            alpha,
+        } = self.to_srgba();
+        QColor::from_rgba_f(red, green, blue, alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quaternion_puts_the_scalar_first() {
+        let rotation = Quat::from_rotation_y(0.5);
+        let quaternion = QQuaternion::from(rotation);
+        assert_eq!(quaternion.scalar(), rotation.w);
+        assert_eq!(quaternion.x(), rotation.x);
+        assert_eq!(quaternion.y(), rotation.y);
+        assert_eq!(quaternion.z(), rotation.z);
+        assert_eq!(Quat::from(quaternion), rotation);
+        assert_eq!(Quat::from(QQuaternion::default()), Quat::IDENTITY);
+    }
+
+    #[test]
+    fn quaternion_round_trip() {
+        let rotation = Quat::from_euler(EulerRot::XYZ, 0.3, -1.2, 2.0);
+        let quaternion: QQuaternion = rotation.into_qt();
+        assert_eq!(Quat::from_qt(&quaternion), rotation);
+    }
+
+    #[test]
+    fn matrix_round_trip() {
+        let matrix = Mat4::from_scale_rotation_translation(
+            Vec3::new(1.0, 2.0, 3.0),
+            Quat::from_rotation_z(0.7),
+            Vec3::new(-4.0, 5.0, 6.0),
+        );
+        let qt_matrix: QMatrix4x4 = matrix.into_qt();
+        assert_eq!(Mat4::from_qt(&qt_matrix), matrix);
+        assert_eq!(Mat4::from(QMatrix4x4::default()), Mat4::IDENTITY);
+    }
+
+    #[test]
+    fn matrix_elements_by_row_and_column() {
+        // Rows of the matrix, which is not symmetric
+        let matrix = Mat4::from_cols_array_2d(&[
+            [1.0, 2.0, 3.0, 4.0],
+            [5.0, 6.0, 7.0, 8.0],
+            [9.0, 10.0, 11.0, 12.0],
+            [13.0, 14.0, 15.0, 16.0],
+        ])
+        .transpose();
+        let qt_matrix = QMatrix4x4::from(matrix);
+        assert_eq!(qt_matrix.get(0, 1), 2.0);
+        assert_eq!(qt_matrix.get(1, 0), 5.0);
+        assert_eq!(qt_matrix.get(2, 3), 12.0);
+        assert_eq!(qt_matrix.get(3, 2), 15.0);
+        for row in 0..4 {
+            for column in 0..4 {
+                assert_eq!(qt_matrix.get(row, column), matrix.row(row)[column]);
+            }
+        }
+
+        // The translation is in the last column
+        let translation = QMatrix4x4::from(Mat4::from_translation(Vec3::new(7.0, 8.0, 9.0)));
+        assert_eq!(translation.get(0, 3), 7.0);
+        assert_eq!(translation.get(1, 3), 8.0);
+        assert_eq!(translation.get(2, 3), 9.0);
+        assert_eq!(translation.get(3, 0), 0.0);
+    }
+
+    #[test]
+    fn matrix_flags_identity() {
+        assert_eq!(QMatrix4x4::default().flag_bits, QMatrix4x4::IDENTITY);
+        let scaled = QMatrix4x4::from(Mat4::from_scale(Vec3::splat(2.0)));
+        assert_eq!(scaled.flag_bits, QMatrix4x4::GENERAL);
+    }
+
+    #[test]
+    fn layouts_match_qt() {
+        assert_eq!(std::mem::size_of::<QQuaternion>(), 4 * 4);
+        assert_eq!(std::mem::size_of::<QMatrix4x4>(), 16 * 4 + 4);
+    }
+}
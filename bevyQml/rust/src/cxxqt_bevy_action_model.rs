@@ -0,0 +1,398 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the list model of the actions of the app
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_action_model")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!(<QtCore/QAbstractListModel>);
+
+        include!("cxx-qt-lib/qhash.h");
+        /// An alias to the QHash<int, QByteArray> type
+        type QHash_i32_QByteArray = cxx_qt_lib::QHash<cxx_qt_lib::QHashPair_i32_QByteArray>;
+        include!("cxx-qt-lib/qmodelindex.h");
+        /// An alias to the QModelIndex type
+        type QModelIndex = cxx_qt_lib::QModelIndex;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+        include!("cxx-qt-lib/qvector.h");
+        /// An alias to the QVector<int> type
+        type QVector_i32 = cxx_qt_lib::QVector<i32>;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // ActionModel based on the Rust struct ActionModelRust.
+        #[qobject]
+        #[qml_element]
+        #[base = "QAbstractListModel"]
+        #[qproperty(i32, count)]
+        type ActionModel = super::ActionModelRust;
+    }
+
+    // QAbstractListModel implementation
+    unsafe extern "RustQt" {
+        #[inherit]
+        #[cxx_name = "beginResetModel"]
+        unsafe fn begin_reset_model(self: Pin<&mut ActionModel>);
+
+        #[inherit]
+        #[cxx_name = "endResetModel"]
+        unsafe fn end_reset_model(self: Pin<&mut ActionModel>);
+
+        #[inherit]
+        fn index(self: &ActionModel, row: i32, column: i32, parent: &QModelIndex) -> QModelIndex;
+
+        #[qsignal]
+        #[inherit]
+        #[cxx_name = "dataChanged"]
+        fn data_changed(
+            self: Pin<&mut ActionModel>,
+            top_left: &QModelIndex,
+            bottom_right: &QModelIndex,
+            roles: &QVector_i32,
+        );
+    }
+
+    unsafe extern "RustQt" {
+        #[qinvokable]
+        #[cxx_override]
+        fn data(self: &ActionModel, index: &QModelIndex, role: i32) -> QVariant;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "roleNames"]
+        fn role_names(self: &ActionModel) -> QHash_i32_QByteArray;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "rowCount"]
+        fn row_count(self: &ActionModel, parent: &QModelIndex) -> i32;
+
+        /// Trigger the action if it is enabled, e.g. from a `Shortcut` or a
+        /// menu item
+        #[qinvokable]
+        fn trigger(self: &ActionModel, name: &QString);
+
+        /// The shortcut of the action, empty if there is none
+        #[qinvokable]
+        fn shortcut(self: &ActionModel, name: &QString) -> QString;
+
+        /// Use another shortcut for the action, written as in
+        /// QKeySequence::PortableText, or an empty one for none
+        ///
+        /// The shortcut is kept in the settings for the next start.
+        #[qinvokable]
+        #[cxx_name = "setShortcut"]
+        fn set_shortcut(self: &ActionModel, name: &QString, shortcut: &QString);
+
+        #[qinvokable]
+        #[cxx_name = "resetShortcut"]
+        fn reset_shortcut(self: &ActionModel, name: &QString);
+
+        #[qinvokable]
+        #[cxx_name = "resetAllShortcuts"]
+        fn reset_all_shortcuts(self: &ActionModel);
+
+        /// The action was triggered, from QML or by a key Bevy received
+        #[qsignal]
+        fn triggered(self: Pin<&mut ActionModel>, name: QString, from_input: bool);
+    }
+
+    impl cxx_qt::Threading for ActionModel {}
+    impl cxx_qt::Constructor<()> for ActionModel {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{
+    QByteArray, QHash, QHashPair_i32_QByteArray, QModelIndex, QString, QVariant, QVector,
+};
+
+use crate::{
+    action::{self, ActionSource, ActionState, QmlActions},
+    runtime::{self, UpdateListener},
+};
+
+/// Qt::UserRole, the first role available to custom models
+const USER_ROLE: i32 = 0x0100;
+
+const NAME_ROLE: i32 = USER_ROLE;
+const TEXT_ROLE: i32 = USER_ROLE + 1;
+const SHORTCUT_ROLE: i32 = USER_ROLE + 2;
+const DEFAULT_SHORTCUT_ROLE: i32 = USER_ROLE + 3;
+const ENABLED_ROLE: i32 = USER_ROLE + 4;
+const CUSTOMIZED_ROLE: i32 = USER_ROLE + 5;
+const CONFLICT_ROLE: i32 = USER_ROLE + 6;
+
+/// The Rust struct for the QObject
+///
+/// The model lists the actions registered with [QmlActions], with the roles
+/// `name`, `text`, `shortcut`, `defaultShortcut`, `enabled`, `customized`
+/// and `conflict`, the name of another action with the same shortcut. A
+/// `Shortcut` for every action lets Qt trigger them, and a dialog can list
+/// them to pick other keys:
+///
+/// ```qml
+/// ActionModel {
+///     id: actions
+///     onTriggered: (name, fromInput) => {
+///         if (name === "file.save")
+///             BevyDocument.save();
+///     }
+/// }
+/// Instantiator {
+///     model: actions
+///     delegate: Shortcut {
+///         sequence: model.shortcut
+///         enabled: model.enabled
+///         onActivated: actions.trigger(model.name)
+///     }
+/// }
+/// ListView {
+///     model: actions
+///     delegate: RowLayout {
+///         Label { text: model.text }
+///         Label { text: model.shortcut; color: model.conflict ? "red" : palette.text }
+///         Button {
+///             text: qsTr("Reset")
+///             enabled: model.customized
+///             onClicked: actions.resetShortcut(model.name)
+///         }
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct ActionModelRust {
+    count: i32,
+    rows: Vec<ActionRow>,
+    /// The revision of [QmlActions] the rows were read at
+    revision: Option<u64>,
+    /// The sequence number of the last trigger looked at
+    seen: Option<u64>,
+    update_listener: Option<UpdateListener>,
+}
+
+#[derive(Clone, PartialEq)]
+struct ActionRow {
+    state: ActionState,
+    conflict: String,
+}
+
+impl cxx_qt::Initialize for qobject::ActionModel {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|model| model.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+        self.refresh();
+    }
+}
+
+impl qobject::ActionModel {
+    pub fn data(&self, index: &QModelIndex, role: i32) -> QVariant {
+        let Some(row) = usize::try_from(index.row())
+            .ok()
+            .and_then(|row| self.rust().rows.get(row))
+        else {
+            return QVariant::default();
+        };
+
+        let string = |value: &str| QVariant::from(&QString::from(value));
+        match role {
+            NAME_ROLE => string(&row.state.name),
+            TEXT_ROLE => string(&row.state.text),
+            SHORTCUT_ROLE => string(&row.state.shortcut),
+            DEFAULT_SHORTCUT_ROLE => string(&row.state.default_shortcut),
+            ENABLED_ROLE => QVariant::from(&row.state.enabled),
+            CUSTOMIZED_ROLE => QVariant::from(&(row.state.shortcut != row.state.default_shortcut)),
+            CONFLICT_ROLE => string(&row.conflict),
+            _ => QVariant::default(),
+        }
+    }
+
+    pub fn role_names(&self) -> QHash<QHashPair_i32_QByteArray> {
+        let mut roles = QHash::<QHashPair_i32_QByteArray>::default();
+        roles.insert(NAME_ROLE, QByteArray::from("name"));
+        roles.insert(TEXT_ROLE, QByteArray::from("text"));
+        roles.insert(SHORTCUT_ROLE, QByteArray::from("shortcut"));
+        roles.insert(DEFAULT_SHORTCUT_ROLE, QByteArray::from("defaultShortcut"));
+        roles.insert(ENABLED_ROLE, QByteArray::from("enabled"));
+        roles.insert(CUSTOMIZED_ROLE, QByteArray::from("customized"));
+        roles.insert(CONFLICT_ROLE, QByteArray::from("conflict"));
+        roles
+    }
+
+    pub fn row_count(&self, _parent: &QModelIndex) -> i32 {
+        self.rust().rows.len() as i32
+    }
+
+    pub fn trigger(&self, name: &QString) {
+        let name = name.to_string();
+        self.modify("trigger", move |world| {
+            action::trigger(world, &name, ActionSource::Qml).map(|_| ())
+        });
+    }
+
+    pub fn shortcut(&self, name: &QString) -> QString {
+        let name = name.to_string();
+        self.rust()
+            .rows
+            .iter()
+            .find(|row| row.state.name == name)
+            .map_or_else(QString::default, |row| QString::from(&row.state.shortcut))
+    }
+
+    pub fn set_shortcut(&self, name: &QString, shortcut: &QString) {
+        let name = name.to_string();
+        let shortcut = shortcut.to_string();
+        self.modify("set the shortcut of", move |world| {
+            world
+                .resource_mut::<QmlActions>()
+                .set_shortcut(&name, &shortcut)
+        });
+    }
+
+    pub fn reset_shortcut(&self, name: &QString) {
+        let name = name.to_string();
+        self.modify("reset the shortcut of", move |world| {
+            world.resource_mut::<QmlActions>().reset_shortcut(&name)
+        });
+    }
+
+    pub fn reset_all_shortcuts(&self) {
+        self.modify("reset the shortcuts of", |world| {
+            world.resource_mut::<QmlActions>().reset_all_shortcuts();
+            Ok(())
+        });
+    }
+
+    /// Change the actions wherever the app runs, the rows follow after the
+    /// next update
+    fn modify(
+        &self,
+        what: &'static str,
+        f: impl FnOnce(&mut World) -> Result<(), String> + Send + 'static,
+    ) {
+        let sent = runtime::send(move |world| {
+            if !world.contains_resource::<QmlActions>() {
+                warn!("Cannot {what} an action without the QmlActionPlugin");
+                return;
+            }
+            if let Err(error) = f(world) {
+                warn!("Cannot {what} an action from QML: {error}");
+            }
+        });
+        if !sent {
+            warn!("Cannot {what} an action without a running Bevy app");
+        }
+    }
+
+    /// Read the actions if they changed, and emit the triggers since the
+    /// last refresh
+    fn refresh(mut self: Pin<&mut Self>) {
+        let revision = self.rust().revision;
+        let seen = self.rust().seen;
+        let update = runtime::with_world(|world| {
+            let actions = world.get_resource::<QmlActions>()?;
+            let rows = (revision != Some(actions.revision())).then(|| {
+                let states: Vec<ActionState> = actions.iter().cloned().collect();
+                (actions.revision(), rows(states))
+            });
+            let triggers: Vec<(QString, bool)> = match seen {
+                Some(seen) => actions
+                    .triggers_since(seen)
+                    .map(|(_, triggered)| {
+                        (
+                            QString::from(&triggered.name),
+                            triggered.source == ActionSource::Input,
+                        )
+                    })
+                    .collect(),
+                None => Vec::new(),
+            };
+            Some((rows, triggers, actions.last_trigger()))
+        })
+        .flatten();
+        let Some((rows, triggers, last_trigger)) = update else {
+            return;
+        };
+
+        self.as_mut().rust_mut().seen = Some(last_trigger);
+        if let Some((revision, rows)) = rows {
+            self.as_mut().rust_mut().revision = Some(revision);
+            self.as_mut().update_rows(rows);
+        }
+        for (name, from_input) in triggers {
+            self.as_mut().triggered(name, from_input);
+        }
+    }
+
+    /// Report the rows which changed, or reset the model if actions were
+    /// added, removed or reordered
+    fn update_rows(mut self: Pin<&mut Self>, rows: Vec<ActionRow>) {
+        let same_actions = rows.len() == self.rust().rows.len()
+            && rows
+                .iter()
+                .zip(&self.rust().rows)
+                .all(|(row, old)| row.state.name == old.state.name);
+        if !same_actions {
+            unsafe {
+                self.as_mut().begin_reset_model();
+            }
+            self.as_mut().rust_mut().rows = rows;
+            unsafe {
+                self.as_mut().end_reset_model();
+            }
+            let count = self.rust().rows.len() as i32;
+            self.set_count(count);
+            return;
+        }
+
+        let changed: Vec<usize> = rows
+            .iter()
+            .zip(&self.rust().rows)
+            .enumerate()
+            .filter(|(_, (row, old))| row != old)
+            .map(|(index, _)| index)
+            .collect();
+        self.as_mut().rust_mut().rows = rows;
+        // Every role may have changed
+        let roles = QVector::<i32>::default();
+        for row in changed {
+            let index = self.index(row as i32, 0, &QModelIndex::default());
+            self.as_mut().data_changed(&index, &index, &roles);
+        }
+    }
+}
+
+/// The rows of the actions, with the first other action sharing the
+/// shortcut of each
+fn rows(states: Vec<ActionState>) -> Vec<ActionRow> {
+    let shortcut_key = |state: &ActionState| state.shortcut.to_lowercase().replace(' ', "");
+    states
+        .iter()
+        .map(|state| {
+            let conflict = states
+                .iter()
+                .find(|other| {
+                    other.name != state.name
+                        && !state.shortcut.is_empty()
+                        && shortcut_key(other) == shortcut_key(state)
+                })
+                .map(|other| other.name.clone())
+                .unwrap_or_default();
+            ActionRow {
+                state: state.clone(),
+                conflict,
+            }
+        })
+        .collect()
+}
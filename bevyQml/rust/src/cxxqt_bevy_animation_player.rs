@@ -0,0 +1,312 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the list model of the animation clips of an
+/// entity, which also plays them
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_animation_player")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!(<QtCore/QAbstractListModel>);
+
+        include!("cxx-qt-lib/qhash.h");
+        /// An alias to the QHash<int, QByteArray> type
+        type QHash_i32_QByteArray = cxx_qt_lib::QHash<cxx_qt_lib::QHashPair_i32_QByteArray>;
+        include!("cxx-qt-lib/qmodelindex.h");
+        /// An alias to the QModelIndex type
+        type QModelIndex = cxx_qt_lib::QModelIndex;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+        include!("cxx-qt-lib/qmap.h");
+        /// An alias to the QVariantMap type
+        type QMap_QString_QVariant = cxx_qt_lib::QMap<cxx_qt_lib::QMapPair_QString_QVariant>;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // AnimationPlayerModel based on the Rust struct AnimationPlayerModelRust.
+        #[qobject]
+        #[qml_element]
+        #[base = "QAbstractListModel"]
+        #[qproperty(u64, entity)]
+        #[qproperty(i32, count)]
+        #[qproperty(QString, clip)]
+        #[qproperty(bool, playing)]
+        #[qproperty(f64, playhead)]
+        #[qproperty(f64, duration)]
+        type AnimationPlayerModel = super::AnimationPlayerModelRust;
+    }
+
+    // QAbstractListModel implementation
+    unsafe extern "RustQt" {
+        #[inherit]
+        #[cxx_name = "beginResetModel"]
+        unsafe fn begin_reset_model(self: Pin<&mut AnimationPlayerModel>);
+
+        #[inherit]
+        #[cxx_name = "endResetModel"]
+        unsafe fn end_reset_model(self: Pin<&mut AnimationPlayerModel>);
+    }
+
+    unsafe extern "RustQt" {
+        #[qinvokable]
+        #[cxx_override]
+        fn data(self: &AnimationPlayerModel, index: &QModelIndex, role: i32) -> QVariant;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "roleNames"]
+        fn role_names(self: &AnimationPlayerModel) -> QHash_i32_QByteArray;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "rowCount"]
+        fn row_count(self: &AnimationPlayerModel, parent: &QModelIndex) -> i32;
+
+        /// Play the clip with this name, returns false if there is none
+        ///
+        /// `options` may hold `loop` to repeat the clip, its `speed` and
+        /// `crossfadeMs`, how long it takes to replace the clips playing.
+        #[qinvokable]
+        fn play(
+            self: &AnimationPlayerModel,
+            name: &QString,
+            options: &QMap_QString_QVariant,
+        ) -> bool;
+
+        /// Pause every clip of the entity
+        #[qinvokable]
+        fn pause(self: &AnimationPlayerModel);
+
+        /// Resume the paused clips of the entity
+        #[qinvokable]
+        fn resume(self: &AnimationPlayerModel);
+
+        /// Stop every clip of the entity
+        #[qinvokable]
+        fn stop(self: &AnimationPlayerModel);
+
+        /// Move the clip shown as `clip` to a time, in seconds
+        #[qinvokable]
+        fn seek(self: &AnimationPlayerModel, time: f64);
+    }
+
+    impl cxx_qt::Threading for AnimationPlayerModel {}
+    impl cxx_qt::Constructor<()> for AnimationPlayerModel {}
+}
+
+use core::pin::Pin;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{
+    QByteArray, QHash, QHashPair_i32_QByteArray, QMap, QMapPair_QString_QVariant, QModelIndex,
+    QString, QVariant,
+};
+
+use crate::{
+    animation::{self, PlayOptions, QmlAnimationClip},
+    runtime::{self, UpdateListener},
+};
+
+/// Qt::UserRole, the first role available to custom models
+const USER_ROLE: i32 = 0x0100;
+
+/// The role which holds the name of a clip
+const NAME_ROLE: i32 = USER_ROLE;
+
+/// The role which holds the length of a clip in seconds
+const DURATION_ROLE: i32 = USER_ROLE + 1;
+
+/// The Rust struct for the QObject
+///
+/// Lists the animation clips of the entity whose bits are set as `entity`,
+/// with a `name` and `duration` role per clip, see [crate::animation] for
+/// which clips these are. `clip` is the clip started last, `playhead` where
+/// it is and `duration` its length, both in seconds:
+///
+/// ```qml
+/// AnimationPlayerModel {
+///     id: animations
+///     entity: character
+/// }
+/// Repeater {
+///     model: animations
+///     Button {
+///         text: model.name
+///         onClicked: animations.play(model.name, { "loop": true, "crossfadeMs": 250 })
+///     }
+/// }
+/// Slider {
+///     to: animations.duration
+///     value: animations.playhead
+///     onMoved: animations.seek(value)
+/// }
+/// ```
+#[derive(Default)]
+pub struct AnimationPlayerModelRust {
+    entity: u64,
+    count: i32,
+    clip: QString,
+    playing: bool,
+    playhead: f64,
+    duration: f64,
+    clips: Vec<QmlAnimationClip>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::AnimationPlayerModel {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|model| model.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.on_entity_changed(|model| model.refresh()).release();
+    }
+}
+
+impl qobject::AnimationPlayerModel {
+    pub fn data(&self, index: &QModelIndex, role: i32) -> QVariant {
+        let Some(clip) = usize::try_from(index.row())
+            .ok()
+            .and_then(|row| self.rust().clips.get(row))
+        else {
+            return QVariant::default();
+        };
+
+        match role {
+            NAME_ROLE => QVariant::from(&QString::from(clip.name.as_str())),
+            DURATION_ROLE => QVariant::from(&f64::from(clip.duration)),
+            _ => QVariant::default(),
+        }
+    }
+
+    pub fn role_names(&self) -> QHash<QHashPair_i32_QByteArray> {
+        let mut roles = QHash::<QHashPair_i32_QByteArray>::default();
+        roles.insert(NAME_ROLE, QByteArray::from("name"));
+        roles.insert(DURATION_ROLE, QByteArray::from("duration"));
+        roles
+    }
+
+    pub fn row_count(&self, _parent: &QModelIndex) -> i32 {
+        self.rust().clips.len() as i32
+    }
+
+    pub fn play(&self, name: &QString, options: &QMap<QMapPair_QString_QVariant>) -> bool {
+        let Some(entity) = self.target() else {
+            return false;
+        };
+        let mut play_options = PlayOptions::default();
+        if let Some(repeat) = options.get(&QString::from("loop")) {
+            play_options.repeat = repeat.value::<bool>().unwrap_or_default();
+        }
+        if let Some(speed) = options
+            .get(&QString::from("speed"))
+            .and_then(|speed| speed.value::<f64>())
+        {
+            play_options.speed = speed as f32;
+        }
+        if let Some(crossfade) = options
+            .get(&QString::from("crossfadeMs"))
+            .and_then(|crossfade| crossfade.value::<f64>())
+        {
+            play_options.crossfade =
+                Duration::try_from_secs_f64(crossfade / 1000.0).unwrap_or_default();
+        }
+
+        let name = name.to_string();
+        let played =
+            runtime::with_world(|world| animation::play(world, entity, &name, play_options))
+                .unwrap_or(false);
+        if played {
+            runtime::request_update();
+        } else {
+            warn!("AnimationPlayerModel cannot play {name:?}, {entity} has no such clip");
+        }
+        played
+    }
+
+    pub fn pause(&self) {
+        self.with_target(|world, entity| animation::set_paused(world, entity, true));
+    }
+
+    pub fn resume(&self) {
+        self.with_target(|world, entity| animation::set_paused(world, entity, false));
+    }
+
+    pub fn stop(&self) {
+        self.with_target(animation::stop);
+    }
+
+    pub fn seek(&self, time: f64) {
+        self.with_target(|world, entity| animation::seek(world, entity, time as f32));
+    }
+
+    fn target(&self) -> Option<Entity> {
+        Entity::try_from_bits(*self.entity()).ok()
+    }
+
+    /// Change the animations of the entity, which asks for an update
+    fn with_target(&self, f: impl FnOnce(&mut World, Entity)) {
+        if let Some(entity) = self.target() {
+            runtime::with_world(|world| {
+                if world.get_entity(entity).is_some() {
+                    f(world, entity);
+                }
+            });
+            runtime::request_update();
+        }
+    }
+
+    /// Follow the clips of the entity and what its animation player does
+    fn refresh(mut self: Pin<&mut Self>) {
+        let target = self.target();
+        let update = target
+            .and_then(|entity| {
+                runtime::with_world(|world| {
+                    world.get_entity(entity)?;
+                    Some((
+                        animation::clips(world, entity),
+                        animation::status(world, entity),
+                    ))
+                })
+                .flatten()
+            })
+            .unwrap_or_default();
+        let (clips, status) = update;
+
+        if self.rust().clips != clips {
+            unsafe {
+                self.as_mut().begin_reset_model();
+            }
+            self.as_mut().rust_mut().clips = clips;
+            unsafe {
+                self.as_mut().end_reset_model();
+            }
+            let count = self.rust().clips.len() as i32;
+            self.as_mut().set_count(count);
+        }
+
+        let clip = QString::from(status.clip.as_deref().unwrap_or_default());
+        if *self.clip() != clip {
+            self.as_mut().set_clip(clip);
+        }
+        if *self.playing() != status.playing {
+            self.as_mut().set_playing(status.playing);
+        }
+        let playhead = f64::from(status.playhead);
+        if *self.playhead() != playhead {
+            self.as_mut().set_playhead(playhead);
+        }
+        let duration = f64::from(status.duration);
+        if *self.duration() != duration {
+            self.as_mut().set_duration(duration);
+        }
+    }
+}
@@ -29,6 +29,8 @@ pub mod qobject {
         #[qml_element]
         #[qproperty(i32, number)]
         #[qproperty(QString, string)]
+        #[qproperty(bool, running)]
+        #[qproperty(bool, degraded)]
         type MyBevyApp = super::MyBevyAppRust;
     }
     // ANCHOR_END: book_rustobj_struct_signature
@@ -43,6 +45,24 @@ pub mod qobject {
         fn say_hi(self: &MyBevyApp, string: &QString, number: i32);
     }
     // ANCHOR_END: book_rustobj_invokable_signature
+
+    unsafe extern "RustQt" {
+        /// Ask the Bevy app to exit, which also quits the Qt event loop
+        #[qinvokable]
+        fn quit(self: Pin<&mut MyBevyApp>);
+
+        /// Resume updating the app after a panic left it degraded
+        #[qinvokable]
+        fn recover(self: Pin<&mut MyBevyApp>);
+
+        /// Rust code panicked, the app is degraded if it was updating
+        #[qsignal]
+        #[cxx_name = "bevyError"]
+        fn bevy_error(self: Pin<&mut MyBevyApp>, message: QString);
+    }
+
+    impl cxx_qt::Threading for MyBevyApp {}
+    impl cxx_qt::Constructor<()> for MyBevyApp {}
 }
 
 // ANCHOR: book_use
@@ -50,15 +70,61 @@ use core::pin::Pin;
 use cxx_qt_lib::QString;
 // ANCHOR_END: book_use
 
+use bevy::{app::AppExit, prelude::*};
+use cxx_qt::{CxxQtType, Threading};
+
+use crate::{
+    cxxqt_object::CurveDemoPlugin,
+    panic::{self, ErrorListener},
+    plugin::{bevy_qml_default_plugins, BevyQmlPlugin},
+    runtime,
+};
+
 /// The Rust struct for the QObject
 // ANCHOR: book_rustobj_struct
 #[derive(Default)]
 pub struct MyBevyAppRust {
     number: i32,
     string: QString,
+    running: bool,
+    degraded: bool,
+    error_listener: Option<ErrorListener>,
 }
 // ANCHOR_END: book_rustobj_struct
 
+impl cxx_qt::Initialize for qobject::MyBevyApp {
+    /// Start the demo app as soon as the element is created, the Qt event loop
+    /// then keeps it running
+    fn initialize(mut self: Pin<&mut Self>) {
+        // Queued, as errors are reported from wherever the panic was caught
+        let qt_thread = self.qt_thread();
+        let listener = panic::on_error(move |message| {
+            let message = QString::from(message);
+            let _ = qt_thread.queue(move |mut app| {
+                let degraded = runtime::is_degraded();
+                app.as_mut().set_degraded(degraded);
+                app.bevy_error(message);
+            });
+        });
+        self.as_mut().rust_mut().error_listener = Some(listener);
+
+        // Built again with a new device if the GPU device gets lost
+        let build = || {
+            let mut app = App::new();
+            app.add_plugins((
+                bevy_qml_default_plugins(),
+                BevyQmlPlugin::default(),
+                CurveDemoPlugin,
+            ));
+            app
+        };
+        runtime::rebuild_with(build);
+        build().run();
+
+        self.set_running(runtime::is_running());
+    }
+}
+
 // ANCHOR: book_rustobj_invokable_impl
 impl qobject::MyBevyApp {
     /// Increment the number Q_PROPERTY
@@ -74,4 +140,20 @@ impl qobject::MyBevyApp {
 }
 // ANCHOR_END: book_rustobj_invokable_impl
 
+impl qobject::MyBevyApp {
+    /// Ask the Bevy app to exit, which also quits the Qt event loop
+    pub fn quit(self: Pin<&mut Self>) {
+        runtime::with_world(|world| {
+            world.send_event(AppExit::Success);
+        });
+        runtime::request_update();
+        self.set_running(false);
+    }
+
+    pub fn recover(self: Pin<&mut Self>) {
+        runtime::recover();
+        self.set_degraded(runtime::is_degraded());
+    }
+}
+
 // ANCHOR_END: book_cxx_qt_module
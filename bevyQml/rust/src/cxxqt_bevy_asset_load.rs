@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that loads a single asset
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_asset_load")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qurl.h");
+        /// An alias to the QUrl type
+        type QUrl = cxx_qt_lib::QUrl;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BevyAssetLoad based on the Rust struct BevyAssetLoadRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(QUrl, url)]
+        #[qproperty(QString, type_hint)]
+        #[qproperty(u64, handle_id)]
+        #[qproperty(QString, status)]
+        #[qproperty(f64, progress)]
+        #[qproperty(i64, bytes)]
+        #[qproperty(QString, error)]
+        type BevyAssetLoad = super::BevyAssetLoadRust;
+    }
+
+    impl cxx_qt::Threading for BevyAssetLoad {}
+    impl cxx_qt::Constructor<()> for BevyAssetLoad {}
+}
+
+use core::pin::Pin;
+
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QString, QUrl};
+
+use crate::{
+    asset::{self, QmlAssetStatus, QmlAssets},
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// Loads the asset at `url` as a `typeHint` asset through [asset::load_url],
+/// and starts over whenever either changes. `status` is `"loading"`,
+/// `"loaded"` or `"failed"`, or empty without a url, and `progress` goes
+/// from 0 to 1 as described by [QmlAssetStatus]. The asset is released when
+/// the object is destroyed.
+#[derive(Default)]
+pub struct BevyAssetLoadRust {
+    url: QUrl,
+    type_hint: QString,
+    handle_id: u64,
+    status: QString,
+    progress: f64,
+    bytes: i64,
+    error: QString,
+    seen: Option<QmlAssetStatus>,
+    reload_queued: bool,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Drop for BevyAssetLoadRust {
+    fn drop(&mut self) {
+        release(self.handle_id);
+    }
+}
+
+impl cxx_qt::Initialize for qobject::BevyAssetLoad {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|load| load.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.as_mut()
+            .on_url_changed(|load| load.queue_reload())
+            .release();
+        self.on_type_hint_changed(|load| load.queue_reload())
+            .release();
+    }
+}
+
+impl qobject::BevyAssetLoad {
+    /// Reload once the current batch of property changes is done, so setting
+    /// both the url and the type hint only starts one load
+    fn queue_reload(mut self: Pin<&mut Self>) {
+        if self.rust().reload_queued {
+            return;
+        }
+        self.as_mut().rust_mut().reload_queued = true;
+        let _ = self.qt_thread().queue(|load| load.reload());
+    }
+
+    fn reload(mut self: Pin<&mut Self>) {
+        self.as_mut().rust_mut().reload_queued = false;
+        release(*self.handle_id());
+        self.as_mut().rust_mut().seen = None;
+
+        let url = self.url().to_string();
+        let type_hint = self.type_hint().to_string();
+        if url.is_empty() {
+            self.as_mut().set_handle_id(0);
+            self.as_mut().set_bytes(0);
+            self.as_mut().set_progress(0.0);
+            self.as_mut().set_error(QString::default());
+            self.set_status(QString::default());
+            return;
+        }
+
+        let result = runtime::with_world(|world| asset::load_url(world, &url, &type_hint))
+            .unwrap_or_else(|| Err("The Bevy app is not running".to_owned()));
+        match result {
+            Ok(id) => {
+                runtime::request_update();
+                self.as_mut().set_handle_id(id);
+                self.refresh();
+            }
+            Err(error) => {
+                self.as_mut().set_handle_id(0);
+                self.as_mut().set_bytes(0);
+                self.as_mut().set_progress(0.0);
+                self.as_mut().set_error(QString::from(&error));
+                self.set_status(QString::from("failed"));
+            }
+        }
+    }
+
+    /// Follow the status of the asset in [QmlAssets]
+    fn refresh(mut self: Pin<&mut Self>) {
+        let id = *self.handle_id();
+        if id == 0 {
+            return;
+        }
+        let update = runtime::with_world(|world| {
+            let assets = world.get_resource::<QmlAssets>()?;
+            Some((assets.status(id)?, assets.bytes(id)))
+        })
+        .flatten();
+        let Some((status, bytes)) = update else {
+            return;
+        };
+        if self.rust().seen.as_ref() == Some(&status) {
+            return;
+        }
+
+        self.as_mut().set_progress(status.progress().into());
+        self.as_mut()
+            .set_bytes(i64::try_from(bytes).unwrap_or(i64::MAX));
+        let (name, error) = match &status {
+            QmlAssetStatus::Loading { .. } => ("loading", ""),
+            QmlAssetStatus::Loaded => ("loaded", ""),
+            QmlAssetStatus::Failed { error } => ("failed", error.as_str()),
+        };
+        self.as_mut().set_error(QString::from(error));
+        self.as_mut().set_status(QString::from(name));
+        self.as_mut().rust_mut().seen = Some(status);
+    }
+}
+
+fn release(id: u64) {
+    if id != 0 {
+        runtime::with_world(|world| {
+            if let Some(mut assets) = world.get_resource_mut::<QmlAssets>() {
+                assets.release(id);
+            }
+        });
+    }
+}
@@ -0,0 +1,405 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML singleton that loads assets
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_assets")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QML singleton with the name
+        // BevyAssets based on the Rust struct BevyAssetsRust.
+        #[qobject]
+        #[qml_element]
+        #[qml_singleton]
+        #[qproperty(bool, loading)]
+        #[qproperty(i32, requested_count)]
+        #[qproperty(i32, loaded_count)]
+        #[qproperty(i32, failed_count)]
+        #[qproperty(i64, bytes_total)]
+        #[qproperty(i64, bytes_loaded)]
+        #[qproperty(f64, progress)]
+        type BevyAssets = super::BevyAssetsRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// Start loading the asset at a `file:`, `qrc:` or `http(s):` URL
+        ///
+        /// Returns the handle id of the asset, or 0 if the load could not
+        /// be started, in which case assetFailed is emitted as well.
+        #[qinvokable]
+        #[cxx_name = "loadAsset"]
+        fn load_asset(self: Pin<&mut BevyAssets>, url: &QString, type_hint: &QString) -> u64;
+
+        /// Load an asset like loadAsset, without blocking while the load
+        /// starts
+        ///
+        /// Returns a task id. Once the asset and its dependencies are loaded
+        /// taskFinished is emitted with the task id and the handle id of the
+        /// asset, otherwise taskFailed.
+        #[qinvokable]
+        #[cxx_name = "loadAssetAsync"]
+        fn load_asset_async(self: Pin<&mut BevyAssets>, url: &QString, type_hint: &QString) -> u64;
+
+        /// Save the world to a `.scn.ron` file, or its meshes to a `.gltf`
+        /// file, at a `file:` URL or a path in the assets folder
+        ///
+        /// The options may name a `root` entity to save along with its
+        /// descendants instead of the whole world, whether to leave the root
+        /// itself out with `descendantsOnly`, the `components` to save and
+        /// whether to save the `resources` as well. Returns a task id,
+        /// taskProgress follows the save until taskFinished or taskFailed.
+        #[qinvokable]
+        #[cxx_name = "saveScene"]
+        fn save_scene(self: Pin<&mut BevyAssets>, url: &QString, options: &QVariant) -> u64;
+
+        /// Spawn the entities saved to a `.scn.ron` file, or the scene of a
+        /// glTF file below a new root entity
+        ///
+        /// Returns a task id. taskFinished is emitted with the list of the
+        /// new top-most entities, otherwise taskFailed.
+        #[qinvokable]
+        #[cxx_name = "loadScene"]
+        fn load_scene(self: Pin<&mut BevyAssets>, url: &QString) -> u64;
+
+        /// Let go of an asset, it is unloaded once nothing else uses it
+        #[qinvokable]
+        #[cxx_name = "releaseAsset"]
+        fn release_asset(self: Pin<&mut BevyAssets>, handle_id: u64);
+
+        #[qinvokable]
+        #[cxx_name = "isLoaded"]
+        fn is_loaded(self: &BevyAssets, handle_id: u64) -> bool;
+
+        /// Ask for a file to open in a native dialog, filtered as in
+        /// `"Models (*.gltf *.glb)"`
+        ///
+        /// Returns the path of the file, or an empty string if the dialog
+        /// was canceled.
+        #[qinvokable]
+        #[cxx_name = "openFileDialog"]
+        fn open_file_dialog(self: &BevyAssets, filters: &QString) -> QString;
+
+        /// Ask for a file to save to in a native dialog
+        ///
+        /// Returns the path of the file, or an empty string if the dialog
+        /// was canceled.
+        #[qinvokable]
+        #[cxx_name = "saveFileDialog"]
+        fn save_file_dialog(self: &BevyAssets) -> QString;
+
+        /// The asset and its dependencies have finished loading
+        #[qsignal]
+        #[cxx_name = "assetLoaded"]
+        fn asset_loaded(self: Pin<&mut BevyAssets>, handle_id: u64);
+
+        #[qsignal]
+        #[cxx_name = "assetFailed"]
+        fn asset_failed(self: Pin<&mut BevyAssets>, handle_id: u64, error: QString);
+
+        /// A task started by an async invokable has finished
+        #[qsignal]
+        #[cxx_name = "taskFinished"]
+        fn task_finished(self: Pin<&mut BevyAssets>, task_id: u64, result: QVariant);
+
+        #[qsignal]
+        #[cxx_name = "taskFailed"]
+        fn task_failed(self: Pin<&mut BevyAssets>, task_id: u64, error: QString);
+
+        /// How far a task is, from 0 to 1, for the tasks which can tell
+        #[qsignal]
+        #[cxx_name = "taskProgress"]
+        fn task_progress(self: Pin<&mut BevyAssets>, task_id: u64, progress: f64);
+    }
+
+    impl cxx_qt::Threading for BevyAssets {}
+    impl cxx_qt::Constructor<()> for BevyAssets {}
+}
+
+use core::pin::Pin;
+use std::path::PathBuf;
+
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QString, QVariant};
+
+use bevy::prelude::*;
+
+use crate::{
+    asset::{
+        self, QmlAssetOutcome, QmlAssetStatus, QmlAssets, QmlLoadProgress, SceneFormat,
+        SceneSaveOptions,
+    },
+    runtime::{self, UpdateListener},
+    task,
+    undo::QmlUndoStack,
+    variant,
+};
+
+/// The Rust struct for the QObject
+///
+/// The loaded assets are kept in the [QmlAssets] resource, where Rust code
+/// can look them up by the handle ids QML passes along.
+///
+/// The other properties follow the [QmlLoadProgress] of every load started
+/// since the last time nothing was loading, so a loading screen can bind a
+/// ProgressBar to `progress`, which goes from 0 to 1.
+///
+/// The paths picked with the file dialogs are sent to Bevy as
+/// [crate::asset::FilePicked] too, and can be loaded right away:
+///
+/// ```qml
+/// MenuItem {
+///     text: qsTr("Open model…")
+///     onTriggered: {
+///         const path = BevyAssets.openFileDialog("Models (*.gltf *.glb)");
+///         if (path !== "")
+///             BevyAssets.loadAsset("file:" + path, "scene");
+///     }
+/// }
+/// ```
+///
+/// `loadAssetAsync` starts the load from a task instead, see [crate::task],
+/// and resolves a promise once the asset is ready:
+///
+/// ```qml
+/// BevyAsync.promise(BevyAssets, BevyAssets.loadAssetAsync(url, "scene"))
+///     .then(handleId => spawner.scene = handleId)
+/// ```
+///
+/// Documents are saved and loaded the same way, see
+/// [crate::asset::SceneSaveOptions] for the options. Saving the whole world
+/// to a `.scn.ron` file marks the undo stack clean:
+///
+/// ```qml
+/// MenuItem {
+///     text: qsTr("Save level")
+///     onTriggered: BevyAsync.promise(BevyAssets, BevyAssets.saveScene(documentUrl, {}))
+///         .catch(error => errorDialog.show(error))
+/// }
+/// MenuItem {
+///     text: qsTr("Export selection…")
+///     onTriggered: BevyAssets.saveScene("file:" + BevyAssets.saveFileDialog(), {
+///         "root": BevySelection.entities[0],
+///         "components": ["Transform", "Name", "Enemy"]
+///     })
+/// }
+/// ```
+#[derive(Default)]
+pub struct BevyAssetsRust {
+    loading: bool,
+    requested_count: i32,
+    loaded_count: i32,
+    failed_count: i32,
+    bytes_total: i64,
+    bytes_loaded: i64,
+    progress: f64,
+    seen: QmlLoadProgress,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::BevyAssets {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|mut assets| {
+                assets.as_mut().refresh_progress();
+                assets.emit_outcomes();
+            });
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+        self.refresh_progress();
+    }
+}
+
+impl qobject::BevyAssets {
+    pub fn load_asset(self: Pin<&mut Self>, url: &QString, type_hint: &QString) -> u64 {
+        let url = url.to_string();
+        let result =
+            runtime::with_world(|world| asset::load_url(world, &url, &type_hint.to_string()))
+                .unwrap_or_else(|| Err("The Bevy app is not running".to_owned()));
+
+        match result {
+            Ok(id) => {
+                runtime::request_update();
+                self.refresh_progress();
+                id
+            }
+            Err(error) => {
+                self.asset_failed(0, QString::from(&format!("{url}: {error}")));
+                0
+            }
+        }
+    }
+
+    pub fn load_asset_async(self: Pin<&mut Self>, url: &QString, type_hint: &QString) -> u64 {
+        let id = task::next_task_id();
+        let url = url.to_string();
+        let type_hint = type_hint.to_string();
+        let load = async move {
+            let handle_id = task::world(move |world| asset::load_url(world, &url, &type_hint))
+                .await
+                .ok_or("The Bevy app is not running")??;
+            task::world_until(move |world| {
+                match world.get_resource::<QmlAssets>()?.status(handle_id) {
+                    Some(QmlAssetStatus::Loading { .. }) => None,
+                    Some(QmlAssetStatus::Loaded) => Some(Ok(handle_id)),
+                    Some(QmlAssetStatus::Failed { error }) => Some(Err(error)),
+                    None => Some(Err(String::from("The asset was released"))),
+                }
+            })
+            .await
+            .ok_or("The Bevy app is not running")?
+        };
+        task::spawn(self.qt_thread(), load, move |assets, result| match result {
+            Ok(handle_id) => assets.task_finished(id, QVariant::from(&handle_id)),
+            Err(error) => assets.task_failed(id, QString::from(&error)),
+        });
+        id
+    }
+
+    pub fn save_scene(self: Pin<&mut Self>, url: &QString, options: &QVariant) -> u64 {
+        let id = task::next_task_id();
+        let url = url.to_string();
+        let options = save_options(options);
+        let whole_world = options.root.is_none() && options.components.is_empty();
+        let qt_thread = self.qt_thread();
+        let mut progress = move |progress: f32| {
+            let _ = qt_thread.queue(move |assets| assets.task_progress(id, progress.into()));
+        };
+        let save = async move {
+            let format = asset::save_scene_file(url, options, &mut progress).await?;
+            if format == SceneFormat::Ron && whole_world {
+                task::world(|world| {
+                    if let Some(mut stack) = world.get_resource_mut::<QmlUndoStack>() {
+                        stack.set_clean();
+                    }
+                })
+                .await;
+            }
+            Ok::<_, String>(())
+        };
+        task::spawn(self.qt_thread(), save, move |assets, result| match result {
+            Ok(()) => assets.task_finished(id, QVariant::default()),
+            Err(error) => assets.task_failed(id, QString::from(&error)),
+        });
+        id
+    }
+
+    pub fn load_scene(self: Pin<&mut Self>, url: &QString) -> u64 {
+        let id = task::next_task_id();
+        let load = asset::load_scene_file(url.to_string());
+        task::spawn(self.qt_thread(), load, move |assets, result| match result {
+            Ok(entities) => {
+                let entities = entities
+                    .into_iter()
+                    .map(|entity| QVariant::from(&entity.to_bits()));
+                assets.task_finished(id, variant::list_to_variant(entities));
+            }
+            Err(error) => assets.task_failed(id, QString::from(&error)),
+        });
+        id
+    }
+
+    pub fn release_asset(self: Pin<&mut Self>, handle_id: u64) {
+        runtime::with_world(|world| {
+            if let Some(mut assets) = world.get_resource_mut::<QmlAssets>() {
+                assets.release(handle_id);
+            }
+        });
+        self.refresh_progress();
+    }
+
+    pub fn is_loaded(&self, handle_id: u64) -> bool {
+        runtime::with_world(|world| {
+            world
+                .get_resource::<QmlAssets>()
+                .is_some_and(|assets| assets.get(handle_id).is_some())
+        })
+        .unwrap_or(false)
+    }
+
+    pub fn open_file_dialog(&self, filters: &QString) -> QString {
+        path_to_qstring(asset::open_file(&filters.to_string()))
+    }
+
+    pub fn save_file_dialog(&self) -> QString {
+        path_to_qstring(asset::save_file())
+    }
+
+    fn refresh_progress(mut self: Pin<&mut Self>) {
+        let progress =
+            runtime::with_world(|world| world.get_resource::<QmlAssets>().map(QmlAssets::progress))
+                .flatten();
+        let Some(progress) = progress.filter(|progress| *progress != self.rust().seen) else {
+            return;
+        };
+
+        self.as_mut().rust_mut().seen = progress;
+        let count = |count: u32| i32::try_from(count).unwrap_or(i32::MAX);
+        let bytes = |bytes: u64| i64::try_from(bytes).unwrap_or(i64::MAX);
+        self.as_mut().set_requested_count(count(progress.requested));
+        self.as_mut().set_loaded_count(count(progress.loaded));
+        self.as_mut().set_failed_count(count(progress.failed));
+        self.as_mut().set_bytes_total(bytes(progress.bytes_total));
+        self.as_mut().set_bytes_loaded(bytes(progress.bytes_loaded));
+        self.as_mut().set_progress(progress.fraction.into());
+        self.set_loading(progress.is_loading());
+    }
+
+    fn emit_outcomes(mut self: Pin<&mut Self>) {
+        let outcomes = runtime::with_world(|world| {
+            world
+                .get_resource_mut::<QmlAssets>()
+                .map(|mut assets| assets.take_outcomes())
+        })
+        .flatten()
+        .unwrap_or_default();
+
+        for outcome in outcomes {
+            match outcome {
+                QmlAssetOutcome::Loaded { id } => self.as_mut().asset_loaded(id),
+                QmlAssetOutcome::Failed { id, error } => {
+                    self.as_mut().asset_failed(id, QString::from(&error))
+                }
+            }
+        }
+    }
+}
+
+/// The options of saveScene, see [SceneSaveOptions]
+fn save_options(options: &QVariant) -> SceneSaveOptions {
+    let mut parsed = SceneSaveOptions::default();
+    for (key, value) in variant::map_entries(options) {
+        match key.as_str() {
+            "root" => {
+                parsed.root = value
+                    .value::<u64>()
+                    .and_then(|bits| Entity::try_from_bits(bits).ok());
+            }
+            "components" => {
+                parsed.components = variant::list_items(&value)
+                    .iter()
+                    .filter_map(|name| name.value::<QString>())
+                    .map(|name| name.to_string())
+                    .collect();
+            }
+            "descendantsOnly" => {
+                parsed.descendants_only = value.value::<bool>().unwrap_or(false);
+            }
+            "resources" => parsed.resources = value.value::<bool>().unwrap_or(false),
+            other => warn!("saveScene has no option {other}"),
+        }
+    }
+    parsed
+}
+
+fn path_to_qstring(path: Option<PathBuf>) -> QString {
+    path.map_or_else(QString::default, |path| QString::from(&*path.to_string_lossy()))
+}
@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that runs benchmarks
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_benchmark")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BenchmarkController based on the Rust struct BenchmarkControllerRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(bool, running)]
+        #[qproperty(f64, progress)]
+        #[qproperty(QString, report)]
+        type BenchmarkController = super::BenchmarkControllerRust;
+
+        /// Run the scenario of the JSON file at the URL, returns whether it
+        /// started
+        #[qinvokable]
+        #[cxx_name = "runBenchmark"]
+        fn run_benchmark(self: Pin<&mut BenchmarkController>, scenario_url: &QString) -> bool;
+
+        /// Run the scenario given as JSON, returns whether it started
+        #[qinvokable]
+        #[cxx_name = "runScenario"]
+        fn run_scenario(self: Pin<&mut BenchmarkController>, scenario: &QString) -> bool;
+
+        /// Stop the running benchmark without a report
+        #[qinvokable]
+        fn cancel(self: Pin<&mut BenchmarkController>);
+
+        /// Emitted with the JSON report once a benchmark finished
+        #[qsignal]
+        fn finished(self: Pin<&mut BenchmarkController>, report: QString);
+
+        /// Emitted when a benchmark cannot start
+        #[qsignal]
+        #[cxx_name = "benchmarkError"]
+        fn benchmark_error(self: Pin<&mut BenchmarkController>, message: QString);
+    }
+
+    impl cxx_qt::Threading for BenchmarkController {}
+    impl cxx_qt::Constructor<()> for BenchmarkController {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::QString;
+
+use crate::{
+    asset,
+    benchmark::{self, BenchmarkScenario, QmlBenchmark},
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// The controller spawns the stress scene of a scenario, records its frame
+/// times and memory and emits `finished` with a JSON report, see
+/// [crate::benchmark] for the scenarios and what the report holds.
+/// `running`, `progress`, from 0 to 1, and `report`, the last one, follow
+/// the benchmark and are not meant to be set from QML. The app keeps
+/// updating while a benchmark runs, also when it renders on demand:
+///
+/// ```qml
+/// BenchmarkController {
+///     id: benchmark
+///     Component.onCompleted: runBenchmark("qrc:/benchmarks/cubes.json")
+///     onFinished: report => {
+///         const result = JSON.parse(report);
+///         console.log(result.fps.toFixed(1), "fps, p99", result.frameTime.p99.toFixed(1), "ms");
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct BenchmarkControllerRust {
+    running: bool,
+    progress: f64,
+    report: QString,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::BenchmarkController {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|controller| controller.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+    }
+}
+
+impl qobject::BenchmarkController {
+    pub fn run_benchmark(self: Pin<&mut Self>, scenario_url: &QString) -> bool {
+        let url = scenario_url.to_string();
+        let scenario = asset::read_url(&url)
+            .and_then(|json| BenchmarkScenario::from_json(&json))
+            .map(|mut scenario| {
+                if scenario.name.is_empty() {
+                    scenario.name = url;
+                }
+                scenario
+            });
+        self.start(scenario)
+    }
+
+    pub fn run_scenario(self: Pin<&mut Self>, scenario: &QString) -> bool {
+        let scenario = BenchmarkScenario::from_json(scenario.to_string().as_bytes());
+        self.start(scenario)
+    }
+
+    pub fn cancel(self: Pin<&mut Self>) {
+        runtime::with_world(benchmark::cancel_benchmark);
+        self.refresh();
+    }
+
+    fn start(mut self: Pin<&mut Self>, scenario: Result<BenchmarkScenario, String>) -> bool {
+        let started = scenario.and_then(|scenario| {
+            runtime::with_world(|world| benchmark::start_benchmark(world, scenario))
+                .unwrap_or_else(|| Err("There is no running Bevy app".to_owned()))
+        });
+        if let Err(message) = started {
+            warn!("BenchmarkController {message}");
+            self.benchmark_error(QString::from(message.as_str()));
+            return false;
+        }
+        self.as_mut().refresh();
+        runtime::request_update();
+        true
+    }
+
+    /// Follow the benchmark, emitting its report once it finished
+    fn refresh(mut self: Pin<&mut Self>) {
+        let Some((running, progress, report)) = runtime::with_world(|world| {
+            let mut benchmark = world.get_resource_mut::<QmlBenchmark>()?;
+            let report = benchmark.take_report();
+            Some((benchmark.is_running(), benchmark.progress(), report))
+        })
+        .flatten() else {
+            return;
+        };
+
+        if *self.running() != running {
+            self.as_mut().set_running(running);
+        }
+        if *self.progress() != progress {
+            self.as_mut().set_progress(progress);
+        }
+        if running {
+            runtime::request_update();
+        }
+        if let Some(report) = report {
+            let report = QString::from(report.to_json().as_str());
+            self.as_mut().set_report(report.clone());
+            self.finished(report);
+        }
+    }
+}
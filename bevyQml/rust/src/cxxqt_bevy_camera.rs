@@ -0,0 +1,245 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML element that declares a camera
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_camera")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qlist.h");
+        /// An alias to the QVariantList type
+        type QList_QVariant = cxx_qt_lib::QList<cxx_qt_lib::QVariant>;
+        include!("cxx-qt-lib/qvector3d.h");
+        /// An alias to the QVector3D type
+        type QVector3D = cxx_qt_lib::QVector3D;
+        include!("bevyqml/convert.h");
+        /// An alias to the QQuaternion type
+        type QQuaternion = crate::convert::QQuaternion;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BevyCamera based on the Rust struct BevyCameraRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(u64, entity)]
+        #[qproperty(QString, name)]
+        #[qproperty(u64, parent_entity)]
+        #[qproperty(QVector3D, position)]
+        #[qproperty(QQuaternion, rotation)]
+        #[qproperty(QVector3D, scale)]
+        #[qproperty(QList_QVariant, components)]
+        #[qproperty(f64, fov)]
+        #[qproperty(f64, near)]
+        #[qproperty(f64, far)]
+        #[qproperty(i32, order)]
+        #[qproperty(bool, active)]
+        #[qproperty(QString, view)]
+        type BevyCamera = super::BevyCameraRust;
+    }
+
+    impl cxx_qt::Threading for BevyCamera {}
+    impl cxx_qt::Constructor<()> for BevyCamera {}
+}
+
+use core::pin::Pin;
+
+use bevy::{prelude::*, render::camera::Projection};
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QList, QString, QVariant, QVector3D};
+
+use crate::{
+    convert::{IntoQt, QQuaternion},
+    declarative::{self, DeclaredEntity, DeclaredProperties},
+    render::QmlView,
+};
+
+/// The Rust struct for the QObject
+///
+/// The element spawns a 3D camera with a perspective projection, whose
+/// vertical field of view `fov` is given in degrees. Cameras with a higher
+/// `order` render on top of those with a lower one, and inactive cameras do
+/// not render at all. A `view` shows the camera in the BevyQuickItems with
+/// that view, see [crate::render::QmlView]. See [crate::declarative] for the
+/// other properties.
+pub struct BevyCameraRust {
+    entity: u64,
+    name: QString,
+    parent_entity: u64,
+    position: QVector3D,
+    rotation: QQuaternion,
+    scale: QVector3D,
+    components: QList<QVariant>,
+    fov: f64,
+    near: f64,
+    far: f64,
+    order: i32,
+    active: bool,
+    view: QString,
+    declared: DeclaredEntity,
+}
+
+impl Default for BevyCameraRust {
+    fn default() -> Self {
+        let projection = PerspectiveProjection::default();
+        Self {
+            entity: 0,
+            name: QString::default(),
+            parent_entity: 0,
+            position: Vec3::ZERO.into_qt(),
+            rotation: QQuaternion::default(),
+            scale: Vec3::ONE.into_qt(),
+            components: QList::default(),
+            fov: f64::from(projection.fov.to_degrees()),
+            near: f64::from(projection.near),
+            far: f64::from(projection.far),
+            order: 0,
+            active: true,
+            view: QString::default(),
+            declared: DeclaredEntity::default(),
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::BevyCamera {
+    fn initialize(mut self: Pin<&mut Self>) {
+        // Spawn once QML has set the initial properties
+        let _ = self.qt_thread().queue(|element| element.spawn());
+
+        self.as_mut()
+            .on_name_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_parent_entity_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_position_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_rotation_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_scale_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_components_changed(|element| element.write_components())
+            .release();
+        self.as_mut()
+            .on_fov_changed(|element| element.write_camera())
+            .release();
+        self.as_mut()
+            .on_near_changed(|element| element.write_camera())
+            .release();
+        self.as_mut()
+            .on_far_changed(|element| element.write_camera())
+            .release();
+        self.as_mut()
+            .on_order_changed(|element| element.write_camera())
+            .release();
+        self.as_mut()
+            .on_active_changed(|element| element.write_camera())
+            .release();
+        self.on_view_changed(|element| element.write_camera())
+            .release();
+    }
+}
+
+impl qobject::BevyCamera {
+    fn spawn(mut self: Pin<&mut Self>) {
+        let properties = self.properties();
+        let components = declarative::component_list(self.components());
+        let camera = self.camera();
+        let Some(entity) = self
+            .as_mut()
+            .rust_mut()
+            .declared
+            .spawn(Camera3dBundle::default())
+        else {
+            warn!("BevyCamera cannot spawn an entity without a running Bevy app");
+            return;
+        };
+        self.rust().declared.update(move |world, entity| {
+            properties.apply(world, entity);
+            camera.apply(world, entity);
+            declarative::apply_components(world, entity, components);
+        });
+        self.set_entity(entity.to_bits());
+    }
+
+    fn properties(&self) -> DeclaredProperties {
+        // Cameras have no visibility to hide them with
+        DeclaredProperties::from_qml(
+            self.name(),
+            *self.parent_entity(),
+            self.position(),
+            self.rotation(),
+            self.scale(),
+            true,
+        )
+    }
+
+    fn camera(&self) -> DeclaredCamera {
+        DeclaredCamera {
+            fov: (*self.fov() as f32).to_radians(),
+            near: *self.near() as f32,
+            far: *self.far() as f32,
+            order: *self.order() as isize,
+            active: *self.active(),
+            view: self.view().to_string(),
+        }
+    }
+
+    fn write_properties(self: Pin<&mut Self>) {
+        let properties = self.properties();
+        self.rust()
+            .declared
+            .update(move |world, entity| properties.apply(world, entity));
+    }
+
+    fn write_components(self: Pin<&mut Self>) {
+        let components = declarative::component_list(self.components());
+        self.rust().declared.update(move |world, entity| {
+            declarative::apply_components(world, entity, components)
+        });
+    }
+
+    fn write_camera(self: Pin<&mut Self>) {
+        let camera = self.camera();
+        self.rust()
+            .declared
+            .update(move |world, entity| camera.apply(world, entity));
+    }
+}
+
+struct DeclaredCamera {
+    fov: f32,
+    near: f32,
+    far: f32,
+    order: isize,
+    active: bool,
+    view: String,
+}
+
+impl DeclaredCamera {
+    fn apply(self, world: &mut World, entity: Entity) {
+        let mut entity = world.entity_mut(entity);
+        if let Some(mut camera) = entity.get_mut::<Camera>() {
+            camera.order = self.order;
+            camera.is_active = self.active;
+        }
+        if let Some(mut projection) = entity.get_mut::<Projection>() {
+            if let Projection::Perspective(perspective) = &mut *projection {
+                perspective.fov = self.fov;
+                perspective.near = self.near;
+                perspective.far = self.far;
+            }
+        }
+        if self.view.is_empty() {
+            entity.remove::<QmlView>();
+        } else if entity.get::<QmlView>().map(|view| view.0.as_str()) != Some(self.view.as_str()) {
+            entity.insert(QmlView::new(self.view));
+        }
+    }
+}
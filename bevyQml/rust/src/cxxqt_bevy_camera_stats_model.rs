@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the list model of what every camera rendered
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_camera_stats_model")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!(<QtCore/QAbstractListModel>);
+
+        include!("cxx-qt-lib/qhash.h");
+        /// An alias to the QHash<int, QByteArray> type
+        type QHash_i32_QByteArray = cxx_qt_lib::QHash<cxx_qt_lib::QHashPair_i32_QByteArray>;
+        include!("cxx-qt-lib/qmodelindex.h");
+        /// An alias to the QModelIndex type
+        type QModelIndex = cxx_qt_lib::QModelIndex;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+        include!("cxx-qt-lib/qvector.h");
+        /// An alias to the QVector<int> type
+        type QVector_i32 = cxx_qt_lib::QVector<i32>;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // CameraStatsModel based on the Rust struct CameraStatsModelRust.
+        #[qobject]
+        #[qml_element]
+        #[base = "QAbstractListModel"]
+        #[qproperty(i32, count)]
+        type CameraStatsModel = super::CameraStatsModelRust;
+    }
+
+    // QAbstractListModel implementation
+    unsafe extern "RustQt" {
+        #[inherit]
+        #[cxx_name = "beginResetModel"]
+        unsafe fn begin_reset_model(self: Pin<&mut CameraStatsModel>);
+
+        #[inherit]
+        #[cxx_name = "endResetModel"]
+        unsafe fn end_reset_model(self: Pin<&mut CameraStatsModel>);
+
+        #[inherit]
+        fn index(
+            self: &CameraStatsModel,
+            row: i32,
+            column: i32,
+            parent: &QModelIndex,
+        ) -> QModelIndex;
+
+        #[qsignal]
+        #[inherit]
+        #[cxx_name = "dataChanged"]
+        fn data_changed(
+            self: Pin<&mut CameraStatsModel>,
+            top_left: &QModelIndex,
+            bottom_right: &QModelIndex,
+            roles: &QVector_i32,
+        );
+    }
+
+    unsafe extern "RustQt" {
+        #[qinvokable]
+        #[cxx_override]
+        fn data(self: &CameraStatsModel, index: &QModelIndex, role: i32) -> QVariant;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "roleNames"]
+        fn role_names(self: &CameraStatsModel) -> QHash_i32_QByteArray;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "rowCount"]
+        fn row_count(self: &CameraStatsModel, parent: &QModelIndex) -> i32;
+    }
+
+    impl cxx_qt::Threading for CameraStatsModel {}
+    impl cxx_qt::Constructor<()> for CameraStatsModel {}
+}
+
+use core::pin::Pin;
+
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{
+    QByteArray, QHash, QHashPair_i32_QByteArray, QModelIndex, QString, QVariant, QVector,
+};
+
+use crate::{
+    diagnostics::{CameraStats, QmlCameraStats},
+    runtime::{self, UpdateListener},
+};
+
+/// Qt::UserRole, the first role available to custom models
+const USER_ROLE: i32 = 0x0100;
+
+const CAMERA_ROLE: i32 = USER_ROLE;
+const NAME_ROLE: i32 = USER_ROLE + 1;
+const VISIBLE_ROLE: i32 = USER_ROLE + 2;
+const CULLED_ROLE: i32 = USER_ROLE + 3;
+const DRAW_CALLS_ROLE: i32 = USER_ROLE + 4;
+const TRIANGLES_ROLE: i32 = USER_ROLE + 5;
+
+/// The Rust struct for the QObject
+///
+/// The model lists the active cameras of the world in the order they render
+/// in, with what they rendered in the last frame, see
+/// [crate::diagnostics::CameraStats]. Its roles are `camera`, the entity
+/// bits, `name`, `visible` and `culled`, the meshes inside and outside of the
+/// frustum of the camera, `drawCalls` and `triangles`:
+///
+/// ```qml
+/// ListView {
+///     model: CameraStatsModel {}
+///     delegate: Label {
+///         text: qsTr("%1: %2 visible, %3 culled, %4 draws, %5 triangles")
+///             .arg(model.name || model.camera).arg(model.visible).arg(model.culled)
+///             .arg(model.drawCalls).arg(model.triangles)
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct CameraStatsModelRust {
+    count: i32,
+    rows: Vec<CameraStats>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::CameraStatsModel {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|model| model.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+        self.refresh();
+    }
+}
+
+impl qobject::CameraStatsModel {
+    pub fn data(&self, index: &QModelIndex, role: i32) -> QVariant {
+        let Some(row) = usize::try_from(index.row())
+            .ok()
+            .and_then(|row| self.rust().rows.get(row))
+        else {
+            return QVariant::default();
+        };
+
+        match role {
+            CAMERA_ROLE => QVariant::from(&row.camera.to_bits()),
+            NAME_ROLE => QVariant::from(&QString::from(&row.name)),
+            VISIBLE_ROLE => QVariant::from(&row.visible),
+            CULLED_ROLE => QVariant::from(&row.culled),
+            DRAW_CALLS_ROLE => QVariant::from(&row.draw_calls),
+            TRIANGLES_ROLE => QVariant::from(&row.triangles),
+            _ => QVariant::default(),
+        }
+    }
+
+    pub fn role_names(&self) -> QHash<QHashPair_i32_QByteArray> {
+        let mut roles = QHash::<QHashPair_i32_QByteArray>::default();
+        roles.insert(CAMERA_ROLE, QByteArray::from("camera"));
+        roles.insert(NAME_ROLE, QByteArray::from("name"));
+        roles.insert(VISIBLE_ROLE, QByteArray::from("visible"));
+        roles.insert(CULLED_ROLE, QByteArray::from("culled"));
+        roles.insert(DRAW_CALLS_ROLE, QByteArray::from("drawCalls"));
+        roles.insert(TRIANGLES_ROLE, QByteArray::from("triangles"));
+        roles
+    }
+
+    pub fn row_count(&self, _parent: &QModelIndex) -> i32 {
+        self.rust().rows.len() as i32
+    }
+
+    /// Read the statistics of the last frame, notifying only the rows which
+    /// changed while the cameras stay the same
+    fn refresh(mut self: Pin<&mut Self>) {
+        let Some(rows) = runtime::with_world(|world| {
+            world
+                .get_resource::<QmlCameraStats>()
+                .map(|stats| stats.cameras.clone())
+        })
+        .flatten() else {
+            return;
+        };
+        if rows == self.rust().rows {
+            return;
+        }
+
+        let old = std::mem::take(&mut self.as_mut().rust_mut().rows);
+        let same_cameras = rows.len() == old.len()
+            && rows
+                .iter()
+                .zip(&old)
+                .all(|(row, old)| row.camera == old.camera);
+        if same_cameras {
+            let changed: Vec<usize> = rows
+                .iter()
+                .zip(&old)
+                .enumerate()
+                .filter(|(_, (row, old))| row != old)
+                .map(|(index, _)| index)
+                .collect();
+            self.as_mut().rust_mut().rows = rows;
+            let roles = QVector::<i32>::default();
+            for row in changed {
+                let index = self.index(row as i32, 0, &QModelIndex::default());
+                self.as_mut().data_changed(&index, &index, &roles);
+            }
+            return;
+        }
+
+        let count = rows.len() as i32;
+        unsafe {
+            self.as_mut().begin_reset_model();
+        }
+        self.as_mut().rust_mut().rows = rows;
+        unsafe {
+            self.as_mut().end_reset_model();
+        }
+        if *self.count() != count {
+            self.as_mut().set_count(count);
+        }
+    }
+}
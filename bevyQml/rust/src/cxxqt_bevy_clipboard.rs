@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML singleton that reaches the clipboard
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_clipboard")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qimage.h");
+        /// An alias to the QImage type
+        type QImage = cxx_qt_lib::QImage;
+        include!("cxx-qt-lib/qlist.h");
+        /// An alias to the QList<quint64> type
+        type QList_u64 = cxx_qt_lib::QList<u64>;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QML singleton with the name
+        // ClipboardBridge based on the Rust struct ClipboardBridgeRust.
+        #[qobject]
+        #[qml_element]
+        #[qml_singleton]
+        type ClipboardBridge = super::ClipboardBridgeRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// The text on the clipboard, empty if there is none
+        #[qinvokable]
+        fn text(self: &ClipboardBridge) -> QString;
+
+        #[qinvokable]
+        #[cxx_name = "setText"]
+        fn set_text(self: &ClipboardBridge, text: &QString);
+
+        /// The image on the clipboard, a null image if there is none
+        #[qinvokable]
+        fn image(self: &ClipboardBridge) -> QImage;
+
+        /// Put an image on the clipboard, such as one emitted by
+        /// BevyQuickItem.frameCaptured
+        #[qinvokable]
+        #[cxx_name = "setImage"]
+        fn set_image(self: &ClipboardBridge, image: &QImage);
+
+        /// Copy the entities, their children and their reflected components
+        /// as a RON scene, returns whether they were copied
+        #[qinvokable]
+        #[cxx_name = "copyEntities"]
+        fn copy_entities(self: &ClipboardBridge, entities: &QList_u64) -> bool;
+
+        /// Spawn the entities of a RON scene on the clipboard below the
+        /// parent, or at the top level if it is 0, and select them
+        ///
+        /// Returns the new top-most entities.
+        #[qinvokable]
+        #[cxx_name = "pasteEntities"]
+        fn paste_entities(self: &ClipboardBridge, parent: u64) -> QList_u64;
+
+        /// Spawn copies of the entities and their children next to them,
+        /// and select them
+        ///
+        /// Returns the new top-most entities.
+        #[qinvokable]
+        fn duplicate(self: &ClipboardBridge, entities: &QList_u64) -> QList_u64;
+    }
+
+    impl cxx_qt::Constructor<()> for ClipboardBridge {}
+}
+
+use bevy::prelude::*;
+use cxx_qt_lib::{QImage, QList, QString};
+
+use crate::{clipboard, runtime, selection::Selection};
+
+/// The Rust struct for the QObject
+///
+/// The clipboard is shared with Bevy through [crate::clipboard::QtClipboard],
+/// so what QML copies shows up there after the next update and the other
+/// way around. Entities are copied as text along with their children, so
+/// they can be pasted into another instance of the application as well.
+/// Pasted and duplicated entities replace the selection of `BevySelection`,
+/// and go on the undo stack of `BevyUndoStack`:
+///
+/// ```qml
+/// Shortcut {
+///     sequence: StandardKey.Copy
+///     onActivated: ClipboardBridge.copyEntities(BevySelection.entities)
+/// }
+/// Shortcut {
+///     sequence: StandardKey.Paste
+///     onActivated: ClipboardBridge.pasteEntities(0)
+/// }
+/// Shortcut {
+///     sequence: "Ctrl+D"
+///     onActivated: ClipboardBridge.duplicate(BevySelection.entities)
+/// }
+/// ```
+#[derive(Default)]
+pub struct ClipboardBridgeRust;
+
+impl qobject::ClipboardBridge {
+    pub fn text(&self) -> QString {
+        QString::from(clipboard::text().as_str())
+    }
+
+    pub fn set_text(&self, text: &QString) {
+        clipboard::set_text(&text.to_string());
+    }
+
+    pub fn image(&self) -> QImage {
+        clipboard::image()
+    }
+
+    pub fn set_image(&self, image: &QImage) {
+        clipboard::set_image(image);
+    }
+
+    pub fn copy_entities(&self, entities: &QList<u64>) -> bool {
+        let entities = entities_from_bits(entities);
+        let ron = runtime::with_world(|world| clipboard::entities_to_ron(world, entities));
+        match ron {
+            Some(Ok(ron)) => {
+                clipboard::set_text(&ron);
+                true
+            }
+            Some(Err(error)) => {
+                warn!("ClipboardBridge failed to copy entities: {error}");
+                false
+            }
+            None => false,
+        }
+    }
+
+    pub fn paste_entities(&self, parent: u64) -> QList<u64> {
+        let ron = clipboard::text();
+        // 0 is no parent rather than an invalid entity
+        let parent = (parent != 0)
+            .then(|| Entity::try_from_bits(parent).ok())
+            .flatten();
+        let spawned = runtime::with_world(|world| {
+            clipboard::spawn_ron(world, &ron, parent).inspect(|entities| select(world, entities))
+        });
+        finish("paste", spawned)
+    }
+
+    pub fn duplicate(&self, entities: &QList<u64>) -> QList<u64> {
+        let entities = entities_from_bits(entities);
+        let spawned = runtime::with_world(|world| {
+            clipboard::duplicate_entities(world, entities)
+                .inspect(|entities| select(world, entities))
+        });
+        finish("duplicate", spawned)
+    }
+}
+
+fn entities_from_bits(entities: &QList<u64>) -> Vec<Entity> {
+    entities
+        .iter()
+        .filter_map(|bits| Entity::try_from_bits(*bits).ok())
+        .collect()
+}
+
+/// Make the new entities the selection, if the app has one
+fn select(world: &mut World, entities: &[Entity]) {
+    if let Some(mut selection) = world.get_resource_mut::<Selection>() {
+        selection.set(entities.iter().copied());
+    }
+}
+
+/// The new entities for QML
+fn finish(what: &str, spawned: Option<Result<Vec<Entity>, String>>) -> QList<u64> {
+    runtime::request_update();
+    let mut list = QList::default();
+    match spawned {
+        Some(Ok(entities)) => {
+            for entity in entities {
+                list.append(entity.to_bits());
+            }
+        }
+        Some(Err(error)) => warn!("ClipboardBridge failed to {what} entities: {error}"),
+        None => {}
+    }
+    list
+}
@@ -0,0 +1,298 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML singleton that queues world mutations
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_commands")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qstringlist.h");
+        /// An alias to the QStringList type
+        type QStringList = cxx_qt_lib::QStringList;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+        include!("cxx-qt-lib/qmap.h");
+        /// An alias to the QVariantMap type
+        type QMap_QString_QVariant = cxx_qt_lib::QMap<cxx_qt_lib::QMapPair_QString_QVariant>;
+        include!("bevyqml/convert.h");
+        /// An alias to the QMatrix4x4 type
+        type QMatrix4x4 = crate::convert::QMatrix4x4;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QML singleton with the name
+        // BevyCommands based on the Rust struct BevyCommandsRust.
+        #[qobject]
+        #[qml_element]
+        #[qml_singleton]
+        type BevyCommands = super::BevyCommandsRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// Reserve a new entity and queue spawning it, returns the entity bits
+        ///
+        /// Returns 0 if no app is running.
+        #[qinvokable]
+        #[cxx_name = "spawnEntity"]
+        fn spawn_entity(self: &BevyCommands) -> u64;
+
+        /// Queue despawning an entity along with its children
+        #[qinvokable]
+        fn despawn(self: &BevyCommands, entity: u64);
+
+        /// Queue inserting a reflected component, or changing the given fields
+        /// of it if the entity already has one
+        #[qinvokable]
+        #[cxx_name = "insertComponent"]
+        fn insert_component(
+            self: &BevyCommands,
+            entity: u64,
+            type_name: &QString,
+            fields: &QMap_QString_QVariant,
+        );
+
+        /// The current value of a reflected component of an entity
+        ///
+        /// Struct components become an object of their fields. Returns
+        /// undefined if the entity has no such component or no app is
+        /// running.
+        #[qinvokable]
+        #[cxx_name = "getComponent"]
+        fn get_component(self: &BevyCommands, entity: u64, type_name: &QString) -> QVariant;
+
+        /// Queue inserting a reflected component, or overwriting what the
+        /// value mentions of it if the entity already has one
+        #[qinvokable]
+        #[cxx_name = "setComponent"]
+        fn set_component(self: &BevyCommands, entity: u64, type_name: &QString, value: &QVariant);
+
+        /// The type paths of the reflected components of an entity
+        #[qinvokable]
+        #[cxx_name = "componentNames"]
+        fn component_names(self: &BevyCommands, entity: u64) -> QStringList;
+
+        /// Queue inserting a reflected resource, or changing the given fields
+        /// of it if it already exists
+        #[qinvokable]
+        #[cxx_name = "setResource"]
+        fn set_resource(self: &BevyCommands, type_name: &QString, fields: &QMap_QString_QVariant);
+
+        /// Queue sending an event registered with a SendEventBridge
+        #[qinvokable]
+        #[cxx_name = "sendEvent"]
+        fn send_event(self: &BevyCommands, name: &QString, fields: &QMap_QString_QVariant);
+
+        /// Queue spawning the `.scn.ron` or glTF scene at a URL below a new
+        /// root entity placed with the transform, returns the root entity
+        ///
+        /// The root becomes a child of parentEntity unless that is 0.
+        /// Returns 0 if the URL is invalid or no app is running.
+        #[qinvokable]
+        #[cxx_name = "spawnScene"]
+        fn spawn_scene(
+            self: &BevyCommands,
+            url: &QString,
+            parent_entity: u64,
+            transform: &QMatrix4x4,
+        ) -> u64;
+
+        /// Queue despawning a scene spawned with spawnScene, given its root
+        #[qinvokable]
+        #[cxx_name = "despawnScene"]
+        fn despawn_scene(self: &BevyCommands, root: u64);
+    }
+}
+
+use bevy::prelude::*;
+use cxx_qt_lib::{QList, QMap, QMapPair_QString_QVariant, QString, QStringList, QVariant};
+
+use crate::{
+    asset,
+    commands::{self, QmlCommand, QmlCommandQueue},
+    convert::{FromQt, QMatrix4x4},
+    runtime,
+};
+
+/// The Rust struct for the QObject
+///
+/// Components and resources are looked up by their short or full type path
+/// and have to be registered with the type registry. New values start from
+/// the reflected `Default`, so the types need `#[reflect(Default)]`.
+///
+/// ```qml
+/// Button {
+///     onClicked: {
+///         const entity = BevyCommands.spawnEntity()
+///         BevyCommands.insertComponent(entity, "Transform", {})
+///         BevyCommands.setResource("GameSettings", { "volume": 0.5 })
+///         BevyCommands.sendEvent("StartGame", { "level": 2 })
+///     }
+/// }
+/// ```
+///
+/// Any reflected component can be read and written as a whole, which is
+/// enough for a property editor that knows nothing about the types:
+///
+/// ```qml
+/// Repeater {
+///     model: BevyCommands.componentNames(selected)
+///     delegate: ComponentEditor {
+///         value: BevyCommands.getComponent(selected, modelData)
+///         onEdited: (value) => BevyCommands.setComponent(selected, modelData, value)
+///     }
+/// }
+/// ```
+///
+/// See [crate::variant] for how the values look in QML.
+///
+/// Scenes are placed as prefabs, and are despawned by their root entity:
+///
+/// ```qml
+/// onClicked: {
+///     const transform = Qt.matrix4x4();
+///     transform.translate(hit.position);
+///     placed.push(BevyCommands.spawnScene("models/crate.glb", 0, transform));
+/// }
+/// ```
+#[derive(Default)]
+pub struct BevyCommandsRust;
+
+impl qobject::BevyCommands {
+    pub fn spawn_entity(&self) -> u64 {
+        // Reserving only needs shared access, so the entity id can be handed
+        // back straight away while the spawn itself is queued
+        let Some(entity) = runtime::with_world(|world| world.entities().reserve_entity()) else {
+            warn!("Cannot spawn an entity from QML without a running Bevy app");
+            return 0;
+        };
+        QmlCommandQueue::global().push(QmlCommand::Spawn(entity));
+        entity.to_bits()
+    }
+
+    pub fn despawn(&self, entity: u64) {
+        if let Some(entity) = entity_from_bits(entity) {
+            QmlCommandQueue::global().push(QmlCommand::Despawn(entity));
+        }
+    }
+
+    pub fn insert_component(
+        &self,
+        entity: u64,
+        type_name: &QString,
+        fields: &QMap<QMapPair_QString_QVariant>,
+    ) {
+        if let Some(entity) = entity_from_bits(entity) {
+            QmlCommandQueue::global().push(QmlCommand::InsertComponent {
+                entity,
+                type_name: type_name.to_string(),
+                fields: field_list(fields),
+            });
+        }
+    }
+
+    pub fn get_component(&self, entity: u64, type_name: &QString) -> QVariant {
+        let Some(entity) = entity_from_bits(entity) else {
+            return QVariant::default();
+        };
+        let type_name = type_name.to_string();
+        let value = runtime::with_world(|world| {
+            let registry = world.resource::<AppTypeRegistry>().clone();
+            let registry = registry.read();
+            commands::read_component(world, &registry, entity, &type_name)
+        });
+        match value {
+            Some(Ok(value)) => value,
+            Some(Err(error)) => {
+                warn!("Cannot read a component for QML: {error}");
+                QVariant::default()
+            }
+            None => QVariant::default(),
+        }
+    }
+
+    pub fn set_component(&self, entity: u64, type_name: &QString, value: &QVariant) {
+        if let Some(entity) = entity_from_bits(entity) {
+            QmlCommandQueue::global().push(QmlCommand::SetComponent {
+                entity,
+                type_name: type_name.to_string(),
+                value: value.clone(),
+            });
+        }
+    }
+
+    pub fn component_names(&self, entity: u64) -> QStringList {
+        let mut list = QList::<QString>::default();
+        let names = entity_from_bits(entity).and_then(|entity| {
+            runtime::with_world(|world| {
+                let registry = world.resource::<AppTypeRegistry>().clone();
+                let registry = registry.read();
+                commands::component_names(world, &registry, entity)
+            })
+        });
+        for name in names.unwrap_or_default() {
+            list.append(QString::from(name.as_str()));
+        }
+        QStringList::from(&list)
+    }
+
+    pub fn set_resource(&self, type_name: &QString, fields: &QMap<QMapPair_QString_QVariant>) {
+        QmlCommandQueue::global().push(QmlCommand::SetResource {
+            type_name: type_name.to_string(),
+            fields: field_list(fields),
+        });
+    }
+
+    pub fn send_event(&self, name: &QString, fields: &QMap<QMapPair_QString_QVariant>) {
+        QmlCommandQueue::global().push(QmlCommand::SendEvent {
+            name: name.to_string(),
+            fields: field_list(fields),
+        });
+    }
+
+    pub fn spawn_scene(&self, url: &QString, parent_entity: u64, transform: &QMatrix4x4) -> u64 {
+        let url = url.to_string();
+        if let Err(error) = asset::resolve_url(&url) {
+            warn!("Cannot spawn the scene {url:?}: {error}");
+            return 0;
+        }
+        let Some(root) = runtime::with_world(|world| world.entities().reserve_entity()) else {
+            warn!("Cannot spawn a scene from QML without a running Bevy app");
+            return 0;
+        };
+        // 0 is no parent rather than an invalid entity
+        let parent = (parent_entity != 0)
+            .then(|| entity_from_bits(parent_entity))
+            .flatten();
+        QmlCommandQueue::global().push(QmlCommand::SpawnScene {
+            root,
+            url,
+            parent,
+            transform: Transform::from_matrix(Mat4::from_qt(transform)),
+        });
+        root.to_bits()
+    }
+
+    pub fn despawn_scene(&self, root: u64) {
+        if let Some(root) = entity_from_bits(root) {
+            QmlCommandQueue::global().push(QmlCommand::DespawnScene(root));
+        }
+    }
+}
+
+fn entity_from_bits(bits: u64) -> Option<Entity> {
+    let entity = Entity::try_from_bits(bits).ok();
+    if entity.is_none() {
+        warn!("{bits} passed from QML is not a valid entity");
+    }
+    entity
+}
+
+fn field_list(fields: &QMap<QMapPair_QString_QVariant>) -> Vec<(String, QVariant)> {
+    fields
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect()
+}
@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that mirrors a component of an entity
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_component")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+        include!("cxx-qt-lib/qmap.h");
+        /// An alias to the QVariantMap type
+        type QMap_QString_QVariant = cxx_qt_lib::QMap<cxx_qt_lib::QMapPair_QString_QVariant>;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BevyComponent based on the Rust struct BevyComponentRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(u64, entity)]
+        #[qproperty(QString, component)]
+        #[qproperty(QMap_QString_QVariant, values)]
+        type BevyComponent = super::BevyComponentRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// The current value of a field of the component
+        #[qinvokable]
+        fn value(self: &BevyComponent, field: &QString) -> QVariant;
+
+        /// Change a field of the component with the next update of the app,
+        /// returns false if the component has no such field
+        #[qinvokable]
+        #[cxx_name = "setValue"]
+        fn set_value(self: Pin<&mut BevyComponent>, field: &QString, value: &QVariant) -> bool;
+    }
+
+    impl cxx_qt::Threading for BevyComponent {}
+    impl cxx_qt::Constructor<()> for BevyComponent {}
+}
+
+use core::pin::Pin;
+
+use bevy::{ecs::component::Tick, prelude::*};
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QMap, QMapPair_QString_QVariant, QString, QVariant};
+
+use crate::{
+    commands::{QmlCommand, QmlCommandQueue},
+    component::QmlComponents,
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// `entity` takes the bits of an [Entity] as returned by [Entity::to_bits],
+/// and `component` the name of a component registered with a
+/// [crate::component::QmlComponentPlugin]. `values` then holds every exposed
+/// field of that component and follows its changes in Bevy, and is empty
+/// while the entity does not have the component. `setValue`
+/// queues the write as a [QmlCommand], so it reaches the world at the same
+/// point of the frame as the other changes made from QML, and a value which
+/// does not fit the field is dropped with a warning then.
+#[derive(Default)]
+pub struct BevyComponentRust {
+    entity: u64,
+    component: QString,
+    values: QMap<QMapPair_QString_QVariant>,
+    changed: Option<Tick>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::BevyComponent {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|component| component.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.as_mut()
+            .on_entity_changed(|component| component.retarget())
+            .release();
+        self.on_component_changed(|component| component.retarget())
+            .release();
+    }
+}
+
+impl qobject::BevyComponent {
+    pub fn value(&self, field: &QString) -> QVariant {
+        self.values().get_or_default(field)
+    }
+
+    pub fn set_value(mut self: Pin<&mut Self>, field: &QString, value: &QVariant) -> bool {
+        let Some(entity) = self.target_entity() else {
+            return false;
+        };
+        let name = self.component().to_string();
+        let field_name = field.to_string();
+        let known = runtime::with_world(|world| {
+            world
+                .get_resource::<QmlComponents>()
+                .is_some_and(|components| components.has_field(&name, &field_name))
+        })
+        .unwrap_or(false);
+        if !known {
+            return false;
+        }
+
+        QmlCommandQueue::global().push(QmlCommand::WriteField {
+            entity,
+            component: name,
+            field: field_name,
+            value: value.clone(),
+        });
+        // Show the new value straight away, and read the component again
+        // once the write was applied in case the value did not fit
+        let mut values = self.values().clone();
+        values.insert_clone(field, value);
+        self.as_mut().rust_mut().changed = None;
+        self.as_mut().set_values(values);
+        true
+    }
+
+    fn target_entity(&self) -> Option<Entity> {
+        Entity::try_from_bits(*self.entity()).ok()
+    }
+
+    /// Forget the previous component and read the new one
+    fn retarget(mut self: Pin<&mut Self>) {
+        self.as_mut().rust_mut().changed = None;
+        self.as_mut().set_values(QMap::default());
+        self.refresh();
+    }
+
+    /// Read the component again if it has changed since the last refresh, or
+    /// clear the values once the entity or the component is gone
+    fn refresh(mut self: Pin<&mut Self>) {
+        let Some(entity) = self.target_entity() else {
+            return;
+        };
+        let name = self.component().to_string();
+        let Some(values) = runtime::with_world(|world| {
+            world
+                .get_resource::<QmlComponents>()
+                .and_then(|components| components.read(world, entity, &name))
+        }) else {
+            return;
+        };
+
+        let Some(values) = values else {
+            if self.rust().changed.is_some() || !self.values().is_empty() {
+                self.as_mut().rust_mut().changed = None;
+                self.set_values(QMap::default());
+            }
+            return;
+        };
+        if self.rust().changed == Some(values.changed) {
+            return;
+        }
+
+        let mut map = QMap::<QMapPair_QString_QVariant>::default();
+        for (field, value) in values.fields {
+            map.insert(QString::from(field), value);
+        }
+        self.as_mut().rust_mut().changed = Some(values.changed);
+        self.set_values(map);
+    }
+}
@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML singleton that shows Bevy diagnostics
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_diagnostics")]
+pub mod qobject {
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QML singleton with the name
+        // DiagnosticsBridge based on the Rust struct DiagnosticsBridgeRust.
+        #[qobject]
+        #[qml_element]
+        #[qml_singleton]
+        #[qproperty(f64, fps)]
+        #[qproperty(f64, frame_time)]
+        #[qproperty(u64, entity_count)]
+        #[qproperty(u64, draw_calls)]
+        #[qproperty(f64, gpu_time)]
+        #[qproperty(f64, gpu_shadow_time)]
+        #[qproperty(f64, gpu_opaque_time)]
+        #[qproperty(f64, gpu_transparent_time)]
+        #[qproperty(f64, gpu_post_time)]
+        #[qproperty(bool, gpu_timed)]
+        #[qproperty(bool, gpu_bound)]
+        type DiagnosticsBridge = super::DiagnosticsBridgeRust;
+    }
+
+    impl cxx_qt::Threading for DiagnosticsBridge {}
+    impl cxx_qt::Constructor<()> for DiagnosticsBridge {}
+}
+
+use core::pin::Pin;
+
+use cxx_qt::{CxxQtType, Threading};
+
+use crate::{
+    diagnostics::DiagnosticsSample,
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// `fps` and `frameTime`, in milliseconds, are smoothed over the last
+/// frames, `entityCount` counts the entities of the world and `drawCalls`
+/// the draw calls of the last rendered frame. They follow the world after
+/// every update, and only notify when they change:
+///
+/// ```qml
+/// Text { text: DiagnosticsBridge.fps.toFixed(0) + " fps" }
+/// ```
+///
+/// `gpuTime` is how long the GPU took for the render passes of a frame, in
+/// milliseconds and smoothed like `frameTime`, split up into
+/// `gpuShadowTime`, `gpuOpaqueTime`, `gpuTransparentTime` and
+/// `gpuPostTime`. `gpuTimed` tells whether the adapter supports the
+/// timestamp queries they are measured with, and `gpuBound` whether the
+/// frames wait for the GPU rather than the CPU:
+///
+/// ```qml
+/// Text {
+///     visible: DiagnosticsBridge.gpuTimed
+///     color: DiagnosticsBridge.gpuBound ? "orange" : "white"
+///     text: "GPU " + DiagnosticsBridge.gpuTime.toFixed(1) + " ms"
+/// }
+/// ```
+#[derive(Default)]
+pub struct DiagnosticsBridgeRust {
+    fps: f64,
+    frame_time: f64,
+    entity_count: u64,
+    draw_calls: u64,
+    gpu_time: f64,
+    gpu_shadow_time: f64,
+    gpu_opaque_time: f64,
+    gpu_transparent_time: f64,
+    gpu_post_time: f64,
+    gpu_timed: bool,
+    gpu_bound: bool,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::DiagnosticsBridge {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|diagnostics| diagnostics.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+        self.refresh();
+    }
+}
+
+impl qobject::DiagnosticsBridge {
+    fn refresh(mut self: Pin<&mut Self>) {
+        let Some(sample) = runtime::with_world(|world| DiagnosticsSample::read(world)).flatten()
+        else {
+            return;
+        };
+
+        if *self.fps() != sample.fps {
+            self.as_mut().set_fps(sample.fps);
+        }
+        if *self.frame_time() != sample.frame_time {
+            self.as_mut().set_frame_time(sample.frame_time);
+        }
+        if *self.entity_count() != sample.entity_count {
+            self.as_mut().set_entity_count(sample.entity_count);
+        }
+        if *self.draw_calls() != sample.draw_calls {
+            self.as_mut().set_draw_calls(sample.draw_calls);
+        }
+        if *self.gpu_time() != sample.gpu_time {
+            self.as_mut().set_gpu_time(sample.gpu_time);
+        }
+        if *self.gpu_shadow_time() != sample.gpu_shadow_time {
+            self.as_mut().set_gpu_shadow_time(sample.gpu_shadow_time);
+        }
+        if *self.gpu_opaque_time() != sample.gpu_opaque_time {
+            self.as_mut().set_gpu_opaque_time(sample.gpu_opaque_time);
+        }
+        if *self.gpu_transparent_time() != sample.gpu_transparent_time {
+            self.as_mut()
+                .set_gpu_transparent_time(sample.gpu_transparent_time);
+        }
+        if *self.gpu_post_time() != sample.gpu_post_time {
+            self.as_mut().set_gpu_post_time(sample.gpu_post_time);
+        }
+        if *self.gpu_timed() != sample.gpu_timed() {
+            self.as_mut().set_gpu_timed(sample.gpu_timed());
+        }
+        if *self.gpu_bound() != sample.gpu_bound() {
+            self.as_mut().set_gpu_bound(sample.gpu_bound());
+        }
+    }
+}
@@ -0,0 +1,243 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the list model of the recent Bevy diagnostics
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_diagnostics_history")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!(<QtCore/QAbstractListModel>);
+
+        include!("cxx-qt-lib/qhash.h");
+        /// An alias to the QHash<int, QByteArray> type
+        type QHash_i32_QByteArray = cxx_qt_lib::QHash<cxx_qt_lib::QHashPair_i32_QByteArray>;
+        include!("cxx-qt-lib/qmodelindex.h");
+        /// An alias to the QModelIndex type
+        type QModelIndex = cxx_qt_lib::QModelIndex;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // DiagnosticsHistoryModel based on the Rust struct
+        // DiagnosticsHistoryModelRust.
+        #[qobject]
+        #[qml_element]
+        #[base = "QAbstractListModel"]
+        #[qproperty(i32, capacity)]
+        #[qproperty(i32, count)]
+        #[qproperty(f64, max_fps)]
+        #[qproperty(f64, frame_time_p50)]
+        #[qproperty(f64, frame_time_p95)]
+        #[qproperty(f64, frame_time_p99)]
+        type DiagnosticsHistoryModel = super::DiagnosticsHistoryModelRust;
+    }
+
+    // QAbstractListModel implementation
+    unsafe extern "RustQt" {
+        #[inherit]
+        #[cxx_name = "beginInsertRows"]
+        unsafe fn begin_insert_rows(
+            self: Pin<&mut DiagnosticsHistoryModel>,
+            parent: &QModelIndex,
+            first: i32,
+            last: i32,
+        );
+
+        #[inherit]
+        #[cxx_name = "endInsertRows"]
+        unsafe fn end_insert_rows(self: Pin<&mut DiagnosticsHistoryModel>);
+
+        #[inherit]
+        #[cxx_name = "beginRemoveRows"]
+        unsafe fn begin_remove_rows(
+            self: Pin<&mut DiagnosticsHistoryModel>,
+            parent: &QModelIndex,
+            first: i32,
+            last: i32,
+        );
+
+        #[inherit]
+        #[cxx_name = "endRemoveRows"]
+        unsafe fn end_remove_rows(self: Pin<&mut DiagnosticsHistoryModel>);
+    }
+
+    unsafe extern "RustQt" {
+        #[qinvokable]
+        #[cxx_override]
+        fn data(self: &DiagnosticsHistoryModel, index: &QModelIndex, role: i32) -> QVariant;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "roleNames"]
+        fn role_names(self: &DiagnosticsHistoryModel) -> QHash_i32_QByteArray;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "rowCount"]
+        fn row_count(self: &DiagnosticsHistoryModel, parent: &QModelIndex) -> i32;
+    }
+
+    impl cxx_qt::Threading for DiagnosticsHistoryModel {}
+    impl cxx_qt::Constructor<()> for DiagnosticsHistoryModel {}
+}
+
+use core::pin::Pin;
+use std::collections::VecDeque;
+
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QByteArray, QHash, QHashPair_i32_QByteArray, QModelIndex, QVariant};
+
+use crate::{
+    diagnostics::{percentile, DiagnosticsSample},
+    runtime::{self, UpdateListener},
+};
+
+/// Qt::UserRole, the first role available to custom models
+const USER_ROLE: i32 = 0x0100;
+
+const FPS_ROLE: i32 = USER_ROLE;
+const FRAME_TIME_ROLE: i32 = USER_ROLE + 1;
+const ENTITY_COUNT_ROLE: i32 = USER_ROLE + 2;
+const DRAW_CALLS_ROLE: i32 = USER_ROLE + 3;
+const GPU_TIME_ROLE: i32 = USER_ROLE + 4;
+
+/// How many samples are kept unless `capacity` is set
+const DEFAULT_CAPACITY: i32 = 120;
+
+/// The Rust struct for the QObject
+///
+/// The model is a ring buffer of the diagnostics sampled after each of the
+/// last `capacity` updates, oldest first, with the roles `fps`, `frameTime`,
+/// `entityCount`, `drawCalls` and `gpuTime`. `maxFps` and the `frameTimeP50`, `P95`
+/// and `P99` percentiles, in milliseconds, cover the samples in the model.
+pub struct DiagnosticsHistoryModelRust {
+    capacity: i32,
+    count: i32,
+    max_fps: f64,
+    frame_time_p50: f64,
+    frame_time_p95: f64,
+    frame_time_p99: f64,
+    samples: VecDeque<DiagnosticsSample>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for DiagnosticsHistoryModelRust {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            count: 0,
+            max_fps: 0.0,
+            frame_time_p50: 0.0,
+            frame_time_p95: 0.0,
+            frame_time_p99: 0.0,
+            samples: VecDeque::new(),
+            update_listener: None,
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::DiagnosticsHistoryModel {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|model| model.sample());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.on_capacity_changed(|model| model.trim()).release();
+    }
+}
+
+impl qobject::DiagnosticsHistoryModel {
+    pub fn data(&self, index: &QModelIndex, role: i32) -> QVariant {
+        let Some(sample) = usize::try_from(index.row())
+            .ok()
+            .and_then(|row| self.rust().samples.get(row))
+        else {
+            return QVariant::default();
+        };
+
+        match role {
+            FPS_ROLE => QVariant::from(&sample.fps),
+            FRAME_TIME_ROLE => QVariant::from(&sample.frame_time),
+            ENTITY_COUNT_ROLE => QVariant::from(&sample.entity_count),
+            DRAW_CALLS_ROLE => QVariant::from(&sample.draw_calls),
+            GPU_TIME_ROLE => QVariant::from(&sample.gpu_time),
+            _ => QVariant::default(),
+        }
+    }
+
+    pub fn role_names(&self) -> QHash<QHashPair_i32_QByteArray> {
+        let mut roles = QHash::<QHashPair_i32_QByteArray>::default();
+        roles.insert(FPS_ROLE, QByteArray::from("fps"));
+        roles.insert(FRAME_TIME_ROLE, QByteArray::from("frameTime"));
+        roles.insert(ENTITY_COUNT_ROLE, QByteArray::from("entityCount"));
+        roles.insert(DRAW_CALLS_ROLE, QByteArray::from("drawCalls"));
+        roles.insert(GPU_TIME_ROLE, QByteArray::from("gpuTime"));
+        roles
+    }
+
+    pub fn row_count(&self, _parent: &QModelIndex) -> i32 {
+        self.rust().samples.len() as i32
+    }
+
+    /// Append the diagnostics of the update that just ran
+    fn sample(mut self: Pin<&mut Self>) {
+        let Some(sample) = runtime::with_world(|world| DiagnosticsSample::read(world)).flatten()
+        else {
+            return;
+        };
+
+        let row = self.rust().samples.len() as i32;
+        unsafe {
+            self.as_mut()
+                .begin_insert_rows(&QModelIndex::default(), row, row);
+        }
+        self.as_mut().rust_mut().samples.push_back(sample);
+        unsafe {
+            self.as_mut().end_insert_rows();
+        }
+        self.trim();
+    }
+
+    /// Drop the oldest samples beyond the capacity and update the statistics
+    fn trim(mut self: Pin<&mut Self>) {
+        let capacity = usize::try_from(*self.capacity()).unwrap_or_default();
+        let excess = self.rust().samples.len().saturating_sub(capacity);
+        if excess > 0 {
+            unsafe {
+                self.as_mut()
+                    .begin_remove_rows(&QModelIndex::default(), 0, excess as i32 - 1);
+            }
+            self.as_mut().rust_mut().samples.drain(..excess);
+            unsafe {
+                self.as_mut().end_remove_rows();
+            }
+        }
+
+        let samples = &self.rust().samples;
+        let count = samples.len() as i32;
+        let max_fps = samples.iter().map(|sample| sample.fps).fold(0.0, f64::max);
+        let mut frame_times: Vec<f64> = samples.iter().map(|sample| sample.frame_time).collect();
+        frame_times.sort_by(f64::total_cmp);
+        let [p50, p95, p99] = [0.5, 0.95, 0.99].map(|rank| percentile(&frame_times, rank));
+
+        if *self.count() != count {
+            self.as_mut().set_count(count);
+        }
+        if *self.max_fps() != max_fps {
+            self.as_mut().set_max_fps(max_fps);
+        }
+        if *self.frame_time_p50() != p50 {
+            self.as_mut().set_frame_time_p50(p50);
+        }
+        if *self.frame_time_p95() != p95 {
+            self.as_mut().set_frame_time_p95(p95);
+        }
+        if *self.frame_time_p99() != p99 {
+            self.as_mut().set_frame_time_p99(p99);
+        }
+    }
+}
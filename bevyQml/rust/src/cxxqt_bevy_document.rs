@@ -0,0 +1,491 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML singleton of the open document
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_document")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qstringlist.h");
+        /// An alias to the QStringList type
+        type QStringList = cxx_qt_lib::QStringList;
+        include!("cxx-qt-lib/qurl.h");
+        /// An alias to the QUrl type
+        type QUrl = cxx_qt_lib::QUrl;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/document.h");
+
+        #[doc(hidden)]
+        #[rust_name = "application_display_name"]
+        fn applicationDisplayName() -> QString;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QML singleton with the name
+        // BevyDocument based on the Rust struct BevyDocumentRust.
+        #[qobject]
+        #[qml_element]
+        #[qml_singleton]
+        #[qproperty(QUrl, file_url)]
+        #[qproperty(QString, file_name)]
+        #[qproperty(bool, untitled)]
+        #[qproperty(bool, modified)]
+        #[qproperty(bool, busy)]
+        #[qproperty(QString, title)]
+        #[qproperty(QString, title_format)]
+        #[qproperty(QString, untitled_name)]
+        #[qproperty(QStringList, recent_files)]
+        #[qproperty(i32, max_recent_files)]
+        #[qproperty(u64, root)]
+        type BevyDocument = super::BevyDocumentRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// Start a new, untitled document
+        ///
+        /// Despawns the descendants of the root entity, if there is one, and
+        /// clears the undo stack.
+        #[qinvokable]
+        #[cxx_name = "newDocument"]
+        fn new_document(self: Pin<&mut BevyDocument>);
+
+        /// Open the scene at a URL as the document, see `BevyAssets.loadScene`
+        ///
+        /// Once the scene is spawned, the descendants of the root entity it
+        /// replaces are despawned, the undo stack is cleared and opened is
+        /// emitted. Returns a task id for taskFinished, with the list of
+        /// the new top-most entities, or taskFailed.
+        #[qinvokable]
+        fn open(self: Pin<&mut BevyDocument>, url: &QUrl) -> u64;
+
+        /// Save the document to its file
+        ///
+        /// Returns 0 for an untitled document, which has to be saved with
+        /// saveAs, otherwise a task id like saveAs.
+        #[qinvokable]
+        fn save(self: Pin<&mut BevyDocument>) -> u64;
+
+        /// Save the document to a `.scn.ron` file, which becomes its file
+        ///
+        /// Saves the descendants of the root entity, or the whole world
+        /// without one, see `BevyAssets.saveScene`. Returns a task id,
+        /// taskProgress follows the save until taskFinished or taskFailed.
+        #[qinvokable]
+        #[cxx_name = "saveAs"]
+        fn save_as(self: Pin<&mut BevyDocument>, url: &QUrl) -> u64;
+
+        #[qinvokable]
+        #[cxx_name = "removeRecentFile"]
+        fn remove_recent_file(self: Pin<&mut BevyDocument>, url: &QString);
+
+        #[qinvokable]
+        #[cxx_name = "clearRecentFiles"]
+        fn clear_recent_files(self: Pin<&mut BevyDocument>);
+
+        /// A new document was started
+        #[qsignal]
+        fn created(self: Pin<&mut BevyDocument>);
+
+        /// The document was opened from the file
+        #[qsignal]
+        fn opened(self: Pin<&mut BevyDocument>, url: QUrl);
+
+        /// The document was saved to the file
+        #[qsignal]
+        fn saved(self: Pin<&mut BevyDocument>, url: QUrl);
+
+        /// A task started by open, save or saveAs has finished
+        #[qsignal]
+        #[cxx_name = "taskFinished"]
+        fn task_finished(self: Pin<&mut BevyDocument>, task_id: u64, result: QVariant);
+
+        #[qsignal]
+        #[cxx_name = "taskFailed"]
+        fn task_failed(self: Pin<&mut BevyDocument>, task_id: u64, error: QString);
+
+        /// How far a save is, from 0 to 1
+        #[qsignal]
+        #[cxx_name = "taskProgress"]
+        fn task_progress(self: Pin<&mut BevyDocument>, task_id: u64, progress: f64);
+    }
+
+    impl cxx_qt::Threading for BevyDocument {}
+    impl cxx_qt::Constructor<()> for BevyDocument {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QList, QString, QStringList, QUrl, QVariant};
+
+use crate::{
+    asset::{self, SceneSaveOptions},
+    runtime::{self, UpdateListener},
+    settings, task,
+    undo::QmlUndoStack,
+    variant,
+};
+
+/// Where the recent files are stored, see [crate::settings]
+const RECENT_FILES_KEY: &str = "recent_files";
+
+/// The Rust struct for the QObject
+///
+/// The file the world, or the tree of entities below `root`, was opened
+/// from or saved to, for the File menu of an editor. `modified` follows the
+/// [QmlUndoStack] and becomes false when the document is saved, and QML may
+/// set it when making changes which are not undone through the stack.
+/// `title` formats `titleFormat` for the window, where `%1` is the file
+/// name, `%2` the application display name and `[*]` stands for an asterisk
+/// while the document is modified, as in QWidget::windowTitle. The files
+/// opened and saved most recently are kept in QSettings.
+///
+/// Without a root entity, new documents and opened files leave the entities
+/// already in the world alone.
+///
+/// ```qml
+/// ApplicationWindow {
+///     title: BevyDocument.title
+///
+///     Action {
+///         text: qsTr("Save")
+///         shortcut: StandardKey.Save
+///         onTriggered: {
+///             if (BevyDocument.untitled)
+///                 saveDialog.open();
+///             else
+///                 BevyDocument.save();
+///         }
+///     }
+///     Menu {
+///         title: qsTr("Open Recent")
+///         Repeater {
+///             model: BevyDocument.recentFiles
+///             MenuItem {
+///                 text: modelData
+///                 onTriggered: BevyDocument.open(modelData)
+///             }
+///         }
+///     }
+///     onClosing: close => {
+///         if (BevyDocument.modified) {
+///             close.accepted = false;
+///             unsavedChangesDialog.open();
+///         }
+///     }
+/// }
+/// ```
+pub struct BevyDocumentRust {
+    file_url: QUrl,
+    file_name: QString,
+    untitled: bool,
+    modified: bool,
+    busy: bool,
+    title: QString,
+    title_format: QString,
+    untitled_name: QString,
+    recent_files: QStringList,
+    max_recent_files: i32,
+    root: u64,
+    /// The recent files as stored in the settings
+    recent: Vec<String>,
+    /// Whether QML set `modified` while the undo stack was clean
+    marked_modified: bool,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for BevyDocumentRust {
+    fn default() -> Self {
+        Self {
+            file_url: QUrl::default(),
+            file_name: QString::default(),
+            untitled: true,
+            modified: false,
+            busy: false,
+            title: QString::default(),
+            title_format: QString::from("%1[*] - %2"),
+            untitled_name: QString::from("Untitled"),
+            recent_files: QStringList::default(),
+            max_recent_files: 10,
+            root: 0,
+            recent: Vec::new(),
+            marked_modified: false,
+            update_listener: None,
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::BevyDocument {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|document| document.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        let recent = settings::load::<Vec<String>>(RECENT_FILES_KEY).unwrap_or_default();
+        self.as_mut().store_recent_files(recent);
+        self.as_mut().update_name();
+        self.as_mut().refresh();
+
+        self.as_mut()
+            .on_file_url_changed(|document| document.update_name())
+            .release();
+        self.as_mut()
+            .on_untitled_name_changed(|document| document.update_name())
+            .release();
+        self.as_mut()
+            .on_title_format_changed(|document| document.update_title())
+            .release();
+        self.as_mut()
+            .on_modified_changed(|document| document.write_modified())
+            .release();
+        self.on_max_recent_files_changed(|document| {
+            let recent = document.rust().recent.clone();
+            document.store_recent_files(recent);
+        })
+        .release();
+    }
+}
+
+impl qobject::BevyDocument {
+    pub fn new_document(mut self: Pin<&mut Self>) {
+        let root = root_entity(*self.root());
+        let sent = runtime::send(move |world| {
+            if let Some(mut root) = root.and_then(|root| world.get_entity_mut(root)) {
+                root.despawn_descendants();
+            }
+            if let Some(mut stack) = world.get_resource_mut::<QmlUndoStack>() {
+                stack.clear();
+            }
+        });
+        if !sent {
+            warn!("Cannot start a new document without a running Bevy app");
+            return;
+        }
+        self.as_mut().rust_mut().marked_modified = false;
+        self.as_mut().set_file_url(QUrl::default());
+        self.as_mut().set_modified(false);
+        self.created();
+    }
+
+    pub fn open(mut self: Pin<&mut Self>, url: &QUrl) -> u64 {
+        let id = task::next_task_id();
+        let root = root_entity(*self.root());
+        let load = asset::load_scene_file(url.to_string());
+        let open = async move {
+            let entities = load.await?;
+            task::world(move |world| {
+                // The previous document goes once the new one is there
+                if let Some(root) = root {
+                    if let Some(mut root) = world.get_entity_mut(root) {
+                        root.despawn_descendants();
+                    }
+                    for &entity in &entities {
+                        world.entity_mut(entity).set_parent(root);
+                    }
+                }
+                if let Some(mut stack) = world.get_resource_mut::<QmlUndoStack>() {
+                    stack.clear();
+                }
+                entities
+            })
+            .await
+            .ok_or_else(|| String::from("The Bevy app is not running"))
+        };
+        let url = url.clone();
+        self.as_mut().set_busy(true);
+        task::spawn(self.qt_thread(), open, move |mut document, result| {
+            document.as_mut().set_busy(false);
+            match result {
+                Ok(entities) => {
+                    document.as_mut().rust_mut().marked_modified = false;
+                    document.as_mut().set_file_url(url.clone());
+                    document.as_mut().add_recent_file(&url);
+                    document.as_mut().set_modified(false);
+                    document.as_mut().opened(url);
+                    let entities = entities
+                        .into_iter()
+                        .map(|entity| QVariant::from(&entity.to_bits()));
+                    document.task_finished(id, variant::list_to_variant(entities));
+                }
+                Err(error) => document.task_failed(id, QString::from(&error)),
+            }
+        });
+        id
+    }
+
+    pub fn save(self: Pin<&mut Self>) -> u64 {
+        if *self.untitled() {
+            return 0;
+        }
+        let url = self.file_url().clone();
+        self.save_as(&url)
+    }
+
+    pub fn save_as(mut self: Pin<&mut Self>, url: &QUrl) -> u64 {
+        let id = task::next_task_id();
+        let root = root_entity(*self.root());
+        let options = SceneSaveOptions {
+            root,
+            descendants_only: root.is_some(),
+            ..default()
+        };
+        let qt_thread = self.qt_thread();
+        let mut progress = move |progress: f32| {
+            let _ = qt_thread.queue(move |document| document.task_progress(id, progress.into()));
+        };
+        let path = url.to_string();
+        let save = async move {
+            asset::save_scene_file(path, options, &mut progress).await?;
+            task::world(|world| {
+                if let Some(mut stack) = world.get_resource_mut::<QmlUndoStack>() {
+                    stack.set_clean();
+                }
+            })
+            .await;
+            Ok::<_, String>(())
+        };
+        let url = url.clone();
+        self.as_mut().set_busy(true);
+        task::spawn(self.qt_thread(), save, move |mut document, result| {
+            document.as_mut().set_busy(false);
+            match result {
+                Ok(()) => {
+                    document.as_mut().rust_mut().marked_modified = false;
+                    document.as_mut().set_file_url(url.clone());
+                    document.as_mut().add_recent_file(&url);
+                    document.as_mut().refresh();
+                    document.as_mut().saved(url);
+                    document.task_finished(id, QVariant::default());
+                }
+                Err(error) => document.task_failed(id, QString::from(&error)),
+            }
+        });
+        id
+    }
+
+    pub fn remove_recent_file(self: Pin<&mut Self>, url: &QString) {
+        let url = url.to_string();
+        let mut recent = self.rust().recent.clone();
+        recent.retain(|file| *file != url);
+        self.store_recent_files(recent);
+    }
+
+    pub fn clear_recent_files(self: Pin<&mut Self>) {
+        self.store_recent_files(Vec::new());
+    }
+
+    fn add_recent_file(self: Pin<&mut Self>, url: &QUrl) {
+        let url = url.to_string();
+        let mut recent = self.rust().recent.clone();
+        recent.retain(|file| *file != url);
+        recent.insert(0, url);
+        self.store_recent_files(recent);
+    }
+
+    fn store_recent_files(mut self: Pin<&mut Self>, mut recent: Vec<String>) {
+        recent.truncate(usize::try_from(*self.max_recent_files()).unwrap_or_default());
+        if recent != self.rust().recent {
+            settings::save(RECENT_FILES_KEY, &recent);
+        }
+        let mut list = QList::<QString>::default();
+        for file in &recent {
+            list.append(QString::from(file));
+        }
+        self.as_mut().rust_mut().recent = recent;
+        self.set_recent_files(QStringList::from(&list));
+    }
+
+    /// Follow the undo stack, which becomes clean when the document is saved
+    fn refresh(self: Pin<&mut Self>) {
+        let modified = self.rust().marked_modified || stack_clean() == Some(false);
+        self.set_modified(modified);
+    }
+
+    /// Keep a `modified` set from QML, or mark the undo stack clean when it
+    /// is reset
+    fn write_modified(mut self: Pin<&mut Self>) {
+        let clean = stack_clean();
+        if *self.modified() {
+            if clean != Some(false) {
+                self.as_mut().rust_mut().marked_modified = true;
+            }
+        } else {
+            self.as_mut().rust_mut().marked_modified = false;
+            if clean == Some(false) {
+                runtime::send(|world| {
+                    if let Some(mut stack) = world.get_resource_mut::<QmlUndoStack>() {
+                        stack.set_clean();
+                    }
+                });
+            }
+        }
+        self.update_title();
+    }
+
+    fn update_name(mut self: Pin<&mut Self>) {
+        let url = self.file_url().to_string();
+        let untitled = url.is_empty();
+        let name = if untitled {
+            self.untitled_name().clone()
+        } else {
+            QString::from(&file_name(&url))
+        };
+        self.as_mut().set_untitled(untitled);
+        self.as_mut().set_file_name(name);
+        self.update_title();
+    }
+
+    fn update_title(self: Pin<&mut Self>) {
+        // The file name goes in last, in case it contains any of the others
+        let title = self
+            .title_format()
+            .to_string()
+            .replace("[*]", if *self.modified() { "*" } else { "" })
+            .replace("%2", &qobject::application_display_name().to_string())
+            .replace("%1", &self.file_name().to_string());
+        self.set_title(QString::from(&title));
+    }
+}
+
+/// Whether the undo stack is clean, [None] without one
+fn stack_clean() -> Option<bool> {
+    runtime::with_world(|world| {
+        world
+            .get_resource::<QmlUndoStack>()
+            .map(QmlUndoStack::is_clean)
+    })
+    .flatten()
+}
+
+fn root_entity(bits: u64) -> Option<Entity> {
+    if bits == 0 {
+        return None;
+    }
+    let entity = Entity::try_from_bits(bits).ok();
+    if entity.is_none() {
+        warn!("{bits} passed from QML is not a valid entity");
+    }
+    entity
+}
+
+/// The name of the file a URL points to, for the title
+fn file_name(url: &str) -> String {
+    asset::scene_file(url)
+        .ok()
+        .and_then(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| url.rsplit('/').next().unwrap_or(url).to_owned())
+}
@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML element that declares an entity
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_entity")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qlist.h");
+        /// An alias to the QVariantList type
+        type QList_QVariant = cxx_qt_lib::QList<cxx_qt_lib::QVariant>;
+        include!("cxx-qt-lib/qvector3d.h");
+        /// An alias to the QVector3D type
+        type QVector3D = cxx_qt_lib::QVector3D;
+        include!("bevyqml/convert.h");
+        /// An alias to the QQuaternion type
+        type QQuaternion = crate::convert::QQuaternion;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BevyEntity based on the Rust struct BevyEntityRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(u64, entity)]
+        #[qproperty(QString, name)]
+        #[qproperty(u64, parent_entity)]
+        #[qproperty(QVector3D, position)]
+        #[qproperty(QQuaternion, rotation)]
+        #[qproperty(QVector3D, scale)]
+        #[qproperty(bool, visible)]
+        #[qproperty(QList_QVariant, components)]
+        type BevyEntity = super::BevyEntityRust;
+    }
+
+    impl cxx_qt::Threading for BevyEntity {}
+    impl cxx_qt::Constructor<()> for BevyEntity {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QList, QString, QVariant, QVector3D};
+
+use crate::{
+    convert::{IntoQt, QQuaternion},
+    declarative::{self, DeclaredEntity, DeclaredProperties},
+};
+
+/// The Rust struct for the QObject
+///
+/// The element spawns an entity with a [Transform] and the [Visibility]
+/// components, which follow `position`, `rotation`, `scale` and `visible`.
+/// `entity` holds the bits of the entity once it has been spawned, see
+/// [crate::declarative] for the other properties.
+pub struct BevyEntityRust {
+    entity: u64,
+    name: QString,
+    parent_entity: u64,
+    position: QVector3D,
+    rotation: QQuaternion,
+    scale: QVector3D,
+    visible: bool,
+    components: QList<QVariant>,
+    declared: DeclaredEntity,
+}
+
+impl Default for BevyEntityRust {
+    fn default() -> Self {
+        Self {
+            entity: 0,
+            name: QString::default(),
+            parent_entity: 0,
+            position: Vec3::ZERO.into_qt(),
+            rotation: QQuaternion::default(),
+            scale: Vec3::ONE.into_qt(),
+            visible: true,
+            components: QList::default(),
+            declared: DeclaredEntity::default(),
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::BevyEntity {
+    fn initialize(mut self: Pin<&mut Self>) {
+        // Spawn once QML has set the initial properties
+        let _ = self.qt_thread().queue(|element| element.spawn());
+
+        self.as_mut()
+            .on_name_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_parent_entity_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_position_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_rotation_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_scale_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_visible_changed(|element| element.write_properties())
+            .release();
+        self.on_components_changed(|element| element.write_components())
+            .release();
+    }
+}
+
+impl qobject::BevyEntity {
+    fn spawn(mut self: Pin<&mut Self>) {
+        let properties = self.properties();
+        let components = declarative::component_list(self.components());
+        let Some(entity) = self
+            .as_mut()
+            .rust_mut()
+            .declared
+            .spawn(SpatialBundle::default())
+        else {
+            warn!("BevyEntity cannot spawn an entity without a running Bevy app");
+            return;
+        };
+        self.rust().declared.update(move |world, entity| {
+            properties.apply(world, entity);
+            declarative::apply_components(world, entity, components);
+        });
+        self.set_entity(entity.to_bits());
+    }
+
+    fn properties(&self) -> DeclaredProperties {
+        DeclaredProperties::from_qml(
+            self.name(),
+            *self.parent_entity(),
+            self.position(),
+            self.rotation(),
+            self.scale(),
+            *self.visible(),
+        )
+    }
+
+    fn write_properties(self: Pin<&mut Self>) {
+        let properties = self.properties();
+        self.rust()
+            .declared
+            .update(move |world, entity| properties.apply(world, entity));
+    }
+
+    fn write_components(self: Pin<&mut Self>) {
+        let components = declarative::component_list(self.components());
+        self.rust().declared.update(move |world, entity| {
+            declarative::apply_components(world, entity, components)
+        });
+    }
+}
@@ -0,0 +1,381 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the tree model of the entity hierarchy
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_entity_tree_model")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!(<QtCore/QAbstractItemModel>);
+
+        include!("cxx-qt-lib/qhash.h");
+        /// An alias to the QHash<int, QByteArray> type
+        type QHash_i32_QByteArray = cxx_qt_lib::QHash<cxx_qt_lib::QHashPair_i32_QByteArray>;
+        include!("cxx-qt-lib/qmodelindex.h");
+        /// An alias to the QModelIndex type
+        type QModelIndex = cxx_qt_lib::QModelIndex;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+        include!("cxx-qt-lib/qvector.h");
+        /// An alias to the QVector<int> type
+        type QVector_i32 = cxx_qt_lib::QVector<i32>;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // EntityTreeModel based on the Rust struct EntityTreeModelRust.
+        #[qobject]
+        #[qml_element]
+        #[base = "QAbstractItemModel"]
+        type EntityTreeModel = super::EntityTreeModelRust;
+    }
+
+    // QAbstractItemModel implementation
+    unsafe extern "RustQt" {
+        #[inherit]
+        #[cxx_name = "beginInsertRows"]
+        unsafe fn begin_insert_rows(
+            self: Pin<&mut EntityTreeModel>,
+            parent: &QModelIndex,
+            first: i32,
+            last: i32,
+        );
+
+        #[inherit]
+        #[cxx_name = "endInsertRows"]
+        unsafe fn end_insert_rows(self: Pin<&mut EntityTreeModel>);
+
+        #[inherit]
+        #[cxx_name = "beginRemoveRows"]
+        unsafe fn begin_remove_rows(
+            self: Pin<&mut EntityTreeModel>,
+            parent: &QModelIndex,
+            first: i32,
+            last: i32,
+        );
+
+        #[inherit]
+        #[cxx_name = "endRemoveRows"]
+        unsafe fn end_remove_rows(self: Pin<&mut EntityTreeModel>);
+
+        #[inherit]
+        #[cxx_name = "beginMoveRows"]
+        unsafe fn begin_move_rows(
+            self: Pin<&mut EntityTreeModel>,
+            source_parent: &QModelIndex,
+            source_first: i32,
+            source_last: i32,
+            destination_parent: &QModelIndex,
+            destination_child: i32,
+        ) -> bool;
+
+        #[inherit]
+        #[cxx_name = "endMoveRows"]
+        unsafe fn end_move_rows(self: Pin<&mut EntityTreeModel>);
+
+        #[inherit]
+        #[cxx_name = "createIndex"]
+        fn create_index(self: &EntityTreeModel, row: i32, column: i32, id: usize) -> QModelIndex;
+
+        #[qsignal]
+        #[inherit]
+        #[cxx_name = "dataChanged"]
+        fn data_changed(
+            self: Pin<&mut EntityTreeModel>,
+            top_left: &QModelIndex,
+            bottom_right: &QModelIndex,
+            roles: &QVector_i32,
+        );
+    }
+
+    unsafe extern "RustQt" {
+        #[qinvokable]
+        #[cxx_override]
+        fn index(
+            self: &EntityTreeModel,
+            row: i32,
+            column: i32,
+            parent: &QModelIndex,
+        ) -> QModelIndex;
+
+        #[qinvokable]
+        #[cxx_override]
+        fn parent(self: &EntityTreeModel, index: &QModelIndex) -> QModelIndex;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "rowCount"]
+        fn row_count(self: &EntityTreeModel, parent: &QModelIndex) -> i32;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "columnCount"]
+        fn column_count(self: &EntityTreeModel, parent: &QModelIndex) -> i32;
+
+        #[qinvokable]
+        #[cxx_override]
+        fn data(self: &EntityTreeModel, index: &QModelIndex, role: i32) -> QVariant;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "roleNames"]
+        fn role_names(self: &EntityTreeModel) -> QHash_i32_QByteArray;
+
+        /// The entity at an index as returned by Entity::to_bits, or 0
+        #[qinvokable]
+        fn entity(self: &EntityTreeModel, index: &QModelIndex) -> u64;
+
+        /// The index of an entity, invalid if it is not in the model
+        #[qinvokable]
+        #[cxx_name = "indexOf"]
+        fn index_of(self: &EntityTreeModel, entity: u64) -> QModelIndex;
+    }
+
+    impl cxx_qt::Threading for EntityTreeModel {}
+    impl cxx_qt::Constructor<()> for EntityTreeModel {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QByteArray, QHash, QHashPair_i32_QByteArray, QModelIndex, QString, QVariant};
+
+use crate::{
+    label::{self, EntityLabel},
+    model::EntityHierarchy,
+    runtime::{self, UpdateListener},
+};
+
+/// Qt::UserRole, holds the bits of the entity
+const ENTITY_ROLE: i32 = 0x0100;
+
+/// The `Name` of the entity, same as the display role
+const NAME_ROLE: i32 = ENTITY_ROLE + 1;
+
+/// The Rust struct for the QObject
+///
+/// Every entity of the world shows up below its `Parent`, with roots in the
+/// order of their ids. Each index has an `entity` role with the bits of the
+/// entity and a `name` role, which is also the display role, holding its
+/// display name, see [crate::label]. The `decoration` and `toolTip` roles
+/// hold the icon URL and tool tip of its `QmlLabel`.
+///
+/// The model is compared to the hierarchy after every update and signals
+/// rows being inserted, removed and moved as entities are spawned,
+/// despawned and reparented, so a TreeView keeps its expanded branches.
+#[derive(Default)]
+pub struct EntityTreeModelRust {
+    hierarchy: EntityHierarchy,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::EntityTreeModel {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|model| model.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+        self.refresh();
+    }
+}
+
+impl qobject::EntityTreeModel {
+    pub fn index(&self, row: i32, column: i32, parent: &QModelIndex) -> QModelIndex {
+        let parent = self.entity_at(parent);
+        usize::try_from(row)
+            .ok()
+            .and_then(|row| self.rust().hierarchy.children(parent).get(row))
+            .map_or_else(QModelIndex::default, |entity| {
+                self.create_index(row, column, entity.to_bits() as usize)
+            })
+    }
+
+    pub fn parent(&self, index: &QModelIndex) -> QModelIndex {
+        let parent = self
+            .entity_at(index)
+            .and_then(|entity| self.rust().hierarchy.parent(entity));
+        self.index_of_entity(parent)
+    }
+
+    pub fn row_count(&self, parent: &QModelIndex) -> i32 {
+        if parent.column() > 0 {
+            return 0;
+        }
+        let entity = self.entity_at(parent);
+        if parent.is_valid() && entity.is_none() {
+            return 0;
+        }
+        self.rust().hierarchy.children(entity).len() as i32
+    }
+
+    pub fn column_count(&self, _parent: &QModelIndex) -> i32 {
+        1
+    }
+
+    pub fn data(&self, index: &QModelIndex, role: i32) -> QVariant {
+        let Some(entity) = self.entity_at(index) else {
+            return QVariant::default();
+        };
+
+        if role == ENTITY_ROLE {
+            return QVariant::from(&entity.to_bits());
+        }
+        let Some(entity_label) = self.rust().hierarchy.label(entity) else {
+            return QVariant::default();
+        };
+        match role {
+            NAME_ROLE => QVariant::from(&QString::from(&entity_label.display_name)),
+            _ => label::role_data(entity_label, role),
+        }
+    }
+
+    pub fn role_names(&self) -> QHash<QHashPair_i32_QByteArray> {
+        let mut roles = QHash::<QHashPair_i32_QByteArray>::default();
+        label::insert_role_names(&mut roles);
+        roles.insert(ENTITY_ROLE, QByteArray::from("entity"));
+        roles.insert(NAME_ROLE, QByteArray::from("name"));
+        roles
+    }
+
+    pub fn entity(&self, index: &QModelIndex) -> u64 {
+        self.entity_at(index).map_or(0, Entity::to_bits)
+    }
+
+    pub fn index_of(&self, entity: u64) -> QModelIndex {
+        let entity = Entity::try_from_bits(entity)
+            .ok()
+            .filter(|entity| self.rust().hierarchy.contains(*entity));
+        self.index_of_entity(entity)
+    }
+
+    fn entity_at(&self, index: &QModelIndex) -> Option<Entity> {
+        if !index.is_valid() {
+            return None;
+        }
+        Entity::try_from_bits(index.internal_id() as u64).ok()
+    }
+
+    /// The index of an entity, or the invalid root index for [None]
+    fn index_of_entity(&self, entity: Option<Entity>) -> QModelIndex {
+        entity
+            .and_then(|entity| {
+                let row = self.rust().hierarchy.row(entity)?;
+                Some(self.create_index(row as i32, 0, entity.to_bits() as usize))
+            })
+            .unwrap_or_default()
+    }
+
+    /// Bring the model in line with the current hierarchy of the world
+    fn refresh(mut self: Pin<&mut Self>) {
+        let Some(hierarchy) = runtime::with_world(EntityHierarchy::capture) else {
+            return;
+        };
+
+        self.as_mut().remove_stale(&hierarchy);
+        self.as_mut().insert_missing(&hierarchy);
+        self.update_labels(&hierarchy);
+    }
+
+    /// Remove every entity which was despawned or has a different parent now
+    ///
+    /// Removing an entity takes everything below it along, which is inserted
+    /// again afterwards wherever it still exists.
+    fn remove_stale(mut self: Pin<&mut Self>, current: &EntityHierarchy) {
+        let mut pending = vec![None];
+        while let Some(parent) = pending.pop() {
+            let children = self.rust().hierarchy.children(parent).to_vec();
+            for (row, entity) in children.into_iter().enumerate().rev() {
+                if current.contains(entity) && current.parent(entity) == parent {
+                    pending.push(Some(entity));
+                    continue;
+                }
+
+                let parent_index = self.index_of_entity(parent);
+                unsafe {
+                    self.as_mut()
+                        .begin_remove_rows(&parent_index, row as i32, row as i32);
+                }
+                self.as_mut().rust_mut().hierarchy.remove_subtree(entity);
+                unsafe {
+                    self.as_mut().end_remove_rows();
+                }
+            }
+        }
+    }
+
+    /// Insert the entities which are not in the model yet and move the ones
+    /// whose siblings were reordered
+    fn insert_missing(mut self: Pin<&mut Self>, current: &EntityHierarchy) {
+        let mut pending = vec![None];
+        while let Some(parent) = pending.pop() {
+            let children = current.children(parent);
+            for (row, &entity) in children.iter().enumerate() {
+                let shown = self.rust().hierarchy.children(parent);
+                if shown.get(row) == Some(&entity) {
+                    continue;
+                }
+
+                let from = shown.iter().position(|other| *other == entity);
+                let parent_index = self.index_of_entity(parent);
+                match from {
+                    Some(from) => {
+                        let moved = unsafe {
+                            self.as_mut().begin_move_rows(
+                                &parent_index,
+                                from as i32,
+                                from as i32,
+                                &parent_index,
+                                row as i32,
+                            )
+                        };
+                        self.as_mut()
+                            .rust_mut()
+                            .hierarchy
+                            .move_child(parent, from, row);
+                        if moved {
+                            unsafe {
+                                self.as_mut().end_move_rows();
+                            }
+                        }
+                    }
+                    None => {
+                        unsafe {
+                            self.as_mut()
+                                .begin_insert_rows(&parent_index, row as i32, row as i32);
+                        }
+                        self.as_mut()
+                            .rust_mut()
+                            .hierarchy
+                            .insert_subtree(parent, row, entity, current);
+                        unsafe {
+                            self.as_mut().end_insert_rows();
+                        }
+                    }
+                }
+            }
+            pending.extend(children.iter().map(|child| Some(*child)));
+        }
+    }
+
+    /// Report the entities whose label changed
+    fn update_labels(mut self: Pin<&mut Self>, current: &EntityHierarchy) {
+        let renamed: Vec<(Entity, EntityLabel)> = current
+            .entities()
+            .filter_map(|entity| {
+                let label = current.label(entity)?;
+                (self.rust().hierarchy.label(entity) != Some(label))
+                    .then(|| (entity, label.clone()))
+            })
+            .collect();
+
+        let mut roles = label::roles();
+        roles.append(NAME_ROLE);
+        for (entity, label) in renamed {
+            self.as_mut().rust_mut().hierarchy.set_label(entity, label);
+            let index = self.index_of_entity(Some(entity));
+            self.as_mut().data_changed(&index, &index, &roles);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that turns Bevy events into a signal
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_event_listener")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qmap.h");
+        /// An alias to the QVariantMap type
+        type QMap_QString_QVariant = cxx_qt_lib::QMap<cxx_qt_lib::QMapPair_QString_QVariant>;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BevyEventListener based on the Rust struct BevyEventListenerRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(QString, event)]
+        type BevyEventListener = super::BevyEventListenerRust;
+
+        /// Emitted for every matching Bevy event with its reflected fields
+        #[qsignal]
+        fn triggered(self: Pin<&mut BevyEventListener>, payload: QMap_QString_QVariant);
+    }
+
+    impl cxx_qt::Threading for BevyEventListener {}
+    impl cxx_qt::Constructor<()> for BevyEventListener {}
+}
+
+use core::pin::Pin;
+
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QMap, QMapPair_QString_QVariant, QString};
+
+use crate::{
+    bridge::ForwardedEvents,
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// `event` is the short type path of an event forwarded with a
+/// [crate::bridge::EventSignalBridge]. Only events sent after the listener
+/// was created are emitted.
+#[derive(Default)]
+pub struct BevyEventListenerRust {
+    event: QString,
+    /// The sequence number of the next event to look at
+    next_sequence: Option<u64>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::BevyEventListener {
+    fn initialize(mut self: Pin<&mut Self>) {
+        self.as_mut().rust_mut().next_sequence = runtime::with_world(|world| {
+            world
+                .get_resource::<ForwardedEvents>()
+                .map(ForwardedEvents::next_sequence)
+        })
+        .flatten();
+
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|listener| listener.poll());
+        });
+        self.rust_mut().update_listener = Some(listener);
+    }
+}
+
+impl qobject::BevyEventListener {
+    /// Emit the events forwarded since the last poll
+    fn poll(mut self: Pin<&mut Self>) {
+        let name = self.event().to_string();
+        let next_sequence = self.rust().next_sequence.unwrap_or_default();
+        let polled = runtime::with_world(|world| {
+            let forwarded = world.get_resource::<ForwardedEvents>()?;
+            let payloads: Vec<_> = forwarded
+                .since(next_sequence)
+                .filter(|event| event.name == name)
+                .map(|event| event.payload.clone())
+                .collect();
+            Some((forwarded.next_sequence(), payloads))
+        })
+        .flatten();
+
+        let Some((next_sequence, payloads)) = polled else {
+            return;
+        };
+        self.as_mut().rust_mut().next_sequence = Some(next_sequence);
+
+        for payload in payloads {
+            let mut map = QMap::<QMapPair_QString_QVariant>::default();
+            for (field, value) in payload {
+                map.insert(QString::from(field.as_str()), value);
+            }
+            self.as_mut().triggered(map);
+        }
+    }
+}
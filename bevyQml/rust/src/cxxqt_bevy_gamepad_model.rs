@@ -0,0 +1,261 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the list model of the connected gamepads
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_gamepad_model")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!(<QtCore/QAbstractListModel>);
+
+        include!("cxx-qt-lib/qhash.h");
+        /// An alias to the QHash<int, QByteArray> type
+        type QHash_i32_QByteArray = cxx_qt_lib::QHash<cxx_qt_lib::QHashPair_i32_QByteArray>;
+        include!("cxx-qt-lib/qmodelindex.h");
+        /// An alias to the QModelIndex type
+        type QModelIndex = cxx_qt_lib::QModelIndex;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+        include!("cxx-qt-lib/qvector.h");
+        /// An alias to the QVector<int> type
+        type QVector_i32 = cxx_qt_lib::QVector<i32>;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // GamepadModel based on the Rust struct GamepadModelRust.
+        #[qobject]
+        #[qml_element]
+        #[base = "QAbstractListModel"]
+        #[qproperty(i32, count)]
+        #[qproperty(bool, connected)]
+        type GamepadModel = super::GamepadModelRust;
+    }
+
+    // QAbstractListModel implementation
+    unsafe extern "RustQt" {
+        #[inherit]
+        #[cxx_name = "beginResetModel"]
+        unsafe fn begin_reset_model(self: Pin<&mut GamepadModel>);
+
+        #[inherit]
+        #[cxx_name = "endResetModel"]
+        unsafe fn end_reset_model(self: Pin<&mut GamepadModel>);
+
+        #[inherit]
+        fn index(self: &GamepadModel, row: i32, column: i32, parent: &QModelIndex) -> QModelIndex;
+
+        #[qsignal]
+        #[inherit]
+        #[cxx_name = "dataChanged"]
+        fn data_changed(
+            self: Pin<&mut GamepadModel>,
+            top_left: &QModelIndex,
+            bottom_right: &QModelIndex,
+            roles: &QVector_i32,
+        );
+    }
+
+    unsafe extern "RustQt" {
+        #[qinvokable]
+        #[cxx_override]
+        fn data(self: &GamepadModel, index: &QModelIndex, role: i32) -> QVariant;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "roleNames"]
+        fn role_names(self: &GamepadModel) -> QHash_i32_QByteArray;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "rowCount"]
+        fn row_count(self: &GamepadModel, parent: &QModelIndex) -> i32;
+
+        /// A gamepad was plugged in or paired
+        #[qsignal]
+        #[cxx_name = "gamepadConnected"]
+        fn gamepad_connected(self: Pin<&mut GamepadModel>, gamepad: i32, name: QString);
+
+        #[qsignal]
+        #[cxx_name = "gamepadDisconnected"]
+        fn gamepad_disconnected(self: Pin<&mut GamepadModel>, gamepad: i32, name: QString);
+    }
+
+    impl cxx_qt::Threading for GamepadModel {}
+    impl cxx_qt::Constructor<()> for GamepadModel {}
+}
+
+use core::pin::Pin;
+
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{
+    QByteArray, QHash, QHashPair_i32_QByteArray, QModelIndex, QString, QVariant, QVector,
+};
+
+use crate::{
+    input::gamepad::{ConnectedGamepad, GamepadPower, GamepadStatus},
+    runtime::{self, UpdateListener},
+};
+
+/// Qt::UserRole, the first role available to custom models
+const USER_ROLE: i32 = 0x0100;
+
+const GAMEPAD_ROLE: i32 = USER_ROLE;
+const NAME_ROLE: i32 = USER_ROLE + 1;
+const POWER_ROLE: i32 = USER_ROLE + 2;
+const BATTERY_ROLE: i32 = USER_ROLE + 3;
+const CHARGING_ROLE: i32 = USER_ROLE + 4;
+
+/// The Rust struct for the QObject
+///
+/// The model lists the gamepads connected to the app, in the order they were
+/// connected, with the roles `gamepad`, the id of the Bevy [bevy::input::gamepad::Gamepad],
+/// `name`, `power` ("unknown", "wired", "discharging", "charging" or
+/// "charged"), `battery` in percent or -1 if it is unknown, and `charging`.
+/// The battery is read again every few seconds, see
+/// [crate::input::gamepad::GamepadStatusSettings]:
+///
+/// ```qml
+/// Row {
+///     visible: gamepads.connected
+///     Repeater {
+///         model: GamepadModel {
+///             id: gamepads
+///             onGamepadDisconnected: (gamepad, name) => toast.show(qsTr("%1 disconnected").arg(name))
+///         }
+///         delegate: Label {
+///             text: model.battery < 0 ? model.name : `${model.name} ${model.battery}%`
+///             color: model.battery >= 0 && model.battery < 20 && !model.charging ? "red" : palette.text
+///         }
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct GamepadModelRust {
+    count: i32,
+    connected: bool,
+    rows: Vec<ConnectedGamepad>,
+    revision: Option<u64>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::GamepadModel {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|model| model.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+        self.refresh();
+    }
+}
+
+impl qobject::GamepadModel {
+    pub fn data(&self, index: &QModelIndex, role: i32) -> QVariant {
+        let Some(row) = usize::try_from(index.row())
+            .ok()
+            .and_then(|row| self.rust().rows.get(row))
+        else {
+            return QVariant::default();
+        };
+
+        match role {
+            GAMEPAD_ROLE => QVariant::from(&(row.gamepad.id as i32)),
+            NAME_ROLE => QVariant::from(&QString::from(&row.name)),
+            POWER_ROLE => QVariant::from(&QString::from(match row.power {
+                GamepadPower::Unknown => "unknown",
+                GamepadPower::Wired => "wired",
+                GamepadPower::Discharging(_) => "discharging",
+                GamepadPower::Charging(_) => "charging",
+                GamepadPower::Charged => "charged",
+            })),
+            BATTERY_ROLE => QVariant::from(&row.power.level().map_or(-1, i32::from)),
+            CHARGING_ROLE => QVariant::from(&row.power.is_charging()),
+            _ => QVariant::default(),
+        }
+    }
+
+    pub fn role_names(&self) -> QHash<QHashPair_i32_QByteArray> {
+        let mut roles = QHash::<QHashPair_i32_QByteArray>::default();
+        roles.insert(GAMEPAD_ROLE, QByteArray::from("gamepad"));
+        roles.insert(NAME_ROLE, QByteArray::from("name"));
+        roles.insert(POWER_ROLE, QByteArray::from("power"));
+        roles.insert(BATTERY_ROLE, QByteArray::from("battery"));
+        roles.insert(CHARGING_ROLE, QByteArray::from("charging"));
+        roles
+    }
+
+    pub fn row_count(&self, _parent: &QModelIndex) -> i32 {
+        self.rust().rows.len() as i32
+    }
+
+    /// Read the connected gamepads, if they changed since the last refresh
+    fn refresh(mut self: Pin<&mut Self>) {
+        let revision = self.rust().revision;
+        let Some((rows, revision)) = runtime::with_world(|world| {
+            let status = world.get_resource::<GamepadStatus>()?;
+            (Some(status.revision()) != revision).then(|| {
+                (
+                    status.iter().cloned().collect::<Vec<_>>(),
+                    status.revision(),
+                )
+            })
+        })
+        .flatten() else {
+            return;
+        };
+        self.as_mut().rust_mut().revision = Some(revision);
+
+        let old = std::mem::take(&mut self.as_mut().rust_mut().rows);
+        let same_gamepads = rows.len() == old.len()
+            && rows
+                .iter()
+                .zip(&old)
+                .all(|(row, old)| row.gamepad == old.gamepad);
+        if same_gamepads {
+            let changed: Vec<usize> = rows
+                .iter()
+                .zip(&old)
+                .enumerate()
+                .filter(|(_, (row, old))| row != old)
+                .map(|(index, _)| index)
+                .collect();
+            self.as_mut().rust_mut().rows = rows;
+            // Only the battery changed
+            let roles = QVector::<i32>::default();
+            for row in changed {
+                let index = self.index(row as i32, 0, &QModelIndex::default());
+                self.as_mut().data_changed(&index, &index, &roles);
+            }
+            return;
+        }
+
+        unsafe {
+            self.as_mut().begin_reset_model();
+        }
+        self.as_mut().rust_mut().rows = rows.clone();
+        unsafe {
+            self.as_mut().end_reset_model();
+        }
+        self.as_mut().set_count(rows.len() as i32);
+        self.as_mut().set_connected(!rows.is_empty());
+
+        for gone in old
+            .iter()
+            .filter(|old| !rows.iter().any(|row| row.gamepad == old.gamepad))
+        {
+            self.as_mut()
+                .gamepad_disconnected(gone.gamepad.id as i32, QString::from(&gone.name));
+        }
+        for new in rows
+            .iter()
+            .filter(|row| !old.iter().any(|old| old.gamepad == row.gamepad))
+        {
+            self.as_mut()
+                .gamepad_connected(new.gamepad.id as i32, QString::from(&new.name));
+        }
+    }
+}
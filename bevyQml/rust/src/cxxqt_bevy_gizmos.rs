@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML singleton that draws debug shapes
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_gizmos")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qcolor.h");
+        /// An alias to the QColor type
+        type QColor = cxx_qt_lib::QColor;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvector3d.h");
+        /// An alias to the QVector3D type
+        type QVector3D = cxx_qt_lib::QVector3D;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QML singleton with the name
+        // BevyGizmos based on the Rust struct BevyGizmosRust.
+        #[qobject]
+        #[qml_element]
+        #[qml_singleton]
+        type BevyGizmos = super::BevyGizmosRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// Draw a line between two points
+        #[qinvokable]
+        #[cxx_name = "drawLine"]
+        fn draw_line(
+            self: &BevyGizmos,
+            start: &QVector3D,
+            end: &QVector3D,
+            color: &QColor,
+            duration: f64,
+        );
+
+        /// Draw the outline of a sphere
+        #[qinvokable]
+        #[cxx_name = "drawSphere"]
+        fn draw_sphere(
+            self: &BevyGizmos,
+            center: &QVector3D,
+            radius: f64,
+            color: &QColor,
+            duration: f64,
+        );
+
+        /// Draw the edges of the axis aligned box between two corners
+        #[qinvokable]
+        #[cxx_name = "drawAabb"]
+        fn draw_aabb(
+            self: &BevyGizmos,
+            min: &QVector3D,
+            max: &QVector3D,
+            color: &QColor,
+            duration: f64,
+        );
+
+        /// Show text at a point of the world, facing the camera
+        #[qinvokable]
+        #[cxx_name = "drawText3d"]
+        fn draw_text_3d(
+            self: &BevyGizmos,
+            position: &QVector3D,
+            text: &QString,
+            color: &QColor,
+            duration: f64,
+        );
+
+        /// Stop drawing every shape, including those which have not expired
+        #[qinvokable]
+        fn clear(self: &BevyGizmos);
+    }
+}
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use cxx_qt_lib::{QColor, QString, QVector3D};
+
+use crate::{
+    convert::FromQt,
+    gizmos::{self, QmlGizmo},
+};
+
+/// The Rust struct for the QObject
+///
+/// Every shape is drawn in the next update of the app, and keeps being drawn
+/// for `duration` seconds, so 0 draws it just once. Logic in QML can redraw
+/// its shapes whenever they change, or leave them up for a while:
+///
+/// ```qml
+/// Connections {
+///     target: view
+///     function onEntityClicked(entity, position) {
+///         BevyGizmos.drawSphere(position, 0.05, "red", 5)
+///         BevyGizmos.drawText3d(position, position.y.toFixed(2) + " m", "white", 5)
+///     }
+/// }
+/// ```
+///
+/// See [crate::gizmos] for how text is shown.
+#[derive(Default)]
+pub struct BevyGizmosRust;
+
+impl qobject::BevyGizmos {
+    pub fn draw_line(&self, start: &QVector3D, end: &QVector3D, color: &QColor, duration: f64) {
+        gizmos::draw(
+            QmlGizmo::Line {
+                start: Vec3::from_qt(start),
+                end: Vec3::from_qt(end),
+                color: Color::from_qt(color),
+            },
+            duration_from_seconds(duration),
+        );
+    }
+
+    pub fn draw_sphere(&self, center: &QVector3D, radius: f64, color: &QColor, duration: f64) {
+        gizmos::draw(
+            QmlGizmo::Sphere {
+                center: Vec3::from_qt(center),
+                radius: radius as f32,
+                color: Color::from_qt(color),
+            },
+            duration_from_seconds(duration),
+        );
+    }
+
+    pub fn draw_aabb(&self, min: &QVector3D, max: &QVector3D, color: &QColor, duration: f64) {
+        gizmos::draw(
+            QmlGizmo::Aabb {
+                min: Vec3::from_qt(min),
+                max: Vec3::from_qt(max),
+                color: Color::from_qt(color),
+            },
+            duration_from_seconds(duration),
+        );
+    }
+
+    pub fn draw_text_3d(
+        &self,
+        position: &QVector3D,
+        text: &QString,
+        color: &QColor,
+        duration: f64,
+    ) {
+        gizmos::draw(
+            QmlGizmo::Text {
+                position: Vec3::from_qt(position),
+                text: text.to_string(),
+                color: Color::from_qt(color),
+            },
+            duration_from_seconds(duration),
+        );
+    }
+
+    pub fn clear(&self) {
+        gizmos::clear();
+    }
+}
+
+/// Negative and invalid durations draw the shape once
+fn duration_from_seconds(seconds: f64) -> Duration {
+    Duration::try_from_secs_f64(seconds).unwrap_or_default()
+}
@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the list model of the scenes, nodes, materials
+/// and animations of a glTF file
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_gltf_model")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!(<QtCore/QAbstractListModel>);
+
+        include!("cxx-qt-lib/qhash.h");
+        /// An alias to the QHash<int, QByteArray> type
+        type QHash_i32_QByteArray = cxx_qt_lib::QHash<cxx_qt_lib::QHashPair_i32_QByteArray>;
+        include!("cxx-qt-lib/qmodelindex.h");
+        /// An alias to the QModelIndex type
+        type QModelIndex = cxx_qt_lib::QModelIndex;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // GltfContentModel based on the Rust struct GltfContentModelRust.
+        #[qobject]
+        #[qml_element]
+        #[base = "QAbstractListModel"]
+        #[qproperty(u64, handle_id)]
+        #[qproperty(QString, kind)]
+        #[qproperty(bool, loaded)]
+        #[qproperty(i32, count)]
+        type GltfContentModel = super::GltfContentModelRust;
+    }
+
+    // QAbstractListModel implementation
+    unsafe extern "RustQt" {
+        #[inherit]
+        #[cxx_name = "beginResetModel"]
+        unsafe fn begin_reset_model(self: Pin<&mut GltfContentModel>);
+
+        #[inherit]
+        #[cxx_name = "endResetModel"]
+        unsafe fn end_reset_model(self: Pin<&mut GltfContentModel>);
+    }
+
+    unsafe extern "RustQt" {
+        #[qinvokable]
+        #[cxx_override]
+        fn data(self: &GltfContentModel, index: &QModelIndex, role: i32) -> QVariant;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "roleNames"]
+        fn role_names(self: &GltfContentModel) -> QHash_i32_QByteArray;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "rowCount"]
+        fn row_count(self: &GltfContentModel, parent: &QModelIndex) -> i32;
+
+        /// The asset path of the part of this kind with this name, or an
+        /// empty string if there is none
+        #[qinvokable]
+        #[cxx_name = "urlOf"]
+        fn url_of(self: &GltfContentModel, kind: &QString, name: &QString) -> QString;
+    }
+
+    impl cxx_qt::Threading for GltfContentModel {}
+    impl cxx_qt::Constructor<()> for GltfContentModel {}
+}
+
+use core::pin::Pin;
+
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QByteArray, QHash, QHashPair_i32_QByteArray, QModelIndex, QString, QVariant};
+
+use crate::{
+    asset::{self, GltfPart},
+    runtime::{self, UpdateListener},
+};
+
+/// Qt::UserRole, the first role available to custom models
+const USER_ROLE: i32 = 0x0100;
+
+/// The role which holds the kind of a part
+const KIND_ROLE: i32 = USER_ROLE;
+
+/// The role which holds the name of a part
+const NAME_ROLE: i32 = USER_ROLE + 1;
+
+/// The role which holds the label of a part within the file
+const LABEL_ROLE: i32 = USER_ROLE + 2;
+
+/// The role which holds the asset path of a part
+const URL_ROLE: i32 = USER_ROLE + 3;
+
+/// The Rust struct for the QObject
+///
+/// Lists the parts of the glTF file loaded as `handleId`, usually that of a
+/// [crate::cxxqt_bevy_asset_load] with the `"gltf"` type hint, see
+/// [asset::gltf_contents] for which parts. Each row has a `kind`, which is
+/// `"scene"`, `"node"`, `"material"` or `"animation"`, the `name` from the
+/// file, its `label` and a `url` which can be passed to
+/// `BevyCommands.spawnScene` and `BevyAssets.loadAsset`. Setting `kind` only
+/// lists the parts of that kind, and `loaded` becomes true once the file is
+/// there:
+///
+/// ```qml
+/// BevyAssetLoad {
+///     id: house
+///     url: "models/house.glb"
+///     typeHint: "gltf"
+/// }
+/// GltfContentModel {
+///     id: variants
+///     handleId: house.handleId
+///     kind: "scene"
+/// }
+/// Menu {
+///     Repeater {
+///         model: variants
+///         MenuItem {
+///             text: model.name
+///             onTriggered: BevyCommands.spawnScene(model.url, 0, Qt.matrix4x4())
+///         }
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct GltfContentModelRust {
+    handle_id: u64,
+    kind: QString,
+    loaded: bool,
+    count: i32,
+    parts: Vec<GltfPart>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::GltfContentModel {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|model| model.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.as_mut()
+            .on_handle_id_changed(|model| model.refresh())
+            .release();
+        self.on_kind_changed(|model| model.refresh()).release();
+    }
+}
+
+impl qobject::GltfContentModel {
+    pub fn data(&self, index: &QModelIndex, role: i32) -> QVariant {
+        let Some(part) = usize::try_from(index.row())
+            .ok()
+            .and_then(|row| self.rust().parts.get(row))
+        else {
+            return QVariant::default();
+        };
+
+        let value = match role {
+            KIND_ROLE => part.kind.as_str(),
+            NAME_ROLE => part.name.as_str(),
+            LABEL_ROLE => part.label.as_str(),
+            URL_ROLE => part.path.as_str(),
+            _ => return QVariant::default(),
+        };
+        QVariant::from(&QString::from(value))
+    }
+
+    pub fn role_names(&self) -> QHash<QHashPair_i32_QByteArray> {
+        let mut roles = QHash::<QHashPair_i32_QByteArray>::default();
+        roles.insert(KIND_ROLE, QByteArray::from("kind"));
+        roles.insert(NAME_ROLE, QByteArray::from("name"));
+        roles.insert(LABEL_ROLE, QByteArray::from("label"));
+        roles.insert(URL_ROLE, QByteArray::from("url"));
+        roles
+    }
+
+    pub fn row_count(&self, _parent: &QModelIndex) -> i32 {
+        self.rust().parts.len() as i32
+    }
+
+    pub fn url_of(&self, kind: &QString, name: &QString) -> QString {
+        let kind = kind.to_string();
+        let name = name.to_string();
+        self.rust()
+            .parts
+            .iter()
+            .find(|part| part.kind.as_str() == kind && part.name == name)
+            .map_or_else(QString::default, |part| QString::from(part.path.as_str()))
+    }
+
+    /// Follow the contents of the file, which only change when it is loaded
+    /// or reloaded
+    fn refresh(mut self: Pin<&mut Self>) {
+        let id = *self.handle_id();
+        let contents = if id == 0 {
+            None
+        } else {
+            runtime::with_world(|world| asset::gltf_contents(world, id)).flatten()
+        };
+        let loaded = contents.is_some();
+
+        let kind = self.kind().to_string();
+        let mut parts = contents.unwrap_or_default();
+        if !kind.is_empty() {
+            parts.retain(|part| part.kind.as_str() == kind);
+        }
+
+        if self.rust().parts != parts {
+            unsafe {
+                self.as_mut().begin_reset_model();
+            }
+            self.as_mut().rust_mut().parts = parts;
+            unsafe {
+                self.as_mut().end_reset_model();
+            }
+            let count = self.rust().parts.len() as i32;
+            self.as_mut().set_count(count);
+        }
+        if *self.loaded() != loaded {
+            self.as_mut().set_loaded(loaded);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that compares captured frames with
+/// golden images
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_golden_image")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qimage.h");
+        /// An alias to the QImage type
+        type QImage = cxx_qt_lib::QImage;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qurl.h");
+        /// An alias to the QUrl type
+        type QUrl = cxx_qt_lib::QUrl;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // GoldenImage based on the Rust struct GoldenImageRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(QUrl, directory)]
+        #[qproperty(i32, channel_tolerance)]
+        #[qproperty(f64, pixel_tolerance)]
+        #[qproperty(bool, missing)]
+        #[qproperty(QString, message)]
+        type GoldenImage = super::GoldenImageRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// Compare the image with the golden image `<directory>/<name>.png`,
+        /// returns whether it matches
+        #[qinvokable]
+        fn compare(self: Pin<&mut GoldenImage>, image: &QImage, name: &QString) -> bool;
+    }
+
+    impl cxx_qt::Constructor<()> for GoldenImage {}
+}
+
+use core::pin::Pin;
+use std::path::Path;
+
+use cxx_qt_lib::{QImage, QString, QUrl};
+
+use crate::testing::{self, GoldenOutcome, Tolerance};
+
+/// The Rust struct for the QObject
+///
+/// Compares frames from the `frameCaptured` signal of a `BevyQuickItem`
+/// with golden images in the local folder `directory`, see [crate::testing].
+/// `channelTolerance` is how far a color channel may be off, out of 255, and
+/// `pixelTolerance` the fraction of pixels which may be off by more. After a
+/// comparison `message` describes the outcome, and `missing` tells whether
+/// the golden image did not exist yet.
+///
+/// ```qml
+/// GoldenImage {
+///     id: golden
+///     directory: Qt.resolvedUrl("goldens")
+/// }
+/// SignalSpy {
+///     id: captured
+///     target: item
+///     signalName: "frameCaptured"
+/// }
+/// function test_scene() {
+///     item.captureFrame("")
+///     captured.wait()
+///     verify(golden.compare(captured.signalArguments[0][0], "scene"), golden.message)
+/// }
+/// ```
+pub struct GoldenImageRust {
+    directory: QUrl,
+    channel_tolerance: i32,
+    pixel_tolerance: f64,
+    missing: bool,
+    message: QString,
+}
+
+impl Default for GoldenImageRust {
+    fn default() -> Self {
+        let tolerance = Tolerance::default();
+        Self {
+            directory: QUrl::default(),
+            channel_tolerance: i32::from(tolerance.channel),
+            pixel_tolerance: f64::from(tolerance.pixels),
+            missing: false,
+            message: QString::default(),
+        }
+    }
+}
+
+impl qobject::GoldenImage {
+    pub fn compare(mut self: Pin<&mut Self>, image: &QImage, name: &QString) -> bool {
+        let directory = self.directory().to_local_file_or_default().to_string();
+        let golden = Path::new(&directory).join(format!("{name}.png"));
+        let golden = golden.to_string_lossy();
+        let tolerance = Tolerance {
+            channel: self.channel_tolerance().clamp(0, 255) as u8,
+            pixels: self.pixel_tolerance().clamp(0.0, 1.0) as f32,
+        };
+
+        let outcome = testing::compare_with_golden(image, &golden, tolerance);
+        let message = QString::from(outcome.message(&golden).as_str());
+        self.as_mut().set_missing(outcome == GoldenOutcome::Missing);
+        self.as_mut().set_message(message);
+        outcome.passed()
+    }
+}
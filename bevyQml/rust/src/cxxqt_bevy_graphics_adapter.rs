@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML singleton that shows the GPU adapter
+/// Bevy renders with
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_graphics_adapter")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qstringlist.h");
+        /// An alias to the QStringList type
+        type QStringList = cxx_qt_lib::QStringList;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QML singleton with the name
+        // GraphicsAdapterBridge based on the Rust struct
+        // GraphicsAdapterBridgeRust.
+        #[qobject]
+        #[qml_element]
+        #[qml_singleton]
+        #[qproperty(QString, name)]
+        #[qproperty(QString, backend)]
+        #[qproperty(QString, device_type)]
+        #[qproperty(QString, driver)]
+        #[qproperty(i32, adapter_index)]
+        #[qproperty(QStringList, adapters)]
+        type GraphicsAdapterBridge = super::GraphicsAdapterBridgeRust;
+    }
+
+    impl cxx_qt::Threading for GraphicsAdapterBridge {}
+    impl cxx_qt::Constructor<()> for GraphicsAdapterBridge {}
+}
+
+use core::pin::Pin;
+
+use bevy::render::renderer::RenderAdapterInfo;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QList, QString, QStringList};
+use wgpu::DeviceType;
+
+use crate::{
+    render::adapter::{self, available_adapters, backend_name},
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// `name`, `backend` and `driver` describe the adapter Bevy renders with,
+/// and `deviceType` is `"discrete"`, `"integrated"`, `"virtual"`,
+/// `"software"` or `"other"`. They are empty until the renderer has started.
+/// `adapters` lists every adapter of the system with its graphics API, and
+/// `adapterIndex` is the one Bevy renders with, or -1. The index can be
+/// asked for with [crate::render::adapter::AdapterSelection] or the
+/// `BEVYQML_ADAPTER` environment variable:
+///
+/// ```qml
+/// Label {
+///     text: GraphicsAdapterBridge.name + " on " + GraphicsAdapterBridge.backend
+///     visible: GraphicsAdapterBridge.deviceType !== "software"
+/// }
+/// ```
+pub struct GraphicsAdapterBridgeRust {
+    name: QString,
+    backend: QString,
+    device_type: QString,
+    driver: QString,
+    adapter_index: i32,
+    adapters: QStringList,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for GraphicsAdapterBridgeRust {
+    fn default() -> Self {
+        Self {
+            name: QString::default(),
+            backend: QString::default(),
+            device_type: QString::default(),
+            driver: QString::default(),
+            adapter_index: -1,
+            adapters: QStringList::default(),
+            update_listener: None,
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::GraphicsAdapterBridge {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let mut adapters = QList::<QString>::default();
+        for info in available_adapters() {
+            let name = format!("{} ({})", info.name, backend_name(info.backend));
+            adapters.append(QString::from(name.as_str()));
+        }
+        self.as_mut().set_adapters(QStringList::from(&adapters));
+
+        // The adapter changes when the app is built anew after losing its
+        // device
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|bridge| bridge.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+        self.refresh();
+    }
+}
+
+impl qobject::GraphicsAdapterBridge {
+    fn refresh(mut self: Pin<&mut Self>) {
+        let Some(info) = runtime::with_world(|world| {
+            world
+                .get_resource::<RenderAdapterInfo>()
+                .map(|info| wgpu::AdapterInfo::clone(info))
+        })
+        .flatten() else {
+            return;
+        };
+
+        let name = QString::from(info.name.as_str());
+        if *self.name() != name {
+            self.as_mut().set_name(name);
+        }
+        let backend = QString::from(backend_name(info.backend));
+        if *self.backend() != backend {
+            self.as_mut().set_backend(backend);
+        }
+        let device_type = QString::from(match info.device_type {
+            DeviceType::DiscreteGpu => "discrete",
+            DeviceType::IntegratedGpu => "integrated",
+            DeviceType::VirtualGpu => "virtual",
+            DeviceType::Cpu => "software",
+            DeviceType::Other => "other",
+        });
+        if *self.device_type() != device_type {
+            self.as_mut().set_device_type(device_type);
+        }
+        let driver = QString::from(format!("{} {}", info.driver, info.driver_info).trim());
+        if *self.driver() != driver {
+            self.as_mut().set_driver(driver);
+        }
+        let index = adapter::adapter_index(&info).map_or(-1, |index| index as i32);
+        if *self.adapter_index() != index {
+            self.as_mut().set_adapter_index(index);
+        }
+    }
+}
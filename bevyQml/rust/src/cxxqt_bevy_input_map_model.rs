@@ -0,0 +1,402 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the list model of the actions and axes of the
+/// input map
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_input_map_model")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!(<QtCore/QAbstractListModel>);
+
+        include!("cxx-qt-lib/qhash.h");
+        /// An alias to the QHash<int, QByteArray> type
+        type QHash_i32_QByteArray = cxx_qt_lib::QHash<cxx_qt_lib::QHashPair_i32_QByteArray>;
+        include!("cxx-qt-lib/qmodelindex.h");
+        /// An alias to the QModelIndex type
+        type QModelIndex = cxx_qt_lib::QModelIndex;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qstringlist.h");
+        /// An alias to the QStringList type
+        type QStringList = cxx_qt_lib::QStringList;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+        include!("cxx-qt-lib/qvector.h");
+        /// An alias to the QVector<int> type
+        type QVector_i32 = cxx_qt_lib::QVector<i32>;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // InputMapModel based on the Rust struct InputMapModelRust.
+        #[qobject]
+        #[qml_element]
+        #[base = "QAbstractListModel"]
+        #[qproperty(i32, count)]
+        type InputMapModel = super::InputMapModelRust;
+    }
+
+    // QAbstractListModel implementation
+    unsafe extern "RustQt" {
+        #[inherit]
+        #[cxx_name = "beginResetModel"]
+        unsafe fn begin_reset_model(self: Pin<&mut InputMapModel>);
+
+        #[inherit]
+        #[cxx_name = "endResetModel"]
+        unsafe fn end_reset_model(self: Pin<&mut InputMapModel>);
+
+        #[inherit]
+        fn index(self: &InputMapModel, row: i32, column: i32, parent: &QModelIndex) -> QModelIndex;
+
+        #[qsignal]
+        #[inherit]
+        #[cxx_name = "dataChanged"]
+        fn data_changed(
+            self: Pin<&mut InputMapModel>,
+            top_left: &QModelIndex,
+            bottom_right: &QModelIndex,
+            roles: &QVector_i32,
+        );
+    }
+
+    unsafe extern "RustQt" {
+        #[qinvokable]
+        #[cxx_override]
+        fn data(self: &InputMapModel, index: &QModelIndex, role: i32) -> QVariant;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "roleNames"]
+        fn role_names(self: &InputMapModel) -> QHash_i32_QByteArray;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "rowCount"]
+        fn row_count(self: &InputMapModel, parent: &QModelIndex) -> i32;
+
+        /// Bind the action, or the positive side of the axis, to sources
+        /// such as `"Key:Space"` or `"GamepadButton:South"`
+        #[qinvokable]
+        #[cxx_name = "setSources"]
+        fn set_sources(self: &InputMapModel, name: &QString, sources: &QStringList);
+
+        /// Bind the negative side of the axis
+        #[qinvokable]
+        #[cxx_name = "setNegativeSources"]
+        fn set_negative_sources(self: &InputMapModel, name: &QString, sources: &QStringList);
+
+        /// Go back to the default sources of the action or axis
+        #[qinvokable]
+        fn reset(self: &InputMapModel, name: &QString);
+
+        #[qinvokable]
+        #[cxx_name = "resetAll"]
+        fn reset_all(self: &InputMapModel);
+
+        /// Wait for the next key, mouse button, gamepad button or stick
+        /// pushed far enough, for picking a new source
+        ///
+        /// Returns a task id. taskFinished is emitted with the source as
+        /// text, or taskFailed if Escape was pressed instead. Keys and mouse
+        /// buttons only reach Bevy through a `BevyQuickItem` with the focus,
+        /// otherwise see keySource.
+        #[qinvokable]
+        #[cxx_name = "captureSource"]
+        fn capture_source(self: Pin<&mut InputMapModel>) -> u64;
+
+        /// The source of a key reported to QML, as in
+        /// `Keys.onPressed: event => inputMap.keySource(event.key, event.modifiers)`
+        ///
+        /// Returns an empty string for keys Bevy does not know.
+        #[qinvokable]
+        #[cxx_name = "keySource"]
+        fn key_source(self: &InputMapModel, key: i32, modifiers: u32) -> QString;
+
+        /// A task started by captureSource has finished
+        #[qsignal]
+        #[cxx_name = "taskFinished"]
+        fn task_finished(self: Pin<&mut InputMapModel>, task_id: u64, result: QVariant);
+
+        #[qsignal]
+        #[cxx_name = "taskFailed"]
+        fn task_failed(self: Pin<&mut InputMapModel>, task_id: u64, error: QString);
+    }
+
+    impl cxx_qt::Threading for InputMapModel {}
+    impl cxx_qt::Constructor<()> for InputMapModel {}
+}
+
+use core::pin::Pin;
+
+use bevy::{ecs::system::SystemState, prelude::*};
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{
+    QByteArray, QHash, QHashPair_i32_QByteArray, QList, QModelIndex, QString, QStringList,
+    QVariant, QVector,
+};
+
+use crate::{
+    input::{
+        keyboard::QtKey,
+        map::{BindingKind, InputBinding, InputMap, InputSource, InputSources},
+    },
+    runtime::{self, UpdateListener},
+    task,
+};
+
+/// Qt::UserRole, the first role available to custom models
+const USER_ROLE: i32 = 0x0100;
+
+const NAME_ROLE: i32 = USER_ROLE;
+const KIND_ROLE: i32 = USER_ROLE + 1;
+const SOURCES_ROLE: i32 = USER_ROLE + 2;
+const NEGATIVE_SOURCES_ROLE: i32 = USER_ROLE + 3;
+const CUSTOMIZED_ROLE: i32 = USER_ROLE + 4;
+const PRESSED_ROLE: i32 = USER_ROLE + 5;
+const VALUE_ROLE: i32 = USER_ROLE + 6;
+
+/// The Rust struct for the QObject
+///
+/// The model lists the actions and axes of the [InputMap] of the app, with
+/// the roles `name`, `kind` ("action" or "axis"), `sources` and
+/// `negativeSources` as lists of texts such as `"Key:Space"`, `customized`,
+/// and `pressed` and `value` as they are in the current update. A dialog
+/// rebinds them with captureSource:
+///
+/// ```qml
+/// ListView {
+///     model: InputMapModel { id: inputMap }
+///     delegate: RowLayout {
+///         Label { text: model.name }
+///         Button {
+///             text: model.sources.join(", ")
+///             onClicked: BevyAsync.promise(inputMap, inputMap.captureSource())
+///                 .then(source => inputMap.setSources(model.name, [source]))
+///         }
+///         Button {
+///             text: qsTr("Reset")
+///             enabled: model.customized
+///             onClicked: inputMap.reset(model.name)
+///         }
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct InputMapModelRust {
+    count: i32,
+    rows: Vec<InputBinding>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::InputMapModel {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|model| model.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+        self.refresh();
+    }
+}
+
+impl qobject::InputMapModel {
+    pub fn data(&self, index: &QModelIndex, role: i32) -> QVariant {
+        let Some(row) = usize::try_from(index.row())
+            .ok()
+            .and_then(|row| self.rust().rows.get(row))
+        else {
+            return QVariant::default();
+        };
+
+        match role {
+            NAME_ROLE => QVariant::from(&QString::from(&row.name)),
+            KIND_ROLE => QVariant::from(&QString::from(match row.kind {
+                BindingKind::Action => "action",
+                BindingKind::Axis => "axis",
+            })),
+            SOURCES_ROLE => QVariant::from(&source_list(&row.sources)),
+            NEGATIVE_SOURCES_ROLE => QVariant::from(&source_list(&row.negative)),
+            CUSTOMIZED_ROLE => QVariant::from(&row.is_customized()),
+            PRESSED_ROLE => QVariant::from(&row.pressed()),
+            VALUE_ROLE => QVariant::from(&f64::from(row.value())),
+            _ => QVariant::default(),
+        }
+    }
+
+    pub fn role_names(&self) -> QHash<QHashPair_i32_QByteArray> {
+        let mut roles = QHash::<QHashPair_i32_QByteArray>::default();
+        roles.insert(NAME_ROLE, QByteArray::from("name"));
+        roles.insert(KIND_ROLE, QByteArray::from("kind"));
+        roles.insert(SOURCES_ROLE, QByteArray::from("sources"));
+        roles.insert(NEGATIVE_SOURCES_ROLE, QByteArray::from("negativeSources"));
+        roles.insert(CUSTOMIZED_ROLE, QByteArray::from("customized"));
+        roles.insert(PRESSED_ROLE, QByteArray::from("pressed"));
+        roles.insert(VALUE_ROLE, QByteArray::from("value"));
+        roles
+    }
+
+    pub fn row_count(&self, _parent: &QModelIndex) -> i32 {
+        self.rust().rows.len() as i32
+    }
+
+    pub fn set_sources(&self, name: &QString, sources: &QStringList) {
+        self.rebind(name, sources, false);
+    }
+
+    pub fn set_negative_sources(&self, name: &QString, sources: &QStringList) {
+        self.rebind(name, sources, true);
+    }
+
+    pub fn reset(&self, name: &QString) {
+        let name = name.to_string();
+        modify("reset", move |map| map.reset(&name));
+    }
+
+    pub fn reset_all(&self) {
+        modify("reset", |map| {
+            map.reset_all();
+            Ok(())
+        });
+    }
+
+    pub fn capture_source(self: Pin<&mut Self>) -> u64 {
+        let id = task::next_task_id();
+        let capture = task::world_until(|world| {
+            let mut state = SystemState::<InputSources>::new(world);
+            let source = state.get(world).first_pressed()?;
+            Some(source)
+        });
+        task::spawn(
+            self.qt_thread(),
+            capture,
+            move |model, source| match source {
+                Some(InputSource::Key(KeyCode::Escape)) => {
+                    model.task_failed(id, QString::from("Canceled"))
+                }
+                Some(source) => model.task_finished(
+                    id,
+                    QVariant::from(&QString::from(source.to_string().as_str())),
+                ),
+                None => model.task_failed(id, QString::from("The Bevy app is not running")),
+            },
+        );
+        id
+    }
+
+    pub fn key_source(&self, key: i32, modifiers: u32) -> QString {
+        let key = QtKey {
+            key,
+            modifiers,
+            text: String::new(),
+            auto_repeat: false,
+        };
+        match key.key_code() {
+            KeyCode::Unidentified(_) => QString::default(),
+            key_code => QString::from(InputSource::Key(key_code).to_string().as_str()),
+        }
+    }
+
+    fn rebind(&self, name: &QString, sources: &QStringList, negative: bool) {
+        let name = name.to_string();
+        let parsed: Result<Vec<InputSource>, String> = QList::<QString>::from(sources)
+            .iter()
+            .map(|source| source.to_string().parse())
+            .collect();
+        let sources = match parsed {
+            Ok(sources) => sources,
+            Err(error) => {
+                warn!("Cannot bind {name:?}: {error}");
+                return;
+            }
+        };
+        modify("bind", move |map| {
+            let binding = map
+                .get(&name)
+                .ok_or_else(|| format!("There is no action or axis {name:?}"))?;
+            let (positive, negative) = if negative {
+                (binding.sources.clone(), sources)
+            } else {
+                (sources, binding.negative.clone())
+            };
+            map.rebind(&name, positive, negative)
+        });
+    }
+
+    /// Read the bindings and their state in the current update
+    fn refresh(mut self: Pin<&mut Self>) {
+        let rows = runtime::with_world(|world| {
+            world
+                .get_resource::<InputMap>()
+                .map(|map| map.bindings().to_vec())
+        })
+        .flatten()
+        .unwrap_or_default();
+
+        let same_bindings = rows.len() == self.rust().rows.len()
+            && rows
+                .iter()
+                .zip(&self.rust().rows)
+                .all(|(row, old)| row.name == old.name);
+        if !same_bindings {
+            unsafe {
+                self.as_mut().begin_reset_model();
+            }
+            self.as_mut().rust_mut().rows = rows;
+            unsafe {
+                self.as_mut().end_reset_model();
+            }
+            let count = self.rust().rows.len() as i32;
+            self.set_count(count);
+            return;
+        }
+
+        let changed: Vec<usize> = rows
+            .iter()
+            .zip(&self.rust().rows)
+            .enumerate()
+            .filter(|(_, (row, old))| row != old)
+            .map(|(index, _)| index)
+            .collect();
+        if changed.is_empty() {
+            return;
+        }
+        self.as_mut().rust_mut().rows = rows;
+        // Every role may have changed
+        let roles = QVector::<i32>::default();
+        for row in changed {
+            let index = self.index(row as i32, 0, &QModelIndex::default());
+            self.as_mut().data_changed(&index, &index, &roles);
+        }
+    }
+}
+
+/// Change the input map wherever the app runs, the rows follow after the
+/// next update
+fn modify(
+    what: &'static str,
+    f: impl FnOnce(&mut InputMap) -> Result<(), String> + Send + 'static,
+) {
+    let sent = runtime::send(move |world| {
+        let Some(mut map) = world.get_resource_mut::<InputMap>() else {
+            warn!("Cannot {what} input without an InputMap");
+            return;
+        };
+        if let Err(error) = f(&mut map) {
+            warn!("Cannot {what} input from QML: {error}");
+        }
+    });
+    if !sent {
+        warn!("Cannot {what} input without a running Bevy app");
+    }
+}
+
+fn source_list(sources: &[InputSource]) -> QStringList {
+    let mut list = QList::<QString>::default();
+    for source in sources {
+        list.append(QString::from(source.to_string().as_str()));
+    }
+    QStringList::from(&list)
+}
@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML element that declares a camera framing
+/// an entity
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_inspection_camera")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvector3d.h");
+        /// An alias to the QVector3D type
+        type QVector3D = cxx_qt_lib::QVector3D;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BevyInspectionCamera based on the Rust struct
+        // BevyInspectionCameraRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(u64, entity)]
+        #[qproperty(QString, name)]
+        #[qproperty(u64, inspected_entity)]
+        #[qproperty(QVector3D, direction)]
+        #[qproperty(f64, margin)]
+        #[qproperty(i32, image_width)]
+        #[qproperty(i32, image_height)]
+        type BevyInspectionCamera = super::BevyInspectionCameraRust;
+
+        /// Frame the entity with the given bits
+        #[qinvokable]
+        fn inspect(self: Pin<&mut BevyInspectionCamera>, entity: u64);
+
+        /// Stop framing an entity, and rendering with it
+        #[qinvokable]
+        fn clear(self: Pin<&mut BevyInspectionCamera>);
+    }
+
+    impl cxx_qt::Threading for BevyInspectionCamera {}
+    impl cxx_qt::Constructor<()> for BevyInspectionCamera {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QString, QVector3D};
+
+use crate::{
+    convert::{FromQt, IntoQt},
+    declarative::{self, DeclaredEntity},
+    inspection::{self, QmlInspectionCamera},
+    render::QmlImageTarget,
+};
+
+/// The Rust struct for the QObject
+///
+/// The element spawns a camera which frames the entity whose bits are
+/// `inspectedEntity`, looking at it from `direction` with `margin` times its
+/// bounds in view, see [crate::inspection]. It renders into an `imageWidth`
+/// by `imageHeight` image published as `image://bevy/<name>`, which a
+/// `BevyTextureSource` keeps live, e.g. in a hover preview:
+///
+/// ```qml
+/// BevyInspectionCamera {
+///     id: inspector
+///     name: "preview"
+/// }
+/// BevyTextureSource {
+///     id: preview
+///     name: inspector.name
+/// }
+/// BevyQuickItem {
+///     id: view
+///     HoverHandler {
+///         onPointChanged: {
+///             const hit = view.pick(point.position.x, point.position.y);
+///             if (hit.entity) inspector.inspect(hit.entity); else inspector.clear();
+///         }
+///     }
+///     Popup {
+///         visible: inspector.inspectedEntity !== 0
+///         Image { source: preview.source; cache: false }
+///     }
+/// }
+/// ```
+pub struct BevyInspectionCameraRust {
+    entity: u64,
+    name: QString,
+    inspected_entity: u64,
+    direction: QVector3D,
+    margin: f64,
+    image_width: i32,
+    image_height: i32,
+    declared: DeclaredEntity,
+}
+
+impl Default for BevyInspectionCameraRust {
+    fn default() -> Self {
+        let inspection = QmlInspectionCamera::default();
+        Self {
+            entity: 0,
+            name: QString::from("inspection"),
+            inspected_entity: 0,
+            direction: inspection.direction.into_qt(),
+            margin: inspection.margin.into(),
+            image_width: 256,
+            image_height: 256,
+            declared: DeclaredEntity::default(),
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::BevyInspectionCamera {
+    fn initialize(mut self: Pin<&mut Self>) {
+        // Spawn once QML has set the initial properties
+        let _ = self.qt_thread().queue(|element| element.spawn());
+
+        self.as_mut()
+            .on_name_changed(|element| element.write_target())
+            .release();
+        self.as_mut()
+            .on_image_width_changed(|element| element.write_target())
+            .release();
+        self.as_mut()
+            .on_image_height_changed(|element| element.write_target())
+            .release();
+        self.as_mut()
+            .on_inspected_entity_changed(|element| element.write_inspection())
+            .release();
+        self.as_mut()
+            .on_direction_changed(|element| element.write_inspection())
+            .release();
+        self.on_margin_changed(|element| element.write_inspection())
+            .release();
+    }
+}
+
+impl qobject::BevyInspectionCamera {
+    pub fn inspect(self: Pin<&mut Self>, entity: u64) {
+        if *self.inspected_entity() != entity {
+            self.set_inspected_entity(entity);
+        }
+    }
+
+    pub fn clear(self: Pin<&mut Self>) {
+        self.inspect(0);
+    }
+
+    fn spawn(mut self: Pin<&mut Self>) {
+        let bundle = inspection::inspection_camera_bundle(self.inspection(), self.target());
+        let Some(entity) = self.as_mut().rust_mut().declared.spawn(bundle) else {
+            warn!("BevyInspectionCamera cannot spawn an entity without a running Bevy app");
+            return;
+        };
+        self.set_entity(entity.to_bits());
+    }
+
+    fn inspection(&self) -> QmlInspectionCamera {
+        QmlInspectionCamera {
+            target: declarative::entity_from_bits(*self.inspected_entity()),
+            direction: Vec3::from_qt(self.direction()),
+            margin: *self.margin() as f32,
+        }
+    }
+
+    fn target(&self) -> QmlImageTarget {
+        QmlImageTarget::new(
+            self.name().to_string(),
+            UVec2::new(
+                (*self.image_width()).max(1) as u32,
+                (*self.image_height()).max(1) as u32,
+            ),
+        )
+    }
+
+    fn write_inspection(self: Pin<&mut Self>) {
+        let inspection = self.inspection();
+        self.rust().declared.update(move |world, entity| {
+            let mut entity = world.entity_mut(entity);
+            if entity.get::<QmlInspectionCamera>() != Some(&inspection) {
+                entity.insert(inspection);
+            }
+        });
+    }
+
+    fn write_target(self: Pin<&mut Self>) {
+        let target = self.target();
+        self.rust().declared.update(move |world, entity| {
+            world.entity_mut(entity).insert(target);
+        });
+    }
+}
@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that shows and hides a named group
+/// of entities
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_layer_group")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // LayerGroup based on the Rust struct LayerGroupRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(QString, name)]
+        #[qproperty(bool, visible)]
+        #[qproperty(i32, render_layer)]
+        #[qproperty(i32, count)]
+        type LayerGroup = super::LayerGroupRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// Put the entity into the group, returns false if it was in there
+        /// already or does not exist
+        #[qinvokable]
+        fn add(self: &LayerGroup, entity: u64) -> bool;
+
+        /// Take the entity out of the group, returns false if it was not in
+        /// there
+        #[qinvokable]
+        fn remove(self: &LayerGroup, entity: u64) -> bool;
+    }
+
+    impl cxx_qt::Threading for LayerGroup {}
+    impl cxx_qt::Constructor<()> for LayerGroup {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::QString;
+
+use crate::{
+    layers::{self, QmlLayerVisibility},
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// Shows and hides the entities in the group `name`, see [crate::layers].
+/// Groups with the same name are one group, so several objects may control
+/// it and `visible` follows changes made elsewhere. Setting the name applies
+/// the other properties to the group. `renderLayer` makes the group stand for
+/// that render layer as well, and is -1 otherwise. `count` is the number of
+/// entities in the group.
+///
+/// Several groups can be gathered in one object, so that they read like
+/// layers of a map:
+///
+/// ```qml
+/// QtObject {
+///     id: layers
+///     property LayerGroup labels: LayerGroup { name: "labels" }
+///     property LayerGroup sensors: LayerGroup { name: "sensors"; renderLayer: 2 }
+/// }
+/// CheckBox {
+///     text: "Labels (" + layers.labels.count + ")"
+///     checked: layers.labels.visible
+///     onToggled: layers.labels.visible = checked
+/// }
+/// ```
+pub struct LayerGroupRust {
+    name: QString,
+    visible: bool,
+    render_layer: i32,
+    count: i32,
+    /// Set while the properties are updated from the world, so the change
+    /// handlers do not write them back
+    syncing: bool,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for LayerGroupRust {
+    fn default() -> Self {
+        Self {
+            name: QString::default(),
+            visible: true,
+            render_layer: -1,
+            count: 0,
+            syncing: false,
+            update_listener: None,
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::LayerGroup {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|group| group.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.as_mut()
+            .on_name_changed(|group| group.write())
+            .release();
+        self.as_mut()
+            .on_visible_changed(|group| group.write())
+            .release();
+        self.on_render_layer_changed(|group| group.write())
+            .release();
+    }
+}
+
+impl qobject::LayerGroup {
+    pub fn add(&self, entity: u64) -> bool {
+        self.change_entity(entity, layers::add_to_layer)
+    }
+
+    pub fn remove(&self, entity: u64) -> bool {
+        self.change_entity(entity, layers::remove_from_layer)
+    }
+
+    fn change_entity(&self, entity: u64, f: fn(&mut World, Entity, &str) -> bool) -> bool {
+        let name = self.name().to_string();
+        let Ok(entity) = Entity::try_from_bits(entity) else {
+            return false;
+        };
+        if name.is_empty() {
+            warn!("LayerGroup cannot change the entities of a group without a name");
+            return false;
+        }
+        let changed = runtime::with_world(|world| f(world, entity, &name)).unwrap_or(false);
+        if changed {
+            runtime::request_update();
+        }
+        changed
+    }
+
+    /// Change the group to the properties
+    fn write(self: Pin<&mut Self>) {
+        if self.rust().syncing {
+            return;
+        }
+        let name = self.name().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let visible = *self.visible();
+        let render_layer = usize::try_from(*self.render_layer()).ok();
+        runtime::send(move |world| {
+            let mut layers = world.resource_mut::<QmlLayerVisibility>();
+            layers.set_visible(&name, visible);
+            layers.set_render_layer(&name, render_layer);
+        });
+        runtime::request_update();
+    }
+
+    /// Follow the group, which other objects may change as well
+    fn refresh(mut self: Pin<&mut Self>) {
+        let name = self.name().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let update = runtime::with_world(|world| {
+            let layers = world.get_resource::<QmlLayerVisibility>()?;
+            let visible = layers.is_visible(&name);
+            let render_layer = layers.render_layer(&name);
+            Some((visible, render_layer, layers::layer_count(world, &name)))
+        })
+        .flatten();
+        let Some((visible, render_layer, count)) = update else {
+            return;
+        };
+
+        self.as_mut().rust_mut().syncing = true;
+        if *self.visible() != visible {
+            self.as_mut().set_visible(visible);
+        }
+        let render_layer = render_layer.map_or(-1, |layer| i32::try_from(layer).unwrap_or(-1));
+        if *self.render_layer() != render_layer {
+            self.as_mut().set_render_layer(render_layer);
+        }
+        let count = i32::try_from(count).unwrap_or(i32::MAX);
+        if *self.count() != count {
+            self.as_mut().set_count(count);
+        }
+        self.as_mut().rust_mut().syncing = false;
+    }
+}
@@ -0,0 +1,313 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML element that declares a light
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_light")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qcolor.h");
+        /// An alias to the QColor type
+        type QColor = cxx_qt_lib::QColor;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qlist.h");
+        /// An alias to the QVariantList type
+        type QList_QVariant = cxx_qt_lib::QList<cxx_qt_lib::QVariant>;
+        include!("cxx-qt-lib/qvector3d.h");
+        /// An alias to the QVector3D type
+        type QVector3D = cxx_qt_lib::QVector3D;
+        include!("bevyqml/convert.h");
+        /// An alias to the QQuaternion type
+        type QQuaternion = crate::convert::QQuaternion;
+    }
+
+    /// The kinds of light a BevyLight can be
+    #[qenum(BevyLight)]
+    enum LightType {
+        /// Shines in all directions from its position
+        Point,
+        /// Shines along its forward direction from infinitely far away
+        Directional,
+        /// Shines in a cone along its forward direction
+        Spot,
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BevyLight based on the Rust struct BevyLightRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(u64, entity)]
+        #[qproperty(QString, name)]
+        #[qproperty(u64, parent_entity)]
+        #[qproperty(QVector3D, position)]
+        #[qproperty(QQuaternion, rotation)]
+        #[qproperty(QVector3D, scale)]
+        #[qproperty(bool, visible)]
+        #[qproperty(QList_QVariant, components)]
+        #[qproperty(LightType, light_type)]
+        #[qproperty(QColor, color)]
+        #[qproperty(f64, intensity)]
+        #[qproperty(f64, range)]
+        #[qproperty(f64, spot_angle)]
+        #[qproperty(bool, shadows)]
+        type BevyLight = super::BevyLightRust;
+    }
+
+    impl cxx_qt::Threading for BevyLight {}
+    impl cxx_qt::Constructor<()> for BevyLight {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QColor, QList, QString, QVariant, QVector3D};
+
+use crate::{
+    convert::{FromQt, IntoQt, QQuaternion},
+    declarative::{self, DeclaredEntity, DeclaredProperties},
+};
+
+/// The Rust struct for the QObject
+///
+/// The element spawns a light of `lightType`. Its `intensity` is in lumens
+/// for point and spot lights and in lux for directional lights, where 0
+/// stands for the Bevy default of the type. `range` only matters to point
+/// and spot lights, and `spotAngle` is the angle in degrees between the
+/// direction of a spot light and the edge of its cone. See
+/// [crate::declarative] for the other properties.
+pub struct BevyLightRust {
+    entity: u64,
+    name: QString,
+    parent_entity: u64,
+    position: QVector3D,
+    rotation: QQuaternion,
+    scale: QVector3D,
+    visible: bool,
+    components: QList<QVariant>,
+    light_type: qobject::LightType,
+    color: QColor,
+    intensity: f64,
+    range: f64,
+    spot_angle: f64,
+    shadows: bool,
+    declared: DeclaredEntity,
+}
+
+impl Default for BevyLightRust {
+    fn default() -> Self {
+        Self {
+            entity: 0,
+            name: QString::default(),
+            parent_entity: 0,
+            position: Vec3::ZERO.into_qt(),
+            rotation: QQuaternion::default(),
+            scale: Vec3::ONE.into_qt(),
+            visible: true,
+            components: QList::default(),
+            light_type: qobject::LightType::Point,
+            color: Color::WHITE.into_qt(),
+            intensity: 0.0,
+            range: 20.0,
+            spot_angle: 45.0,
+            shadows: false,
+            declared: DeclaredEntity::default(),
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::BevyLight {
+    fn initialize(mut self: Pin<&mut Self>) {
+        // Spawn once QML has set the initial properties
+        let _ = self.qt_thread().queue(|element| element.spawn());
+
+        self.as_mut()
+            .on_name_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_parent_entity_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_position_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_rotation_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_scale_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_visible_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_components_changed(|element| element.write_components())
+            .release();
+        self.as_mut()
+            .on_light_type_changed(|element| element.write_light())
+            .release();
+        self.as_mut()
+            .on_color_changed(|element| element.write_light())
+            .release();
+        self.as_mut()
+            .on_intensity_changed(|element| element.write_light())
+            .release();
+        self.as_mut()
+            .on_range_changed(|element| element.write_light())
+            .release();
+        self.as_mut()
+            .on_spot_angle_changed(|element| element.write_light())
+            .release();
+        self.on_shadows_changed(|element| element.write_light())
+            .release();
+    }
+}
+
+impl qobject::BevyLight {
+    fn spawn(mut self: Pin<&mut Self>) {
+        let properties = self.properties();
+        let components = declarative::component_list(self.components());
+        let light = self.light();
+        let Some(entity) = self
+            .as_mut()
+            .rust_mut()
+            .declared
+            .spawn(SpatialBundle::default())
+        else {
+            warn!("BevyLight cannot spawn an entity without a running Bevy app");
+            return;
+        };
+        self.rust().declared.update(move |world, entity| {
+            light.apply(world, entity);
+            properties.apply(world, entity);
+            declarative::apply_components(world, entity, components);
+        });
+        self.set_entity(entity.to_bits());
+    }
+
+    fn properties(&self) -> DeclaredProperties {
+        DeclaredProperties::from_qml(
+            self.name(),
+            *self.parent_entity(),
+            self.position(),
+            self.rotation(),
+            self.scale(),
+            *self.visible(),
+        )
+    }
+
+    fn light(&self) -> DeclaredLight {
+        let color = Color::from_qt(self.color());
+        let intensity = (*self.intensity() > 0.0).then_some(*self.intensity() as f32);
+        let range = *self.range() as f32;
+        let shadows_enabled = *self.shadows();
+        let light_type = *self.light_type();
+        if light_type == qobject::LightType::Directional {
+            let defaults = DirectionalLight::default();
+            DeclaredLight::Directional(DirectionalLight {
+                color,
+                illuminance: intensity.unwrap_or(defaults.illuminance),
+                shadows_enabled,
+                ..defaults
+            })
+        } else if light_type == qobject::LightType::Spot {
+            let defaults = SpotLight::default();
+            DeclaredLight::Spot(SpotLight {
+                color,
+                intensity: intensity.unwrap_or(defaults.intensity),
+                range,
+                shadows_enabled,
+                outer_angle: (*self.spot_angle() as f32).to_radians(),
+                ..defaults
+            })
+        } else {
+            let defaults = PointLight::default();
+            DeclaredLight::Point(PointLight {
+                color,
+                intensity: intensity.unwrap_or(defaults.intensity),
+                range,
+                shadows_enabled,
+                ..defaults
+            })
+        }
+    }
+
+    fn write_properties(self: Pin<&mut Self>) {
+        let properties = self.properties();
+        self.rust()
+            .declared
+            .update(move |world, entity| properties.apply(world, entity));
+    }
+
+    fn write_components(self: Pin<&mut Self>) {
+        let components = declarative::component_list(self.components());
+        self.rust().declared.update(move |world, entity| {
+            declarative::apply_components(world, entity, components)
+        });
+    }
+
+    fn write_light(self: Pin<&mut Self>) {
+        let light = self.light();
+        self.rust()
+            .declared
+            .update(move |world, entity| light.apply(world, entity));
+    }
+}
+
+enum DeclaredLight {
+    Point(PointLight),
+    Directional(DirectionalLight),
+    Spot(SpotLight),
+}
+
+impl DeclaredLight {
+    /// Change the light of the entity in place, or replace it with a light
+    /// of the other kind
+    fn apply(self, world: &mut World, entity: Entity) {
+        let mut entity = world.entity_mut(entity);
+        let transform = entity.get::<Transform>().copied().unwrap_or_default();
+        let visibility = entity.get::<Visibility>().copied().unwrap_or_default();
+        match self {
+            Self::Point(light) => {
+                if let Some(mut current) = entity.get_mut::<PointLight>() {
+                    *current = light;
+                    return;
+                }
+                entity.remove::<(DirectionalLight, SpotLight)>();
+                entity.insert(PointLightBundle {
+                    point_light: light,
+                    transform,
+                    visibility,
+                    ..default()
+                });
+            }
+            Self::Directional(light) => {
+                if let Some(mut current) = entity.get_mut::<DirectionalLight>() {
+                    *current = light;
+                    return;
+                }
+                entity.remove::<(PointLight, SpotLight)>();
+                entity.insert(DirectionalLightBundle {
+                    directional_light: light,
+                    transform,
+                    visibility,
+                    ..default()
+                });
+            }
+            Self::Spot(light) => {
+                if let Some(mut current) = entity.get_mut::<SpotLight>() {
+                    *current = light;
+                    return;
+                }
+                entity.remove::<(PointLight, DirectionalLight)>();
+                entity.insert(SpotLightBundle {
+                    spot_light: light,
+                    transform,
+                    visibility,
+                    ..default()
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,322 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that mirrors the light of an entity
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_light_bridge")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qcolor.h");
+        /// An alias to the QColor type
+        type QColor = cxx_qt_lib::QColor;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // LightBridge based on the Rust struct LightBridgeRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(u64, entity)]
+        #[qproperty(QString, name)]
+        #[qproperty(bool, bound)]
+        #[qproperty(QString, kind)]
+        #[qproperty(QColor, color)]
+        #[qproperty(f64, intensity)]
+        #[qproperty(f64, range)]
+        #[qproperty(bool, shadows)]
+        #[qproperty(f64, inner_angle)]
+        #[qproperty(f64, outer_angle)]
+        type LightBridge = super::LightBridgeRust;
+    }
+
+    impl cxx_qt::Threading for LightBridge {}
+    impl cxx_qt::Constructor<()> for LightBridge {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QColor, QString};
+
+use crate::{
+    convert::{FromQt, IntoQt},
+    cxxqt_bevy_transform::find_entity,
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// The entity is found like that of a [crate::cxxqt_bevy_transform], and
+/// `bound` tells whether it exists and has a [PointLight], [SpotLight] or
+/// [DirectionalLight]. `kind` is `"point"`, `"spot"` or `"directional"`
+/// accordingly, and empty while unbound.
+///
+/// The other properties follow the light and setting them changes it.
+/// `intensity` is in lumens for point and spot lights and in lux for
+/// directional lights. `range` only matters to point and spot lights, and
+/// `innerAngle` and `outerAngle` only to spot lights, in degrees between
+/// their direction and where the cone starts and stops fading out:
+///
+/// ```qml
+/// LightBridge {
+///     id: sun
+///     name: "Sun"
+/// }
+/// Slider {
+///     from: 0; to: 100000
+///     value: sun.intensity
+///     onMoved: sun.intensity = value
+/// }
+/// Switch {
+///     checked: sun.shadows
+///     onToggled: sun.shadows = checked
+/// }
+/// ```
+pub struct LightBridgeRust {
+    entity: u64,
+    name: QString,
+    bound: bool,
+    kind: QString,
+    color: QColor,
+    intensity: f64,
+    range: f64,
+    shadows: bool,
+    inner_angle: f64,
+    outer_angle: f64,
+    target: Option<Entity>,
+    /// The light last read from the entity
+    seen: Option<LightValues>,
+    /// Set while the properties are updated from the world, so the change
+    /// handlers do not write them back
+    syncing: bool,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for LightBridgeRust {
+    fn default() -> Self {
+        Self {
+            entity: 0,
+            name: QString::default(),
+            bound: false,
+            kind: QString::default(),
+            color: Color::WHITE.into_qt(),
+            intensity: 0.0,
+            range: 0.0,
+            shadows: false,
+            inner_angle: 0.0,
+            outer_angle: 0.0,
+            target: None,
+            seen: None,
+            syncing: false,
+            update_listener: None,
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::LightBridge {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|light| light.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.as_mut()
+            .on_entity_changed(|light| light.retarget())
+            .release();
+        self.as_mut()
+            .on_name_changed(|light| light.retarget())
+            .release();
+        self.as_mut()
+            .on_color_changed(|light| light.write())
+            .release();
+        self.as_mut()
+            .on_intensity_changed(|light| light.write())
+            .release();
+        self.as_mut()
+            .on_range_changed(|light| light.write())
+            .release();
+        self.as_mut()
+            .on_shadows_changed(|light| light.write())
+            .release();
+        self.as_mut()
+            .on_inner_angle_changed(|light| light.write())
+            .release();
+        self.on_outer_angle_changed(|light| light.write()).release();
+    }
+}
+
+impl qobject::LightBridge {
+    /// Look the entity up again and read its light
+    fn retarget(mut self: Pin<&mut Self>) {
+        let mut rust = self.as_mut().rust_mut();
+        rust.target = None;
+        rust.seen = None;
+        self.refresh();
+    }
+
+    /// Change the light of the entity to the properties
+    fn write(self: Pin<&mut Self>) {
+        if self.rust().syncing {
+            return;
+        }
+        let Some(entity) = self.rust().target else {
+            return;
+        };
+
+        let outer_angle = (*self.outer_angle() as f32)
+            .to_radians()
+            .clamp(0.0, std::f32::consts::FRAC_PI_2);
+        let values = LightValues {
+            color: Color::from_qt(self.color()),
+            intensity: self.intensity().max(0.0) as f32,
+            range: self.range().max(0.0) as f32,
+            shadows: *self.shadows(),
+            inner_angle: (*self.inner_angle() as f32)
+                .to_radians()
+                .clamp(0.0, outer_angle),
+            outer_angle,
+        };
+        runtime::with_world(|world| {
+            if values.apply(world, entity) {
+                runtime::request_update();
+            }
+        });
+    }
+
+    /// Find the entity if needed and read its light if it has changed
+    fn refresh(mut self: Pin<&mut Self>) {
+        let name = self.name().to_string();
+        let bits = *self.entity();
+        let target = self.rust().target;
+        let update = runtime::with_world(|world| {
+            let entity = target
+                .filter(|entity| world.get_entity(*entity).is_some())
+                .or_else(|| find_entity(world, &name, bits))?;
+            let (kind, values) = LightValues::read(world, entity)?;
+            Some((entity, kind, values))
+        });
+
+        let Some(update) = update else {
+            // The app is busy, try again after the next update
+            return;
+        };
+        let Some((entity, kind, values)) = update else {
+            let mut rust = self.as_mut().rust_mut();
+            rust.target = None;
+            rust.seen = None;
+            self.as_mut().set_kind(QString::default());
+            self.set_bound(false);
+            return;
+        };
+
+        self.as_mut().rust_mut().target = Some(entity);
+        if self.rust().seen.as_ref() != Some(&values) {
+            self.as_mut().rust_mut().syncing = true;
+            self.as_mut().set_color(values.color.into_qt());
+            self.as_mut().set_intensity(values.intensity.into());
+            self.as_mut().set_range(values.range.into());
+            self.as_mut().set_shadows(values.shadows);
+            self.as_mut()
+                .set_inner_angle(values.inner_angle.to_degrees().into());
+            self.as_mut()
+                .set_outer_angle(values.outer_angle.to_degrees().into());
+            self.as_mut().rust_mut().syncing = false;
+            self.as_mut().rust_mut().seen = Some(values);
+        }
+        self.as_mut().set_kind(QString::from(kind));
+        self.set_bound(true);
+    }
+}
+
+/// The values of a light the bridge edits, whichever kind it is
+#[derive(Clone, Debug, PartialEq)]
+struct LightValues {
+    color: Color,
+    /// Lumens, or lux for directional lights
+    intensity: f32,
+    range: f32,
+    shadows: bool,
+    /// Radians
+    inner_angle: f32,
+    /// Radians
+    outer_angle: f32,
+}
+
+impl LightValues {
+    /// The kind and values of the light of the entity
+    fn read(world: &World, entity: Entity) -> Option<(&'static str, Self)> {
+        let entity = world.get_entity(entity)?;
+        if let Some(light) = entity.get::<PointLight>() {
+            return Some((
+                "point",
+                Self {
+                    color: light.color,
+                    intensity: light.intensity,
+                    range: light.range,
+                    shadows: light.shadows_enabled,
+                    inner_angle: 0.0,
+                    outer_angle: 0.0,
+                },
+            ));
+        }
+        if let Some(light) = entity.get::<SpotLight>() {
+            return Some((
+                "spot",
+                Self {
+                    color: light.color,
+                    intensity: light.intensity,
+                    range: light.range,
+                    shadows: light.shadows_enabled,
+                    inner_angle: light.inner_angle,
+                    outer_angle: light.outer_angle,
+                },
+            ));
+        }
+        let light = entity.get::<DirectionalLight>()?;
+        Some((
+            "directional",
+            Self {
+                color: light.color,
+                intensity: light.illuminance,
+                range: 0.0,
+                shadows: light.shadows_enabled,
+                inner_angle: 0.0,
+                outer_angle: 0.0,
+            },
+        ))
+    }
+
+    /// Change the light of the entity, returns whether anything changed
+    ///
+    /// Values which do not apply to the kind of light are ignored.
+    fn apply(self, world: &mut World, entity: Entity) -> bool {
+        match LightValues::read(world, entity) {
+            None => return false,
+            Some((_, current)) if current == self => return false,
+            Some(_) => {}
+        }
+        let mut entity = world.entity_mut(entity);
+        if let Some(mut light) = entity.get_mut::<PointLight>() {
+            light.color = self.color;
+            light.intensity = self.intensity;
+            light.range = self.range;
+            light.shadows_enabled = self.shadows;
+        } else if let Some(mut light) = entity.get_mut::<SpotLight>() {
+            light.color = self.color;
+            light.intensity = self.intensity;
+            light.range = self.range;
+            light.shadows_enabled = self.shadows;
+            light.inner_angle = self.inner_angle;
+            light.outer_angle = self.outer_angle;
+        } else if let Some(mut light) = entity.get_mut::<DirectionalLight>() {
+            light.color = self.color;
+            light.illuminance = self.intensity;
+            light.shadows_enabled = self.shadows;
+        }
+        true
+    }
+}
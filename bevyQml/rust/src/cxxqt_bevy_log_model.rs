@@ -0,0 +1,278 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the list model of the log output of Bevy
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_log_model")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!(<QtCore/QAbstractListModel>);
+
+        include!("cxx-qt-lib/qhash.h");
+        /// An alias to the QHash<int, QByteArray> type
+        type QHash_i32_QByteArray = cxx_qt_lib::QHash<cxx_qt_lib::QHashPair_i32_QByteArray>;
+        include!("cxx-qt-lib/qmodelindex.h");
+        /// An alias to the QModelIndex type
+        type QModelIndex = cxx_qt_lib::QModelIndex;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // LogModel based on the Rust struct LogModelRust.
+        #[qobject]
+        #[qml_element]
+        #[base = "QAbstractListModel"]
+        #[qproperty(QString, minimum_level)]
+        #[qproperty(QString, target)]
+        #[qproperty(i32, count)]
+        type LogModel = super::LogModelRust;
+    }
+
+    // QAbstractListModel implementation
+    unsafe extern "RustQt" {
+        #[inherit]
+        #[cxx_name = "beginInsertRows"]
+        unsafe fn begin_insert_rows(
+            self: Pin<&mut LogModel>,
+            parent: &QModelIndex,
+            first: i32,
+            last: i32,
+        );
+
+        #[inherit]
+        #[cxx_name = "endInsertRows"]
+        unsafe fn end_insert_rows(self: Pin<&mut LogModel>);
+
+        #[inherit]
+        #[cxx_name = "beginRemoveRows"]
+        unsafe fn begin_remove_rows(
+            self: Pin<&mut LogModel>,
+            parent: &QModelIndex,
+            first: i32,
+            last: i32,
+        );
+
+        #[inherit]
+        #[cxx_name = "endRemoveRows"]
+        unsafe fn end_remove_rows(self: Pin<&mut LogModel>);
+
+        #[inherit]
+        #[cxx_name = "beginResetModel"]
+        unsafe fn begin_reset_model(self: Pin<&mut LogModel>);
+
+        #[inherit]
+        #[cxx_name = "endResetModel"]
+        unsafe fn end_reset_model(self: Pin<&mut LogModel>);
+    }
+
+    unsafe extern "RustQt" {
+        #[qinvokable]
+        #[cxx_override]
+        fn data(self: &LogModel, index: &QModelIndex, role: i32) -> QVariant;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "roleNames"]
+        fn role_names(self: &LogModel) -> QHash_i32_QByteArray;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "rowCount"]
+        fn row_count(self: &LogModel, parent: &QModelIndex) -> i32;
+
+        /// Remove the records logged so far
+        #[qinvokable]
+        fn clear(self: Pin<&mut LogModel>);
+    }
+
+    impl cxx_qt::Threading for LogModel {}
+    impl cxx_qt::Constructor<()> for LogModel {}
+}
+
+use core::pin::Pin;
+
+use bevy::{prelude::*, utils::tracing::Level};
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QByteArray, QHash, QHashPair_i32_QByteArray, QModelIndex, QString, QVariant};
+
+use crate::{
+    log::{self, LogRecord, LOG_CAPACITY},
+    runtime::{self, UpdateListener},
+};
+
+/// Qt::UserRole, the first role available to custom models
+const USER_ROLE: i32 = 0x0100;
+
+const LEVEL_ROLE: i32 = USER_ROLE;
+const TARGET_ROLE: i32 = USER_ROLE + 1;
+const MESSAGE_ROLE: i32 = USER_ROLE + 2;
+const SEQUENCE_ROLE: i32 = USER_ROLE + 3;
+
+/// The Rust struct for the QObject
+///
+/// The model lists the latest records logged through Bevy, oldest first,
+/// with the roles `level` ("ERROR", "WARN", "INFO", "DEBUG" or "TRACE"),
+/// `target`, `message` and `sequence`. Only records at least as severe as
+/// `minimumLevel` and whose target starts with `target` are shown, empty
+/// strings show everything. New records are picked up after every update.
+#[derive(Default)]
+pub struct LogModelRust {
+    minimum_level: QString,
+    target: QString,
+    count: i32,
+    /// The parsed `minimum_level`, [None] to show every level
+    minimum: Option<Level>,
+    rows: Vec<LogRecord>,
+    /// The sequence number of the last record looked at
+    seen: u64,
+    /// Records up to this sequence number were cleared
+    cleared: u64,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::LogModel {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|model| model.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.as_mut()
+            .on_minimum_level_changed(|model| model.refilter())
+            .release();
+        self.as_mut()
+            .on_target_changed(|model| model.refilter())
+            .release();
+        self.refilter();
+    }
+}
+
+impl qobject::LogModel {
+    pub fn data(&self, index: &QModelIndex, role: i32) -> QVariant {
+        let Some(record) = usize::try_from(index.row())
+            .ok()
+            .and_then(|row| self.rust().rows.get(row))
+        else {
+            return QVariant::default();
+        };
+
+        match role {
+            LEVEL_ROLE => QVariant::from(&QString::from(record.level.as_str())),
+            TARGET_ROLE => QVariant::from(&QString::from(record.target.as_str())),
+            MESSAGE_ROLE => QVariant::from(&QString::from(record.message.as_str())),
+            SEQUENCE_ROLE => QVariant::from(&record.sequence),
+            _ => QVariant::default(),
+        }
+    }
+
+    pub fn role_names(&self) -> QHash<QHashPair_i32_QByteArray> {
+        let mut roles = QHash::<QHashPair_i32_QByteArray>::default();
+        roles.insert(LEVEL_ROLE, QByteArray::from("level"));
+        roles.insert(TARGET_ROLE, QByteArray::from("target"));
+        roles.insert(MESSAGE_ROLE, QByteArray::from("message"));
+        roles.insert(SEQUENCE_ROLE, QByteArray::from("sequence"));
+        roles
+    }
+
+    pub fn row_count(&self, _parent: &QModelIndex) -> i32 {
+        self.rust().rows.len() as i32
+    }
+
+    pub fn clear(mut self: Pin<&mut Self>) {
+        let seen = self.rust().seen;
+        self.as_mut().rust_mut().cleared = seen;
+        self.refilter();
+    }
+
+    /// Start over with the kept records which pass the filters
+    fn refilter(mut self: Pin<&mut Self>) {
+        let level = self.minimum_level().to_string();
+        let minimum = level.parse::<Level>().ok();
+        if !level.is_empty() && minimum.is_none() {
+            warn!("LogModel does not know the level {level:?}");
+        }
+        self.as_mut().rust_mut().minimum = minimum;
+
+        let cleared = self.rust().cleared;
+        let records = log::records_since(cleared);
+        let seen = records.last().map_or(cleared, |record| record.sequence);
+        let rows: Vec<LogRecord> = records
+            .into_iter()
+            .filter(|record| self.accepts(record))
+            .collect();
+
+        unsafe {
+            self.as_mut().begin_reset_model();
+        }
+        let mut rust = self.as_mut().rust_mut();
+        rust.rows = rows;
+        rust.seen = seen;
+        unsafe {
+            self.as_mut().end_reset_model();
+        }
+        self.update_count();
+    }
+
+    /// Append the records logged since the last refresh, dropping the oldest
+    /// rows beyond the capacity
+    fn refresh(mut self: Pin<&mut Self>) {
+        let records = log::records_since(self.rust().seen);
+        let Some(last) = records.last() else {
+            return;
+        };
+        self.as_mut().rust_mut().seen = last.sequence;
+
+        let added: Vec<LogRecord> = records
+            .into_iter()
+            .filter(|record| self.accepts(record))
+            .collect();
+        if added.is_empty() {
+            return;
+        }
+
+        let first = self.rust().rows.len() as i32;
+        let last = first + added.len() as i32 - 1;
+        unsafe {
+            self.as_mut()
+                .begin_insert_rows(&QModelIndex::default(), first, last);
+        }
+        self.as_mut().rust_mut().rows.extend(added);
+        unsafe {
+            self.as_mut().end_insert_rows();
+        }
+
+        let excess = self.rust().rows.len().saturating_sub(LOG_CAPACITY);
+        if excess > 0 {
+            unsafe {
+                self.as_mut()
+                    .begin_remove_rows(&QModelIndex::default(), 0, excess as i32 - 1);
+            }
+            self.as_mut().rust_mut().rows.drain(..excess);
+            unsafe {
+                self.as_mut().end_remove_rows();
+            }
+        }
+        self.update_count();
+    }
+
+    fn accepts(&self, record: &LogRecord) -> bool {
+        // More verbose levels compare as greater
+        self.rust()
+            .minimum
+            .map_or(true, |minimum| record.level <= minimum)
+            && record.target.starts_with(&self.target().to_string())
+    }
+
+    fn update_count(mut self: Pin<&mut Self>) {
+        let count = self.rust().rows.len() as i32;
+        if *self.count() != count {
+            self.as_mut().set_count(count);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that loads gameplay systems from a
+/// dynamic library
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_logic")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BevyLogic based on the Rust struct BevyLogicRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(QString, path)]
+        #[qproperty(bool, auto_reload)]
+        #[qproperty(QString, status)]
+        #[qproperty(QString, error)]
+        #[qproperty(i32, generation)]
+        type BevyLogic = super::BevyLogicRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// Load the library at `path`, in place of the one loaded before
+        #[qinvokable]
+        fn reload(self: Pin<&mut BevyLogic>) -> bool;
+
+        /// Remove the systems of the library and unload it
+        #[qinvokable]
+        fn unload(self: Pin<&mut BevyLogic>);
+
+        /// Emitted after every attempt to load the library
+        #[qsignal]
+        fn reloaded(self: Pin<&mut BevyLogic>, ok: bool, message: QString);
+    }
+
+    impl cxx_qt::Threading for BevyLogic {}
+    impl cxx_qt::Constructor<()> for BevyLogic {}
+}
+
+use core::pin::Pin;
+use std::{path::PathBuf, time::SystemTime};
+
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::QString;
+
+use crate::{
+    logic::{self, QmlLogicLibrary},
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// Loads gameplay systems from the dynamic library at `path`, see
+/// [crate::logic] for how to build one. `status` is `"unloaded"`, `"loaded"`
+/// or `"failed"`, in which case `error` tells why. `generation` counts the
+/// libraries loaded. With `autoReload` the library is loaded again once it
+/// was rebuilt and stayed unchanged for an update, also the first time.
+///
+/// ```qml
+/// BevyLogic {
+///     id: logic
+///     path: "target/debug/libgameplay.so"
+///     onReloaded: (ok, message) => console.log(message)
+/// }
+/// Button {
+///     text: qsTr("Reload logic")
+///     onClicked: logic.reload()
+/// }
+/// ```
+pub struct BevyLogicRust {
+    path: QString,
+    auto_reload: bool,
+    status: QString,
+    error: QString,
+    generation: i32,
+    /// When the library at the path was last loaded or tried to be
+    seen_modified: Option<SystemTime>,
+    /// The time the library was modified as of the previous update, to
+    /// wait for builds to finish writing it
+    pending_modified: Option<SystemTime>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for BevyLogicRust {
+    fn default() -> Self {
+        Self {
+            path: QString::default(),
+            auto_reload: false,
+            status: QString::from("unloaded"),
+            error: QString::default(),
+            generation: 0,
+            seen_modified: None,
+            pending_modified: None,
+            update_listener: None,
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::BevyLogic {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|logic| logic.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+    }
+}
+
+impl qobject::BevyLogic {
+    pub fn reload(mut self: Pin<&mut Self>) -> bool {
+        let path = PathBuf::from(self.path().to_string());
+        if path.as_os_str().is_empty() {
+            self.finish(Err(String::from("BevyLogic has no path to load")));
+            return false;
+        }
+        self.as_mut().rust_mut().seen_modified = logic::modified(&path);
+
+        let result = runtime::with_world(|world| {
+            if !world.contains_resource::<QmlLogicLibrary>() {
+                return Err(String::from("the app has no QmlLogicPlugin"));
+            }
+            logic::load_logic(world, &path)?;
+            Ok(world.resource::<QmlLogicLibrary>().generation())
+        })
+        .unwrap_or_else(|| Err(String::from("there is no Bevy app running")));
+        runtime::request_update();
+
+        let ok = result.is_ok();
+        if let Ok(generation) = result {
+            let generation = i32::try_from(generation).unwrap_or(i32::MAX);
+            self.as_mut().set_generation(generation);
+        }
+        self.finish(result.map(|_| format!("Loaded {}", path.display())));
+        ok
+    }
+
+    pub fn unload(mut self: Pin<&mut Self>) {
+        runtime::with_world(logic::unload_logic);
+        runtime::request_update();
+        self.as_mut().set_error(QString::default());
+        self.set_status(QString::from("unloaded"));
+    }
+
+    /// Report the outcome of loading the library
+    fn finish(mut self: Pin<&mut Self>, result: Result<String, String>) {
+        let (ok, status, error, message) = match result {
+            Ok(message) => (true, "loaded", String::new(), message),
+            Err(error) => (false, "failed", error.clone(), error),
+        };
+        self.as_mut().set_error(QString::from(error.as_str()));
+        self.as_mut().set_status(QString::from(status));
+        self.reloaded(ok, QString::from(message.as_str()));
+    }
+
+    /// Load the library again once it was rebuilt
+    fn refresh(mut self: Pin<&mut Self>) {
+        if !*self.auto_reload() {
+            return;
+        }
+        let path = PathBuf::from(self.path().to_string());
+        let modified = logic::modified(&path);
+        let pending = std::mem::replace(&mut self.as_mut().rust_mut().pending_modified, modified);
+        if modified.is_some() && modified != self.rust().seen_modified && modified == pending {
+            self.reload();
+        }
+    }
+}
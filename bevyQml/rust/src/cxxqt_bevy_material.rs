@@ -0,0 +1,368 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that mirrors the StandardMaterial of
+/// an entity
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_material")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qcolor.h");
+        /// An alias to the QColor type
+        type QColor = cxx_qt_lib::QColor;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qurl.h");
+        /// An alias to the QUrl type
+        type QUrl = cxx_qt_lib::QUrl;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // MaterialBridge based on the Rust struct MaterialBridgeRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(u64, entity)]
+        #[qproperty(QString, name)]
+        #[qproperty(bool, bound)]
+        #[qproperty(QColor, base_color)]
+        #[qproperty(f64, metallic)]
+        #[qproperty(f64, roughness)]
+        #[qproperty(QColor, emissive)]
+        #[qproperty(f64, emissive_intensity)]
+        #[qproperty(QUrl, base_color_texture)]
+        #[qproperty(QUrl, metallic_roughness_texture)]
+        #[qproperty(QUrl, normal_map_texture)]
+        #[qproperty(QUrl, emissive_texture)]
+        #[qproperty(QUrl, occlusion_texture)]
+        type MaterialBridge = super::MaterialBridgeRust;
+    }
+
+    impl cxx_qt::Threading for MaterialBridge {}
+    impl cxx_qt::Constructor<()> for MaterialBridge {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QColor, QString, QUrl};
+
+use crate::{
+    asset,
+    convert::{FromQt, IntoQt},
+    cxxqt_bevy_transform::find_entity,
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// The entity is found like that of a [crate::cxxqt_bevy_transform], and
+/// `bound` tells whether it exists and has a [StandardMaterial].
+///
+/// The properties follow the material and setting them changes it, which
+/// changes every entity sharing the material asset. `emissive` is the color
+/// of the emitted light and `emissiveIntensity` scales it beyond what a color
+/// holds. The texture slots hold the URL each texture was loaded from, or are
+/// empty without a texture or for textures made in code. Setting a URL loads
+/// the image as by [asset::resolve_url], and clearing it removes the texture:
+///
+/// ```qml
+/// MaterialBridge {
+///     id: hull
+///     name: "Hull"
+/// }
+/// ColorDialog {
+///     selectedColor: hull.baseColor
+///     onAccepted: hull.baseColor = selectedColor
+/// }
+/// Slider {
+///     value: hull.roughness
+///     onMoved: hull.roughness = value
+/// }
+/// ```
+pub struct MaterialBridgeRust {
+    entity: u64,
+    name: QString,
+    bound: bool,
+    base_color: QColor,
+    metallic: f64,
+    roughness: f64,
+    emissive: QColor,
+    emissive_intensity: f64,
+    base_color_texture: QUrl,
+    metallic_roughness_texture: QUrl,
+    normal_map_texture: QUrl,
+    emissive_texture: QUrl,
+    occlusion_texture: QUrl,
+    target: Option<Entity>,
+    /// The values last read from the material
+    seen: Option<MaterialValues>,
+    /// Set while the properties are updated from the world, so the change
+    /// handlers do not write them back
+    syncing: bool,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for MaterialBridgeRust {
+    fn default() -> Self {
+        let defaults = StandardMaterial::default();
+        Self {
+            entity: 0,
+            name: QString::default(),
+            bound: false,
+            base_color: defaults.base_color.into_qt(),
+            metallic: defaults.metallic.into(),
+            roughness: defaults.perceptual_roughness.into(),
+            emissive: Color::BLACK.into_qt(),
+            emissive_intensity: 1.0,
+            base_color_texture: QUrl::default(),
+            metallic_roughness_texture: QUrl::default(),
+            normal_map_texture: QUrl::default(),
+            emissive_texture: QUrl::default(),
+            occlusion_texture: QUrl::default(),
+            target: None,
+            seen: None,
+            syncing: false,
+            update_listener: None,
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::MaterialBridge {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|material| material.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.as_mut()
+            .on_entity_changed(|material| material.retarget())
+            .release();
+        self.as_mut()
+            .on_name_changed(|material| material.retarget())
+            .release();
+        self.as_mut()
+            .on_base_color_changed(|material| material.write())
+            .release();
+        self.as_mut()
+            .on_metallic_changed(|material| material.write())
+            .release();
+        self.as_mut()
+            .on_roughness_changed(|material| material.write())
+            .release();
+        self.as_mut()
+            .on_emissive_changed(|material| material.write())
+            .release();
+        self.as_mut()
+            .on_emissive_intensity_changed(|material| material.write())
+            .release();
+        self.as_mut()
+            .on_base_color_texture_changed(|material| material.write())
+            .release();
+        self.as_mut()
+            .on_metallic_roughness_texture_changed(|material| material.write())
+            .release();
+        self.as_mut()
+            .on_normal_map_texture_changed(|material| material.write())
+            .release();
+        self.as_mut()
+            .on_emissive_texture_changed(|material| material.write())
+            .release();
+        self.on_occlusion_texture_changed(|material| material.write())
+            .release();
+    }
+}
+
+impl qobject::MaterialBridge {
+    /// Look the entity up again and read its material
+    fn retarget(mut self: Pin<&mut Self>) {
+        let mut rust = self.as_mut().rust_mut();
+        rust.target = None;
+        rust.seen = None;
+        self.refresh();
+    }
+
+    /// Change the material of the entity to the properties
+    fn write(self: Pin<&mut Self>) {
+        if self.rust().syncing {
+            return;
+        }
+        let Some(entity) = self.rust().target else {
+            return;
+        };
+
+        let emissive = LinearRgba::from(Color::from_qt(self.emissive()))
+            * (*self.emissive_intensity()).max(0.0) as f32;
+        let values = MaterialValues {
+            base_color: Color::from_qt(self.base_color()),
+            metallic: (*self.metallic()).clamp(0.0, 1.0) as f32,
+            roughness: (*self.roughness()).clamp(0.0, 1.0) as f32,
+            emissive: emissive.with_alpha(1.0),
+            textures: [
+                self.base_color_texture().to_string(),
+                self.metallic_roughness_texture().to_string(),
+                self.normal_map_texture().to_string(),
+                self.emissive_texture().to_string(),
+                self.occlusion_texture().to_string(),
+            ],
+        };
+        runtime::with_world(|world| {
+            if values.apply(world, entity) {
+                runtime::request_update();
+            }
+        });
+    }
+
+    /// Find the entity if needed and read its material if it has changed
+    fn refresh(mut self: Pin<&mut Self>) {
+        let name = self.name().to_string();
+        let bits = *self.entity();
+        let target = self.rust().target;
+        let update = runtime::with_world(|world| {
+            let entity = target
+                .filter(|entity| world.get_entity(*entity).is_some())
+                .or_else(|| find_entity(world, &name, bits))?;
+            Some((entity, MaterialValues::read(world, entity)?))
+        });
+
+        let Some(update) = update else {
+            // The app is busy, try again after the next update
+            return;
+        };
+        let Some((entity, values)) = update else {
+            let mut rust = self.as_mut().rust_mut();
+            rust.target = None;
+            rust.seen = None;
+            self.set_bound(false);
+            return;
+        };
+
+        self.as_mut().rust_mut().target = Some(entity);
+        if self.rust().seen.as_ref() != Some(&values) {
+            // Split the emissive color into a color and how far it goes
+            // beyond one
+            let emissive = values.emissive;
+            let intensity = emissive.red.max(emissive.green).max(emissive.blue).max(1.0);
+            let [base_color, metallic_roughness, normal_map, emissive_texture, occlusion] = values
+                .textures
+                .each_ref()
+                .map(|url| QUrl::from(url.as_str()));
+
+            self.as_mut().rust_mut().syncing = true;
+            self.as_mut().set_base_color(values.base_color.into_qt());
+            self.as_mut().set_metallic(values.metallic.into());
+            self.as_mut().set_roughness(values.roughness.into());
+            self.as_mut()
+                .set_emissive(Color::from((emissive / intensity).with_alpha(1.0)).into_qt());
+            self.as_mut().set_emissive_intensity(intensity.into());
+            self.as_mut().set_base_color_texture(base_color);
+            self.as_mut()
+                .set_metallic_roughness_texture(metallic_roughness);
+            self.as_mut().set_normal_map_texture(normal_map);
+            self.as_mut().set_emissive_texture(emissive_texture);
+            self.as_mut().set_occlusion_texture(occlusion);
+            self.as_mut().rust_mut().syncing = false;
+            self.as_mut().rust_mut().seen = Some(values);
+        }
+        self.set_bound(true);
+    }
+}
+
+/// The values of a [StandardMaterial] the bridge edits
+#[derive(Clone, Debug, PartialEq)]
+struct MaterialValues {
+    base_color: Color,
+    metallic: f32,
+    roughness: f32,
+    emissive: LinearRgba,
+    /// The URLs of the base color, metallic roughness, normal map, emissive
+    /// and occlusion textures
+    textures: [String; 5],
+}
+
+impl MaterialValues {
+    fn read(world: &World, entity: Entity) -> Option<Self> {
+        let handle = world.get::<Handle<StandardMaterial>>(entity)?;
+        let material = world
+            .get_resource::<Assets<StandardMaterial>>()?
+            .get(handle)?;
+        Some(Self {
+            base_color: material.base_color,
+            metallic: material.metallic,
+            roughness: material.perceptual_roughness,
+            emissive: material.emissive,
+            textures: texture_slots(material).map(|slot| texture_url(slot.as_ref())),
+        })
+    }
+
+    /// Change the material of the entity, returns whether anything changed
+    fn apply(self, world: &mut World, entity: Entity) -> bool {
+        let Some(handle) = world.get::<Handle<StandardMaterial>>(entity).cloned() else {
+            return false;
+        };
+        if Self::read(world, entity).as_ref() == Some(&self) {
+            return false;
+        }
+        let Some(asset_server) = world.get_resource::<AssetServer>().cloned() else {
+            return false;
+        };
+        let Some(mut materials) = world.get_resource_mut::<Assets<StandardMaterial>>() else {
+            return false;
+        };
+        let Some(material) = materials.get_mut(&handle) else {
+            return false;
+        };
+
+        material.base_color = self.base_color;
+        material.metallic = self.metallic;
+        material.perceptual_roughness = self.roughness;
+        material.emissive = self.emissive;
+        let [base_color, metallic_roughness, normal_map, emissive, occlusion] = self.textures;
+        set_texture(&asset_server, &mut material.base_color_texture, base_color);
+        set_texture(
+            &asset_server,
+            &mut material.metallic_roughness_texture,
+            metallic_roughness,
+        );
+        set_texture(&asset_server, &mut material.normal_map_texture, normal_map);
+        set_texture(&asset_server, &mut material.emissive_texture, emissive);
+        set_texture(&asset_server, &mut material.occlusion_texture, occlusion);
+        true
+    }
+}
+
+/// The texture slots of a material, in the order of [MaterialValues::textures]
+fn texture_slots(material: &StandardMaterial) -> [Option<Handle<Image>>; 5] {
+    [
+        material.base_color_texture.clone(),
+        material.metallic_roughness_texture.clone(),
+        material.normal_map_texture.clone(),
+        material.emissive_texture.clone(),
+        material.occlusion_texture.clone(),
+    ]
+}
+
+/// The URL a texture was loaded from, empty without one
+fn texture_url(texture: Option<&Handle<Image>>) -> String {
+    texture
+        .and_then(Handle::path)
+        .map(ToString::to_string)
+        .unwrap_or_default()
+}
+
+/// Load the texture at the URL into the slot unless it already holds it
+fn set_texture(asset_server: &AssetServer, slot: &mut Option<Handle<Image>>, url: String) {
+    if texture_url(slot.as_ref()) == url {
+        return;
+    }
+    if url.is_empty() {
+        *slot = None;
+        return;
+    }
+    match asset::resolve_url(&url) {
+        Ok(path) => *slot = Some(asset_server.load(path)),
+        Err(error) => warn!("MaterialBridge cannot load the texture {url:?}: {error}"),
+    }
+}
@@ -0,0 +1,124 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML singleton that shows the memory of the
+/// app
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_memory_diagnostics")]
+pub mod qobject {
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QML singleton with the name
+        // MemoryDiagnosticsBridge based on the Rust struct
+        // MemoryDiagnosticsBridgeRust.
+        #[qobject]
+        #[qml_element]
+        #[qml_singleton]
+        #[qproperty(u64, entity_count)]
+        #[qproperty(u64, archetype_count)]
+        #[qproperty(u64, component_bytes)]
+        #[qproperty(u64, mesh_count)]
+        #[qproperty(u64, mesh_bytes)]
+        #[qproperty(u64, image_count)]
+        #[qproperty(u64, image_bytes)]
+        #[qproperty(u64, material_count)]
+        #[qproperty(u64, gpu_buffer_bytes)]
+        #[qproperty(u64, gpu_texture_bytes)]
+        type MemoryDiagnosticsBridge = super::MemoryDiagnosticsBridgeRust;
+    }
+
+    impl cxx_qt::Threading for MemoryDiagnosticsBridge {}
+    impl cxx_qt::Constructor<()> for MemoryDiagnosticsBridge {}
+}
+
+use core::pin::Pin;
+
+use cxx_qt::{CxxQtType, Threading};
+
+use crate::{
+    memory::MemoryDiagnostics,
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// The properties follow the [MemoryDiagnostics] of the world, which are
+/// measured about once a second, and only notify when they change. The
+/// `Bytes` properties are estimates, see [crate::memory], which are meant
+/// to be watched for growth over the hours of a long-running app:
+///
+/// ```qml
+/// Connections {
+///     target: MemoryDiagnosticsBridge
+///     function onGpuTextureBytesChanged() {
+///         if (MemoryDiagnosticsBridge.gpuTextureBytes > 512 * 1024 * 1024)
+///             console.warn("textures keep piling up")
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct MemoryDiagnosticsBridgeRust {
+    entity_count: u64,
+    archetype_count: u64,
+    component_bytes: u64,
+    mesh_count: u64,
+    mesh_bytes: u64,
+    image_count: u64,
+    image_bytes: u64,
+    material_count: u64,
+    gpu_buffer_bytes: u64,
+    gpu_texture_bytes: u64,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::MemoryDiagnosticsBridge {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|memory| memory.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+        self.refresh();
+    }
+}
+
+impl qobject::MemoryDiagnosticsBridge {
+    fn refresh(mut self: Pin<&mut Self>) {
+        let Some(memory) =
+            runtime::with_world(|world| world.get_resource::<MemoryDiagnostics>().cloned())
+                .flatten()
+        else {
+            return;
+        };
+
+        if *self.entity_count() != memory.entities {
+            self.as_mut().set_entity_count(memory.entities);
+        }
+        if *self.archetype_count() != memory.archetypes {
+            self.as_mut().set_archetype_count(memory.archetypes);
+        }
+        if *self.component_bytes() != memory.component_bytes {
+            self.as_mut().set_component_bytes(memory.component_bytes);
+        }
+        if *self.mesh_count() != memory.meshes {
+            self.as_mut().set_mesh_count(memory.meshes);
+        }
+        if *self.mesh_bytes() != memory.mesh_bytes {
+            self.as_mut().set_mesh_bytes(memory.mesh_bytes);
+        }
+        if *self.image_count() != memory.images {
+            self.as_mut().set_image_count(memory.images);
+        }
+        if *self.image_bytes() != memory.image_bytes {
+            self.as_mut().set_image_bytes(memory.image_bytes);
+        }
+        if *self.material_count() != memory.materials {
+            self.as_mut().set_material_count(memory.materials);
+        }
+        if *self.gpu_buffer_bytes() != memory.gpu_buffer_bytes {
+            self.as_mut().set_gpu_buffer_bytes(memory.gpu_buffer_bytes);
+        }
+        if *self.gpu_texture_bytes() != memory.gpu_texture_bytes {
+            self.as_mut()
+                .set_gpu_texture_bytes(memory.gpu_texture_bytes);
+        }
+    }
+}
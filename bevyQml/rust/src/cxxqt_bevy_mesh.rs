@@ -0,0 +1,288 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML element that declares a mesh entity
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_mesh")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qcolor.h");
+        /// An alias to the QColor type
+        type QColor = cxx_qt_lib::QColor;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qlist.h");
+        /// An alias to the QVariantList type
+        type QList_QVariant = cxx_qt_lib::QList<cxx_qt_lib::QVariant>;
+        include!("cxx-qt-lib/qvector3d.h");
+        /// An alias to the QVector3D type
+        type QVector3D = cxx_qt_lib::QVector3D;
+        include!("bevyqml/convert.h");
+        /// An alias to the QQuaternion type
+        type QQuaternion = crate::convert::QQuaternion;
+    }
+
+    /// The shapes a BevyMesh can have, all of them one unit across
+    #[qenum(BevyMesh)]
+    enum Shape {
+        Cube,
+        Sphere,
+        /// A square in the XZ plane, facing up
+        Plane,
+        Cylinder,
+        Capsule,
+        Torus,
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BevyMesh based on the Rust struct BevyMeshRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(u64, entity)]
+        #[qproperty(QString, name)]
+        #[qproperty(u64, parent_entity)]
+        #[qproperty(QVector3D, position)]
+        #[qproperty(QQuaternion, rotation)]
+        #[qproperty(QVector3D, scale)]
+        #[qproperty(bool, visible)]
+        #[qproperty(QList_QVariant, components)]
+        #[qproperty(Shape, shape)]
+        #[qproperty(QString, source)]
+        #[qproperty(QColor, color)]
+        #[qproperty(f64, metallic)]
+        #[qproperty(f64, roughness)]
+        type BevyMesh = super::BevyMeshRust;
+    }
+
+    impl cxx_qt::Threading for BevyMesh {}
+    impl cxx_qt::Constructor<()> for BevyMesh {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QColor, QList, QString, QVariant, QVector3D};
+
+use crate::{
+    asset,
+    convert::{FromQt, IntoQt, QQuaternion},
+    declarative::{self, DeclaredEntity, DeclaredProperties},
+};
+
+/// The Rust struct for the QObject
+///
+/// The element spawns an entity with a mesh and a [StandardMaterial] of
+/// `color`, `metallic` and `roughness`. The mesh is a `shape` sized by
+/// `scale`, or the mesh asset at the `source` URL, such as
+/// `"models/ship.gltf#Mesh0/Primitive0"`, when that is set. See
+/// [crate::declarative] for the other properties.
+pub struct BevyMeshRust {
+    entity: u64,
+    name: QString,
+    parent_entity: u64,
+    position: QVector3D,
+    rotation: QQuaternion,
+    scale: QVector3D,
+    visible: bool,
+    components: QList<QVariant>,
+    shape: qobject::Shape,
+    source: QString,
+    color: QColor,
+    metallic: f64,
+    roughness: f64,
+    declared: DeclaredEntity,
+}
+
+impl Default for BevyMeshRust {
+    fn default() -> Self {
+        Self {
+            entity: 0,
+            name: QString::default(),
+            parent_entity: 0,
+            position: Vec3::ZERO.into_qt(),
+            rotation: QQuaternion::default(),
+            scale: Vec3::ONE.into_qt(),
+            visible: true,
+            components: QList::default(),
+            shape: qobject::Shape::Cube,
+            source: QString::default(),
+            color: Color::WHITE.into_qt(),
+            metallic: 0.0,
+            roughness: 0.5,
+            declared: DeclaredEntity::default(),
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::BevyMesh {
+    fn initialize(mut self: Pin<&mut Self>) {
+        // Spawn once QML has set the initial properties
+        let _ = self.qt_thread().queue(|element| element.spawn());
+
+        self.as_mut()
+            .on_name_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_parent_entity_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_position_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_rotation_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_scale_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_visible_changed(|element| element.write_properties())
+            .release();
+        self.as_mut()
+            .on_components_changed(|element| element.write_components())
+            .release();
+        self.as_mut()
+            .on_shape_changed(|element| element.write_mesh())
+            .release();
+        self.as_mut()
+            .on_source_changed(|element| element.write_mesh())
+            .release();
+        self.as_mut()
+            .on_color_changed(|element| element.write_material())
+            .release();
+        self.as_mut()
+            .on_metallic_changed(|element| element.write_material())
+            .release();
+        self.on_roughness_changed(|element| element.write_material())
+            .release();
+    }
+}
+
+impl qobject::BevyMesh {
+    fn spawn(mut self: Pin<&mut Self>) {
+        let properties = self.properties();
+        let components = declarative::component_list(self.components());
+        let mesh = self.mesh();
+        let material = self.material();
+        let Some(entity) = self
+            .as_mut()
+            .rust_mut()
+            .declared
+            .spawn(SpatialBundle::default())
+        else {
+            warn!("BevyMesh cannot spawn an entity without a running Bevy app");
+            return;
+        };
+        self.rust().declared.update(move |world, entity| {
+            properties.apply(world, entity);
+            mesh.apply(world, entity);
+            apply_material(world, entity, material);
+            declarative::apply_components(world, entity, components);
+        });
+        self.set_entity(entity.to_bits());
+    }
+
+    fn properties(&self) -> DeclaredProperties {
+        DeclaredProperties::from_qml(
+            self.name(),
+            *self.parent_entity(),
+            self.position(),
+            self.rotation(),
+            self.scale(),
+            *self.visible(),
+        )
+    }
+
+    fn mesh(&self) -> DeclaredMesh {
+        let source = self.source().to_string();
+        if !source.is_empty() {
+            return DeclaredMesh::Asset(source);
+        }
+        let shape = *self.shape();
+        DeclaredMesh::Shape(if shape == qobject::Shape::Sphere {
+            Sphere::default().into()
+        } else if shape == qobject::Shape::Plane {
+            Plane3d::default().mesh().size(1.0, 1.0).into()
+        } else if shape == qobject::Shape::Cylinder {
+            Cylinder::default().into()
+        } else if shape == qobject::Shape::Capsule {
+            Capsule3d::new(0.25, 0.5).into()
+        } else if shape == qobject::Shape::Torus {
+            Torus::new(0.3, 0.5).into()
+        } else {
+            Cuboid::default().into()
+        })
+    }
+
+    fn material(&self) -> StandardMaterial {
+        StandardMaterial {
+            base_color: Color::from_qt(self.color()),
+            metallic: *self.metallic() as f32,
+            perceptual_roughness: *self.roughness() as f32,
+            ..default()
+        }
+    }
+
+    fn write_properties(self: Pin<&mut Self>) {
+        let properties = self.properties();
+        self.rust()
+            .declared
+            .update(move |world, entity| properties.apply(world, entity));
+    }
+
+    fn write_components(self: Pin<&mut Self>) {
+        let components = declarative::component_list(self.components());
+        self.rust().declared.update(move |world, entity| {
+            declarative::apply_components(world, entity, components)
+        });
+    }
+
+    fn write_mesh(self: Pin<&mut Self>) {
+        let mesh = self.mesh();
+        self.rust()
+            .declared
+            .update(move |world, entity| mesh.apply(world, entity));
+    }
+
+    fn write_material(self: Pin<&mut Self>) {
+        let material = self.material();
+        self.rust()
+            .declared
+            .update(move |world, entity| apply_material(world, entity, material));
+    }
+}
+
+/// Where the mesh of an element comes from
+enum DeclaredMesh {
+    Shape(Mesh),
+    Asset(String),
+}
+
+impl DeclaredMesh {
+    fn apply(self, world: &mut World, entity: Entity) {
+        let handle = match self {
+            Self::Shape(mesh) => world.resource_mut::<Assets<Mesh>>().add(mesh),
+            Self::Asset(url) => match asset::resolve_url(&url) {
+                Ok(path) => world.resource::<AssetServer>().load(path),
+                Err(error) => {
+                    warn!("BevyMesh cannot load {url:?}: {error}");
+                    return;
+                }
+            },
+        };
+        world.entity_mut(entity).insert(handle);
+    }
+}
+
+/// Change the material of the entity in place, or give it one
+fn apply_material(world: &mut World, entity: Entity, material: StandardMaterial) {
+    let handle = world.get::<Handle<StandardMaterial>>(entity).cloned();
+    let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+    if let Some(current) = handle.as_ref().and_then(|handle| materials.get_mut(handle)) {
+        *current = material;
+        return;
+    }
+    let handle = materials.add(material);
+    world.entity_mut(entity).insert(handle);
+}
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML element that declares the camera of a
+/// mini view
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_mini_view_camera")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvector3d.h");
+        /// An alias to the QVector3D type
+        type QVector3D = cxx_qt_lib::QVector3D;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BevyMiniViewCamera based on the Rust struct BevyMiniViewCameraRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(u64, entity)]
+        #[qproperty(QString, name)]
+        #[qproperty(QVector3D, center)]
+        #[qproperty(u64, follow_entity)]
+        #[qproperty(f64, extent)]
+        #[qproperty(f64, altitude)]
+        #[qproperty(i32, image_width)]
+        #[qproperty(i32, image_height)]
+        type BevyMiniViewCamera = super::BevyMiniViewCameraRust;
+
+        /// The point of the world shown at a position of the view, from zero
+        /// to one of its width and height, or NaN
+        #[qinvokable]
+        fn map_to_world(self: &BevyMiniViewCamera, x: f64, y: f64) -> QVector3D;
+    }
+
+    impl cxx_qt::Threading for BevyMiniViewCamera {}
+    impl cxx_qt::Constructor<()> for BevyMiniViewCamera {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QString, QVector3D};
+
+use crate::{
+    convert::{FromQt, IntoQt},
+    declarative::{self, DeclaredEntity},
+    mini_view::{self, QmlMiniView},
+    render::QmlImageTarget,
+    runtime,
+};
+
+/// The Rust struct for the QObject
+///
+/// The element spawns an orthographic camera looking down onto `center`, or
+/// onto the entity whose bits are `followEntity`, from `altitude` above. The
+/// width of the view covers `extent` of the world. It renders into an
+/// `imageWidth` by `imageHeight` image published as `image://bevy/<name>`,
+/// see [crate::mini_view]. `BevyMiniView` shows it, see
+/// `qml/BevyMiniView.qml`.
+pub struct BevyMiniViewCameraRust {
+    entity: u64,
+    name: QString,
+    center: QVector3D,
+    follow_entity: u64,
+    extent: f64,
+    altitude: f64,
+    image_width: i32,
+    image_height: i32,
+    declared: DeclaredEntity,
+}
+
+impl Default for BevyMiniViewCameraRust {
+    fn default() -> Self {
+        let view = QmlMiniView::default();
+        Self {
+            entity: 0,
+            name: QString::from("miniview"),
+            center: view.center.into_qt(),
+            follow_entity: 0,
+            extent: view.extent.into(),
+            altitude: view.altitude.into(),
+            image_width: 256,
+            image_height: 256,
+            declared: DeclaredEntity::default(),
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::BevyMiniViewCamera {
+    fn initialize(mut self: Pin<&mut Self>) {
+        // Spawn once QML has set the initial properties
+        let _ = self.qt_thread().queue(|element| element.spawn());
+
+        self.as_mut()
+            .on_name_changed(|element| element.write_target())
+            .release();
+        self.as_mut()
+            .on_image_width_changed(|element| element.write_target())
+            .release();
+        self.as_mut()
+            .on_image_height_changed(|element| element.write_target())
+            .release();
+        self.as_mut()
+            .on_center_changed(|element| element.write_view())
+            .release();
+        self.as_mut()
+            .on_follow_entity_changed(|element| element.write_view())
+            .release();
+        self.as_mut()
+            .on_extent_changed(|element| element.write_view())
+            .release();
+        self.on_altitude_changed(|element| element.write_view())
+            .release();
+    }
+}
+
+impl qobject::BevyMiniViewCamera {
+    pub fn map_to_world(&self, x: f64, y: f64) -> QVector3D {
+        let position = Vec2::new(x as f32, y as f32);
+        self.rust()
+            .declared
+            .get()
+            .and_then(|camera| {
+                runtime::with_world(|world| mini_view::world_position(world, camera, position))
+            })
+            .flatten()
+            .unwrap_or(Vec3::NAN)
+            .into_qt()
+    }
+
+    fn spawn(mut self: Pin<&mut Self>) {
+        let bundle = mini_view::mini_view_bundle(self.view(), self.target());
+        let Some(entity) = self.as_mut().rust_mut().declared.spawn(bundle) else {
+            warn!("BevyMiniViewCamera cannot spawn an entity without a running Bevy app");
+            return;
+        };
+        self.set_entity(entity.to_bits());
+    }
+
+    fn view(&self) -> QmlMiniView {
+        QmlMiniView {
+            center: Vec3::from_qt(self.center()),
+            follow: declarative::entity_from_bits(*self.follow_entity()),
+            extent: *self.extent() as f32,
+            altitude: *self.altitude() as f32,
+        }
+    }
+
+    fn target(&self) -> QmlImageTarget {
+        QmlImageTarget::new(
+            self.name().to_string(),
+            UVec2::new(
+                (*self.image_width()).max(1) as u32,
+                (*self.image_height()).max(1) as u32,
+            ),
+        )
+    }
+
+    fn write_view(self: Pin<&mut Self>) {
+        let view = self.view();
+        self.rust().declared.update(move |world, entity| {
+            let mut entity = world.entity_mut(entity);
+            if entity.get::<QmlMiniView>() != Some(&view) {
+                entity.insert(view);
+            }
+        });
+    }
+
+    fn write_target(self: Pin<&mut Self>) {
+        let target = self.target();
+        self.rust().declared.update(move |world, entity| {
+            world.entity_mut(entity).insert(target);
+        });
+    }
+}
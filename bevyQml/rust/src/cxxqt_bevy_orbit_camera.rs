@@ -0,0 +1,339 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that sets up an orbit camera
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_orbit_camera")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvector3d.h");
+        /// An alias to the QVector3D type
+        type QVector3D = cxx_qt_lib::QVector3D;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // OrbitCameraController based on the Rust struct OrbitCameraControllerRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(u64, camera)]
+        #[qproperty(bool, bound)]
+        #[qproperty(bool, enabled)]
+        #[qproperty(QVector3D, target)]
+        #[qproperty(f64, distance)]
+        #[qproperty(f64, yaw)]
+        #[qproperty(f64, pitch)]
+        #[qproperty(f64, min_pitch)]
+        #[qproperty(f64, max_pitch)]
+        #[qproperty(f64, min_distance)]
+        #[qproperty(f64, max_distance)]
+        #[qproperty(f64, damping)]
+        #[qproperty(f64, orbit_speed)]
+        #[qproperty(f64, pan_speed)]
+        #[qproperty(f64, zoom_speed)]
+        #[qproperty(QString, orbit_button)]
+        #[qproperty(QString, pan_button)]
+        #[qproperty(bool, wheel_zoom)]
+        type OrbitCameraController = super::OrbitCameraControllerRust;
+    }
+
+    impl cxx_qt::Threading for OrbitCameraController {}
+    impl cxx_qt::Constructor<()> for OrbitCameraController {}
+}
+
+use core::pin::Pin;
+
+use bevy::{ecs::component::Tick, prelude::*};
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QString, QVector3D};
+
+use crate::{
+    camera::OrbitCamera,
+    convert::{FromQt, IntoQt},
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// Adds an [OrbitCamera] to `camera`, the bits of a camera [Entity] as
+/// returned by [Entity::to_bits], or to the first 3D camera while it is 0.
+/// `bound` tells whether there is such a camera. The orbit starts out where
+/// the camera is, looking at `target`.
+///
+/// Angles are in degrees, `orbitSpeed` in degrees per logical pixel. The
+/// buttons are `"left"`, `"right"` or `"middle"`, or empty to turn orbiting
+/// or panning off. `target`, `distance`, `yaw` and `pitch` follow the camera
+/// as it is moved by input. The camera is left where it is when the
+/// controller is destroyed.
+pub struct OrbitCameraControllerRust {
+    camera: u64,
+    bound: bool,
+    enabled: bool,
+    target: QVector3D,
+    distance: f64,
+    yaw: f64,
+    pitch: f64,
+    min_pitch: f64,
+    max_pitch: f64,
+    min_distance: f64,
+    max_distance: f64,
+    damping: f64,
+    orbit_speed: f64,
+    pan_speed: f64,
+    zoom_speed: f64,
+    orbit_button: QString,
+    pan_button: QString,
+    wheel_zoom: bool,
+    attached: Option<Entity>,
+    changed: Option<Tick>,
+    /// Set while the properties are updated from the world, so the change
+    /// handlers do not write them back
+    syncing: bool,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for OrbitCameraControllerRust {
+    fn default() -> Self {
+        let orbit = OrbitCamera::default();
+        Self {
+            camera: 0,
+            bound: false,
+            enabled: orbit.enabled,
+            target: orbit.target.into_qt(),
+            distance: orbit.distance.into(),
+            yaw: orbit.yaw.to_degrees().into(),
+            pitch: orbit.pitch.to_degrees().into(),
+            min_pitch: orbit.min_pitch.to_degrees().into(),
+            max_pitch: orbit.max_pitch.to_degrees().into(),
+            min_distance: orbit.min_distance.into(),
+            max_distance: orbit.max_distance.into(),
+            damping: orbit.damping.into(),
+            orbit_speed: orbit.orbit_speed.to_degrees().into(),
+            pan_speed: orbit.pan_speed.into(),
+            zoom_speed: orbit.zoom_speed.into(),
+            orbit_button: button_name(orbit.orbit_button),
+            pan_button: button_name(orbit.pan_button),
+            wheel_zoom: orbit.wheel_zoom,
+            attached: None,
+            changed: None,
+            syncing: false,
+            update_listener: None,
+        }
+    }
+}
+
+impl Drop for OrbitCameraControllerRust {
+    fn drop(&mut self) {
+        if let Some(entity) = self.attached {
+            runtime::with_world(|world| {
+                if let Some(mut entity) = world.get_entity_mut(entity) {
+                    entity.remove::<OrbitCamera>();
+                }
+            });
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::OrbitCameraController {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|controller| controller.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.as_mut()
+            .on_camera_changed(|controller| controller.detach())
+            .release();
+        // Every other property changes the orbit
+        self.as_mut()
+            .on_enabled_changed(|controller| controller.write())
+            .release();
+        self.as_mut()
+            .on_target_changed(|controller| controller.write())
+            .release();
+        self.as_mut()
+            .on_distance_changed(|controller| controller.write())
+            .release();
+        self.as_mut()
+            .on_yaw_changed(|controller| controller.write())
+            .release();
+        self.as_mut()
+            .on_pitch_changed(|controller| controller.write())
+            .release();
+        self.as_mut()
+            .on_min_pitch_changed(|controller| controller.write())
+            .release();
+        self.as_mut()
+            .on_max_pitch_changed(|controller| controller.write())
+            .release();
+        self.as_mut()
+            .on_min_distance_changed(|controller| controller.write())
+            .release();
+        self.as_mut()
+            .on_max_distance_changed(|controller| controller.write())
+            .release();
+        self.as_mut()
+            .on_damping_changed(|controller| controller.write())
+            .release();
+        self.as_mut()
+            .on_orbit_speed_changed(|controller| controller.write())
+            .release();
+        self.as_mut()
+            .on_pan_speed_changed(|controller| controller.write())
+            .release();
+        self.as_mut()
+            .on_zoom_speed_changed(|controller| controller.write())
+            .release();
+        self.as_mut()
+            .on_orbit_button_changed(|controller| controller.write())
+            .release();
+        self.as_mut()
+            .on_pan_button_changed(|controller| controller.write())
+            .release();
+        self.on_wheel_zoom_changed(|controller| controller.write())
+            .release();
+    }
+}
+
+impl qobject::OrbitCameraController {
+    /// Take the orbit off the previous camera and put it on the new one
+    fn detach(mut self: Pin<&mut Self>) {
+        if let Some(entity) = self.as_mut().rust_mut().attached.take() {
+            runtime::with_world(|world| {
+                if let Some(mut entity) = world.get_entity_mut(entity) {
+                    entity.remove::<OrbitCamera>();
+                }
+            });
+        }
+        self.as_mut().rust_mut().changed = None;
+        self.refresh();
+    }
+
+    /// The orbit described by the properties
+    fn orbit(&self) -> OrbitCamera {
+        let degrees = |value: &f64| (*value as f32).to_radians();
+        OrbitCamera {
+            target: Vec3::from_qt(self.target()),
+            distance: *self.distance() as f32,
+            yaw: degrees(self.yaw()),
+            pitch: degrees(self.pitch()),
+            min_pitch: degrees(self.min_pitch()),
+            max_pitch: degrees(self.max_pitch()),
+            min_distance: *self.min_distance() as f32,
+            max_distance: *self.max_distance() as f32,
+            damping: *self.damping() as f32,
+            orbit_speed: degrees(self.orbit_speed()),
+            pan_speed: *self.pan_speed() as f32,
+            zoom_speed: *self.zoom_speed() as f32,
+            orbit_button: button_from_name(&self.orbit_button().to_string()),
+            pan_button: button_from_name(&self.pan_button().to_string()),
+            wheel_zoom: *self.wheel_zoom(),
+            enabled: *self.enabled(),
+            ..default()
+        }
+    }
+
+    /// Change the orbit of the camera to the properties
+    fn write(self: Pin<&mut Self>) {
+        if self.rust().syncing {
+            return;
+        }
+        let Some(entity) = self.rust().attached else {
+            return;
+        };
+        let orbit = self.orbit();
+        runtime::with_world(|world| {
+            if let Some(mut current) = world.get_mut::<OrbitCamera>(entity) {
+                current.apply(orbit);
+            }
+        });
+        runtime::request_update();
+    }
+
+    /// Attach to the camera if needed and read the orbit back if input moved it
+    fn refresh(mut self: Pin<&mut Self>) {
+        let bits = *self.camera();
+        let attached = self.rust().attached;
+        let seen = self.rust().changed;
+        let orbit = self.orbit();
+        let update = runtime::with_world(|world| {
+            let entity = match attached.filter(|entity| world.get_entity(*entity).is_some()) {
+                Some(entity) => entity,
+                None => {
+                    let entity = find_camera(world, bits)?;
+                    let eye = world.get::<Transform>(entity)?.translation;
+                    let mut start = orbit.clone();
+                    let from_eye = OrbitCamera::looking_at(eye, orbit.target);
+                    start.distance = from_eye.distance;
+                    start.yaw = from_eye.yaw;
+                    start.pitch = from_eye.pitch;
+                    start.clamp();
+                    world.entity_mut(entity).insert(start);
+                    entity
+                }
+            };
+            let current = world.get_entity(entity)?.get_ref::<OrbitCamera>()?;
+            let changed = current.last_changed();
+            let current = (seen != Some(changed)).then(|| current.clone());
+            Some((entity, changed, current))
+        });
+
+        let Some(update) = update else {
+            // The app is busy, try again after the next update
+            return;
+        };
+        let Some((entity, changed, current)) = update else {
+            let mut rust = self.as_mut().rust_mut();
+            rust.attached = None;
+            rust.changed = None;
+            self.set_bound(false);
+            return;
+        };
+
+        let mut rust = self.as_mut().rust_mut();
+        rust.attached = Some(entity);
+        rust.changed = Some(changed);
+        if let Some(current) = current {
+            self.as_mut().rust_mut().syncing = true;
+            self.as_mut().set_target(current.target.into_qt());
+            self.as_mut().set_distance(current.distance.into());
+            self.as_mut().set_yaw(current.yaw.to_degrees().into());
+            self.as_mut().set_pitch(current.pitch.to_degrees().into());
+            self.as_mut().rust_mut().syncing = false;
+        }
+        self.set_bound(true);
+    }
+}
+
+/// The camera with the bits, or the first 3D camera for 0
+fn find_camera(world: &mut World, bits: u64) -> Option<Entity> {
+    if bits != 0 {
+        let entity = Entity::try_from_bits(bits).ok()?;
+        return world.get::<Camera>(entity).is_some().then_some(entity);
+    }
+    world
+        .query_filtered::<Entity, With<Camera3d>>()
+        .iter(world)
+        .min()
+}
+
+fn button_from_name(name: &str) -> Option<MouseButton> {
+    match name.to_ascii_lowercase().as_str() {
+        "left" => Some(MouseButton::Left),
+        "right" => Some(MouseButton::Right),
+        "middle" => Some(MouseButton::Middle),
+        _ => None,
+    }
+}
+
+fn button_name(button: Option<MouseButton>) -> QString {
+    QString::from(match button {
+        Some(MouseButton::Left) => "left",
+        Some(MouseButton::Right) => "right",
+        Some(MouseButton::Middle) => "middle",
+        _ => "",
+    })
+}
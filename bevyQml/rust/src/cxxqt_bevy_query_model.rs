@@ -0,0 +1,601 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the list model of the entities matching a query
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_query_model")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!(<QtCore/QAbstractListModel>);
+
+        include!("cxx-qt-lib/qhash.h");
+        /// An alias to the QHash<int, QByteArray> type
+        type QHash_i32_QByteArray = cxx_qt_lib::QHash<cxx_qt_lib::QHashPair_i32_QByteArray>;
+        include!("cxx-qt-lib/qmodelindex.h");
+        /// An alias to the QModelIndex type
+        type QModelIndex = cxx_qt_lib::QModelIndex;
+        include!("cxx-qt-lib/qstringlist.h");
+        /// An alias to the QStringList type
+        type QStringList = cxx_qt_lib::QStringList;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+        include!("cxx-qt-lib/qvector.h");
+        /// An alias to the QVector<int> type
+        type QVector_i32 = cxx_qt_lib::QVector<i32>;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // QueryListModel based on the Rust struct QueryListModelRust.
+        #[qobject]
+        #[qml_element]
+        #[base = "QAbstractListModel"]
+        #[qproperty(QStringList, components)]
+        #[qproperty(i32, count)]
+        type QueryListModel = super::QueryListModelRust;
+    }
+
+    // QAbstractListModel implementation
+    unsafe extern "RustQt" {
+        #[inherit]
+        #[cxx_name = "beginInsertRows"]
+        unsafe fn begin_insert_rows(
+            self: Pin<&mut QueryListModel>,
+            parent: &QModelIndex,
+            first: i32,
+            last: i32,
+        );
+
+        #[inherit]
+        #[cxx_name = "endInsertRows"]
+        unsafe fn end_insert_rows(self: Pin<&mut QueryListModel>);
+
+        #[inherit]
+        #[cxx_name = "beginRemoveRows"]
+        unsafe fn begin_remove_rows(
+            self: Pin<&mut QueryListModel>,
+            parent: &QModelIndex,
+            first: i32,
+            last: i32,
+        );
+
+        #[inherit]
+        #[cxx_name = "endRemoveRows"]
+        unsafe fn end_remove_rows(self: Pin<&mut QueryListModel>);
+
+        #[inherit]
+        #[cxx_name = "beginMoveRows"]
+        unsafe fn begin_move_rows(
+            self: Pin<&mut QueryListModel>,
+            source_parent: &QModelIndex,
+            source_first: i32,
+            source_last: i32,
+            destination_parent: &QModelIndex,
+            destination_child: i32,
+        ) -> bool;
+
+        #[inherit]
+        #[cxx_name = "endMoveRows"]
+        unsafe fn end_move_rows(self: Pin<&mut QueryListModel>);
+
+        #[inherit]
+        #[cxx_name = "beginResetModel"]
+        unsafe fn begin_reset_model(self: Pin<&mut QueryListModel>);
+
+        #[inherit]
+        #[cxx_name = "endResetModel"]
+        unsafe fn end_reset_model(self: Pin<&mut QueryListModel>);
+
+        #[inherit]
+        fn index(self: &QueryListModel, row: i32, column: i32, parent: &QModelIndex)
+            -> QModelIndex;
+
+        #[qsignal]
+        #[inherit]
+        #[cxx_name = "dataChanged"]
+        fn data_changed(
+            self: Pin<&mut QueryListModel>,
+            top_left: &QModelIndex,
+            bottom_right: &QModelIndex,
+            roles: &QVector_i32,
+        );
+    }
+
+    unsafe extern "RustQt" {
+        #[qinvokable]
+        #[cxx_override]
+        fn data(self: &QueryListModel, index: &QModelIndex, role: i32) -> QVariant;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "roleNames"]
+        fn role_names(self: &QueryListModel) -> QHash_i32_QByteArray;
+
+        #[qinvokable]
+        #[cxx_override]
+        #[cxx_name = "rowCount"]
+        fn row_count(self: &QueryListModel, parent: &QModelIndex) -> i32;
+
+        /// The entity shown in a row as returned by Entity::to_bits, or 0
+        #[qinvokable]
+        fn entity(self: &QueryListModel, row: i32) -> u64;
+
+        /// The index of the row of an entity, invalid if it is not in the model
+        #[qinvokable]
+        #[cxx_name = "indexOf"]
+        fn index_of(self: &QueryListModel, entity: u64) -> QModelIndex;
+    }
+
+    impl cxx_qt::Threading for QueryListModel {}
+    impl cxx_qt::Constructor<()> for QueryListModel {}
+}
+
+use core::pin::Pin;
+use std::{collections::HashSet, sync::Arc};
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{
+    QByteArray, QHash, QHashPair_i32_QByteArray, QList, QModelIndex, QString, QStringList,
+    QVariant, QVector,
+};
+
+use crate::{
+    label,
+    model::{self, QueryRowValues, QuerySnapshot, QuerySnapshotReader},
+    runtime::{self, UpdateListener},
+};
+
+/// Qt::UserRole, the first role available to custom models
+const USER_ROLE: i32 = 0x0100;
+
+/// The role which holds the bits of the entity of a row
+const ENTITY_ROLE: i32 = USER_ROLE;
+
+struct QueryRow {
+    entity: Entity,
+    values: QueryRowValues,
+}
+
+/// The Rust struct for the QObject
+///
+/// `components` names the reflected components an entity needs to show up
+/// in the model, e.g. `["Name", "Health"]`. Every row then has an `entity`
+/// role as well as a role per field of those components, see
+/// [crate::model::QuerySpec::resolve] for how the roles are named. The
+/// `display`, `decoration` and `toolTip` roles show the entity as described
+/// in [crate::label].
+///
+/// The model reads the snapshots the Bevy side publishes of the query, see
+/// [crate::model::watch_query], rather than the world, so it scales to tens
+/// of thousands of entities and also works while the app runs on a thread of
+/// its own. The rows follow the world after every update: rows of entities
+/// which no longer match are removed, new entities are inserted and rows
+/// out of place are moved where the query has them, and only the roles of
+/// components that changed are reported through dataChanged. The model is
+/// only reset when the roles change, so views keep their delegates, scroll
+/// positions and add and remove transitions instead of rebuilding them.
+#[derive(Default)]
+pub struct QueryListModelRust {
+    components: QStringList,
+    count: i32,
+    reader: Option<QuerySnapshotReader>,
+    roles: Arc<[String]>,
+    rows: Vec<QueryRow>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::QueryListModel {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|model| model.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.on_components_changed(|model| model.requery())
+            .release();
+    }
+}
+
+impl qobject::QueryListModel {
+    pub fn data(&self, index: &QModelIndex, role: i32) -> QVariant {
+        let Some(row) = usize::try_from(index.row())
+            .ok()
+            .and_then(|row| self.rust().rows.get(row))
+        else {
+            return QVariant::default();
+        };
+
+        if role == ENTITY_ROLE {
+            return QVariant::from(&row.entity.to_bits());
+        }
+        if role < ENTITY_ROLE {
+            return label::role_data(&row.values.label, role);
+        }
+        usize::try_from(role - ENTITY_ROLE - 1)
+            .ok()
+            .and_then(|column| row.values.columns.get(column))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn role_names(&self) -> QHash<QHashPair_i32_QByteArray> {
+        let mut roles = QHash::<QHashPair_i32_QByteArray>::default();
+        label::insert_role_names(&mut roles);
+        roles.insert(ENTITY_ROLE, QByteArray::from("entity"));
+        for (role, name) in (ENTITY_ROLE + 1..).zip(self.rust().roles.iter()) {
+            roles.insert(role, QByteArray::from(name.as_str()));
+        }
+        roles
+    }
+
+    pub fn row_count(&self, _parent: &QModelIndex) -> i32 {
+        self.rust().rows.len() as i32
+    }
+
+    pub fn entity(&self, row: i32) -> u64 {
+        usize::try_from(row)
+            .ok()
+            .and_then(|row| self.rust().rows.get(row))
+            .map_or(0, |row| row.entity.to_bits())
+    }
+
+    pub fn index_of(&self, entity: u64) -> QModelIndex {
+        self.rust()
+            .rows
+            .iter()
+            .position(|row| row.entity.to_bits() == entity)
+            .map_or_else(QModelIndex::default, |row| {
+                self.index(row as i32, 0, &QModelIndex::default())
+            })
+    }
+
+    /// Watch the query of the new components and start over with an empty
+    /// model
+    fn requery(mut self: Pin<&mut Self>) {
+        let names: Vec<String> = QList::<QString>::from(self.components())
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let reader = if names.is_empty() {
+            None
+        } else {
+            model::watch_query(names)
+        };
+
+        unsafe {
+            self.as_mut().begin_reset_model();
+        }
+        let mut rust = self.as_mut().rust_mut();
+        rust.reader = reader;
+        rust.roles = Arc::default();
+        rust.rows.clear();
+        unsafe {
+            self.as_mut().end_reset_model();
+        }
+        self.set_count(0);
+    }
+
+    /// Bring the rows in line with the latest snapshot of the query
+    fn refresh(mut self: Pin<&mut Self>) {
+        let snapshot = {
+            let mut rust = self.as_mut().rust_mut();
+            let Some(reader) = rust.reader.as_mut() else {
+                return;
+            };
+            if !reader.update() {
+                return;
+            }
+            reader.read().clone()
+        };
+        if let Some(error) = &snapshot.error {
+            warn!("QueryListModel cannot query {}: {error}", self.components());
+            return;
+        }
+
+        if snapshot.roles != self.rust().roles {
+            self.as_mut().reset_rows(&snapshot);
+        } else {
+            self.as_mut().sync_rows(&snapshot.rows);
+        }
+
+        let count = self.rust().rows.len() as i32;
+        if *self.count() != count {
+            self.set_count(count);
+        }
+    }
+
+    /// Show the rows of a snapshot with other roles, which views only pick
+    /// up on a reset
+    fn reset_rows(mut self: Pin<&mut Self>, snapshot: &QuerySnapshot) {
+        unsafe {
+            self.as_mut().begin_reset_model();
+        }
+        let mut rust = self.as_mut().rust_mut();
+        rust.roles = snapshot.roles.clone();
+        rust.rows = snapshot
+            .rows
+            .iter()
+            .map(|(entity, values)| QueryRow {
+                entity: *entity,
+                values: values.clone(),
+            })
+            .collect();
+        unsafe {
+            self.as_mut().end_reset_model();
+        }
+    }
+
+    /// Remove, insert, move and update the rows to match the rows of a
+    /// snapshot
+    fn sync_rows(mut self: Pin<&mut Self>, target: &[(Entity, QueryRowValues)]) {
+        let shown: Vec<Entity> = self.rust().rows.iter().map(|row| row.entity).collect();
+        let entities: Vec<Entity> = target.iter().map(|(entity, _)| *entity).collect();
+        let parent = QModelIndex::default();
+        for change in row_changes(&shown, &entities) {
+            match change {
+                RowChange::Remove { first, last } => {
+                    unsafe {
+                        self.as_mut()
+                            .begin_remove_rows(&parent, first as i32, last as i32);
+                    }
+                    self.as_mut().rust_mut().rows.drain(first..=last);
+                    unsafe {
+                        self.as_mut().end_remove_rows();
+                    }
+                }
+                RowChange::Insert { first, last } => {
+                    unsafe {
+                        self.as_mut()
+                            .begin_insert_rows(&parent, first as i32, last as i32);
+                    }
+                    let added = target[first..=last]
+                        .iter()
+                        .map(|(entity, values)| QueryRow {
+                            entity: *entity,
+                            values: values.clone(),
+                        });
+                    self.as_mut().rust_mut().rows.splice(first..first, added);
+                    unsafe {
+                        self.as_mut().end_insert_rows();
+                    }
+                }
+                RowChange::Move { from, to } => {
+                    let moved = unsafe {
+                        self.as_mut().begin_move_rows(
+                            &parent,
+                            from as i32,
+                            from as i32,
+                            &parent,
+                            to as i32,
+                        )
+                    };
+                    let mut rust = self.as_mut().rust_mut();
+                    let row = rust.rows.remove(from);
+                    rust.rows.insert(to, row);
+                    if moved {
+                        unsafe {
+                            self.as_mut().end_move_rows();
+                        }
+                    }
+                }
+            }
+        }
+
+        for (row, (entity, values)) in target.iter().enumerate() {
+            if self.rust().rows.get(row).map(|row| row.entity) != Some(*entity) {
+                break;
+            }
+            self.as_mut().update_row(row, values);
+        }
+    }
+
+    /// Store the new values of a row and report the roles which changed
+    fn update_row(mut self: Pin<&mut Self>, row: usize, values: &QueryRowValues) {
+        // Unchanged rows share their values with the previous snapshot
+        if Arc::ptr_eq(values, &self.rust().rows[row].values) {
+            return;
+        }
+
+        let shown = &self.rust().rows[row].values;
+        let mut roles = if shown.label != values.label {
+            label::roles()
+        } else {
+            QVector::<i32>::default()
+        };
+        for (role, (old, new)) in (ENTITY_ROLE + 1..).zip(shown.columns.iter().zip(&values.columns))
+        {
+            if old != new {
+                roles.append(role);
+            }
+        }
+        self.as_mut().rust_mut().rows[row].values = values.clone();
+
+        if !roles.is_empty() {
+            let index = self.index(row as i32, 0, &QModelIndex::default());
+            self.as_mut().data_changed(&index, &index, &roles);
+        }
+    }
+}
+
+/// A change to the rows of the model
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RowChange {
+    /// Remove the rows from `first` to `last`
+    Remove { first: usize, last: usize },
+    /// Insert the rows from `first` to `last` of the target
+    Insert { first: usize, last: usize },
+    /// Move the row at `from` up to `to`
+    Move { from: usize, to: usize },
+}
+
+/// The changes which turn the rows of the shown entities into the rows of
+/// the target, to be applied in order
+///
+/// The rows of entities which are no longer in the target are removed a
+/// block at a time from the bottom up. Going down the target, entities
+/// which are not shown yet are then inserted a block at a time and rows out
+/// of place are moved up to where the target has them. The target must not
+/// hold an entity twice.
+fn row_changes(shown: &[Entity], target: &[Entity]) -> Vec<RowChange> {
+    let mut changes = Vec::new();
+    let matching: HashSet<Entity> = target.iter().copied().collect();
+    let mut rows = shown.to_vec();
+    let mut end = rows.len();
+    while let Some(last) = (0..end).rev().find(|&row| !matching.contains(&rows[row])) {
+        let first = (0..last)
+            .rev()
+            .take_while(|&row| !matching.contains(&rows[row]))
+            .last()
+            .unwrap_or(last);
+        rows.drain(first..=last);
+        changes.push(RowChange::Remove { first, last });
+        end = first;
+    }
+
+    let kept: HashSet<Entity> = rows.iter().copied().collect();
+    let mut row = 0;
+    while row < target.len() {
+        let entity = target[row];
+        if !kept.contains(&entity) {
+            let end = target[row..]
+                .iter()
+                .position(|entity| kept.contains(entity))
+                .map_or(target.len(), |count| row + count);
+            rows.splice(row..row, target[row..end].iter().copied());
+            changes.push(RowChange::Insert {
+                first: row,
+                last: end - 1,
+            });
+            row = end;
+            continue;
+        }
+
+        if rows[row] != entity {
+            // The rows before are in place, so the entity comes later
+            let Some(from) = rows[row + 1..]
+                .iter()
+                .position(|shown| *shown == entity)
+                .map(|offset| row + 1 + offset)
+            else {
+                break;
+            };
+            let moved = rows.remove(from);
+            rows.insert(row, moved);
+            changes.push(RowChange::Move { from, to: row });
+        }
+        row += 1;
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entities(indices: &[u32]) -> Vec<Entity> {
+        indices
+            .iter()
+            .map(|&index| Entity::from_raw(index))
+            .collect()
+    }
+
+    /// The changes between the rows, after checking they lead to the target
+    fn changes(shown: &[u32], target: &[u32]) -> Vec<RowChange> {
+        let (shown, target) = (entities(shown), entities(target));
+        let changes = row_changes(&shown, &target);
+        let mut rows = shown;
+        for change in &changes {
+            match *change {
+                RowChange::Remove { first, last } => {
+                    rows.drain(first..=last);
+                }
+                RowChange::Insert { first, last } => {
+                    rows.splice(first..first, target[first..=last].iter().copied());
+                }
+                RowChange::Move { from, to } => {
+                    let moved = rows.remove(from);
+                    rows.insert(to, moved);
+                }
+            }
+        }
+        assert_eq!(rows, target);
+        changes
+    }
+
+    #[test]
+    fn unchanged() {
+        assert_eq!(changes(&[], &[]), []);
+        assert_eq!(changes(&[1, 2, 3], &[1, 2, 3]), []);
+    }
+
+    #[test]
+    fn inserts_blocks() {
+        assert_eq!(
+            changes(&[], &[1, 2]),
+            [RowChange::Insert { first: 0, last: 1 }]
+        );
+        assert_eq!(
+            changes(&[2, 4], &[1, 2, 3, 5, 4, 6]),
+            [
+                RowChange::Insert { first: 0, last: 0 },
+                RowChange::Insert { first: 2, last: 3 },
+                RowChange::Insert { first: 5, last: 5 },
+            ]
+        );
+    }
+
+    #[test]
+    fn removes_blocks_from_the_bottom_up() {
+        assert_eq!(
+            changes(&[1, 2], &[]),
+            [RowChange::Remove { first: 0, last: 1 }]
+        );
+        assert_eq!(
+            changes(&[1, 2, 3, 4, 5, 6], &[2, 5]),
+            [
+                RowChange::Remove { first: 5, last: 5 },
+                RowChange::Remove { first: 2, last: 3 },
+                RowChange::Remove { first: 0, last: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn moves_rows_up() {
+        assert_eq!(
+            changes(&[1, 2, 3], &[3, 1, 2]),
+            [RowChange::Move { from: 2, to: 0 }]
+        );
+        assert_eq!(
+            changes(&[1, 2, 3], &[2, 3, 1]),
+            [
+                RowChange::Move { from: 1, to: 0 },
+                RowChange::Move { from: 2, to: 1 },
+            ]
+        );
+        assert_eq!(
+            changes(&[1, 2, 3, 4], &[4, 3, 2, 1]),
+            [
+                RowChange::Move { from: 3, to: 0 },
+                RowChange::Move { from: 3, to: 1 },
+                RowChange::Move { from: 3, to: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn mixed_changes() {
+        assert_eq!(
+            changes(&[1, 2, 3, 4, 5], &[6, 4, 1, 7, 8, 3]),
+            [
+                RowChange::Remove { first: 4, last: 4 },
+                RowChange::Remove { first: 1, last: 1 },
+                RowChange::Insert { first: 0, last: 0 },
+                RowChange::Move { from: 3, to: 1 },
+                RowChange::Insert { first: 3, last: 4 },
+            ]
+        );
+    }
+}
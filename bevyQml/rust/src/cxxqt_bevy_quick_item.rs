@@ -0,0 +1,1122 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QQuickItem that displays the Bevy scene
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_quick_item")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("bevyqml/quickitem.h");
+        /// The scene graph node returned from updatePaintNode
+        type QSGNode;
+        /// QQuickItem::UpdatePaintNodeData
+        type QQuickItemUpdatePaintNodeData;
+
+        include!("bevyqml/input.h");
+        type QMouseEvent;
+        type QHoverEvent;
+        type QWheelEvent;
+        type QKeyEvent;
+        type QFocusEvent;
+        type QTouchEvent;
+
+        include!("bevyqml/ime.h");
+        type QInputMethodEvent;
+
+        include!("bevyqml/drop.h");
+        type QDropEvent;
+        type QDragEnterEvent;
+        type QDragMoveEvent;
+        type QDragLeaveEvent;
+
+        include!("cxx-qt-lib/qcolor.h");
+        /// An alias to the QColor type
+        type QColor = cxx_qt_lib::QColor;
+        include!("cxx-qt-lib/qimage.h");
+        /// An alias to the QImage type
+        type QImage = cxx_qt_lib::QImage;
+        include!("cxx-qt-lib/qlist.h");
+        /// An alias to the QList<QUrl> type
+        type QList_QUrl = cxx_qt_lib::QList<cxx_qt_lib::QUrl>;
+        /// An alias to the QVariantList type
+        type QList_QVariant = cxx_qt_lib::QList<cxx_qt_lib::QVariant>;
+        include!("cxx-qt-lib/qpoint.h");
+        /// An alias to the QPoint type
+        type QPoint = cxx_qt_lib::QPoint;
+        include!("cxx-qt-lib/qpointf.h");
+        /// An alias to the QPointF type
+        type QPointF = cxx_qt_lib::QPointF;
+        include!("cxx-qt-lib/qrectf.h");
+        /// An alias to the QRectF type
+        type QRectF = cxx_qt_lib::QRectF;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvector3d.h");
+        /// An alias to the QVector3D type
+        type QVector3D = cxx_qt_lib::QVector3D;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+        include!("cxx-qt-lib/qmap.h");
+        /// An alias to the QVariantMap type
+        type QMap_QString_QVariant = cxx_qt_lib::QMap<cxx_qt_lib::QMapPair_QString_QVariant>;
+    }
+
+    /// What the input method asks the item about, the values of
+    /// Qt::InputMethodQuery
+    #[namespace = "Qt"]
+    #[repr(i32)]
+    enum InputMethodQuery {
+        ImEnabled = 0x1,
+        ImCursorRectangle = 0x2,
+        ImCursorPosition = 0x8,
+        ImSurroundingText = 0x10,
+        ImCurrentSelection = 0x20,
+        ImAnchorPosition = 0x80,
+    }
+
+    #[namespace = "Qt"]
+    unsafe extern "C++" {
+        include!("bevyqml/ime.h");
+        type InputMethodQuery;
+    }
+
+    /// How the render target of a BevyQuickItem follows the size of the item
+    #[qenum(BevyQuickItem)]
+    enum ResizeMode {
+        /// Render at the physical size of the item
+        Stretch,
+        /// Render at renderWidth by renderHeight pixels, scaled to fit the item
+        FixedResolution,
+        /// Render at superSampling times the physical size of the item
+        SuperSample,
+    }
+
+    /// How a BevyQuickItem shows the scene
+    #[qenum(BevyQuickItem)]
+    enum ViewMode {
+        /// The materials as they are
+        Shaded,
+        /// The edges of the triangles on top of the materials
+        Wireframe,
+        /// The vertex normals as lines
+        Normals,
+        /// A checker pattern laid out by the texture coordinates
+        Uvs,
+        /// A dim color which grows brighter the more surfaces overlap
+        Overdraw,
+        /// The bounding boxes of the meshes on top of the materials
+        Aabbs,
+    }
+
+    /// How frames get from Bevy into the scene graph of a BevyQuickItem
+    #[qenum(BevyQuickItem)]
+    enum Interop {
+        /// Share the render target when the scene graph renders on the same
+        /// GPU with a fitting graphics API, and copy frames otherwise
+        Automatic,
+        /// Read every frame back and upload it again
+        Copy,
+        /// Share the render target through Vulkan external memory
+        VulkanExternalMemory,
+        /// Share the render target through a Direct3D NT handle
+        D3DSharedHandle,
+        /// Share the render target through an IOSurface
+        MetalIOSurface,
+        /// Share the render target between OpenGL contexts
+        OpenGLShareContext,
+    }
+
+    unsafe extern "RustQt" {
+        // The QQuickItem definition
+        // We tell CXX-Qt that we want a QQuickItem subclass with the name
+        // BevyQuickItem based on the Rust struct BevyQuickItemRust.
+        #[qobject]
+        #[qml_element]
+        #[base = "QQuickItem"]
+        #[qproperty(bool, select_on_click)]
+        #[qproperty(QString, view)]
+        #[qproperty(u64, camera)]
+        #[qproperty(QList_QVariant, viewports)]
+        #[qproperty(QString, world)]
+        #[qproperty(ResizeMode, resize_mode)]
+        #[qproperty(i32, render_width)]
+        #[qproperty(i32, render_height)]
+        #[qproperty(f64, super_sampling)]
+        #[qproperty(f64, aspect_ratio)]
+        #[qproperty(i32, resize_delay)]
+        #[qproperty(bool, dynamic_resolution)]
+        #[qproperty(f64, target_frame_ms)]
+        #[qproperty(f64, current_scale)]
+        #[qproperty(bool, show_grid)]
+        #[qproperty(bool, show_axes)]
+        #[qproperty(f64, grid_size)]
+        #[qproperty(i32, grid_subdivisions)]
+        #[qproperty(f64, grid_fade_distance)]
+        #[qproperty(QColor, grid_color)]
+        #[qproperty(ViewMode, view_mode)]
+        #[qproperty(i32, cursor_shape)]
+        #[qproperty(bool, cursor_visible)]
+        #[qproperty(bool, pointer_locked)]
+        #[qproperty(Interop, interop)]
+        #[qproperty(Interop, interop_backend)]
+        type BevyQuickItem = super::BevyQuickItemRust;
+
+        /// Override QQuickItem::updatePaintNode to show the latest Bevy frame
+        #[cxx_override]
+        #[cxx_name = "updatePaintNode"]
+        unsafe fn update_paint_node(
+            self: Pin<&mut BevyQuickItem>,
+            old_node: *mut QSGNode,
+            data: *mut QQuickItemUpdatePaintNodeData,
+        ) -> *mut QSGNode;
+
+        /// Forward mouse presses to Bevy
+        #[cxx_override]
+        #[cxx_name = "mousePressEvent"]
+        unsafe fn mouse_press_event(self: Pin<&mut BevyQuickItem>, event: *mut QMouseEvent);
+
+        /// Forward the second press of a double click to Bevy
+        #[cxx_override]
+        #[cxx_name = "mouseDoubleClickEvent"]
+        unsafe fn mouse_double_click_event(self: Pin<&mut BevyQuickItem>, event: *mut QMouseEvent);
+
+        /// Forward mouse releases to Bevy
+        #[cxx_override]
+        #[cxx_name = "mouseReleaseEvent"]
+        unsafe fn mouse_release_event(self: Pin<&mut BevyQuickItem>, event: *mut QMouseEvent);
+
+        /// Forward cursor movement while a button is pressed to Bevy
+        #[cxx_override]
+        #[cxx_name = "mouseMoveEvent"]
+        unsafe fn mouse_move_event(self: Pin<&mut BevyQuickItem>, event: *mut QMouseEvent);
+
+        /// Forward the cursor entering the item to Bevy
+        #[cxx_override]
+        #[cxx_name = "hoverEnterEvent"]
+        unsafe fn hover_enter_event(self: Pin<&mut BevyQuickItem>, event: *mut QHoverEvent);
+
+        /// Forward cursor movement without buttons pressed to Bevy
+        #[cxx_override]
+        #[cxx_name = "hoverMoveEvent"]
+        unsafe fn hover_move_event(self: Pin<&mut BevyQuickItem>, event: *mut QHoverEvent);
+
+        /// Forward the cursor leaving the item to Bevy
+        #[cxx_override]
+        #[cxx_name = "hoverLeaveEvent"]
+        unsafe fn hover_leave_event(self: Pin<&mut BevyQuickItem>, event: *mut QHoverEvent);
+
+        /// Forward the mouse wheel to Bevy
+        #[cxx_override]
+        #[cxx_name = "wheelEvent"]
+        unsafe fn wheel_event(self: Pin<&mut BevyQuickItem>, event: *mut QWheelEvent);
+
+        /// Forward key presses to Bevy
+        #[cxx_override]
+        #[cxx_name = "keyPressEvent"]
+        unsafe fn key_press_event(self: Pin<&mut BevyQuickItem>, event: *mut QKeyEvent);
+
+        /// Forward key releases to Bevy
+        #[cxx_override]
+        #[cxx_name = "keyReleaseEvent"]
+        unsafe fn key_release_event(self: Pin<&mut BevyQuickItem>, event: *mut QKeyEvent);
+
+        /// Tell Bevy the item gained keyboard focus
+        #[cxx_override]
+        #[cxx_name = "focusInEvent"]
+        unsafe fn focus_in_event(self: Pin<&mut BevyQuickItem>, event: *mut QFocusEvent);
+
+        /// Tell Bevy the item lost keyboard focus, which releases all keys
+        #[cxx_override]
+        #[cxx_name = "focusOutEvent"]
+        unsafe fn focus_out_event(self: Pin<&mut BevyQuickItem>, event: *mut QFocusEvent);
+
+        /// Forward text composed with an input method to Bevy
+        #[cxx_override]
+        #[cxx_name = "inputMethodEvent"]
+        unsafe fn input_method_event(self: Pin<&mut BevyQuickItem>, event: *mut QInputMethodEvent);
+
+        /// Answer the input method about the Bevy text field with the focus
+        #[cxx_override]
+        #[cxx_name = "inputMethodQuery"]
+        fn input_method_query(self: &BevyQuickItem, query: InputMethodQuery) -> QVariant;
+
+        /// Forward touch points to Bevy
+        #[cxx_override]
+        #[cxx_name = "touchEvent"]
+        unsafe fn touch_event(self: Pin<&mut BevyQuickItem>, event: *mut QTouchEvent);
+
+        /// Cancel the active touches when another item takes them over
+        #[cxx_override]
+        #[cxx_name = "touchUngrabEvent"]
+        fn touch_ungrab_event(self: Pin<&mut BevyQuickItem>);
+
+        /// Forward a QTabletEvent to Bevy, called by bevyqml/tablet.h
+        #[cxx_name = "tabletEvent"]
+        fn tablet_event(
+            self: Pin<&mut BevyQuickItem>,
+            event_type: i32,
+            position: &QPointF,
+            pressure: f64,
+            x_tilt: f64,
+            y_tilt: f64,
+            rotation: f64,
+            tool: i32,
+            tool_id: i64,
+        );
+
+        /// Tell Bevy the stylus left the tablet, called by bevyqml/tablet.h
+        #[cxx_name = "tabletProximityLeft"]
+        fn tablet_proximity_left(self: Pin<&mut BevyQuickItem>);
+
+        /// Accept files dragged onto the item and tell Bevy about them
+        #[cxx_override]
+        #[cxx_name = "dragEnterEvent"]
+        unsafe fn drag_enter_event(self: Pin<&mut BevyQuickItem>, event: *mut QDragEnterEvent);
+
+        /// Move the cursor along with the files dragged over the item
+        #[cxx_override]
+        #[cxx_name = "dragMoveEvent"]
+        unsafe fn drag_move_event(self: Pin<&mut BevyQuickItem>, event: *mut QDragMoveEvent);
+
+        /// Tell Bevy the dragged files left the item
+        #[cxx_override]
+        #[cxx_name = "dragLeaveEvent"]
+        unsafe fn drag_leave_event(self: Pin<&mut BevyQuickItem>, event: *mut QDragLeaveEvent);
+
+        /// Hand the dropped files to Bevy and QML
+        #[cxx_override]
+        #[cxx_name = "dropEvent"]
+        unsafe fn drop_event(self: Pin<&mut BevyQuickItem>, event: *mut QDropEvent);
+
+        /// Pick the entity shown at a position of the item
+        ///
+        /// Returns the `entity`, the world space `position` and `normal` of
+        /// the hit as well as its `distance` from the camera, or an empty map
+        /// if no mesh was hit.
+        #[qinvokable]
+        fn pick(self: &BevyQuickItem, x: f64, y: f64) -> QMap_QString_QVariant;
+
+        /// Where a point of the world appears in the item, in its coordinates
+        ///
+        /// Both coordinates are NaN when the point is behind the camera or
+        /// the item shows no camera.
+        #[qinvokable]
+        #[cxx_name = "worldToItem"]
+        fn world_to_item(self: &BevyQuickItem, position: &QVector3D) -> QPointF;
+
+        /// The ray through a position of the item, in its coordinates
+        ///
+        /// Returns the world space `origin` of the ray and its unit
+        /// `direction`, or an empty map if the item shows no camera.
+        #[qinvokable]
+        #[cxx_name = "itemToWorldRay"]
+        fn item_to_world_ray(self: &BevyQuickItem, position: &QPointF) -> QMap_QString_QVariant;
+
+        /// Where the origin of an entity, moved by an offset in world space,
+        /// appears in the item
+        ///
+        /// Returns the `position` in item coordinates and the `distance`
+        /// from the camera, or an empty map if the point is behind the
+        /// camera or the entity has no transform. With `occlusion` set,
+        /// `occluded` tells whether other meshes hide the point.
+        #[qinvokable]
+        #[cxx_name = "projectEntity"]
+        fn project_entity(
+            self: &BevyQuickItem,
+            entity: u64,
+            offset: &QVector3D,
+            occlusion: bool,
+        ) -> QMap_QString_QVariant;
+
+        /// Ask for an update of the Bevy app, which only matters while it
+        /// renders on demand
+        #[qinvokable]
+        #[cxx_name = "requestUpdate"]
+        fn request_update(self: &BevyQuickItem);
+
+        /// Read back the next frame shown by the item, which is then emitted
+        /// with frameCaptured
+        ///
+        /// The frame is also saved to `path` unless it is empty, in the
+        /// format given by its suffix.
+        #[qinvokable]
+        #[cxx_name = "captureFrame"]
+        fn capture_frame(self: Pin<&mut BevyQuickItem>, path: &QString);
+
+        /// Emitted with a frame asked for with captureFrame, and the path it
+        /// was saved to or an empty string if it was not saved
+        #[qsignal]
+        #[cxx_name = "frameCaptured"]
+        fn frame_captured(self: Pin<&mut BevyQuickItem>, image: QImage, path: QString);
+
+        /// Emitted after every update of the app the item shows, once the
+        /// item is in step with it
+        #[qsignal]
+        #[cxx_name = "sceneUpdated"]
+        fn scene_updated(self: Pin<&mut BevyQuickItem>);
+
+        /// Emitted when the GPU device of the main app was lost, with what the
+        /// driver said about it
+        #[qsignal]
+        #[cxx_name = "deviceLost"]
+        fn device_lost(self: Pin<&mut BevyQuickItem>, reason: QString);
+
+        /// Emitted once the main app was built anew with a new device after
+        /// deviceLost, and the item shows its frames again
+        #[qsignal]
+        #[cxx_name = "deviceRestored"]
+        fn device_restored(self: Pin<&mut BevyQuickItem>);
+
+        /// Emitted when the scene graph of the window was torn down, e.g. as
+        /// Qt lost its own graphics device, and the item shares its frames
+        /// anew
+        #[qsignal]
+        #[cxx_name = "sceneGraphReset"]
+        fn scene_graph_reset(self: Pin<&mut BevyQuickItem>);
+
+        /// Emitted when the item is clicked with the entity under the cursor,
+        /// or 0 if nothing was hit
+        #[qsignal]
+        #[cxx_name = "entityClicked"]
+        fn entity_clicked(self: Pin<&mut BevyQuickItem>, entity: u64);
+
+        /// Emitted when URLs are dropped onto the item, with the position of
+        /// the drop in scene coordinates
+        #[qsignal]
+        #[cxx_name = "filesDropped"]
+        fn files_dropped(self: Pin<&mut BevyQuickItem>, urls: QList_QUrl, scene_position: QPointF);
+
+        /// Define that we need to inherit hasActiveFocus() from the base class
+        #[inherit]
+        #[cxx_name = "hasActiveFocus"]
+        fn has_active_focus(self: &BevyQuickItem) -> bool;
+
+        /// Define that we need to inherit update() from the base class
+        #[inherit]
+        fn update(self: Pin<&mut BevyQuickItem>);
+
+        /// Define that we need to inherit width() from the base class
+        #[inherit]
+        fn width(self: &BevyQuickItem) -> f64;
+
+        /// Define that we need to inherit height() from the base class
+        #[inherit]
+        fn height(self: &BevyQuickItem) -> f64;
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        #[doc(hidden)]
+        #[rust_name = "quick_item_set_has_contents"]
+        fn quickItemSetHasContents(item: Pin<&mut BevyQuickItem>);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_pace_frames"]
+        fn quickItemPaceFrames(item: Pin<&mut BevyQuickItem>);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_track_window_visibility"]
+        fn quickItemTrackWindowVisibility(item: Pin<&mut BevyQuickItem>);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_request_update_on_resize"]
+        fn quickItemRequestUpdateOnResize(item: Pin<&mut BevyQuickItem>);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_request_update_on_scale_change"]
+        fn quickItemRequestUpdateOnScaleChange(item: Pin<&mut BevyQuickItem>);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_track_scene_graph"]
+        fn quickItemTrackSceneGraph(item: Pin<&mut BevyQuickItem>);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_accept_mouse_input"]
+        fn quickItemAcceptMouseInput(item: Pin<&mut BevyQuickItem>);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_accept_keyboard_input"]
+        fn quickItemAcceptKeyboardInput(item: Pin<&mut BevyQuickItem>);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_accept_touch_input"]
+        fn quickItemAcceptTouchInput(item: Pin<&mut BevyQuickItem>);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_accept_drops"]
+        fn quickItemAcceptDrops(item: Pin<&mut BevyQuickItem>);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_map_to_scene"]
+        fn quickItemMapToScene(item: &BevyQuickItem, position: &QPointF) -> QPointF;
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_force_active_focus"]
+        fn quickItemForceActiveFocus(item: Pin<&mut BevyQuickItem>);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_set_cursor_shape"]
+        fn quickItemSetCursorShape(item: Pin<&mut BevyQuickItem>, shape: i32);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_move_cursor"]
+        fn quickItemMoveCursor(item: Pin<&mut BevyQuickItem>, position: &QPointF);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_grab_mouse"]
+        fn quickItemGrabMouse(item: Pin<&mut BevyQuickItem>, grab: bool);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_set_accepts_input_method"]
+        fn quickItemSetAcceptsInputMethod(item: Pin<&mut BevyQuickItem>, accepts: bool);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_update_input_method"]
+        fn quickItemUpdateInputMethod(item: Pin<&mut BevyQuickItem>);
+
+        #[doc(hidden)]
+        #[rust_name = "input_method_event_preedit_string"]
+        fn inputMethodEventPreeditString(event: &QInputMethodEvent) -> QString;
+
+        #[doc(hidden)]
+        #[rust_name = "input_method_event_commit_string"]
+        fn inputMethodEventCommitString(event: &QInputMethodEvent) -> QString;
+
+        #[doc(hidden)]
+        #[rust_name = "input_method_event_cursor_position"]
+        fn inputMethodEventCursorPosition(event: &QInputMethodEvent) -> i32;
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_device_pixel_ratio"]
+        fn quickItemDevicePixelRatio(item: &BevyQuickItem) -> f64;
+
+        #[doc(hidden)]
+        #[rust_name = "mouse_event_position"]
+        fn mouseEventPosition(event: &QMouseEvent) -> QPointF;
+
+        #[doc(hidden)]
+        #[rust_name = "mouse_event_button"]
+        fn mouseEventButton(event: &QMouseEvent) -> i32;
+
+        #[doc(hidden)]
+        #[rust_name = "mouse_event_buttons"]
+        fn mouseEventButtons(event: &QMouseEvent) -> u32;
+
+        #[doc(hidden)]
+        #[rust_name = "mouse_event_modifiers"]
+        fn mouseEventModifiers(event: &QMouseEvent) -> u32;
+
+        #[doc(hidden)]
+        #[rust_name = "hover_event_position"]
+        fn hoverEventPosition(event: &QHoverEvent) -> QPointF;
+
+        #[doc(hidden)]
+        #[rust_name = "wheel_event_position"]
+        fn wheelEventPosition(event: &QWheelEvent) -> QPointF;
+
+        #[doc(hidden)]
+        #[rust_name = "wheel_event_modifiers"]
+        fn wheelEventModifiers(event: &QWheelEvent) -> u32;
+
+        #[doc(hidden)]
+        #[rust_name = "wheel_event_angle_delta"]
+        fn wheelEventAngleDelta(event: &QWheelEvent) -> QPoint;
+
+        #[doc(hidden)]
+        #[rust_name = "wheel_event_pixel_delta"]
+        fn wheelEventPixelDelta(event: &QWheelEvent) -> QPoint;
+
+        #[doc(hidden)]
+        #[rust_name = "key_event_key"]
+        fn keyEventKey(event: &QKeyEvent) -> i32;
+
+        #[doc(hidden)]
+        #[rust_name = "key_event_modifiers"]
+        fn keyEventModifiers(event: &QKeyEvent) -> u32;
+
+        #[doc(hidden)]
+        #[rust_name = "key_event_text"]
+        fn keyEventText(event: &QKeyEvent) -> QString;
+
+        #[doc(hidden)]
+        #[rust_name = "key_event_is_auto_repeat"]
+        fn keyEventIsAutoRepeat(event: &QKeyEvent) -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "touch_event_point_count"]
+        fn touchEventPointCount(event: &QTouchEvent) -> i32;
+
+        #[doc(hidden)]
+        #[rust_name = "touch_event_point_id"]
+        fn touchEventPointId(event: &QTouchEvent, index: i32) -> i32;
+
+        #[doc(hidden)]
+        #[rust_name = "touch_event_point_position"]
+        fn touchEventPointPosition(event: &QTouchEvent, index: i32) -> QPointF;
+
+        #[doc(hidden)]
+        #[rust_name = "touch_event_point_state"]
+        fn touchEventPointState(event: &QTouchEvent, index: i32) -> i32;
+
+        #[doc(hidden)]
+        #[rust_name = "touch_event_point_pressure"]
+        fn touchEventPointPressure(event: &QTouchEvent, index: i32) -> f64;
+
+        #[doc(hidden)]
+        #[rust_name = "drop_event_urls"]
+        fn dropEventUrls(event: &QDropEvent) -> QList_QUrl;
+
+        #[doc(hidden)]
+        #[rust_name = "drop_event_position"]
+        fn dropEventPosition(event: &QDropEvent) -> QPointF;
+
+        #[doc(hidden)]
+        #[rust_name = "drop_event_set_accepted"]
+        fn dropEventSetAccepted(event: Pin<&mut QDropEvent>, accepted: bool);
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_update_texture_node"]
+        unsafe fn quickItemUpdateTextureNode(
+            item: Pin<&mut BevyQuickItem>,
+            old_node: *mut QSGNode,
+            pixels: &[u8],
+            width: u32,
+            height: u32,
+            rect: &QRectF,
+        ) -> *mut QSGNode;
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/tablet.h");
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_forward_tablet_events"]
+        fn quickItemForwardTabletEvents(item: Pin<&mut BevyQuickItem>);
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/interop.h");
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_graphics_device"]
+        fn quickItemGraphicsDevice(
+            item: Pin<&mut BevyQuickItem>,
+            device_uuid: &mut [u8],
+            uuid_valid: &mut bool,
+        ) -> i32;
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_adopt_vulkan_device"]
+        fn quickItemAdoptVulkanDevice(
+            item: Pin<&mut BevyQuickItem>,
+            handshake: i32,
+            instance: u64,
+            physical_device: u64,
+            device: u64,
+            queue_family: u32,
+            queue_index: u32,
+        );
+
+        #[doc(hidden)]
+        #[rust_name = "quick_item_update_shared_texture_node"]
+        unsafe fn quickItemUpdateSharedTextureNode(
+            item: Pin<&mut BevyQuickItem>,
+            old_node: *mut QSGNode,
+            frame: &SharedTextureFrame,
+            rect: &QRectF,
+        ) -> *mut QSGNode;
+    }
+
+    /// A texture exported by the render world to import, or a frame to show,
+    /// see updateSharedTextureNode()
+    #[namespace = "bevyqml"]
+    struct SharedTextureFrame {
+        backend: i32,
+        buffer: i32,
+        handle: i64,
+        fence: i64,
+        key: u64,
+        allocation_size: u64,
+        width: u32,
+        height: u32,
+        /// Whether the scene graph waits for its GPU before handing the
+        /// buffer back
+        wait_idle: bool,
+        /// The [SharedTextureSlot] the buffer is handed back to
+        slot: usize,
+    }
+
+    #[namespace = "bevyqml"]
+    extern "Rust" {
+        /// Keep the slot alive for a node of the scene graph
+        #[cxx_name = "sharedTextureSlotRetain"]
+        unsafe fn shared_texture_slot_retain(slot: usize);
+
+        #[cxx_name = "sharedTextureSlotRelease"]
+        unsafe fn shared_texture_slot_release(slot: usize);
+
+        /// The frame drawing the buffer has ended
+        #[cxx_name = "sharedTextureHandBack"]
+        unsafe fn shared_texture_hand_back(slot: usize, buffer: i32);
+    }
+
+    impl cxx_qt::Threading for BevyQuickItem {}
+    impl cxx_qt::Constructor<()> for BevyQuickItem {}
+}
+
+mod capture;
+mod cursor;
+mod dynamic_resolution;
+mod events;
+mod pacing;
+mod resize;
+mod sharing;
+mod view;
+mod view_mode;
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::CxxQtType;
+use cxx_qt_lib::{QColor, QList, QPointF, QString, QVariant};
+
+use crate::{
+    convert::IntoQt,
+    grid::QmlGrid,
+    input::{self, raw_motion},
+    render::{
+        interop::{self, SharedTextureSlot},
+        FrameSink, QuickItemTarget, QuickItemView, ResizeMode,
+    },
+    runtime::{self, UpdateListener},
+    view_mode::QmlViewMode,
+};
+
+/// The Rust struct for the QQuickItem
+///
+/// The item shows the scene of the app hosted by [crate::runtime], which is
+/// started with [crate::plugin::BevyQmlPlugin]. Every item renders into a
+/// target of its own, with a window standing in for the item in the world,
+/// so sizing, input and picking follow the item they happen in. The modules
+/// next to this file describe each part of the item along with its state:
+/// `view` the cameras it shows, `resize` and `dynamic_resolution` the size
+/// of its target, `view_mode` the grid and debug renderings, `events` and
+/// `cursor` its input, `capture` reading frames back, `sharing` how frames
+/// reach the scene graph and `pacing` when the item updates:
+///
+/// ```qml
+/// BevyQuickItem {
+///     anchors.fill: parent
+///     focus: true
+///     selectOnClick: true
+///     onEntityClicked: entity => inspector.entity = entity
+/// }
+/// ```
+pub struct BevyQuickItemRust {
+    select_on_click: bool,
+    view: QString,
+    camera: u64,
+    viewports: QList<QVariant>,
+    world: QString,
+    resize_mode: qobject::ResizeMode,
+    render_width: i32,
+    render_height: i32,
+    super_sampling: f64,
+    aspect_ratio: f64,
+    resize_delay: i32,
+    dynamic_resolution: bool,
+    target_frame_ms: f64,
+    current_scale: f64,
+    show_grid: bool,
+    show_axes: bool,
+    grid_size: f64,
+    grid_subdivisions: i32,
+    grid_fade_distance: f64,
+    grid_color: QColor,
+    view_mode: qobject::ViewMode,
+    cursor_shape: i32,
+    cursor_visible: bool,
+    pointer_locked: bool,
+    interop: qobject::Interop,
+    interop_backend: qobject::Interop,
+    target: Option<Entity>,
+    /// The name of the app the target lives in
+    target_world: String,
+    /// The [runtime::generation] of the main app the target was spawned in
+    generation: u64,
+    sink: FrameSink,
+    resize: resize::ResizeState,
+    sharing: sharing::Sharing,
+    events: events::EventState,
+    pointer: cursor::PointerState,
+    captures: capture::Captures,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for BevyQuickItemRust {
+    fn default() -> Self {
+        let grid = QmlGrid::default();
+        Self {
+            select_on_click: false,
+            view: QString::default(),
+            camera: 0,
+            viewports: QList::default(),
+            world: QString::default(),
+            resize_mode: qobject::ResizeMode::Stretch,
+            render_width: 1920,
+            render_height: 1080,
+            super_sampling: 2.0,
+            aspect_ratio: 0.0,
+            resize_delay: 0,
+            dynamic_resolution: false,
+            target_frame_ms: 1000.0 / 60.0,
+            current_scale: 1.0,
+            show_grid: grid.grid,
+            show_axes: grid.axes,
+            grid_size: grid.size.into(),
+            grid_subdivisions: grid.subdivisions as i32,
+            grid_fade_distance: grid.fade_distance.into(),
+            grid_color: grid.color.into_qt(),
+            view_mode: qobject::ViewMode::Shaded,
+            cursor_shape: 0,
+            cursor_visible: true,
+            pointer_locked: false,
+            interop: qobject::Interop::Automatic,
+            interop_backend: qobject::Interop::Copy,
+            target: None,
+            target_world: String::new(),
+            generation: runtime::generation(),
+            sink: FrameSink::default(),
+            resize: Default::default(),
+            sharing: Default::default(),
+            events: Default::default(),
+            pointer: Default::default(),
+            captures: Default::default(),
+            update_listener: None,
+        }
+    }
+}
+
+impl Drop for BevyQuickItemRust {
+    fn drop(&mut self) {
+        let captures = std::mem::take(&mut self.captures);
+        // The main app may have been torn down since, along with the target
+        if self.target_world.is_empty() && self.generation != runtime::generation() {
+            return;
+        }
+        let target = self.target;
+        runtime::with_world_in(&self.target_world, |world| {
+            captures.stop(world);
+            if let Some(entity) = target {
+                world.despawn(entity);
+            }
+        });
+    }
+}
+
+impl cxx_qt::Initialize for qobject::BevyQuickItem {
+    fn initialize(mut self: Pin<&mut Self>) {
+        qobject::quick_item_set_has_contents(self.as_mut());
+        qobject::quick_item_track_scene_graph(self.as_mut());
+        self.as_mut()
+            .on_scene_graph_reset(|mut item| item.as_mut().reset_shared_textures())
+            .release();
+        self.as_mut()
+            .on_view_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_camera_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_viewports_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_world_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_resize_mode_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_render_width_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_render_height_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_super_sampling_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_aspect_ratio_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_dynamic_resolution_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_target_frame_ms_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_show_grid_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_show_axes_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_grid_size_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_grid_subdivisions_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_grid_fade_distance_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_grid_color_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_view_mode_changed(|_| runtime::request_update())
+            .release();
+        self.as_mut()
+            .on_cursor_shape_changed(|item| item.apply_cursor())
+            .release();
+        self.as_mut()
+            .on_cursor_visible_changed(|item| item.apply_cursor())
+            .release();
+        self.as_mut()
+            .on_pointer_locked_changed(|item| item.apply_pointer_lock())
+            .release();
+        qobject::quick_item_accept_mouse_input(self.as_mut());
+        qobject::quick_item_accept_keyboard_input(self.as_mut());
+        qobject::quick_item_accept_touch_input(self.as_mut());
+        qobject::quick_item_accept_drops(self.as_mut());
+        qobject::quick_item_forward_tablet_events(self.as_mut());
+        raw_motion::install();
+        self.as_mut()
+            .on_interop_changed(|mut item| {
+                // Ask the scene graph again with the next frame
+                item.as_mut().rust_mut().sharing.negotiated = false;
+                item.update();
+            })
+            .release();
+        if let Some(vulkan) = interop::adapter_identity().and_then(|identity| identity.vulkan) {
+            qobject::quick_item_adopt_vulkan_device(
+                self.as_mut(),
+                interop::vulkan_handshake() as i32,
+                vulkan.instance,
+                vulkan.physical_device,
+                vulkan.device,
+                vulkan.queue_family,
+                vulkan.queue_index,
+            );
+        }
+
+        self.follow_updates();
+    }
+}
+
+impl qobject::BevyQuickItem {
+    /// Show the latest Bevy frame in the scene graph
+    ///
+    /// Frames are sampled straight from the Bevy render target when the scene
+    /// graph renders on the same device, and are copied otherwise.
+    ///
+    /// # Safety
+    ///
+    /// Called by the scene graph with the node previously returned from this method.
+    pub unsafe fn update_paint_node(
+        mut self: Pin<&mut Self>,
+        old_node: *mut qobject::QSGNode,
+        _data: *mut qobject::QQuickItemUpdatePaintNodeData,
+    ) -> *mut qobject::QSGNode {
+        let _span = info_span!("BevyQuickItem update paint node").entered();
+        if !self.rust().sharing.negotiated {
+            self.as_mut().negotiate_backend();
+        }
+
+        let rect = self.content_rect().into_qt();
+        if let Some(node) = self.as_mut().update_shared_node(old_node, &rect) {
+            return node;
+        }
+
+        let frame = self.rust().sink.take().unwrap_or_default();
+        qobject::quick_item_update_texture_node(
+            self,
+            old_node,
+            &frame.data,
+            frame.width,
+            frame.height,
+            &rect,
+        )
+    }
+
+    /// Keep the render target in the world in step with the item and schedule a repaint
+    fn sync(mut self: Pin<&mut Self>) {
+        let _span = info_span!("BevyQuickItem sync").entered();
+        let device_pixel_ratio = qobject::quick_item_device_pixel_ratio(&self) as f32;
+        let policy = self.resize_policy();
+        let logical_size = self.content_rect().size();
+        let wanted = policy.target_size(logical_size, device_pixel_ratio);
+        let scale_factor = policy.scale_factor(device_pixel_ratio);
+
+        let world_name = self.world().to_string();
+        if world_name != self.rust().target_world {
+            // Move to the other app by starting over with a new target there
+            let captures = std::mem::take(&mut self.as_mut().rust_mut().captures);
+            let target = self.as_mut().rust_mut().target.take();
+            self.with_target_world(|world| {
+                captures.stop(world);
+                if let Some(entity) = target {
+                    world.despawn(entity);
+                }
+            });
+            let mut rust = self.as_mut().rust_mut();
+            rust.target_world = world_name;
+            rust.resize = Default::default();
+        }
+        if self.rust().target_world.is_empty() && self.rust().generation != runtime::generation() {
+            self.as_mut().start_over_after_device_loss();
+        }
+
+        let dynamic_scale = self.as_mut().sync_dynamic_resolution();
+        let settled = self.as_mut().settle_size(wanted);
+        let size = (settled.as_vec2() * dynamic_scale)
+            .round()
+            .max(Vec2::ONE)
+            .as_uvec2();
+        let scale_factor = scale_factor * dynamic_scale;
+        // The window maps the cursor onto the pixels of the target, which the
+        // scale factor misses for fixed resolutions and delayed resizes
+        let window_scale = if size == wanted && !matches!(policy, ResizeMode::FixedResolution(_))
+        {
+            scale_factor
+        } else {
+            size.x as f32 / logical_size.x.max(1.0)
+        };
+
+        let target = self.rust().target;
+        let sink = self.rust().sink.clone();
+        let backend = self.rust().sharing.backend;
+        let shared = self.rust().sharing.shared.clone();
+        let view = QuickItemView {
+            name: self.view().to_string(),
+            camera: Entity::try_from_bits(*self.camera()).ok(),
+            viewports: self.viewport_layout(),
+        };
+        let grid = self.grid();
+        let view_mode = self.view_mode_component();
+        let target = self.with_target_world(|world| match target {
+            Some(entity) => {
+                if let Some(mut target) = world.get_mut::<QuickItemTarget>(entity) {
+                    if target.size != size {
+                        target.size = size;
+                    }
+                    if target.scale_factor != scale_factor {
+                        target.scale_factor = scale_factor;
+                    }
+                    if target.backend != backend {
+                        target.backend = backend;
+                    }
+                }
+                if world.get::<QuickItemView>(entity) != Some(&view) {
+                    world.entity_mut(entity).insert(view);
+                }
+                if world.get::<QmlGrid>(entity) != Some(&grid) {
+                    world.entity_mut(entity).insert(grid);
+                }
+                if world.get::<QmlViewMode>(entity) != Some(&view_mode) {
+                    world.entity_mut(entity).insert(view_mode);
+                }
+                if let Some(mut window) = world.get_mut::<Window>(entity) {
+                    input::resize_item_window(&mut window, logical_size, window_scale);
+                }
+                entity
+            }
+            None => {
+                let mut target =
+                    QuickItemTarget::new(&mut world.resource_mut::<Assets<Image>>(), size, sink);
+                target.scale_factor = scale_factor;
+                target.backend = backend;
+                target.shared = shared;
+                let entity = world.spawn((target, view, grid, view_mode)).id();
+                input::attach_item_window(
+                    world,
+                    entity,
+                    input::item_window(logical_size, window_scale),
+                );
+                entity
+            }
+        });
+
+        if target.is_some() {
+            self.as_mut().rust_mut().target = target;
+        }
+        self.as_mut().sync_cursor();
+        self.as_mut().sync_ime();
+        self.as_mut().finish_captures();
+        self.as_mut().update();
+        self.scene_updated();
+    }
+
+    /// Spawn a new target once the main app lost its device, as the target
+    /// went with the app
+    fn start_over_after_device_loss(mut self: Pin<&mut Self>) {
+        let generation = runtime::generation();
+        let mut rust = self.as_mut().rust_mut();
+        rust.generation = generation;
+        rust.target = None;
+        rust.resize = Default::default();
+        rust.captures = Default::default();
+        rust.pointer.synced = None;
+        // Nodes still showing frames of the lost device keep the old slot
+        rust.sharing.shared = SharedTextureSlot::default();
+        rust.sharing.negotiated = false;
+
+        let reason = runtime::device_loss().unwrap_or_default();
+        self.as_mut().device_lost(QString::from(reason.as_str()));
+        if runtime::is_running() {
+            self.as_mut().device_restored();
+        }
+    }
+
+    /// Run the closure with the world of the app this item shows
+    fn with_target_world<R>(&self, f: impl FnOnce(&mut World) -> R) -> Option<R> {
+        runtime::with_world_in(&self.rust().target_world, f)
+    }
+
+    /// Run the closure with the world and the window standing in for this item
+    ///
+    /// This forwards input, so it also asks for an update.
+    fn with_item_window(&self, f: impl FnOnce(&mut World, Entity)) {
+        if let Some(window) = self.rust().target {
+            self.with_target_world(|world| f(world, window));
+            runtime::request_update();
+        }
+    }
+}
+
+fn to_vec2(point: &QPointF) -> Vec2 {
+    Vec2::new(point.x() as f32, point.y() as f32)
+}
+
+/// # Safety
+///
+/// Called by the scene graph with a slot from [SharedTextureSlot::as_raw] of
+/// the item it is drawing.
+unsafe fn shared_texture_slot_retain(slot: usize) {
+    SharedTextureSlot::retain_raw(slot);
+}
+
+/// # Safety
+///
+/// Called by the scene graph with a slot it retained.
+unsafe fn shared_texture_slot_release(slot: usize) {
+    SharedTextureSlot::release_raw(slot);
+}
+
+/// # Safety
+///
+/// Called by the scene graph with a slot it retained.
+unsafe fn shared_texture_hand_back(slot: usize, buffer: i32) {
+    SharedTextureSlot::hand_back_raw(slot, buffer as usize);
+}
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Reading frames of a `BevyQuickItem` back for QML.
+//!
+//! `captureFrame` reads back the next frame the item shows, at the size of
+//! its render target, and emits it with `frameCaptured`. The frame is saved
+//! as well unless the path is empty, in the format given by its suffix, and
+//! `frameCaptured` tells where it went, or an empty path if saving failed:
+//!
+//! ```qml
+//! BevyQuickItem {
+//!     id: view
+//!     Shortcut {
+//!         sequence: "F12"
+//!         onActivated: view.captureFrame(StandardPaths.writableLocation(StandardPaths.PicturesLocation) + "/bevy.png")
+//!     }
+//!     onFrameCaptured: (image, path) => console.log("saved a frame to", path)
+//! }
+//! ```
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::CxxQtType;
+use cxx_qt_lib::QString;
+
+use super::qobject;
+use crate::{
+    image,
+    render::{FrameCapture, QuickItemTarget},
+    runtime,
+};
+
+/// The frames asked for with captureFrame, and where to save them
+#[derive(Default)]
+pub(super) struct Captures(Vec<(FrameCapture, String)>);
+
+impl Captures {
+    /// Stop reading back the frames still asked for
+    pub(super) fn stop(self, world: &mut World) {
+        for (capture, _) in self.0 {
+            capture.stop(world);
+        }
+    }
+}
+
+impl qobject::BevyQuickItem {
+    pub fn capture_frame(mut self: Pin<&mut Self>, path: &QString) {
+        let capture = self.rust().target.and_then(|target| {
+            self.with_target_world(|world| {
+                let image = world.get::<QuickItemTarget>(target)?.image.clone();
+                Some(FrameCapture::start(world, image))
+            })
+            .flatten()
+        });
+        let Some(capture) = capture else {
+            warn!("BevyQuickItem cannot capture a frame before it shows one");
+            return;
+        };
+        self.as_mut()
+            .rust_mut()
+            .captures
+            .0
+            .push((capture, path.to_string()));
+        runtime::request_update();
+    }
+
+    /// Emit the frames which arrived for captureFrame
+    pub(super) fn finish_captures(mut self: Pin<&mut Self>) {
+        let Captures(captures) = std::mem::take(&mut self.as_mut().rust_mut().captures);
+        let mut pending = Vec::new();
+        for (capture, path) in captures {
+            let Some(frame) = capture.take() else {
+                pending.push((capture, path));
+                continue;
+            };
+            self.with_target_world(|world| capture.stop(world));
+
+            let qimage = image::qimage_from_frame(&frame);
+            let saved = if path.is_empty() {
+                String::new()
+            } else if image::save_qimage(&qimage, &path) {
+                path
+            } else {
+                warn!("BevyQuickItem failed to save a captured frame to {path:?}");
+                String::new()
+            };
+            self.as_mut()
+                .frame_captured(qimage, QString::from(saved.as_str()));
+        }
+        // Keep the frames asked for while emitting as well
+        self.as_mut().rust_mut().captures.0.extend(pending);
+    }
+}
@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! The cursor shown over a `BevyQuickItem` and locking the pointer to it.
+//!
+//! `cursorShape` is the Qt.CursorShape shown over the item and
+//! `cursorVisible` hides it. `pointerLocked` hides the cursor and holds it in
+//! the middle of the item, so mouse moves only reach Bevy as
+//! [bevy::input::mouse::MouseMotion], as FPS-style cameras want it. The lock
+//! is released as soon as the item loses the focus, e.g. when its window is
+//! deactivated. All three follow the [Cursor] of the window standing in for
+//! the item, which Bevy systems may set as they would for a native window,
+//! with [CursorGrabMode::Confined] locking the pointer as well:
+//!
+//! ```qml
+//! BevyQuickItem {
+//!     id: view
+//!     cursorShape: pointerLocked ? Qt.ArrowCursor : Qt.CrossCursor
+//!     TapHandler {
+//!         acceptedButtons: Qt.RightButton
+//!         onTapped: view.pointerLocked = true
+//!     }
+//!     Keys.onEscapePressed: pointerLocked = false
+//! }
+//! ```
+
+use core::pin::Pin;
+
+use bevy::{
+    prelude::*,
+    window::{Cursor, CursorGrabMode, CursorIcon},
+};
+use cxx_qt::CxxQtType;
+use cxx_qt_lib::QPointF;
+
+use super::{qobject, to_vec2};
+use crate::{convert::IntoQt, input::mouse, runtime};
+
+/// Qt::BlankCursor, shown while the cursor is hidden or the pointer locked
+const BLANK_CURSOR: i32 = 10;
+
+/// How far the cursor of the item window is in step with the properties
+#[derive(Default)]
+pub(super) struct PointerState {
+    /// The window the cursor was last synced with, and its cursor by then
+    pub(super) synced: Option<(Entity, CursorState)>,
+    /// Where the locked pointer is held, in item coordinates
+    pub(super) lock_anchor: Option<Vec2>,
+}
+
+/// The part of the [Cursor] of the item window the cursor properties follow
+#[derive(Clone, Copy, PartialEq)]
+pub(super) struct CursorState {
+    icon: CursorIcon,
+    visible: bool,
+    grab_mode: CursorGrabMode,
+}
+
+impl CursorState {
+    fn write(&self, cursor: &mut Cursor) {
+        cursor.icon = self.icon;
+        cursor.visible = self.visible;
+        cursor.grab_mode = self.grab_mode;
+    }
+}
+
+impl From<&Cursor> for CursorState {
+    fn from(cursor: &Cursor) -> Self {
+        Self {
+            icon: cursor.icon,
+            visible: cursor.visible,
+            grab_mode: cursor.grab_mode,
+        }
+    }
+}
+
+impl qobject::BevyQuickItem {
+    /// Bring the cursor properties and the cursor of the item window in
+    /// line, whichever of them changed since the last update
+    pub(super) fn sync_cursor(mut self: Pin<&mut Self>) {
+        let Some(window) = self.rust().target else {
+            return;
+        };
+        let Some(current) = self
+            .with_target_world(|world| {
+                world
+                    .get::<Window>(window)
+                    .map(|window| CursorState::from(&window.cursor))
+            })
+            .flatten()
+        else {
+            return;
+        };
+        let synced = self
+            .rust()
+            .pointer
+            .synced
+            .filter(|(synced_window, _)| *synced_window == window)
+            .map(|(_, cursor)| cursor);
+
+        if synced.is_some_and(|synced| synced != current) {
+            // Changed by Bevy, the shape is only taken over if it differs in
+            // Bevy's terms, so shapes Bevy has no icon for are kept
+            if mouse::cursor_icon_from_qt(*self.cursor_shape()) != current.icon {
+                self.as_mut()
+                    .set_cursor_shape(mouse::qt_cursor_shape(current.icon));
+            }
+            self.as_mut().set_cursor_visible(current.visible);
+            self.as_mut()
+                .set_pointer_locked(current.grab_mode != CursorGrabMode::None);
+            self.as_mut().rust_mut().pointer.synced = Some((window, current));
+            return;
+        }
+
+        let wanted = CursorState {
+            icon: mouse::cursor_icon_from_qt(*self.cursor_shape()),
+            visible: *self.cursor_visible(),
+            grab_mode: match (*self.pointer_locked(), current.grab_mode) {
+                (false, _) => CursorGrabMode::None,
+                (true, CursorGrabMode::None) => CursorGrabMode::Locked,
+                (true, grab_mode) => grab_mode,
+            },
+        };
+        if wanted != current {
+            self.with_target_world(|world| {
+                if let Some(mut window) = world.get_mut::<Window>(window) {
+                    wanted.write(&mut window.cursor);
+                }
+            });
+        }
+        self.as_mut().rust_mut().pointer.synced = Some((window, wanted));
+    }
+
+    /// Show the cursor asked for by the properties
+    pub(super) fn apply_cursor(self: Pin<&mut Self>) {
+        let shape = if *self.cursor_visible() && !*self.pointer_locked() {
+            *self.cursor_shape()
+        } else {
+            BLANK_CURSOR
+        };
+        qobject::quick_item_set_cursor_shape(self, shape);
+        runtime::request_update();
+    }
+
+    /// Lock the pointer to the middle of the item or release it
+    pub(super) fn apply_pointer_lock(mut self: Pin<&mut Self>) {
+        let locked = *self.pointer_locked();
+        if locked == self.rust().pointer.lock_anchor.is_some() {
+            return;
+        }
+        if locked {
+            if !self.has_active_focus() {
+                qobject::quick_item_force_active_focus(self.as_mut());
+            }
+            let anchor = Vec2::new(self.width() as f32, self.height() as f32) / 2.0;
+            self.as_mut().rust_mut().pointer.lock_anchor = Some(anchor);
+            qobject::quick_item_grab_mouse(self.as_mut(), true);
+            qobject::quick_item_move_cursor(self.as_mut(), &anchor.into_qt());
+            let position = self.content_position(anchor);
+            self.with_item_window(|world, window| mouse::cursor_moved(world, window, position));
+        } else {
+            self.as_mut().rust_mut().pointer.lock_anchor = None;
+            qobject::quick_item_grab_mouse(self.as_mut(), false);
+        }
+        self.apply_cursor();
+    }
+
+    /// Report a move of the locked pointer as motion and put the cursor
+    /// back, returns whether the pointer is locked
+    pub(super) fn locked_motion(self: Pin<&mut Self>, position: &QPointF) -> bool {
+        let Some(anchor) = self.rust().pointer.lock_anchor else {
+            return false;
+        };
+        let delta = to_vec2(position) - anchor;
+        // Putting the cursor back moves it as well
+        if delta != Vec2::ZERO {
+            self.with_item_window(|world, _| mouse::motion(world, delta));
+            qobject::quick_item_move_cursor(self, &anchor.into_qt());
+        }
+        true
+    }
+}
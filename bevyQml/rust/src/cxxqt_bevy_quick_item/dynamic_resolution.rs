@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Rendering a `BevyQuickItem` at a lower resolution while frames are slow.
+//!
+//! With `dynamicResolution` set, the render target shrinks down to half its
+//! size while frames take longer than `targetFrameMs` milliseconds, and
+//! grows back once they are fast again, see
+//! [crate::render::DynamicResolution]. The frames are stretched over the
+//! item with linear filtering, and `currentScale` tells the scale they are
+//! rendered at:
+//!
+//! ```qml
+//! BevyQuickItem {
+//!     dynamicResolution: settings.adaptiveQuality
+//!     targetFrameMs: 1000 / 30
+//!     Label {
+//!         visible: parent.currentScale < 1
+//!         text: qsTr("Rendering at %1%").arg(Math.round(parent.currentScale * 100))
+//!     }
+//! }
+//! ```
+
+use core::pin::Pin;
+
+use cxx_qt::CxxQtType;
+
+use super::qobject;
+use crate::render::DynamicResolution;
+
+impl qobject::BevyQuickItem {
+    /// Bring the dynamic resolution of the target in step with the item, and
+    /// return the scale to render at
+    pub(super) fn sync_dynamic_resolution(mut self: Pin<&mut Self>) -> f32 {
+        let target = self.rust().target;
+        let enabled = *self.dynamic_resolution();
+        let target_frame_ms = *self.target_frame_ms() as f32;
+        let scale = self
+            .with_target_world(|world| {
+                let mut entity = world.get_entity_mut(target?)?;
+                if !enabled {
+                    entity.remove::<DynamicResolution>();
+                    return None;
+                }
+                match entity.get_mut::<DynamicResolution>() {
+                    Some(mut resolution) => {
+                        if resolution.target_frame_ms != target_frame_ms {
+                            resolution.target_frame_ms = target_frame_ms;
+                        }
+                        Some(resolution.scale())
+                    }
+                    None => {
+                        entity.insert(DynamicResolution::new(target_frame_ms));
+                        Some(1.0)
+                    }
+                }
+            })
+            .flatten()
+            .unwrap_or(1.0);
+
+        if *self.current_scale() != f64::from(scale) {
+            self.as_mut().set_current_scale(scale.into());
+        }
+        scale
+    }
+}
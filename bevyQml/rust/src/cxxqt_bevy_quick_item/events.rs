@@ -0,0 +1,583 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Forwarding the input of a `BevyQuickItem` to the window standing in for
+//! it in the world.
+//!
+//! Mouse, wheel, keyboard, touch and focus events reach Bevy as they would
+//! for a native window. Pointer and keyboard input on the meshes of
+//! interactive [crate::qml_texture::QmlTexture] panels goes to their QML
+//! scenes instead, and handles of the transform gizmo take the left button
+//! for themselves, see [crate::transform_gizmo].
+//!
+//! With `selectOnClick` set, clicking an entity selects it in the
+//! [crate::selection::Selection], clicking it with Ctrl held toggles it and
+//! clicking the background clears the selection. Every click emits
+//! `entityClicked` with the entity under the cursor.
+//!
+//! While a Bevy text field sets [Window::ime_enabled] on the window of the
+//! item, the item takes text from input methods and virtual keyboards and
+//! reports it as [bevy::window::Ime] events, see [crate::input::ime]. The
+//! pens and erasers of graphics tablets are reported as
+//! [crate::input::stylus::StylusInput] events with their pressure and tilt,
+//! as well as mouse events.
+//!
+//! Local files dragged onto the item are reported to Bevy as
+//! [bevy::window::FileDragAndDrop] events of its window, see
+//! [crate::input::drop] for loading them as assets. Every drop of URLs is
+//! also emitted with `filesDropped`:
+//!
+//! ```qml
+//! BevyQuickItem {
+//!     onFilesDropped: (urls, scenePosition) => {
+//!         const position = mapFromItem(null, scenePosition);
+//!         const hit = pick(position.x, position.y);
+//!         for (const url of urls)
+//!             placeModel(url, hit.position);
+//!     }
+//! }
+//! ```
+
+use core::pin::Pin;
+use std::path::PathBuf;
+
+use bevy::{input::ButtonState, prelude::*};
+use cxx_qt::CxxQtType;
+use cxx_qt_lib::{QList, QPointF, QRectF, QString, QUrl, QVariant};
+
+use super::{qobject, to_vec2};
+use crate::{
+    input::{
+        drop, ime,
+        keyboard::{self, QtKey},
+        mouse,
+        stylus::{self, StylusInput, StylusPhase, StylusTool},
+        touch::{self, QtTouchPoint},
+    },
+    qml_texture, runtime, selection, transform_gizmo,
+};
+
+/// How far the cursor may move between press and release of a click, in
+/// logical pixels
+const CLICK_DISTANCE: f32 = 4.0;
+
+/// Qt::ControlModifier, which extends the selection when clicking
+const CONTROL_MODIFIER: u32 = 0x0400_0000;
+
+/// What the item keeps track of between input events
+#[derive(Default)]
+pub(super) struct EventState {
+    /// Where the left button was pressed, for telling clicks from drags
+    press_position: Option<Vec2>,
+    /// Whether the left button grabbed a handle of the transform gizmo
+    gizmo_grabbed: bool,
+    /// Whether Bevy was told about files being dragged over the item
+    hovering_files: bool,
+    /// Whether the window asks for an input method, and where its
+    /// candidate window goes
+    ime: (bool, Vec2),
+    /// Whether text is being composed with the input method
+    preediting: bool,
+    /// The last report of the stylus while it is near the tablet
+    stylus: Option<StylusInput>,
+}
+
+impl qobject::BevyQuickItem {
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn mouse_press_event(mut self: Pin<&mut Self>, event: *mut qobject::QMouseEvent) {
+        if !self.has_active_focus() {
+            qobject::quick_item_force_active_focus(self.as_mut());
+        }
+        let event = &*event;
+        if self.route_mouse_to_panel(event, qml_texture::MOUSE_BUTTON_PRESS) {
+            return;
+        }
+        if mouse::mouse_button_from_qt(qobject::mouse_event_button(event))
+            == Some(MouseButton::Left)
+        {
+            let position = to_vec2(&qobject::mouse_event_position(event));
+            // Handles of the transform gizmo take the button for themselves
+            let grabbed = self
+                .with_image_position(position, |world, image, position| {
+                    Some(transform_gizmo::press(world, image, position))
+                })
+                .unwrap_or(false);
+            if grabbed {
+                self.as_mut().rust_mut().events.gizmo_grabbed = true;
+                runtime::request_update();
+                return;
+            }
+            self.as_mut().rust_mut().events.press_position = Some(position);
+        }
+        self.forward_mouse_button(event, ButtonState::Pressed);
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn mouse_double_click_event(self: Pin<&mut Self>, event: *mut qobject::QMouseEvent) {
+        let event = &*event;
+        if self.route_mouse_to_panel(event, qml_texture::MOUSE_BUTTON_DOUBLE_CLICK) {
+            return;
+        }
+        // Qt replaces the second press with the double click, Bevy only
+        // knows about presses
+        self.forward_mouse_button(event, ButtonState::Pressed);
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn mouse_release_event(mut self: Pin<&mut Self>, event: *mut qobject::QMouseEvent) {
+        let event = &*event;
+        if self.rust().events.gizmo_grabbed
+            && mouse::mouse_button_from_qt(qobject::mouse_event_button(event))
+                == Some(MouseButton::Left)
+        {
+            self.as_mut().rust_mut().events.gizmo_grabbed = false;
+            self.with_target_world(transform_gizmo::release);
+            runtime::request_update();
+            return;
+        }
+        if self.route_mouse_to_panel(event, qml_texture::MOUSE_BUTTON_RELEASE) {
+            self.as_mut().rust_mut().events.press_position = None;
+            return;
+        }
+        self.forward_mouse_button(event, ButtonState::Released);
+
+        if mouse::mouse_button_from_qt(qobject::mouse_event_button(event))
+            != Some(MouseButton::Left)
+        {
+            return;
+        }
+        let position = to_vec2(&qobject::mouse_event_position(event));
+        let pressed_at = self.as_mut().rust_mut().events.press_position.take();
+        if pressed_at.is_some_and(|pressed_at| pressed_at.distance(position) <= CLICK_DISTANCE) {
+            let entity = self.pick_entity(position).map(|hit| hit.entity);
+            if *self.select_on_click() {
+                let extend = qobject::mouse_event_modifiers(event) & CONTROL_MODIFIER != 0;
+                self.with_target_world(|world| {
+                    selection::select_clicked(world, entity, extend);
+                });
+                runtime::request_update();
+            }
+            self.entity_clicked(entity.map_or(0, Entity::to_bits));
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn mouse_move_event(mut self: Pin<&mut Self>, event: *mut qobject::QMouseEvent) {
+        let event = &*event;
+        let position = qobject::mouse_event_position(event);
+        if self.as_mut().locked_motion(&position) {
+            return;
+        }
+        if self.route_mouse_to_panel(event, qml_texture::MOUSE_MOVE) {
+            return;
+        }
+        if self.rust().events.gizmo_grabbed {
+            self.with_image_position(to_vec2(&position), |world, image, position| {
+                Some(transform_gizmo::drag(world, image, position))
+            });
+            runtime::request_update();
+        }
+        self.forward_cursor(position);
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn hover_enter_event(self: Pin<&mut Self>, event: *mut qobject::QHoverEvent) {
+        let position = qobject::hover_event_position(&*event);
+        self.with_item_window(|world, window| mouse::cursor_entered(world, window));
+        self.forward_cursor(position);
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn hover_move_event(mut self: Pin<&mut Self>, event: *mut qobject::QHoverEvent) {
+        let position = qobject::hover_event_position(&*event);
+        if self.as_mut().locked_motion(&position) {
+            return;
+        }
+        if self.route_hover_to_panel(to_vec2(&position)) {
+            return;
+        }
+        self.with_image_position(to_vec2(&position), |world, image, position| {
+            transform_gizmo::hover(world, image, position);
+            Some(())
+        });
+        self.forward_cursor(position);
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn hover_leave_event(self: Pin<&mut Self>, _event: *mut qobject::QHoverEvent) {
+        // The locked pointer only slips out between two moves
+        if self.rust().pointer.lock_anchor.is_some() {
+            return;
+        }
+        self.with_item_window(|world, window| mouse::cursor_left(world, window));
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn wheel_event(self: Pin<&mut Self>, event: *mut qobject::QWheelEvent) {
+        let event = &*event;
+        let angle_delta = qobject::wheel_event_angle_delta(event);
+        let pixel_delta = qobject::wheel_event_pixel_delta(event);
+        if self.has_panels() {
+            let hit = self.pick_entity(to_vec2(&qobject::wheel_event_position(event)));
+            let delta = IVec2::new(angle_delta.x(), angle_delta.y());
+            let modifiers = qobject::wheel_event_modifiers(event);
+            if qml_texture::route_wheel(hit.as_ref(), delta, modifiers) {
+                return;
+            }
+        }
+        let angle_delta = Vec2::new(angle_delta.x() as f32, angle_delta.y() as f32);
+        let pixel_delta = Vec2::new(pixel_delta.x() as f32, pixel_delta.y() as f32);
+        self.with_item_window(|world, window| {
+            mouse::wheel(world, window, angle_delta, pixel_delta)
+        });
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn key_press_event(self: Pin<&mut Self>, event: *mut qobject::QKeyEvent) {
+        self.forward_key(&*event, ButtonState::Pressed);
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn key_release_event(self: Pin<&mut Self>, event: *mut qobject::QKeyEvent) {
+        self.forward_key(&*event, ButtonState::Released);
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn focus_in_event(self: Pin<&mut Self>, _event: *mut qobject::QFocusEvent) {
+        let (ime_enabled, _) = self.rust().events.ime;
+        self.with_item_window(|world, window| {
+            keyboard::focus_changed(world, window, true);
+            if ime_enabled {
+                ime::enabled_changed(world, window, true);
+            }
+        });
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn focus_out_event(mut self: Pin<&mut Self>, _event: *mut qobject::QFocusEvent) {
+        let (ime_enabled, _) = self.rust().events.ime;
+        let preediting = std::mem::take(&mut self.as_mut().rust_mut().events.preediting);
+        self.with_item_window(|world, window| {
+            keyboard::focus_changed(world, window, false);
+            if preediting {
+                ime::preedit(world, window, String::new(), None);
+            }
+            if ime_enabled {
+                ime::enabled_changed(world, window, false);
+            }
+        });
+        self.set_pointer_locked(false);
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn input_method_event(
+        mut self: Pin<&mut Self>,
+        event: *mut qobject::QInputMethodEvent,
+    ) {
+        let event = &*event;
+        let commit = qobject::input_method_event_commit_string(event).to_string();
+        let preedit = qobject::input_method_event_preedit_string(event).to_string();
+        let cursor = usize::try_from(qobject::input_method_event_cursor_position(event)).ok();
+        let was_preediting = self.rust().events.preediting;
+        self.as_mut().rust_mut().events.preediting = !preedit.is_empty();
+        self.with_item_window(|world, window| {
+            if !commit.is_empty() {
+                ime::commit(world, window, commit);
+            }
+            if !preedit.is_empty() || was_preediting {
+                ime::preedit(world, window, preedit, cursor);
+            }
+        });
+    }
+
+    pub fn input_method_query(&self, query: qobject::InputMethodQuery) -> QVariant {
+        use qobject::InputMethodQuery;
+
+        let (enabled, position) = self.rust().events.ime;
+        if query == InputMethodQuery::ImEnabled {
+            QVariant::from(&enabled)
+        } else if query == InputMethodQuery::ImCursorRectangle {
+            let position = position + self.content_rect().min;
+            QVariant::from(&QRectF::new(position.x.into(), position.y.into(), 1.0, 1.0))
+        } else if query == InputMethodQuery::ImCursorPosition
+            || query == InputMethodQuery::ImAnchorPosition
+        {
+            // Bevy does not tell what the text field holds
+            QVariant::from(&0)
+        } else if query == InputMethodQuery::ImSurroundingText
+            || query == InputMethodQuery::ImCurrentSelection
+        {
+            QVariant::from(&QString::default())
+        } else {
+            QVariant::default()
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn touch_event(self: Pin<&mut Self>, event: *mut qobject::QTouchEvent) {
+        let event = &*event;
+        let points: Vec<_> = (0..qobject::touch_event_point_count(event))
+            .map(|index| QtTouchPoint {
+                id: qobject::touch_event_point_id(event, index),
+                position: self
+                    .content_position(to_vec2(&qobject::touch_event_point_position(event, index))),
+                state: qobject::touch_event_point_state(event, index),
+                pressure: qobject::touch_event_point_pressure(event, index),
+            })
+            .collect();
+        self.with_item_window(|world, window| touch::touch(world, window, &points));
+    }
+
+    pub fn touch_ungrab_event(self: Pin<&mut Self>) {
+        self.with_item_window(touch::cancel);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn tablet_event(
+        mut self: Pin<&mut Self>,
+        event_type: i32,
+        position: &QPointF,
+        pressure: f64,
+        x_tilt: f64,
+        y_tilt: f64,
+        rotation: f64,
+        tool: i32,
+        tool_id: i64,
+    ) {
+        let Some(phase) = StylusPhase::from_qt(event_type) else {
+            return;
+        };
+        let Some(window) = self.rust().target else {
+            return;
+        };
+        let input = StylusInput {
+            window,
+            phase,
+            position: self.content_position(to_vec2(position)),
+            pressure: pressure as f32,
+            tilt: Vec2::new(x_tilt as f32, y_tilt as f32),
+            rotation: rotation as f32,
+            tool: StylusTool::from_qt(tool),
+            tool_id,
+        };
+        let entered = self.rust().events.stylus.is_none().then(|| StylusInput {
+            phase: StylusPhase::Entered,
+            ..input.clone()
+        });
+        self.as_mut().rust_mut().events.stylus = Some(input.clone());
+        self.with_item_window(|world, _| {
+            if let Some(entered) = entered {
+                stylus::stylus(world, entered);
+            }
+            stylus::stylus(world, input);
+        });
+    }
+
+    pub fn tablet_proximity_left(mut self: Pin<&mut Self>) {
+        let Some(last) = self.as_mut().rust_mut().events.stylus.take() else {
+            return;
+        };
+        self.with_item_window(|world, _| {
+            stylus::stylus(
+                world,
+                StylusInput {
+                    phase: StylusPhase::Left,
+                    pressure: 0.0,
+                    ..last
+                },
+            )
+        });
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn drag_enter_event(mut self: Pin<&mut Self>, event: *mut qobject::QDragEnterEvent) {
+        // QDragEnterEvent derives from QDropEvent
+        let mut event = Pin::new_unchecked(&mut *(event as *mut qobject::QDropEvent));
+        let urls = qobject::drop_event_urls(&event);
+        qobject::drop_event_set_accepted(event.as_mut(), !urls.is_empty());
+        if urls.is_empty() {
+            return;
+        }
+
+        let paths = local_paths(&urls);
+        let position = qobject::drop_event_position(&event);
+        if !paths.is_empty() {
+            self.as_mut().rust_mut().events.hovering_files = true;
+            self.with_item_window(|world, window| drop::hovered(world, window, &paths));
+        }
+        self.forward_cursor(position);
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn drag_move_event(self: Pin<&mut Self>, event: *mut qobject::QDragMoveEvent) {
+        // QDragMoveEvent derives from QDropEvent
+        let mut event = Pin::new_unchecked(&mut *(event as *mut qobject::QDropEvent));
+        qobject::drop_event_set_accepted(event.as_mut(), true);
+        self.forward_cursor(qobject::drop_event_position(&event));
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn drag_leave_event(
+        mut self: Pin<&mut Self>,
+        _event: *mut qobject::QDragLeaveEvent,
+    ) {
+        if std::mem::take(&mut self.as_mut().rust_mut().events.hovering_files) {
+            self.with_item_window(drop::canceled);
+        }
+        self.with_item_window(|world, window| mouse::cursor_left(world, window));
+    }
+
+    /// # Safety
+    ///
+    /// Called by Qt with a valid event.
+    pub unsafe fn drop_event(mut self: Pin<&mut Self>, event: *mut qobject::QDropEvent) {
+        let mut event = Pin::new_unchecked(&mut *event);
+        let urls = qobject::drop_event_urls(&event);
+        qobject::drop_event_set_accepted(event.as_mut(), !urls.is_empty());
+        self.as_mut().rust_mut().events.hovering_files = false;
+        if urls.is_empty() {
+            return;
+        }
+
+        let position = qobject::drop_event_position(&event);
+        self.forward_cursor(position.clone());
+        let paths = local_paths(&urls);
+        if !paths.is_empty() {
+            self.with_item_window(|world, window| drop::dropped(world, window, &paths));
+        }
+        let scene_position = qobject::quick_item_map_to_scene(&self, &position);
+        self.as_mut().files_dropped(urls, scene_position);
+    }
+
+    /// Follow a Bevy text field asking for an input method, or moving its
+    /// text cursor
+    pub(super) fn sync_ime(mut self: Pin<&mut Self>) {
+        let Some(window) = self.rust().target else {
+            return;
+        };
+        let Some((enabled, position)) = self
+            .with_target_world(|world| ime::state(world, window))
+            .flatten()
+        else {
+            return;
+        };
+        let (was_enabled, old_position) = self.rust().events.ime;
+        self.as_mut().rust_mut().events.ime = (enabled, position);
+        if enabled != was_enabled {
+            qobject::quick_item_set_accepts_input_method(self.as_mut(), enabled);
+            if self.has_active_focus() {
+                self.with_item_window(|world, window| ime::enabled_changed(world, window, enabled));
+            }
+        } else if enabled && position != old_position {
+            qobject::quick_item_update_input_method(self.as_mut());
+        }
+    }
+
+    /// Whether input may go to [crate::qml_texture::QmlTexture] panels,
+    /// which only exist in the main world
+    fn has_panels(&self) -> bool {
+        self.rust().target_world.is_empty() && qml_texture::has_interactive_panels()
+    }
+
+    fn forward_mouse_button(&self, event: &qobject::QMouseEvent, state: ButtonState) {
+        let position = self.content_position(to_vec2(&qobject::mouse_event_position(event)));
+        let button = mouse::mouse_button_from_qt(qobject::mouse_event_button(event));
+        self.with_item_window(|world, window| {
+            mouse::cursor_moved(world, window, position);
+            if let Some(button) = button {
+                mouse::button(world, window, button, state);
+            }
+        });
+    }
+
+    fn forward_key(&self, event: &qobject::QKeyEvent, state: ButtonState) {
+        // Qt only delivers keys to the item with active focus, but events
+        // may still be sent directly to the item
+        if !self.has_active_focus() {
+            return;
+        }
+
+        let key = QtKey {
+            key: qobject::key_event_key(event),
+            modifiers: qobject::key_event_modifiers(event),
+            text: qobject::key_event_text(event).to_string(),
+            auto_repeat: qobject::key_event_is_auto_repeat(event),
+        };
+        if self.rust().target_world.is_empty()
+            && qml_texture::route_key(&key, state == ButtonState::Pressed)
+        {
+            return;
+        }
+        self.with_item_window(|world, window| keyboard::key(world, window, &key, state));
+    }
+
+    /// Hand a mouse event to the panel under the cursor, returns whether it took it
+    fn route_mouse_to_panel(&self, event: &qobject::QMouseEvent, event_type: i32) -> bool {
+        if !self.has_panels() {
+            return false;
+        }
+        let hit = self.pick_entity(to_vec2(&qobject::mouse_event_position(event)));
+        qml_texture::route_mouse(
+            hit.as_ref(),
+            event_type,
+            qobject::mouse_event_button(event),
+            qobject::mouse_event_buttons(event),
+            qobject::mouse_event_modifiers(event),
+        )
+    }
+
+    /// Move the cursor over the panel under it, returns whether there is one
+    fn route_hover_to_panel(&self, position: Vec2) -> bool {
+        if !self.has_panels() {
+            return false;
+        }
+        let hit = self.pick_entity(position);
+        qml_texture::route_mouse(hit.as_ref(), qml_texture::MOUSE_MOVE, 0, 0, 0)
+    }
+
+    fn forward_cursor(&self, position: QPointF) {
+        let position = self.content_position(to_vec2(&position));
+        self.with_item_window(|world, window| mouse::cursor_moved(world, window, position));
+    }
+}
+
+/// The paths of the URLs which refer to local files
+fn local_paths(urls: &QList<QUrl>) -> Vec<PathBuf> {
+    urls.iter()
+        .filter(|url| url.is_local_file())
+        .map(|url| PathBuf::from(url.to_local_file_or_default().to_string()))
+        .collect()
+}
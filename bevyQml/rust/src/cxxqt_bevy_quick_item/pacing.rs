@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! When a `BevyQuickItem` updates the app, and when it follows it.
+//!
+//! With [crate::runtime::FramePacing::SceneGraph] the window showing the
+//! item updates the app once per frame it renders, and while the window is
+//! hidden or minimized the [crate::redraw::HiddenWindowPolicy] decides
+//! whether the app keeps running. Resizing the item or moving its window to
+//! a screen with another scale asks for an update as well, so apps rendering
+//! on demand follow it.
+//!
+//! After every update of the app the item brings its render target in step
+//! with its properties, schedules a repaint and emits `sceneUpdated`. QML
+//! changing something Bevy cannot see, e.g. an input of a shader, asks for
+//! an update with `requestUpdate`:
+//!
+//! ```qml
+//! BevyQuickItem {
+//!     id: view
+//!     Slider {
+//!         onMoved: {
+//!             Settings.exposure = value;
+//!             view.requestUpdate();
+//!         }
+//!     }
+//! }
+//! ```
+
+use core::pin::Pin;
+
+use cxx_qt::{CxxQtType, Threading};
+
+use super::qobject;
+use crate::runtime;
+
+impl qobject::BevyQuickItem {
+    pub fn request_update(&self) {
+        runtime::request_update();
+    }
+
+    /// Let the window of the item pace the app, and sync the item after
+    /// every update
+    pub(super) fn follow_updates(mut self: Pin<&mut Self>) {
+        qobject::quick_item_pace_frames(self.as_mut());
+        qobject::quick_item_track_window_visibility(self.as_mut());
+        qobject::quick_item_request_update_on_resize(self.as_mut());
+        qobject::quick_item_request_update_on_scale_change(self.as_mut());
+
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            // Syncing calls back into the item, so defer it until the update
+            // notification has finished
+            let _ = qt_thread.queue(|item| item.sync());
+        });
+        self.rust_mut().update_listener = Some(listener);
+    }
+}
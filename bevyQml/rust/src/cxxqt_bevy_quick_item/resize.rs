@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! How the render target of a `BevyQuickItem` follows the size of the item.
+//!
+//! The render target has the size of the item in physical pixels, and
+//! follows the device pixel ratio of its window when that moves to another
+//! screen. Viewports in camera coordinates thus compare to
+//! [Window::physical_cursor_position], and Bevy UI is scaled by
+//! [crate::render::QmlUiScale].
+//!
+//! `resizeMode` decides how the render target follows the item:
+//! `BevyQuickItem.Stretch` renders at the size of the item,
+//! `BevyQuickItem.FixedResolution` at `renderWidth` by `renderHeight` pixels
+//! whatever the size of the item, and `BevyQuickItem.SuperSample` at
+//! `superSampling` times the size of the item for smoother edges. A positive
+//! `aspectRatio` letterboxes the frames to that ratio of width to height,
+//! which a fixed resolution otherwise keeps on its own. With a `resizeDelay`
+//! in milliseconds the target only follows once the item stopped resizing
+//! for that long, stretching the last frame in the meantime. See
+//! [crate::render::RenderTargetPool] for reusing targets between sizes:
+//!
+//! ```qml
+//! BevyQuickItem {
+//!     resizeMode: BevyQuickItem.FixedResolution
+//!     renderWidth: 1280
+//!     renderHeight: 720
+//!     resizeDelay: 100
+//! }
+//! ```
+
+use core::pin::Pin;
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use cxx_qt::CxxQtType;
+
+use super::qobject;
+use crate::{
+    render::{letterbox, ResizeMode},
+    runtime,
+};
+
+/// The size of the render target while the item is resized
+#[derive(Default)]
+pub(super) struct ResizeState {
+    /// The size last given to the target
+    target_size: Option<UVec2>,
+    /// The size the item has been resized to, and since when
+    pending_size: Option<(UVec2, Instant)>,
+}
+
+impl qobject::BevyQuickItem {
+    /// The size to give the render target, which only follows the item once
+    /// it kept its size for resizeDelay milliseconds
+    pub(super) fn settle_size(mut self: Pin<&mut Self>, size: UVec2) -> UVec2 {
+        let delay = Duration::from_millis((*self.resize_delay()).max(0) as u64);
+        let mut rust = self.as_mut().rust_mut();
+        let resize = &mut rust.resize;
+        let current = match resize.target_size {
+            Some(current) if current != size && !delay.is_zero() => current,
+            _ => {
+                resize.target_size = Some(size);
+                resize.pending_size = None;
+                return size;
+            }
+        };
+
+        let now = Instant::now();
+        match resize.pending_size {
+            Some((pending, since)) if pending == size => {
+                if now.duration_since(since) >= delay {
+                    resize.target_size = Some(size);
+                    resize.pending_size = None;
+                    return size;
+                }
+            }
+            _ => resize.pending_size = Some((size, now)),
+        }
+        // Keep updating until the delay has passed
+        runtime::request_update();
+        current
+    }
+
+    /// How the render target follows the size of the item
+    pub(super) fn resize_policy(&self) -> ResizeMode {
+        let mode = *self.resize_mode();
+        if mode == qobject::ResizeMode::FixedResolution {
+            ResizeMode::FixedResolution(UVec2::new(
+                (*self.render_width()).max(1) as u32,
+                (*self.render_height()).max(1) as u32,
+            ))
+        } else if mode == qobject::ResizeMode::SuperSample {
+            ResizeMode::SuperSample(*self.super_sampling() as f32)
+        } else {
+            ResizeMode::Stretch
+        }
+    }
+
+    /// The part of the item showing the frames, in logical pixels
+    pub(super) fn content_rect(&self) -> Rect {
+        let aspect_ratio = Some(*self.aspect_ratio() as f32)
+            .filter(|ratio| *ratio > 0.0)
+            .or_else(|| self.resize_policy().aspect_ratio());
+        letterbox(
+            Vec2::new(self.width() as f32, self.height() as f32),
+            aspect_ratio,
+        )
+    }
+
+    /// Make a position in the item relative to the frames it shows
+    pub(super) fn content_position(&self, position: Vec2) -> Vec2 {
+        position - self.content_rect().min
+    }
+}
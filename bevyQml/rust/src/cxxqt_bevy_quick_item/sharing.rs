@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! How frames get from Bevy into the scene graph of a `BevyQuickItem`, and
+//! what happens when either of them loses its GPU device.
+//!
+//! Frames are shared with the scene graph without copying them when it
+//! renders on the same GPU as Bevy with a fitting graphics API, see
+//! [crate::render::interop]. `interopBackend` tells how the frames are shown
+//! once the item has been rendered, and `interop` forces a backend, such as
+//! `BevyQuickItem.Copy` on drivers where sharing misbehaves:
+//!
+//! ```qml
+//! BevyQuickItem {
+//!     interop: settings.copyFrames ? BevyQuickItem.Copy : BevyQuickItem.Automatic
+//!     Label {
+//!         text: parent.interopBackend === BevyQuickItem.Copy ? "Copying frames" : "Sharing frames"
+//!     }
+//! }
+//! ```
+//!
+//! When the GPU device of Bevy is lost, e.g. as the driver reset the GPU,
+//! the item emits `deviceLost` and stops showing new frames. Once the main
+//! app was built anew with a new device, see [crate::runtime::rebuild_with],
+//! the item renders into a new target and emits `deviceRestored`. When the
+//! scene graph loses its own device, the item shares its frames anew and
+//! emits `sceneGraphReset`:
+//!
+//! ```qml
+//! BevyQuickItem {
+//!     onDeviceLost: reason => notice.show(qsTr("The graphics were reset: %1").arg(reason))
+//!     onDeviceRestored: notice.hide()
+//! }
+//! ```
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::QRectF;
+
+use super::qobject;
+use crate::{
+    render::interop::{self, InteropBackend, QtGraphicsApi, SharedTextureSlot},
+    runtime,
+};
+
+/// How the frames of the item are shared with the scene graph
+#[derive(Default)]
+pub(super) struct Sharing {
+    /// Whether the scene graph has been asked which graphics device it uses
+    pub(super) negotiated: bool,
+    pub(super) backend: InteropBackend,
+    pub(super) shared: SharedTextureSlot,
+}
+
+impl qobject::BevyQuickItem {
+    /// Show the last frame shared by the render world, returns None when
+    /// frames are copied instead
+    ///
+    /// # Safety
+    ///
+    /// Called from updatePaintNode with the node previously returned from it.
+    pub(super) unsafe fn update_shared_node(
+        mut self: Pin<&mut Self>,
+        old_node: *mut qobject::QSGNode,
+        rect: &QRectF,
+    ) -> Option<*mut qobject::QSGNode> {
+        let backend = self.rust().sharing.backend;
+        if backend == InteropBackend::Copy {
+            return None;
+        }
+        let shared = self.rust().sharing.shared.clone();
+        if shared.take_failure() {
+            warn!("Bevy could not share a {backend:?} texture, copying frames instead");
+            self.as_mut().set_backend(InteropBackend::Copy);
+            return None;
+        }
+
+        let frame = qobject::SharedTextureFrame {
+            backend: backend as i32,
+            buffer: 0,
+            handle: -1,
+            fence: -1,
+            key: 0,
+            allocation_size: 0,
+            width: 0,
+            height: 0,
+            wait_idle: shared.waits_for_gpu(),
+            slot: shared.as_raw(),
+        };
+
+        // Import the textures of newly created targets, then show the
+        // buffer of the last frame
+        let mut node = old_node;
+        for export in shared.take() {
+            let import = qobject::SharedTextureFrame {
+                buffer: export.buffer as i32,
+                handle: export.handle,
+                fence: export.fence,
+                allocation_size: export.allocation_size,
+                width: export.size.x,
+                height: export.size.y,
+                ..frame
+            };
+            node =
+                qobject::quick_item_update_shared_texture_node(self.as_mut(), node, &import, rect);
+            if node.is_null() {
+                // The old node has been deleted by the failed import
+                warn!("Failed to import a {backend:?} shared texture, copying frames instead");
+                self.as_mut().set_backend(InteropBackend::Copy);
+                return Some(std::ptr::null_mut());
+            }
+        }
+
+        let (buffer, key) = shared.released();
+        let show = qobject::SharedTextureFrame {
+            buffer: buffer as i32,
+            key,
+            ..frame
+        };
+        let node = qobject::quick_item_update_shared_texture_node(self, node, &show, rect);
+        if key != 0 && !node.is_null() {
+            shared.acquire(buffer, key);
+        }
+        Some(node)
+    }
+
+    /// Ask the scene graph which device it renders with and whether Bevy can
+    /// share its render target with it
+    ///
+    /// This runs on the scene graph thread while the GUI thread is blocked.
+    pub(super) fn negotiate_backend(mut self: Pin<&mut Self>) {
+        // Wait for the renderer to start before deciding on the copy path
+        if interop::adapter_identity().is_none() {
+            return;
+        }
+
+        let mut device_uuid = [0; 16];
+        let mut uuid_valid = false;
+        let api = QtGraphicsApi::from(qobject::quick_item_graphics_device(
+            self.as_mut(),
+            &mut device_uuid,
+            &mut uuid_valid,
+        ));
+        let forced = self.forced_backend();
+        let backend = interop::negotiate(api, uuid_valid.then_some(device_uuid), forced);
+        info!("BevyQuickItem shows frames of the Qt {api:?} renderer using {backend:?}");
+
+        self.as_mut().set_backend(backend);
+        self.rust_mut().sharing.negotiated = true;
+    }
+
+    /// Export the shared textures anew and ask the scene graph again which
+    /// device it uses, after it dropped its nodes along with the textures
+    pub(super) fn reset_shared_textures(mut self: Pin<&mut Self>) {
+        info!("The scene graph was reset, sharing frames anew");
+        self.rust().sharing.shared.reset();
+        self.as_mut().rust_mut().sharing.negotiated = false;
+        runtime::request_update();
+        self.update();
+    }
+
+    /// The backend asked for with `interop`, if not left to [interop::negotiate]
+    fn forced_backend(&self) -> Option<InteropBackend> {
+        let interop = *self.interop();
+        if interop == qobject::Interop::Copy {
+            Some(InteropBackend::Copy)
+        } else if interop == qobject::Interop::VulkanExternalMemory {
+            Some(InteropBackend::VulkanExternalMemory)
+        } else if interop == qobject::Interop::D3DSharedHandle {
+            Some(InteropBackend::D3DSharedHandle)
+        } else if interop == qobject::Interop::MetalIOSurface {
+            Some(InteropBackend::MetalIOSurface)
+        } else if interop == qobject::Interop::OpenGLShareContext {
+            Some(InteropBackend::OpenGLShareContext)
+        } else {
+            None
+        }
+    }
+
+    /// Show frames through the backend, and tell QML once the scene graph has
+    /// released the GUI thread
+    fn set_backend(mut self: Pin<&mut Self>, backend: InteropBackend) {
+        self.as_mut().rust_mut().sharing.backend = backend;
+        let interop_backend = match backend {
+            InteropBackend::VulkanExternalMemory => qobject::Interop::VulkanExternalMemory,
+            InteropBackend::D3DSharedHandle => qobject::Interop::D3DSharedHandle,
+            InteropBackend::MetalIOSurface => qobject::Interop::MetalIOSurface,
+            InteropBackend::OpenGLShareContext => qobject::Interop::OpenGLShareContext,
+            InteropBackend::Copy => qobject::Interop::Copy,
+        };
+        let _ = self
+            .qt_thread()
+            .queue(move |item| item.set_interop_backend(interop_backend));
+    }
+}
@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! The cameras a `BevyQuickItem` shows, and mapping between the item and
+//! the world they look at.
+//!
+//! Several items can show the same world from different cameras. An item
+//! shows the cameras with the [crate::render::QmlView] named by `view`, and
+//! the camera whose entity bits are set as `camera`. Items without either
+//! show the cameras that would render to the primary window. `world` names
+//! an independent app hosted with [crate::plugin::QmlWorldPlugin] for the
+//! item to show instead of the main app, see [crate::window].
+//!
+//! `viewports` lays out several cameras side by side in the item, for local
+//! multiplayer or comparing views. Every entry names the
+//! [crate::render::QmlView] of its cameras as `camera`, and the part of the
+//! item they show in as `rect`, from zero to one of the width and height of
+//! the item, see [crate::render::QuickItemViewport]. Picking and
+//! `itemToWorldRay` go through the camera under the position:
+//!
+//! ```qml
+//! BevyQuickItem {
+//!     viewports: [
+//!         { camera: "P1", rect: Qt.rect(0, 0, 0.5, 1) },
+//!         { camera: "P2", rect: Qt.rect(0.5, 0, 0.5, 1) }
+//!     ]
+//! }
+//! ```
+//!
+//! `worldToItem` maps a point of the world onto the item and `itemToWorldRay`
+//! goes the other way, both through the camera `pick` casts its rays from.
+//! Overlays can follow the scene by mapping their anchor whenever
+//! `sceneUpdated` is emitted, as `qml/EntityOverlay.qml` does for an entity:
+//!
+//! ```qml
+//! BevyQuickItem {
+//!     id: view
+//!     Label {
+//!         id: callout
+//!         text: "Spawn"
+//!     }
+//!     onSceneUpdated: {
+//!         const point = view.worldToItem(Qt.vector3d(0, 2, 0));
+//!         callout.visible = !isNaN(point.x);
+//!         callout.x = point.x;
+//!         callout.y = point.y;
+//!     }
+//! }
+//! ```
+
+use bevy::prelude::*;
+use cxx_qt::CxxQtType;
+use cxx_qt_lib::{QMap, QMapPair_QString_QVariant, QPointF, QRectF, QString, QVariant, QVector3D};
+
+use super::{qobject, to_vec2};
+use crate::{
+    convert::{FromQt, IntoQt},
+    picking::{self, PickHit},
+    render::{QuickItemTarget, QuickItemViewport},
+    variant,
+};
+
+impl qobject::BevyQuickItem {
+    pub fn pick(&self, x: f64, y: f64) -> QMap<QMapPair_QString_QVariant> {
+        let mut map = QMap::<QMapPair_QString_QVariant>::default();
+        if let Some(hit) = self.pick_entity(Vec2::new(x as f32, y as f32)) {
+            map.insert(
+                QString::from("entity"),
+                QVariant::from(&hit.entity.to_bits()),
+            );
+            map.insert(
+                QString::from("position"),
+                variant::vec3_to_variant(hit.position),
+            );
+            map.insert(
+                QString::from("normal"),
+                variant::vec3_to_variant(hit.normal),
+            );
+            map.insert(
+                QString::from("distance"),
+                QVariant::from(&f64::from(hit.distance)),
+            );
+        }
+        map
+    }
+
+    pub fn world_to_item(&self, position: &QVector3D) -> QPointF {
+        let position = Vec3::from_qt(position);
+        let content_origin = self.content_rect().min;
+        self.with_image_scale(|world, image, scale| {
+            let camera = picking::target_camera(world, image)?;
+            let point = picking::viewport_point(world, camera, position)?;
+            Some(point / scale + content_origin)
+        })
+        .unwrap_or(Vec2::NAN)
+        .into_qt()
+    }
+
+    pub fn item_to_world_ray(&self, position: &QPointF) -> QMap<QMapPair_QString_QVariant> {
+        let mut map = QMap::<QMapPair_QString_QVariant>::default();
+        let ray = self.with_image_position(to_vec2(position), |world, image, position| {
+            let camera = picking::camera_at(world, image, position)?;
+            picking::viewport_ray(world, camera, position)
+        });
+        if let Some(ray) = ray {
+            map.insert(
+                QString::from("origin"),
+                variant::vec3_to_variant(ray.origin),
+            );
+            map.insert(
+                QString::from("direction"),
+                variant::vec3_to_variant(*ray.direction),
+            );
+        }
+        map
+    }
+
+    pub fn project_entity(
+        &self,
+        entity: u64,
+        offset: &QVector3D,
+        occlusion: bool,
+    ) -> QMap<QMapPair_QString_QVariant> {
+        let mut map = QMap::<QMapPair_QString_QVariant>::default();
+        let Ok(entity) = Entity::try_from_bits(entity) else {
+            return map;
+        };
+        let offset = Vec3::from_qt(offset);
+        let content_origin = self.content_rect().min;
+        let projection = self.with_image_scale(|world, image, scale| {
+            let position = world.get::<GlobalTransform>(entity)?.translation() + offset;
+            let camera = picking::target_camera(world, image)?;
+            let point = picking::viewport_point(world, camera, position)?;
+            let distance = world
+                .get::<GlobalTransform>(camera)?
+                .translation()
+                .distance(position);
+            let occluded = occlusion && picking::occluded(world, camera, position, entity);
+            Some((point / scale + content_origin, distance, occluded))
+        });
+        if let Some((point, distance, occluded)) = projection {
+            let position: QPointF = point.into_qt();
+            map.insert(QString::from("position"), QVariant::from(&position));
+            map.insert(
+                QString::from("distance"),
+                QVariant::from(&f64::from(distance)),
+            );
+            map.insert(QString::from("occluded"), QVariant::from(&occluded));
+        }
+        map
+    }
+
+    /// The cameras laid out by viewports, leaving out entries without a
+    /// camera or a rect
+    pub(super) fn viewport_layout(&self) -> Vec<QuickItemViewport> {
+        self.viewports()
+            .iter()
+            .filter_map(|entry| {
+                let entries = variant::map_entries(entry);
+                let value = |key: &str| {
+                    entries
+                        .iter()
+                        .find(|(name, _)| name == key)
+                        .map(|(_, value)| value)
+                };
+                let view = value("camera")?.value::<QString>()?.to_string();
+                let rect = Rect::from_qt(&value("rect")?.value::<QRectF>()?);
+                Some(QuickItemViewport { view, rect })
+            })
+            .collect()
+    }
+
+    /// Cast a ray through a position of the item, given in logical pixels
+    pub(super) fn pick_entity(&self, position: Vec2) -> Option<PickHit> {
+        self.with_image_position(position, |world, image, position| {
+            picking::pick(world, image, position)
+        })
+    }
+
+    /// Run the closure with the render target of the item and a position of
+    /// the item, given in logical pixels, in physical pixels of the target
+    pub(super) fn with_image_position<R>(
+        &self,
+        position: Vec2,
+        f: impl FnOnce(&mut World, &Handle<Image>, Vec2) -> Option<R>,
+    ) -> Option<R> {
+        let position = self.content_position(position);
+        self.with_image_scale(|world, image, scale| f(world, image, position * scale))
+    }
+
+    /// Run the closure with the render target of the item and how many of
+    /// its physical pixels make up a logical pixel of the frames shown
+    fn with_image_scale<R>(
+        &self,
+        f: impl FnOnce(&mut World, &Handle<Image>, Vec2) -> Option<R>,
+    ) -> Option<R> {
+        let target = self.rust().target?;
+        let logical_size = self.content_rect().size().max(Vec2::ONE);
+        self.with_target_world(|world| {
+            let item_target = world.get::<QuickItemTarget>(target)?;
+            let image = item_target.image.clone();
+            // The target is measured in physical pixels
+            let scale = item_target.size.as_vec2() / logical_size;
+            f(world, &image, scale)
+        })
+        .flatten()
+    }
+}
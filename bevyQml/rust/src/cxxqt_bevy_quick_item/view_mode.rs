@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! The ground grid and the debug renderings of a `BevyQuickItem`.
+//!
+//! `showGrid` draws a ground grid in the XZ plane and `showAxes` the axes of
+//! the world, see [crate::grid]. Major lines are `gridSize` apart with
+//! `gridSubdivisions` minor lines between them, and every line fades out
+//! towards `gridFadeDistance` from the camera:
+//!
+//! ```qml
+//! BevyQuickItem {
+//!     showGrid: gridButton.checked
+//!     showAxes: true
+//!     gridColor: palette.mid
+//! }
+//! ```
+//!
+//! `viewMode` switches the item to a debug rendering such as
+//! `BevyQuickItem.Wireframe` or `BevyQuickItem.Normals`, see
+//! [crate::view_mode] for which of them show in every view:
+//!
+//! ```qml
+//! BevyQuickItem {
+//!     viewMode: wireframeButton.checked ? BevyQuickItem.Wireframe : BevyQuickItem.Shaded
+//! }
+//! ```
+
+use bevy::prelude::*;
+
+use super::qobject;
+use crate::{convert::FromQt, grid::QmlGrid, view_mode::QmlViewMode};
+
+impl qobject::BevyQuickItem {
+    /// The grid and axes asked for by the properties of the item
+    pub(super) fn grid(&self) -> QmlGrid {
+        QmlGrid {
+            grid: *self.show_grid(),
+            axes: *self.show_axes(),
+            size: *self.grid_size() as f32,
+            subdivisions: (*self.grid_subdivisions()).max(1) as u32,
+            fade_distance: *self.grid_fade_distance() as f32,
+            color: Color::from_qt(self.grid_color()),
+            ..default()
+        }
+    }
+
+    /// The debug rendering asked for by viewMode
+    pub(super) fn view_mode_component(&self) -> QmlViewMode {
+        let mode = *self.view_mode();
+        if mode == qobject::ViewMode::Wireframe {
+            QmlViewMode::Wireframe
+        } else if mode == qobject::ViewMode::Normals {
+            QmlViewMode::Normals
+        } else if mode == qobject::ViewMode::Uvs {
+            QmlViewMode::Uvs
+        } else if mode == qobject::ViewMode::Overdraw {
+            QmlViewMode::Overdraw
+        } else if mode == qobject::ViewMode::Aabbs {
+            QmlViewMode::Aabbs
+        } else {
+            QmlViewMode::Shaded
+        }
+    }
+}
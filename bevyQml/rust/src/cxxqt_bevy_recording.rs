@@ -0,0 +1,383 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that records what a BevyQuickItem shows
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_recording")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // RecordingController based on the Rust struct RecordingControllerRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(QString, world)]
+        #[qproperty(QString, view)]
+        #[qproperty(f64, framerate)]
+        #[qproperty(QString, output)]
+        #[qproperty(QString, encoder)]
+        #[qproperty(bool, recording)]
+        #[qproperty(i32, frame_count)]
+        type RecordingController = super::RecordingControllerRust;
+
+        /// Start recording, unless a recording is running already
+        #[qinvokable]
+        fn start(self: Pin<&mut RecordingController>);
+
+        /// Stop recording, the last frames are still written before finished
+        /// is emitted
+        #[qinvokable]
+        fn stop(self: Pin<&mut RecordingController>);
+
+        /// Emitted once every frame of a recording was written
+        #[qsignal]
+        fn finished(self: Pin<&mut RecordingController>, frames: i32);
+
+        /// Emitted when a recording fails, it is stopped as well
+        #[qsignal]
+        #[cxx_name = "recordingError"]
+        fn recording_error(self: Pin<&mut RecordingController>, message: QString);
+    }
+
+    impl cxx_qt::Threading for RecordingController {}
+    impl cxx_qt::Constructor<()> for RecordingController {}
+}
+
+use core::pin::Pin;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    thread,
+    time::Instant,
+};
+
+use bevy::prelude::*;
+use crossbeam_channel::{Receiver, Sender};
+use cxx_qt::{CxxQtThread, CxxQtType, Threading};
+use cxx_qt_lib::QString;
+
+use crate::{
+    image,
+    render::{Frame, FrameCapture, QuickItemTarget, QuickItemView},
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// The controller records the `BevyQuickItem` whose `view` has the same
+/// name, in the app named by `world`, at `framerate` frames per second.
+/// Frames are repeated when the app renders slower than that, and skipped
+/// when it renders faster, so the recording plays back in real time.
+///
+/// Without an `encoder` the frames are saved to the `output` directory as
+/// `frame_00000.png`, `frame_00001.png` and so on. Otherwise `encoder` is a
+/// command line, split at whitespace, whose standard input receives the
+/// frames as raw RGBA8 pixels. `{width}`, `{height}` and `{framerate}` in it
+/// are replaced by those of the recording:
+///
+/// ```qml
+/// RecordingController {
+///     id: recorder
+///     encoder: "ffmpeg -y -f rawvideo -pix_fmt rgba -s {width}x{height} -r {framerate} -i - demo.mp4"
+///     onFinished: frames => console.log("recorded", frames, "frames")
+/// }
+/// ```
+///
+/// Frames are written on a thread of their own, `recording` and
+/// `frameCount` follow the recording and are not meant to be set from QML.
+/// The app keeps updating while it records, also when it renders on demand.
+pub struct RecordingControllerRust {
+    world: QString,
+    view: QString,
+    framerate: f64,
+    output: QString,
+    encoder: QString,
+    recording: bool,
+    frame_count: i32,
+    session: Option<Session>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for RecordingControllerRust {
+    fn default() -> Self {
+        Self {
+            world: QString::default(),
+            view: QString::default(),
+            framerate: 30.0,
+            output: QString::from("."),
+            encoder: QString::default(),
+            recording: false,
+            frame_count: 0,
+            session: None,
+            update_listener: None,
+        }
+    }
+}
+
+/// A running recording
+struct Session {
+    capture: FrameCapture,
+    /// The name of the app the capture lives in
+    world: String,
+    started: Instant,
+    framerate: f64,
+    frames: Sender<Frame>,
+    /// The latest frame, repeated until the next one arrives
+    latest: Option<Frame>,
+}
+
+impl Session {
+    /// Send the frames due by now, returns how many were sent in total
+    fn queue_due_frames(&mut self, mut written: i32) -> i32 {
+        if let Some(frame) = self.capture.take() {
+            self.latest = Some(frame);
+        }
+        let Some(latest) = &self.latest else {
+            return written;
+        };
+
+        let due = (self.started.elapsed().as_secs_f64() * self.framerate) as i32 + 1;
+        while written < due {
+            if self.frames.send(latest.clone()).is_err() {
+                // The thread stopped early and reports why
+                break;
+            }
+            written += 1;
+        }
+        written
+    }
+}
+
+impl Drop for RecordingControllerRust {
+    fn drop(&mut self) {
+        if let Some(session) = self.session.take() {
+            runtime::with_world_in(&session.world, |world| session.capture.stop(world));
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::RecordingController {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|controller| controller.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+    }
+}
+
+impl qobject::RecordingController {
+    pub fn start(mut self: Pin<&mut Self>) {
+        // A stopped recording is running until it finished
+        if *self.recording() {
+            return;
+        }
+        let framerate = *self.framerate();
+        if framerate.is_nan() || framerate <= 0.0 {
+            self.fail(format!("cannot record at {framerate} frames per second"));
+            return;
+        }
+
+        let world_name = self.world().to_string();
+        let view = self.view().to_string();
+        let capture = runtime::with_world_in(&world_name, |world| {
+            let image = world
+                .query::<(&QuickItemTarget, &QuickItemView)>()
+                .iter(world)
+                .find(|(_, item_view)| item_view.name == view)
+                .map(|(target, _)| target.image.clone())?;
+            Some(FrameCapture::start(world, image))
+        })
+        .flatten();
+        let Some(capture) = capture else {
+            self.fail(format!(
+                "there is no BevyQuickItem showing the view {view:?}"
+            ));
+            return;
+        };
+
+        let writer = match self.encoder().to_string() {
+            encoder if encoder.trim().is_empty() => FrameWriter::Png {
+                directory: PathBuf::from(self.output().to_string()),
+            },
+            encoder => FrameWriter::Encoder {
+                command_line: encoder,
+                framerate,
+            },
+        };
+        let (frames, receiver) = crossbeam_channel::unbounded();
+        let qt_thread = self.qt_thread();
+        thread::Builder::new()
+            .name("bevy recording".into())
+            .spawn(move || write_frames(writer, receiver, qt_thread))
+            .expect("failed to spawn the recording thread");
+
+        self.as_mut().rust_mut().session = Some(Session {
+            capture,
+            world: world_name,
+            started: Instant::now(),
+            framerate,
+            frames,
+            latest: None,
+        });
+        self.as_mut().set_frame_count(0);
+        self.as_mut().set_recording(true);
+        runtime::request_update();
+    }
+
+    pub fn stop(mut self: Pin<&mut Self>) {
+        // Dropping the sender lets the thread finish the recording
+        if let Some(session) = self.as_mut().rust_mut().session.take() {
+            runtime::with_world_in(&session.world, |world| session.capture.stop(world));
+        }
+    }
+
+    /// Queue the frames due since the last update
+    fn refresh(mut self: Pin<&mut Self>) {
+        let previous = *self.frame_count();
+        let written = {
+            let mut rust = self.as_mut().rust_mut();
+            let Some(session) = rust.session.as_mut() else {
+                return;
+            };
+            session.queue_due_frames(previous)
+        };
+        if written != previous {
+            self.as_mut().set_frame_count(written);
+        }
+        runtime::request_update();
+    }
+
+    fn fail(mut self: Pin<&mut Self>, message: String) {
+        warn!("RecordingController {message}");
+        self.as_mut().stop();
+        self.recording_error(QString::from(message.as_str()));
+    }
+
+    /// Called once the thread wrote the last frame
+    fn finish(mut self: Pin<&mut Self>, result: Result<i32, String>) {
+        self.as_mut().stop();
+        self.as_mut().set_recording(false);
+        match result {
+            Ok(frames) => self.finished(frames),
+            Err(message) => self.fail(message),
+        }
+    }
+}
+
+/// Where the frames of a recording go
+enum FrameWriter {
+    Png {
+        directory: PathBuf,
+    },
+    Encoder {
+        command_line: String,
+        framerate: f64,
+    },
+}
+
+/// An encoder started for the size of the first frame
+struct Encoder {
+    child: Child,
+    stdin: ChildStdin,
+    size: (u32, u32),
+}
+
+/// Write the frames until the controller stops sending them
+fn write_frames(
+    writer: FrameWriter,
+    frames: Receiver<Frame>,
+    qt_thread: CxxQtThread<qobject::RecordingController>,
+) {
+    let result = match writer {
+        FrameWriter::Png { directory } => write_png_sequence(&directory, &frames),
+        FrameWriter::Encoder {
+            command_line,
+            framerate,
+        } => pipe_to_encoder(&command_line, framerate, &frames),
+    };
+    let _ = qt_thread.queue(move |controller| controller.finish(result));
+}
+
+fn write_png_sequence(directory: &Path, frames: &Receiver<Frame>) -> Result<i32, String> {
+    std::fs::create_dir_all(directory)
+        .map_err(|error| format!("cannot create {}: {error}", directory.display()))?;
+    let mut written = 0;
+    for frame in frames {
+        let path = directory.join(format!("frame_{written:05}.png"));
+        let path = path.to_string_lossy();
+        if !image::save_frame(&frame, &path) {
+            return Err(format!("failed to save a frame to {path}"));
+        }
+        written += 1;
+    }
+    Ok(written)
+}
+
+fn pipe_to_encoder(
+    command_line: &str,
+    framerate: f64,
+    frames: &Receiver<Frame>,
+) -> Result<i32, String> {
+    let mut encoder: Option<Encoder> = None;
+    let mut written = 0;
+    for frame in frames {
+        if encoder.is_none() {
+            encoder = Some(start_encoder(command_line, framerate, &frame)?);
+        }
+        let Some(encoder) = encoder.as_mut() else {
+            continue;
+        };
+        // Raw video cannot change size along the way
+        if encoder.size != (frame.width, frame.height) {
+            continue;
+        }
+        encoder
+            .stdin
+            .write_all(&frame.data)
+            .map_err(|error| format!("failed to write a frame to the encoder: {error}"))?;
+        written += 1;
+    }
+
+    let Some(Encoder {
+        mut child, stdin, ..
+    }) = encoder
+    else {
+        return Ok(0);
+    };
+    // Closing its input tells the encoder the recording is over
+    drop(stdin);
+    let status = child
+        .wait()
+        .map_err(|error| format!("failed to wait for the encoder: {error}"))?;
+    if !status.success() {
+        return Err(format!("the encoder exited with {status}"));
+    }
+    Ok(written)
+}
+
+fn start_encoder(command_line: &str, framerate: f64, frame: &Frame) -> Result<Encoder, String> {
+    let mut arguments = command_line.split_whitespace().map(|argument| {
+        argument
+            .replace("{width}", &frame.width.to_string())
+            .replace("{height}", &frame.height.to_string())
+            .replace("{framerate}", &framerate.to_string())
+    });
+    let program = arguments.next().ok_or("the encoder is empty")?;
+    let mut child = Command::new(&program)
+        .args(arguments)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("cannot start the encoder {program:?}: {error}"))?;
+    let stdin = child.stdin.take().ok_or("the encoder has no input")?;
+    Ok(Encoder {
+        child,
+        stdin,
+        size: (frame.width, frame.height),
+    })
+}
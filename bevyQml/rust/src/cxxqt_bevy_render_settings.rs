@@ -0,0 +1,360 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that mirrors the graphics options of
+/// a camera
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_render_settings")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qcolor.h");
+        /// An alias to the QColor type
+        type QColor = cxx_qt_lib::QColor;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qurl.h");
+        /// An alias to the QUrl type
+        type QUrl = cxx_qt_lib::QUrl;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // RenderSettingsBridge based on the Rust struct RenderSettingsBridgeRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(u64, entity)]
+        #[qproperty(QString, name)]
+        #[qproperty(bool, bound)]
+        #[qproperty(bool, hdr)]
+        #[qproperty(i32, msaa)]
+        #[qproperty(QString, tonemapping)]
+        #[qproperty(f64, exposure)]
+        #[qproperty(bool, bloom)]
+        #[qproperty(f64, bloom_intensity)]
+        #[qproperty(bool, fog)]
+        #[qproperty(QColor, fog_color)]
+        #[qproperty(f64, fog_start)]
+        #[qproperty(f64, fog_end)]
+        #[qproperty(QUrl, skybox)]
+        #[qproperty(f64, skybox_brightness)]
+        #[qproperty(QUrl, environment_diffuse)]
+        #[qproperty(QUrl, environment_specular)]
+        #[qproperty(f64, environment_intensity)]
+        type RenderSettingsBridge = super::RenderSettingsBridgeRust;
+    }
+
+    impl cxx_qt::Threading for RenderSettingsBridge {}
+    impl cxx_qt::Constructor<()> for RenderSettingsBridge {}
+}
+
+use core::pin::Pin;
+
+use bevy::{core_pipeline::bloom::BloomSettings, prelude::*, render::camera::Exposure};
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QColor, QString, QUrl};
+
+use crate::{
+    convert::{FromQt, IntoQt},
+    cxxqt_bevy_transform::find_entity,
+    render_settings::{self, QmlEnvironmentMap, QmlFog, QmlRenderSettings, QmlSkybox},
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// The camera is found like the entity of a [crate::cxxqt_bevy_transform],
+/// and `bound` tells whether it exists and is a camera. The properties follow
+/// the camera and setting them changes it, see [crate::render_settings].
+///
+/// `msaa` is the number of samples per pixel, where 1 turns MSAA off, and
+/// applies to all cameras. `tonemapping` is one of `"none"`, `"reinhard"`,
+/// `"reinhardLuminance"`, `"acesFitted"`, `"agx"`,
+/// `"somewhatBoringDisplayTransform"`, `"tonyMcMapface"` or
+/// `"blenderFilmic"`, and `exposure` is in EV100. Bloom needs `hdr`. Fog fades
+/// to `fogColor` between `fogStart` and `fogEnd`, and fog with another falloff
+/// becomes linear once it is edited. `skybox` and the
+/// `environmentDiffuse` and `environmentSpecular` maps are URLs of cubemap
+/// images, such as `.ktx2` files, and clearing them removes the skybox or
+/// the image based lighting:
+///
+/// ```qml
+/// RenderSettingsBridge {
+///     id: graphics
+///     name: "MainCamera"
+/// }
+/// ComboBox {
+///     model: [1, 2, 4, 8]
+///     currentIndex: model.indexOf(graphics.msaa)
+///     onActivated: graphics.msaa = currentValue
+/// }
+/// CheckBox {
+///     text: "Bloom"
+///     checked: graphics.bloom
+///     onToggled: {
+///         graphics.hdr = checked;
+///         graphics.bloom = checked;
+///     }
+/// }
+/// ```
+pub struct RenderSettingsBridgeRust {
+    entity: u64,
+    name: QString,
+    bound: bool,
+    hdr: bool,
+    msaa: i32,
+    tonemapping: QString,
+    exposure: f64,
+    bloom: bool,
+    bloom_intensity: f64,
+    fog: bool,
+    fog_color: QColor,
+    fog_start: f64,
+    fog_end: f64,
+    skybox: QUrl,
+    skybox_brightness: f64,
+    environment_diffuse: QUrl,
+    environment_specular: QUrl,
+    environment_intensity: f64,
+    target: Option<Entity>,
+    /// The settings last read from the camera
+    seen: Option<QmlRenderSettings>,
+    /// Set while the properties are updated from the world, so the change
+    /// handlers do not write them back
+    syncing: bool,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for RenderSettingsBridgeRust {
+    fn default() -> Self {
+        Self {
+            entity: 0,
+            name: QString::default(),
+            bound: false,
+            hdr: false,
+            msaa: 4,
+            tonemapping: QString::from(render_settings::tonemapping_name(default())),
+            exposure: Exposure::default().ev100.into(),
+            bloom: false,
+            bloom_intensity: BloomSettings::NATURAL.intensity.into(),
+            fog: false,
+            fog_color: Color::WHITE.into_qt(),
+            fog_start: 0.0,
+            fog_end: 100.0,
+            skybox: QUrl::default(),
+            skybox_brightness: 1000.0,
+            environment_diffuse: QUrl::default(),
+            environment_specular: QUrl::default(),
+            environment_intensity: 1000.0,
+            target: None,
+            seen: None,
+            syncing: false,
+            update_listener: None,
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::RenderSettingsBridge {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|settings| settings.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.as_mut()
+            .on_entity_changed(|settings| settings.retarget())
+            .release();
+        self.as_mut()
+            .on_name_changed(|settings| settings.retarget())
+            .release();
+        self.as_mut()
+            .on_hdr_changed(|settings| settings.write())
+            .release();
+        self.as_mut()
+            .on_msaa_changed(|settings| settings.write())
+            .release();
+        self.as_mut()
+            .on_tonemapping_changed(|settings| settings.write())
+            .release();
+        self.as_mut()
+            .on_exposure_changed(|settings| settings.write())
+            .release();
+        self.as_mut()
+            .on_bloom_changed(|settings| settings.write())
+            .release();
+        self.as_mut()
+            .on_bloom_intensity_changed(|settings| settings.write())
+            .release();
+        self.as_mut()
+            .on_fog_changed(|settings| settings.write())
+            .release();
+        self.as_mut()
+            .on_fog_color_changed(|settings| settings.write())
+            .release();
+        self.as_mut()
+            .on_fog_start_changed(|settings| settings.write())
+            .release();
+        self.as_mut()
+            .on_fog_end_changed(|settings| settings.write())
+            .release();
+        self.as_mut()
+            .on_skybox_changed(|settings| settings.write())
+            .release();
+        self.as_mut()
+            .on_skybox_brightness_changed(|settings| settings.write())
+            .release();
+        self.as_mut()
+            .on_environment_diffuse_changed(|settings| settings.write())
+            .release();
+        self.as_mut()
+            .on_environment_specular_changed(|settings| settings.write())
+            .release();
+        self.on_environment_intensity_changed(|settings| settings.write())
+            .release();
+    }
+}
+
+impl qobject::RenderSettingsBridge {
+    /// Look the camera up again and read its settings
+    fn retarget(mut self: Pin<&mut Self>) {
+        let mut rust = self.as_mut().rust_mut();
+        rust.target = None;
+        rust.seen = None;
+        self.refresh();
+    }
+
+    /// Change the settings of the camera to the properties
+    fn write(self: Pin<&mut Self>) {
+        if self.rust().syncing {
+            return;
+        }
+        let Some(entity) = self.rust().target else {
+            return;
+        };
+
+        let tonemapping = self.tonemapping().to_string();
+        let Some(tonemapping) = render_settings::tonemapping_from_name(&tonemapping) else {
+            warn!("RenderSettingsBridge has no tonemapping method {tonemapping:?}");
+            return;
+        };
+        let skybox = self.skybox().to_string();
+        let diffuse_url = self.environment_diffuse().to_string();
+        let specular_url = self.environment_specular().to_string();
+        let settings = QmlRenderSettings {
+            hdr: *self.hdr(),
+            msaa: u32::try_from(*self.msaa()).unwrap_or(1),
+            tonemapping,
+            exposure: *self.exposure() as f32,
+            bloom: self
+                .bloom()
+                .then_some(self.bloom_intensity().max(0.0) as f32),
+            fog: self.fog().then(|| QmlFog {
+                color: Color::from_qt(self.fog_color()),
+                range: Some((*self.fog_start() as f32, *self.fog_end() as f32)),
+            }),
+            skybox: (!skybox.is_empty()).then(|| QmlSkybox {
+                url: skybox,
+                brightness: *self.skybox_brightness() as f32,
+            }),
+            environment_map: (!diffuse_url.is_empty() && !specular_url.is_empty()).then(|| {
+                QmlEnvironmentMap {
+                    diffuse_url,
+                    specular_url,
+                    intensity: *self.environment_intensity() as f32,
+                }
+            }),
+        };
+        runtime::with_world(|world| {
+            if render_settings::apply(world, entity, settings) {
+                runtime::request_update();
+            }
+        });
+    }
+
+    /// Find the camera if needed and read its settings if they have changed
+    fn refresh(mut self: Pin<&mut Self>) {
+        let name = self.name().to_string();
+        let bits = *self.entity();
+        let target = self.rust().target;
+        let update = runtime::with_world(|world| {
+            let entity = target
+                .filter(|entity| world.get_entity(*entity).is_some())
+                .or_else(|| find_entity(world, &name, bits))?;
+            Some((entity, render_settings::read(world, entity)?))
+        });
+
+        let Some(update) = update else {
+            // The app is busy, try again after the next update
+            return;
+        };
+        let Some((entity, settings)) = update else {
+            let mut rust = self.as_mut().rust_mut();
+            rust.target = None;
+            rust.seen = None;
+            self.set_bound(false);
+            return;
+        };
+
+        self.as_mut().rust_mut().target = Some(entity);
+        if self.rust().seen.as_ref() != Some(&settings) {
+            self.as_mut().rust_mut().syncing = true;
+            self.as_mut().sync(&settings);
+            self.as_mut().rust_mut().syncing = false;
+            self.as_mut().rust_mut().seen = Some(settings);
+        }
+        self.set_bound(true);
+    }
+
+    /// Set the properties to the settings read from the camera
+    ///
+    /// The properties of options which are off keep their values, so turning
+    /// the option on again brings them back.
+    fn sync(mut self: Pin<&mut Self>, settings: &QmlRenderSettings) {
+        self.as_mut().set_hdr(settings.hdr);
+        self.as_mut()
+            .set_msaa(i32::try_from(settings.msaa).unwrap_or(i32::MAX));
+        self.as_mut()
+            .set_tonemapping(QString::from(render_settings::tonemapping_name(
+                settings.tonemapping,
+            )));
+        self.as_mut().set_exposure(settings.exposure.into());
+
+        self.as_mut().set_bloom(settings.bloom.is_some());
+        if let Some(intensity) = settings.bloom {
+            self.as_mut().set_bloom_intensity(intensity.into());
+        }
+
+        self.as_mut().set_fog(settings.fog.is_some());
+        if let Some(fog) = &settings.fog {
+            self.as_mut().set_fog_color(fog.color.into_qt());
+            if let Some((start, end)) = fog.range {
+                self.as_mut().set_fog_start(start.into());
+                self.as_mut().set_fog_end(end.into());
+            }
+        }
+
+        match &settings.skybox {
+            Some(skybox) => {
+                self.as_mut().set_skybox(QUrl::from(skybox.url.as_str()));
+                self.as_mut()
+                    .set_skybox_brightness(skybox.brightness.into());
+            }
+            None => self.as_mut().set_skybox(QUrl::default()),
+        }
+
+        match &settings.environment_map {
+            Some(map) => {
+                self.as_mut()
+                    .set_environment_diffuse(QUrl::from(map.diffuse_url.as_str()));
+                self.as_mut()
+                    .set_environment_specular(QUrl::from(map.specular_url.as_str()));
+                self.as_mut()
+                    .set_environment_intensity(map.intensity.into());
+            }
+            None => {
+                self.as_mut().set_environment_diffuse(QUrl::default());
+                self.as_mut().set_environment_specular(QUrl::default());
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that mirrors a Bevy resource
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_resource")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+        include!("cxx-qt-lib/qmap.h");
+        /// An alias to the QVariantMap type
+        type QMap_QString_QVariant = cxx_qt_lib::QMap<cxx_qt_lib::QMapPair_QString_QVariant>;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BevyResource based on the Rust struct BevyResourceRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(QString, name)]
+        #[qproperty(QMap_QString_QVariant, values)]
+        type BevyResource = super::BevyResourceRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// The current value of a field of the resource
+        #[qinvokable]
+        fn value(self: &BevyResource, field: &QString) -> QVariant;
+
+        /// Change a field of the resource, returns false if the value does not fit
+        #[qinvokable]
+        #[cxx_name = "setValue"]
+        fn set_value(self: Pin<&mut BevyResource>, field: &QString, value: &QVariant) -> bool;
+    }
+
+    impl cxx_qt::Threading for BevyResource {}
+    impl cxx_qt::Constructor<()> for BevyResource {}
+}
+
+use core::pin::Pin;
+use std::any::TypeId;
+
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QMap, QMapPair_QString_QVariant, QString, QVariant};
+
+use crate::{
+    bridge::{resource_fields, set_resource_field, BridgedResources},
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// `values` holds every field of the resource registered with a
+/// [crate::bridge::ResourceBridge] under `name`, and is updated whenever the
+/// resource changes in Bevy.
+#[derive(Default)]
+pub struct BevyResourceRust {
+    name: QString,
+    values: QMap<QMapPair_QString_QVariant>,
+    revision: Option<(TypeId, u64)>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::BevyResource {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|resource| resource.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.on_name_changed(|resource| {
+            resource.rust_mut().revision = None;
+            resource.refresh();
+        })
+        .release();
+    }
+}
+
+impl qobject::BevyResource {
+    pub fn value(&self, field: &QString) -> QVariant {
+        self.values().get_or_default(field)
+    }
+
+    pub fn set_value(mut self: Pin<&mut Self>, field: &QString, value: &QVariant) -> bool {
+        let name = self.name().to_string();
+        let field_name = field.to_string();
+        let applied = runtime::with_world(|world| {
+            let type_id = world.get_resource::<BridgedResources>()?.lookup(&name)?;
+            Some(set_resource_field(world, type_id, &field_name, value))
+        })
+        .flatten()
+        .unwrap_or(false);
+
+        if applied {
+            runtime::request_update();
+            // Show the new value straight away rather than after the next update
+            let mut values = self.values().clone();
+            values.insert_clone(field, value);
+            self.as_mut().set_values(values);
+        }
+        applied
+    }
+
+    /// Read the resource again if it has changed since the last refresh
+    fn refresh(mut self: Pin<&mut Self>) {
+        let name = self.name().to_string();
+        let seen = self.rust().revision;
+        let fields = runtime::with_world(|world| {
+            let bridged = world.get_resource::<BridgedResources>()?;
+            let type_id = bridged.lookup(&name)?;
+            let revision = (type_id, bridged.revision(type_id));
+            if seen == Some(revision) {
+                return None;
+            }
+            Some((revision, resource_fields(world, type_id)?))
+        })
+        .flatten();
+
+        let Some((revision, fields)) = fields else {
+            return;
+        };
+
+        let mut values = QMap::<QMapPair_QString_QVariant>::default();
+        for (field, value) in fields {
+            values.insert(QString::from(field.as_str()), value);
+        }
+        self.as_mut().rust_mut().revision = Some(revision);
+        self.set_values(values);
+    }
+}
@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML singleton that mirrors the selection
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_selection")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qlist.h");
+        /// An alias to the QList<quint64> type
+        type QList_u64 = cxx_qt_lib::QList<u64>;
+        /// An alias to the QVariantList type
+        type QList_QVariant = cxx_qt_lib::QList<cxx_qt_lib::QVariant>;
+        include!("cxx-qt-lib/qmodelindex.h");
+        /// An alias to the QModelIndex type
+        type QModelIndex = cxx_qt_lib::QModelIndex;
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/model.h");
+
+        #[doc(hidden)]
+        #[rust_name = "model_index_entity"]
+        fn modelIndexEntity(index: &QModelIndex) -> u64;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QML singleton with the name
+        // BevySelection based on the Rust struct BevySelectionRust.
+        #[qobject]
+        #[qml_element]
+        #[qml_singleton]
+        #[qproperty(QList_u64, entities)]
+        type BevySelection = super::BevySelectionRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// Select an entity, keeping the current selection if `extend` is set
+        #[qinvokable]
+        fn select(self: Pin<&mut BevySelection>, entity: u64, extend: bool);
+
+        #[qinvokable]
+        fn deselect(self: Pin<&mut BevySelection>, entity: u64);
+
+        /// Select the entity if it is not selected and deselect it otherwise
+        #[qinvokable]
+        fn toggle(self: Pin<&mut BevySelection>, entity: u64);
+
+        #[qinvokable]
+        fn clear(self: Pin<&mut BevySelection>);
+
+        #[qinvokable]
+        #[cxx_name = "isSelected"]
+        fn is_selected(self: &BevySelection, entity: u64) -> bool;
+
+        /// Select the entities shown at indexes of the entity models, such as
+        /// the selectedIndexes of an ItemSelectionModel
+        #[qinvokable]
+        #[cxx_name = "selectIndexes"]
+        fn select_indexes(self: Pin<&mut BevySelection>, indexes: &QList_QVariant);
+    }
+
+    impl cxx_qt::Threading for BevySelection {}
+    impl cxx_qt::Constructor<()> for BevySelection {}
+}
+
+use core::pin::Pin;
+
+use bevy::{ecs::component::Tick, prelude::*};
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QList, QModelIndex, QVariant};
+
+use crate::{
+    runtime::{self, UpdateListener},
+    selection::Selection,
+};
+
+/// The Rust struct for the QObject
+///
+/// `entities` holds the bits of the selected entities in selection order.
+/// Changes made here apply to the [Selection] straight away, and changes
+/// made in Bevy, e.g. by picking in a view, show up after the next update.
+///
+/// `EntitySelectionModel.qml` keeps an ItemSelectionModel of one of the
+/// entity models in step with this singleton.
+#[derive(Default)]
+pub struct BevySelectionRust {
+    entities: QList<u64>,
+    changed: Option<Tick>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::BevySelection {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|selection| selection.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+        self.refresh();
+    }
+}
+
+impl qobject::BevySelection {
+    pub fn select(self: Pin<&mut Self>, entity: u64, extend: bool) {
+        if let Ok(entity) = Entity::try_from_bits(entity) {
+            self.modify(|selection| selection.select(entity, extend));
+        }
+    }
+
+    pub fn deselect(self: Pin<&mut Self>, entity: u64) {
+        if let Ok(entity) = Entity::try_from_bits(entity) {
+            self.modify(|selection| selection.deselect(entity));
+        }
+    }
+
+    pub fn toggle(self: Pin<&mut Self>, entity: u64) {
+        if let Ok(entity) = Entity::try_from_bits(entity) {
+            self.modify(|selection| selection.toggle(entity));
+        }
+    }
+
+    pub fn clear(self: Pin<&mut Self>) {
+        self.modify(Selection::clear);
+    }
+
+    pub fn is_selected(&self, entity: u64) -> bool {
+        self.entities().contains(&entity)
+    }
+
+    pub fn select_indexes(self: Pin<&mut Self>, indexes: &QList<QVariant>) {
+        let entities: Vec<Entity> = indexes
+            .iter()
+            .filter_map(|index| index.value::<QModelIndex>())
+            .filter_map(|index| Entity::try_from_bits(qobject::model_index_entity(&index)).ok())
+            .collect();
+        self.modify(|selection| selection.set(entities));
+    }
+
+    /// Change the selection in the world and show the result straight away
+    fn modify(self: Pin<&mut Self>, f: impl FnOnce(&mut Selection)) {
+        let applied = runtime::with_world(|world| {
+            world.get_resource_mut::<Selection>().map(|mut selection| {
+                // Only mark the selection as changed if it did change
+                let mut changed = selection.clone();
+                f(&mut changed);
+                if changed.entities() != selection.entities() {
+                    *selection = changed;
+                }
+            })
+        })
+        .flatten();
+
+        if applied.is_some() {
+            runtime::request_update();
+            self.refresh();
+        }
+    }
+
+    /// Read the selection again if it changed since the last refresh
+    fn refresh(mut self: Pin<&mut Self>) {
+        let seen = self.rust().changed;
+        let update = runtime::with_world(|world| {
+            let selection = world.get_resource_ref::<Selection>()?;
+            let changed = selection.last_changed();
+            (seen != Some(changed)).then(|| {
+                let mut entities = QList::<u64>::default();
+                for entity in selection.entities() {
+                    entities.append(entity.to_bits());
+                }
+                (changed, entities)
+            })
+        })
+        .flatten();
+
+        if let Some((changed, entities)) = update {
+            self.as_mut().rust_mut().changed = Some(changed);
+            self.set_entities(entities);
+        }
+    }
+}
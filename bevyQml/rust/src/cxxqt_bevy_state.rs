@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that follows a Bevy state
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_state")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qstringlist.h");
+        /// An alias to the QStringList type
+        type QStringList = cxx_qt_lib::QStringList;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BevyState based on the Rust struct BevyStateRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(QString, name)]
+        #[qproperty(QString, state)]
+        #[qproperty(QStringList, states)]
+        type BevyState = super::BevyStateRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// Ask for the state to change to the variant with this name in the
+        /// next state transition, returns false if there is no such variant
+        #[qinvokable]
+        #[cxx_name = "requestState"]
+        fn request_state(self: &BevyState, state: &QString) -> bool;
+
+        /// Emitted for every transition of the state, with an empty string
+        /// for a side where the state did not exist
+        #[qsignal]
+        fn transitioned(self: Pin<&mut BevyState>, exited: QString, entered: QString);
+
+        /// Emitted when a transition entered a state
+        #[qsignal]
+        fn entered(self: Pin<&mut BevyState>, state: QString);
+
+        /// Emitted when a transition left a state
+        #[qsignal]
+        fn exited(self: Pin<&mut BevyState>, state: QString);
+    }
+
+    impl cxx_qt::Threading for BevyState {}
+    impl cxx_qt::Constructor<()> for BevyState {}
+}
+
+use core::pin::Pin;
+
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QList, QString, QStringList};
+
+use crate::{
+    bridge::{self, BridgedStates},
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// Follows the state registered with a [crate::bridge::StateBridge] under
+/// `name`. `state` is the name of the variant it is in, or empty while the
+/// state does not exist, and `states` lists every variant. Bevy enums have
+/// no Qt enum to map to, so variants are passed around by name.
+///
+/// The signals report each transition once the app has updated, after the
+/// schedules of `OnEnter` and `OnExit` have run:
+///
+/// ```qml
+/// BevyState {
+///     name: "GameState"
+///     onEntered: state => {
+///         if (state === "Menu")
+///             stack.replace(menuPage);
+///         else if (state === "Playing")
+///             stack.replace(hudPage);
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct BevyStateRust {
+    name: QString,
+    state: QString,
+    states: QStringList,
+    /// The index of the state in [BridgedStates]
+    index: Option<usize>,
+    /// The sequence number of the next transition to report
+    next_transition: u64,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::BevyState {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|state| state.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.on_name_changed(|state| state.bind()).release();
+    }
+}
+
+impl qobject::BevyState {
+    pub fn request_state(&self, state: &QString) -> bool {
+        let Some(index) = self.rust().index else {
+            return false;
+        };
+        let name = state.to_string();
+        let requested = runtime::with_world(|world| bridge::request_state(world, index, &name))
+            .unwrap_or(false);
+        if requested {
+            runtime::request_update();
+        }
+        requested
+    }
+
+    /// Look the state up by its name, starting from the variant it is in now
+    fn bind(mut self: Pin<&mut Self>) {
+        let name = self.name().to_string();
+        let bound = runtime::with_world(|world| {
+            let bridged = world.get_resource::<BridgedStates>()?;
+            let index = bridged.lookup(&name)?;
+            Some((
+                index,
+                bridged.sequence(),
+                bridged.names(index).to_vec(),
+                bridged.current(index).unwrap_or_default().to_owned(),
+            ))
+        })
+        .flatten();
+
+        let (index, sequence, names, current) = match bound {
+            Some((index, sequence, names, current)) => (Some(index), sequence, names, current),
+            None => (None, 0, Vec::new(), String::new()),
+        };
+        let mut states = QList::<QString>::default();
+        for name in &names {
+            states.append(QString::from(name.as_str()));
+        }
+        let current = QString::from(current.as_str());
+
+        let rebound = self.rust().index != index;
+        let mut rust = self.as_mut().rust_mut();
+        rust.index = index;
+        rust.next_transition = sequence;
+        if rebound {
+            self.as_mut().set_states(QStringList::from(&states));
+        }
+        if *self.state() != current {
+            self.set_state(current);
+        }
+    }
+
+    /// Emit the transitions since the last refresh
+    fn refresh(mut self: Pin<&mut Self>) {
+        let Some(index) = self.rust().index else {
+            // The app may register the state after the element was created
+            if !self.name().is_empty() {
+                self.bind();
+            }
+            return;
+        };
+        let since = self.rust().next_transition;
+        let update = runtime::with_world(|world| {
+            let bridged = world.get_resource::<BridgedStates>()?;
+            Some((
+                bridged.sequence(),
+                bridged.transitions_since(index, since),
+                bridged.current(index).unwrap_or_default().to_owned(),
+            ))
+        })
+        .flatten();
+        let Some((sequence, transitions, current)) = update else {
+            return;
+        };
+
+        self.as_mut().rust_mut().next_transition = sequence;
+        let current = QString::from(current.as_str());
+        if *self.state() != current {
+            self.as_mut().set_state(current);
+        }
+        for transition in transitions {
+            let exited = QString::from(transition.exited.as_deref().unwrap_or_default());
+            let entered = QString::from(transition.entered.as_deref().unwrap_or_default());
+            if !exited.is_empty() {
+                self.as_mut().exited(exited.clone());
+            }
+            if !entered.is_empty() {
+                self.as_mut().entered(entered.clone());
+            }
+            self.as_mut().transitioned(exited, entered);
+        }
+    }
+}
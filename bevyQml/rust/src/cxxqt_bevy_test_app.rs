@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that runs the demo app for tests
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_test_app")]
+pub mod qobject {
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BevyTestApp based on the Rust struct BevyTestAppRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(bool, running)]
+        type BevyTestApp = super::BevyTestAppRust;
+    }
+
+    impl cxx_qt::Constructor<()> for BevyTestApp {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+
+use crate::{
+    cxxqt_object::CurveDemoPlugin,
+    plugin::BevyQmlPlugin,
+    runtime,
+    testing::{bevy_qml_test_plugins, QmlTestingPlugin},
+};
+
+/// The Rust struct for the QObject
+///
+/// Like `MyBevyApp`, but built with [bevy_qml_test_plugins] and
+/// [QmlTestingPlugin], so that the demo scene looks the same in every frame
+/// on every machine.
+#[derive(Default)]
+pub struct BevyTestAppRust {
+    running: bool,
+}
+
+impl cxx_qt::Initialize for qobject::BevyTestApp {
+    fn initialize(self: Pin<&mut Self>) {
+        App::new()
+            .add_plugins((
+                bevy_qml_test_plugins(),
+                BevyQmlPlugin::default(),
+                QmlTestingPlugin,
+                CurveDemoPlugin,
+            ))
+            .run();
+
+        self.set_running(runtime::is_running());
+    }
+}
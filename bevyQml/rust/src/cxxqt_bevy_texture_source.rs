@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that follows an offscreen Bevy image
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_texture_source")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qurl.h");
+        /// An alias to the QUrl type
+        type QUrl = cxx_qt_lib::QUrl;
+    }
+
+    /// A frame handed to the image provider as tightly packed RGBA8 pixels
+    #[namespace = "bevyqml"]
+    struct ImageFrame {
+        width: u32,
+        height: u32,
+        data: Vec<u8>,
+    }
+
+    #[namespace = "bevyqml"]
+    extern "Rust" {
+        /// The latest frame published for the given image name
+        #[cxx_name = "latestFrame"]
+        fn latest_frame(name: &QString) -> ImageFrame;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // BevyTextureSource based on the Rust struct BevyTextureSourceRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(QString, name)]
+        #[qproperty(QUrl, source)]
+        type BevyTextureSource = super::BevyTextureSourceRust;
+    }
+
+    impl cxx_qt::Threading for BevyTextureSource {}
+    impl cxx_qt::Constructor<()> for BevyTextureSource {}
+}
+
+use core::pin::Pin;
+
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QString, QUrl};
+
+use crate::{
+    panic,
+    render::published_sink,
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// The `source` property points at `image://bevy/<name>` and changes whenever
+/// Bevy publishes a new frame for the image, so binding it to an `Image` with
+/// `cache: false` keeps the image live.
+#[derive(Default)]
+pub struct BevyTextureSourceRust {
+    name: QString,
+    source: QUrl,
+    generation: u64,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::BevyTextureSource {
+    fn initialize(self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|source| source.refresh());
+        });
+        self.rust_mut().update_listener = Some(listener);
+    }
+}
+
+impl qobject::BevyTextureSource {
+    /// Point the source at the latest frame if a new one has been published
+    fn refresh(mut self: Pin<&mut Self>) {
+        let name = self.name().to_string();
+        if name.is_empty() {
+            return;
+        }
+
+        let generation = published_sink(&name).generation();
+        if generation == self.rust().generation {
+            return;
+        }
+
+        self.as_mut().rust_mut().generation = generation;
+        self.set_source(QUrl::from(&format!("image://bevy/{name}/{generation}")));
+    }
+}
+
+fn latest_frame(name: &QString) -> qobject::ImageFrame {
+    // Called from the image provider, which must not see a panic
+    let frame = panic::catch_panic("Reading a published frame", || {
+        published_sink(&name.to_string()).latest()
+    })
+    .flatten()
+    .unwrap_or_default();
+    qobject::ImageFrame {
+        width: frame.width,
+        height: frame.height,
+        data: frame.data,
+    }
+}
@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML singleton that controls the time of the
+/// world
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_time")]
+pub mod qobject {
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QML singleton with the name
+        // BevyTime based on the Rust struct BevyTimeRust.
+        #[qobject]
+        #[qml_element]
+        #[qml_singleton]
+        #[qproperty(bool, paused)]
+        #[qproperty(f64, time_scale)]
+        #[qproperty(f64, elapsed)]
+        type BevyTime = super::BevyTimeRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// Advance the paused world by one fixed timestep
+        #[qinvokable]
+        #[cxx_name = "stepFrame"]
+        fn step_frame(self: &BevyTime);
+    }
+
+    impl cxx_qt::Threading for BevyTime {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+
+use crate::{
+    runtime::{self, UpdateListener},
+    time_control,
+};
+
+/// The Rust struct for the QObject
+///
+/// `paused` and `timeScale` follow [Time<Virtual>], and `elapsed` is its
+/// elapsed time in seconds, which stands still while paused. `stepFrame()`
+/// moves the paused world forward by one timestep of [Time<Fixed>], see
+/// [crate::time_control]:
+///
+/// ```qml
+/// RowLayout {
+///     Button {
+///         text: BevyTime.paused ? "Play" : "Pause"
+///         onClicked: BevyTime.paused = !BevyTime.paused
+///     }
+///     Button {
+///         text: "Step"
+///         enabled: BevyTime.paused
+///         onClicked: BevyTime.stepFrame()
+///     }
+///     Slider {
+///         from: 0.1; to: 4
+///         value: BevyTime.timeScale
+///         onMoved: BevyTime.timeScale = value
+///     }
+///     Label { text: BevyTime.elapsed.toFixed(2) + " s" }
+/// }
+/// ```
+pub struct BevyTimeRust {
+    paused: bool,
+    time_scale: f64,
+    elapsed: f64,
+    /// Set while the properties are updated from the world, so the change
+    /// handlers do not write them back
+    syncing: bool,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for BevyTimeRust {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            time_scale: 1.0,
+            elapsed: 0.0,
+            syncing: false,
+            update_listener: None,
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::BevyTime {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|time| time.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+        self.as_mut().refresh();
+
+        self.as_mut()
+            .on_paused_changed(|time| time.write_paused())
+            .release();
+        self.on_time_scale_changed(|time| time.write_time_scale())
+            .release();
+    }
+}
+
+impl qobject::BevyTime {
+    pub fn step_frame(&self) {
+        runtime::send(time_control::step);
+        runtime::request_update();
+    }
+
+    fn write_paused(self: Pin<&mut Self>) {
+        if self.rust().syncing {
+            return;
+        }
+        let paused = *self.paused();
+        runtime::send(move |world| {
+            let mut time = world.resource_mut::<Time<Virtual>>();
+            if paused {
+                time.pause();
+            } else {
+                time.unpause();
+            }
+        });
+        runtime::request_update();
+    }
+
+    fn write_time_scale(self: Pin<&mut Self>) {
+        if self.rust().syncing {
+            return;
+        }
+        let time_scale = self.time_scale().max(0.0);
+        runtime::send(move |world| {
+            world
+                .resource_mut::<Time<Virtual>>()
+                .set_relative_speed_f64(time_scale);
+        });
+        runtime::request_update();
+    }
+
+    /// Read the virtual time of the world
+    fn refresh(mut self: Pin<&mut Self>) {
+        let time = runtime::with_world(|world| {
+            let time = world.get_resource::<Time<Virtual>>()?;
+            Some((
+                time.is_paused(),
+                time.relative_speed_f64(),
+                time.elapsed_seconds_f64(),
+            ))
+        })
+        .flatten();
+        let Some((paused, time_scale, elapsed)) = time else {
+            return;
+        };
+
+        self.as_mut().rust_mut().syncing = true;
+        if *self.paused() != paused {
+            self.as_mut().set_paused(paused);
+        }
+        if *self.time_scale() != time_scale {
+            self.as_mut().set_time_scale(time_scale);
+        }
+        if *self.elapsed() != elapsed {
+            self.as_mut().set_elapsed(elapsed);
+        }
+        self.as_mut().rust_mut().syncing = false;
+    }
+}
@@ -0,0 +1,185 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML singleton that captures Chrome traces
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_trace")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QML singleton with the name
+        // BevyTrace based on the Rust struct BevyTraceRust.
+        #[qobject]
+        #[qml_element]
+        #[qml_singleton]
+        #[qproperty(QString, path)]
+        #[qproperty(f64, max_duration_ms)]
+        #[qproperty(bool, capturing)]
+        #[qproperty(i32, event_count)]
+        type BevyTrace = super::BevyTraceRust;
+
+        /// Start a capture, dropping one which is running already
+        #[qinvokable]
+        fn start(self: Pin<&mut BevyTrace>);
+
+        /// Stop the capture and save it to path, saved is emitted once it
+        /// was written
+        #[qinvokable]
+        fn stop(self: Pin<&mut BevyTrace>);
+
+        /// Emitted once a capture was saved
+        #[qsignal]
+        fn saved(self: Pin<&mut BevyTrace>, path: QString, events: i32);
+
+        /// Emitted when a capture could not be saved
+        #[qsignal]
+        #[cxx_name = "traceError"]
+        fn trace_error(self: Pin<&mut BevyTrace>, message: QString);
+    }
+
+    impl cxx_qt::Threading for BevyTrace {}
+    impl cxx_qt::Constructor<()> for BevyTrace {}
+}
+
+use core::pin::Pin;
+use std::{path::PathBuf, thread};
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::QString;
+
+use crate::{
+    runtime::{self, UpdateListener},
+    trace,
+};
+
+/// The Rust struct for the QObject
+///
+/// The singleton captures the spans of the app and of the items showing it,
+/// see [crate::trace], and saves them as a Chrome trace at `path` when
+/// stopped, for the CTF Visualizer of Qt Creator or Perfetto. Captures stop
+/// on their own after `maxDurationMs`, unless it is 0, checked whenever the
+/// app updates. `capturing` and `eventCount` follow the capture and are not
+/// meant to be set from QML:
+///
+/// ```qml
+/// Shortcut {
+///     sequence: "F10"
+///     onActivated: BevyTrace.capturing ? BevyTrace.stop() : BevyTrace.start()
+/// }
+/// Connections {
+///     target: BevyTrace
+///     function onSaved(path, events) { console.log("saved", events, "events to", path) }
+/// }
+/// ```
+pub struct BevyTraceRust {
+    path: QString,
+    max_duration_ms: f64,
+    capturing: bool,
+    event_count: i32,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for BevyTraceRust {
+    fn default() -> Self {
+        Self {
+            path: QString::from("bevy-trace.json"),
+            max_duration_ms: 10_000.0,
+            capturing: false,
+            event_count: 0,
+            update_listener: None,
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::BevyTrace {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|bevy_trace| bevy_trace.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+        self.refresh();
+    }
+}
+
+impl qobject::BevyTrace {
+    pub fn start(mut self: Pin<&mut Self>) {
+        trace::start_capture();
+        if *self.event_count() != 0 {
+            self.as_mut().set_event_count(0);
+        }
+        if !*self.capturing() {
+            self.set_capturing(true);
+        }
+    }
+
+    pub fn stop(mut self: Pin<&mut Self>) {
+        let capture = trace::stop_capture();
+        if *self.capturing() {
+            self.as_mut().set_capturing(false);
+        }
+        let Some(capture) = capture else {
+            return;
+        };
+        let events = capture.event_count() as i32;
+        if *self.event_count() != events {
+            self.as_mut().set_event_count(events);
+        }
+        if capture.dropped() > 0 {
+            warn!(
+                "BevyTrace dropped {} events past the first {}",
+                capture.dropped(),
+                trace::CAPTURE_CAPACITY
+            );
+        }
+
+        // Large captures take a while to write
+        let path = PathBuf::from(self.path().to_string());
+        let qt_thread = self.qt_thread();
+        thread::Builder::new()
+            .name("bevy trace".into())
+            .spawn(move || {
+                let result = capture
+                    .save(&path)
+                    .map(|()| path.display().to_string())
+                    .map_err(|error| format!("cannot save {}: {error}", path.display()));
+                let _ = qt_thread.queue(move |bevy_trace| match result {
+                    Ok(path) => bevy_trace.saved(QString::from(path.as_str()), events),
+                    Err(message) => {
+                        warn!("BevyTrace {message}");
+                        bevy_trace.trace_error(QString::from(message.as_str()));
+                    }
+                });
+            })
+            .expect("failed to spawn the trace thread");
+    }
+
+    /// Follow the running capture, stopping it once it ran for long enough
+    fn refresh(mut self: Pin<&mut Self>) {
+        let Some(elapsed) = trace::capture_elapsed() else {
+            // Stopped from Rust, which keeps what was captured
+            if *self.capturing() {
+                self.set_capturing(false);
+            }
+            return;
+        };
+        let max_duration_ms = *self.max_duration_ms();
+        if max_duration_ms > 0.0 && elapsed.as_secs_f64() * 1000.0 >= max_duration_ms {
+            self.stop();
+            return;
+        }
+
+        let events = trace::captured_event_count() as i32;
+        if *self.event_count() != events {
+            self.as_mut().set_event_count(events);
+        }
+        if !*self.capturing() {
+            self.set_capturing(true);
+        }
+    }
+}
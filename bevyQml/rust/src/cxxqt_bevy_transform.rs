@@ -0,0 +1,234 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QObject that mirrors the Transform of an entity
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_transform")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvector3d.h");
+        /// An alias to the QVector3D type
+        type QVector3D = cxx_qt_lib::QVector3D;
+        include!("bevyqml/convert.h");
+        /// An alias to the QQuaternion type
+        type QQuaternion = crate::convert::QQuaternion;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QObject class with the name
+        // TransformBridge based on the Rust struct TransformBridgeRust.
+        #[qobject]
+        #[qml_element]
+        #[qproperty(u64, entity)]
+        #[qproperty(QString, name)]
+        #[qproperty(bool, bound)]
+        #[qproperty(QVector3D, position)]
+        #[qproperty(QQuaternion, rotation)]
+        #[qproperty(QVector3D, scale)]
+        type TransformBridge = super::TransformBridgeRust;
+    }
+
+    impl cxx_qt::Threading for TransformBridge {}
+    impl cxx_qt::Constructor<()> for TransformBridge {}
+}
+
+use core::pin::Pin;
+use std::{cell::Cell, rc::Rc};
+
+use bevy::{ecs::component::Tick, prelude::*};
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QString, QVector3D};
+
+use crate::{
+    batch::{self, BatchSubscription},
+    convert::{FromQt, IntoQt, QQuaternion},
+    runtime::{self, UpdateListener},
+};
+
+/// The Rust struct for the QObject
+///
+/// The entity is found by its `Name` when `name` is set, and by `entity`,
+/// the bits of an [Entity] as returned by [Entity::to_bits], otherwise.
+/// `bound` tells whether such an entity with a [Transform] exists. Once it
+/// is found, the properties are only read again after updates which changed
+/// its [Transform], see [crate::batch].
+///
+/// `position`, `rotation` and `scale` follow the [Transform] of the entity,
+/// and setting them changes it, so they can be animated from QML:
+///
+/// ```qml
+/// TransformBridge {
+///     name: "Door"
+///     Behavior on rotation { RotationAnimation { duration: 300 } }
+///     NumberAnimation on position.y { from: 0; to: 2; duration: 1000 }
+/// }
+/// ```
+pub struct TransformBridgeRust {
+    entity: u64,
+    name: QString,
+    bound: bool,
+    position: QVector3D,
+    rotation: QQuaternion,
+    scale: QVector3D,
+    target: Option<Entity>,
+    changed: Option<Tick>,
+    /// Set while the properties are updated from the world, so the change
+    /// handlers do not write them back
+    syncing: bool,
+    /// Whether the entity is looked for after every update, which stops
+    /// once it is found and the subscription to its changes takes over
+    searching: Rc<Cell<bool>>,
+    subscription: Option<BatchSubscription>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for TransformBridgeRust {
+    fn default() -> Self {
+        Self {
+            entity: 0,
+            name: QString::default(),
+            bound: false,
+            position: Vec3::ZERO.into_qt(),
+            rotation: QQuaternion::default(),
+            scale: Vec3::ONE.into_qt(),
+            target: None,
+            changed: None,
+            syncing: false,
+            searching: Rc::new(Cell::new(true)),
+            subscription: None,
+            update_listener: None,
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::TransformBridge {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let searching = self.rust().searching.clone();
+        let listener = runtime::on_update(move || {
+            if searching.get() {
+                let _ = qt_thread.queue(|transform| transform.refresh());
+            }
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        self.as_mut()
+            .on_entity_changed(|transform| transform.retarget())
+            .release();
+        self.as_mut()
+            .on_name_changed(|transform| transform.retarget())
+            .release();
+        self.as_mut()
+            .on_position_changed(|transform| transform.write())
+            .release();
+        self.as_mut()
+            .on_rotation_changed(|transform| transform.write())
+            .release();
+        self.on_scale_changed(|transform| transform.write())
+            .release();
+    }
+}
+
+impl qobject::TransformBridge {
+    /// Look the entity up again and read its transform
+    fn retarget(mut self: Pin<&mut Self>) {
+        let mut rust = self.as_mut().rust_mut();
+        rust.target = None;
+        rust.changed = None;
+        rust.subscription = None;
+        rust.searching.set(true);
+        self.refresh();
+    }
+
+    /// Change the transform of the entity to the properties
+    fn write(self: Pin<&mut Self>) {
+        if self.rust().syncing {
+            return;
+        }
+        let Some(entity) = self.rust().target else {
+            return;
+        };
+
+        let transform = Transform {
+            translation: Vec3::from_qt(self.position()),
+            rotation: Quat::from_qt(self.rotation()).normalize(),
+            scale: Vec3::from_qt(self.scale()),
+        };
+        runtime::with_world(|world| {
+            if let Some(mut current) = world.get_mut::<Transform>(entity) {
+                if *current != transform {
+                    *current = transform;
+                    runtime::request_update();
+                }
+            }
+        });
+    }
+
+    /// Find the entity if needed and read its transform if it has changed
+    fn refresh(mut self: Pin<&mut Self>) {
+        let name = self.name().to_string();
+        let bits = *self.entity();
+        let target = self.rust().target;
+        let seen = self.rust().changed;
+        let update = runtime::with_world(|world| {
+            let entity = target
+                .filter(|entity| world.get_entity(*entity).is_some())
+                .or_else(|| find_entity(world, &name, bits))?;
+            let transform = world.get_entity(entity)?.get_ref::<Transform>()?;
+            let changed = transform.last_changed();
+            let transform = (seen != Some(changed)).then_some(*transform);
+            Some((entity, changed, transform))
+        });
+
+        let Some(update) = update else {
+            // The app is busy, try again after the next update
+            return;
+        };
+        let Some((entity, changed, transform)) = update else {
+            let mut rust = self.as_mut().rust_mut();
+            rust.target = None;
+            rust.changed = None;
+            rust.subscription = None;
+            rust.searching.set(true);
+            self.set_bound(false);
+            return;
+        };
+
+        if self.rust().target != Some(entity) {
+            let qt_thread = self.qt_thread();
+            let subscription = batch::subscribe::<Transform>(entity, move || {
+                let _ = qt_thread.queue(|transform| transform.refresh());
+            });
+            let mut rust = self.as_mut().rust_mut();
+            rust.subscription = Some(subscription);
+            rust.searching.set(false);
+        }
+        let mut rust = self.as_mut().rust_mut();
+        rust.target = Some(entity);
+        rust.changed = Some(changed);
+        if let Some(transform) = transform {
+            self.as_mut().rust_mut().syncing = true;
+            self.as_mut().set_position(transform.translation.into_qt());
+            self.as_mut().set_rotation(transform.rotation.into_qt());
+            self.as_mut().set_scale(transform.scale.into_qt());
+            self.as_mut().rust_mut().syncing = false;
+        }
+        self.set_bound(true);
+    }
+}
+
+/// The first entity with the name, or the entity with the bits without a name
+pub(crate) fn find_entity(world: &mut World, name: &str, bits: u64) -> Option<Entity> {
+    if name.is_empty() {
+        let entity = Entity::try_from_bits(bits).ok()?;
+        return world.get_entity(entity).is_some().then_some(entity);
+    }
+    world
+        .query::<(Entity, &Name)>()
+        .iter(world)
+        .filter(|(_, other)| other.as_str() == name)
+        .map(|(entity, _)| entity)
+        .min()
+}
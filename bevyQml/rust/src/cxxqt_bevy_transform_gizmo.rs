@@ -0,0 +1,260 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML singleton that controls the transform
+/// gizmo
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_transform_gizmo")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("bevyqml/convert.h");
+        /// An alias to the QMatrix4x4 type
+        type QMatrix4x4 = crate::convert::QMatrix4x4;
+    }
+
+    /// What the handles of the gizmo change
+    #[qenum(BevyTransformGizmo)]
+    enum Mode {
+        Translate,
+        Rotate,
+        Scale,
+    }
+
+    /// Which axes the handles follow when translating or rotating
+    #[qenum(BevyTransformGizmo)]
+    enum Space {
+        World,
+        /// The axes of the entity, which scaling always follows
+        Local,
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QML singleton with the name
+        // BevyTransformGizmo based on the Rust struct BevyTransformGizmoRust.
+        #[qobject]
+        #[qml_element]
+        #[qml_singleton]
+        #[qproperty(bool, enabled)]
+        #[qproperty(Mode, mode)]
+        #[qproperty(Space, space)]
+        #[qproperty(bool, snapping)]
+        #[qproperty(f64, translation_step)]
+        #[qproperty(f64, rotation_step)]
+        #[qproperty(f64, scale_step)]
+        #[qproperty(f64, size)]
+        #[qproperty(bool, dragging)]
+        type BevyTransformGizmo = super::BevyTransformGizmoRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// A handle was let go after changing the transform of an entity
+        ///
+        /// The transform is the local transform the entity ended up with.
+        #[qsignal]
+        #[cxx_name = "transformEdited"]
+        fn transform_edited(
+            self: Pin<&mut BevyTransformGizmo>,
+            entity: u64,
+            new_transform: QMatrix4x4,
+        );
+    }
+
+    impl cxx_qt::Threading for BevyTransformGizmo {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+
+use crate::{
+    convert::IntoQt,
+    runtime::{self, UpdateListener},
+    transform_gizmo::{
+        TransformGizmo, TransformGizmoMode, TransformGizmoSpace, TransformGizmoState,
+    },
+};
+
+/// The Rust struct for the QObject
+///
+/// The properties mirror the [TransformGizmo] resource, so the gizmo can be
+/// switched on and configured from a toolbar. `dragging` tells whether a
+/// handle is being dragged. The steps apply while `snapping` is set, with
+/// `rotationStep` in degrees. Every edit is also put on the undo stack, see
+/// `BevyUndoStack`.
+///
+/// ```qml
+/// ToolBar {
+///     RowLayout {
+///         ToolButton {
+///             text: "Move"
+///             checked: BevyTransformGizmo.mode === BevyTransformGizmo.Translate
+///             onClicked: BevyTransformGizmo.mode = BevyTransformGizmo.Translate
+///         }
+///         CheckBox {
+///             text: "Snap"
+///             checked: BevyTransformGizmo.snapping
+///             onToggled: BevyTransformGizmo.snapping = checked
+///         }
+///     }
+/// }
+/// Connections {
+///     target: BevyTransformGizmo
+///     function onTransformEdited(entity, transform) { inspector.refresh(entity) }
+/// }
+/// ```
+pub struct BevyTransformGizmoRust {
+    enabled: bool,
+    mode: qobject::Mode,
+    space: qobject::Space,
+    snapping: bool,
+    translation_step: f64,
+    rotation_step: f64,
+    scale_step: f64,
+    size: f64,
+    dragging: bool,
+    /// Set while the properties are updated from the world, so the change
+    /// handlers do not write them back
+    syncing: bool,
+    update_listener: Option<UpdateListener>,
+}
+
+impl Default for BevyTransformGizmoRust {
+    fn default() -> Self {
+        let settings = TransformGizmo::default();
+        Self {
+            enabled: settings.enabled,
+            mode: qobject::Mode::Translate,
+            space: qobject::Space::World,
+            snapping: settings.snapping,
+            translation_step: settings.translation_step.into(),
+            rotation_step: settings.rotation_step.into(),
+            scale_step: settings.scale_step.into(),
+            size: settings.size.into(),
+            dragging: false,
+            syncing: false,
+            update_listener: None,
+        }
+    }
+}
+
+impl cxx_qt::Initialize for qobject::BevyTransformGizmo {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|gizmo| gizmo.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+
+        // Start from what the app configured
+        let settings =
+            runtime::with_world(|world| world.get_resource::<TransformGizmo>().cloned()).flatten();
+        if let Some(settings) = settings {
+            self.as_mut().read_settings(&settings);
+        }
+
+        self.as_mut()
+            .on_enabled_changed(|gizmo| gizmo.write_settings())
+            .release();
+        self.as_mut()
+            .on_mode_changed(|gizmo| gizmo.write_settings())
+            .release();
+        self.as_mut()
+            .on_space_changed(|gizmo| gizmo.write_settings())
+            .release();
+        self.as_mut()
+            .on_snapping_changed(|gizmo| gizmo.write_settings())
+            .release();
+        self.as_mut()
+            .on_translation_step_changed(|gizmo| gizmo.write_settings())
+            .release();
+        self.as_mut()
+            .on_rotation_step_changed(|gizmo| gizmo.write_settings())
+            .release();
+        self.as_mut()
+            .on_scale_step_changed(|gizmo| gizmo.write_settings())
+            .release();
+        self.on_size_changed(|gizmo| gizmo.write_settings())
+            .release();
+    }
+}
+
+impl qobject::BevyTransformGizmo {
+    fn read_settings(mut self: Pin<&mut Self>, settings: &TransformGizmo) {
+        self.as_mut().rust_mut().syncing = true;
+        self.as_mut().set_enabled(settings.enabled);
+        self.as_mut().set_mode(match settings.mode {
+            TransformGizmoMode::Translate => qobject::Mode::Translate,
+            TransformGizmoMode::Rotate => qobject::Mode::Rotate,
+            TransformGizmoMode::Scale => qobject::Mode::Scale,
+        });
+        self.as_mut().set_space(match settings.space {
+            TransformGizmoSpace::World => qobject::Space::World,
+            TransformGizmoSpace::Local => qobject::Space::Local,
+        });
+        self.as_mut().set_snapping(settings.snapping);
+        self.as_mut()
+            .set_translation_step(settings.translation_step.into());
+        self.as_mut()
+            .set_rotation_step(settings.rotation_step.into());
+        self.as_mut().set_scale_step(settings.scale_step.into());
+        self.as_mut().set_size(settings.size.into());
+        self.as_mut().rust_mut().syncing = false;
+    }
+
+    fn write_settings(self: Pin<&mut Self>) {
+        if self.rust().syncing {
+            return;
+        }
+        let mode = *self.mode();
+        let space = *self.space();
+        let settings = TransformGizmo {
+            enabled: *self.enabled(),
+            mode: if mode == qobject::Mode::Rotate {
+                TransformGizmoMode::Rotate
+            } else if mode == qobject::Mode::Scale {
+                TransformGizmoMode::Scale
+            } else {
+                TransformGizmoMode::Translate
+            },
+            space: if space == qobject::Space::Local {
+                TransformGizmoSpace::Local
+            } else {
+                TransformGizmoSpace::World
+            },
+            snapping: *self.snapping(),
+            translation_step: *self.translation_step() as f32,
+            rotation_step: *self.rotation_step() as f32,
+            scale_step: *self.scale_step() as f32,
+            size: *self.size() as f32,
+        };
+        runtime::send(move |world| world.insert_resource(settings));
+        runtime::request_update();
+    }
+
+    /// Emit the edits made since the last update
+    fn refresh(mut self: Pin<&mut Self>) {
+        let update = runtime::with_world(|world| {
+            let mut state = world.get_resource_mut::<TransformGizmoState>()?;
+            let edits = if state.edits.is_empty() {
+                Vec::new()
+            } else {
+                std::mem::take(&mut state.edits)
+            };
+            Some((edits, state.is_dragging()))
+        })
+        .flatten();
+        let Some((edits, dragging)) = update else {
+            return;
+        };
+
+        if *self.dragging() != dragging {
+            self.as_mut().set_dragging(dragging);
+        }
+        for edited in edits {
+            self.as_mut().transform_edited(
+                edited.entity.to_bits(),
+                edited.new_transform.compute_matrix().into_qt(),
+            );
+        }
+    }
+}
@@ -0,0 +1,372 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML singleton of the undo stack
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_undo_stack")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+        include!("bevyqml/convert.h");
+        /// An alias to the QMatrix4x4 type
+        type QMatrix4x4 = crate::convert::QMatrix4x4;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QML singleton with the name
+        // BevyUndoStack based on the Rust struct BevyUndoStackRust.
+        #[qobject]
+        #[qml_element]
+        #[qml_singleton]
+        #[qproperty(bool, can_undo)]
+        #[qproperty(bool, can_redo)]
+        #[qproperty(QString, undo_text)]
+        #[qproperty(QString, redo_text)]
+        #[qproperty(bool, clean)]
+        #[qproperty(i32, count)]
+        #[qproperty(i32, index)]
+        #[qproperty(i32, undo_limit)]
+        type BevyUndoStack = super::BevyUndoStackRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// Take back the last applied edit
+        #[qinvokable]
+        fn undo(self: Pin<&mut BevyUndoStack>);
+
+        /// Apply the edit after the last applied one again
+        #[qinvokable]
+        fn redo(self: Pin<&mut BevyUndoStack>);
+
+        /// Drop every edit without undoing it
+        #[qinvokable]
+        fn clear(self: Pin<&mut BevyUndoStack>);
+
+        /// Mark the current state as clean, e.g. after saving, like
+        /// QUndoStack::setClean()
+        #[qinvokable]
+        #[cxx_name = "markClean"]
+        fn mark_clean(self: Pin<&mut BevyUndoStack>);
+
+        /// Undo and redo the edits made until endMacro together
+        #[qinvokable]
+        #[cxx_name = "beginMacro"]
+        fn begin_macro(self: Pin<&mut BevyUndoStack>, text: &QString);
+
+        #[qinvokable]
+        #[cxx_name = "endMacro"]
+        fn end_macro(self: Pin<&mut BevyUndoStack>);
+
+        /// The text of the edit at the index, for a list of the history
+        #[qinvokable]
+        fn text(self: &BevyUndoStack, index: i32) -> QString;
+
+        /// Spawn an empty entity, returns it or 0 if no app is running
+        #[qinvokable]
+        #[cxx_name = "spawnEntity"]
+        fn spawn_entity(self: Pin<&mut BevyUndoStack>) -> u64;
+
+        /// Despawn an entity, keeping its reflected components for undo
+        #[qinvokable]
+        fn despawn(self: Pin<&mut BevyUndoStack>, entity: u64);
+
+        /// Change the transform of an entity
+        ///
+        /// With `merge` set, consecutive changes of the same entity become
+        /// one edit, e.g. while dragging a slider.
+        #[qinvokable]
+        #[cxx_name = "setTransform"]
+        fn set_transform(
+            self: Pin<&mut BevyUndoStack>,
+            entity: u64,
+            transform: &QMatrix4x4,
+            merge: bool,
+        );
+
+        /// Insert a reflected component, or overwrite what the value
+        /// mentions of an existing one
+        ///
+        /// With `merge` set, consecutive edits of the same component become
+        /// one edit.
+        #[qinvokable]
+        #[cxx_name = "setComponent"]
+        fn set_component(
+            self: Pin<&mut BevyUndoStack>,
+            entity: u64,
+            type_name: &QString,
+            value: &QVariant,
+            merge: bool,
+        );
+    }
+
+    impl cxx_qt::Threading for BevyUndoStack {}
+    impl cxx_qt::Constructor<()> for BevyUndoStack {}
+}
+
+use core::pin::Pin;
+
+use bevy::prelude::*;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QString, QVariant};
+
+use crate::{
+    commands::{self, QmlCommandQueue},
+    convert::{FromQt, QMatrix4x4},
+    runtime::{self, UpdateListener},
+    undo::{self, DespawnEntity, QmlUndoStack, SetComponent, SetTransform, SpawnEntity},
+};
+
+/// The Rust struct for the QObject
+///
+/// Mirrors the [QmlUndoStack] of the app, with the properties and methods of
+/// a QUndoStack, except that `markClean()` stands in for `setClean()`, which
+/// is the setter of `clean`. Edits made through `spawnEntity`, `despawn`,
+/// `setTransform` and `setComponent` are applied straight away and can be
+/// undone, as can the edits made with the handles of the transform gizmo.
+/// Edits made through `BevyCommands` are not recorded. See [crate::undo] for
+/// edits of other kinds.
+///
+/// ```qml
+/// Action {
+///     text: qsTr("Undo %1").arg(BevyUndoStack.undoText)
+///     shortcut: StandardKey.Undo
+///     enabled: BevyUndoStack.canUndo
+///     onTriggered: BevyUndoStack.undo()
+/// }
+/// Slider {
+///     onMoved: {
+///         const transform = Qt.matrix4x4();
+///         transform.translate(Qt.vector3d(0, value, 0));
+///         BevyUndoStack.setTransform(selected, transform, true);
+///     }
+/// }
+/// ```
+///
+/// Several edits become one with a macro:
+///
+/// ```qml
+/// onClicked: {
+///     BevyUndoStack.beginMacro(qsTr("Delete selection"));
+///     for (const entity of BevySelection.entities)
+///         BevyUndoStack.despawn(entity);
+///     BevyUndoStack.endMacro();
+/// }
+/// ```
+#[derive(Default)]
+pub struct BevyUndoStackRust {
+    can_undo: bool,
+    can_redo: bool,
+    undo_text: QString,
+    redo_text: QString,
+    clean: bool,
+    count: i32,
+    index: i32,
+    undo_limit: i32,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::BevyUndoStack {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|stack| stack.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+        self.as_mut().refresh();
+
+        self.on_undo_limit_changed(|stack| stack.write_undo_limit())
+            .release();
+    }
+}
+
+impl qobject::BevyUndoStack {
+    pub fn undo(self: Pin<&mut Self>) {
+        self.edit("undo", undo::undo);
+    }
+
+    pub fn redo(self: Pin<&mut Self>) {
+        self.edit("redo", undo::redo);
+    }
+
+    pub fn clear(self: Pin<&mut Self>) {
+        self.modify(QmlUndoStack::clear);
+    }
+
+    pub fn mark_clean(self: Pin<&mut Self>) {
+        self.modify(QmlUndoStack::set_clean);
+    }
+
+    pub fn begin_macro(self: Pin<&mut Self>, text: &QString) {
+        let text = text.to_string();
+        self.edit("begin a macro", |world| {
+            undo::begin_macro(world, text);
+            Ok(())
+        });
+    }
+
+    pub fn end_macro(self: Pin<&mut Self>) {
+        self.edit("end a macro", |world| {
+            undo::end_macro(world);
+            Ok(())
+        });
+    }
+
+    pub fn text(&self, index: i32) -> QString {
+        let text = usize::try_from(index).ok().and_then(|index| {
+            runtime::with_world(|world| {
+                world
+                    .get_resource::<QmlUndoStack>()
+                    .map(|stack| stack.text(index))
+            })
+            .flatten()
+        });
+        QString::from(text.unwrap_or_default().as_str())
+    }
+
+    pub fn spawn_entity(self: Pin<&mut Self>) -> u64 {
+        self.edit("spawn an entity", |world| {
+            let entity = world.entities().reserve_entity();
+            undo::push(world, SpawnEntity::new(entity))?;
+            Ok(entity.to_bits())
+        })
+        .unwrap_or(0)
+    }
+
+    pub fn despawn(self: Pin<&mut Self>, entity: u64) {
+        let Some(entity) = entity_from_bits(entity) else {
+            return;
+        };
+        self.edit("despawn an entity", |world| {
+            undo::push(world, DespawnEntity::new(world, entity))
+        });
+    }
+
+    pub fn set_transform(self: Pin<&mut Self>, entity: u64, transform: &QMatrix4x4, merge: bool) {
+        let Some(entity) = entity_from_bits(entity) else {
+            return;
+        };
+        let transform = Transform::from_matrix(Mat4::from_qt(transform));
+        self.edit("change a transform", |world| {
+            let command = SetTransform::new(world, entity, transform);
+            if merge {
+                undo::push(world, command.merging())
+            } else {
+                undo::push(world, command)
+            }
+        });
+    }
+
+    pub fn set_component(
+        self: Pin<&mut Self>,
+        entity: u64,
+        type_name: &QString,
+        value: &QVariant,
+        merge: bool,
+    ) {
+        let Some(entity) = entity_from_bits(entity) else {
+            return;
+        };
+        let type_name = type_name.to_string();
+        let value = value.clone();
+        self.edit("set a component", |world| {
+            let command = SetComponent::new(world, entity, type_name, value);
+            if merge {
+                undo::push(world, command.merging())
+            } else {
+                undo::push(world, command)
+            }
+        });
+    }
+
+    /// Run an edit with the world and show the state of the stack after it
+    fn edit<R>(
+        self: Pin<&mut Self>,
+        what: &str,
+        f: impl FnOnce(&mut World) -> Result<R, String>,
+    ) -> Option<R> {
+        let result = runtime::with_world(|world| {
+            // Edits queued through BevyCommands come first
+            if world.contains_resource::<QmlCommandQueue>() {
+                commands::apply_qml_commands(world);
+            }
+            f(world)
+        });
+        runtime::request_update();
+        self.refresh();
+        match result {
+            Some(Ok(output)) => Some(output),
+            Some(Err(error)) => {
+                warn!("Cannot {what} from QML: {error}");
+                None
+            }
+            None => {
+                warn!("Cannot {what} from QML without a running Bevy app");
+                None
+            }
+        }
+    }
+
+    /// Change the stack itself, without touching the world
+    fn modify(self: Pin<&mut Self>, f: impl FnOnce(&mut QmlUndoStack)) {
+        runtime::with_world(|world| {
+            if let Some(mut stack) = world.get_resource_mut::<QmlUndoStack>() {
+                f(&mut stack);
+            }
+        });
+        self.refresh();
+    }
+
+    fn write_undo_limit(self: Pin<&mut Self>) {
+        let limit = usize::try_from(*self.undo_limit()).unwrap_or_default();
+        self.modify(|stack| {
+            if stack.undo_limit() != limit {
+                stack.set_undo_limit(limit);
+            }
+        });
+    }
+
+    /// Read the state of the stack
+    fn refresh(mut self: Pin<&mut Self>) {
+        let state = runtime::with_world(|world| {
+            let stack = world.get_resource::<QmlUndoStack>()?;
+            Some((
+                stack.can_undo(),
+                stack.can_redo(),
+                QString::from(stack.undo_text().as_str()),
+                QString::from(stack.redo_text().as_str()),
+                stack.is_clean(),
+                i32::try_from(stack.count()).unwrap_or(i32::MAX),
+                i32::try_from(stack.index()).unwrap_or(i32::MAX),
+                i32::try_from(stack.undo_limit()).unwrap_or(i32::MAX),
+            ))
+        })
+        .flatten();
+        let Some((can_undo, can_redo, undo_text, redo_text, clean, count, index, undo_limit)) =
+            state
+        else {
+            return;
+        };
+
+        // The setters only emit the change signals for new values
+        self.as_mut().set_can_undo(can_undo);
+        self.as_mut().set_can_redo(can_redo);
+        self.as_mut().set_undo_text(undo_text);
+        self.as_mut().set_redo_text(redo_text);
+        self.as_mut().set_clean(clean);
+        self.as_mut().set_count(count);
+        self.as_mut().set_index(index);
+        self.set_undo_limit(undo_limit);
+    }
+}
+
+fn entity_from_bits(bits: u64) -> Option<Entity> {
+    let entity = Entity::try_from_bits(bits).ok();
+    if entity.is_none() {
+        warn!("{bits} passed from QML is not a valid entity");
+    }
+    entity
+}
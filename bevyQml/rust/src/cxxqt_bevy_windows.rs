@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The bridge definition for the QML singleton that opens additional windows
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_bevy_windows")]
+pub mod qobject {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qstringlist.h");
+        /// An alias to the QStringList type
+        type QStringList = cxx_qt_lib::QStringList;
+    }
+
+    unsafe extern "RustQt" {
+        // The QObject definition
+        // We tell CXX-Qt that we want a QML singleton with the name
+        // BevyWindows based on the Rust struct BevyWindowsRust.
+        #[qobject]
+        #[qml_element]
+        #[qml_singleton]
+        #[qproperty(QStringList, windows)]
+        #[qproperty(QStringList, worlds)]
+        type BevyWindows = super::BevyWindowsRust;
+    }
+
+    unsafe extern "RustQt" {
+        /// Open a window showing the QML file at `source`
+        ///
+        /// `world` names an independent app for the window to show, or is
+        /// empty for the main world. Opening a window which is already open
+        /// brings it to the front.
+        #[qinvokable]
+        #[cxx_name = "openWindow"]
+        fn open_window(
+            self: Pin<&mut BevyWindows>,
+            name: &QString,
+            source: &QString,
+            world: &QString,
+        );
+
+        #[qinvokable]
+        #[cxx_name = "closeWindow"]
+        fn close_window(self: Pin<&mut BevyWindows>, name: &QString);
+
+        #[qinvokable]
+        #[cxx_name = "isOpen"]
+        fn is_open(self: &BevyWindows, name: &QString) -> bool;
+    }
+
+    impl cxx_qt::Threading for BevyWindows {}
+    impl cxx_qt::Constructor<()> for BevyWindows {}
+}
+
+use core::pin::Pin;
+
+use bevy::ecs::component::Tick;
+use cxx_qt::{CxxQtType, Threading};
+use cxx_qt_lib::{QList, QString, QStringList};
+
+use crate::{
+    runtime::{self, UpdateListener},
+    window::QmlWindowRegistry,
+};
+
+/// The Rust struct for the QObject
+///
+/// `windows` holds the names of the open windows of the [QmlWindowRegistry]
+/// and `worlds` the names of the independent apps a window can show.
+#[derive(Default)]
+pub struct BevyWindowsRust {
+    windows: QStringList,
+    worlds: QStringList,
+    open: Vec<String>,
+    world_names: Vec<String>,
+    changed: Option<Tick>,
+    update_listener: Option<UpdateListener>,
+}
+
+impl cxx_qt::Initialize for qobject::BevyWindows {
+    fn initialize(mut self: Pin<&mut Self>) {
+        let qt_thread = self.qt_thread();
+        let listener = runtime::on_update(move || {
+            let _ = qt_thread.queue(|windows| windows.refresh());
+        });
+        self.as_mut().rust_mut().update_listener = Some(listener);
+        self.refresh();
+    }
+}
+
+impl qobject::BevyWindows {
+    pub fn open_window(self: Pin<&mut Self>, name: &QString, source: &QString, world: &QString) {
+        let (name, source, world) = (name.to_string(), source.to_string(), world.to_string());
+        self.modify(|registry| registry.open(name, source, world));
+    }
+
+    pub fn close_window(self: Pin<&mut Self>, name: &QString) {
+        let name = name.to_string();
+        self.modify(|registry| registry.close(&name));
+    }
+
+    pub fn is_open(&self, name: &QString) -> bool {
+        self.rust().open.contains(&name.to_string())
+    }
+
+    fn modify(self: Pin<&mut Self>, f: impl FnOnce(&mut QmlWindowRegistry)) {
+        let applied = runtime::with_world(|world| {
+            world
+                .get_resource_mut::<QmlWindowRegistry>()
+                .map(|mut registry| f(&mut registry))
+        })
+        .flatten();
+
+        if applied.is_some() {
+            runtime::request_update();
+            self.refresh();
+        }
+    }
+
+    /// Read the open windows again if the registry changed since the last refresh
+    fn refresh(mut self: Pin<&mut Self>) {
+        let world_names = runtime::world_names();
+        if world_names != self.rust().world_names {
+            let worlds = string_list(&world_names);
+            self.as_mut().rust_mut().world_names = world_names;
+            self.as_mut().set_worlds(worlds);
+        }
+
+        let seen = self.rust().changed;
+        let update = runtime::with_world(|world| {
+            let registry = world.get_resource_ref::<QmlWindowRegistry>()?;
+            let changed = registry.last_changed();
+            (seen != Some(changed)).then(|| {
+                let open: Vec<String> = registry
+                    .windows()
+                    .iter()
+                    .filter(|window| window.open)
+                    .map(|window| window.name.clone())
+                    .collect();
+                (changed, open)
+            })
+        })
+        .flatten();
+
+        if let Some((changed, open)) = update {
+            let windows = string_list(&open);
+            let mut rust = self.as_mut().rust_mut();
+            rust.changed = Some(changed);
+            rust.open = open;
+            self.set_windows(windows);
+        }
+    }
+}
+
+fn string_list(strings: &[String]) -> QStringList {
+    let mut list = QList::<QString>::default();
+    for string in strings {
+        list.append(QString::from(string.as_str()));
+    }
+    QStringList::from(&list)
+}
@@ -14,6 +14,7 @@ use bevy::{
     prelude::*,
 };
 
+use crate::bridge::ResourceBridge;
 
 #[derive(Component)]
 struct Curve(CubicCurve<Vec3>);
@@ -69,6 +70,32 @@ pub struct MyObjectRust {
 }
 // ANCHOR_END: book_rustobj_struct
 
+/// The demo scene: a cube following a bezier curve above a ground plane
+pub struct CurveDemoPlugin;
+
+impl Plugin for CurveDemoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurveDemoSettings>()
+            .add_plugins(ResourceBridge::<CurveDemoSettings>::default())
+            .add_systems(Startup, setup)
+            .add_systems(Update, animate_cube);
+    }
+}
+
+/// Settings of the demo scene, which QML edits through a BevyResource
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct CurveDemoSettings {
+    /// Whether the curve the cube follows is drawn
+    pub show_curve: bool,
+}
+
+impl Default for CurveDemoSettings {
+    fn default() -> Self {
+        Self { show_curve: true }
+    }
+}
+
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -125,12 +152,19 @@ fn setup(
     });
 }
 
-fn animate_cube(time: Res<Time>, mut query: Query<(&mut Transform, &Curve)>, mut gizmos: Gizmos) {
+fn animate_cube(
+    time: Res<Time>,
+    settings: Res<CurveDemoSettings>,
+    mut query: Query<(&mut Transform, &Curve)>,
+    mut gizmos: Gizmos,
+) {
     let t = (time.elapsed_seconds().sin() + 1.) / 2.;
 
     for (mut transform, cubic_curve) in &mut query {
         // Draw the curve
-        gizmos.linestrip(cubic_curve.0.iter_positions(50), WHITE);
+        if settings.show_curve {
+            gizmos.linestrip(cubic_curve.0.iter_positions(50), WHITE);
+        }
         // position takes a point from the curve where 0 is the initial point
         // and 1 is the last point
         transform.translation = cubic_curve.0.position(t);
@@ -147,17 +181,8 @@ impl qobject::MyObject {
 
     /// Print a log message with the given string and number
     pub fn say_hi(&self, string: &QString, number: i32) {
-                App::new()
-                    .add_plugins(DefaultPlugins)
-                    .add_systems(Startup, setup)
-                    .add_systems(Update, animate_cube)
-                    .run();
-            
-            
-        
         println!("Hi from Rust! String is '{string}' and number is {number}");
     }
-    
 }
 // ANCHOR_END: book_rustobj_invokable_impl
 
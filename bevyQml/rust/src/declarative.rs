@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Entities declared in QML markup.
+//!
+//! The scene elements `BevyEntity`, `BevyMesh`, `BevyLight` and `BevyCamera`
+//! each spawn an entity once QML has set up their initial properties, and
+//! despawn it along with its children when they are destroyed. Their
+//! properties are written into the world whenever they change, so bindings
+//! and animations drive the entity:
+//!
+//! ```qml
+//! BevyEntity {
+//!     id: ship
+//!     name: "Ship"
+//!     position: Qt.vector3d(0, hover.value, 0)
+//!     components: [{ type: "Health", current: 80, max: 100 }]
+//! }
+//! BevyMesh {
+//!     parentEntity: ship.entity
+//!     shape: BevyMesh.Cube
+//!     color: "orange"
+//! }
+//! BevyLight {
+//!     lightType: BevyLight.Directional
+//!     rotation: Qt.quaternion(0.92, -0.38, 0, 0)
+//! }
+//! BevyCamera {
+//!     position: Qt.vector3d(0, 2, 8)
+//! }
+//! ```
+//!
+//! QML objects cannot nest without a list property to hold them, so a
+//! hierarchy is built by binding `parentEntity` to the `entity` of another
+//! element. The entries of `components` name a reflected component with
+//! `type`, the other keys set its fields as with `BevyCommands`. Components
+//! dropped from the list stay on the entity.
+
+use bevy::prelude::*;
+use cxx_qt_lib::{QList, QString, QVariant, QVector3D};
+
+use crate::{
+    commands::{self, QmlCommand},
+    convert::{FromQt, QQuaternion},
+    runtime, variant,
+};
+
+/// Marks the entities spawned by the scene elements of QML
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct QmlDeclared;
+
+pub struct QmlDeclarativePlugin;
+
+impl Plugin for QmlDeclarativePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<QmlDeclared>();
+    }
+}
+
+/// The entity of a scene element, which is despawned with the element
+#[derive(Default)]
+pub struct DeclaredEntity(Option<Entity>);
+
+impl DeclaredEntity {
+    pub fn get(&self) -> Option<Entity> {
+        self.0
+    }
+
+    /// Reserve the entity and spawn it with the bundle, returns [None] if
+    /// no app is running
+    pub fn spawn(&mut self, bundle: impl Bundle) -> Option<Entity> {
+        let entity = runtime::with_world(|world| world.entities().reserve_entity())?;
+        runtime::send(move |world| {
+            // Reserved entities only exist once the world has been flushed
+            world.flush();
+            if let Some(mut entity) = world.get_or_spawn(entity) {
+                entity.insert((bundle, QmlDeclared));
+            }
+        });
+        self.0 = Some(entity);
+        Some(entity)
+    }
+
+    /// Change the entity, unless it has not been spawned or is gone
+    pub fn update(&self, f: impl FnOnce(&mut World, Entity) + Send + 'static) {
+        let Some(entity) = self.0 else {
+            return;
+        };
+        runtime::send(move |world| {
+            if world.get_entity(entity).is_some() {
+                f(world, entity);
+            }
+        });
+    }
+}
+
+impl Drop for DeclaredEntity {
+    fn drop(&mut self) {
+        if let Some(entity) = self.0.take() {
+            runtime::send(move |world| {
+                if let Some(entity) = world.get_entity_mut(entity) {
+                    entity.despawn_recursive();
+                }
+            });
+        }
+    }
+}
+
+/// The properties every scene element has
+#[derive(Clone, Debug)]
+pub struct DeclaredProperties {
+    pub name: String,
+    pub parent: Option<Entity>,
+    pub transform: Transform,
+    pub visible: bool,
+}
+
+impl DeclaredProperties {
+    /// The properties as the element holds them
+    pub fn from_qml(
+        name: &QString,
+        parent_entity: u64,
+        position: &QVector3D,
+        rotation: &QQuaternion,
+        scale: &QVector3D,
+        visible: bool,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            parent: entity_from_bits(parent_entity),
+            transform: Transform {
+                translation: Vec3::from_qt(position),
+                rotation: Quat::from_qt(rotation).normalize(),
+                scale: Vec3::from_qt(scale),
+            },
+            visible,
+        }
+    }
+
+    /// Write the properties into the entity
+    pub fn apply(self, world: &mut World, entity: Entity) {
+        let parent = self
+            .parent
+            .filter(|parent| *parent != entity && world.get_entity(*parent).is_some());
+        let Some(mut entity) = world.get_entity_mut(entity) else {
+            return;
+        };
+
+        if self.name.is_empty() {
+            entity.remove::<Name>();
+        } else if entity.get::<Name>().map(Name::as_str) != Some(self.name.as_str()) {
+            entity.insert(Name::new(self.name));
+        }
+        if entity.get::<Transform>() != Some(&self.transform) {
+            entity.insert(self.transform);
+        }
+        // Only entities spawned with the rest of the visibility components
+        // can be hidden
+        if let Some(mut visibility) = entity.get_mut::<Visibility>() {
+            let wanted = if self.visible {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+            if *visibility != wanted {
+                *visibility = wanted;
+            }
+        }
+        let current = entity.get::<Parent>().map(Parent::get);
+        if current != parent {
+            match parent {
+                Some(parent) => entity.set_parent(parent),
+                None => entity.remove_parent(),
+            };
+        }
+    }
+}
+
+/// The entity bits QML passes around, 0 meaning no entity
+pub fn entity_from_bits(bits: u64) -> Option<Entity> {
+    Entity::try_from_bits(bits).ok()
+}
+
+/// The components listed in QML, as their type names and fields
+pub fn component_list(components: &QList<QVariant>) -> Vec<(String, Vec<(String, QVariant)>)> {
+    components
+        .iter()
+        .filter_map(|component| {
+            let mut fields = variant::map_entries(component);
+            let index = fields.iter().position(|(key, _)| key == "type");
+            let type_name = index
+                .map(|index| fields.remove(index).1)
+                .and_then(|type_name| type_name.value::<QString>())
+                .map(|type_name| type_name.to_string())
+                .filter(|type_name| !type_name.is_empty());
+            if type_name.is_none() {
+                warn!("Ignoring a component declared in QML without a type");
+            }
+            Some((type_name?, fields))
+        })
+        .collect()
+}
+
+/// Insert the reflected components, or change their fields when the entity
+/// already has them
+pub fn apply_components(
+    world: &mut World,
+    entity: Entity,
+    components: Vec<(String, Vec<(String, QVariant)>)>,
+) {
+    for (type_name, fields) in components {
+        let command = QmlCommand::InsertComponent {
+            entity,
+            type_name,
+            fields,
+        };
+        if let Err(error) = commands::apply_command(world, command) {
+            warn!("Failed to apply a component declared in QML: {error}");
+        }
+    }
+}
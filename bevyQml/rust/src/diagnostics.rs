@@ -0,0 +1,405 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! The Bevy diagnostics shown by the `DiagnosticsBridge` QML singleton and
+//! the `DiagnosticsHistoryModel` behind `BevyStatsOverlay`, and the
+//! statistics of every camera shown by the `CameraStatsModel`.
+//!
+//! The GPU times of the render passes come from the timestamp queries of
+//! Bevy's [RenderDiagnosticsPlugin], which are only available where the
+//! adapter supports `TIMESTAMP_QUERY`. Comparing them with the frame time
+//! tells frames which wait for the GPU apart from those which wait for the
+//! CPU.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use bevy::{
+    core_pipeline::core_3d::{AlphaMask3d, Opaque3d, Transparent3d},
+    diagnostic::{
+        Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, EntityCountDiagnosticsPlugin,
+        FrameTimeDiagnosticsPlugin, RegisterDiagnostic,
+    },
+    prelude::*,
+    render::{
+        diagnostic::RenderDiagnosticsPlugin,
+        mesh::PrimitiveTopology,
+        render_phase::{BinnedPhaseItem, ViewBinnedRenderPhases, ViewSortedRenderPhases},
+        view::{RenderLayers, VisibilitySystems, VisibleEntities, WithMesh},
+        Render, RenderApp, RenderSet,
+    },
+    utils::HashMap,
+};
+
+/// The latest values of the diagnostics QML shows
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DiagnosticsSample {
+    /// Frames per second, smoothed
+    pub fps: f64,
+    /// The duration of a frame in milliseconds, smoothed
+    pub frame_time: f64,
+    pub entity_count: u64,
+    /// The draw calls of the last rendered frame, see [QmlDiagnosticsPlugin::DRAW_CALLS]
+    pub draw_calls: u64,
+    /// The GPU time of the render passes in milliseconds, smoothed, 0
+    /// without timestamp queries, see [QmlDiagnosticsPlugin::GPU_TIME]
+    pub gpu_time: f64,
+    pub gpu_shadow_time: f64,
+    pub gpu_opaque_time: f64,
+    pub gpu_transparent_time: f64,
+    pub gpu_post_time: f64,
+}
+
+/// How close the GPU time needs to come to the frame time for a frame to
+/// count as waiting for the GPU
+const GPU_BOUND_RATIO: f64 = 0.9;
+
+impl DiagnosticsSample {
+    /// Read the values from the [DiagnosticsStore] of the world, [None]
+    /// without one
+    pub fn read(world: &World) -> Option<Self> {
+        let store = world.get_resource::<DiagnosticsStore>()?;
+        let smoothed = |path| {
+            store
+                .get(path)
+                .and_then(|diagnostic| diagnostic.smoothed())
+                .unwrap_or_default()
+        };
+        let latest = |path| {
+            store
+                .get(path)
+                .and_then(|diagnostic| diagnostic.value())
+                .unwrap_or_default() as u64
+        };
+        Some(Self {
+            fps: smoothed(&FrameTimeDiagnosticsPlugin::FPS),
+            frame_time: smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME),
+            entity_count: latest(&EntityCountDiagnosticsPlugin::ENTITY_COUNT),
+            draw_calls: latest(&QmlDiagnosticsPlugin::DRAW_CALLS),
+            gpu_time: smoothed(&QmlDiagnosticsPlugin::GPU_TIME),
+            gpu_shadow_time: smoothed(&QmlDiagnosticsPlugin::GPU_SHADOW_TIME),
+            gpu_opaque_time: smoothed(&QmlDiagnosticsPlugin::GPU_OPAQUE_TIME),
+            gpu_transparent_time: smoothed(&QmlDiagnosticsPlugin::GPU_TRANSPARENT_TIME),
+            gpu_post_time: smoothed(&QmlDiagnosticsPlugin::GPU_POST_TIME),
+        })
+    }
+
+    /// Whether the GPU timed its passes
+    pub fn gpu_timed(&self) -> bool {
+        self.gpu_time > 0.0
+    }
+
+    /// Whether frames take as long as they do because of the GPU, rather
+    /// than the CPU or waiting for vsync
+    pub fn gpu_bound(&self) -> bool {
+        self.gpu_timed() && self.gpu_time >= self.frame_time * GPU_BOUND_RATIO
+    }
+}
+
+/// What an active camera rendered in the last frame
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraStats {
+    pub camera: Entity,
+    /// The [Name] of the camera, empty without one
+    pub name: String,
+    /// The meshes which passed frustum culling
+    pub visible: u64,
+    /// The meshes on the render layers of the camera which were visible
+    /// otherwise, but lie outside of its frustum
+    pub culled: u64,
+    /// The draw calls of the 3D render phases of the camera, see
+    /// [QmlDiagnosticsPlugin::DRAW_CALLS]
+    pub draw_calls: u64,
+    /// The triangles of the visible meshes, counting only meshes which are
+    /// kept in the main world
+    pub triangles: u64,
+}
+
+/// The [CameraStats] of every active camera, in the order of the cameras,
+/// updated after visibility has been checked in every frame
+#[derive(Resource, Clone, Debug, Default)]
+pub struct QmlCameraStats {
+    pub cameras: Vec<CameraStats>,
+}
+
+/// Adds the diagnostics plugins whose values QML shows, unless the app
+/// already has them, counts the draw calls of the 3D render phases, sums
+/// up the GPU times of the render passes and keeps the [QmlCameraStats]
+pub struct QmlDiagnosticsPlugin;
+
+impl QmlDiagnosticsPlugin {
+    /// The draw calls of the opaque, alpha masked and transparent 3D phases
+    /// of every view
+    ///
+    /// Batched meshes are counted once per bin, which is how many draws they
+    /// take without multi-draw support.
+    pub const DRAW_CALLS: DiagnosticPath = DiagnosticPath::const_new("render/draw_calls");
+
+    /// The GPU time of every render pass of a frame in milliseconds
+    ///
+    /// Passes are timed by the [RenderDiagnosticsPlugin], there are no
+    /// measurements where the adapter lacks timestamp queries.
+    pub const GPU_TIME: DiagnosticPath = DiagnosticPath::const_new("render/gpu/total");
+    /// The GPU time of the shadow passes of every light in milliseconds
+    pub const GPU_SHADOW_TIME: DiagnosticPath = DiagnosticPath::const_new("render/gpu/shadow");
+    /// The GPU time of the prepasses and the opaque passes in milliseconds
+    pub const GPU_OPAQUE_TIME: DiagnosticPath = DiagnosticPath::const_new("render/gpu/opaque");
+    /// The GPU time of the transparent passes in milliseconds
+    pub const GPU_TRANSPARENT_TIME: DiagnosticPath =
+        DiagnosticPath::const_new("render/gpu/transparent");
+    /// The GPU time of the post processing passes in milliseconds, such as
+    /// bloom, tonemapping and anti-aliasing
+    pub const GPU_POST_TIME: DiagnosticPath = DiagnosticPath::const_new("render/gpu/post");
+}
+
+impl Plugin for QmlDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        if !app.is_plugin_added::<EntityCountDiagnosticsPlugin>() {
+            app.add_plugins(EntityCountDiagnosticsPlugin);
+        }
+
+        // Headless apps render nothing to time
+        let renders = app.get_sub_app(RenderApp).is_some();
+        if renders && !app.is_plugin_added::<RenderDiagnosticsPlugin>() {
+            app.add_plugins(RenderDiagnosticsPlugin);
+        }
+
+        let draw_calls = DrawCalls::default();
+        app.register_diagnostic(Diagnostic::new(Self::DRAW_CALLS))
+            .register_diagnostic(Diagnostic::new(Self::GPU_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(Self::GPU_SHADOW_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(Self::GPU_OPAQUE_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(Self::GPU_TRANSPARENT_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(Self::GPU_POST_TIME).with_suffix("ms"))
+            .insert_resource(draw_calls.clone())
+            .init_resource::<QmlCameraStats>()
+            .add_systems(Update, (measure_draw_calls, measure_gpu_passes))
+            .add_systems(
+                PostUpdate,
+                collect_camera_stats.after(VisibilitySystems::CheckVisibility),
+            );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.insert_resource(draw_calls).add_systems(
+            Render,
+            count_draw_calls
+                .after(RenderSet::PhaseSort)
+                .before(RenderSet::Render),
+        );
+    }
+}
+
+/// The draw calls counted by the render world for every view, for the main
+/// world
+///
+/// Views keep the entity of their camera in the render world.
+#[derive(Resource, Clone, Default)]
+struct DrawCalls(Arc<Mutex<HashMap<Entity, u64>>>);
+
+fn count_draw_calls(
+    draw_calls: Res<DrawCalls>,
+    opaque: Res<ViewBinnedRenderPhases<Opaque3d>>,
+    alpha_mask: Res<ViewBinnedRenderPhases<AlphaMask3d>>,
+    transparent: Res<ViewSortedRenderPhases<Transparent3d>>,
+) {
+    let mut counts = HashMap::<Entity, u64>::default();
+    binned_draws(&opaque, &mut counts);
+    binned_draws(&alpha_mask, &mut counts);
+    for (view, phase) in transparent.iter() {
+        *counts.entry(*view).or_default() += phase.items.len() as u64;
+    }
+    *draw_calls.0.lock().unwrap() = counts;
+}
+
+fn binned_draws<BPI: BinnedPhaseItem>(
+    phases: &ViewBinnedRenderPhases<BPI>,
+    counts: &mut HashMap<Entity, u64>,
+) {
+    for (view, phase) in phases.iter() {
+        *counts.entry(*view).or_default() +=
+            (phase.batchable_keys.len() + phase.unbatchable_keys.len()) as u64;
+    }
+}
+
+fn measure_draw_calls(draw_calls: Res<DrawCalls>, mut diagnostics: Diagnostics) {
+    let count: u64 = draw_calls.0.lock().unwrap().values().sum();
+    diagnostics.add_measurement(&QmlDiagnosticsPlugin::DRAW_CALLS, || count as f64);
+}
+
+/// The passes timed by the [RenderDiagnosticsPlugin] which belong to post
+/// processing, by the names of their spans
+const POST_PASSES: &[&str] = &[
+    "auto_exposure",
+    "bloom",
+    "contrast_adaptive_sharpening",
+    "depth_of_field",
+    "fxaa",
+    "motion_blur",
+    "smaa",
+    "taa",
+    "tonemapping",
+    "upscaling",
+];
+
+/// Sum up the GPU times of the passes which were timed since the last run,
+/// as the passes which did not run keep their last measurement
+fn measure_gpu_passes(
+    store: Res<DiagnosticsStore>,
+    mut diagnostics: Diagnostics,
+    mut measured_until: Local<Option<Instant>>,
+) {
+    let mut latest = *measured_until;
+    let (mut total, mut shadow, mut opaque, mut transparent, mut post) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for diagnostic in store.iter() {
+        // Spans nested in a pass are part of its time already
+        let Some(pass) = diagnostic
+            .path()
+            .as_str()
+            .strip_prefix("render/")
+            .and_then(|path| path.strip_suffix("/elapsed_gpu"))
+            .filter(|pass| !pass.contains('/'))
+        else {
+            continue;
+        };
+        let Some(measurement) = diagnostic.measurements().last() else {
+            continue;
+        };
+        if measured_until.is_some_and(|until| measurement.time <= until) {
+            continue;
+        }
+        latest = latest.max(Some(measurement.time));
+
+        let elapsed = measurement.value;
+        total += elapsed;
+        if pass.contains("shadow") {
+            shadow += elapsed;
+        } else if pass.contains("opaque") || pass.contains("prepass") {
+            opaque += elapsed;
+        } else if pass.contains("transparent") {
+            transparent += elapsed;
+        } else if POST_PASSES.iter().any(|post| pass.contains(post)) {
+            post += elapsed;
+        }
+    }
+    // Nothing was rendered, or the timestamps are not back yet
+    if latest == *measured_until {
+        return;
+    }
+    *measured_until = latest;
+
+    diagnostics.add_measurement(&QmlDiagnosticsPlugin::GPU_TIME, || total);
+    diagnostics.add_measurement(&QmlDiagnosticsPlugin::GPU_SHADOW_TIME, || shadow);
+    diagnostics.add_measurement(&QmlDiagnosticsPlugin::GPU_OPAQUE_TIME, || opaque);
+    diagnostics.add_measurement(&QmlDiagnosticsPlugin::GPU_TRANSPARENT_TIME, || transparent);
+    diagnostics.add_measurement(&QmlDiagnosticsPlugin::GPU_POST_TIME, || post);
+}
+
+fn collect_camera_stats(
+    mut stats: ResMut<QmlCameraStats>,
+    draw_calls: Res<DrawCalls>,
+    cameras: Query<(
+        Entity,
+        &Camera,
+        &VisibleEntities,
+        Option<&RenderLayers>,
+        Option<&Name>,
+    )>,
+    meshes: Query<(&Handle<Mesh>, &InheritedVisibility, Option<&RenderLayers>)>,
+    // Headless apps have no meshes
+    mesh_assets: Option<Res<Assets<Mesh>>>,
+) {
+    let draw_calls = draw_calls.0.lock().unwrap();
+    let default_layers = RenderLayers::default();
+    let mut cameras: Vec<_> = cameras
+        .iter()
+        .filter(|(_, camera, ..)| camera.is_active)
+        .collect();
+    cameras.sort_by_key(|(entity, camera, ..)| (camera.order, *entity));
+
+    let collected: Vec<CameraStats> = cameras
+        .into_iter()
+        .map(|(entity, _, visible_entities, layers, name)| {
+            let layers = layers.unwrap_or(&default_layers);
+            let candidates = meshes
+                .iter()
+                .filter(|(_, inherited, mesh_layers)| {
+                    inherited.get() && mesh_layers.unwrap_or(&default_layers).intersects(layers)
+                })
+                .count() as u64;
+            let visible = visible_entities.len::<WithMesh>() as u64;
+            let triangles = visible_entities
+                .iter::<WithMesh>()
+                .filter_map(|entity| meshes.get(*entity).ok())
+                .filter_map(|(mesh, ..)| mesh_assets.as_ref()?.get(mesh))
+                .map(triangle_count)
+                .sum();
+            CameraStats {
+                camera: entity,
+                name: name.map(|name| name.to_string()).unwrap_or_default(),
+                visible,
+                culled: candidates.saturating_sub(visible),
+                draw_calls: draw_calls.get(&entity).copied().unwrap_or_default(),
+                triangles,
+            }
+        })
+        .collect();
+    if stats.cameras != collected {
+        stats.cameras = collected;
+    }
+}
+
+/// The nearest rank percentile of sorted values, 0 if there are none
+pub fn percentile(sorted: &[f64], rank: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let index = (rank * sorted.len() as f64).ceil() as usize;
+    sorted[index.clamp(1, sorted.len()) - 1]
+}
+
+/// How many triangles the mesh is drawn with
+fn triangle_count(mesh: &Mesh) -> u64 {
+    let vertices = mesh
+        .indices()
+        .map_or_else(|| mesh.count_vertices(), |indices| indices.len()) as u64;
+    match mesh.primitive_topology() {
+        PrimitiveTopology::TriangleList => vertices / 3,
+        PrimitiveTopology::TriangleStrip => vertices.saturating_sub(2),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_of_nothing_is_zero() {
+        assert_eq!(percentile(&[], 0.5), 0.0);
+    }
+
+    #[test]
+    fn percentile_takes_the_nearest_rank() {
+        let sorted = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+        assert_eq!(percentile(&sorted, 0.5), 5.0);
+        assert_eq!(percentile(&sorted, 0.55), 6.0);
+        assert_eq!(percentile(&sorted, 0.95), 10.0);
+        assert_eq!(percentile(&sorted, 0.99), 10.0);
+    }
+
+    #[test]
+    fn percentile_clamps_the_rank() {
+        let sorted = [2.0, 4.0, 8.0];
+        assert_eq!(percentile(&sorted, 0.0), 2.0);
+        assert_eq!(percentile(&sorted, -1.0), 2.0);
+        assert_eq!(percentile(&sorted, 1.0), 8.0);
+        assert_eq!(percentile(&sorted, 2.0), 8.0);
+        assert_eq!(percentile(&[16.0], 0.5), 16.0);
+    }
+}
@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Debug shapes drawn from QML, see [crate::cxxqt_bevy_gizmos] for the QML
+//! side.
+//!
+//! Shapes are drawn with [Gizmos] in the next update, and again in every
+//! update until their duration has passed. Text has no gizmo of its own, so
+//! it is shown as a UI label over every active 3D camera that can see its
+//! position, which needs the UI plugin.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, window::RequestRedraw};
+
+use crate::runtime;
+
+/// A shape drawn from QML
+#[derive(Clone, Debug)]
+pub enum QmlGizmo {
+    Line {
+        start: Vec3,
+        end: Vec3,
+        color: Color,
+    },
+    Sphere {
+        center: Vec3,
+        radius: f32,
+        color: Color,
+    },
+    /// An axis aligned box between two corners
+    Aabb { min: Vec3, max: Vec3, color: Color },
+    Text {
+        position: Vec3,
+        text: String,
+        color: Color,
+    },
+}
+
+/// The shapes drawn from QML, with how long they have left
+#[derive(Resource, Default)]
+pub struct QmlGizmos {
+    shapes: Vec<(QmlGizmo, Duration)>,
+}
+
+impl QmlGizmos {
+    /// Draw the shape in the next update, and keep drawing it until the
+    /// duration has passed
+    pub fn push(&mut self, shape: QmlGizmo, duration: Duration) {
+        self.shapes.push((shape, duration));
+    }
+
+    pub fn clear(&mut self) {
+        self.shapes.clear();
+    }
+}
+
+/// Marks the UI labels showing [QmlGizmo::Text], which last a single update
+#[derive(Component)]
+struct QmlGizmoLabel;
+
+/// The font size of [QmlGizmo::Text], in logical pixels
+const LABEL_FONT_SIZE: f32 = 16.0;
+
+pub struct QmlGizmosPlugin;
+
+impl Plugin for QmlGizmosPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QmlGizmos>()
+            .add_systems(Update, (despawn_labels, draw_gizmos).chain());
+    }
+}
+
+/// Draw a shape from the GUI thread, whichever thread the app runs on
+pub fn draw(shape: QmlGizmo, duration: Duration) {
+    runtime::send(move |world| {
+        if let Some(mut gizmos) = world.get_resource_mut::<QmlGizmos>() {
+            gizmos.push(shape, duration);
+        }
+    });
+    runtime::request_update();
+}
+
+/// Stop drawing every shape drawn from QML
+pub fn clear() {
+    runtime::send(|world| {
+        if let Some(mut gizmos) = world.get_resource_mut::<QmlGizmos>() {
+            gizmos.clear();
+        }
+    });
+    runtime::request_update();
+}
+
+fn despawn_labels(mut commands: Commands, labels: Query<Entity, With<QmlGizmoLabel>>) {
+    for label in &labels {
+        commands.entity(label).despawn_recursive();
+    }
+}
+
+fn draw_gizmos(
+    mut commands: Commands,
+    mut gizmos: Gizmos,
+    mut shapes: ResMut<QmlGizmos>,
+    mut redraw: EventWriter<RequestRedraw>,
+    time: Res<Time>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform), With<Camera3d>>,
+) {
+    if shapes.shapes.is_empty() {
+        return;
+    }
+
+    for (shape, _) in &shapes.shapes {
+        match shape {
+            QmlGizmo::Line { start, end, color } => gizmos.line(*start, *end, *color),
+            QmlGizmo::Sphere {
+                center,
+                radius,
+                color,
+            } => {
+                gizmos.sphere(*center, Quat::IDENTITY, *radius, *color);
+            }
+            QmlGizmo::Aabb { min, max, color } => {
+                let bounds = Transform::from_translation((*min + *max) / 2.0)
+                    .with_scale((*max - *min).abs());
+                gizmos.cuboid(bounds, *color);
+            }
+            QmlGizmo::Text {
+                position,
+                text,
+                color,
+            } => {
+                for (entity, camera, transform) in &cameras {
+                    if !camera.is_active {
+                        continue;
+                    }
+                    let Some(point) = camera.world_to_viewport(transform, *position) else {
+                        continue;
+                    };
+                    commands.spawn((
+                        TextBundle::from_section(
+                            text.clone(),
+                            TextStyle {
+                                font_size: LABEL_FONT_SIZE,
+                                color: *color,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(point.x),
+                            top: Val::Px(point.y),
+                            ..default()
+                        }),
+                        TargetCamera(entity),
+                        QmlGizmoLabel,
+                    ));
+                }
+            }
+        }
+    }
+
+    let delta = time.delta();
+    shapes.shapes.retain_mut(|(_, remaining)| {
+        *remaining = remaining.saturating_sub(delta);
+        !remaining.is_zero()
+    });
+    // Keep updating apps rendered on demand until the shapes expire
+    if !shapes.shapes.is_empty() {
+        redraw.send(RequestRedraw);
+    }
+}
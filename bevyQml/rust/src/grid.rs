@@ -0,0 +1,190 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! A ground grid and the axes of the world, drawn for `BevyQuickItem`s with
+//! `showGrid` or `showAxes` set.
+//!
+//! The grid lies in the XZ plane and follows the camera, so it seems to go on
+//! forever while only the lines within the fade distance of the camera are
+//! drawn. Lines fade out towards that distance. They are drawn with
+//! [Gizmos], which every camera of the world with the default render layers
+//! shows, not only those of the item.
+
+use bevy::{color::Alpha, prelude::*, render::camera::RenderTarget};
+
+use crate::render::QuickItemTarget;
+
+/// How the grid and axes of a [QuickItemTarget] look
+///
+/// `BevyQuickItem` keeps this in step with its properties.
+#[derive(Component, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub struct QmlGrid {
+    /// Whether the grid is drawn
+    pub grid: bool,
+    /// Whether the axes are drawn
+    pub axes: bool,
+    /// The size of a cell, between the major lines
+    pub size: f32,
+    /// How many parts the minor lines split a cell into along each side, one
+    /// for no minor lines
+    pub subdivisions: u32,
+    /// How far from the camera the lines have faded out
+    pub fade_distance: f32,
+    /// The color of the major lines, which the minor lines draw fainter
+    pub color: Color,
+    pub x_axis_color: Color,
+    pub y_axis_color: Color,
+    pub z_axis_color: Color,
+}
+
+impl Default for QmlGrid {
+    fn default() -> Self {
+        Self {
+            grid: false,
+            axes: false,
+            size: 1.0,
+            subdivisions: 10,
+            fade_distance: 50.0,
+            color: Color::srgba(0.5, 0.5, 0.5, 0.8),
+            x_axis_color: Color::srgb(0.9, 0.2, 0.2),
+            y_axis_color: Color::srgb(0.3, 0.8, 0.2),
+            z_axis_color: Color::srgb(0.2, 0.4, 0.9),
+        }
+    }
+}
+
+/// How much fainter the minor lines are than the major lines
+const MINOR_ALPHA: f32 = 0.35;
+
+/// The most lines drawn along each direction, beyond which the minor lines
+/// and then every other major line are left out
+const MAX_LINES: f32 = 400.0;
+
+/// How many segments a line is split into for fading
+const LINE_SEGMENTS: usize = 24;
+
+pub struct QmlGridPlugin;
+
+impl Plugin for QmlGridPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<QmlGrid>()
+            .add_systems(PostUpdate, draw_grids);
+    }
+}
+
+fn draw_grids(
+    mut gizmos: Gizmos,
+    targets: Query<(&QmlGrid, &QuickItemTarget)>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+) {
+    for (grid, target) in &targets {
+        if !grid.grid && !grid.axes {
+            continue;
+        }
+        for (camera, transform) in &cameras {
+            if camera.is_active
+                && matches!(&camera.target, RenderTarget::Image(image) if *image == target.image)
+            {
+                draw_grid(&mut gizmos, grid, transform.translation());
+            }
+        }
+    }
+}
+
+/// Draw the grid and axes seen by a camera at the eye position
+fn draw_grid(gizmos: &mut Gizmos, grid: &QmlGrid, eye: Vec3) {
+    let fade_distance = grid.fade_distance.max(f32::EPSILON);
+    // The lines on the ground plane within the fade distance lie in a circle
+    // around the point below the camera
+    let reach = (fade_distance * fade_distance - eye.y * eye.y)
+        .max(0.0)
+        .sqrt();
+    let center = Vec2::new(eye.x, eye.z);
+    let fade = |point: Vec3, color: Color| {
+        let visibility = (1.0 - point.distance(eye) / fade_distance).clamp(0.0, 1.0);
+        color.with_alpha(color.alpha() * visibility)
+    };
+
+    if grid.grid && reach > 0.0 && grid.size > 0.0 {
+        let subdivisions = grid.subdivisions.max(1);
+        let mut spacing = grid.size / subdivisions as f32;
+        let mut per_cell = subdivisions;
+        if 2.0 * reach / spacing > MAX_LINES {
+            // Too dense to tell apart, keep the major lines only
+            spacing = grid.size * (2.0 * reach / grid.size / MAX_LINES).ceil().max(1.0);
+            per_cell = 1;
+        }
+        let minor_color = grid.color.with_alpha(grid.color.alpha() * MINOR_ALPHA);
+
+        for along_x in [false, true] {
+            // The lines run along one axis and are spaced along the other
+            let (offset, across) = if along_x {
+                (center.y, center.x)
+            } else {
+                (center.x, center.y)
+            };
+            let first = ((offset - reach) / spacing).ceil() as i64;
+            let last = ((offset + reach) / spacing).floor() as i64;
+            for index in first..=last {
+                let position = index as f32 * spacing;
+                if grid.axes && index == 0 {
+                    // The axis is drawn over this line
+                    continue;
+                }
+                let color = if index.rem_euclid(i64::from(per_cell)) == 0 {
+                    grid.color
+                } else {
+                    minor_color
+                };
+                let half_length = (reach * reach - (position - offset).powi(2))
+                    .max(0.0)
+                    .sqrt();
+                let point = |t: f32| {
+                    let along = across + t * half_length;
+                    if along_x {
+                        Vec3::new(along, 0.0, position)
+                    } else {
+                        Vec3::new(position, 0.0, along)
+                    }
+                };
+                draw_faded(gizmos, point, color, fade);
+            }
+        }
+    }
+
+    if grid.axes {
+        let reach = fade_distance;
+        draw_faded(
+            gizmos,
+            |t| Vec3::new(eye.x + t * reach, 0.0, 0.0),
+            grid.x_axis_color,
+            fade,
+        );
+        draw_faded(
+            gizmos,
+            |t| Vec3::new(0.0, eye.y + t * reach, 0.0),
+            grid.y_axis_color,
+            fade,
+        );
+        draw_faded(
+            gizmos,
+            |t| Vec3::new(0.0, 0.0, eye.z + t * reach),
+            grid.z_axis_color,
+            fade,
+        );
+    }
+}
+
+/// Draw the line through `point(-1)` and `point(1)` in segments, faded by
+/// their distance from the camera
+fn draw_faded(
+    gizmos: &mut Gizmos,
+    point: impl Fn(f32) -> Vec3,
+    color: Color,
+    fade: impl Fn(Vec3, Color) -> Color,
+) {
+    gizmos.linestrip_gradient((0..=LINE_SEGMENTS).map(|segment| {
+        let position = point(segment as f32 / LINE_SEGMENTS as f32 * 2.0 - 1.0);
+        (position, fade(position, color))
+    }));
+}
@@ -0,0 +1,200 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Conversions between QImage and Bevy [Image] assets.
+//!
+//! Images drawn with QPainter or grabbed from QML items can be used as Bevy
+//! textures, and Bevy images can be handed to anything in Qt taking a QImage.
+//!
+//! Eight bit QImages hold sRGB encoded colors unless their color space says
+//! otherwise, so they become [TextureFormat::Rgba8UnormSrgb] images, and
+//! [TextureFormat::Rgba8Unorm] ones when the color space is linear. Floating
+//! point QImages become [TextureFormat::Rgba32Float] images, which are linear.
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_image")]
+mod ffi {
+    /// How pixels are laid out in the buffers shared with C++
+    #[namespace = "bevyqml"]
+    #[repr(u8)]
+    enum PixelLayout {
+        Rgba8,
+        Rgba32Float,
+        R8,
+    }
+
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qimage.h");
+        /// An alias to the QImage type
+        type QImage = cxx_qt_lib::QImage;
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/image.h");
+        type PixelLayout;
+
+        #[doc(hidden)]
+        #[rust_name = "qimage_pixel_layout"]
+        fn qimagePixelLayout(image: &QImage) -> PixelLayout;
+
+        #[doc(hidden)]
+        #[rust_name = "qimage_is_linear"]
+        fn qimageIsLinear(image: &QImage) -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "qimage_to_pixels"]
+        fn qimageToPixels(
+            image: &QImage,
+            layout: PixelLayout,
+            data: &mut Vec<u8>,
+            width: &mut u32,
+            height: &mut u32,
+        ) -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "qimage_from_pixels"]
+        fn qimageFromPixels(
+            width: u32,
+            height: u32,
+            layout: PixelLayout,
+            linear: bool,
+            data: &[u8],
+        ) -> QImage;
+
+        #[doc(hidden)]
+        #[rust_name = "qimage_save"]
+        fn qimageSave(image: &QImage, path: &QString) -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "qimage_load"]
+        fn qimageLoad(path: &QString) -> QImage;
+
+        #[doc(hidden)]
+        #[rust_name = "qimage_is_null"]
+        fn qimageIsNull(image: &QImage) -> bool;
+    }
+}
+
+use std::borrow::Cow;
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::TextureFormatPixelInfo,
+    },
+};
+use cxx_qt_lib::{QImage, QString};
+
+use crate::render::Frame;
+
+use ffi::PixelLayout;
+
+/// Copy a QImage into a new [Image]
+///
+/// Returns [None] for a null QImage.
+pub fn image_from_qimage(qimage: &QImage, asset_usage: RenderAssetUsages) -> Option<Image> {
+    let layout = ffi::qimage_pixel_layout(qimage);
+    let mut data = Vec::new();
+    let (mut width, mut height) = (0, 0);
+    if !ffi::qimage_to_pixels(qimage, layout, &mut data, &mut width, &mut height) {
+        return None;
+    }
+
+    let format = match layout {
+        PixelLayout::Rgba32Float => TextureFormat::Rgba32Float,
+        _ if ffi::qimage_is_linear(qimage) => TextureFormat::Rgba8Unorm,
+        _ => TextureFormat::Rgba8UnormSrgb,
+    };
+    Some(Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        format,
+        asset_usage,
+    ))
+}
+
+/// Copy an [Image] into a new QImage
+///
+/// Only two dimensional images with a single layer are converted, in one of
+/// the RGBA8, BGRA8, R8 or RGBA32 float formats. Other images return [None].
+pub fn qimage_from_image(image: &Image) -> Option<QImage> {
+    let descriptor = &image.texture_descriptor;
+    if descriptor.dimension != TextureDimension::D2 || descriptor.size.depth_or_array_layers != 1 {
+        return None;
+    }
+
+    let (layout, linear, data) = match descriptor.format {
+        TextureFormat::Rgba8UnormSrgb => (PixelLayout::Rgba8, false, Cow::from(&image.data)),
+        TextureFormat::Rgba8Unorm => (PixelLayout::Rgba8, true, Cow::from(&image.data)),
+        TextureFormat::Bgra8UnormSrgb => (PixelLayout::Rgba8, false, bgra_to_rgba(&image.data)),
+        TextureFormat::Bgra8Unorm => (PixelLayout::Rgba8, true, bgra_to_rgba(&image.data)),
+        TextureFormat::R8Unorm => (PixelLayout::R8, true, Cow::from(&image.data)),
+        TextureFormat::Rgba32Float => (PixelLayout::Rgba32Float, true, Cow::from(&image.data)),
+        _ => return None,
+    };
+
+    let size = image.size();
+    let expected = size.x as usize * size.y as usize * descriptor.format.pixel_size();
+    if data.len() != expected {
+        return None;
+    }
+    Some(ffi::qimage_from_pixels(
+        size.x, size.y, layout, linear, &data,
+    ))
+}
+
+/// Copy a frame read back from a render target into a new QImage
+pub fn qimage_from_frame(frame: &Frame) -> QImage {
+    qimage_from_rgba8(frame.width, frame.height, &frame.data)
+}
+
+/// Copy tightly packed sRGB RGBA8 pixels into a new QImage
+pub fn qimage_from_rgba8(width: u32, height: u32, data: &[u8]) -> QImage {
+    ffi::qimage_from_pixels(width, height, PixelLayout::Rgba8, false, data)
+}
+
+/// The pixels of a QImage as tightly packed RGBA8, with its width and height
+///
+/// Returns [None] for a null QImage.
+pub fn qimage_to_rgba8(image: &QImage) -> Option<(u32, u32, Vec<u8>)> {
+    let mut data = Vec::new();
+    let (mut width, mut height) = (0, 0);
+    ffi::qimage_to_pixels(image, PixelLayout::Rgba8, &mut data, &mut width, &mut height)
+        .then_some((width, height, data))
+}
+
+/// Read an image file, in the format given by its contents
+///
+/// Returns [None] if the file is missing or cannot be read.
+pub fn load_qimage(path: &str) -> Option<QImage> {
+    let image = ffi::qimage_load(&QString::from(path));
+    (!ffi::qimage_is_null(&image)).then_some(image)
+}
+
+/// Save a QImage to a file, in the format given by the suffix of the path
+pub fn save_qimage(image: &QImage, path: &str) -> bool {
+    ffi::qimage_save(image, &QString::from(path))
+}
+
+/// Save a frame to a file like [save_qimage], this works off the GUI thread
+/// as well
+pub fn save_frame(frame: &Frame, path: &str) -> bool {
+    save_qimage(&qimage_from_frame(frame), path)
+}
+
+fn bgra_to_rgba(data: &[u8]) -> Cow<'static, [u8]> {
+    let mut data = data.to_vec();
+    for pixel in data.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+    Cow::Owned(data)
+}
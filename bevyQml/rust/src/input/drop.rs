@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Files dragged onto the item and dropped there.
+//!
+//! Local files are reported as [FileDragAndDrop] events of the window
+//! standing in for the item, just like Bevy reports files dropped onto a
+//! native window. With [FileDropSettings::load_assets] set, dropped files
+//! with a known extension are loaded right away as well and announced with
+//! [AssetDropped].
+
+use std::path::PathBuf;
+
+use bevy::{asset::LoadedUntypedAsset, prelude::*, window::FileDragAndDrop};
+
+/// Whether dropped files are loaded as assets
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct FileDropSettings {
+    /// Load the dropped files whose extension is listed in `extensions`
+    pub load_assets: bool,
+    /// The extensions of the files to load, in lower case and without the dot
+    pub extensions: Vec<String>,
+}
+
+impl Default for FileDropSettings {
+    fn default() -> Self {
+        Self {
+            load_assets: false,
+            extensions: [
+                "gltf", "glb", "png", "jpg", "jpeg", "ktx2", "hdr", "exr", "ogg", "wav", "ttf",
+                "otf", "wgsl",
+            ]
+            .map(String::from)
+            .to_vec(),
+        }
+    }
+}
+
+impl FileDropSettings {
+    /// Whether the file is loaded when it is dropped
+    pub fn loads(&self, path: &std::path::Path) -> bool {
+        self.load_assets
+            && path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| {
+                    self.extensions
+                        .iter()
+                        .any(|known| known.eq_ignore_ascii_case(extension))
+                })
+    }
+}
+
+/// A dropped file started loading
+///
+/// The asset is the [LoadedUntypedAsset::handle] of `handle` once it has
+/// loaded, whatever its type turns out to be.
+#[derive(Event, Clone, Debug)]
+pub struct AssetDropped {
+    pub window: Entity,
+    pub path: PathBuf,
+    pub handle: Handle<LoadedUntypedAsset>,
+}
+
+/// Files are being dragged over the window
+pub fn hovered(world: &mut World, window: Entity, paths: &[PathBuf]) {
+    for path in paths {
+        world.send_event(FileDragAndDrop::HoveredFile {
+            window,
+            path_buf: path.clone(),
+        });
+    }
+}
+
+/// The files being dragged left the window without being dropped
+pub fn canceled(world: &mut World, window: Entity) {
+    world.send_event(FileDragAndDrop::HoveredFileCanceled { window });
+}
+
+/// Files were dropped onto the window
+pub fn dropped(world: &mut World, window: Entity, paths: &[PathBuf]) {
+    for path in paths {
+        world.send_event(FileDragAndDrop::DroppedFile {
+            window,
+            path_buf: path.clone(),
+        });
+    }
+
+    let Some(settings) = world.get_resource::<FileDropSettings>() else {
+        return;
+    };
+    let loaded: Vec<_> = paths
+        .iter()
+        .filter(|path| settings.loads(path))
+        .cloned()
+        .collect();
+    if loaded.is_empty() {
+        return;
+    }
+    let Some(asset_server) = world.get_resource::<AssetServer>().cloned() else {
+        warn!("Cannot load dropped files without an asset server");
+        return;
+    };
+    for path in loaded {
+        let handle = asset_server.load_untyped(path.clone());
+        world.send_event(AssetDropped {
+            window,
+            path,
+            handle,
+        });
+    }
+}
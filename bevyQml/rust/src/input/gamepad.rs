@@ -0,0 +1,396 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Gamepads read with gilrs, whoever runs the app.
+//!
+//! Qt 6 has no gamepad module, so controllers are read with gilrs, which
+//! picks up the SDL game controller mappings, including those listed in the
+//! `SDL_GAMECONTROLLERCONFIG` environment variable. [QmlGamepadPlugin] keeps
+//! an instance of its own, next to the one of Bevy's `GilrsPlugin`:
+//!
+//! - Without a `GilrsPlugin`, its events are sent as Bevy [GamepadEvent]s,
+//!   so [Gamepads], [ButtonInput<GamepadButton>] and [Axis<GamepadAxis>]
+//!   work as usual.
+//! - While the app is idle in [crate::redraw::UpdateMode::OnDemand], the
+//!   runtime polls it every [GamepadStatusSettings::idle_poll_interval] and
+//!   wakes the app on input, which the Qt event loop knows nothing about.
+//! - [GamepadStatus] lists the connected gamepads with their name and
+//!   battery, for the indicators of the `GamepadModel` in QML.
+
+use std::time::{Duration, Instant};
+
+use bevy::{
+    gilrs::GilrsPlugin,
+    input::{
+        gamepad::{
+            GamepadAxis, GamepadAxisChangedEvent, GamepadButton, GamepadButtonChangedEvent,
+            GamepadConnection, GamepadConnectionEvent, GamepadEvent, GamepadInfo, GamepadSettings,
+        },
+        InputSystem,
+    },
+    prelude::*,
+    window::RequestRedraw,
+};
+use gilrs::{EventType, GilrsBuilder, PowerInfo};
+
+/// Reads gamepads with gilrs and keeps [GamepadStatus] up to date
+pub struct QmlGamepadPlugin;
+
+impl Plugin for QmlGamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GamepadStatusSettings>()
+            .init_resource::<GamepadStatusSettings>()
+            .init_resource::<GamepadStatus>();
+
+        let gilrs = match GilrsBuilder::new()
+            .with_default_filters(false)
+            .set_update_state(false)
+            .build()
+        {
+            Ok(gilrs) => gilrs,
+            Err(error) => {
+                warn!("Gamepads are not available: {error}");
+                return;
+            }
+        };
+        let forward = !app.is_plugin_added::<GilrsPlugin>();
+        app.insert_non_send_resource(GamepadBackend {
+            gilrs,
+            pending: Vec::new(),
+            forward,
+            power_checked: None,
+        })
+        .add_systems(Startup, announce_gamepads)
+        .add_systems(PreUpdate, read_gamepads.before(InputSystem));
+    }
+}
+
+/// How the gamepads are watched
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct GamepadStatusSettings {
+    /// How often gamepads are polled while the app is idle on demand, or
+    /// [None] to only read them in updates
+    pub idle_poll_interval: Option<Duration>,
+    /// How often the battery of the gamepads is read again
+    pub power_interval: Duration,
+}
+
+impl Default for GamepadStatusSettings {
+    fn default() -> Self {
+        Self {
+            idle_poll_interval: Some(Duration::from_millis(50)),
+            power_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// How a gamepad is powered
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GamepadPower {
+    /// The gamepad does not tell
+    #[default]
+    Unknown,
+    /// Powered through its cable, without a battery
+    Wired,
+    /// Running on its battery, charged by this percentage
+    Discharging(u8),
+    /// Charging its battery, charged by this percentage
+    Charging(u8),
+    /// Plugged in with a full battery
+    Charged,
+}
+
+impl GamepadPower {
+    /// The charge of the battery in percent, if it is known
+    pub fn level(&self) -> Option<u8> {
+        match self {
+            Self::Discharging(level) | Self::Charging(level) => Some(*level),
+            Self::Charged => Some(100),
+            Self::Unknown | Self::Wired => None,
+        }
+    }
+
+    pub fn is_charging(&self) -> bool {
+        matches!(self, Self::Charging(_))
+    }
+}
+
+impl From<PowerInfo> for GamepadPower {
+    fn from(power: PowerInfo) -> Self {
+        match power {
+            PowerInfo::Unknown => Self::Unknown,
+            PowerInfo::Wired => Self::Wired,
+            PowerInfo::Discharging(level) => Self::Discharging(level),
+            PowerInfo::Charging(level) => Self::Charging(level),
+            PowerInfo::Charged => Self::Charged,
+        }
+    }
+}
+
+/// A gamepad which is connected
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConnectedGamepad {
+    pub gamepad: Gamepad,
+    pub name: String,
+    pub power: GamepadPower,
+}
+
+/// The gamepads connected right now, in the order they were connected
+#[derive(Resource, Clone, Debug, Default)]
+pub struct GamepadStatus {
+    gamepads: Vec<ConnectedGamepad>,
+    revision: u64,
+}
+
+impl GamepadStatus {
+    pub fn iter(&self) -> impl Iterator<Item = &ConnectedGamepad> {
+        self.gamepads.iter()
+    }
+
+    pub fn get(&self, gamepad: Gamepad) -> Option<&ConnectedGamepad> {
+        self.gamepads
+            .iter()
+            .find(|status| status.gamepad == gamepad)
+    }
+
+    pub fn len(&self) -> usize {
+        self.gamepads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.gamepads.is_empty()
+    }
+
+    /// Grows whenever a gamepad comes or goes, or its battery changes
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn connect(&mut self, status: ConnectedGamepad) {
+        self.gamepads.retain(|old| old.gamepad != status.gamepad);
+        self.gamepads.push(status);
+        self.revision += 1;
+    }
+
+    fn disconnect(&mut self, gamepad: Gamepad) {
+        let count = self.gamepads.len();
+        self.gamepads.retain(|status| status.gamepad != gamepad);
+        if self.gamepads.len() != count {
+            self.revision += 1;
+        }
+    }
+}
+
+/// The gilrs instance of [QmlGamepadPlugin]
+pub(crate) struct GamepadBackend {
+    gilrs: gilrs::Gilrs,
+    /// Events read while the app was idle, for the next update
+    pending: Vec<gilrs::Event>,
+    /// Whether the events become Bevy events, as no `GilrsPlugin` sends them
+    forward: bool,
+    power_checked: Option<Instant>,
+}
+
+impl GamepadBackend {
+    /// Take the events read so far and those waiting in gilrs
+    fn events(&mut self) -> Vec<gilrs::Event> {
+        let mut events = std::mem::take(&mut self.pending);
+        while let Some(event) = self.gilrs.next_event() {
+            events.push(event);
+        }
+        events
+    }
+
+    fn status(&self, id: gilrs::GamepadId) -> ConnectedGamepad {
+        let gamepad = self.gilrs.gamepad(id);
+        ConnectedGamepad {
+            gamepad: bevy_gamepad(id),
+            name: gamepad.name().to_string(),
+            power: gamepad.power_info().into(),
+        }
+    }
+}
+
+/// How often the runtime polls the gamepads while the app is idle, [None]
+/// if there is nothing to poll
+pub(crate) fn idle_poll_interval(world: &World) -> Option<Duration> {
+    world.get_non_send_resource::<GamepadBackend>()?;
+    world
+        .get_resource::<GamepadStatusSettings>()
+        .and_then(|settings| settings.idle_poll_interval)
+}
+
+/// Read the gamepads of an idle app, returning whether it has input to
+/// update for
+pub(crate) fn poll_idle(world: &mut World) -> bool {
+    let Some(mut backend) = world.get_non_send_resource_mut::<GamepadBackend>() else {
+        return false;
+    };
+    while let Some(event) = backend.gilrs.next_event() {
+        backend.pending.push(event);
+    }
+    !backend.pending.is_empty()
+}
+
+/// The Bevy gamepad of a gilrs gamepad, numbered as `GilrsPlugin` does
+fn bevy_gamepad(id: gilrs::GamepadId) -> Gamepad {
+    Gamepad::new(id.into())
+}
+
+/// List the gamepads connected before the app started
+fn announce_gamepads(
+    backend: NonSend<GamepadBackend>,
+    mut status: ResMut<GamepadStatus>,
+    mut events: EventWriter<GamepadEvent>,
+) {
+    for (id, gamepad) in backend.gilrs.gamepads() {
+        status.connect(backend.status(id));
+        if backend.forward {
+            events.send(connection_event(bevy_gamepad(id), Some(gamepad.name())));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn read_gamepads(
+    mut backend: NonSendMut<GamepadBackend>,
+    mut status: ResMut<GamepadStatus>,
+    settings: Res<GamepadStatusSettings>,
+    gamepad_settings: Option<Res<GamepadSettings>>,
+    buttons: Option<Res<Axis<GamepadButton>>>,
+    axes: Option<Res<Axis<GamepadAxis>>>,
+    mut events: EventWriter<GamepadEvent>,
+    mut redraw: EventWriter<RequestRedraw>,
+) {
+    let gilrs_events = backend.events();
+    if !gilrs_events.is_empty() {
+        redraw.send(RequestRedraw);
+    }
+
+    for event in gilrs_events {
+        let gamepad = bevy_gamepad(event.id);
+        match event.event {
+            EventType::Connected => {
+                let connected = backend.status(event.id);
+                if backend.forward {
+                    events.send(connection_event(gamepad, Some(&connected.name)));
+                }
+                status.connect(connected);
+            }
+            EventType::Disconnected => {
+                status.disconnect(gamepad);
+                if backend.forward {
+                    events.send(connection_event(gamepad, None));
+                }
+            }
+            _ if !backend.forward => {}
+            EventType::ButtonChanged(button, value, _) => {
+                let Some(button_type) = button_type(button) else {
+                    continue;
+                };
+                let button = GamepadButton::new(gamepad, button_type);
+                let old_value = buttons.as_ref().and_then(|buttons| buttons.get(button));
+                let value = match &gamepad_settings {
+                    Some(gamepad_settings) => gamepad_settings
+                        .get_button_axis_settings(button)
+                        .filter(value, old_value),
+                    None => Some(value),
+                };
+                if let Some(value) = value {
+                    events.send(GamepadButtonChangedEvent::new(gamepad, button_type, value).into());
+                }
+            }
+            EventType::AxisChanged(axis, value, _) => {
+                let Some(axis_type) = axis_type(axis) else {
+                    continue;
+                };
+                let axis = GamepadAxis::new(gamepad, axis_type);
+                let old_value = axes.as_ref().and_then(|axes| axes.get(axis));
+                let value = match &gamepad_settings {
+                    Some(gamepad_settings) => gamepad_settings
+                        .get_axis_settings(axis)
+                        .filter(value, old_value),
+                    None => Some(value),
+                };
+                if let Some(value) = value {
+                    events.send(GamepadAxisChangedEvent::new(gamepad, axis_type, value).into());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let now = Instant::now();
+    let power_due = !backend
+        .power_checked
+        .is_some_and(|checked| now.duration_since(checked) < settings.power_interval);
+    if power_due && !status.is_empty() {
+        backend.power_checked = Some(now);
+        let powers: Vec<(Gamepad, GamepadPower)> = backend
+            .gilrs
+            .gamepads()
+            .map(|(id, gamepad)| (bevy_gamepad(id), gamepad.power_info().into()))
+            .collect();
+        let changed = powers
+            .iter()
+            .any(|(gamepad, power)| status.get(*gamepad).is_some_and(|old| old.power != *power));
+        if changed {
+            for connected in &mut status.gamepads {
+                if let Some((_, power)) = powers.iter().find(|(id, _)| *id == connected.gamepad) {
+                    connected.power = *power;
+                }
+            }
+            status.revision += 1;
+        }
+    }
+}
+
+fn connection_event(gamepad: Gamepad, name: Option<&str>) -> GamepadEvent {
+    let connection = match name {
+        Some(name) => GamepadConnection::Connected(GamepadInfo {
+            name: name.to_string(),
+        }),
+        None => GamepadConnection::Disconnected,
+    };
+    GamepadConnectionEvent::new(gamepad, connection).into()
+}
+
+fn button_type(button: gilrs::Button) -> Option<GamepadButtonType> {
+    use gilrs::Button;
+    Some(match button {
+        Button::South => GamepadButtonType::South,
+        Button::East => GamepadButtonType::East,
+        Button::North => GamepadButtonType::North,
+        Button::West => GamepadButtonType::West,
+        Button::C => GamepadButtonType::C,
+        Button::Z => GamepadButtonType::Z,
+        Button::LeftTrigger => GamepadButtonType::LeftTrigger,
+        Button::LeftTrigger2 => GamepadButtonType::LeftTrigger2,
+        Button::RightTrigger => GamepadButtonType::RightTrigger,
+        Button::RightTrigger2 => GamepadButtonType::RightTrigger2,
+        Button::Select => GamepadButtonType::Select,
+        Button::Start => GamepadButtonType::Start,
+        Button::Mode => GamepadButtonType::Mode,
+        Button::LeftThumb => GamepadButtonType::LeftThumb,
+        Button::RightThumb => GamepadButtonType::RightThumb,
+        Button::DPadUp => GamepadButtonType::DPadUp,
+        Button::DPadDown => GamepadButtonType::DPadDown,
+        Button::DPadLeft => GamepadButtonType::DPadLeft,
+        Button::DPadRight => GamepadButtonType::DPadRight,
+        Button::Unknown => return None,
+    })
+}
+
+fn axis_type(axis: gilrs::Axis) -> Option<GamepadAxisType> {
+    use gilrs::Axis;
+    Some(match axis {
+        Axis::LeftStickX => GamepadAxisType::LeftStickX,
+        Axis::LeftStickY => GamepadAxisType::LeftStickY,
+        Axis::LeftZ => GamepadAxisType::LeftZ,
+        Axis::RightStickX => GamepadAxisType::RightStickX,
+        Axis::RightStickY => GamepadAxisType::RightStickY,
+        Axis::RightZ => GamepadAxisType::RightZ,
+        // The d-pad is reported as buttons
+        Axis::DPadX | Axis::DPadY | Axis::Unknown => return None,
+    })
+}
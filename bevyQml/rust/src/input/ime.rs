@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Text composed with an input method.
+//!
+//! Bevy text fields ask for an input method by setting
+//! [Window::ime_enabled] on the window of the item while they have the
+//! focus, and place the candidate window with [Window::ime_position]. The
+//! item then takes Qt input method events, so CJK input methods and virtual
+//! keyboards work, and reports them as [Ime] events. Like winit, the
+//! preedit is cleared before the text is committed.
+
+use bevy::{prelude::*, window::Ime};
+
+/// Whether the window asks for an input method, and where its candidate
+/// window goes in logical pixels
+pub fn state(world: &World, window: Entity) -> Option<(bool, Vec2)> {
+    world
+        .get::<Window>(window)
+        .map(|window| (window.ime_enabled, window.ime_position))
+}
+
+/// The input method became available to the window, or went away
+pub fn enabled_changed(world: &mut World, window: Entity, enabled: bool) {
+    world.send_event(if enabled {
+        Ime::Enabled { window }
+    } else {
+        Ime::Disabled { window }
+    });
+}
+
+/// The text being composed changed
+///
+/// `cursor` is the position of the cursor in UTF-16 code units, as Qt
+/// reports it, or [None] if it is hidden.
+pub fn preedit(world: &mut World, window: Entity, value: String, cursor: Option<usize>) {
+    let cursor = cursor.map(|cursor| {
+        let index = byte_index(&value, cursor);
+        (index, index)
+    });
+    world.send_event(Ime::Preedit {
+        window,
+        value,
+        cursor,
+    });
+}
+
+/// The composed text was committed
+pub fn commit(world: &mut World, window: Entity, value: String) {
+    preedit(world, window, String::new(), None);
+    world.send_event(Ime::Commit { window, value });
+}
+
+/// The byte index of a position in UTF-16 code units
+fn byte_index(text: &str, utf16_index: usize) -> usize {
+    let mut units = 0;
+    for (index, character) in text.char_indices() {
+        if units >= utf16_index {
+            return index;
+        }
+        units += character.len_utf16();
+    }
+    text.len()
+}
@@ -0,0 +1,378 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Keys and text input.
+//!
+//! Qt only reports the logical key, so [KeyCode]s are derived from it using a
+//! US layout. Left and right modifiers cannot be told apart and are reported
+//! as the left one.
+
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardFocusLost, KeyboardInput, NativeKeyCode},
+        ButtonState,
+    },
+    prelude::*,
+    window::WindowFocused,
+};
+
+/// Qt::KeypadModifier
+const KEYPAD_MODIFIER: u32 = 0x2000_0000;
+
+/// A key as reported by QKeyEvent
+#[derive(Clone, Debug)]
+pub struct QtKey {
+    /// QKeyEvent::key()
+    pub key: i32,
+    /// QKeyEvent::modifiers()
+    pub modifiers: u32,
+    /// QKeyEvent::text()
+    pub text: String,
+    /// QKeyEvent::isAutoRepeat()
+    pub auto_repeat: bool,
+}
+
+impl QtKey {
+    fn is_keypad(&self) -> bool {
+        self.modifiers & KEYPAD_MODIFIER != 0
+    }
+
+    /// The physical key, assuming a US layout
+    pub fn key_code(&self) -> KeyCode {
+        if self.is_keypad() {
+            if let Some(key_code) = keypad_key_code(self.key) {
+                return key_code;
+            }
+        }
+        key_code_from_qt(self.key).unwrap_or(KeyCode::Unidentified(NativeKeyCode::Unidentified))
+    }
+
+    /// The key with the current layout and modifiers applied
+    pub fn logical_key(&self) -> Key {
+        if let Some(key) = named_key(self.key) {
+            return key;
+        }
+        if !printable(&self.text).is_empty() {
+            return Key::Character(self.text.as_str().into());
+        }
+        Key::Unidentified(bevy::input::keyboard::NativeKey::Unidentified)
+    }
+}
+
+/// Remove control characters which Qt reports as text, e.g. for Ctrl+C
+fn printable(text: &str) -> String {
+    text.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// A key was pressed or released in the window
+pub fn key(world: &mut World, window: Entity, key: &QtKey, state: ButtonState) {
+    // Qt repeats releases as well, Bevy expects a key to stay pressed
+    if key.auto_repeat && state == ButtonState::Released {
+        return;
+    }
+
+    let logical_key = key.logical_key();
+    world.send_event(KeyboardInput {
+        key_code: key.key_code(),
+        logical_key,
+        state,
+        window,
+    });
+
+    if state == ButtonState::Pressed {
+        for char in printable(&key.text).chars() {
+            #[allow(deprecated)]
+            world.send_event(ReceivedCharacter {
+                window,
+                char: char.to_string().into(),
+            });
+        }
+    }
+}
+
+/// The window gained or lost keyboard focus
+///
+/// On losing focus every pressed key is released, as Qt will not tell the
+/// item about releases that happen while it is not focused.
+pub fn focus_changed(world: &mut World, window: Entity, focused: bool) {
+    if let Some(mut bevy_window) = world.get_mut::<Window>(window) {
+        bevy_window.focused = focused;
+    }
+    world.send_event(WindowFocused { window, focused });
+    if !focused {
+        world.send_event(KeyboardFocusLost);
+    }
+}
+
+fn keypad_key_code(key: i32) -> Option<KeyCode> {
+    Some(match key {
+        0x30 => KeyCode::Numpad0,
+        0x31 => KeyCode::Numpad1,
+        0x32 => KeyCode::Numpad2,
+        0x33 => KeyCode::Numpad3,
+        0x34 => KeyCode::Numpad4,
+        0x35 => KeyCode::Numpad5,
+        0x36 => KeyCode::Numpad6,
+        0x37 => KeyCode::Numpad7,
+        0x38 => KeyCode::Numpad8,
+        0x39 => KeyCode::Numpad9,
+        0x2a => KeyCode::NumpadMultiply,
+        0x2b => KeyCode::NumpadAdd,
+        0x2d => KeyCode::NumpadSubtract,
+        0x2e => KeyCode::NumpadDecimal,
+        0x2f => KeyCode::NumpadDivide,
+        0x3d => KeyCode::NumpadEqual,
+        0x0100_0005 => KeyCode::NumpadEnter,
+        _ => return None,
+    })
+}
+
+/// Map a Qt::Key onto the key at the same position on a US layout
+pub fn key_code_from_qt(key: i32) -> Option<KeyCode> {
+    // Qt::Key_F1 to Qt::Key_F35
+    if (0x0100_0030..=0x0100_0052).contains(&key) {
+        return Some(function_key_code(key - 0x0100_0030));
+    }
+
+    Some(match key {
+        0x20 => KeyCode::Space,
+        0x27 | 0x22 => KeyCode::Quote,
+        0x2c | 0x3c => KeyCode::Comma,
+        0x2d | 0x5f => KeyCode::Minus,
+        0x2e | 0x3e => KeyCode::Period,
+        0x2f | 0x3f => KeyCode::Slash,
+        0x30 | 0x29 => KeyCode::Digit0,
+        0x31 | 0x21 => KeyCode::Digit1,
+        0x32 | 0x40 => KeyCode::Digit2,
+        0x33 | 0x23 => KeyCode::Digit3,
+        0x34 | 0x24 => KeyCode::Digit4,
+        0x35 | 0x25 => KeyCode::Digit5,
+        0x36 | 0x5e => KeyCode::Digit6,
+        0x37 | 0x26 => KeyCode::Digit7,
+        0x38 | 0x2a => KeyCode::Digit8,
+        0x39 | 0x28 => KeyCode::Digit9,
+        0x3b | 0x3a => KeyCode::Semicolon,
+        0x3d | 0x2b => KeyCode::Equal,
+        0x41 => KeyCode::KeyA,
+        0x42 => KeyCode::KeyB,
+        0x43 => KeyCode::KeyC,
+        0x44 => KeyCode::KeyD,
+        0x45 => KeyCode::KeyE,
+        0x46 => KeyCode::KeyF,
+        0x47 => KeyCode::KeyG,
+        0x48 => KeyCode::KeyH,
+        0x49 => KeyCode::KeyI,
+        0x4a => KeyCode::KeyJ,
+        0x4b => KeyCode::KeyK,
+        0x4c => KeyCode::KeyL,
+        0x4d => KeyCode::KeyM,
+        0x4e => KeyCode::KeyN,
+        0x4f => KeyCode::KeyO,
+        0x50 => KeyCode::KeyP,
+        0x51 => KeyCode::KeyQ,
+        0x52 => KeyCode::KeyR,
+        0x53 => KeyCode::KeyS,
+        0x54 => KeyCode::KeyT,
+        0x55 => KeyCode::KeyU,
+        0x56 => KeyCode::KeyV,
+        0x57 => KeyCode::KeyW,
+        0x58 => KeyCode::KeyX,
+        0x59 => KeyCode::KeyY,
+        0x5a => KeyCode::KeyZ,
+        0x5b | 0x7b => KeyCode::BracketLeft,
+        0x5c | 0x7c => KeyCode::Backslash,
+        0x5d | 0x7d => KeyCode::BracketRight,
+        0x60 | 0x7e => KeyCode::Backquote,
+        0x0100_0000 => KeyCode::Escape,
+        0x0100_0001 | 0x0100_0002 => KeyCode::Tab,
+        0x0100_0003 => KeyCode::Backspace,
+        0x0100_0004 => KeyCode::Enter,
+        0x0100_0005 => KeyCode::NumpadEnter,
+        0x0100_0006 => KeyCode::Insert,
+        0x0100_0007 => KeyCode::Delete,
+        0x0100_0008 => KeyCode::Pause,
+        0x0100_0009 => KeyCode::PrintScreen,
+        0x0100_0010 => KeyCode::Home,
+        0x0100_0011 => KeyCode::End,
+        0x0100_0012 => KeyCode::ArrowLeft,
+        0x0100_0013 => KeyCode::ArrowUp,
+        0x0100_0014 => KeyCode::ArrowRight,
+        0x0100_0015 => KeyCode::ArrowDown,
+        0x0100_0016 => KeyCode::PageUp,
+        0x0100_0017 => KeyCode::PageDown,
+        0x0100_0020 => KeyCode::ShiftLeft,
+        // Qt swaps Control and Meta on macOS, so Key_Control is Command there
+        #[cfg(target_os = "macos")]
+        0x0100_0021 => KeyCode::SuperLeft,
+        #[cfg(target_os = "macos")]
+        0x0100_0022 => KeyCode::ControlLeft,
+        #[cfg(not(target_os = "macos"))]
+        0x0100_0021 => KeyCode::ControlLeft,
+        #[cfg(not(target_os = "macos"))]
+        0x0100_0022 => KeyCode::SuperLeft,
+        0x0100_0023 => KeyCode::AltLeft,
+        0x0100_1103 => KeyCode::AltRight,
+        0x0100_0024 => KeyCode::CapsLock,
+        0x0100_0025 => KeyCode::NumLock,
+        0x0100_0026 => KeyCode::ScrollLock,
+        0x0100_0053 => KeyCode::SuperLeft,
+        0x0100_0054 => KeyCode::SuperRight,
+        0x0100_0055 => KeyCode::ContextMenu,
+        _ => return None,
+    })
+}
+
+fn function_key_code(index: i32) -> KeyCode {
+    const FUNCTION_KEYS: [KeyCode; 35] = [
+        KeyCode::F1,
+        KeyCode::F2,
+        KeyCode::F3,
+        KeyCode::F4,
+        KeyCode::F5,
+        KeyCode::F6,
+        KeyCode::F7,
+        KeyCode::F8,
+        KeyCode::F9,
+        KeyCode::F10,
+        KeyCode::F11,
+        KeyCode::F12,
+        KeyCode::F13,
+        KeyCode::F14,
+        KeyCode::F15,
+        KeyCode::F16,
+        KeyCode::F17,
+        KeyCode::F18,
+        KeyCode::F19,
+        KeyCode::F20,
+        KeyCode::F21,
+        KeyCode::F22,
+        KeyCode::F23,
+        KeyCode::F24,
+        KeyCode::F25,
+        KeyCode::F26,
+        KeyCode::F27,
+        KeyCode::F28,
+        KeyCode::F29,
+        KeyCode::F30,
+        KeyCode::F31,
+        KeyCode::F32,
+        KeyCode::F33,
+        KeyCode::F34,
+        KeyCode::F35,
+    ];
+    FUNCTION_KEYS[index as usize]
+}
+
+/// Keys which do not produce text, as named keys
+fn named_key(key: i32) -> Option<Key> {
+    if (0x0100_0030..=0x0100_0052).contains(&key) {
+        return Some(match function_key_code(key - 0x0100_0030) {
+            KeyCode::F1 => Key::F1,
+            KeyCode::F2 => Key::F2,
+            KeyCode::F3 => Key::F3,
+            KeyCode::F4 => Key::F4,
+            KeyCode::F5 => Key::F5,
+            KeyCode::F6 => Key::F6,
+            KeyCode::F7 => Key::F7,
+            KeyCode::F8 => Key::F8,
+            KeyCode::F9 => Key::F9,
+            KeyCode::F10 => Key::F10,
+            KeyCode::F11 => Key::F11,
+            KeyCode::F12 => Key::F12,
+            _ => Key::Unidentified(bevy::input::keyboard::NativeKey::Unidentified),
+        });
+    }
+
+    Some(match key {
+        0x20 => Key::Space,
+        0x0100_0000 => Key::Escape,
+        0x0100_0001 | 0x0100_0002 => Key::Tab,
+        0x0100_0003 => Key::Backspace,
+        0x0100_0004 | 0x0100_0005 => Key::Enter,
+        0x0100_0006 => Key::Insert,
+        0x0100_0007 => Key::Delete,
+        0x0100_0008 => Key::Pause,
+        0x0100_0009 => Key::PrintScreen,
+        0x0100_0010 => Key::Home,
+        0x0100_0011 => Key::End,
+        0x0100_0012 => Key::ArrowLeft,
+        0x0100_0013 => Key::ArrowUp,
+        0x0100_0014 => Key::ArrowRight,
+        0x0100_0015 => Key::ArrowDown,
+        0x0100_0016 => Key::PageUp,
+        0x0100_0017 => Key::PageDown,
+        0x0100_0020 => Key::Shift,
+        #[cfg(target_os = "macos")]
+        0x0100_0021 => Key::Super,
+        #[cfg(target_os = "macos")]
+        0x0100_0022 => Key::Control,
+        #[cfg(not(target_os = "macos"))]
+        0x0100_0021 => Key::Control,
+        #[cfg(not(target_os = "macos"))]
+        0x0100_0022 => Key::Super,
+        0x0100_0023 => Key::Alt,
+        0x0100_1103 => Key::AltGraph,
+        0x0100_0024 => Key::CapsLock,
+        0x0100_0025 => Key::NumLock,
+        0x0100_0026 => Key::ScrollLock,
+        0x0100_0053 | 0x0100_0054 => Key::Super,
+        0x0100_0055 => Key::ContextMenu,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qt_key(key: i32, modifiers: u32) -> QtKey {
+        QtKey {
+            key,
+            modifiers,
+            text: String::new(),
+            auto_repeat: false,
+        }
+    }
+
+    #[test]
+    fn maps_letters_and_digits() {
+        // Qt::Key_A, Qt::Key_Z, Qt::Key_0, Qt::Key_9
+        assert_eq!(key_code_from_qt(0x41), Some(KeyCode::KeyA));
+        assert_eq!(key_code_from_qt(0x5a), Some(KeyCode::KeyZ));
+        assert_eq!(key_code_from_qt(0x30), Some(KeyCode::Digit0));
+        assert_eq!(key_code_from_qt(0x39), Some(KeyCode::Digit9));
+        // Shifted characters are on the key they are typed with
+        assert_eq!(key_code_from_qt(0x40), Some(KeyCode::Digit2));
+        assert_eq!(key_code_from_qt(0x3f), Some(KeyCode::Slash));
+    }
+
+    #[test]
+    fn maps_named_keys() {
+        assert_eq!(key_code_from_qt(0x0100_0000), Some(KeyCode::Escape));
+        assert_eq!(key_code_from_qt(0x0100_0012), Some(KeyCode::ArrowLeft));
+        assert_eq!(key_code_from_qt(0x0100_0030), Some(KeyCode::F1));
+        assert_eq!(key_code_from_qt(0x0100_0052), Some(KeyCode::F35));
+    }
+
+    #[test]
+    fn unmapped_keys() {
+        // Qt::Key_VolumeUp, Qt::Key_unknown and lower case letters, which
+        // Qt never reports
+        assert_eq!(key_code_from_qt(0x0100_0072), None);
+        assert_eq!(key_code_from_qt(0x01ff_ffff), None);
+        assert_eq!(key_code_from_qt(0x61), None);
+        assert_eq!(
+            qt_key(0x0100_0072, 0).key_code(),
+            KeyCode::Unidentified(NativeKeyCode::Unidentified)
+        );
+    }
+
+    #[test]
+    fn keypad_modifier() {
+        assert_eq!(qt_key(0x35, 0).key_code(), KeyCode::Digit5);
+        assert_eq!(qt_key(0x35, KEYPAD_MODIFIER).key_code(), KeyCode::Numpad5);
+        assert_eq!(qt_key(0x2b, 0).key_code(), KeyCode::Equal);
+        assert_eq!(qt_key(0x2b, KEYPAD_MODIFIER).key_code(), KeyCode::NumpadAdd);
+        // Keys without a keypad counterpart ignore the modifier
+        assert_eq!(qt_key(0x41, KEYPAD_MODIFIER).key_code(), KeyCode::KeyA);
+    }
+}
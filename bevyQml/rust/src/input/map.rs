@@ -0,0 +1,664 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Gameplay actions and axes bound to keys, mouse buttons and gamepads.
+//!
+//! Systems ask the [InputMap] whether `jump` is pressed or how far `move_x`
+//! goes, rather than checking raw keys, and players rebind them from QML
+//! through an `InputMapModel`:
+//!
+//! ```ignore
+//! app.insert_resource(
+//!     InputMap::default()
+//!         .with_action("jump", [
+//!             InputSource::Key(KeyCode::Space),
+//!             InputSource::GamepadButton(GamepadButtonType::South),
+//!         ])
+//!         .with_axis(
+//!             "move_x",
+//!             [
+//!                 InputSource::Key(KeyCode::KeyD),
+//!                 InputSource::gamepad_axis(GamepadAxisType::LeftStickX),
+//!             ],
+//!             [InputSource::Key(KeyCode::KeyA)],
+//!         ),
+//! );
+//!
+//! fn move_player(input: Res<InputMap>, mut players: Query<&mut Velocity, With<Player>>) {
+//!     for mut velocity in &mut players {
+//!         velocity.x = input.axis("move_x") * SPEED;
+//!         if input.just_pressed("jump") {
+//!             velocity.y = JUMP_SPEED;
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The map is updated in [PreUpdate] after Bevy's [InputSystem], taking
+//! every connected gamepad into account. Bindings changed at runtime are
+//! kept in QSettings, see [crate::settings], and applied again when the
+//! map is inserted on the next start.
+//!
+//! QML refers to sources by text, such as `Key:Space`, `Mouse:Left`,
+//! `GamepadButton:South` or `GamepadAxis:-LeftStickY`, using the names of
+//! the variants of [KeyCode], [MouseButton], [GamepadButtonType] and
+//! [GamepadAxisType].
+
+use std::{fmt, str::FromStr};
+
+use bevy::{
+    input::gamepad::{GamepadAxis, GamepadButton, Gamepads},
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant},
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::settings;
+
+/// Where the rebound sources are stored, see [crate::settings]
+const BINDINGS_KEY: &str = "input_map";
+
+/// How far a source goes before an action bound to it is pressed
+const PRESS_THRESHOLD: f32 = 0.5;
+
+/// A key, button or gamepad axis an action or axis is bound to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputSource {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    GamepadButton(GamepadButtonType),
+    /// A stick or trigger, whose value is negated if `inverted`
+    GamepadAxis {
+        axis: GamepadAxisType,
+        inverted: bool,
+    },
+}
+
+impl InputSource {
+    pub fn gamepad_axis(axis: GamepadAxisType) -> Self {
+        Self::GamepadAxis {
+            axis,
+            inverted: false,
+        }
+    }
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(key) => write!(f, "Key:{key:?}"),
+            Self::Mouse(button) => write!(f, "Mouse:{button:?}"),
+            Self::GamepadButton(button) => write!(f, "GamepadButton:{button:?}"),
+            Self::GamepadAxis { axis, inverted } => {
+                let sign = if *inverted { "-" } else { "" };
+                write!(f, "GamepadAxis:{sign}{axis:?}")
+            }
+        }
+    }
+}
+
+impl FromStr for InputSource {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let invalid = || format!("{text:?} is not an input source");
+        let (kind, name) = text.split_once(':').ok_or_else(invalid)?;
+        match kind {
+            "Key" => unit_variant(name).map(Self::Key),
+            "Mouse" => unit_variant(name).map(Self::Mouse),
+            "GamepadButton" => unit_variant(name).map(Self::GamepadButton),
+            "GamepadAxis" => {
+                let (inverted, name) = match name.strip_prefix('-') {
+                    Some(name) => (true, name),
+                    None => (false, name),
+                };
+                unit_variant(name).map(|axis| Self::GamepadAxis { axis, inverted })
+            }
+            _ => None,
+        }
+        .ok_or_else(invalid)
+    }
+}
+
+/// The variant of an enum without fields, by its name
+fn unit_variant<T: FromReflect>(name: &str) -> Option<T> {
+    T::from_reflect(&DynamicEnum::new(name, DynamicVariant::Unit))
+}
+
+/// Whether an action or an axis is bound
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BindingKind {
+    Action,
+    Axis,
+}
+
+/// The sources of an action or axis
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputBinding {
+    pub name: String,
+    pub kind: BindingKind,
+    /// Press the action, or push the axis the positive way
+    pub sources: Vec<InputSource>,
+    /// Push the axis the negative way, unused for actions
+    pub negative: Vec<InputSource>,
+    pub default_sources: Vec<InputSource>,
+    pub default_negative: Vec<InputSource>,
+    pressed: bool,
+    just_pressed: bool,
+    just_released: bool,
+    value: f32,
+}
+
+impl InputBinding {
+    fn new(
+        name: String,
+        kind: BindingKind,
+        sources: Vec<InputSource>,
+        negative: Vec<InputSource>,
+    ) -> Self {
+        Self {
+            name,
+            kind,
+            default_sources: sources.clone(),
+            default_negative: negative.clone(),
+            sources,
+            negative,
+            pressed: false,
+            just_pressed: false,
+            just_released: false,
+            value: 0.0,
+        }
+    }
+
+    pub fn pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// The value of an axis from -1 to 1, or 0 to 1 for an action
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Whether the sources differ from the defaults
+    pub fn is_customized(&self) -> bool {
+        self.sources != self.default_sources || self.negative != self.default_negative
+    }
+}
+
+/// Named actions and axes bound to input sources
+#[derive(Resource, Clone, Debug)]
+pub struct InputMap {
+    bindings: Vec<InputBinding>,
+    /// Stick values closer to the center than this count as 0
+    pub dead_zone: f32,
+    /// Bumped whenever the sources of a binding change
+    revision: u64,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self {
+            bindings: Vec::new(),
+            dead_zone: 0.1,
+            revision: 0,
+        }
+    }
+}
+
+impl InputMap {
+    /// Add an action, which is pressed while any of its sources is
+    pub fn with_action(
+        mut self,
+        name: impl Into<String>,
+        sources: impl IntoIterator<Item = InputSource>,
+    ) -> Self {
+        let binding = InputBinding::new(
+            name.into(),
+            BindingKind::Action,
+            sources.into_iter().collect(),
+            Vec::new(),
+        );
+        self.insert(binding);
+        self
+    }
+
+    /// Add an axis, the sum of its positive sources minus its negative ones
+    pub fn with_axis(
+        mut self,
+        name: impl Into<String>,
+        positive: impl IntoIterator<Item = InputSource>,
+        negative: impl IntoIterator<Item = InputSource>,
+    ) -> Self {
+        let binding = InputBinding::new(
+            name.into(),
+            BindingKind::Axis,
+            positive.into_iter().collect(),
+            negative.into_iter().collect(),
+        );
+        self.insert(binding);
+        self
+    }
+
+    /// The actions and axes in the order they were added
+    pub fn bindings(&self) -> &[InputBinding] {
+        &self.bindings
+    }
+
+    pub fn get(&self, name: &str) -> Option<&InputBinding> {
+        self.bindings.iter().find(|binding| binding.name == name)
+    }
+
+    /// Whether the action is held down, false for unknown names
+    pub fn pressed(&self, name: &str) -> bool {
+        self.get(name).is_some_and(|binding| binding.pressed)
+    }
+
+    /// Whether the action was pressed in this update
+    pub fn just_pressed(&self, name: &str) -> bool {
+        self.get(name).is_some_and(|binding| binding.just_pressed)
+    }
+
+    /// Whether the action was released in this update
+    pub fn just_released(&self, name: &str) -> bool {
+        self.get(name).is_some_and(|binding| binding.just_released)
+    }
+
+    /// The value of the axis from -1 to 1, 0 for unknown names
+    pub fn axis(&self, name: &str) -> f32 {
+        self.get(name).map_or(0.0, |binding| binding.value)
+    }
+
+    /// Changes whenever the sources of a binding change
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Bind the action or axis to other sources, keeping them in the
+    /// settings
+    pub fn rebind(
+        &mut self,
+        name: &str,
+        sources: Vec<InputSource>,
+        negative: Vec<InputSource>,
+    ) -> Result<(), String> {
+        let binding = self
+            .bindings
+            .iter_mut()
+            .find(|binding| binding.name == name)
+            .ok_or_else(|| format!("There is no action or axis {name:?}"))?;
+        binding.sources = sources;
+        binding.negative = negative;
+        self.revision += 1;
+        self.store();
+        Ok(())
+    }
+
+    /// Go back to the default sources of the action or axis
+    pub fn reset(&mut self, name: &str) -> Result<(), String> {
+        let binding = self
+            .get(name)
+            .ok_or_else(|| format!("There is no action or axis {name:?}"))?;
+        let (sources, negative) = (
+            binding.default_sources.clone(),
+            binding.default_negative.clone(),
+        );
+        self.rebind(name, sources, negative)
+    }
+
+    /// Go back to the default sources of every action and axis
+    pub fn reset_all(&mut self) {
+        for binding in &mut self.bindings {
+            binding.sources = binding.default_sources.clone();
+            binding.negative = binding.default_negative.clone();
+        }
+        self.revision += 1;
+        self.store();
+    }
+
+    fn insert(&mut self, binding: InputBinding) {
+        match self
+            .bindings
+            .iter_mut()
+            .find(|other| other.name == binding.name)
+        {
+            Some(other) => *other = binding,
+            None => self.bindings.push(binding),
+        }
+        self.revision += 1;
+    }
+
+    /// Keep the customized bindings in the settings
+    fn store(&self) {
+        let stored: HashMap<&str, StoredBinding> = self
+            .bindings
+            .iter()
+            .filter(|binding| binding.is_customized())
+            .map(|binding| {
+                let texts =
+                    |sources: &[InputSource]| sources.iter().map(ToString::to_string).collect();
+                let stored = StoredBinding {
+                    sources: texts(&binding.sources),
+                    negative: texts(&binding.negative),
+                };
+                (binding.name.as_str(), stored)
+            })
+            .collect();
+        settings::save(BINDINGS_KEY, &stored);
+    }
+
+    /// Apply the bindings kept in the settings
+    fn restore(&mut self) {
+        let Some(stored) = settings::load::<HashMap<String, StoredBinding>>(BINDINGS_KEY) else {
+            return;
+        };
+        let parse = |texts: &[String]| -> Vec<InputSource> {
+            texts
+                .iter()
+                .filter_map(|text| {
+                    text.parse()
+                        .map_err(|error| warn!("Ignoring a stored input binding: {error}"))
+                        .ok()
+                })
+                .collect()
+        };
+        for binding in &mut self.bindings {
+            if let Some(stored) = stored.get(&binding.name) {
+                binding.sources = parse(&stored.sources);
+                binding.negative = parse(&stored.negative);
+            }
+        }
+        self.revision += 1;
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredBinding {
+    sources: Vec<String>,
+    negative: Vec<String>,
+}
+
+/// The state of the devices the sources are read from
+#[derive(bevy::ecs::system::SystemParam)]
+pub struct InputSources<'w> {
+    keys: Option<Res<'w, ButtonInput<KeyCode>>>,
+    mouse: Option<Res<'w, ButtonInput<MouseButton>>>,
+    gamepads: Option<Res<'w, Gamepads>>,
+    gamepad_buttons: Option<Res<'w, ButtonInput<GamepadButton>>>,
+    gamepad_axes: Option<Res<'w, Axis<GamepadAxis>>>,
+}
+
+impl InputSources<'_> {
+    /// How far the source goes, 0 or 1 for keys and buttons
+    pub fn value(&self, source: InputSource, dead_zone: f32) -> f32 {
+        let held = |pressed: bool| if pressed { 1.0 } else { 0.0 };
+        match source {
+            InputSource::Key(key) => held(self.keys.as_ref().is_some_and(|keys| keys.pressed(key))),
+            InputSource::Mouse(button) => held(
+                self.mouse
+                    .as_ref()
+                    .is_some_and(|mouse| mouse.pressed(button)),
+            ),
+            InputSource::GamepadButton(button) => held(self.gamepads().any(|gamepad| {
+                self.gamepad_buttons
+                    .as_ref()
+                    .is_some_and(|buttons| buttons.pressed(GamepadButton::new(gamepad, button)))
+            })),
+            InputSource::GamepadAxis { axis, inverted } => {
+                let sign = if inverted { -1.0 } else { 1.0 };
+                // The gamepad pushed furthest wins
+                self.gamepads()
+                    .filter_map(|gamepad| {
+                        self.gamepad_axes
+                            .as_ref()?
+                            .get(GamepadAxis::new(gamepad, axis))
+                    })
+                    .map(|value| {
+                        if value.abs() < dead_zone {
+                            0.0
+                        } else {
+                            value * sign
+                        }
+                    })
+                    .fold(0.0, |furthest: f32, value| {
+                        if value.abs() > furthest.abs() {
+                            value
+                        } else {
+                            furthest
+                        }
+                    })
+            }
+        }
+    }
+
+    /// The first source going past the threshold, for picking a binding
+    pub fn first_pressed(&self) -> Option<InputSource> {
+        if let Some(key) = self
+            .keys
+            .as_ref()
+            .and_then(|keys| keys.get_just_pressed().next().copied())
+        {
+            return Some(InputSource::Key(key));
+        }
+        if let Some(button) = self
+            .mouse
+            .as_ref()
+            .and_then(|mouse| mouse.get_just_pressed().next().copied())
+        {
+            return Some(InputSource::Mouse(button));
+        }
+        if let Some(button) = self
+            .gamepad_buttons
+            .as_ref()
+            .and_then(|buttons| buttons.get_just_pressed().next().copied())
+        {
+            return Some(InputSource::GamepadButton(button.button_type));
+        }
+        let axes = self.gamepad_axes.as_ref()?;
+        axes.devices().find_map(|axis| {
+            let value = axes.get(*axis)?;
+            (value.abs() > PRESS_THRESHOLD).then_some(InputSource::GamepadAxis {
+                axis: axis.axis_type,
+                inverted: value < 0.0,
+            })
+        })
+    }
+
+    fn gamepads(&self) -> impl Iterator<Item = Gamepad> + '_ {
+        self.gamepads.iter().flat_map(|gamepads| gamepads.iter())
+    }
+}
+
+/// Apply the bindings kept in the settings to a new map
+pub(crate) fn restore_bindings(mut map: ResMut<InputMap>) {
+    map.restore();
+}
+
+/// Read the sources of every action and axis
+pub(crate) fn update_input_map(mut map: ResMut<InputMap>, sources: InputSources) {
+    let dead_zone = map.dead_zone;
+    // Systems only see the map change when bindings change
+    for binding in &mut map.bypass_change_detection().bindings {
+        let positive: f32 = binding
+            .sources
+            .iter()
+            .map(|source| sources.value(*source, dead_zone))
+            .sum();
+        let negative: f32 = binding
+            .negative
+            .iter()
+            .map(|source| sources.value(*source, dead_zone))
+            .sum();
+        let pressed = match binding.kind {
+            BindingKind::Action => binding
+                .sources
+                .iter()
+                .any(|source| sources.value(*source, dead_zone) > PRESS_THRESHOLD),
+            BindingKind::Axis => (positive - negative).abs() > PRESS_THRESHOLD,
+        };
+        binding.value = match binding.kind {
+            BindingKind::Action => positive.clamp(0.0, 1.0),
+            BindingKind::Axis => (positive - negative).clamp(-1.0, 1.0),
+        };
+        binding.just_pressed = pressed && !binding.pressed;
+        binding.just_released = !pressed && binding.pressed;
+        binding.pressed = pressed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input_map() -> InputMap {
+        InputMap::default()
+            .with_action("jump", [InputSource::Key(KeyCode::Space)])
+            .with_axis(
+                "move_x",
+                [InputSource::Key(KeyCode::KeyD)],
+                [InputSource::Key(KeyCode::KeyA)],
+            )
+    }
+
+    /// A world with the map and keyboard, and a schedule updating the map
+    fn world(map: InputMap) -> (World, Schedule) {
+        let mut world = World::new();
+        world.insert_resource(map);
+        world.init_resource::<ButtonInput<KeyCode>>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(update_input_map);
+        (world, schedule)
+    }
+
+    #[test]
+    fn looks_up_bindings() {
+        let map = input_map();
+        let names: Vec<&str> = map
+            .bindings()
+            .iter()
+            .map(|binding| binding.name.as_str())
+            .collect();
+        assert_eq!(names, ["jump", "move_x"]);
+        assert_eq!(map.get("jump").unwrap().kind, BindingKind::Action);
+        assert_eq!(
+            map.get("move_x").unwrap().negative,
+            [InputSource::Key(KeyCode::KeyA)]
+        );
+        assert!(map.get("crouch").is_none());
+        assert!(!map.pressed("crouch"));
+        assert_eq!(map.axis("crouch"), 0.0);
+
+        // Adding a binding again replaces it in place
+        let map = map.with_action("jump", [InputSource::Key(KeyCode::KeyW)]);
+        assert_eq!(map.bindings().len(), 2);
+        assert_eq!(map.bindings()[0].sources, [InputSource::Key(KeyCode::KeyW)]);
+    }
+
+    #[test]
+    fn reads_the_sources() {
+        let (mut world, mut schedule) = world(input_map());
+        let press = |world: &mut World, key| {
+            world.resource_mut::<ButtonInput<KeyCode>>().press(key);
+        };
+
+        press(&mut world, KeyCode::Space);
+        press(&mut world, KeyCode::KeyA);
+        schedule.run(&mut world);
+        let map = world.resource::<InputMap>();
+        assert!(map.pressed("jump"));
+        assert!(map.just_pressed("jump"));
+        assert_eq!(map.axis("move_x"), -1.0);
+
+        press(&mut world, KeyCode::KeyD);
+        schedule.run(&mut world);
+        let map = world.resource::<InputMap>();
+        assert!(map.pressed("jump"));
+        assert!(!map.just_pressed("jump"));
+        assert_eq!(map.axis("move_x"), 0.0);
+        assert!(!map.get("move_x").unwrap().pressed());
+
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .release(KeyCode::Space);
+        schedule.run(&mut world);
+        let map = world.resource::<InputMap>();
+        assert!(!map.pressed("jump"));
+        assert!(map.just_released("jump"));
+    }
+
+    #[test]
+    fn rebinds() {
+        let mut map = input_map();
+        let revision = map.revision();
+        map.rebind("jump", vec![InputSource::Key(KeyCode::KeyW)], Vec::new())
+            .unwrap();
+        assert!(map.revision() > revision);
+        let jump = map.get("jump").unwrap();
+        assert_eq!(jump.sources, [InputSource::Key(KeyCode::KeyW)]);
+        assert_eq!(jump.default_sources, [InputSource::Key(KeyCode::Space)]);
+        assert!(jump.is_customized());
+        assert!(!map.get("move_x").unwrap().is_customized());
+        assert!(map.rebind("crouch", Vec::new(), Vec::new()).is_err());
+
+        // The new source is used from the next update
+        let (mut world, mut schedule) = world(map);
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyW);
+        schedule.run(&mut world);
+        assert!(world.resource::<InputMap>().pressed("jump"));
+
+        let mut map = world.resource_mut::<InputMap>();
+        map.reset("jump").unwrap();
+        assert_eq!(
+            map.get("jump").unwrap().sources,
+            [InputSource::Key(KeyCode::Space)]
+        );
+        assert!(!map.get("jump").unwrap().is_customized());
+    }
+
+    #[test]
+    fn restores_rebound_sources() {
+        let mut map = input_map();
+        map.rebind(
+            "move_x",
+            vec![InputSource::Key(KeyCode::ArrowRight)],
+            vec![InputSource::gamepad_axis(GamepadAxisType::LeftStickX)],
+        )
+        .unwrap();
+
+        let mut restored = input_map();
+        restored.restore();
+        assert_eq!(restored.get("move_x"), map.get("move_x"));
+        assert_eq!(restored.get("jump"), map.get("jump"));
+
+        map.reset_all();
+        let mut restored = input_map();
+        restored.restore();
+        assert!(!restored.get("move_x").unwrap().is_customized());
+    }
+
+    #[test]
+    fn sources_as_text() {
+        for source in [
+            InputSource::Key(KeyCode::Space),
+            InputSource::Mouse(MouseButton::Left),
+            InputSource::GamepadButton(GamepadButtonType::South),
+            InputSource::GamepadAxis {
+                axis: GamepadAxisType::LeftStickY,
+                inverted: true,
+            },
+        ] {
+            assert_eq!(source.to_string().parse(), Ok(source));
+        }
+        assert_eq!(
+            "GamepadAxis:-LeftStickY".parse(),
+            Ok(InputSource::GamepadAxis {
+                axis: GamepadAxisType::LeftStickY,
+                inverted: true,
+            })
+        );
+        assert!("Key:Hyper".parse::<InputSource>().is_err());
+        assert!("Space".parse::<InputSource>().is_err());
+        assert!("Pedal:Left".parse::<InputSource>().is_err());
+    }
+}
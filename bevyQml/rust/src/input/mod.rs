@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Translates the input a QML item receives into Bevy input events.
+//!
+//! Bevy input events refer to the window they happened in, so every
+//! [crate::cxxqt_bevy_quick_item] target entity also carries a [Window] that
+//! stands in for the item. Nothing creates a native window for it, but code
+//! that relies on [Window::cursor_position] or the [PrimaryWindow] keeps
+//! working. The first item becomes the [PrimaryWindow].
+
+pub mod drop;
+pub mod gamepad;
+pub mod ime;
+pub mod keyboard;
+pub mod map;
+pub mod mouse;
+pub mod raw_motion;
+pub mod stylus;
+pub mod touch;
+
+use bevy::{
+    input::{touch::touch_screen_input_system, InputSystem},
+    prelude::*,
+    window::{PrimaryWindow, WindowResolution},
+};
+
+/// Registers the events and settings used by the forwarded input
+pub struct QmlInputPlugin;
+
+impl Plugin for QmlInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<touch::CameraGesture>()
+            .add_event::<drop::AssetDropped>()
+            .add_event::<stylus::StylusInput>()
+            .register_type::<drop::FileDropSettings>()
+            .init_resource::<drop::FileDropSettings>()
+            .register_type::<touch::TouchGestureBindings>()
+            .init_resource::<touch::TouchGestureBindings>()
+            .register_type::<raw_motion::RawMouseMotion>()
+            .init_resource::<raw_motion::RawMouseMotion>()
+            .add_systems(
+                PreUpdate,
+                raw_motion::forward_raw_motion.before(InputSystem),
+            )
+            .add_systems(
+                PreUpdate,
+                touch::recognize_gestures
+                    .after(InputSystem)
+                    .after(touch_screen_input_system),
+            )
+            .add_systems(
+                PreUpdate,
+                (
+                    map::restore_bindings.run_if(resource_added::<map::InputMap>),
+                    map::update_input_map,
+                )
+                    .chain()
+                    .after(InputSystem)
+                    .run_if(resource_exists::<map::InputMap>),
+            );
+    }
+}
+
+/// Create the window which stands in for an item of the given logical size
+pub fn item_window(logical_size: Vec2, scale_factor: f32) -> Window {
+    let mut window = Window {
+        title: "BevyQuickItem".into(),
+        resolution: WindowResolution::default(),
+        ..default()
+    };
+    resize_item_window(&mut window, logical_size, scale_factor);
+    window
+}
+
+/// Keep the window in step with the geometry of its item
+pub fn resize_item_window(window: &mut Window, logical_size: Vec2, scale_factor: f32) {
+    let physical = (logical_size * scale_factor).round().max(Vec2::ONE);
+    if window.resolution.physical_width() != physical.x as u32
+        || window.resolution.physical_height() != physical.y as u32
+    {
+        window
+            .resolution
+            .set_physical_resolution(physical.x as u32, physical.y as u32);
+    }
+    if window.resolution.scale_factor() != scale_factor {
+        window.resolution.set_scale_factor(scale_factor);
+    }
+}
+
+/// Spawn the window for an item on its target entity
+pub fn attach_item_window(world: &mut World, entity: Entity, window: Window) {
+    let primary = world
+        .query_filtered::<(), With<PrimaryWindow>>()
+        .iter(world)
+        .next()
+        .is_none();
+
+    let mut entity = world.entity_mut(entity);
+    entity.insert(window);
+    if primary {
+        entity.insert(PrimaryWindow);
+    }
+}
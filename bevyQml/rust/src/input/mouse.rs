@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Mouse buttons, cursor movement and the wheel.
+//!
+//! Positions are in logical pixels relative to the top left corner of the
+//! item, which is what Qt hands to the item and what Bevy expects from
+//! [CursorMoved]. Every move is also reported as [MouseMotion], which is
+//! all that is reported while the pointer is locked to the item, unless
+//! [crate::input::raw_motion] reports the raw motion of the mouse instead.
+
+use bevy::{
+    input::{
+        mouse::{MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel},
+        ButtonState,
+    },
+    prelude::*,
+    window::{CursorEntered, CursorIcon, CursorLeft},
+};
+
+use super::raw_motion;
+
+/// Qt reports one notch of a regular mouse wheel as 120 eighths of a degree
+const ANGLE_DELTA_PER_LINE: f32 = 120.0;
+
+/// Convert a single Qt::MouseButton flag into a Bevy mouse button
+pub fn mouse_button_from_qt(button: i32) -> Option<MouseButton> {
+    match button {
+        0x01 => Some(MouseButton::Left),
+        0x02 => Some(MouseButton::Right),
+        0x04 => Some(MouseButton::Middle),
+        0x08 => Some(MouseButton::Back),
+        0x10 => Some(MouseButton::Forward),
+        0 => None,
+        // Qt::ExtraButton4 and above, numbered from the lowest bit
+        other => Some(MouseButton::Other(other.trailing_zeros() as u16)),
+    }
+}
+
+/// A mouse button was pressed or released over the window
+pub fn button(world: &mut World, window: Entity, button: MouseButton, state: ButtonState) {
+    world.send_event(MouseButtonInput {
+        button,
+        state,
+        window,
+    });
+}
+
+/// The cursor moved to the given position within the window
+pub fn cursor_moved(world: &mut World, window: Entity, position: Vec2) {
+    let Some(mut bevy_window) = world.get_mut::<Window>(window) else {
+        return;
+    };
+    if bevy_window.cursor_position() == Some(position) {
+        return;
+    }
+    let delta = bevy_window
+        .cursor_position()
+        .map(|previous| position - previous);
+    bevy_window.set_cursor_position(Some(position));
+
+    world.send_event(CursorMoved {
+        window,
+        position,
+        delta,
+    });
+    if let Some(delta) = delta {
+        motion(world, delta);
+    }
+}
+
+/// The mouse moved by the given delta, without the cursor moving while the
+/// pointer is locked
+pub fn motion(world: &mut World, delta: Vec2) {
+    if raw_motion::replaces_cursor(world) {
+        return;
+    }
+    world.send_event(MouseMotion { delta });
+}
+
+/// The cursor entered the window
+pub fn cursor_entered(world: &mut World, window: Entity) {
+    world.send_event(CursorEntered { window });
+}
+
+/// The cursor left the window
+pub fn cursor_left(world: &mut World, window: Entity) {
+    if let Some(mut bevy_window) = world.get_mut::<Window>(window) {
+        bevy_window.set_cursor_position(None);
+    }
+    world.send_event(CursorLeft { window });
+}
+
+/// The wheel was turned, as reported by QWheelEvent
+///
+/// High resolution devices such as touchpads report a pixel delta which is
+/// preferred, otherwise the angle delta is converted into lines.
+pub fn wheel(world: &mut World, window: Entity, angle_delta: Vec2, pixel_delta: Vec2) {
+    let (unit, delta) = if pixel_delta != Vec2::ZERO {
+        (MouseScrollUnit::Pixel, pixel_delta)
+    } else {
+        (MouseScrollUnit::Line, angle_delta / ANGLE_DELTA_PER_LINE)
+    };
+
+    world.send_event(MouseWheel {
+        unit,
+        x: delta.x,
+        y: delta.y,
+        window,
+    });
+}
+
+/// The Qt::CursorShape closest to a Bevy cursor icon
+pub fn qt_cursor_shape(icon: CursorIcon) -> i32 {
+    match icon {
+        CursorIcon::Crosshair => 2,
+        CursorIcon::Wait => 3,
+        CursorIcon::Text | CursorIcon::VerticalText => 4,
+        CursorIcon::NsResize | CursorIcon::NResize | CursorIcon::SResize => 5,
+        CursorIcon::EwResize | CursorIcon::EResize | CursorIcon::WResize => 6,
+        CursorIcon::NeswResize | CursorIcon::NeResize | CursorIcon::SwResize => 7,
+        CursorIcon::NwseResize | CursorIcon::NwResize | CursorIcon::SeResize => 8,
+        CursorIcon::Move | CursorIcon::AllScroll => 9,
+        CursorIcon::RowResize => 11,
+        CursorIcon::ColResize => 12,
+        CursorIcon::Pointer => 13,
+        CursorIcon::NotAllowed | CursorIcon::NoDrop => 14,
+        CursorIcon::Help => 15,
+        CursorIcon::Progress => 16,
+        CursorIcon::Grab => 17,
+        CursorIcon::Grabbing => 18,
+        CursorIcon::Copy => 19,
+        CursorIcon::Alias => 21,
+        // Qt::ArrowCursor
+        _ => 0,
+    }
+}
+
+/// The Bevy cursor icon closest to a Qt::CursorShape
+pub fn cursor_icon_from_qt(shape: i32) -> CursorIcon {
+    match shape {
+        2 => CursorIcon::Crosshair,
+        3 => CursorIcon::Wait,
+        4 => CursorIcon::Text,
+        5 => CursorIcon::NsResize,
+        6 => CursorIcon::EwResize,
+        7 => CursorIcon::NeswResize,
+        8 => CursorIcon::NwseResize,
+        9 | 20 => CursorIcon::Move,
+        11 => CursorIcon::RowResize,
+        12 => CursorIcon::ColResize,
+        13 => CursorIcon::Pointer,
+        14 => CursorIcon::NotAllowed,
+        15 => CursorIcon::Help,
+        16 => CursorIcon::Progress,
+        17 => CursorIcon::Grab,
+        18 => CursorIcon::Grabbing,
+        19 => CursorIcon::Copy,
+        21 => CursorIcon::Alias,
+        // Qt::ArrowCursor, Qt::UpArrowCursor and the blank or bitmap cursors
+        _ => CursorIcon::Default,
+    }
+}
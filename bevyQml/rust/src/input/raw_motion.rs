@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Unaccelerated mouse motion read from the raw input of the platform.
+//!
+//! Qt only reports where the cursor is, after the pointer acceleration of
+//! the platform was applied, which makes FPS-style cameras feel off. Where
+//! the platform has raw input, currently on Windows, the motion of the mouse
+//! is read from it and reported as [MouseMotion] instead of the movement of
+//! the cursor, as long as [RawMouseMotion::enabled] is set. Elsewhere
+//! [MouseMotion] keeps following the cursor, see [crate::input::mouse].
+//!
+//! Raw input is started by the first `BevyQuickItem`, and its motion goes to
+//! the first app updated afterwards, which is the main app.
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_raw_motion")]
+mod ffi {
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/rawmotion.h");
+
+        #[doc(hidden)]
+        #[rust_name = "install_raw_mouse_input"]
+        fn installRawMouseInput() -> bool;
+    }
+
+    #[namespace = "bevyqml"]
+    extern "Rust" {
+        /// The mouse moved, in the units of the device
+        #[cxx_name = "rawMouseMotion"]
+        fn raw_mouse_motion(x: f64, y: f64);
+    }
+}
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use bevy::{input::mouse::MouseMotion, prelude::*};
+
+use crate::runtime;
+
+/// Whether raw input was started
+static AVAILABLE: AtomicBool = AtomicBool::new(false);
+/// Whether raw motion is wanted, as of the last update
+static ENABLED: AtomicBool = AtomicBool::new(true);
+/// The motion since the last update
+static DELTA: Mutex<Vec2> = Mutex::new(Vec2::ZERO);
+
+/// Whether mouse motion is read from raw input where the platform has it
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct RawMouseMotion {
+    pub enabled: bool,
+}
+
+impl Default for RawMouseMotion {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Start reading raw input, which needs the QCoreApplication
+pub fn install() {
+    if ffi::install_raw_mouse_input() {
+        AVAILABLE.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Whether the platform reports raw mouse motion
+pub fn is_available() -> bool {
+    AVAILABLE.load(Ordering::Relaxed)
+}
+
+/// Whether [MouseMotion] comes from raw input rather than the cursor
+pub fn replaces_cursor(world: &World) -> bool {
+    is_available()
+        && world
+            .get_resource::<RawMouseMotion>()
+            .is_some_and(|raw| raw.enabled)
+}
+
+fn raw_mouse_motion(x: f64, y: f64) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    *DELTA.lock().unwrap() += Vec2::new(x as f32, y as f32);
+    runtime::request_update();
+}
+
+/// Report the raw motion since the last update
+pub(crate) fn forward_raw_motion(raw: Res<RawMouseMotion>, mut motion: EventWriter<MouseMotion>) {
+    ENABLED.store(raw.enabled, Ordering::Relaxed);
+    let delta = std::mem::take(&mut *DELTA.lock().unwrap());
+    if raw.enabled && delta != Vec2::ZERO {
+        motion.send(MouseMotion { delta });
+    }
+}
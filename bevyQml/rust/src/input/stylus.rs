@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Pens and erasers of graphics tablets.
+//!
+//! Qt reports the stylus with QTabletEvent, which is translated into
+//! [StylusInput] events carrying the pressure, tilt and rotation. Qt still
+//! turns the stylus into mouse events as well, so picking and cameras keep
+//! working with a pen while drawing and sculpting tools read [StylusInput]:
+//!
+//! ```ignore
+//! fn paint(mut stylus: EventReader<StylusInput>, mut canvas: ResMut<Canvas>) {
+//!     for input in stylus.read() {
+//!         if input.phase == StylusPhase::Moved && input.pressure > 0.0 {
+//!             canvas.dab(input.position, input.pressure, input.tool == StylusTool::Eraser);
+//!         }
+//!     }
+//! }
+//! ```
+
+use bevy::prelude::*;
+
+/// QEvent::TabletMove
+const TABLET_MOVE: i32 = 87;
+/// QEvent::TabletPress
+const TABLET_PRESS: i32 = 92;
+/// QEvent::TabletRelease
+const TABLET_RELEASE: i32 = 93;
+
+/// What happened to the stylus
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub enum StylusPhase {
+    /// The stylus came close to the tablet over the window
+    Entered,
+    /// The stylus touched the tablet
+    Pressed,
+    /// The stylus moved, touching the tablet or hovering above it
+    Moved,
+    /// The stylus was lifted off the tablet
+    Released,
+    /// The stylus was taken away from the tablet
+    Left,
+}
+
+/// The end of the stylus in use
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum StylusTool {
+    #[default]
+    Unknown,
+    Pen,
+    Eraser,
+    /// A puck, which is moved over the tablet like a mouse
+    Cursor,
+}
+
+/// A stylus on a graphics tablet, over the window standing in for an item
+#[derive(Event, Clone, Debug, PartialEq, Reflect)]
+pub struct StylusInput {
+    pub window: Entity,
+    pub phase: StylusPhase,
+    /// Where the stylus is in logical pixels, like [CursorMoved::position],
+    /// the last known position when it [StylusPhase::Left]
+    pub position: Vec2,
+    /// How hard the stylus is pressed, from 0 to 1
+    pub pressure: f32,
+    /// The angle between the stylus and the perpendicular of the tablet
+    /// along x and y, in degrees from -60 to 60
+    pub tilt: Vec2,
+    /// The rotation of the stylus around its axis, in degrees
+    pub rotation: f32,
+    pub tool: StylusTool,
+    /// Tells several styluses on the same tablet apart
+    pub tool_id: i64,
+}
+
+impl StylusPhase {
+    /// The phase of a QTabletEvent of the given QEvent::Type
+    pub fn from_qt(event_type: i32) -> Option<Self> {
+        match event_type {
+            TABLET_MOVE => Some(Self::Moved),
+            TABLET_PRESS => Some(Self::Pressed),
+            TABLET_RELEASE => Some(Self::Released),
+            _ => None,
+        }
+    }
+}
+
+impl StylusTool {
+    /// The tool as numbered by bevyqml/tablet.h
+    pub fn from_qt(tool: i32) -> Self {
+        match tool {
+            1 => Self::Pen,
+            2 => Self::Eraser,
+            3 => Self::Cursor,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// The stylus did something over the window
+pub fn stylus(world: &mut World, input: StylusInput) {
+    world.send_event(input);
+}
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Touch points and the gestures recognised from them.
+//!
+//! Every touch point is forwarded as a [TouchInput]. On top of that two
+//! finger pinches and rotations are reported as [PinchGesture] and
+//! [RotationGesture], and all drags, pinches and rotations are turned into
+//! [CameraGesture]s according to the [TouchGestureBindings], so camera
+//! controllers do not have to interpret raw touches.
+
+use bevy::{
+    input::{
+        gestures::{PinchGesture, RotationGesture},
+        touch::{ForceTouch, TouchPhase},
+    },
+    prelude::*,
+};
+
+/// QEventPoint::State
+const POINT_PRESSED: i32 = 0x01;
+const POINT_UPDATED: i32 = 0x02;
+const POINT_RELEASED: i32 = 0x08;
+
+/// A camera action a touch gesture can be bound to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum CameraAction {
+    /// Ignore the gesture
+    #[default]
+    None,
+    /// Move towards or away from the focus, `delta.x` is the relative change in scale
+    Zoom,
+    /// Rotate around the focus, `delta` is in logical pixels
+    Orbit,
+    /// Move parallel to the view, `delta` is in logical pixels
+    Pan,
+    /// Rotate around the view direction, `delta.x` is in radians
+    Roll,
+}
+
+/// Which [CameraAction] each touch gesture triggers
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct TouchGestureBindings {
+    pub one_finger_drag: CameraAction,
+    pub two_finger_drag: CameraAction,
+    pub pinch: CameraAction,
+    pub rotate: CameraAction,
+}
+
+impl Default for TouchGestureBindings {
+    fn default() -> Self {
+        Self {
+            one_finger_drag: CameraAction::Orbit,
+            two_finger_drag: CameraAction::Pan,
+            pinch: CameraAction::Zoom,
+            rotate: CameraAction::Roll,
+        }
+    }
+}
+
+/// A touch gesture that has been mapped onto a [CameraAction]
+#[derive(Event, Clone, Debug)]
+pub struct CameraGesture {
+    /// The window the touches happened in
+    pub window: Entity,
+    pub action: CameraAction,
+    pub delta: Vec2,
+}
+
+/// A touch point as reported by QTouchEvent
+#[derive(Clone, Debug)]
+pub struct QtTouchPoint {
+    /// QEventPoint::id()
+    pub id: i32,
+    /// The position in logical pixels relative to the item
+    pub position: Vec2,
+    /// QEventPoint::state()
+    pub state: i32,
+    /// QEventPoint::pressure(), between 0 and 1
+    pub pressure: f64,
+}
+
+impl QtTouchPoint {
+    fn phase(&self) -> Option<TouchPhase> {
+        match self.state {
+            POINT_PRESSED => Some(TouchPhase::Started),
+            POINT_UPDATED => Some(TouchPhase::Moved),
+            POINT_RELEASED => Some(TouchPhase::Ended),
+            // Stationary points have not changed
+            _ => None,
+        }
+    }
+}
+
+/// The touch points of the window changed
+pub fn touch(world: &mut World, window: Entity, points: &[QtTouchPoint]) {
+    for point in points {
+        let Some(phase) = point.phase() else {
+            continue;
+        };
+        world.send_event(TouchInput {
+            phase,
+            position: point.position,
+            window,
+            force: Some(ForceTouch::Normalized(point.pressure)),
+            id: point.id as u32 as u64,
+        });
+    }
+}
+
+/// Another item took over the touches, so every active touch is cancelled
+pub fn cancel(world: &mut World, window: Entity) {
+    let touches: Vec<_> = world
+        .resource::<Touches>()
+        .iter()
+        .map(|touch| (touch.id(), touch.position()))
+        .collect();
+
+    for (id, position) in touches {
+        world.send_event(TouchInput {
+            phase: TouchPhase::Canceled,
+            position,
+            window,
+            force: None,
+            id,
+        });
+    }
+}
+
+/// Turn the movement of the active touches into gestures
+pub(crate) fn recognize_gestures(
+    touches: Res<Touches>,
+    mut touch_events: EventReader<TouchInput>,
+    bindings: Res<TouchGestureBindings>,
+    mut window: Local<Option<Entity>>,
+    mut pinches: EventWriter<PinchGesture>,
+    mut rotations: EventWriter<RotationGesture>,
+    mut gestures: EventWriter<CameraGesture>,
+) {
+    if let Some(event) = touch_events.read().last() {
+        *window = Some(event.window);
+    }
+    let Some(window) = *window else {
+        return;
+    };
+
+    let mut send = |action: CameraAction, delta: Vec2| {
+        if action != CameraAction::None && delta != Vec2::ZERO {
+            gestures.send(CameraGesture {
+                window,
+                action,
+                delta,
+            });
+        }
+    };
+
+    let active: Vec<_> = touches.iter().collect();
+    match active.as_slice() {
+        [touch] => send(bindings.one_finger_drag, touch.delta()),
+        [first, second] => {
+            let previous = second.previous_position() - first.previous_position();
+            let current = second.position() - first.position();
+
+            let previous_distance = previous.length();
+            if previous_distance > f32::EPSILON {
+                let scale = current.length() / previous_distance - 1.0;
+                if scale != 0.0 {
+                    pinches.send(PinchGesture(scale));
+                }
+                send(bindings.pinch, Vec2::new(scale, 0.0));
+            }
+
+            let angle = previous.angle_between(current);
+            if angle.is_finite() && angle != 0.0 {
+                rotations.send(RotationGesture(angle));
+                send(bindings.rotate, Vec2::new(angle, 0.0));
+            }
+
+            send(
+                bindings.two_finger_drag,
+                (first.delta() + second.delta()) / 2.0,
+            );
+        }
+        _ => {}
+    }
+}
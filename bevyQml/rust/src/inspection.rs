@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! A camera framing a single entity, for hover previews and other
+//! picture-in-picture views, set up by the `BevyInspectionCamera` QML
+//! element.
+//!
+//! The camera renders into an offscreen image published with a
+//! [QmlImageTarget], and follows the entity so that its bounds and those of
+//! its descendants fill the image. It stops rendering while there is nothing
+//! to inspect.
+//!
+//! [QmlImageTarget]: crate::render::QmlImageTarget
+
+use bevy::{
+    prelude::*,
+    render::{camera::Projection, primitives::Aabb},
+    transform::TransformSystem,
+};
+
+use crate::render::QmlImageTarget;
+
+/// Frames the entity with the camera on the same entity
+#[derive(Component, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub struct QmlInspectionCamera {
+    /// The entity to frame, the camera is inactive without one
+    pub target: Option<Entity>,
+    /// Where the camera looks at the entity from, relative to its center
+    pub direction: Vec3,
+    /// How much room is left around the bounds of the entity, one for none
+    pub margin: f32,
+}
+
+impl Default for QmlInspectionCamera {
+    fn default() -> Self {
+        Self {
+            target: None,
+            direction: Vec3::new(1.0, 0.8, 1.0),
+            margin: 1.2,
+        }
+    }
+}
+
+pub struct QmlInspectionPlugin;
+
+impl Plugin for QmlInspectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<QmlInspectionCamera>().add_systems(
+            PostUpdate,
+            frame_inspected.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// An inactive camera for inspecting entities, rendering into the image of
+/// the target
+pub fn inspection_camera_bundle(
+    inspection: QmlInspectionCamera,
+    target: QmlImageTarget,
+) -> impl Bundle {
+    (
+        Camera3dBundle {
+            // Render before the cameras of the items, which may show the
+            // preview as well
+            camera: Camera {
+                order: -1,
+                is_active: false,
+                ..default()
+            },
+            ..default()
+        },
+        inspection,
+        target,
+    )
+}
+
+/// The bounding sphere of the entity and its descendants in world space, as
+/// its center and radius
+///
+/// Entities without an [Aabb] anywhere in their hierarchy get a sphere of
+/// radius one around their origin.
+fn world_bounds(
+    entity: Entity,
+    bounds: &Query<(&GlobalTransform, Option<&Aabb>, Option<&Children>)>,
+) -> Option<(Vec3, f32)> {
+    let (transform, _, _) = bounds.get(entity).ok()?;
+    let origin = transform.translation();
+
+    let mut min = Vec3::INFINITY;
+    let mut max = Vec3::NEG_INFINITY;
+    let mut pending = vec![entity];
+    while let Some(entity) = pending.pop() {
+        let Ok((transform, aabb, children)) = bounds.get(entity) else {
+            continue;
+        };
+        if let Some(aabb) = aabb {
+            let center = Vec3::from(aabb.center);
+            let half_extents = Vec3::from(aabb.half_extents);
+            for corner in 0..8 {
+                let sign = Vec3::new(
+                    if corner & 1 == 0 { -1.0 } else { 1.0 },
+                    if corner & 2 == 0 { -1.0 } else { 1.0 },
+                    if corner & 4 == 0 { -1.0 } else { 1.0 },
+                );
+                let point = transform.transform_point(center + half_extents * sign);
+                min = min.min(point);
+                max = max.max(point);
+            }
+        }
+        if let Some(children) = children {
+            pending.extend(children.iter().copied());
+        }
+    }
+
+    if min.cmpgt(max).any() {
+        return Some((origin, 1.0));
+    }
+    let center = (min + max) / 2.0;
+    Some((center, ((max - min).length() / 2.0).max(f32::EPSILON)))
+}
+
+fn frame_inspected(
+    mut cameras: Query<(
+        &QmlInspectionCamera,
+        &mut Camera,
+        &mut Transform,
+        &mut Projection,
+    )>,
+    bounds: Query<(&GlobalTransform, Option<&Aabb>, Option<&Children>)>,
+) {
+    for (inspection, mut camera, mut transform, mut projection) in &mut cameras {
+        let framed = inspection
+            .target
+            .and_then(|target| world_bounds(target, &bounds));
+        if camera.is_active != framed.is_some() {
+            camera.is_active = framed.is_some();
+        }
+        let Some((center, radius)) = framed else {
+            continue;
+        };
+
+        let fov = match &*projection {
+            Projection::Perspective(perspective) => perspective.fov,
+            Projection::Orthographic(_) => std::f32::consts::FRAC_PI_4,
+        };
+        // Fit the sphere into the narrower of the two fields of view
+        let aspect_ratio = camera
+            .logical_viewport_size()
+            .map_or(1.0, |size| size.x / size.y.max(1.0));
+        let vertical = fov / 2.0;
+        let horizontal = (vertical.tan() * aspect_ratio).atan();
+        let half_fov = vertical.min(horizontal);
+        let distance = radius * inspection.margin.max(1.0) / half_fov.sin().max(0.01);
+        let direction = inspection.direction.try_normalize().unwrap_or(Vec3::Z);
+
+        let wanted =
+            Transform::from_translation(center + direction * distance).looking_at(center, Vec3::Y);
+        if *transform != wanted {
+            *transform = wanted;
+        }
+        if let Projection::Perspective(perspective) = &mut *projection {
+            let near = (distance - radius * 2.0).max(0.01);
+            let far = distance + radius * 2.0;
+            if perspective.near != near || perspective.far != far {
+                perspective.near = near;
+                perspective.far = far;
+            }
+        }
+    }
+}
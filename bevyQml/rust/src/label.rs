@@ -0,0 +1,140 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! How entities are shown in outliners and pickers built on the models of
+//! this crate, such as `EntityTreeModel` and `QueryListModel`.
+//!
+//! The models fill the standard display, decoration and tool tip roles of
+//! every row from its [EntityLabel], so a TreeView or ComboBox shows
+//! something sensible without a custom delegate. By default the label is
+//! the [Name] of the entity, or its id if it has none. A [QmlLabel] gives an
+//! entity a display name, an icon and a tool tip of its own:
+//!
+//! ```ignore
+//! commands.spawn((
+//!     Name::new("enemy_03"),
+//!     QmlLabel {
+//!         display_name: String::from("Goblin"),
+//!         icon: String::from("qrc:/icons/goblin.svg"),
+//!         tooltip: String::from("Patrols the north gate"),
+//!     },
+//! ));
+//! ```
+
+use bevy::{
+    ecs::component::{ComponentTicks, Tick},
+    prelude::*,
+};
+use cxx_qt_lib::{QByteArray, QHash, QHashPair_i32_QByteArray, QString, QUrl, QVariant, QVector};
+
+/// Qt::DisplayRole, the display name
+pub const DISPLAY_ROLE: i32 = 0;
+
+/// Qt::DecorationRole, the URL of the icon
+pub const DECORATION_ROLE: i32 = 1;
+
+/// Qt::ToolTipRole
+pub const TOOLTIP_ROLE: i32 = 3;
+
+/// What the models show for an entity, next to its [Name]
+///
+/// Empty fields are left to the defaults of [EntityLabel].
+#[derive(Component, Clone, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub struct QmlLabel {
+    /// The text of the display role, in place of the [Name]
+    pub display_name: String,
+    /// The URL of an image for the decoration role, e.g. `qrc:/icons/door.svg`
+    pub icon: String,
+    /// The text of the tool tip role
+    pub tooltip: String,
+}
+
+/// The display name, icon and tool tip of an entity as the models show them
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EntityLabel {
+    pub display_name: String,
+    pub icon: String,
+    pub tooltip: String,
+}
+
+impl EntityLabel {
+    /// The label from the [QmlLabel] of the entity, falling back to its
+    /// [Name] and then its id for the display name
+    pub fn new(entity: Entity, name: Option<&Name>, label: Option<&QmlLabel>) -> Self {
+        let label = label.cloned().unwrap_or_default();
+        let display_name = if !label.display_name.is_empty() {
+            label.display_name
+        } else if let Some(name) = name {
+            name.as_str().to_owned()
+        } else {
+            format!("{entity}")
+        };
+        Self {
+            display_name,
+            icon: label.icon,
+            tooltip: label.tooltip,
+        }
+    }
+
+    /// The label of an entity of the world
+    pub fn of(world: &World, entity: Entity) -> Self {
+        let Some(entity_ref) = world.get_entity(entity) else {
+            return Self::new(entity, None, None);
+        };
+        Self::new(
+            entity,
+            entity_ref.get::<Name>(),
+            entity_ref.get::<QmlLabel>(),
+        )
+    }
+
+    /// Whether the [Name] or [QmlLabel] of the entity changed after `since`
+    pub fn changed_since(world: &World, entity: Entity, since: Tick) -> bool {
+        let Some(entity_ref) = world.get_entity(entity) else {
+            return false;
+        };
+        let this_run = world.read_change_tick();
+        let changed = |ticks: Option<ComponentTicks>| {
+            ticks.is_some_and(|ticks| ticks.is_changed(since, this_run))
+        };
+        changed(entity_ref.get_change_ticks::<Name>())
+            || changed(entity_ref.get_change_ticks::<QmlLabel>())
+    }
+}
+
+/// The value of a standard role for the label, invalid for other roles and
+/// for an empty icon or tool tip
+pub fn role_data(label: &EntityLabel, role: i32) -> QVariant {
+    match role {
+        DISPLAY_ROLE => QVariant::from(&QString::from(&label.display_name)),
+        DECORATION_ROLE if !label.icon.is_empty() => {
+            QVariant::from(&QUrl::from(label.icon.as_str()))
+        }
+        TOOLTIP_ROLE if !label.tooltip.is_empty() => QVariant::from(&QString::from(&label.tooltip)),
+        _ => QVariant::default(),
+    }
+}
+
+/// Add the standard roles to the role names of a model
+pub fn insert_role_names(roles: &mut QHash<QHashPair_i32_QByteArray>) {
+    roles.insert(DISPLAY_ROLE, QByteArray::from("display"));
+    roles.insert(DECORATION_ROLE, QByteArray::from("decoration"));
+    roles.insert(TOOLTIP_ROLE, QByteArray::from("toolTip"));
+}
+
+/// The standard roles, for dataChanged after a label changed
+pub fn roles() -> QVector<i32> {
+    let mut roles = QVector::<i32>::default();
+    roles.append(DISPLAY_ROLE);
+    roles.append(DECORATION_ROLE);
+    roles.append(TOOLTIP_ROLE);
+    roles
+}
+
+pub struct QmlLabelPlugin;
+
+impl Plugin for QmlLabelPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<QmlLabel>();
+    }
+}
@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Named groups of entities which QML shows and hides together, see
+//! [crate::cxxqt_bevy_layer_group] for the QML side.
+//!
+//! Entities join groups with a [QmlLayers] component. While a group is
+//! hidden its entities are [Visibility::Hidden], and they get their own
+//! visibility back once it is shown again. A group can also stand for a
+//! render layer, which is then taken from the [RenderLayers] of every camera
+//! while the group is hidden, for scenes already sorted into render layers.
+
+use bevy::{
+    prelude::*,
+    render::view::{Layer, RenderLayers, VisibilitySystems},
+    utils::{HashMap, HashSet},
+};
+
+/// The groups an entity belongs to, it is hidden while any of them is
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct QmlLayers {
+    pub names: Vec<String>,
+}
+
+/// Which groups are hidden, and the render layers groups stand for
+#[derive(Resource, Clone, Debug, Default)]
+pub struct QmlLayerVisibility {
+    hidden: HashSet<String>,
+    render_layers: HashMap<String, Layer>,
+}
+
+impl QmlLayerVisibility {
+    pub fn is_visible(&self, name: &str) -> bool {
+        !self.hidden.contains(name)
+    }
+
+    pub fn set_visible(&mut self, name: &str, visible: bool) {
+        if visible {
+            self.hidden.remove(name);
+        } else {
+            self.hidden.insert(name.to_owned());
+        }
+    }
+
+    /// The render layer the group stands for
+    pub fn render_layer(&self, name: &str) -> Option<Layer> {
+        self.render_layers.get(name).copied()
+    }
+
+    pub fn set_render_layer(&mut self, name: &str, layer: Option<Layer>) {
+        match layer {
+            Some(layer) => self.render_layers.insert(name.to_owned(), layer),
+            None => self.render_layers.remove(name),
+        };
+    }
+
+    /// Whether any of the groups is hidden
+    fn hides(&self, names: &[String]) -> bool {
+        names.iter().any(|name| self.hidden.contains(name))
+    }
+
+    /// The render layers of the hidden groups
+    fn hidden_render_layers(&self) -> RenderLayers {
+        self.render_layers
+            .iter()
+            .filter(|(name, _)| self.hidden.contains(*name))
+            .fold(RenderLayers::none(), |layers, (_, layer)| {
+                layers.with(*layer)
+            })
+    }
+}
+
+/// The visibility of an entity before a group hid it
+#[derive(Component)]
+struct HiddenByLayer(Visibility);
+
+/// The render layers hidden groups took from a camera
+#[derive(Component)]
+struct RemovedRenderLayers(RenderLayers);
+
+pub struct QmlLayersPlugin;
+
+impl Plugin for QmlLayersPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<QmlLayers>()
+            .init_resource::<QmlLayerVisibility>()
+            .add_systems(
+                PostUpdate,
+                (hide_entities, hide_render_layers).before(VisibilitySystems::VisibilityPropagate),
+            );
+    }
+}
+
+/// Put an entity into a group, returns false if it was in there already
+pub fn add_to_layer(world: &mut World, entity: Entity, name: &str) -> bool {
+    let Some(mut entity) = world.get_entity_mut(entity) else {
+        return false;
+    };
+    match entity.get_mut::<QmlLayers>() {
+        Some(layers) if layers.names.iter().any(|other| other == name) => false,
+        Some(mut layers) => {
+            layers.names.push(name.to_owned());
+            true
+        }
+        None => {
+            entity.insert(QmlLayers {
+                names: vec![name.to_owned()],
+            });
+            true
+        }
+    }
+}
+
+/// Take an entity out of a group, returns false if it was not in there
+pub fn remove_from_layer(world: &mut World, entity: Entity, name: &str) -> bool {
+    let Some(mut layers) = world.get_mut::<QmlLayers>(entity) else {
+        return false;
+    };
+    let Some(index) = layers.names.iter().position(|other| other == name) else {
+        return false;
+    };
+    layers.names.remove(index);
+    true
+}
+
+/// How many entities are in a group
+pub fn layer_count(world: &mut World, name: &str) -> usize {
+    world
+        .query::<&QmlLayers>()
+        .iter(world)
+        .filter(|layers| layers.names.iter().any(|other| other == name))
+        .count()
+}
+
+fn hide_entities(
+    mut commands: Commands,
+    visibility: Res<QmlLayerVisibility>,
+    mut entities: Query<(Entity, &QmlLayers, &mut Visibility, Option<&HiddenByLayer>)>,
+    mut left: Query<(Entity, &HiddenByLayer, &mut Visibility), Without<QmlLayers>>,
+) {
+    for (entity, layers, mut current, hidden_by) in &mut entities {
+        match (visibility.hides(&layers.names), hidden_by) {
+            (true, None) => {
+                commands.entity(entity).insert(HiddenByLayer(*current));
+                *current = Visibility::Hidden;
+            }
+            (false, Some(HiddenByLayer(previous))) => {
+                *current = *previous;
+                commands.entity(entity).remove::<HiddenByLayer>();
+            }
+            _ => {}
+        }
+    }
+
+    // Entities which left their groups while hidden
+    for (entity, HiddenByLayer(previous), mut current) in &mut left {
+        *current = *previous;
+        commands.entity(entity).remove::<HiddenByLayer>();
+    }
+}
+
+fn hide_render_layers(
+    mut commands: Commands,
+    visibility: Res<QmlLayerVisibility>,
+    cameras: Query<(Entity, Option<&RenderLayers>, Option<&RemovedRenderLayers>), With<Camera>>,
+) {
+    let hidden = visibility.hidden_render_layers();
+    for (camera, layers, removed) in &cameras {
+        let current = layers.cloned().unwrap_or_default();
+        let mut layers = current.clone();
+        let mut still_removed = RenderLayers::none();
+
+        // Give back the layers of groups shown again
+        for layer in removed.iter().flat_map(|removed| removed.0.iter()) {
+            if hidden.intersects(&RenderLayers::layer(layer)) {
+                still_removed = still_removed.with(layer);
+            } else {
+                layers = layers.with(layer);
+            }
+        }
+        for layer in hidden.iter() {
+            if layers.intersects(&RenderLayers::layer(layer)) {
+                layers = layers.without(layer);
+                still_removed = still_removed.with(layer);
+            }
+        }
+
+        if layers != current {
+            commands.entity(camera).insert(layers);
+        }
+        let was_removed = removed.map_or_else(RenderLayers::none, |removed| removed.0.clone());
+        if still_removed != was_removed {
+            if still_removed == RenderLayers::none() {
+                commands.entity(camera).remove::<RemovedRenderLayers>();
+            } else {
+                commands
+                    .entity(camera)
+                    .insert(RemovedRenderLayers(still_removed));
+            }
+        }
+    }
+}
@@ -5,7 +5,112 @@
 //
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+// Lets the code generated by bevy_qml_derive refer to this crate by name,
+// also from within the crate itself.
+extern crate self as qml_minimal;
+
 // ANCHOR: book_mod_statement
-pub mod cxxqt_object;
+pub mod cxxqt_bevy_action_model;
+pub mod cxxqt_bevy_animation_player;
 pub mod cxxqt_bevy_app;
+pub mod cxxqt_bevy_asset_load;
+pub mod cxxqt_bevy_assets;
+pub mod cxxqt_bevy_benchmark;
+pub mod cxxqt_bevy_camera;
+pub mod cxxqt_bevy_camera_stats_model;
+pub mod cxxqt_bevy_clipboard;
+pub mod cxxqt_bevy_commands;
+pub mod cxxqt_bevy_component;
+pub mod cxxqt_bevy_diagnostics;
+pub mod cxxqt_bevy_diagnostics_history;
+pub mod cxxqt_bevy_document;
+pub mod cxxqt_bevy_entity;
+pub mod cxxqt_bevy_entity_tree_model;
+pub mod cxxqt_bevy_event_listener;
+pub mod cxxqt_bevy_gamepad_model;
+pub mod cxxqt_bevy_gizmos;
+pub mod cxxqt_bevy_gltf_model;
+#[cfg(feature = "testing")]
+pub mod cxxqt_bevy_golden_image;
+pub mod cxxqt_bevy_graphics_adapter;
+pub mod cxxqt_bevy_input_map_model;
+pub mod cxxqt_bevy_inspection_camera;
+pub mod cxxqt_bevy_layer_group;
+pub mod cxxqt_bevy_light;
+pub mod cxxqt_bevy_light_bridge;
+pub mod cxxqt_bevy_log_model;
+#[cfg(feature = "hot_logic")]
+pub mod cxxqt_bevy_logic;
+pub mod cxxqt_bevy_material;
+pub mod cxxqt_bevy_memory_diagnostics;
+pub mod cxxqt_bevy_mesh;
+pub mod cxxqt_bevy_mini_view_camera;
+pub mod cxxqt_bevy_orbit_camera;
+pub mod cxxqt_bevy_query_model;
+pub mod cxxqt_bevy_quick_item;
+pub mod cxxqt_bevy_recording;
+pub mod cxxqt_bevy_render_settings;
+pub mod cxxqt_bevy_resource;
+pub mod cxxqt_bevy_selection;
+pub mod cxxqt_bevy_state;
+#[cfg(feature = "testing")]
+pub mod cxxqt_bevy_test_app;
+pub mod cxxqt_bevy_texture_source;
+pub mod cxxqt_bevy_time;
+pub mod cxxqt_bevy_trace;
+pub mod cxxqt_bevy_transform;
+pub mod cxxqt_bevy_transform_gizmo;
+pub mod cxxqt_bevy_undo_stack;
+pub mod cxxqt_bevy_windows;
+pub mod cxxqt_object;
 // ANCHOR_END: book_mod_statement
+
+pub mod action;
+pub mod animation;
+pub mod app;
+pub mod asset;
+pub mod batch;
+pub mod benchmark;
+pub mod bridge;
+pub mod camera;
+pub mod clipboard;
+pub mod commands;
+pub mod component;
+pub mod convert;
+pub mod declarative;
+pub mod diagnostics;
+pub mod gizmos;
+pub mod grid;
+pub mod image;
+pub mod input;
+pub mod inspection;
+pub mod label;
+pub mod layers;
+pub mod log;
+#[cfg(feature = "hot_logic")]
+pub mod logic;
+pub mod memory;
+pub mod mini_view;
+pub mod model;
+pub mod panic;
+pub mod picking;
+pub mod plugin;
+pub mod qml_texture;
+pub mod redraw;
+pub mod render;
+pub mod render_settings;
+pub mod runtime;
+pub mod selection;
+pub mod settings;
+pub mod snapshot;
+pub mod task;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod theme;
+pub mod time_control;
+pub mod trace;
+pub mod transform_gizmo;
+pub mod undo;
+pub mod variant;
+pub mod view_mode;
+pub mod window;
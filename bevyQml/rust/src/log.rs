@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Forwards the log output of Bevy to Qt logging, and keeps the latest
+//! records for the `LogModel` of QML debug consoles.
+//!
+//! [qt_log_layer] is the custom layer of the [LogPlugin] set up by
+//! [crate::plugin::bevy_qml_default_plugins]. Every event that passes the
+//! filter of the plugin is logged to the Qt category named after its target,
+//! e.g. `bevy.bevy_render.renderer` for `bevy_render::renderer`, so Qt
+//! message handlers see it and `QT_LOGGING_RULES` can filter it. Bevy keeps
+//! writing to stderr as well, `QT_LOGGING_RULES="bevy.*=false"` silences the
+//! Qt copy where both end up on the console.
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_log")]
+mod ffi {
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/log.h");
+
+        /// Log the message to the Qt category of the target
+        #[rust_name = "qt_log"]
+        fn qtLog(level: u8, target: &str, message: &str);
+    }
+}
+
+use std::{
+    collections::VecDeque,
+    fmt::{self, Write},
+    sync::{Mutex, OnceLock},
+};
+
+use bevy::{
+    log::{
+        tracing_subscriber::{layer::Context, Layer},
+        BoxedLayer,
+    },
+    prelude::*,
+    utils::tracing::{
+        field::{Field, Visit},
+        Event, Level, Subscriber,
+    },
+};
+
+use crate::trace::TraceLayer;
+
+/// How many records are kept for the log models
+pub const LOG_CAPACITY: usize = 1000;
+
+/// A log event, as kept for the log models
+#[derive(Clone, Debug)]
+pub struct LogRecord {
+    /// Counts up from 1 across all records
+    pub sequence: u64,
+    pub level: Level,
+    pub target: String,
+    /// The message followed by the other fields of the event
+    pub message: String,
+}
+
+#[derive(Default)]
+struct LogBuffer {
+    records: VecDeque<LogRecord>,
+    sequence: u64,
+}
+
+fn buffer() -> &'static Mutex<LogBuffer> {
+    static BUFFER: OnceLock<Mutex<LogBuffer>> = OnceLock::new();
+    BUFFER.get_or_init(Default::default)
+}
+
+/// The records kept which came after the one with the given sequence number,
+/// oldest first
+pub fn records_since(sequence: u64) -> Vec<LogRecord> {
+    let buffer = buffer().lock().unwrap();
+    let skip = buffer
+        .records
+        .iter()
+        .take_while(|record| record.sequence <= sequence)
+        .count();
+    buffer.records.iter().skip(skip).cloned().collect()
+}
+
+/// The custom layer for the [LogPlugin] which forwards events to Qt, and
+/// records spans while a [crate::trace] capture runs
+pub fn qt_log_layer(_app: &mut App) -> Option<BoxedLayer> {
+    Some(Box::new(QtLogLayer.and_then(TraceLayer)))
+}
+
+struct QtLogLayer;
+
+impl<S: Subscriber> Layer<S> for QtLogLayer {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let message = visitor.finish();
+
+        ffi::qt_log(level_value(*metadata.level()), metadata.target(), &message);
+
+        let mut buffer = buffer().lock().unwrap();
+        buffer.sequence += 1;
+        let record = LogRecord {
+            sequence: buffer.sequence,
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message,
+        };
+        if buffer.records.len() == LOG_CAPACITY {
+            buffer.records.pop_front();
+        }
+        buffer.records.push_back(record);
+    }
+}
+
+/// Must match the levels in bevyqml/log.h
+pub(crate) fn level_value(level: Level) -> u8 {
+    match level {
+        Level::TRACE => 0,
+        Level::DEBUG => 1,
+        Level::INFO => 2,
+        Level::WARN => 3,
+        Level::ERROR => 4,
+    }
+}
+
+/// Formats the message of an event like the fmt layer of Bevy does
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl MessageVisitor {
+    fn finish(self) -> String {
+        match (self.message.is_empty(), self.fields.is_empty()) {
+            (_, true) => self.message,
+            (true, false) => self.fields,
+            (false, false) => format!("{} {}", self.message, self.fields),
+        }
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            if !self.fields.is_empty() {
+                self.fields.push(' ');
+            }
+            let _ = write!(self.fields, "{}={value:?}", field.name());
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Gameplay systems loaded from a dynamic library, which can be rebuilt and
+//! loaded again while the app keeps running, built with the `hot_logic`
+//! feature. See [crate::cxxqt_bevy_logic] for the QML side.
+//!
+//! The library is a crate of type `dylib`, built by the same compiler with
+//! the same version of Bevy and of this crate, which adds its systems to the
+//! [QmlLogic] schedule:
+//!
+//! ```ignore
+//! #[no_mangle]
+//! pub fn bevy_qml_logic(schedule: &mut Schedule) {
+//!     schedule.add_systems((move_enemies, score_hits).chain());
+//! }
+//! ```
+//!
+//! [QmlLogic] runs during `Update`. Loading a library drops the schedule
+//! with the systems of the previous one before unloading it, while the
+//! entities and resources of the world stay as they are. Components and
+//! resources should therefore be declared by the app rather than by the
+//! library, as the types of an unloaded library cannot be used any more.
+//!
+//! The library is copied before it is loaded, so it can be rebuilt in place
+//! while loaded.
+
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+use libloading::Library;
+
+use crate::panic;
+
+/// The schedule the systems of the logic library run in
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct QmlLogic;
+
+/// The name of the function the logic library exports
+pub const LOGIC_SYMBOL: &str = "bevy_qml_logic";
+
+/// The signature of [LOGIC_SYMBOL]
+pub type BuildLogic = fn(&mut Schedule);
+
+/// The logic library which is loaded, if any
+#[derive(Resource, Default)]
+pub struct QmlLogicLibrary {
+    loaded: Option<LoadedLogic>,
+    generation: u32,
+}
+
+struct LoadedLogic {
+    /// Dropped after the schedule, which holds code of the library
+    _library: Library,
+    /// The copy which was loaded
+    copy: PathBuf,
+    source: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl QmlLogicLibrary {
+    /// The path the loaded library was copied from
+    pub fn path(&self) -> Option<&Path> {
+        self.loaded.as_ref().map(|loaded| loaded.source.as_path())
+    }
+
+    /// How many times a library was loaded
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Whether the library was rebuilt since it was loaded
+    pub fn is_outdated(&self) -> bool {
+        self.loaded
+            .as_ref()
+            .is_some_and(|loaded| modified(&loaded.source) != loaded.modified)
+    }
+}
+
+pub struct QmlLogicPlugin;
+
+impl Plugin for QmlLogicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QmlLogicLibrary>()
+            .add_systems(Update, run_logic);
+    }
+}
+
+fn run_logic(world: &mut World) {
+    // There is no schedule until a library is loaded
+    let _ = world.try_run_schedule(QmlLogic);
+}
+
+/// Load the logic library at the path, in place of the one loaded before
+///
+/// On failure no library is loaded afterwards, and the error tells why.
+pub fn load_logic(world: &mut World, path: &Path) -> Result<(), String> {
+    unload_logic(world);
+
+    let mut logic = world.resource_mut::<QmlLogicLibrary>();
+    logic.generation += 1;
+    let generation = logic.generation;
+
+    let source_modified = modified(path);
+    let copy = copy_path(path, generation);
+    std::fs::copy(path, &copy).map_err(|error| format!("Cannot copy {path:?}: {error}"))?;
+
+    // SAFETY: the library is built against the same Bevy as the app, and
+    // runs no code other than its initializers when loaded
+    let library = match unsafe { Library::new(&copy) } {
+        Ok(library) => library,
+        Err(error) => {
+            let _ = std::fs::remove_file(&copy);
+            return Err(format!("Cannot load {path:?}: {error}"));
+        }
+    };
+    // SAFETY: the function is declared with the signature of BuildLogic, and
+    // the pointer is not used after the library is unloaded
+    let build = match unsafe { library.get::<BuildLogic>(LOGIC_SYMBOL.as_bytes()) } {
+        Ok(build) => *build,
+        Err(error) => {
+            drop(library);
+            let _ = std::fs::remove_file(&copy);
+            return Err(format!("{path:?} does not export {LOGIC_SYMBOL}: {error}"));
+        }
+    };
+
+    let mut schedule = Schedule::new(QmlLogic);
+    if panic::catch_panic(LOGIC_SYMBOL, || build(&mut schedule)).is_none() {
+        drop(schedule);
+        drop(library);
+        let _ = std::fs::remove_file(&copy);
+        return Err(format!("{LOGIC_SYMBOL} of {path:?} panicked"));
+    }
+    world.add_schedule(schedule);
+    world.resource_mut::<QmlLogicLibrary>().loaded = Some(LoadedLogic {
+        _library: library,
+        copy,
+        source: path.to_owned(),
+        modified: source_modified,
+    });
+    info!("Loaded the logic of {path:?}");
+    Ok(())
+}
+
+/// Remove the systems of the logic library and unload it
+pub fn unload_logic(world: &mut World) {
+    if let Some(mut schedules) = world.get_resource_mut::<Schedules>() {
+        schedules.remove(QmlLogic);
+    }
+    let loaded = world
+        .get_resource_mut::<QmlLogicLibrary>()
+        .and_then(|mut logic| logic.loaded.take());
+    if let Some(loaded) = loaded {
+        let copy = loaded.copy.clone();
+        drop(loaded);
+        let _ = std::fs::remove_file(copy);
+    }
+}
+
+pub(crate) fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Where a library is copied to, a new path each time so that the system
+/// loads it again instead of handing out the previous one
+fn copy_path(path: &Path, generation: u32) -> PathBuf {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| String::from("logic"));
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    std::env::temp_dir().join(format!(
+        "{name}-{}-{generation}{extension}",
+        std::process::id()
+    ))
+}
@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Estimates of the memory the app holds on to, for long-running apps such
+//! as kiosks which watch for leaks, shown by the
+//! `MemoryDiagnosticsBridge` QML singleton.
+//!
+//! [MemoryDiagnostics] is measured every
+//! [QmlMemoryDiagnosticsPlugin::interval] of real time, as walking the
+//! archetypes and assets is too slow for every frame. The values are
+//! estimates: components count with their size in their storage, without
+//! the heap memory they own, meshes and images with the size of their data
+//! in the main world, which is gone once an asset only lives in the render
+//! world, and the GPU with the buffers of meshes and the textures of images,
+//! including render targets, without what the driver adds. Each value is a
+//! diagnostic under `memory/` as well, so it shows up next to the other Bevy
+//! diagnostics.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    prelude::*,
+    render::{
+        mesh::{GpuBufferInfo, GpuMesh, Indices},
+        render_asset::RenderAssets,
+        render_resource::Texture,
+        texture::GpuImage,
+        Render, RenderApp, RenderSet,
+    },
+    time::common_conditions::on_real_timer,
+};
+use serde::Serialize;
+
+/// What the app allocated at the last measurement
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryDiagnostics {
+    pub entities: u64,
+    pub archetypes: u64,
+    /// The bytes of the components of every entity in their tables and
+    /// sparse sets
+    pub component_bytes: u64,
+    pub meshes: u64,
+    /// The bytes of the vertices and indices of the meshes
+    pub mesh_bytes: u64,
+    pub images: u64,
+    /// The bytes of the pixels of the images
+    pub image_bytes: u64,
+    pub materials: u64,
+    /// The bytes of the vertex and index buffers uploaded for meshes
+    pub gpu_buffer_bytes: u64,
+    /// The bytes of the textures uploaded for images, with their mipmaps
+    pub gpu_texture_bytes: u64,
+}
+
+/// Measures the [MemoryDiagnostics] of the app
+pub struct QmlMemoryDiagnosticsPlugin {
+    /// How often the memory is measured
+    pub interval: Duration,
+}
+
+impl Default for QmlMemoryDiagnosticsPlugin {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl QmlMemoryDiagnosticsPlugin {
+    pub const ARCHETYPES: DiagnosticPath = DiagnosticPath::const_new("memory/archetypes");
+    /// See [MemoryDiagnostics::component_bytes]
+    pub const COMPONENT_BYTES: DiagnosticPath = DiagnosticPath::const_new("memory/component_bytes");
+    pub const MESH_BYTES: DiagnosticPath = DiagnosticPath::const_new("memory/mesh_bytes");
+    pub const IMAGE_BYTES: DiagnosticPath = DiagnosticPath::const_new("memory/image_bytes");
+    pub const GPU_BUFFER_BYTES: DiagnosticPath =
+        DiagnosticPath::const_new("memory/gpu_buffer_bytes");
+    pub const GPU_TEXTURE_BYTES: DiagnosticPath =
+        DiagnosticPath::const_new("memory/gpu_texture_bytes");
+}
+
+impl Plugin for QmlMemoryDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        let gpu_memory = GpuMemory::default();
+        app.register_diagnostic(Diagnostic::new(Self::ARCHETYPES))
+            .register_diagnostic(Diagnostic::new(Self::COMPONENT_BYTES))
+            .register_diagnostic(Diagnostic::new(Self::MESH_BYTES))
+            .register_diagnostic(Diagnostic::new(Self::IMAGE_BYTES))
+            .register_diagnostic(Diagnostic::new(Self::GPU_BUFFER_BYTES))
+            .register_diagnostic(Diagnostic::new(Self::GPU_TEXTURE_BYTES))
+            .insert_resource(gpu_memory.clone())
+            .init_resource::<MemoryDiagnostics>()
+            .add_systems(
+                Last,
+                (measure_memory, record_memory)
+                    .chain()
+                    .run_if(on_real_timer(self.interval)),
+            );
+
+        // Headless apps have nothing on the GPU
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .insert_resource(gpu_memory)
+            .add_systems(Render, measure_gpu_memory.in_set(RenderSet::Cleanup));
+    }
+}
+
+/// The bytes of the buffers and textures measured by the render world, for
+/// the main world
+#[derive(Resource, Clone, Default)]
+struct GpuMemory(Arc<Mutex<(u64, u64)>>);
+
+fn measure_memory(world: &mut World) {
+    let mut memory = MemoryDiagnostics {
+        entities: world.entities().len() as u64,
+        archetypes: world.archetypes().len() as u64,
+        component_bytes: component_bytes(world),
+        ..default()
+    };
+    // Headless apps have no meshes, images or materials
+    if let Some(meshes) = world.get_resource::<Assets<Mesh>>() {
+        memory.meshes = meshes.len() as u64;
+        memory.mesh_bytes = meshes.iter().map(|(_, mesh)| mesh_bytes(mesh)).sum();
+    }
+    if let Some(images) = world.get_resource::<Assets<Image>>() {
+        memory.images = images.len() as u64;
+        memory.image_bytes = images
+            .iter()
+            .map(|(_, image)| image.data.len() as u64)
+            .sum();
+    }
+    if let Some(materials) = world.get_resource::<Assets<StandardMaterial>>() {
+        memory.materials = materials.len() as u64;
+    }
+    if let Some(gpu_memory) = world.get_resource::<GpuMemory>() {
+        (memory.gpu_buffer_bytes, memory.gpu_texture_bytes) = *gpu_memory.0.lock().unwrap();
+    }
+
+    let mut current = world.resource_mut::<MemoryDiagnostics>();
+    if *current != memory {
+        *current = memory;
+    }
+}
+
+fn record_memory(memory: Res<MemoryDiagnostics>, mut diagnostics: Diagnostics) {
+    let mut measure = |path: &DiagnosticPath, value: u64| {
+        diagnostics.add_measurement(path, || value as f64);
+    };
+    measure(&QmlMemoryDiagnosticsPlugin::ARCHETYPES, memory.archetypes);
+    measure(
+        &QmlMemoryDiagnosticsPlugin::COMPONENT_BYTES,
+        memory.component_bytes,
+    );
+    measure(&QmlMemoryDiagnosticsPlugin::MESH_BYTES, memory.mesh_bytes);
+    measure(&QmlMemoryDiagnosticsPlugin::IMAGE_BYTES, memory.image_bytes);
+    measure(
+        &QmlMemoryDiagnosticsPlugin::GPU_BUFFER_BYTES,
+        memory.gpu_buffer_bytes,
+    );
+    measure(
+        &QmlMemoryDiagnosticsPlugin::GPU_TEXTURE_BYTES,
+        memory.gpu_texture_bytes,
+    );
+}
+
+/// The size of the components of every archetype times its entities
+fn component_bytes(world: &World) -> u64 {
+    let components = world.components();
+    world
+        .archetypes()
+        .iter()
+        .map(|archetype| {
+            let size: usize = archetype
+                .components()
+                .filter_map(|id| components.get_info(id))
+                .map(|info| info.layout().size())
+                .sum();
+            (size * archetype.len()) as u64
+        })
+        .sum()
+}
+
+fn mesh_bytes(mesh: &Mesh) -> u64 {
+    let indices = match mesh.indices() {
+        Some(Indices::U16(indices)) => indices.len() as u64 * 2,
+        Some(Indices::U32(indices)) => indices.len() as u64 * 4,
+        None => 0,
+    };
+    mesh.get_vertex_size() * mesh.count_vertices() as u64 + indices
+}
+
+fn measure_gpu_memory(
+    gpu_memory: Res<GpuMemory>,
+    meshes: Res<RenderAssets<GpuMesh>>,
+    images: Res<RenderAssets<GpuImage>>,
+) {
+    let buffers = meshes
+        .iter()
+        .map(|(_, mesh)| {
+            let indices = match &mesh.buffer_info {
+                GpuBufferInfo::Indexed { buffer, .. } => buffer.size(),
+                GpuBufferInfo::NonIndexed => 0,
+            };
+            mesh.vertex_buffer.size() + indices
+        })
+        .sum();
+    let textures = images
+        .iter()
+        .map(|(_, image)| texture_bytes(&image.texture))
+        .sum();
+    *gpu_memory.0.lock().unwrap() = (buffers, textures);
+}
+
+/// The size of every mip level and layer of the texture
+fn texture_bytes(texture: &Texture) -> u64 {
+    let format = texture.format();
+    let (block_width, block_height) = format.block_dimensions();
+    // Combined depth and stencil formats have no size of their own
+    let block_size = format.block_copy_size(None).unwrap_or(4) as u64;
+    let size = texture.size();
+    let level_bytes = |level: u32| {
+        let width = (size.width >> level).max(1).div_ceil(block_width) as u64;
+        let height = (size.height >> level).max(1).div_ceil(block_height) as u64;
+        width * height * block_size
+    };
+    let bytes: u64 = (0..texture.mip_level_count()).map(level_bytes).sum();
+    bytes * size.depth_or_array_layers as u64 * texture.sample_count() as u64
+}
@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Top-down views of the world for minimaps, shown by the `BevyMiniView`
+//! QML component.
+//!
+//! A mini view is an orthographic camera looking straight down onto the XZ
+//! plane, with -Z at the top of the image. It renders into an offscreen
+//! image published with a [QmlImageTarget], which QML shows through the
+//! `image://bevy/` image provider as any other image target.
+//!
+//! [QmlImageTarget]: crate::render::QmlImageTarget
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{Projection, ScalingMode},
+        view::RenderLayers,
+    },
+    transform::TransformSystem,
+};
+
+use crate::{picking, render::QmlImageTarget};
+
+/// Keeps the camera on the same entity above a point of the world, looking
+/// down
+#[derive(Component, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub struct QmlMiniView {
+    /// The point of the world in the middle of the view
+    pub center: Vec3,
+    /// An entity the view follows in the XZ plane instead of staying at
+    /// [QmlMiniView::center]
+    pub follow: Option<Entity>,
+    /// How much of the world the width of the view covers
+    pub extent: f32,
+    /// How high above its center the camera is, which is also how far below
+    /// the center it sees
+    pub altitude: f32,
+}
+
+impl Default for QmlMiniView {
+    fn default() -> Self {
+        Self {
+            center: Vec3::ZERO,
+            follow: None,
+            extent: 50.0,
+            altitude: 100.0,
+        }
+    }
+}
+
+pub struct QmlMiniViewPlugin;
+
+impl Plugin for QmlMiniViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<QmlMiniView>().add_systems(
+            PostUpdate,
+            place_mini_views.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// A camera for a mini view, rendering into the image of the target
+pub fn mini_view_bundle(view: QmlMiniView, target: QmlImageTarget) -> impl Bundle {
+    (
+        Camera3dBundle {
+            // Render before the cameras of the items, which may show the
+            // mini view as well
+            camera: Camera {
+                order: -1,
+                ..default()
+            },
+            projection: projection(&view),
+            transform: transform(&view, view.center),
+            ..default()
+        },
+        view,
+        target,
+    )
+}
+
+fn projection(view: &QmlMiniView) -> Projection {
+    Projection::Orthographic(OrthographicProjection {
+        near: 0.0,
+        far: view.altitude.max(0.0) * 2.0,
+        scaling_mode: ScalingMode::FixedHorizontal(view.extent.max(f32::EPSILON)),
+        ..default()
+    })
+}
+
+fn transform(view: &QmlMiniView, center: Vec3) -> Transform {
+    Transform::from_translation(center + Vec3::Y * view.altitude)
+        .looking_to(Vec3::NEG_Y, Vec3::NEG_Z)
+}
+
+fn place_mini_views(
+    mut views: Query<(Ref<QmlMiniView>, &mut Transform, &mut Projection)>,
+    followed: Query<&GlobalTransform, Without<QmlMiniView>>,
+) {
+    for (view, mut camera_transform, mut camera_projection) in &mut views {
+        let center = match view.follow.and_then(|entity| followed.get(entity).ok()) {
+            Some(target) => {
+                let translation = target.translation();
+                Vec3::new(translation.x, view.center.y, translation.z)
+            }
+            None => view.center,
+        };
+        let wanted = transform(&view, center);
+        if *camera_transform != wanted {
+            *camera_transform = wanted;
+        }
+        if view.is_changed() {
+            *camera_projection = projection(&view);
+        }
+    }
+}
+
+/// The point of the world shown at a position of the mini view, from zero to
+/// one of its width and height
+///
+/// This is the closest mesh under the position that picking sees, or the
+/// point on the plane through the center of the view otherwise.
+pub fn world_position(world: &mut World, camera: Entity, position: Vec2) -> Option<Vec3> {
+    let size = world.get::<QmlImageTarget>(camera)?.size.as_vec2();
+    let view = world.get::<QmlMiniView>(camera)?.clone();
+    let ray = picking::viewport_ray(world, camera, position * size)?;
+    let layers = world
+        .get::<RenderLayers>(camera)
+        .cloned()
+        .unwrap_or_default();
+    if let Some(hit) = picking::cast_ray(world, ray, &layers) {
+        return Some(hit.position);
+    }
+    let distance = ray.intersect_plane(view.center, InfinitePlane3d::new(Vec3::Y))?;
+    Some(ray.get_point(distance))
+}
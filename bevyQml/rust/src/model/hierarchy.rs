@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! A copy of the `Parent`/`Children` hierarchy of a world.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::label::{EntityLabel, QmlLabel};
+
+/// Every entity of a world arranged by its parent
+///
+/// Root entities, which includes entities whose `Parent` no longer exists,
+/// are kept under [None] in the order of their ids, and children in the
+/// order of the `Children` component of their parent.
+#[derive(Default)]
+pub struct EntityHierarchy {
+    parents: HashMap<Entity, Option<Entity>>,
+    children: HashMap<Option<Entity>, Vec<Entity>>,
+    labels: HashMap<Entity, EntityLabel>,
+}
+
+impl EntityHierarchy {
+    pub fn capture(world: &mut World) -> Self {
+        let mut query = world.query::<(
+            Entity,
+            Option<&Parent>,
+            Option<&Children>,
+            Option<&Name>,
+            Option<&QmlLabel>,
+        )>();
+        let mut hierarchy = Self::default();
+        let mut order = HashMap::<Entity, Vec<Entity>>::default();
+
+        for (entity, parent, children, name, label) in query.iter(world) {
+            let parent = parent
+                .map(Parent::get)
+                .filter(|parent| world.get_entity(*parent).is_some());
+            hierarchy.parents.insert(entity, parent);
+            if let Some(children) = children {
+                order.insert(entity, children.to_vec());
+            }
+            hierarchy
+                .labels
+                .insert(entity, EntityLabel::new(entity, name, label));
+        }
+
+        // Children lists can briefly disagree with Parent while commands are
+        // applied, so only keep the children which point back at the parent
+        for (&entity, &parent) in &hierarchy.parents {
+            hierarchy.children.entry(parent).or_default().push(entity);
+        }
+        for (parent, children) in &mut hierarchy.children {
+            match parent.and_then(|parent| order.get(&parent)) {
+                Some(order) => children.sort_by_key(|child| {
+                    order
+                        .iter()
+                        .position(|other| other == child)
+                        .unwrap_or(usize::MAX)
+                }),
+                None => children.sort_unstable(),
+            }
+        }
+        hierarchy
+    }
+
+    /// Every entity in the hierarchy, in no particular order
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.parents.keys().copied()
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.parents.contains_key(&entity)
+    }
+
+    /// The parent of the entity, [None] for roots as well as unknown entities
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        self.parents.get(&entity).copied().flatten()
+    }
+
+    /// The children of an entity, or the roots for [None]
+    pub fn children(&self, parent: Option<Entity>) -> &[Entity] {
+        self.children.get(&parent).map_or(&[], Vec::as_slice)
+    }
+
+    /// The position of the entity among its siblings
+    pub fn row(&self, entity: Entity) -> Option<usize> {
+        let parent = *self.parents.get(&entity)?;
+        self.children(parent)
+            .iter()
+            .position(|other| *other == entity)
+    }
+
+    /// How the entity is shown, see [EntityLabel::new]
+    pub fn label(&self, entity: Entity) -> Option<&EntityLabel> {
+        self.labels.get(&entity)
+    }
+
+    /// Remove the entity and everything below it
+    pub fn remove_subtree(&mut self, entity: Entity) {
+        let Some(parent) = self.parents.get(&entity).copied() else {
+            return;
+        };
+        if let Some(siblings) = self.children.get_mut(&parent) {
+            siblings.retain(|other| *other != entity);
+        }
+
+        let mut pending = vec![entity];
+        while let Some(entity) = pending.pop() {
+            self.parents.remove(&entity);
+            self.labels.remove(&entity);
+            pending.extend(self.children.remove(&Some(entity)).unwrap_or_default());
+        }
+    }
+
+    /// Insert the entity at `row` of `parent` together with everything below
+    /// it in `source`
+    pub fn insert_subtree(
+        &mut self,
+        parent: Option<Entity>,
+        row: usize,
+        entity: Entity,
+        source: &EntityHierarchy,
+    ) {
+        let siblings = self.children.entry(parent).or_default();
+        siblings.insert(row.min(siblings.len()), entity);
+
+        let mut pending = vec![(parent, entity)];
+        while let Some((parent, entity)) = pending.pop() {
+            self.parents.insert(entity, parent);
+            if let Some(label) = source.label(entity) {
+                self.labels.insert(entity, label.clone());
+            }
+            let children = source.children(Some(entity));
+            if !children.is_empty() {
+                self.children.insert(Some(entity), children.to_vec());
+                pending.extend(children.iter().map(|child| (Some(entity), *child)));
+            }
+        }
+    }
+
+    /// Move a child of `parent` from one row to another
+    pub fn move_child(&mut self, parent: Option<Entity>, from: usize, to: usize) {
+        if let Some(siblings) = self.children.get_mut(&parent) {
+            let entity = siblings.remove(from);
+            siblings.insert(to, entity);
+        }
+    }
+
+    pub fn set_label(&mut self, entity: Entity, label: EntityLabel) {
+        self.labels.insert(entity, label);
+    }
+}
@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! The world side of the item models which present ECS data to QML views.
+
+mod hierarchy;
+mod query;
+mod query_snapshot;
+
+pub use hierarchy::EntityHierarchy;
+pub use query::{QueryColumn, QuerySpec};
+pub use query_snapshot::{
+    watch_query, QmlQuerySnapshotPlugin, QmlQuerySnapshots, QueryRowData, QueryRowValues,
+    QuerySnapshot, QuerySnapshotReader,
+};
@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Queries over reflected components, named from QML.
+
+use std::any::TypeId;
+
+use bevy::{
+    ecs::{component::Tick, query::QueryBuilder},
+    prelude::*,
+    reflect::{ReflectRef, TypeInfo, TypeRegistry},
+};
+
+use crate::{
+    commands::lookup,
+    variant::{self, QVariant},
+};
+
+#[derive(Clone)]
+struct QueriedComponent {
+    type_id: TypeId,
+    reflect: ReflectComponent,
+}
+
+/// A value of every matching entity, shown by the model under its own role
+#[derive(Clone, Debug)]
+pub struct QueryColumn {
+    pub role: String,
+    component: usize,
+    /// The struct field to read, or [None] to convert the whole component
+    field: Option<String>,
+}
+
+/// The components a query asks for and the columns read from them
+///
+/// Components are named like in [crate::commands::QmlCommand], by their short
+/// or full type path, and have to be registered with `#[reflect(Component)]`.
+#[derive(Clone)]
+pub struct QuerySpec {
+    components: Vec<QueriedComponent>,
+    columns: Vec<QueryColumn>,
+}
+
+impl QuerySpec {
+    /// Look up the components and derive a column for each of their fields
+    ///
+    /// Fields are named by their own name, or prefixed with the short name of
+    /// their component when several components share a field name. Components
+    /// which are not structs, such as newtypes, make up a single column named
+    /// after the component with its first letter in lower case.
+    pub fn resolve(registry: &TypeRegistry, names: &[String]) -> Result<Self, String> {
+        let mut components = Vec::new();
+        let mut fields = Vec::new();
+
+        for name in names {
+            let registration = lookup(registry, name)?;
+            let reflect = registration
+                .data::<ReflectComponent>()
+                .ok_or_else(|| format!("{name} does not reflect Component"))?
+                .clone();
+            let short_name = registration.type_info().type_path_table().short_path();
+
+            let index = components.len();
+            match registration.type_info() {
+                TypeInfo::Struct(info) => fields.extend(
+                    info.field_names()
+                        .iter()
+                        .map(|field| (index, short_name, Some(field.to_string()))),
+                ),
+                _ => fields.push((index, short_name, None)),
+            }
+            components.push(QueriedComponent {
+                type_id: registration.type_id(),
+                reflect,
+            });
+        }
+
+        let shared = |field: &str| {
+            fields
+                .iter()
+                .filter(|(_, _, other)| other.as_deref() == Some(field))
+                .count()
+                > 1
+        };
+        let columns = fields
+            .iter()
+            .map(|(component, short_name, field)| {
+                let role = match field {
+                    Some(field) if shared(field) => format!("{short_name}_{field}"),
+                    Some(field) => field.clone(),
+                    None => lower_first(short_name),
+                };
+                QueryColumn {
+                    role,
+                    component: *component,
+                    field: field.clone(),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            components,
+            columns,
+        })
+    }
+
+    pub fn columns(&self) -> &[QueryColumn] {
+        &self.columns
+    }
+
+    /// Every entity which has all of the components, in a stable order
+    pub fn matching(&self, world: &mut World) -> Vec<Entity> {
+        let mut ids = Vec::with_capacity(self.components.len());
+        for component in &self.components {
+            // A component which was never inserted is not known to the world yet
+            let Some(id) = world.components().get_id(component.type_id) else {
+                return Vec::new();
+            };
+            ids.push(id);
+        }
+
+        let mut builder = QueryBuilder::<Entity>::new(world);
+        for id in ids {
+            builder.with_id(id);
+        }
+        let mut query = builder.build();
+        let mut entities: Vec<Entity> = query.iter(world).collect();
+        entities.sort_unstable();
+        entities
+    }
+
+    /// Whether any of the components of the entity changed after `since`
+    pub fn changed_since(&self, world: &World, entity: Entity, since: Tick) -> bool {
+        let Some(entity_ref) = world.get_entity(entity) else {
+            return false;
+        };
+        let this_run = world.read_change_tick();
+        self.components.iter().any(|component| {
+            world
+                .components()
+                .get_id(component.type_id)
+                .and_then(|id| entity_ref.get_change_ticks_by_id(id))
+                .is_some_and(|ticks| ticks.is_changed(since, this_run))
+        })
+    }
+
+    /// The value of every column for the entity, invalid where it cannot be converted
+    pub fn read(&self, world: &World, entity: Entity) -> Vec<QVariant> {
+        let Some(entity_ref) = world.get_entity(entity) else {
+            return vec![QVariant::default(); self.columns.len()];
+        };
+
+        self.columns
+            .iter()
+            .map(|column| {
+                let component = self.components[column.component]
+                    .reflect
+                    .reflect(entity_ref)?;
+                match (&column.field, component.reflect_ref()) {
+                    (Some(field), ReflectRef::Struct(value)) => {
+                        variant::to_variant(value.field(field)?)
+                    }
+                    (None, ReflectRef::TupleStruct(value)) if value.field_len() == 1 => {
+                        variant::to_variant(value.field(0)?)
+                    }
+                    (None, _) => variant::to_variant(component),
+                    _ => None,
+                }
+            })
+            .map(Option::unwrap_or_default)
+            .collect()
+    }
+}
+
+fn lower_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
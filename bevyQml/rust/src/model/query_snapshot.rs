@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Snapshots of the results of queries, which the Bevy side fills at the end
+//! of every update and models read on the GUI thread without touching the
+//! world.
+//!
+//! A model asks for a query with [watch_query] and gets the reading end of
+//! a triple buffer, see [crate::snapshot]. The [QmlQuerySnapshotPlugin]
+//! publishes a new [QuerySnapshot] whenever the matching entities or their
+//! components changed. The values of a row are only read again when one of
+//! its components, its `Name` or its `QmlLabel` changed, and are shared with
+//! the previous snapshots otherwise, so publishing costs little more than
+//! the query itself and a model can tell changed rows apart by comparing
+//! pointers. This also works
+//! while the app runs on a thread of its own.
+
+use std::sync::Arc;
+
+use bevy::{
+    ecs::{component::Tick, entity::EntityHashMap},
+    prelude::*,
+};
+
+use super::QuerySpec;
+use crate::{
+    label::EntityLabel,
+    runtime,
+    snapshot::{snapshot_buffer, SnapshotReader, SnapshotWriter},
+    variant::QVariant,
+};
+
+/// The values of a row, shared between snapshots while the components of
+/// its entity do not change
+pub type QueryRowValues = Arc<QueryRowData>;
+
+#[derive(Clone, Default)]
+pub struct QueryRowData {
+    /// How the entity is shown in the standard roles
+    pub label: EntityLabel,
+    /// The value of every column
+    pub columns: Vec<QVariant>,
+}
+
+/// The entities matching a query and the values of their columns
+#[derive(Clone, Default)]
+pub struct QuerySnapshot {
+    /// The roles of the columns, see [QuerySpec::resolve]
+    pub roles: Arc<[String]>,
+    /// The matching entities in a stable order, with their values
+    pub rows: Vec<(Entity, QueryRowValues)>,
+    /// Why the components could not be queried, if they could not
+    pub error: Option<String>,
+}
+
+/// The snapshots of a query as the model reads them
+pub type QuerySnapshotReader = SnapshotReader<Arc<QuerySnapshot>>;
+
+/// Start publishing snapshots of the entities with the named components
+///
+/// The snapshots stop once the reader is dropped. Returns [None] if there is
+/// no app to query.
+pub fn watch_query(components: Vec<String>) -> Option<QuerySnapshotReader> {
+    let (writer, reader) = snapshot_buffer(Arc::new(QuerySnapshot::default()));
+    let sent = runtime::send(move |world| {
+        let spec = {
+            let registry = world.resource::<AppTypeRegistry>().read();
+            QuerySpec::resolve(&registry, &components)
+        };
+        let watched = WatchedQuery::new(spec, writer);
+        world
+            .get_resource_or_insert_with(QmlQuerySnapshots::default)
+            .queries
+            .push(watched);
+    });
+    sent.then_some(reader)
+}
+
+struct WatchedQuery {
+    spec: Result<QuerySpec, String>,
+    writer: SnapshotWriter<Arc<QuerySnapshot>>,
+    rows: EntityHashMap<QueryRowValues>,
+    order: Vec<Entity>,
+    last_published: Option<Tick>,
+}
+
+impl WatchedQuery {
+    fn new(spec: Result<QuerySpec, String>, writer: SnapshotWriter<Arc<QuerySnapshot>>) -> Self {
+        Self {
+            spec,
+            writer,
+            rows: EntityHashMap::default(),
+            order: Vec::new(),
+            last_published: None,
+        }
+    }
+
+    /// Publish a new snapshot if anything changed since the last one
+    fn publish(&mut self, world: &mut World) {
+        let tick = world.read_change_tick();
+        let first = self.last_published.is_none();
+        let spec = match &self.spec {
+            Ok(spec) => spec,
+            Err(error) => {
+                if first {
+                    self.writer.write(Arc::new(QuerySnapshot {
+                        error: Some(error.clone()),
+                        ..default()
+                    }));
+                    self.last_published = Some(tick);
+                }
+                return;
+            }
+        };
+
+        let entities = spec.matching(world);
+        let mut changed = first || entities != self.order;
+        let mut rows = EntityHashMap::default();
+        for &entity in &entities {
+            let previous = self.rows.remove(&entity);
+            let values = match (previous, self.last_published) {
+                (Some(values), Some(since))
+                    if !spec.changed_since(world, entity, since)
+                        && !EntityLabel::changed_since(world, entity, since) =>
+                {
+                    values
+                }
+                _ => {
+                    changed = true;
+                    Arc::new(QueryRowData {
+                        label: EntityLabel::of(world, entity),
+                        columns: spec.read(world, entity),
+                    })
+                }
+            };
+            rows.insert(entity, values);
+        }
+        self.rows = rows;
+        self.order = entities;
+        self.last_published = Some(tick);
+        if !changed {
+            return;
+        }
+
+        let roles = spec
+            .columns()
+            .iter()
+            .map(|column| column.role.clone())
+            .collect();
+        self.writer.write(Arc::new(QuerySnapshot {
+            roles,
+            rows: self
+                .order
+                .iter()
+                .map(|entity| (*entity, self.rows[entity].clone()))
+                .collect(),
+            error: None,
+        }));
+    }
+}
+
+/// The queries models watch
+#[derive(Resource, Default)]
+pub struct QmlQuerySnapshots {
+    queries: Vec<WatchedQuery>,
+}
+
+impl QmlQuerySnapshots {
+    /// How many queries are watched
+    pub fn len(&self) -> usize {
+        self.queries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queries.is_empty()
+    }
+}
+
+pub struct QmlQuerySnapshotPlugin;
+
+impl Plugin for QmlQuerySnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QmlQuerySnapshots>()
+            .add_systems(Last, publish_query_snapshots);
+    }
+}
+
+fn publish_query_snapshots(world: &mut World) {
+    world.resource_scope(|world, mut snapshots: Mut<QmlQuerySnapshots>| {
+        snapshots
+            .queries
+            .retain(|query| !query.writer.is_orphaned());
+        for query in &mut snapshots.queries {
+            query.publish(world);
+        }
+    });
+}
@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Keeps panics in Rust code from unwinding into C++, where they would abort
+//! the whole Qt application.
+//!
+//! [crate::runtime] runs the updates of the apps, the closures given to
+//! [crate::runtime::with_world] and the update listeners through [catch], so
+//! a panicking system or QML element is logged and reported to the
+//! [on_error] listeners, such as the `bevyError` signal of `MyBevyApp`,
+//! instead. A panic while updating the main app leaves it degraded, see
+//! [crate::runtime::is_degraded].
+//!
+//! Entry points called from Qt which do not go through the runtime can use
+//! [catch_panic] themselves:
+//!
+//! ```ignore
+//! pub fn frobnicate(&self) {
+//!     panic::catch_panic("frobnicate", || self.rust().frobnicate());
+//! }
+//! ```
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+};
+
+use bevy::prelude::*;
+
+type ErrorCallback = Rc<RefCell<Option<Box<dyn FnMut(&str)>>>>;
+
+thread_local! {
+    static ERROR_LISTENERS: RefCell<Vec<ErrorCallback>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Run the closure, turning a panic into an error reported to the
+/// [on_error] listeners
+///
+/// Returns [None] if the closure panicked. `context` tells what was running
+/// in the report.
+pub fn catch_panic<R>(context: &str, f: impl FnOnce() -> R) -> Option<R> {
+    catch(f)
+        .map_err(|message| report_error(format!("{context} panicked: {message}")))
+        .ok()
+}
+
+/// Run the closure, returning the message of its panic if it panicked
+///
+/// Use this rather than [catch_panic] while holding state the listeners may
+/// reach, and report the message once it is released.
+pub(crate) fn catch<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| panic_message(&*payload))
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".into()
+    }
+}
+
+/// Log the error and pass it on to the [on_error] listeners of this thread
+pub fn report_error(message: impl Into<String>) {
+    let message = message.into();
+    error!("{message}");
+    notify_error(&message);
+}
+
+/// Pass an error which was already logged on to the [on_error] listeners
+pub(crate) fn notify_error(message: &str) {
+    // Clone the list so callbacks can register new listeners while running
+    let listeners = ERROR_LISTENERS.with(|listeners| {
+        let mut listeners = listeners.borrow_mut();
+        listeners.retain(|listener| listener.try_borrow().map_or(true, |cb| cb.is_some()));
+        listeners.clone()
+    });
+    for listener in listeners {
+        if let Ok(mut callback) = listener.try_borrow_mut() {
+            if let Some(callback) = callback.as_mut() {
+                // A panicking listener must not take the others down with it
+                let _ = catch(|| callback(message));
+            }
+        }
+    }
+}
+
+/// Register a callback for the errors reported on the GUI thread
+///
+/// The callback is removed once the returned [ErrorListener] is dropped.
+pub fn on_error(callback: impl FnMut(&str) + 'static) -> ErrorListener {
+    let callback: ErrorCallback = Rc::new(RefCell::new(Some(Box::new(callback))));
+    ERROR_LISTENERS.with(|listeners| listeners.borrow_mut().push(callback.clone()));
+    ErrorListener(callback)
+}
+
+/// Keeps a callback registered with [on_error] alive
+///
+/// The callback is removed when this is dropped.
+pub struct ErrorListener(ErrorCallback);
+
+impl Drop for ErrorListener {
+    fn drop(&mut self) {
+        if let Ok(mut callback) = self.0.try_borrow_mut() {
+            callback.take();
+        }
+    }
+}
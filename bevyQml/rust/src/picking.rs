@@ -0,0 +1,321 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Ray casts against the meshes of the scene, so QML can pick entities
+//! under the cursor of a [crate::cxxqt_bevy_quick_item] view.
+//!
+//! Picking runs on the CPU against the vertex data of the mesh assets, so it
+//! only sees meshes which are kept in the main world and are triangle lists.
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+        primitives::Aabb,
+        view::RenderLayers,
+    },
+};
+
+/// Marks an entity that can be picked when [PickingMode::PickableOnly] is set
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct QmlPickable;
+
+/// Which meshes are hit by picking
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Resource, Default)]
+pub enum PickingMode {
+    /// Every visible mesh the camera can see
+    #[default]
+    AllMeshes,
+    /// Only visible meshes with a [QmlPickable] component
+    PickableOnly,
+}
+
+/// The closest mesh along a ray
+#[derive(Clone, Copy, Debug)]
+pub struct PickHit {
+    pub entity: Entity,
+    /// Where the ray hit the mesh, in world space
+    pub position: Vec3,
+    /// The normal of the triangle that was hit, facing the ray
+    pub normal: Vec3,
+    /// The distance from the origin of the ray
+    pub distance: f32,
+    /// The texture coordinates at the hit, for meshes which have them
+    pub uv: Option<Vec2>,
+}
+
+pub struct QmlPickingPlugin;
+
+impl Plugin for QmlPickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<QmlPickable>()
+            .register_type::<PickingMode>()
+            .init_resource::<PickingMode>();
+    }
+}
+
+/// Pick the entity shown at a position of a render target image
+///
+/// The position is given in physical pixels of the image, and the ray is
+/// cast from the camera whose viewport shows it, see [camera_at].
+pub fn pick(world: &mut World, image: &Handle<Image>, position: Vec2) -> Option<PickHit> {
+    let camera = camera_at(world, image, position)?;
+    let ray = viewport_ray(world, camera, position)?;
+    let layers = world
+        .get::<RenderLayers>(camera)
+        .cloned()
+        .unwrap_or_default();
+    cast_ray(world, ray, &layers)
+}
+
+/// The active camera with the highest order that renders into the image
+pub fn target_camera(world: &mut World, image: &Handle<Image>) -> Option<Entity> {
+    world
+        .query::<(Entity, &Camera)>()
+        .iter(world)
+        .filter(|(_, camera)| {
+            camera.is_active
+                && matches!(&camera.target, RenderTarget::Image(target) if target == image)
+        })
+        .max_by_key(|(_, camera)| camera.order)
+        .map(|(entity, _)| entity)
+}
+
+/// The active camera with the highest order that renders into the image at
+/// a position in physical pixels
+///
+/// Cameras laid out side by side in the image only count where their
+/// viewport is. Positions outside of every viewport fall back to
+/// [target_camera].
+pub fn camera_at(world: &mut World, image: &Handle<Image>, position: Vec2) -> Option<Entity> {
+    world
+        .query::<(Entity, &Camera)>()
+        .iter(world)
+        .filter(|(_, camera)| {
+            camera.is_active
+                && matches!(&camera.target, RenderTarget::Image(target) if target == image)
+                && camera
+                    .logical_viewport_rect()
+                    .is_some_and(|rect| rect.contains(position))
+        })
+        .max_by_key(|(_, camera)| camera.order)
+        .map(|(entity, _)| entity)
+        .or_else(|| target_camera(world, image))
+}
+
+/// The ray through a position of the viewport of a camera, in physical pixels
+pub fn viewport_ray(world: &World, camera: Entity, position: Vec2) -> Option<Ray3d> {
+    let camera_ref = world.get::<Camera>(camera)?;
+    let transform = world.get::<GlobalTransform>(camera)?;
+
+    // Image targets have a scale factor of one, so the logical viewport of
+    // the camera is measured in physical pixels
+    let position = position - camera_ref.logical_viewport_rect()?.min;
+    camera_ref.viewport_to_world(transform, position)
+}
+
+/// Where a point of the world appears in the viewport of a camera, in
+/// physical pixels, unless it is behind the camera or beyond its far plane
+pub fn viewport_point(world: &World, camera: Entity, position: Vec3) -> Option<Vec2> {
+    let camera_ref = world.get::<Camera>(camera)?;
+    let transform = world.get::<GlobalTransform>(camera)?;
+    let point = camera_ref.world_to_viewport(transform, position)?;
+    Some(point + camera_ref.logical_viewport_rect()?.min)
+}
+
+/// Whether a point of the world is hidden from a camera behind a mesh
+///
+/// Meshes of the entity and its descendants do not hide the point, so a
+/// point inside the entity counts as seen when the entity is.
+pub fn occluded(world: &mut World, camera: Entity, position: Vec3, entity: Entity) -> bool {
+    let Some(ray) = viewport_point(world, camera, position)
+        .and_then(|point| viewport_ray(world, camera, point))
+    else {
+        return false;
+    };
+    let layers = world
+        .get::<RenderLayers>(camera)
+        .cloned()
+        .unwrap_or_default();
+    let Some(hit) = cast_ray(world, ray, &layers) else {
+        return false;
+    };
+    // Leave some room for the precision of the hit
+    if hit.distance >= ray.origin.distance(position) * 0.999 {
+        return false;
+    }
+    let mut ancestor = Some(hit.entity);
+    while let Some(current) = ancestor {
+        if current == entity {
+            return false;
+        }
+        ancestor = world.get::<Parent>(current).map(Parent::get);
+    }
+    true
+}
+
+/// Find the closest mesh hit by a ray among those visible on the layers
+pub fn cast_ray(world: &mut World, ray: Ray3d, layers: &RenderLayers) -> Option<PickHit> {
+    let mode = world
+        .get_resource::<PickingMode>()
+        .copied()
+        .unwrap_or_default();
+    let mut query = world.query::<(
+        Entity,
+        &Handle<Mesh>,
+        &GlobalTransform,
+        Option<&Aabb>,
+        Option<&RenderLayers>,
+        Option<&InheritedVisibility>,
+        Has<QmlPickable>,
+    )>();
+    let meshes = world.resource::<Assets<Mesh>>();
+
+    let mut closest: Option<PickHit> = None;
+    for (entity, mesh, transform, aabb, mesh_layers, visibility, pickable) in query.iter(world) {
+        if mode == PickingMode::PickableOnly && !pickable {
+            continue;
+        }
+        if visibility.is_some_and(|visibility| !visibility.get()) {
+            continue;
+        }
+        if !mesh_layers
+            .unwrap_or(&RenderLayers::default())
+            .intersects(layers)
+        {
+            continue;
+        }
+        let Some(mesh) = meshes.get(mesh) else {
+            continue;
+        };
+
+        let Some(hit) = intersect_mesh(ray, mesh, transform, aabb) else {
+            continue;
+        };
+        if closest.map_or(true, |closest| hit.distance < closest.distance) {
+            closest = Some(PickHit { entity, ..hit });
+        }
+    }
+    closest
+}
+
+/// The closest hit of a ray with a triangle list mesh, the entity is left as a placeholder
+fn intersect_mesh(
+    ray: Ray3d,
+    mesh: &Mesh,
+    transform: &GlobalTransform,
+    aabb: Option<&Aabb>,
+) -> Option<PickHit> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return None;
+    }
+    let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?.as_float3()?;
+
+    // Points along the ray keep their parameter in mesh space, so the
+    // distances found there are world space distances as well
+    let world_to_mesh = transform.compute_matrix().inverse();
+    let origin = world_to_mesh.transform_point3(ray.origin);
+    let direction = world_to_mesh.transform_vector3(*ray.direction);
+
+    if let Some(aabb) = aabb {
+        intersect_aabb(origin, direction, aabb)?;
+    }
+
+    let triangle = |indices: [usize; 3]| {
+        let [a, b, c] = indices.map(|index| positions.get(index).copied().map(Vec3::from));
+        Some([a?, b?, c?])
+    };
+    let triangles: Box<dyn Iterator<Item = [usize; 3]>> = match mesh.indices() {
+        Some(Indices::U16(indices)) => Box::new(
+            indices
+                .chunks_exact(3)
+                .map(|chunk| [chunk[0], chunk[1], chunk[2]].map(usize::from)),
+        ),
+        Some(Indices::U32(indices)) => Box::new(
+            indices
+                .chunks_exact(3)
+                .map(|chunk| [chunk[0], chunk[1], chunk[2]].map(|index| index as usize)),
+        ),
+        None => Box::new(
+            (0..positions.len() / 3).map(|index| [index * 3, index * 3 + 1, index * 3 + 2]),
+        ),
+    };
+
+    let (distance, indices, [a, b, c], [u, v]) = triangles
+        .filter_map(|indices| Some((indices, triangle(indices)?)))
+        .filter_map(|(indices, vertices)| {
+            let (distance, barycentric) = intersect_triangle(origin, direction, vertices)?;
+            Some((distance, indices, vertices, barycentric))
+        })
+        .min_by(|(a, ..), (b, ..)| a.total_cmp(b))?;
+
+    let uv = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+        Some(VertexAttributeValues::Float32x2(uvs)) => {
+            let [uv_a, uv_b, uv_c] = indices.map(|index| uvs.get(index).copied().map(Vec2::from));
+            Some(uv_a? * (1.0 - u - v) + uv_b? * u + uv_c? * v)
+        }
+        _ => None,
+    };
+
+    // Normals transform with the inverse transpose of the model matrix
+    let mut normal = world_to_mesh
+        .transpose()
+        .transform_vector3((b - a).cross(c - a))
+        .normalize_or_zero();
+    if normal.dot(*ray.direction) > 0.0 {
+        normal = -normal;
+    }
+
+    Some(PickHit {
+        entity: Entity::PLACEHOLDER,
+        position: ray.get_point(distance),
+        normal,
+        distance,
+        uv,
+    })
+}
+
+/// Slab test of a ray against a bounding box, returns where the ray enters it
+fn intersect_aabb(origin: Vec3, direction: Vec3, aabb: &Aabb) -> Option<f32> {
+    let min = Vec3::from(aabb.min());
+    let max = Vec3::from(aabb.max());
+    let inverse = direction.recip();
+    let t1 = (min - origin) * inverse;
+    let t2 = (max - origin) * inverse;
+    let near = t1.min(t2).max_element();
+    let far = t1.max(t2).min_element();
+    (far >= near.max(0.0)).then_some(near.max(0.0))
+}
+
+/// Möller–Trumbore intersection of a ray with a triangle, returns the
+/// distance along the ray and the barycentric coordinates of `b` and `c`
+fn intersect_triangle(
+    origin: Vec3,
+    direction: Vec3,
+    [a, b, c]: [Vec3; 3],
+) -> Option<(f32, [f32; 2])> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let inverse = determinant.recip();
+    let s = origin - a;
+    let u = s.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(edge1);
+    let v = direction.dot(q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(q) * inverse;
+    (t > 0.0).then_some((t, [u, v]))
+}
@@ -0,0 +1,324 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::time::Duration;
+
+#[cfg(target_os = "linux")]
+use bevy::render::pipelined_rendering::PipelinedRenderingPlugin;
+use bevy::{
+    app::{PluginGroupBuilder, PluginsState, ScheduleRunnerPlugin},
+    diagnostic::DiagnosticsPlugin,
+    log::LogPlugin,
+    prelude::*,
+    render::RenderPlugin,
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+
+use crate::{
+    action::QmlActionPlugin,
+    asset::{HttpAssetPlugin, QmlAssetsPlugin, QrcAssetPlugin},
+    batch::QmlBatchedComponent,
+    benchmark::QmlBenchmarkPlugin,
+    camera::QmlCameraPlugin,
+    clipboard::QmlClipboardPlugin,
+    commands::QmlCommandsPlugin,
+    declarative::QmlDeclarativePlugin,
+    diagnostics::QmlDiagnosticsPlugin,
+    gizmos::QmlGizmosPlugin,
+    grid::QmlGridPlugin,
+    input::{gamepad::QmlGamepadPlugin, QmlInputPlugin},
+    inspection::QmlInspectionPlugin,
+    label::QmlLabelPlugin,
+    layers::QmlLayersPlugin,
+    log::qt_log_layer,
+    memory::QmlMemoryDiagnosticsPlugin,
+    mini_view::QmlMiniViewPlugin,
+    model::QmlQuerySnapshotPlugin,
+    picking::QmlPickingPlugin,
+    qml_texture::QmlTexturePlugin,
+    redraw::QmlRedrawPlugin,
+    render::{adapter::AdapterSelection, QuickItemRenderPlugin},
+    runtime::{self, FramePacing},
+    selection::QmlSelectionPlugin,
+    task::QmlTaskPlugin,
+    theme::QmlThemePlugin,
+    time_control::QmlTimeControlPlugin,
+    transform_gizmo::QmlTransformGizmoPlugin,
+    undo::QmlUndoPlugin,
+    view_mode::QmlViewModePlugin,
+    window::QmlWindowPlugin,
+};
+
+/// Replaces the winit runner so the Qt event loop drives the Bevy [App]
+///
+/// Calling [App::run] with this plugin returns straight away and leaves the
+/// app hosted by [crate::runtime], which pumps the schedules from a QTimer on
+/// the GUI thread. The QGuiApplication must exist before the app is run.
+///
+/// ```ignore
+/// App::new()
+///     .add_plugins((bevy_qml_default_plugins(), BevyQmlPlugin::default()))
+///     .run();
+/// ```
+///
+/// Headless apps, such as simulations on a server, only need a
+/// QCoreApplication and are built on [bevy_qml_headless_plugins]:
+///
+/// ```ignore
+/// App::new()
+///     .add_plugins((bevy_qml_headless_plugins(), BevyQmlPlugin::headless()))
+///     .run();
+/// ```
+pub struct BevyQmlPlugin {
+    /// How often the Bevy schedules are run, unless they are paced by the
+    /// scene graph
+    pub tick_interval: Duration,
+    /// Whether updates follow the tick interval or the frames of the scene
+    /// graph
+    pub pacing: FramePacing,
+    /// Run `FixedUpdate` at this timestep, however often frames are rendered
+    ///
+    /// Bevy keeps its own default timestep if this is [None].
+    pub fixed_timestep: Option<Duration>,
+    /// Leave out the plugins needing Qt GUI or Bevy's rendering, for apps
+    /// built on [bevy_qml_headless_plugins]
+    pub headless: bool,
+}
+
+impl Default for BevyQmlPlugin {
+    fn default() -> Self {
+        Self {
+            tick_interval: Duration::from_millis(16),
+            pacing: FramePacing::default(),
+            fixed_timestep: None,
+            headless: false,
+        }
+    }
+}
+
+impl BevyQmlPlugin {
+    /// The default settings, for an app without windows
+    pub fn headless() -> Self {
+        Self {
+            headless: true,
+            ..default()
+        }
+    }
+}
+
+impl Plugin for BevyQmlPlugin {
+    fn build(&self, app: &mut App) {
+        let tick_interval = self.tick_interval;
+        let pacing = self.pacing;
+        if let Some(timestep) = self.fixed_timestep {
+            app.insert_resource(Time::<Fixed>::from_duration(timestep));
+        }
+        #[cfg(feature = "hot_logic")]
+        app.add_plugins(crate::logic::QmlLogicPlugin);
+        if self.headless {
+            // What the bridge objects need to reach the world
+            app.add_plugins((
+                QmlCommandsPlugin,
+                QmlDiagnosticsPlugin,
+                QmlMemoryDiagnosticsPlugin::default(),
+                QmlDeclarativePlugin,
+                QmlTimeControlPlugin,
+                QmlLayersPlugin,
+                QmlTaskPlugin,
+                QmlBatchedComponent::<Transform>::default(),
+                QmlQuerySnapshotPlugin,
+                QmlLabelPlugin,
+                QmlUndoPlugin,
+                QmlActionPlugin,
+            ))
+            .set_runner(move |app| qt_runner(app, tick_interval, pacing));
+            return;
+        }
+        // A tuple holds at most 15 plugins
+        app.add_plugins((
+            (
+                QuickItemRenderPlugin,
+                QmlInputPlugin,
+                QmlCommandsPlugin,
+                QmlPickingPlugin,
+                QmlSelectionPlugin,
+                QmlAssetsPlugin,
+                QmlTexturePlugin,
+                QmlCameraPlugin,
+                QmlQuerySnapshotPlugin,
+                QmlLabelPlugin,
+                QmlUndoPlugin,
+                QmlActionPlugin,
+                QmlGamepadPlugin,
+                QmlInspectionPlugin,
+            ),
+            (
+                QmlWindowPlugin,
+                QmlRedrawPlugin,
+                QmlDiagnosticsPlugin,
+                QmlThemePlugin,
+                QmlClipboardPlugin,
+                QmlDeclarativePlugin,
+                QmlGizmosPlugin,
+                QmlTransformGizmoPlugin,
+                QmlGridPlugin,
+                QmlTimeControlPlugin,
+                QmlLayersPlugin,
+                QmlViewModePlugin,
+                QmlTaskPlugin,
+                QmlBatchedComponent::<Transform>::default(),
+                QmlMiniViewPlugin,
+            ),
+            (QmlMemoryDiagnosticsPlugin::default(), QmlBenchmarkPlugin),
+        ))
+        .set_runner(move |app| qt_runner(app, tick_interval, pacing));
+    }
+}
+
+/// Hosts an independent app next to the one of [BevyQmlPlugin], for QML
+/// windows which show a world of their own
+///
+/// Running the app hands it over to [runtime::install_world] under the name,
+/// and `BevyQuickItem`s with that `world` show it. It is updated right after
+/// the main app, which should be running first.
+///
+/// ```ignore
+/// App::new()
+///     .add_plugins((bevy_qml_default_plugins(), QmlWorldPlugin::new("preview")))
+///     .run();
+/// ```
+pub struct QmlWorldPlugin {
+    pub name: String,
+}
+
+impl QmlWorldPlugin {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+impl Plugin for QmlWorldPlugin {
+    fn build(&self, app: &mut App) {
+        let name = self.name.clone();
+        app.add_plugins((
+            QuickItemRenderPlugin,
+            QmlInputPlugin,
+            QmlPickingPlugin,
+            QmlCameraPlugin,
+        ))
+        .set_runner(move |mut app| {
+            finish_plugins(&mut app);
+            runtime::install_world(name, app);
+            AppExit::Success
+        });
+    }
+}
+
+/// The [MinimalPlugins] with transforms, hierarchies, diagnostics and
+/// assets, for headless apps running in a QCoreApplication
+///
+/// Nothing is rendered, so `BevyQuickItem`s show nothing. Like
+/// [bevy_qml_default_plugins] this adds the `qrc://`, `http://` and
+/// `https://` asset sources and forwards the log output to Qt logging.
+pub fn bevy_qml_headless_plugins() -> PluginGroupBuilder {
+    MinimalPlugins
+        .build()
+        .disable::<ScheduleRunnerPlugin>()
+        .add(LogPlugin {
+            custom_layer: qt_log_layer,
+            ..default()
+        })
+        .add(TransformPlugin)
+        .add(HierarchyPlugin)
+        .add(DiagnosticsPlugin)
+        .add(QrcAssetPlugin)
+        .add(HttpAssetPlugin)
+        .add(AssetPlugin::default())
+}
+
+/// The [DefaultPlugins] without winit, as windows and input are provided by Qt
+///
+/// This also adds the `qrc://` asset source from [QrcAssetPlugin] and the
+/// `http://` and `https://` sources from [HttpAssetPlugin], and forwards the
+/// log output to Qt logging with [qt_log_layer]. Bevy renders with the
+/// adapter the environment asks for, see [AdapterSelection::from_env].
+pub fn bevy_qml_default_plugins() -> PluginGroupBuilder {
+    bevy_qml_plugins_with_adapter(&AdapterSelection::from_env())
+}
+
+/// The [bevy_qml_default_plugins] rendering with the adapter of the
+/// selection, e.g. the integrated GPU of a laptop:
+///
+/// ```ignore
+/// App::new()
+///     .add_plugins((
+///         bevy_qml_plugins_with_adapter(&AdapterSelection {
+///             power_preference: PowerPreference::LowPower,
+///             ..default()
+///         }),
+///         BevyQmlPlugin::default(),
+///     ))
+///     .run();
+/// ```
+pub fn bevy_qml_plugins_with_adapter(selection: &AdapterSelection) -> PluginGroupBuilder {
+    let plugins = base_plugins();
+    if *selection == AdapterSelection::default() {
+        return plugins;
+    }
+    plugins.set(RenderPlugin {
+        render_creation: selection.render_creation(),
+        ..default()
+    })
+}
+
+fn base_plugins() -> PluginGroupBuilder {
+    DefaultPlugins
+        .set(LogPlugin {
+            custom_layer: qt_log_layer,
+            ..default()
+        })
+        .set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        })
+        .disable::<WinitPlugin>()
+        .add_before::<AssetPlugin, _>(QrcAssetPlugin)
+        .add_before::<AssetPlugin, _>(HttpAssetPlugin)
+}
+
+/// The [bevy_qml_default_plugins] rendering with OpenGL, in a context which
+/// shares its textures with the scene graph
+///
+/// This is for Linux devices where Qt Quick is stuck on OpenGL, such as older
+/// embedded boards without a Vulkan driver, see
+/// [crate::render::interop::opengl]. Bevy renders on the GUI thread then, and
+/// the QGuiApplication must exist before this is called.
+#[cfg(target_os = "linux")]
+pub fn bevy_qml_opengl_plugins() -> PluginGroupBuilder {
+    base_plugins()
+        .set(RenderPlugin {
+            render_creation: crate::render::interop::opengl::render_creation(),
+            ..default()
+        })
+        .disable::<PipelinedRenderingPlugin>()
+}
+
+fn qt_runner(mut app: App, tick_interval: Duration, pacing: FramePacing) -> AppExit {
+    finish_plugins(&mut app);
+    runtime::install(app, tick_interval, pacing);
+
+    // The app keeps running inside the Qt event loop
+    AppExit::Success
+}
+
+pub(crate) fn finish_plugins(app: &mut App) {
+    if app.plugins_state() != PluginsState::Cleaned {
+        // The render plugin creates the GPU device asynchronously
+        while app.plugins_state() == PluginsState::Adding {
+            bevy::tasks::tick_global_task_pools_on_main_thread();
+        }
+        app.finish();
+        app.cleanup();
+    }
+}
@@ -0,0 +1,438 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! QML scenes rendered into Bevy textures, for interactive panels inside the
+//! world such as cockpit screens or signs.
+//!
+//! An entity with a [QmlTexture] gets the QML file at its `source` rendered
+//! offscreen with QQuickRenderControl into its `image`, which is then used
+//! as the texture of the material of a mesh:
+//!
+//! ```ignore
+//! let texture = QmlTexture::new(&mut images, "qrc:/qml/Screen.qml", UVec2::new(512, 256));
+//! commands.spawn((
+//!     PbrBundle {
+//!         mesh: meshes.add(Rectangle::new(2.0, 1.0)),
+//!         material: materials.add(StandardMaterial {
+//!             base_color_texture: Some(texture.image.clone()),
+//!             unlit: true,
+//!             ..default()
+//!         }),
+//!         ..default()
+//!     },
+//!     texture,
+//! ));
+//! ```
+//!
+//! Clicking, hovering and scrolling the mesh in a `BevyQuickItem` goes to the
+//! QML scene instead of Bevy, at the texture coordinates of the picked point,
+//! and a clicked panel receives the keyboard until something else is clicked.
+//! The scene lives on the GUI thread, so it is only rendered after updates.
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_qml_texture")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qurl.h");
+        /// An alias to the QUrl type
+        type QUrl = cxx_qt_lib::QUrl;
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/qmltexture.h");
+        /// Renders a QML scene offscreen
+        type QmlTextureRenderer;
+
+        #[cxx_name = "errorString"]
+        fn error_string(self: &QmlTextureRenderer) -> QString;
+
+        fn resize(self: Pin<&mut QmlTextureRenderer>, width: u32, height: u32);
+
+        fn render(self: Pin<&mut QmlTextureRenderer>, pixels: &mut Vec<u8>) -> bool;
+
+        #[cxx_name = "sendMouse"]
+        fn send_mouse(
+            self: Pin<&mut QmlTextureRenderer>,
+            event_type: i32,
+            x: f64,
+            y: f64,
+            button: i32,
+            buttons: u32,
+            modifiers: u32,
+        );
+
+        #[cxx_name = "sendWheel"]
+        fn send_wheel(
+            self: Pin<&mut QmlTextureRenderer>,
+            x: f64,
+            y: f64,
+            angle_x: i32,
+            angle_y: i32,
+            modifiers: u32,
+        );
+
+        #[cxx_name = "sendKey"]
+        fn send_key(
+            self: Pin<&mut QmlTextureRenderer>,
+            pressed: bool,
+            key: i32,
+            modifiers: u32,
+            text: &QString,
+            auto_repeat: bool,
+        );
+
+        #[cxx_name = "setFocused"]
+        fn set_focused(self: Pin<&mut QmlTextureRenderer>, focused: bool);
+
+        #[doc(hidden)]
+        #[rust_name = "qml_texture_renderer_new"]
+        fn qmlTextureRendererNew(
+            source: &QUrl,
+            width: u32,
+            height: u32,
+        ) -> UniquePtr<QmlTextureRenderer>;
+    }
+}
+
+use std::cell::{Cell, RefCell};
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension},
+    },
+    utils::HashMap,
+};
+use cxx::UniquePtr;
+use cxx_qt_lib::{QString, QUrl};
+
+use crate::{
+    input::keyboard::QtKey,
+    picking::PickHit,
+    render::TARGET_FORMAT,
+    runtime::{self, UpdateListener},
+};
+
+/// QEvent::MouseButtonPress
+pub const MOUSE_BUTTON_PRESS: i32 = 2;
+/// QEvent::MouseButtonRelease
+pub const MOUSE_BUTTON_RELEASE: i32 = 3;
+/// QEvent::MouseButtonDblClick
+pub const MOUSE_BUTTON_DOUBLE_CLICK: i32 = 4;
+/// QEvent::MouseMove
+pub const MOUSE_MOVE: i32 = 5;
+
+/// A QML scene rendered into an image
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+pub struct QmlTexture {
+    /// The URL of the QML file, its root item is resized to fill the texture
+    pub source: String,
+    /// The size of the texture in pixels
+    pub size: UVec2,
+    /// Filled with the rendered scene, to be used as the texture of a material
+    pub image: Handle<Image>,
+    /// Whether pointer and keyboard input on the mesh goes to the scene
+    pub interactive: bool,
+}
+
+impl QmlTexture {
+    /// Create a transparent image for the scene and add it to the assets
+    pub fn new(images: &mut Assets<Image>, source: impl Into<String>, size: UVec2) -> Self {
+        let size = size.max(UVec2::ONE);
+        let image = Image::new_fill(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0; 4],
+            TARGET_FORMAT,
+            RenderAssetUsages::default(),
+        );
+        Self {
+            source: source.into(),
+            size,
+            image: images.add(image),
+            interactive: true,
+        }
+    }
+}
+
+pub struct QmlTexturePlugin;
+
+impl Plugin for QmlTexturePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<QmlTexture>()
+            .insert_non_send_resource(QmlTextureListener(runtime::on_update(sync_panels)));
+    }
+}
+
+/// Renders the scenes after every update for as long as the app exists
+struct QmlTextureListener(#[allow(dead_code)] UpdateListener);
+
+struct Panel {
+    source: String,
+    size: UVec2,
+    interactive: bool,
+    renderer: UniquePtr<ffi::QmlTextureRenderer>,
+    /// The last position of the pointer on the texture, in pixels
+    pointer: Vec2,
+}
+
+thread_local! {
+    static PANELS: RefCell<HashMap<Entity, Panel>> = RefCell::new(HashMap::default());
+    /// The panel receiving the pointer while a button is held
+    static GRAB: Cell<Option<Entity>> = const { Cell::new(None) };
+    /// The panel receiving the keyboard
+    static FOCUS: Cell<Option<Entity>> = const { Cell::new(None) };
+}
+
+/// Whether any panel takes input, so views only pick for panels when needed
+pub fn has_interactive_panels() -> bool {
+    PANELS.with(|panels| {
+        panels
+            .try_borrow()
+            .is_ok_and(|panels| panels.values().any(|panel| panel.interactive))
+    })
+}
+
+/// Send a mouse event to the panel under the pointer, or to the one holding
+/// the pointer while a button is pressed
+///
+/// Pressing a button on a panel gives it the keyboard, and pressing anywhere
+/// else takes it away again. Returns whether a panel took the event, in which
+/// case it should not reach Bevy.
+pub fn route_mouse(
+    hit: Option<&PickHit>,
+    event_type: i32,
+    button: i32,
+    buttons: u32,
+    modifiers: u32,
+) -> bool {
+    let target = GRAB.get().or_else(|| panel_hit(hit));
+    if event_type == MOUSE_BUTTON_PRESS || event_type == MOUSE_BUTTON_DOUBLE_CLICK {
+        set_focus(target);
+    }
+    let Some(target) = target else {
+        return false;
+    };
+
+    let delivered = with_panel(target, hit, |panel, position| {
+        runtime::request_update();
+        panel.renderer.pin_mut().send_mouse(
+            event_type,
+            position.x.into(),
+            position.y.into(),
+            button,
+            buttons,
+            modifiers,
+        );
+    });
+    match event_type {
+        MOUSE_BUTTON_PRESS | MOUSE_BUTTON_DOUBLE_CLICK => GRAB.set(Some(target)),
+        MOUSE_BUTTON_RELEASE if buttons == 0 => GRAB.set(None),
+        _ => {}
+    }
+    delivered
+}
+
+/// Send the mouse wheel to the panel under the pointer
+pub fn route_wheel(hit: Option<&PickHit>, angle_delta: IVec2, modifiers: u32) -> bool {
+    let Some(target) = GRAB.get().or_else(|| panel_hit(hit)) else {
+        return false;
+    };
+    with_panel(target, hit, |panel, position| {
+        runtime::request_update();
+        panel.renderer.pin_mut().send_wheel(
+            position.x.into(),
+            position.y.into(),
+            angle_delta.x,
+            angle_delta.y,
+            modifiers,
+        );
+    })
+}
+
+/// Send a key to the panel with the keyboard, returns whether there is one
+pub fn route_key(key: &QtKey, pressed: bool) -> bool {
+    let Some(target) = FOCUS.get() else {
+        return false;
+    };
+    with_panel(target, None, |panel, _| {
+        runtime::request_update();
+        panel.renderer.pin_mut().send_key(
+            pressed,
+            key.key,
+            key.modifiers,
+            &QString::from(key.text.as_str()),
+            key.auto_repeat,
+        );
+    })
+}
+
+/// The interactive panel that was hit
+fn panel_hit(hit: Option<&PickHit>) -> Option<Entity> {
+    let hit = hit.filter(|hit| hit.uv.is_some())?;
+    PANELS.with(|panels| {
+        let panels = panels.try_borrow().ok()?;
+        panels
+            .get(&hit.entity)
+            .is_some_and(|panel| panel.interactive)
+            .then_some(hit.entity)
+    })
+}
+
+fn set_focus(target: Option<Entity>) {
+    let focus = FOCUS.get();
+    if focus == target {
+        return;
+    }
+    FOCUS.set(target);
+    for (entity, focused) in [(focus, false), (target, true)] {
+        if let Some(entity) = entity {
+            with_panel(entity, None, |panel, _| {
+                panel.renderer.pin_mut().set_focused(focused);
+            });
+        }
+    }
+}
+
+/// Run the closure with a panel and the position of the pointer on it
+///
+/// The position is taken from the hit if it is on the panel, and is the
+/// last known one otherwise.
+fn with_panel(entity: Entity, hit: Option<&PickHit>, f: impl FnOnce(&mut Panel, Vec2)) -> bool {
+    PANELS.with(|panels| {
+        let Ok(mut panels) = panels.try_borrow_mut() else {
+            return false;
+        };
+        let Some(panel) = panels.get_mut(&entity) else {
+            return false;
+        };
+        if panel.renderer.is_null() {
+            return false;
+        }
+        if let Some(uv) = hit
+            .filter(|hit| hit.entity == entity)
+            .and_then(|hit| hit.uv)
+        {
+            panel.pointer = uv * panel.size.as_vec2();
+        }
+        let position = panel.pointer;
+        f(panel, position);
+        true
+    })
+}
+
+/// Follow the [QmlTexture] components and copy newly rendered frames into
+/// their images
+fn sync_panels() {
+    let Some(textures) = runtime::with_world(|world| {
+        world
+            .query::<(Entity, &QmlTexture)>()
+            .iter(world)
+            .map(|(entity, texture)| (entity, texture.clone()))
+            .collect::<Vec<_>>()
+    }) else {
+        return;
+    };
+
+    // QML runs bindings and signal handlers while rendering, which may reach
+    // back into the world, so the world is not borrowed here
+    let frames: Vec<(Handle<Image>, UVec2, Vec<u8>)> = PANELS.with(|panels| {
+        let Ok(mut panels) = panels.try_borrow_mut() else {
+            return Vec::new();
+        };
+        panels.retain(|entity, _| textures.iter().any(|(other, _)| other == entity));
+
+        let mut frames = Vec::new();
+        for (entity, texture) in textures {
+            let size = texture.size.max(UVec2::ONE);
+            let panel = panels
+                .entry(entity)
+                .and_modify(|panel| {
+                    if panel.source != texture.source {
+                        *panel = Panel::new(&texture.source, size);
+                    }
+                })
+                .or_insert_with(|| Panel::new(&texture.source, size));
+            panel.interactive = texture.interactive;
+            if panel.renderer.is_null() {
+                continue;
+            }
+            if panel.size != size {
+                panel.size = size;
+                panel.renderer.pin_mut().resize(size.x, size.y);
+            }
+
+            let mut pixels = Vec::new();
+            if panel.renderer.pin_mut().render(&mut pixels) {
+                frames.push((texture.image.clone(), size, pixels));
+            }
+        }
+        frames
+    });
+
+    // Forget the grab and focus of panels which are gone
+    for state in [&GRAB, &FOCUS] {
+        state.with(|state| {
+            let gone = state.get().is_some_and(|entity| {
+                PANELS.with(|panels| {
+                    panels
+                        .try_borrow()
+                        .is_ok_and(|panels| !panels.contains_key(&entity))
+                })
+            });
+            if gone {
+                state.set(None);
+            }
+        });
+    }
+
+    if frames.is_empty() {
+        return;
+    }
+    // The new frames are only seen by the renderer in the next update
+    runtime::request_update();
+    runtime::with_world(|world| {
+        let mut images = world.resource_mut::<Assets<Image>>();
+        for (handle, size, pixels) in frames {
+            let Some(image) = images.get_mut(&handle) else {
+                continue;
+            };
+            if image.size() != size {
+                image.resize(Extent3d {
+                    width: size.x,
+                    height: size.y,
+                    depth_or_array_layers: 1,
+                });
+            }
+            if image.data.len() == pixels.len() {
+                image.data = pixels;
+            }
+        }
+    });
+}
+
+impl Panel {
+    fn new(source: &str, size: UVec2) -> Self {
+        let mut renderer = ffi::qml_texture_renderer_new(&QUrl::from(source), size.x, size.y);
+        let error = renderer.error_string().to_string();
+        if !error.is_empty() {
+            warn!("Failed to render {source} into a texture: {error}");
+            renderer = UniquePtr::null();
+        }
+        Self {
+            source: source.to_owned(),
+            size,
+            interactive: true,
+            renderer,
+            pointer: Vec2::ZERO,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Rendering on demand, for applications such as CAD or visualization tools
+//! which only change in response to the user.
+//!
+//! With [UpdateMode::OnDemand] the app is only updated, and thus rendered,
+//! when something asks for it:
+//!
+//! - QML elements changing the world or a `BevyQuickItem` receiving input,
+//!   resizing or having `requestUpdate()` called,
+//! - [crate::runtime::request_update] from the GUI thread,
+//! - a [RequestRedraw] event sent by a system,
+//! - the previous update changing what is shown, such as moving an entity or
+//!   a camera, or loading a mesh, material or image.
+//!
+//! Updates also keep running while assets loaded from QML are loading.
+//! Systems animating the scene over time, or waiting for assets loaded
+//! elsewhere, keep sending [RequestRedraw] for as long as they run.
+//!
+//! ```ignore
+//! app.insert_resource(UpdateMode::OnDemand);
+//! ```
+//!
+//! Independently of that, [HiddenWindowPolicy] decides what happens while
+//! every window showing a `BevyQuickItem` is hidden or minimized.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, render::view::VisibilitySystems, window::RequestRedraw};
+
+use crate::asset::QmlAssets;
+
+/// When the hosted app is updated
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Resource, Default)]
+pub enum UpdateMode {
+    /// Update at every tick or frame, see [crate::runtime::FramePacing]
+    #[default]
+    Continuous,
+    /// Only update when the scene changed or an update was requested
+    OnDemand,
+}
+
+/// How the hosted app is updated while none of its windows can be seen
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Resource, Default)]
+pub enum HiddenWindowPolicy {
+    /// Keep updating as usual, e.g. for simulations which must not stall
+    KeepRunning,
+    /// Update at most once per interval
+    Throttle { interval: Duration },
+    /// Stop updating until a window is shown again
+    #[default]
+    Pause,
+}
+
+pub struct QmlRedrawPlugin;
+
+impl Plugin for QmlRedrawPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<UpdateMode>()
+            .register_type::<HiddenWindowPolicy>()
+            .init_resource::<UpdateMode>()
+            .init_resource::<HiddenWindowPolicy>()
+            .add_systems(
+                PostUpdate,
+                request_redraw_on_changes
+                    .after(VisibilitySystems::CheckVisibility)
+                    .run_if(resource_equals(UpdateMode::OnDemand)),
+            );
+    }
+}
+
+type SceneChanged = Or<(
+    Changed<GlobalTransform>,
+    Changed<Camera>,
+    Changed<Projection>,
+    Changed<OrthographicProjection>,
+    Changed<PerspectiveProjection>,
+    Changed<Visibility>,
+    Changed<Handle<Mesh>>,
+    Changed<Handle<StandardMaterial>>,
+)>;
+
+/// Ask for another update if this one changed what cameras show
+#[allow(clippy::too_many_arguments)]
+fn request_redraw_on_changes(
+    changed: Query<(), SceneChanged>,
+    mut removed_meshes: RemovedComponents<Handle<Mesh>>,
+    mut removed_transforms: RemovedComponents<GlobalTransform>,
+    mut images: EventReader<AssetEvent<Image>>,
+    mut meshes: EventReader<AssetEvent<Mesh>>,
+    mut materials: EventReader<AssetEvent<StandardMaterial>>,
+    qml_assets: Option<Res<QmlAssets>>,
+    mode: Res<UpdateMode>,
+    mut redraw: EventWriter<RequestRedraw>,
+) {
+    let removed = removed_meshes.read().count() + removed_transforms.read().count() > 0;
+    let assets = images.read().count() + meshes.read().count() + materials.read().count() > 0
+        || qml_assets.is_some_and(|assets| assets.progress().is_loading());
+    // Switching to on demand shows the last state of the scene
+    if mode.is_changed() || removed || assets || !changed.is_empty() {
+        redraw.send(RequestRedraw);
+    }
+}
@@ -0,0 +1,266 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Choosing the GPU adapter and the graphics API Bevy renders with.
+//!
+//! Laptops with an integrated and a discrete GPU make the choice matter: the
+//! scene graph usually renders on the GPU driving the screen, and frames can
+//! only be shared without copying when Bevy renders on the same one, see
+//! [crate::render::interop]. An [AdapterSelection] picks the adapter by power
+//! preference or by its index in [available_adapters], and tells whether a
+//! software renderer such as llvmpipe or WARP will do. The environment can
+//! override it without rebuilding the app, see [AdapterSelection::with_env]:
+//!
+//! ```sh
+//! BEVYQML_BACKEND=vulkan BEVYQML_POWER_PREFERENCE=low ./app
+//! ```
+//!
+//! The adapter Bevy selected is the [RenderAdapterInfo] resource of the main
+//! world, and `GraphicsAdapterBridge` shows it in QML.
+//!
+//! [RenderAdapterInfo]: bevy::render::renderer::RenderAdapterInfo
+
+use std::sync::{Arc, OnceLock};
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::WgpuWrapper,
+        renderer::{RenderAdapter, RenderAdapterInfo, RenderDevice, RenderInstance, RenderQueue},
+        settings::{Backends, PowerPreference, RenderCreation, WgpuSettings},
+    },
+    tasks::block_on,
+};
+use wgpu::DeviceType;
+
+/// Overrides [AdapterSelection::backends] with a comma separated list of
+/// `vulkan`, `dx12`, `metal` and `gl`
+pub const BACKEND_VAR: &str = "BEVYQML_BACKEND";
+/// Overrides [AdapterSelection::power_preference] with `low`, `high` or `none`
+pub const POWER_PREFERENCE_VAR: &str = "BEVYQML_POWER_PREFERENCE";
+/// Overrides [AdapterSelection::adapter_index]
+pub const ADAPTER_VAR: &str = "BEVYQML_ADAPTER";
+/// Overrides [AdapterSelection::allow_software] with `1` or `0`
+pub const ALLOW_SOFTWARE_VAR: &str = "BEVYQML_ALLOW_SOFTWARE";
+
+/// Which adapter Bevy renders with, see [AdapterSelection::render_creation]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdapterSelection {
+    /// The graphics APIs to choose from, those wgpu picks by default if
+    /// [None]
+    pub backends: Option<Backends>,
+    /// Whether to prefer the discrete GPU or the integrated one
+    pub power_preference: PowerPreference,
+    /// Render with the adapter at this index of [available_adapters],
+    /// whatever the power preference
+    pub adapter_index: Option<usize>,
+    /// Whether a software renderer may be picked
+    pub allow_software: bool,
+}
+
+impl Default for AdapterSelection {
+    /// The choice Bevy makes on its own
+    fn default() -> Self {
+        Self {
+            backends: None,
+            power_preference: PowerPreference::HighPerformance,
+            adapter_index: None,
+            allow_software: true,
+        }
+    }
+}
+
+impl AdapterSelection {
+    /// The default selection with the overrides of the environment
+    pub fn from_env() -> Self {
+        Self::default().with_env()
+    }
+
+    /// Apply the overrides of the environment, such as [BACKEND_VAR]
+    ///
+    /// Values which cannot be parsed are logged and left out.
+    pub fn with_env(mut self) -> Self {
+        if let Ok(value) = std::env::var(BACKEND_VAR) {
+            let backends = wgpu::util::parse_backends_from_comma_list(&value);
+            if backends.is_empty() {
+                warn!("{BACKEND_VAR}={value:?} names no graphics API, ignoring it");
+            } else {
+                self.backends = Some(backends);
+            }
+        }
+        if let Ok(value) = std::env::var(POWER_PREFERENCE_VAR) {
+            match value.to_lowercase().as_str() {
+                "low" => self.power_preference = PowerPreference::LowPower,
+                "high" => self.power_preference = PowerPreference::HighPerformance,
+                "none" => self.power_preference = PowerPreference::None,
+                _ => warn!("{POWER_PREFERENCE_VAR}={value:?} is not low, high or none"),
+            }
+        }
+        if let Ok(value) = std::env::var(ADAPTER_VAR) {
+            match value.parse() {
+                Ok(index) => self.adapter_index = Some(index),
+                Err(_) => warn!("{ADAPTER_VAR}={value:?} is not an adapter index"),
+            }
+        }
+        if let Ok(value) = std::env::var(ALLOW_SOFTWARE_VAR) {
+            self.allow_software = !matches!(value.as_str(), "" | "0" | "false");
+        }
+        self
+    }
+
+    /// How the [bevy::render::RenderPlugin] creates its renderer
+    ///
+    /// Bevy picks the adapter itself unless an index is asked for or
+    /// software renderers are ruled out. If no adapter fits, Bevy picks one
+    /// itself as well.
+    pub fn render_creation(&self) -> RenderCreation {
+        let mut settings = WgpuSettings {
+            power_preference: self.power_preference,
+            ..default()
+        };
+        if let Some(backends) = self.backends {
+            settings.backends = Some(backends);
+        }
+        if self.adapter_index.is_none() && self.allow_software {
+            return RenderCreation::Automatic(settings);
+        }
+
+        match self.create_renderer(&settings) {
+            Some((device, queue, adapter_info, adapter, instance)) => {
+                RenderCreation::Manual(device, queue, adapter_info, adapter, instance)
+            }
+            None => {
+                warn!("No GPU adapter fits {self:?}, letting Bevy pick one");
+                RenderCreation::Automatic(settings)
+            }
+        }
+    }
+
+    /// Whether the adapter may be picked at all
+    fn allows(&self, info: &wgpu::AdapterInfo) -> bool {
+        let backend = Backends::from(info.backend);
+        self.backends
+            .map_or(true, |backends| backends.contains(backend))
+            && (self.allow_software || info.device_type != DeviceType::Cpu)
+    }
+
+    fn create_renderer(
+        &self,
+        settings: &WgpuSettings,
+    ) -> Option<(
+        RenderDevice,
+        RenderQueue,
+        RenderAdapterInfo,
+        RenderAdapter,
+        RenderInstance,
+    )> {
+        let instance = new_instance(settings);
+        let mut adapters: Vec<_> = instance
+            .enumerate_adapters(Backends::all())
+            .into_iter()
+            .filter(|adapter| self.allows(&adapter.get_info()))
+            .collect();
+        let adapter = match self.adapter_index {
+            Some(index) => {
+                let wanted = available_adapters().get(index)?;
+                adapters
+                    .into_iter()
+                    .find(|adapter| same_adapter(&adapter.get_info(), wanted))?
+            }
+            None => {
+                adapters.sort_by_key(|adapter| {
+                    device_type_rank(adapter.get_info().device_type, self.power_preference)
+                });
+                adapters.into_iter().next()?
+            }
+        };
+
+        // The features and limits Bevy asks for by default
+        let info = adapter.get_info();
+        let mut features = adapter.features();
+        if info.device_type == DeviceType::DiscreteGpu {
+            features -= wgpu::Features::MAPPABLE_PRIMARY_BUFFERS;
+        }
+        let (device, queue) = block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("bevy_qml_selected_device"),
+                required_features: features,
+                required_limits: adapter.limits(),
+            },
+            None,
+        ))
+        .ok()?;
+
+        info!("Bevy renders with {} on {:?}", info.name, info.backend);
+        Some((
+            RenderDevice::from(device),
+            RenderQueue(Arc::new(WgpuWrapper::new(queue))),
+            RenderAdapterInfo(WgpuWrapper::new(info)),
+            RenderAdapter(Arc::new(WgpuWrapper::new(adapter))),
+            RenderInstance(Arc::new(WgpuWrapper::new(instance))),
+        ))
+    }
+}
+
+fn new_instance(settings: &WgpuSettings) -> wgpu::Instance {
+    wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: settings.backends.unwrap_or(Backends::all()),
+        flags: settings.instance_flags,
+        dx12_shader_compiler: settings.dx12_shader_compiler.clone(),
+        gles_minor_version: settings.gles3_minor_version,
+    })
+}
+
+/// Orders adapters by how well they fit the power preference, lowest first
+fn device_type_rank(device_type: DeviceType, preference: PowerPreference) -> u8 {
+    match (preference, device_type) {
+        (PowerPreference::LowPower, DeviceType::IntegratedGpu) => 0,
+        (PowerPreference::LowPower, DeviceType::DiscreteGpu) => 1,
+        (PowerPreference::HighPerformance, DeviceType::DiscreteGpu) => 0,
+        (PowerPreference::HighPerformance, DeviceType::IntegratedGpu) => 1,
+        (PowerPreference::None, DeviceType::IntegratedGpu | DeviceType::DiscreteGpu) => 0,
+        (_, DeviceType::VirtualGpu) => 2,
+        (_, DeviceType::Other) => 3,
+        (_, DeviceType::Cpu) => 4,
+    }
+}
+
+fn same_adapter(a: &wgpu::AdapterInfo, b: &wgpu::AdapterInfo) -> bool {
+    a.backend == b.backend && a.vendor == b.vendor && a.device == b.device && a.name == b.name
+}
+
+/// Every adapter of the system, for every graphics API
+///
+/// [AdapterSelection::adapter_index] indexes into this list, which is only
+/// enumerated once.
+pub fn available_adapters() -> &'static [wgpu::AdapterInfo] {
+    static ADAPTERS: OnceLock<Vec<wgpu::AdapterInfo>> = OnceLock::new();
+    ADAPTERS.get_or_init(|| {
+        new_instance(&WgpuSettings {
+            backends: Some(Backends::all()),
+            ..default()
+        })
+        .enumerate_adapters(Backends::all())
+        .iter()
+        .map(wgpu::Adapter::get_info)
+        .collect()
+    })
+}
+
+/// The index of the adapter into [available_adapters]
+pub fn adapter_index(info: &wgpu::AdapterInfo) -> Option<usize> {
+    available_adapters()
+        .iter()
+        .position(|available| same_adapter(available, info))
+}
+
+/// A name for the graphics API of the backend, as shown in QML
+pub fn backend_name(backend: wgpu::Backend) -> &'static str {
+    match backend {
+        wgpu::Backend::Vulkan => "Vulkan",
+        wgpu::Backend::Dx12 => "Direct3D 12",
+        wgpu::Backend::Metal => "Metal",
+        wgpu::Backend::Gl => "OpenGL",
+        wgpu::Backend::BrowserWebGpu => "WebGPU",
+        wgpu::Backend::Empty => "None",
+    }
+}
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Frames read back from a render target on request, for screenshots and
+//! recordings, whichever way the target reaches Qt.
+
+use bevy::prelude::*;
+
+use super::{readback::FrameReadback, Frame, FrameSink};
+
+/// Reads back every frame rendered into an image until it is stopped
+///
+/// The frames arrive after the updates following [FrameCapture::start], and
+/// only the latest one is kept until it is taken.
+pub struct FrameCapture {
+    entity: Entity,
+    sink: FrameSink,
+}
+
+impl FrameCapture {
+    pub fn start(world: &mut World, image: Handle<Image>) -> Self {
+        let sink = FrameSink::default();
+        let entity = world
+            .spawn(FrameReadback {
+                image,
+                sink: sink.clone(),
+            })
+            .id();
+        Self { entity, sink }
+    }
+
+    /// The latest frame read back since the last call, if there is one
+    pub fn take(&self) -> Option<Frame> {
+        self.sink.take()
+    }
+
+    /// Stop reading back frames
+    pub fn stop(self, world: &mut World) {
+        world.despawn(self.entity);
+    }
+}
@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Loss of the GPU device Bevy renders with.
+//!
+//! Devices are lost when the driver resets the GPU after a hang or an
+//! update, when a remote desktop session takes the GPU away, or when the
+//! adapter goes away. Every use of the device fails from then on, which wgpu
+//! reports by panicking in every frame. [DeviceLossPlugin] records the loss
+//! in [DeviceLoss] instead, and [crate::runtime] tears the app down rather
+//! than updating it, and builds it anew with a new device where it can, see
+//! [crate::runtime::rebuild_with].
+
+use std::sync::{Arc, Mutex};
+
+use bevy::{prelude::*, render::renderer::RenderDevice};
+use wgpu::DeviceLostReason;
+
+/// Why the GPU device of the app was lost, once it was
+#[derive(Resource, Clone, Default)]
+pub struct DeviceLoss(Arc<Mutex<Option<String>>>);
+
+impl DeviceLoss {
+    pub fn reason(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Why the GPU device of the app in the world was lost, if it was
+pub fn lost_reason(world: &World) -> Option<String> {
+    world.get_resource::<DeviceLoss>()?.reason()
+}
+
+/// Watches the [RenderDevice] of the app for its loss, see [DeviceLoss]
+pub struct DeviceLossPlugin;
+
+impl Plugin for DeviceLossPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DeviceLoss>();
+    }
+
+    fn finish(&self, app: &mut App) {
+        // The renderer has started by now
+        let Some(device) = app.world().get_resource::<RenderDevice>() else {
+            return;
+        };
+        watch(device, app.world().resource::<DeviceLoss>().clone());
+    }
+}
+
+fn watch(device: &RenderDevice, loss: DeviceLoss) {
+    let lost = loss.clone();
+    device
+        .wgpu_device()
+        .set_device_lost_callback(move |reason, message| {
+            // Dropping the app drops its device as well
+            if matches!(
+                reason,
+                DeviceLostReason::Dropped | DeviceLostReason::ReplacedCallback
+            ) {
+                return;
+            }
+            error!("The GPU device of Bevy was lost ({reason:?}): {message}");
+            lost.0
+                .lock()
+                .unwrap()
+                .get_or_insert_with(|| format!("{message} ({reason:?})"));
+        });
+
+    // Errors are expected once the device is gone, until the app is torn down
+    device
+        .wgpu_device()
+        .on_uncaptured_error(Box::new(move |error| {
+            if loss.reason().is_some() {
+                debug!("wgpu error after the device was lost: {error}");
+            } else {
+                panic!("wgpu error: {error}");
+            }
+        }));
+}
@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Scaling the render target of a `BevyQuickItem` down while frames take
+//! too long, to keep the UI responsive on weak GPUs.
+
+use bevy::prelude::*;
+
+/// Gaps between updates longer than this are taken for idle time of apps
+/// updated on demand rather than slow frames, in milliseconds
+const IDLE_GAP_MS: f32 = 250.0;
+
+/// How many frames the scale is kept after it changed, so the frame time can
+/// follow before it is judged again
+const SETTLE_FRAMES: u32 = 30;
+
+/// Scales the render target of the item down while its frames take longer
+/// than the target frame time, and back up once they are fast again
+///
+/// The item renders at [DynamicResolution::scale] times the size it would
+/// otherwise have, and the scene graph stretches the frames over the item
+/// with linear filtering. The scale moves in steps of a twentieth, so a
+/// [super::RenderTargetPool] can hand back the images of earlier steps.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct DynamicResolution {
+    /// The frame time to keep below, in milliseconds
+    pub target_frame_ms: f32,
+    /// The smallest scale the target is rendered at
+    pub min_scale: f32,
+    scale: f32,
+    /// The frame time smoothed over the last frames, in milliseconds
+    smoothed_ms: Option<f32>,
+    /// Frames left before the scale is judged again
+    settling: u32,
+}
+
+impl Default for DynamicResolution {
+    fn default() -> Self {
+        Self::new(1000.0 / 60.0)
+    }
+}
+
+impl DynamicResolution {
+    pub fn new(target_frame_ms: f32) -> Self {
+        Self {
+            target_frame_ms,
+            min_scale: 0.5,
+            scale: 1.0,
+            smoothed_ms: None,
+            settling: 0,
+        }
+    }
+
+    /// The scale the target is rendered at right now, at most one
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Take the duration of the last frame into account
+    ///
+    /// Frames slower than the target by a margin scale the target down by a
+    /// tenth, and frames close to the target scale it up by a twentieth. The
+    /// margins keep the scale from flipping back and forth around the
+    /// target, including when frames wait for vsync.
+    pub fn record_frame(&mut self, frame_ms: f32) {
+        if !frame_ms.is_finite() || frame_ms <= 0.0 || frame_ms > IDLE_GAP_MS {
+            return;
+        }
+        let smoothed = match self.smoothed_ms {
+            Some(smoothed) => smoothed + (frame_ms - smoothed) * 0.1,
+            None => frame_ms,
+        };
+        self.smoothed_ms = Some(smoothed);
+        if self.settling > 0 {
+            self.settling -= 1;
+            return;
+        }
+
+        let target = self.target_frame_ms.max(1.0);
+        let min_scale = self.min_scale.clamp(0.05, 1.0);
+        let scale = if smoothed > target * 1.15 {
+            self.scale - 0.1
+        } else if smoothed < target * 1.05 {
+            self.scale + 0.05
+        } else {
+            return;
+        };
+        let scale = ((scale * 20.0).round() / 20.0).clamp(min_scale, 1.0);
+        if scale != self.scale {
+            self.scale = scale;
+            self.settling = SETTLE_FRAMES;
+        }
+    }
+}
+
+/// Feed the duration of every frame to the dynamic resolution of the items
+pub(super) fn adjust_dynamic_resolution(
+    time: Res<Time<Real>>,
+    mut resolutions: Query<&mut DynamicResolution>,
+) {
+    let frame_ms = time.delta_seconds() * 1000.0;
+    for mut resolution in &mut resolutions {
+        resolution.record_frame(frame_ms);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET_MS: f32 = 1000.0 / 60.0;
+
+    fn record(resolution: &mut DynamicResolution, frame_ms: f32, frames: u32) {
+        for _ in 0..frames {
+            resolution.record_frame(frame_ms);
+        }
+    }
+
+    #[test]
+    fn keeps_full_scale_while_fast() {
+        let mut resolution = DynamicResolution::new(TARGET_MS);
+        record(&mut resolution, 8.0, 100);
+        assert_eq!(resolution.scale(), 1.0);
+    }
+
+    #[test]
+    fn scales_down_then_settles() {
+        let mut resolution = DynamicResolution::new(TARGET_MS);
+        resolution.record_frame(30.0);
+        assert_eq!(resolution.scale(), 0.9);
+        // Judged again only after the settle frames
+        record(&mut resolution, 30.0, SETTLE_FRAMES);
+        assert_eq!(resolution.scale(), 0.9);
+        resolution.record_frame(30.0);
+        assert_eq!(resolution.scale(), 0.8);
+    }
+
+    #[test]
+    fn scales_back_up_once_fast() {
+        let mut resolution = DynamicResolution::new(TARGET_MS);
+        resolution.record_frame(30.0);
+        record(&mut resolution, 8.0, SETTLE_FRAMES + 1);
+        assert_eq!(resolution.scale(), 0.95);
+        record(&mut resolution, 8.0, SETTLE_FRAMES + 1);
+        assert_eq!(resolution.scale(), 1.0);
+        record(&mut resolution, 8.0, 100);
+        assert_eq!(resolution.scale(), 1.0);
+    }
+
+    #[test]
+    fn holds_within_the_margins() {
+        let mut resolution = DynamicResolution::new(TARGET_MS);
+        resolution.record_frame(30.0);
+        record(&mut resolution, TARGET_MS * 1.1, 200);
+        assert_eq!(resolution.scale(), 0.9);
+    }
+
+    #[test]
+    fn smooths_single_slow_frames() {
+        let mut resolution = DynamicResolution::new(TARGET_MS);
+        record(&mut resolution, 10.0, 10);
+        // Smoothed to 12ms, which is still fast
+        resolution.record_frame(30.0);
+        assert_eq!(resolution.scale(), 1.0);
+        record(&mut resolution, 30.0, 4);
+        assert_eq!(resolution.scale(), 1.0);
+        // Until the slow frames pull the smoothed time over the margin
+        resolution.record_frame(30.0);
+        assert_eq!(resolution.scale(), 0.9);
+    }
+
+    #[test]
+    fn ignores_idle_gaps_and_bad_durations() {
+        let mut resolution = DynamicResolution::new(TARGET_MS);
+        for frame_ms in [IDLE_GAP_MS + 1.0, 0.0, -5.0, f32::NAN, f32::INFINITY] {
+            resolution.record_frame(frame_ms);
+        }
+        assert_eq!(resolution, DynamicResolution::new(TARGET_MS));
+    }
+
+    #[test]
+    fn stops_at_the_min_scale() {
+        let mut resolution = DynamicResolution::new(TARGET_MS);
+        resolution.min_scale = 0.7;
+        record(&mut resolution, 30.0, 1000);
+        assert_eq!(resolution.scale(), 0.7);
+
+        // Even a min scale of zero keeps something to render
+        let mut resolution = DynamicResolution::new(TARGET_MS);
+        resolution.min_scale = 0.0;
+        record(&mut resolution, 100.0, 1000);
+        assert_eq!(resolution.scale(), 0.05);
+    }
+}
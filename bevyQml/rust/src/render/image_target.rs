@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Cameras rendering into named offscreen images which QML shows through the
+//! `image://bevy/` image provider.
+
+use std::sync::{Mutex, OnceLock};
+
+use bevy::{prelude::*, render::camera::RenderTarget, utils::HashMap};
+
+use super::{
+    new_render_target_image, readback::FrameReadback, resize_render_target_image, FrameSink,
+};
+
+/// Renders the camera on the same entity into an offscreen image published
+/// under `name`
+///
+/// QML can then show it with `Image { source: "image://bevy/<name>" }`, or
+/// through a `BevyTextureSource` which refreshes the source on every frame.
+#[derive(Component, Clone, Debug)]
+pub struct QmlImageTarget {
+    /// The name used in the `image://bevy/<name>` URL
+    pub name: String,
+    /// The size of the image in pixels
+    pub size: UVec2,
+}
+
+impl QmlImageTarget {
+    pub fn new(name: impl Into<String>, size: UVec2) -> Self {
+        Self {
+            name: name.into(),
+            size,
+        }
+    }
+}
+
+fn sinks() -> &'static Mutex<HashMap<String, FrameSink>> {
+    static SINKS: OnceLock<Mutex<HashMap<String, FrameSink>>> = OnceLock::new();
+    SINKS.get_or_init(Default::default)
+}
+
+/// The sink that frames for the given image name are published to
+///
+/// The sink is created on first use, so QML can ask for an image before the
+/// camera rendering it has been spawned.
+pub fn published_sink(name: &str) -> FrameSink {
+    sinks()
+        .lock()
+        .unwrap()
+        .entry(name.to_owned())
+        .or_default()
+        .clone()
+}
+
+/// Create, resize and rename the images of cameras with a [QmlImageTarget]
+pub(crate) fn update_image_targets(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut cameras: Query<
+        (Entity, &QmlImageTarget, &mut Camera, Option<&FrameReadback>),
+        Changed<QmlImageTarget>,
+    >,
+) {
+    for (entity, target, mut camera, readback) in &mut cameras {
+        let image = match readback {
+            Some(readback) => {
+                if let Some(image) = images.get_mut(&readback.image) {
+                    resize_render_target_image(image, target.size);
+                }
+                readback.image.clone()
+            }
+            None => images.add(new_render_target_image(target.size)),
+        };
+
+        camera.target = RenderTarget::Image(image.clone());
+        commands.entity(entity).insert(FrameReadback {
+            image,
+            sink: published_sink(&target.name),
+        });
+    }
+}
@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Render targets backed by Direct3D 12 resources in a shared heap, which
+//! the Qt Direct3D 11 and 12 backends open through an NT handle.
+//!
+//! Unlike memory exported from Vulkan, both devices use the texture in
+//! turns. A shared fence stands in for the keyed mutex of Direct3D 11: Bevy
+//! releases every frame under an odd key, and the scene graph releases it
+//! back under the next even one once it has drawn it, see [SharedFence].
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_d3d12")]
+mod ffi {
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/d3d12.h");
+
+        #[doc(hidden)]
+        #[rust_name = "d3d12_device_luid"]
+        unsafe fn d3d12DeviceLuid(device: usize, luid: &mut [u8]);
+
+        #[doc(hidden)]
+        #[rust_name = "d3d12_create_shared_texture"]
+        unsafe fn d3d12CreateSharedTexture(
+            device: usize,
+            width: u32,
+            height: u32,
+            resource: &mut usize,
+            handle: &mut i64,
+            allocation_size: &mut u64,
+        ) -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "d3d12_create_shared_fence"]
+        unsafe fn d3d12CreateSharedFence(
+            device: usize,
+            fence: &mut usize,
+            handle: &mut i64,
+        ) -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "d3d12_signal_fence"]
+        unsafe fn d3d12SignalFence(fence: usize, value: u64);
+
+        #[doc(hidden)]
+        #[rust_name = "d3d12_wait_for_fence"]
+        unsafe fn d3d12WaitForFence(fence: usize, value: u64, timeout_ms: u32) -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "d3d12_release_fence"]
+        unsafe fn d3d12ReleaseFence(fence: usize);
+
+        #[doc(hidden)]
+        #[rust_name = "d3d12_close_handle"]
+        unsafe fn d3d12CloseHandle(handle: i64);
+    }
+}
+
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    render::{render_resource::Texture, renderer::RenderDevice},
+};
+use wgpu::hal::{api::Dx12, dx12 as hal_dx12};
+
+use super::{AdapterIdentity, InteropBackend, SharedTextureExport};
+use crate::render::TARGET_FORMAT;
+
+/// The raw ID3D12Device of the render device, if Bevy renders with Direct3D 12
+fn raw_device(render_device: &RenderDevice) -> Option<usize> {
+    // Safety: the pointer is only used while the render device is alive
+    unsafe {
+        render_device
+            .wgpu_device()
+            .as_hal::<Dx12, _, _>(|device| Some(device?.raw_device().as_mut_ptr() as usize))
+    }
+}
+
+/// Query the LUID of the adapter if Bevy renders with Direct3D 12
+pub(super) fn adapter_identity(world: &World) -> AdapterIdentity {
+    let Some(device) = raw_device(world.resource::<RenderDevice>()) else {
+        return AdapterIdentity::default();
+    };
+
+    let mut device_uuid = [0; 16];
+    // Safety: the device is alive, see raw_device
+    unsafe { ffi::d3d12_device_luid(device, &mut device_uuid) };
+    AdapterIdentity {
+        backend: InteropBackend::D3DSharedHandle,
+        device_uuid: Some(device_uuid),
+        vulkan: None,
+    }
+}
+
+/// Allocate a render target in a shared heap and wrap it as a wgpu texture,
+/// along with the fence handing it over to the scene graph
+pub(super) fn create_shared_texture(
+    render_device: &RenderDevice,
+    size: UVec2,
+) -> Option<(Texture, SharedTextureExport, SharedFence)> {
+    let device = raw_device(render_device)?;
+    let extent = wgpu::Extent3d {
+        width: size.x.max(1),
+        height: size.y.max(1),
+        depth_or_array_layers: 1,
+    };
+
+    let (mut fence, mut fence_handle) = (0, -1);
+    // Safety: the device is alive, see raw_device, and the fence is handed
+    // over with a reference
+    if !unsafe { ffi::d3d12_create_shared_fence(device, &mut fence, &mut fence_handle) } {
+        return None;
+    }
+    let fence = SharedFence { fence, released: 0 };
+
+    let (mut resource, mut handle, mut allocation_size) = (0, -1, 0);
+    // Safety: as above, for the resource
+    let created = unsafe {
+        ffi::d3d12_create_shared_texture(
+            device,
+            extent.width,
+            extent.height,
+            &mut resource,
+            &mut handle,
+            &mut allocation_size,
+        )
+    };
+    if !created {
+        // Safety: the handle was never handed out, nobody imports it now
+        unsafe { ffi::d3d12_close_handle(fence_handle) };
+        return None;
+    }
+
+    // Safety: the resource was created with a matching descriptor, and wgpu
+    // releases its reference when it drops the texture
+    let hal_texture = unsafe {
+        hal_dx12::Device::texture_from_raw(
+            d3d12::Resource::from_raw(resource as *mut _),
+            TARGET_FORMAT,
+            wgpu::TextureDimension::D2,
+            extent,
+            1,
+            1,
+        )
+    };
+    let descriptor = wgpu::TextureDescriptor {
+        label: Some("bevy_qml_shared_target"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TARGET_FORMAT,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    };
+    // Safety: the hal texture was created from the same device with a matching descriptor
+    let texture = unsafe {
+        render_device
+            .wgpu_device()
+            .create_texture_from_hal::<Dx12>(hal_texture, &descriptor)
+    };
+
+    Some((
+        Texture::from(texture),
+        SharedTextureExport {
+            backend: InteropBackend::D3DSharedHandle,
+            buffer: 0,
+            handle,
+            fence: fence_handle,
+            allocation_size,
+            size: UVec2::new(extent.width, extent.height),
+        },
+        fence,
+    ))
+}
+
+/// A fence shared with the scene graph, which hands the texture back and
+/// forth like a keyed mutex
+pub(super) struct SharedFence {
+    fence: usize,
+    /// The key the last frame was released under
+    released: u64,
+}
+
+// Safety: ID3D12Fence is free threaded
+unsafe impl Send for SharedFence {}
+unsafe impl Sync for SharedFence {}
+
+impl SharedFence {
+    /// Hand the texture over to the scene graph under the next odd key, once
+    /// the GPU has finished the frame, and return the key
+    pub(super) fn release(&mut self) -> u64 {
+        self.released += if self.released == 0 { 1 } else { 2 };
+        // Safety: the fence lives as long as self
+        unsafe { ffi::d3d12_signal_fence(self.fence, self.released) };
+        self.released
+    }
+
+    /// Wait for the scene graph to hand the texture back under the key
+    pub(super) fn acquire(&self, key: u64, timeout: Duration) -> bool {
+        // Safety: the fence lives as long as self
+        unsafe { ffi::d3d12_wait_for_fence(self.fence, key, timeout.as_millis() as u32) }
+    }
+}
+
+impl Drop for SharedFence {
+    fn drop(&mut self) {
+        // Safety: the reference was handed over by d3d12_create_shared_fence
+        unsafe { ffi::d3d12_release_fence(self.fence) };
+    }
+}
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Render targets backed by an IOSurface, which the Qt Metal backend wraps
+//! in a texture of its own device on macOS and iOS.
+//!
+//! Bevy renders offscreen, so the only CAMetalLayer is the one of the Qt
+//! window, which Qt sizes for the screen. The surface has the size of the
+//! render target, which follows the device pixel ratio of the item, so on
+//! Retina screens it holds two or three pixels per point and the scene graph
+//! draws it into the logical rectangle of the item.
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_iosurface")]
+mod ffi {
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/metal.h");
+
+        #[doc(hidden)]
+        #[rust_name = "metal_device_registry_id"]
+        unsafe fn metalDeviceRegistryId(device: usize) -> u64;
+
+        #[doc(hidden)]
+        #[rust_name = "metal_create_shared_texture"]
+        unsafe fn metalCreateSharedTexture(
+            device: usize,
+            width: u32,
+            height: u32,
+            texture: &mut usize,
+            surface: &mut i64,
+            allocation_size: &mut u64,
+        ) -> bool;
+    }
+}
+
+use bevy::{
+    prelude::*,
+    render::{render_resource::Texture, renderer::RenderDevice},
+};
+use metal::foreign_types::ForeignType;
+use wgpu::hal::{api::Metal, metal as hal_metal, CopyExtent};
+
+use super::{AdapterIdentity, InteropBackend, SharedTextureExport};
+use crate::render::TARGET_FORMAT;
+
+/// The raw MTLDevice of the render device, if Bevy renders with Metal
+fn raw_device(render_device: &RenderDevice) -> Option<usize> {
+    // Safety: the pointer is only used while the render device is alive
+    unsafe {
+        render_device
+            .wgpu_device()
+            .as_hal::<Metal, _, _>(|device| Some(device?.raw_device().lock().as_ptr() as usize))
+    }
+}
+
+/// Query the registry ID of the GPU if Bevy renders with Metal
+pub(super) fn adapter_identity(world: &World) -> AdapterIdentity {
+    let Some(device) = raw_device(world.resource::<RenderDevice>()) else {
+        return AdapterIdentity::default();
+    };
+
+    let mut device_uuid = [0; 16];
+    // Safety: the device is alive, see raw_device
+    let registry_id = unsafe { ffi::metal_device_registry_id(device) };
+    device_uuid[..8].copy_from_slice(&registry_id.to_ne_bytes());
+    AdapterIdentity {
+        backend: InteropBackend::MetalIOSurface,
+        device_uuid: Some(device_uuid),
+        vulkan: None,
+    }
+}
+
+/// Allocate a render target backed by an IOSurface and wrap it as a wgpu texture
+pub(super) fn create_shared_texture(
+    render_device: &RenderDevice,
+    size: UVec2,
+) -> Option<(Texture, SharedTextureExport)> {
+    let device = raw_device(render_device)?;
+    let extent = wgpu::Extent3d {
+        width: size.x.max(1),
+        height: size.y.max(1),
+        depth_or_array_layers: 1,
+    };
+
+    let (mut raw_texture, mut surface, mut allocation_size) = (0, -1, 0);
+    // Safety: the device is alive, see raw_device, and the texture is handed
+    // over with a reference
+    let created = unsafe {
+        ffi::metal_create_shared_texture(
+            device,
+            extent.width,
+            extent.height,
+            &mut raw_texture,
+            &mut surface,
+            &mut allocation_size,
+        )
+    };
+    if !created {
+        return None;
+    }
+
+    // Safety: the texture was created with a matching descriptor, and wgpu
+    // releases its reference when it drops the texture
+    let hal_texture = unsafe {
+        hal_metal::Device::texture_from_raw(
+            metal::Texture::from_ptr(raw_texture as *mut _),
+            TARGET_FORMAT,
+            metal::MTLTextureType::D2,
+            1,
+            1,
+            CopyExtent {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            },
+        )
+    };
+    let descriptor = wgpu::TextureDescriptor {
+        label: Some("bevy_qml_shared_target"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TARGET_FORMAT,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    };
+    // Safety: the hal texture was created from the same device with a matching descriptor
+    let texture = unsafe {
+        render_device
+            .wgpu_device()
+            .create_texture_from_hal::<Metal>(hal_texture, &descriptor)
+    };
+
+    Some((
+        Texture::from(texture),
+        SharedTextureExport {
+            backend: InteropBackend::MetalIOSurface,
+            buffer: 0,
+            handle: surface,
+            fence: -1,
+            allocation_size,
+            size: UVec2::new(extent.width, extent.height),
+        },
+    ))
+}
@@ -0,0 +1,706 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Zero-copy sharing of render targets between Bevy's wgpu device and the
+//! Qt RHI.
+//!
+//! Copying every frame through the CPU is slow, so when both renderers run
+//! on the same GPU with a compatible graphics API the render target is
+//! allocated as exportable memory, and the scene graph imports it and
+//! samples it directly. Whenever the handshake fails the item keeps using
+//! the readback path in [super::readback].
+//!
+//! On Linux, Vulkan external memory needs the scene graph to render with
+//! Vulkan, e.g. by setting `QSG_RHI_BACKEND=vulkan` or calling
+//! QQuickWindow::setGraphicsApi(). On Windows, Bevy rendering with Direct3D
+//! 12 shares its targets through NT handles with the Direct3D 11 backend
+//! Qt uses by default, or with the Direct3D 12 one from Qt 6.6 on, see
+//! [d3d12]. On macOS and iOS, Bevy and the Qt Metal backend share targets
+//! backed by an IOSurface, see [iosurface]. On Linux devices where the scene
+//! graph is stuck on OpenGL, Bevy can render with OpenGL as well, in a
+//! context sharing its textures with the scene graph, see [opengl]. When
+//! creating a shared target fails, the item goes back to copying frames as
+//! well.
+//!
+//! Bevy and the scene graph hand a shared target back and forth: the scene
+//! graph waits for the frame Bevy released on the GPU before sampling it, and
+//! Bevy waits for it to be handed back before rendering into it again. With
+//! [SharedFrameSync::TripleBuffering], Bevy renders into another target
+//! instead of waiting.
+//!
+//! The scene graph would pick a Vulkan device of its own, which need not be
+//! on the GPU Bevy renders with. So the windows showing a `BevyQuickItem`
+//! adopt Bevy's VkInstance and physical device before they are exposed, and
+//! enable the external memory extensions on the device they create, see
+//! [VulkanHandshake]. This needs the Bevy app to be running before the QML
+//! creates its windows, as with [crate::app::BevyQmlApp].
+
+#[cfg(target_os = "windows")]
+mod d3d12;
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+mod iosurface;
+#[cfg(target_os = "linux")]
+pub mod opengl;
+#[cfg(target_os = "linux")]
+mod vulkan;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex, RwLock,
+    },
+    time::Duration,
+};
+
+use bevy::{
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        render_asset::{prepare_assets, RenderAssets},
+        render_resource::{Maintain, Texture},
+        renderer::RenderDevice,
+        texture::GpuImage,
+        Render, RenderApp, RenderSet,
+    },
+    utils::HashMap,
+};
+
+/// The graphics APIs reported by QSGRendererInterface::graphicsApi()
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum QtGraphicsApi {
+    Unknown = 0,
+    Software = 1,
+    OpenVG = 2,
+    OpenGL = 3,
+    Direct3D11 = 4,
+    Vulkan = 5,
+    Metal = 6,
+    Null = 7,
+    Direct3D12 = 8,
+}
+
+impl From<i32> for QtGraphicsApi {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Software,
+            2 => Self::OpenVG,
+            3 => Self::OpenGL,
+            4 => Self::Direct3D11,
+            5 => Self::Vulkan,
+            6 => Self::Metal,
+            7 => Self::Null,
+            8 => Self::Direct3D12,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// How pixels get from a Bevy render target into the scene graph
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(i32)]
+pub enum InteropBackend {
+    /// Read the frame back to the CPU and upload it again
+    #[default]
+    Copy = 0,
+    /// Share device memory through VK_KHR_external_memory_fd
+    VulkanExternalMemory = 1,
+    /// Share a D3D12 resource through an NT handle
+    D3DSharedHandle = 2,
+    /// Share an IOSurface backed Metal texture
+    MetalIOSurface = 3,
+    /// Share a texture between OpenGL contexts sharing their objects
+    OpenGLShareContext = 4,
+}
+
+/// How the windows of the scene graph take up the Vulkan device of Bevy
+///
+/// This only matters when the scene graph renders with Vulkan.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(i32)]
+pub enum VulkanHandshake {
+    /// The scene graph picks its own instance and device, and frames are
+    /// only shared if it happens to pick the GPU of Bevy
+    None = 0,
+    /// The scene graph creates its own device on the physical device of
+    /// Bevy, through Bevy's instance, and imports the shared memory into it
+    #[default]
+    PhysicalDevice = 1,
+    /// The scene graph renders with the device and queue of Bevy
+    ///
+    /// Both submit to the same queue, which is only safe with the basic
+    /// render loop, `QSG_RENDER_LOOP=basic`, rendering on the GUI thread.
+    Device = 2,
+}
+
+static VULKAN_HANDSHAKE: Mutex<VulkanHandshake> = Mutex::new(VulkanHandshake::PhysicalDevice);
+
+/// Choose how windows adopt the Vulkan device of Bevy, before the QML creates them
+pub fn set_vulkan_handshake(handshake: VulkanHandshake) {
+    *VULKAN_HANDSHAKE.lock().unwrap() = handshake;
+}
+
+/// How windows adopt the Vulkan device of Bevy, see [set_vulkan_handshake]
+pub fn vulkan_handshake() -> VulkanHandshake {
+    *VULKAN_HANDSHAKE.lock().unwrap()
+}
+
+/// The raw Vulkan objects Bevy renders with, for the scene graph to adopt
+#[derive(Clone, Copy, Debug)]
+pub struct VulkanDevice {
+    pub instance: u64,
+    pub physical_device: u64,
+    pub device: u64,
+    pub queue_family: u32,
+    pub queue_index: u32,
+}
+
+/// The GPU Bevy renders with, as seen by the interop layer
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AdapterIdentity {
+    /// The zero-copy backend this adapter could offer on this platform
+    pub backend: InteropBackend,
+    /// VkPhysicalDeviceIDProperties::deviceUUID, or the LUID on Windows
+    pub device_uuid: Option<[u8; 16]>,
+    /// The Vulkan objects of the renderer, if it renders with Vulkan
+    pub vulkan: Option<VulkanDevice>,
+}
+
+static ADAPTER_IDENTITY: RwLock<Option<AdapterIdentity>> = RwLock::new(None);
+
+/// The identity of the adapter Bevy selected, once the renderer has started
+///
+/// An app built anew after losing its device replaces the identity of the
+/// one before, see [crate::render::device].
+pub fn adapter_identity() -> Option<AdapterIdentity> {
+    *ADAPTER_IDENTITY.read().unwrap()
+}
+
+/// Decide how an item should receive its frames given what Qt renders with
+///
+/// A `forced` backend is used without checking that both render on the same
+/// GPU, as long as the graphics APIs fit.
+pub fn negotiate(
+    qt_api: QtGraphicsApi,
+    qt_device_uuid: Option<[u8; 16]>,
+    forced: Option<InteropBackend>,
+) -> InteropBackend {
+    let Some(identity) = adapter_identity() else {
+        return InteropBackend::Copy;
+    };
+    if forced.is_some_and(|forced| forced != identity.backend) {
+        return InteropBackend::Copy;
+    }
+
+    let api_matches = match identity.backend {
+        InteropBackend::VulkanExternalMemory => qt_api == QtGraphicsApi::Vulkan,
+        InteropBackend::D3DSharedHandle => {
+            matches!(
+                qt_api,
+                QtGraphicsApi::Direct3D11 | QtGraphicsApi::Direct3D12
+            )
+        }
+        InteropBackend::MetalIOSurface => qt_api == QtGraphicsApi::Metal,
+        InteropBackend::OpenGLShareContext => qt_api == QtGraphicsApi::OpenGL,
+        InteropBackend::Copy => false,
+    };
+
+    // Memory can only be shared between devices on the same physical GPU,
+    // which contexts sharing their objects are on anyway
+    let same_device = forced.is_some()
+        || identity.backend == InteropBackend::OpenGLShareContext
+        || identity.device_uuid.is_some() && identity.device_uuid == qt_device_uuid;
+
+    if api_matches && same_device {
+        identity.backend
+    } else {
+        InteropBackend::Copy
+    }
+}
+
+/// How Bevy and the scene graph take turns with a shared render target
+///
+/// Sharing a single texture naively flickers, as Bevy renders the next frame
+/// into it while the scene graph still draws the last one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource, ExtractResource, Reflect)]
+#[reflect(Resource)]
+pub enum SharedFrameSync {
+    /// Hand a single texture back and forth: the scene graph waits for Bevy
+    /// to finish a frame before drawing it, and Bevy waits for the scene
+    /// graph to hand the texture back before rendering into it again
+    ///
+    /// Direct3D hands frames over with a shared fence, OpenGL with sync
+    /// objects. With Vulkan, Metal and OpenGL the scene graph waits for its
+    /// own GPU work at the end of a frame before handing the texture back.
+    #[default]
+    FenceWait,
+    /// Render into three textures in turn, never into the one the scene
+    /// graph draws or the one it is about to draw, so neither renderer waits
+    /// for the other at the cost of two more textures per item
+    TripleBuffering,
+}
+
+impl SharedFrameSync {
+    /// The number of textures shared per target
+    fn buffers(self) -> usize {
+        match self {
+            Self::FenceWait => 1,
+            Self::TripleBuffering => 3,
+        }
+    }
+}
+
+/// An exported render target handed over to the scene graph
+#[derive(Clone, Debug)]
+pub struct SharedTextureExport {
+    pub backend: InteropBackend,
+    /// Which of the textures of the target this is, see [SharedFrameSync]
+    pub buffer: usize,
+    /// A file descriptor, an NT handle, an IOSurface or an OpenGL texture
+    /// name, ownership of handles passes to the importer
+    pub handle: i64,
+    /// An NT handle to the fence handing the texture back and forth, or -1
+    /// where the backend needs none
+    pub fence: i64,
+    /// The size of the exported allocation in bytes
+    pub allocation_size: u64,
+    pub size: UVec2,
+}
+
+/// Which texture each renderer has, guarded by the mutex of the slot
+#[derive(Default)]
+struct FrameState {
+    /// The buffer and key of the last frame Bevy released, key 0 before the
+    /// first
+    released: (usize, u64),
+    /// The buffer the scene graph draws, until it hands it back
+    held: Option<usize>,
+    /// The key each buffer was last taken under by the scene graph
+    acquired: [u64; 3],
+}
+
+#[derive(Default)]
+struct SlotInner {
+    exports: Mutex<Vec<SharedTextureExport>>,
+    failed: AtomicBool,
+    stale: AtomicBool,
+    waits_for_gpu: AtomicBool,
+    frames: Mutex<FrameState>,
+    handed_back: Condvar,
+}
+
+impl SlotInner {
+    fn hand_back(&self, buffer: usize) {
+        let mut frames = self.frames.lock().unwrap();
+        if frames.held == Some(buffer) {
+            frames.held = None;
+            self.handed_back.notify_all();
+        }
+    }
+}
+
+/// Where the render world publishes newly exported textures for Qt, and
+/// where both renderers pass frames back and forth
+///
+/// New exports are only published when the target is (re)created, the
+/// contents of the shared memory then update in place every frame. Every
+/// frame is released under a key, which the scene graph waits for before
+/// drawing the buffer, and the scene graph hands the buffer back once its
+/// frame has ended, see [SharedFrameSync].
+#[derive(Clone, Default)]
+pub struct SharedTextureSlot(Arc<SlotInner>);
+
+impl SharedTextureSlot {
+    pub fn publish(&self, exports: Vec<SharedTextureExport>, sync: SharedFrameSync) {
+        *self.0.exports.lock().unwrap() = exports;
+        self.0
+            .waits_for_gpu
+            .store(sync == SharedFrameSync::FenceWait, Ordering::Release);
+        *self.0.frames.lock().unwrap() = FrameState::default();
+        self.0.handed_back.notify_all();
+    }
+
+    pub fn take(&self) -> Vec<SharedTextureExport> {
+        std::mem::take(&mut *self.0.exports.lock().unwrap())
+    }
+
+    /// Tell Qt that no texture could be shared, so it copies frames instead
+    pub fn fail(&self) {
+        self.0.failed.store(true, Ordering::Release);
+    }
+
+    /// Whether sharing failed, which is only reported once
+    pub fn take_failure(&self) -> bool {
+        self.0.failed.swap(false, Ordering::AcqRel)
+    }
+
+    /// Ask the render world to export the textures anew, as the scene graph
+    /// dropped what it imported, e.g. after losing its graphics device
+    pub fn reset(&self) {
+        self.0.stale.store(true, Ordering::Release);
+    }
+
+    /// Whether the scene graph waits for its GPU before handing a buffer back
+    pub fn waits_for_gpu(&self) -> bool {
+        self.0.waits_for_gpu.load(Ordering::Acquire)
+    }
+
+    /// The buffer and the key the last finished frame was released under,
+    /// the key is 0 before the first
+    pub fn released(&self) -> (usize, u64) {
+        self.0.frames.lock().unwrap().released
+    }
+
+    /// Qt has taken the buffer released under the key
+    pub fn acquire(&self, buffer: usize, key: u64) {
+        let mut frames = self.0.frames.lock().unwrap();
+        frames.held = Some(buffer);
+        if let Some(acquired) = frames.acquired.get_mut(buffer) {
+            *acquired = key;
+        }
+    }
+
+    /// A pointer to the slot for the scene graph, which keeps it alive with
+    /// [SharedTextureSlot::retain_raw] as its nodes may outlive the item
+    pub fn as_raw(&self) -> usize {
+        Arc::as_ptr(&self.0) as usize
+    }
+
+    /// # Safety
+    ///
+    /// The pointer comes from [SharedTextureSlot::as_raw] of a live slot.
+    pub unsafe fn retain_raw(raw: usize) {
+        Arc::increment_strong_count(raw as *const SlotInner);
+    }
+
+    /// # Safety
+    ///
+    /// The pointer was retained with [SharedTextureSlot::retain_raw].
+    pub unsafe fn release_raw(raw: usize) {
+        Arc::decrement_strong_count(raw as *const SlotInner);
+    }
+
+    /// The scene graph is done with the buffer, it may be rendered into again
+    ///
+    /// # Safety
+    ///
+    /// The pointer was retained with [SharedTextureSlot::retain_raw].
+    pub unsafe fn hand_back_raw(raw: usize, buffer: usize) {
+        (*(raw as *const SlotInner)).hand_back(buffer);
+    }
+
+    fn take_stale(&self) -> bool {
+        self.0.stale.swap(false, Ordering::AcqRel)
+    }
+
+    fn release(&self, buffer: usize, key: u64) {
+        self.0.frames.lock().unwrap().released = (buffer, key);
+    }
+
+    /// Wait for the scene graph to hand the buffer back, returning the key it
+    /// was taken under or 0 if it was not taken, or None after the timeout
+    fn wait_handed_back(&self, buffer: usize, timeout: Duration) -> Option<u64> {
+        let frames = self.0.frames.lock().unwrap();
+        let (frames, result) = self
+            .0
+            .handed_back
+            .wait_timeout_while(frames, timeout, |frames| frames.held == Some(buffer))
+            .unwrap();
+        (!result.timed_out()).then(|| frames.acquired.get(buffer).copied().unwrap_or_default())
+    }
+
+    /// A buffer the scene graph neither draws nor is about to draw
+    fn free_buffer(&self, buffers: usize) -> usize {
+        let frames = self.0.frames.lock().unwrap();
+        let latest = (frames.released.1 != 0).then_some(frames.released.0);
+        (0..buffers)
+            .find(|&buffer| frames.held != Some(buffer) && latest != Some(buffer))
+            .unwrap_or_default()
+    }
+}
+
+/// How long the render world waits for the scene graph to hand a texture
+/// back before rendering into it anyway
+const ACQUIRE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Only Direct3D and OpenGL synchronize frames with the scene graph on the
+/// GPU
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+enum SharedFence {}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+impl SharedFence {
+    fn release(&mut self) -> u64 {
+        match *self {}
+    }
+
+    fn acquire(&self, _key: u64, _timeout: Duration) -> bool {
+        match *self {}
+    }
+}
+
+#[cfg(target_os = "windows")]
+use d3d12::SharedFence;
+#[cfg(target_os = "linux")]
+use opengl::SharedFence;
+
+/// Renders the target image into memory that is shared with the scene graph
+///
+/// Inserted by the QML item instead of a readback once [negotiate] succeeded.
+#[derive(Component, Clone, ExtractComponent)]
+pub struct SharedTextureTarget {
+    pub image: Handle<Image>,
+    pub backend: InteropBackend,
+    pub slot: SharedTextureSlot,
+}
+
+/// Textures backed by exportable memory, which replace the GPU images that
+/// Bevy allocated for the targets
+#[derive(Default, Resource)]
+struct SharedTextures(HashMap<AssetId<Image>, SharedTexture>);
+
+struct SharedTexture {
+    size: UVec2,
+    sync: SharedFrameSync,
+    buffers: Vec<SharedBuffer>,
+    /// The buffer rendered into this frame
+    current: usize,
+    /// The key of the last frame of the buffers without a fence
+    frames: u64,
+}
+
+struct SharedBuffer {
+    texture: Texture,
+    fence: Option<SharedFence>,
+}
+
+pub struct InteropPlugin;
+
+impl Plugin for InteropPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SharedFrameSync>()
+            .register_type::<SharedFrameSync>()
+            .add_plugins((
+                ExtractComponentPlugin::<SharedTextureTarget>::default(),
+                ExtractResourcePlugin::<SharedFrameSync>::default(),
+            ));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        #[cfg(target_os = "linux")]
+        if opengl::is_shared() {
+            opengl::build_render_app(render_app);
+        }
+
+        render_app.init_resource::<SharedTextures>().add_systems(
+            Render,
+            (
+                prepare_shared_textures
+                    .in_set(RenderSet::PrepareAssets)
+                    .after(prepare_assets::<GpuImage>),
+                wait_for_shared_frames
+                    .after(RenderSet::Render)
+                    .before(RenderSet::Cleanup),
+            ),
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        // The renderer has started by now, which is before the QML creates
+        // the windows that adopt its device
+        if let Some(render_app) = app.get_sub_app(RenderApp) {
+            detect_adapter(render_app.world());
+        }
+    }
+}
+
+fn detect_adapter(world: &World) {
+    #[cfg(target_os = "linux")]
+    let identity = match vulkan::adapter_identity(world) {
+        identity if identity.backend == InteropBackend::Copy => opengl::adapter_identity(world),
+        identity => identity,
+    };
+    #[cfg(target_os = "windows")]
+    let identity = d3d12::adapter_identity(world);
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    let identity = iosurface::adapter_identity(world);
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "ios"
+    )))]
+    let identity = {
+        let _ = world;
+        AdapterIdentity::default()
+    };
+
+    info!("Bevy QML interop adapter: {identity:?}");
+    *ADAPTER_IDENTITY.write().unwrap() = Some(identity);
+}
+
+/// Allocate exportable textures for the shared targets and swap them into
+/// the GPU images, so the cameras render straight into the shared memory
+#[cfg_attr(
+    not(any(
+        target_os = "linux",
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "ios"
+    )),
+    allow(unused_variables)
+)]
+fn prepare_shared_textures(
+    targets: Query<&SharedTextureTarget>,
+    mut gpu_images: ResMut<RenderAssets<GpuImage>>,
+    mut shared: ResMut<SharedTextures>,
+    render_device: Res<RenderDevice>,
+    sync: Res<SharedFrameSync>,
+) {
+    shared
+        .0
+        .retain(|id, _| targets.iter().any(|target| target.image.id() == *id));
+
+    for target in &targets {
+        let id = target.image.id();
+        let Some(gpu_image) = gpu_images.get_mut(id) else {
+            continue;
+        };
+
+        let stale = target.slot.take_stale();
+        let needs_export = stale
+            || shared.0.get(&id).map_or(true, |texture| {
+                texture.size != gpu_image.size || texture.sync != *sync
+            });
+        if needs_export {
+            let created: Option<Vec<(SharedBuffer, SharedTextureExport)>> = (0..sync.buffers())
+                .map(|buffer| {
+                    let (texture, export, fence) =
+                        create_shared_texture(target.backend, &render_device, gpu_image.size)?;
+                    Some((
+                        SharedBuffer { texture, fence },
+                        SharedTextureExport { buffer, ..export },
+                    ))
+                })
+                .collect();
+
+            let Some(created) = created else {
+                warn!(
+                    "Failed to create a {:?} shared texture, copying frames instead",
+                    target.backend
+                );
+                shared.0.remove(&id);
+                target.slot.fail();
+                continue;
+            };
+            let (buffers, exports) = created.into_iter().unzip();
+            target.slot.publish(exports, *sync);
+            shared.0.insert(
+                id,
+                SharedTexture {
+                    size: gpu_image.size,
+                    sync: *sync,
+                    buffers,
+                    current: 0,
+                    frames: 0,
+                },
+            );
+        }
+
+        let texture = shared.0.get_mut(&id).unwrap();
+        match texture.sync {
+            // Wait for the scene graph to finish drawing the frame it took last
+            SharedFrameSync::FenceWait => {
+                let buffer = &texture.buffers[0];
+                let acquired = target.slot.wait_handed_back(0, ACQUIRE_TIMEOUT);
+                let fenced = match (&buffer.fence, acquired) {
+                    (Some(fence), Some(acquired)) if acquired > 0 => {
+                        fence.acquire(acquired + 1, ACQUIRE_TIMEOUT)
+                    }
+                    (_, acquired) => acquired.is_some(),
+                };
+                if !fenced {
+                    warn!("The scene graph did not hand back a shared texture in time");
+                }
+            }
+            // Render into a buffer the scene graph has no use for right now
+            SharedFrameSync::TripleBuffering => {
+                texture.current = target.slot.free_buffer(texture.buffers.len());
+            }
+        }
+
+        // Bevy recreates the GPU image whenever the asset changes, so the
+        // shared texture is swapped back in whenever it is not the current one
+        let texture = &texture.buffers[texture.current].texture;
+        if gpu_image.texture.id() != texture.id() {
+            gpu_image.texture = texture.clone();
+            gpu_image.texture_view = texture.create_view(&Default::default());
+        }
+    }
+}
+
+/// Create a texture of the backend along with its export, and the fence it is
+/// handed back and forth with where the backend has one
+#[cfg_attr(
+    not(any(
+        target_os = "linux",
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "ios"
+    )),
+    allow(unused_variables)
+)]
+fn create_shared_texture(
+    backend: InteropBackend,
+    render_device: &RenderDevice,
+    size: UVec2,
+) -> Option<(Texture, SharedTextureExport, Option<SharedFence>)> {
+    match backend {
+        #[cfg(target_os = "linux")]
+        InteropBackend::VulkanExternalMemory => vulkan::create_shared_texture(render_device, size)
+            .map(|(texture, export)| (texture, export, None)),
+        #[cfg(target_os = "linux")]
+        InteropBackend::OpenGLShareContext => opengl::create_shared_texture(render_device, size)
+            .map(|(texture, export, fence)| (texture, export, Some(fence))),
+        #[cfg(target_os = "windows")]
+        InteropBackend::D3DSharedHandle => d3d12::create_shared_texture(render_device, size)
+            .map(|(texture, export, fence)| (texture, export, Some(fence))),
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        InteropBackend::MetalIOSurface => iosurface::create_shared_texture(render_device, size)
+            .map(|(texture, export)| (texture, export, None)),
+        _ => None,
+    }
+}
+
+/// The scene graph samples the shared memory as soon as the item updates, so
+/// the frame has to be finished on the GPU before the main world moves on
+///
+/// The buffer rendered into is then released to the scene graph under a new
+/// key, which the backends with a fence hand out.
+fn wait_for_shared_frames(
+    targets: Query<&SharedTextureTarget>,
+    mut shared: ResMut<SharedTextures>,
+    render_device: Res<RenderDevice>,
+) {
+    if targets.is_empty() {
+        return;
+    }
+    render_device.poll(Maintain::wait()).panic_on_timeout();
+
+    for target in &targets {
+        let Some(texture) = shared.0.get_mut(&target.image.id()) else {
+            continue;
+        };
+        let current = texture.current;
+        let key = match &mut texture.buffers[current].fence {
+            Some(fence) => fence.release(),
+            None => {
+                texture.frames += 1;
+                texture.frames
+            }
+        };
+        target.slot.release(current, key);
+    }
+}
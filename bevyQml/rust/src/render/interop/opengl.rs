@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Render targets in an OpenGL context shared with the scene graph, for
+//! devices where Qt Quick only renders with OpenGL, such as older embedded
+//! Linux boards without a Vulkan driver.
+//!
+//! Bevy renders through the GL backend of wgpu in a context of this crate,
+//! which shares its objects with the contexts of the scene graph, see
+//! [render_creation]. The texture names of the render targets are then
+//! valid for the scene graph as they are, and a sync object created after
+//! every frame lets it wait for the commands of Bevy on the GPU, see
+//! [SharedFence].
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_opengl")]
+mod ffi {
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/opengl.h");
+
+        #[doc(hidden)]
+        #[rust_name = "opengl_share_context_create"]
+        fn openglShareContextCreate() -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "opengl_share_context_make_current"]
+        fn openglShareContextMakeCurrent() -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "opengl_get_proc_address"]
+        fn openglGetProcAddress(name: &str) -> usize;
+
+        #[doc(hidden)]
+        #[rust_name = "opengl_fence_sync"]
+        fn openglFenceSync() -> i64;
+
+        #[doc(hidden)]
+        #[rust_name = "opengl_delete_sync"]
+        unsafe fn openglDeleteSync(sync: i64);
+    }
+}
+
+use std::{
+    ffi::c_void,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use bevy::{
+    app::SubApp,
+    ecs::schedule::{ExecutorKind, ScheduleLabel},
+    prelude::*,
+    render::{
+        render_resource::{Texture, WgpuWrapper},
+        renderer::{RenderAdapter, RenderAdapterInfo, RenderDevice, RenderInstance, RenderQueue},
+        settings::{Backends, RenderCreation, WgpuSettings},
+        ExtractSchedule, Render, RenderSet,
+    },
+    tasks::block_on,
+};
+use wgpu::hal::{api::Gles, gles as hal_gles};
+
+use super::{AdapterIdentity, InteropBackend, SharedTextureExport};
+use crate::render::TARGET_FORMAT;
+
+/// Whether Bevy renders in the shared context
+static SHARED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the renderer was created by [render_creation] in the shared context
+pub fn is_shared() -> bool {
+    SHARED.load(Ordering::Acquire)
+}
+
+/// Render with the GL backend of wgpu, in a context sharing its objects with
+/// the scene graph
+///
+/// This needs the QGuiApplication to exist, created with
+/// `Qt::AA_ShareOpenGLContexts` as [crate::app::BevyQmlApp] does. If there is
+/// no context to share with, wgpu still renders with its own GL context and
+/// frames are copied.
+pub fn render_creation() -> RenderCreation {
+    if let Some((device, queue, adapter_info, adapter, instance)) = create_shared_renderer() {
+        SHARED.store(true, Ordering::Release);
+        return RenderCreation::Manual(device, queue, adapter_info, adapter, instance);
+    }
+
+    warn!("No OpenGL context to share with the scene graph, copying frames instead");
+    RenderCreation::Automatic(WgpuSettings {
+        backends: Some(Backends::GL),
+        ..default()
+    })
+}
+
+fn create_shared_renderer() -> Option<(
+    RenderDevice,
+    RenderQueue,
+    RenderAdapterInfo,
+    RenderAdapter,
+    RenderInstance,
+)> {
+    if !ffi::opengl_share_context_create() {
+        return None;
+    }
+
+    // Safety: the shared context is current on this thread, and the render
+    // world makes it current again before it renders, see make_current
+    let exposed = unsafe {
+        hal_gles::Adapter::new_external(|name| ffi::opengl_get_proc_address(name) as *const c_void)
+    }?;
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::GL,
+        ..default()
+    });
+    // Safety: the adapter belongs to the GL backend of the instance
+    let adapter = unsafe { instance.create_adapter_from_hal(exposed) };
+    let (device, queue) = block_on(adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            label: Some("bevy_qml_shared_gl_device"),
+            required_features: wgpu::Features::empty(),
+            required_limits: adapter.limits(),
+        },
+        None,
+    ))
+    .ok()?;
+
+    info!("Bevy renders in an OpenGL context shared with the scene graph");
+    Some((
+        RenderDevice::from(device),
+        RenderQueue(Arc::new(WgpuWrapper::new(queue))),
+        RenderAdapterInfo(WgpuWrapper::new(adapter.get_info())),
+        RenderAdapter(Arc::new(WgpuWrapper::new(adapter))),
+        RenderInstance(Arc::new(WgpuWrapper::new(instance))),
+    ))
+}
+
+/// Render on the thread the shared context is current on, the GUI thread
+pub(super) fn build_render_app(render_app: &mut SubApp) {
+    for schedule in [ExtractSchedule.intern(), Render.intern()] {
+        render_app.edit_schedule(schedule, |schedule| {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        });
+    }
+    render_app.add_systems(Render, make_current.in_set(RenderSet::ExtractCommands));
+}
+
+/// Make the shared context current, as the scene graph may have made its own
+/// current on the GUI thread since the last frame
+fn make_current() {
+    if !ffi::opengl_share_context_make_current() {
+        error!("Failed to make the OpenGL context of Bevy current");
+    }
+}
+
+/// Offer the shared context to the scene graph when Bevy renders in it
+pub(super) fn adapter_identity(_world: &World) -> AdapterIdentity {
+    if !is_shared() {
+        return AdapterIdentity::default();
+    }
+
+    // Sharing a context already means rendering on the same GPU
+    AdapterIdentity {
+        backend: InteropBackend::OpenGLShareContext,
+        device_uuid: None,
+        vulkan: None,
+    }
+}
+
+/// Create a render target in the shared context, whose texture name the scene
+/// graph samples directly
+pub(super) fn create_shared_texture(
+    render_device: &RenderDevice,
+    size: UVec2,
+) -> Option<(Texture, SharedTextureExport, SharedFence)> {
+    let extent = wgpu::Extent3d {
+        width: size.x.max(1),
+        height: size.y.max(1),
+        depth_or_array_layers: 1,
+    };
+    // Sampled as well, so that wgpu allocates a texture and not a renderbuffer
+    let texture = render_device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("bevy_qml_shared_target"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TARGET_FORMAT,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+
+    // Safety: the name is only read, the texture outlives the import as the
+    // scene graph is handed a new one before this one is dropped
+    let name = unsafe {
+        texture.as_hal::<Gles, _, _>(|texture| match &texture?.inner {
+            hal_gles::TextureInner::Texture { raw, .. } => Some(raw.0.get()),
+            _ => None,
+        })
+    }?;
+
+    Some((
+        texture,
+        SharedTextureExport {
+            backend: InteropBackend::OpenGLShareContext,
+            buffer: 0,
+            handle: i64::from(name),
+            fence: -1,
+            allocation_size: 0,
+            size: UVec2::new(extent.width, extent.height),
+        },
+        SharedFence(0),
+    ))
+}
+
+/// The sync object of the last frame, which the scene graph waits for on the
+/// GPU before sampling the texture
+///
+/// The key a frame is released under is the GLsync itself. It is deleted once
+/// the buffer is released again, by which time the scene graph has issued its
+/// wait. The other way around, the scene graph finishes its own commands
+/// before handing the texture back, see [super::SharedFrameSync].
+pub(super) struct SharedFence(i64);
+
+impl SharedFence {
+    /// Hand the frame over to the scene graph, returning the key it waits for
+    pub(super) fn release(&mut self) -> u64 {
+        let sync = ffi::opengl_fence_sync();
+        let previous = std::mem::replace(&mut self.0, sync);
+        // Safety: the previous sync object was created by opengl_fence_sync
+        // and is not used by Bevy anymore
+        unsafe { ffi::opengl_delete_sync(previous) };
+        sync as u64
+    }
+
+    /// The texture was handed back once the scene graph finished with it
+    pub(super) fn acquire(&self, _key: u64, _timeout: Duration) -> bool {
+        true
+    }
+}
+
+impl Drop for SharedFence {
+    fn drop(&mut self) {
+        // Safety: as in release
+        unsafe { ffi::opengl_delete_sync(self.0) };
+    }
+}
@@ -0,0 +1,241 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Render targets backed by Vulkan memory exported as an opaque file
+//! descriptor, which the Qt Vulkan backend imports into its own device.
+
+use ash::{
+    extensions::khr::ExternalMemoryFd,
+    vk::{self, Handle},
+};
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::Texture,
+        renderer::{RenderAdapter, RenderDevice},
+    },
+};
+use wgpu::hal::{api::Vulkan, vulkan as hal_vulkan};
+
+use super::{AdapterIdentity, InteropBackend, SharedTextureExport, VulkanDevice};
+use crate::render::TARGET_FORMAT;
+
+/// Query the device UUID and the raw objects of the adapter if Bevy renders
+/// with Vulkan
+pub(super) fn adapter_identity(world: &World) -> AdapterIdentity {
+    let adapter = world.resource::<RenderAdapter>();
+
+    // Safety: the raw handles are only used to query properties while the
+    // adapter is kept alive by the render world
+    let device_uuid = unsafe {
+        adapter.as_hal::<Vulkan, _, _>(|adapter| {
+            let adapter = adapter?;
+            let instance = adapter.shared_instance().raw_instance();
+
+            let mut id_properties = vk::PhysicalDeviceIDProperties::default();
+            let mut properties =
+                vk::PhysicalDeviceProperties2::builder().push_next(&mut id_properties);
+            instance
+                .get_physical_device_properties2(adapter.raw_physical_device(), &mut properties);
+            Some(id_properties.device_uuid)
+        })
+    };
+
+    match device_uuid {
+        Some(device_uuid) => AdapterIdentity {
+            backend: InteropBackend::VulkanExternalMemory,
+            device_uuid: Some(device_uuid),
+            vulkan: vulkan_device(world),
+        },
+        None => AdapterIdentity::default(),
+    }
+}
+
+/// The handles of the instance, the device and the queue Bevy renders with
+fn vulkan_device(world: &World) -> Option<VulkanDevice> {
+    let render_device = world.resource::<RenderDevice>();
+
+    // Safety: the handles are only read, the scene graph adopting them does
+    // not outlive the renderer
+    unsafe {
+        render_device
+            .wgpu_device()
+            .as_hal::<Vulkan, _, _>(|device| {
+                let device = device?;
+                Some(VulkanDevice {
+                    instance: device.shared_instance().raw_instance().handle().as_raw(),
+                    physical_device: device.raw_physical_device().as_raw(),
+                    device: device.raw_device().handle().as_raw(),
+                    queue_family: device.queue_family_index(),
+                    queue_index: device.queue_index(),
+                })
+            })
+    }
+}
+
+/// Keeps the raw Vulkan objects behind a wgpu texture alive until wgpu drops it
+struct ExportedImage {
+    device: ash::Device,
+    image: vk::Image,
+    memory: vk::DeviceMemory,
+}
+
+impl Drop for ExportedImage {
+    fn drop(&mut self) {
+        // Safety: wgpu drops the guard after it has destroyed its texture
+        unsafe {
+            self.device.destroy_image(self.image, None);
+            self.device.free_memory(self.memory, None);
+        }
+    }
+}
+
+/// Allocate a render target with exportable memory and wrap it as a wgpu texture
+pub(super) fn create_shared_texture(
+    render_device: &RenderDevice,
+    size: UVec2,
+) -> Option<(Texture, SharedTextureExport)> {
+    let extent = wgpu::Extent3d {
+        width: size.x.max(1),
+        height: size.y.max(1),
+        depth_or_array_layers: 1,
+    };
+    let usage = wgpu::TextureUsages::TEXTURE_BINDING
+        | wgpu::TextureUsages::COPY_SRC
+        | wgpu::TextureUsages::COPY_DST
+        | wgpu::TextureUsages::RENDER_ATTACHMENT;
+
+    // Safety: the image is created, bound and handed to wgpu before anything
+    // else can use it, and stays alive through its drop guard
+    let (hal_texture, export) = unsafe {
+        render_device
+            .wgpu_device()
+            .as_hal::<Vulkan, _, _>(|device| create_exported_image(device?, extent))?
+    };
+
+    let descriptor = wgpu::TextureDescriptor {
+        label: Some("bevy_qml_shared_target"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TARGET_FORMAT,
+        usage,
+        view_formats: &[],
+    };
+
+    // Safety: the hal texture was created from the same device with a matching descriptor
+    let texture = unsafe {
+        render_device
+            .wgpu_device()
+            .create_texture_from_hal::<Vulkan>(hal_texture, &descriptor)
+    };
+
+    Some((Texture::from(texture), export))
+}
+
+unsafe fn create_exported_image(
+    device: &hal_vulkan::Device,
+    extent: wgpu::Extent3d,
+) -> Option<(hal_vulkan::Texture, SharedTextureExport)> {
+    let raw_device = device.raw_device();
+    let instance = device.shared_instance().raw_instance();
+    let physical_device = device.raw_physical_device();
+
+    let mut external_info = vk::ExternalMemoryImageCreateInfo::builder()
+        .handle_types(vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD);
+    let image_info = vk::ImageCreateInfo::builder()
+        .image_type(vk::ImageType::TYPE_2D)
+        .format(vk::Format::R8G8B8A8_SRGB)
+        .extent(vk::Extent3D {
+            width: extent.width,
+            height: extent.height,
+            depth: 1,
+        })
+        .mip_levels(1)
+        .array_layers(1)
+        .samples(vk::SampleCountFlags::TYPE_1)
+        .tiling(vk::ImageTiling::OPTIMAL)
+        .usage(
+            vk::ImageUsageFlags::SAMPLED
+                | vk::ImageUsageFlags::TRANSFER_SRC
+                | vk::ImageUsageFlags::TRANSFER_DST
+                | vk::ImageUsageFlags::COLOR_ATTACHMENT,
+        )
+        .sharing_mode(vk::SharingMode::EXCLUSIVE)
+        .initial_layout(vk::ImageLayout::UNDEFINED)
+        .push_next(&mut external_info);
+    let image = raw_device.create_image(&image_info, None).ok()?;
+
+    let requirements = raw_device.get_image_memory_requirements(image);
+    let memory_properties = instance.get_physical_device_memory_properties(physical_device);
+    let Some(memory_type_index) = (0..memory_properties.memory_type_count).find(|&index| {
+        requirements.memory_type_bits & (1 << index) != 0
+            && memory_properties.memory_types[index as usize]
+                .property_flags
+                .contains(vk::MemoryPropertyFlags::DEVICE_LOCAL)
+    }) else {
+        raw_device.destroy_image(image, None);
+        return None;
+    };
+
+    let mut export_info = vk::ExportMemoryAllocateInfo::builder()
+        .handle_types(vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD);
+    let mut dedicated_info = vk::MemoryDedicatedAllocateInfo::builder().image(image);
+    let allocate_info = vk::MemoryAllocateInfo::builder()
+        .allocation_size(requirements.size)
+        .memory_type_index(memory_type_index)
+        .push_next(&mut export_info)
+        .push_next(&mut dedicated_info);
+    let memory = match raw_device.allocate_memory(&allocate_info, None) {
+        Ok(memory) => memory,
+        Err(_) => {
+            raw_device.destroy_image(image, None);
+            return None;
+        }
+    };
+
+    let exported = ExportedImage {
+        device: raw_device.clone(),
+        image,
+        memory,
+    };
+    raw_device.bind_image_memory(image, memory, 0).ok()?;
+
+    let fd_device = ExternalMemoryFd::new(instance, raw_device);
+    let fd = fd_device
+        .get_memory_fd(
+            &vk::MemoryGetFdInfoKHR::builder()
+                .memory(memory)
+                .handle_type(vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD),
+        )
+        .ok()?;
+
+    let hal_descriptor = wgpu::hal::TextureDescriptor {
+        label: Some("bevy_qml_shared_target"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TARGET_FORMAT,
+        usage: wgpu::hal::TextureUses::RESOURCE
+            | wgpu::hal::TextureUses::COPY_SRC
+            | wgpu::hal::TextureUses::COPY_DST
+            | wgpu::hal::TextureUses::COLOR_TARGET,
+        memory_flags: wgpu::hal::MemoryFlags::empty(),
+        view_formats: Vec::new(),
+    };
+    let texture =
+        hal_vulkan::Device::texture_from_raw(image, &hal_descriptor, Some(Box::new(exported)));
+
+    Some((
+        texture,
+        SharedTextureExport {
+            backend: InteropBackend::VulkanExternalMemory,
+            buffer: 0,
+            handle: fd.into(),
+            fence: -1,
+            allocation_size: requirements.size,
+            size: UVec2::new(extent.width, extent.height),
+        },
+    ))
+}
@@ -0,0 +1,435 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Offscreen rendering of Bevy cameras into images that QML can display.
+
+pub mod adapter;
+mod capture;
+pub mod device;
+mod dynamic_resolution;
+mod image_target;
+pub mod interop;
+mod readback;
+mod resize;
+
+pub use capture::FrameCapture;
+pub use dynamic_resolution::DynamicResolution;
+pub use image_target::{published_sink, QmlImageTarget};
+pub use readback::{Frame, FrameSink};
+pub use resize::{letterbox, RenderTargetPool, ResizeMode};
+
+use interop::{InteropBackend, SharedTextureSlot, SharedTextureTarget};
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{RenderTarget, Viewport},
+        extract_component::ExtractComponentPlugin,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+    },
+    utils::HashMap,
+    window::{PrimaryWindow, WindowRef},
+};
+
+/// The texture format used for every QML facing render target
+///
+/// This matches QImage::Format_RGBA8888 so frames can be handed to Qt as is.
+pub const TARGET_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+/// An offscreen render target that is displayed by a QML item
+#[derive(Component, Clone)]
+pub struct QuickItemTarget {
+    /// The image the cameras render into
+    pub image: Handle<Image>,
+    /// The size of the image in physical pixels
+    pub size: UVec2,
+    /// How many pixels of the image make up a logical pixel of the item,
+    /// see [ResizeMode::scale_factor]
+    pub scale_factor: f32,
+    /// Where the read back frames are published for the QML item
+    pub sink: FrameSink,
+    /// How frames reach the QML item, see [interop::negotiate]
+    pub backend: InteropBackend,
+    /// Where shared textures are published when not copying frames
+    pub shared: SharedTextureSlot,
+}
+
+impl QuickItemTarget {
+    /// Create a new render target of the given size and add its image to the assets
+    pub fn new(images: &mut Assets<Image>, size: UVec2, sink: FrameSink) -> Self {
+        Self {
+            image: images.add(new_render_target_image(size)),
+            size,
+            scale_factor: 1.0,
+            sink,
+            backend: InteropBackend::Copy,
+            shared: SharedTextureSlot::default(),
+        }
+    }
+}
+
+/// Which cameras render into a [QuickItemTarget], besides those pointed at it
+///
+/// Items without a name take the cameras which would render to the primary
+/// window.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct QuickItemView {
+    /// Cameras with a [QmlView] of this name render into the item
+    pub name: String,
+    /// A camera which renders into the item, whatever its [QmlView]
+    pub camera: Option<Entity>,
+    /// Cameras laid out side by side in the item, see [QuickItemViewport]
+    pub viewports: Vec<QuickItemViewport>,
+}
+
+impl QuickItemView {
+    /// Whether the cameras with a [QmlView] of this name render into the item
+    pub fn shows(&self, view: &str) -> bool {
+        self.name == view || self.viewports.iter().any(|viewport| viewport.view == view)
+    }
+}
+
+/// Where the cameras with a [QmlView] of this name show in an item
+///
+/// Several viewports lay out cameras side by side in the same render target,
+/// as for split screen or comparison views. Every camera of the view
+/// renders into the item, with its [Camera::viewport] following the rect as
+/// the item is resized. The cameras render in the order of the viewports,
+/// and only those of the first viewport clear the target.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuickItemViewport {
+    /// The name of the [QmlView] of the cameras
+    pub view: String,
+    /// The part of the item the cameras show in, from zero to one of its
+    /// width and height
+    pub rect: Rect,
+}
+
+/// Renders the camera into the `BevyQuickItem` whose `view` has this name
+///
+/// Several items can show the same world this way, each from its own
+/// camera:
+///
+/// ```ignore
+/// commands.spawn((Camera3dBundle::default(), QmlView::new("top")));
+/// ```
+///
+/// ```qml
+/// BevyQuickItem { view: "top" }
+/// ```
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default)]
+pub struct QmlView(pub String);
+
+impl QmlView {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+}
+
+/// The scale of Bevy UI on screens with a device pixel ratio of one
+///
+/// Cameras render into images at the physical size of their item, which
+/// Bevy UI sees as a scale factor of one. [UiScale] is therefore kept at this
+/// scale times the device pixel ratio of the primary item, and should not be
+/// set directly.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Resource, Default)]
+pub struct QmlUiScale(pub f32);
+
+impl Default for QmlUiScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Create an empty image which can be used as a camera render target and read back
+pub fn new_render_target_image(size: UVec2) -> Image {
+    let extent = Extent3d {
+        width: size.x.max(1),
+        height: size.y.max(1),
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("bevy_qml_target"),
+            size: extent,
+            dimension: TextureDimension::D2,
+            format: TARGET_FORMAT,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_SRC
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(extent);
+    image
+}
+
+/// Resize a render target image if its size differs from the given one
+pub(crate) fn resize_render_target_image(image: &mut Image, size: UVec2) {
+    let extent = Extent3d {
+        width: size.x.max(1),
+        height: size.y.max(1),
+        depth_or_array_layers: 1,
+    };
+    if image.texture_descriptor.size != extent {
+        image.resize(extent);
+    }
+}
+
+/// Renders cameras into [QuickItemTarget]s and reads the frames back for QML
+pub struct QuickItemRenderPlugin;
+
+impl Plugin for QuickItemRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<QmlView>()
+            .register_type::<QmlUiScale>()
+            .init_resource::<QmlUiScale>()
+            .init_resource::<RenderTargetPool>()
+            .add_plugins((
+                ExtractComponentPlugin::<readback::FrameReadback>::default(),
+                readback::ReadbackPlugin,
+                interop::InteropPlugin,
+                device::DeviceLossPlugin,
+            ))
+            .add_systems(
+                PreUpdate,
+                (
+                    rescale_viewports,
+                    sync_ui_scale,
+                    dynamic_resolution::adjust_dynamic_resolution,
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    image_target::update_image_targets,
+                    resize_targets,
+                    retarget_cameras,
+                    layout_viewports,
+                    sync_readbacks,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Resize the images of any targets whose size has changed
+///
+/// With a [RenderTargetPool] the image is swapped for one of the new size
+/// instead, and the old one is kept in the pool.
+fn resize_targets(
+    mut targets: Query<&mut QuickItemTarget, Changed<QuickItemTarget>>,
+    mut images: ResMut<Assets<Image>>,
+    mut pool: ResMut<RenderTargetPool>,
+) {
+    for mut target in &mut targets {
+        let Some(current) = images.get(&target.image).map(Image::size) else {
+            continue;
+        };
+        let size = target.size.max(UVec2::ONE);
+        if current == size {
+            continue;
+        }
+        if pool.capacity == 0 {
+            if let Some(image) = images.get_mut(&target.image) {
+                resize_render_target_image(image, size);
+            }
+            continue;
+        }
+        let image = pool
+            .take(size)
+            .unwrap_or_else(|| images.add(new_render_target_image(size)));
+        let previous = std::mem::replace(&mut target.image, image);
+        pool.put(current, previous);
+    }
+}
+
+/// Keep the viewports of the cameras of an item in place when its device
+/// pixel ratio changes, as they are measured in physical pixels
+fn rescale_viewports(
+    targets: Query<(Entity, &QuickItemTarget), Changed<QuickItemTarget>>,
+    mut removed: RemovedComponents<QuickItemTarget>,
+    mut cameras: Query<&mut Camera>,
+    mut scale_factors: Local<HashMap<Entity, f32>>,
+) {
+    for entity in removed.read() {
+        scale_factors.remove(&entity);
+    }
+    for (entity, target) in &targets {
+        let previous = scale_factors.insert(entity, target.scale_factor);
+        let Some(previous) = previous.filter(|previous| *previous != target.scale_factor) else {
+            continue;
+        };
+        let ratio = target.scale_factor / previous;
+        for mut camera in &mut cameras {
+            if !matches!(&camera.target, RenderTarget::Image(image) if *image == target.image) {
+                continue;
+            }
+            if let Some(viewport) = &mut camera.viewport {
+                viewport.physical_position =
+                    (viewport.physical_position.as_vec2() * ratio).round().as_uvec2();
+                viewport.physical_size = (viewport.physical_size.as_vec2() * ratio)
+                    .round()
+                    .as_uvec2()
+                    .max(UVec2::ONE);
+            }
+        }
+    }
+}
+
+/// Scale Bevy UI with the device pixel ratio of the primary item, or of the
+/// first item if none is primary
+fn sync_ui_scale(
+    targets: Query<(&QuickItemTarget, Has<PrimaryWindow>)>,
+    scale: Res<QmlUiScale>,
+    ui_scale: Option<ResMut<UiScale>>,
+) {
+    let Some(mut ui_scale) = ui_scale else {
+        return;
+    };
+    let scale_factor = targets
+        .iter()
+        .find(|(_, primary)| *primary)
+        .or_else(|| targets.iter().next())
+        .map_or(1.0, |(target, _)| target.scale_factor);
+    let value = scale.0 * scale_factor;
+    if ui_scale.0 != value {
+        ui_scale.0 = value;
+    }
+}
+
+/// Point cameras at the targets of the items they belong to
+///
+/// The camera an item names and the cameras with the [QmlView] of an item
+/// render into it. There is no primary window when embedded in QML, so the
+/// cameras that would render to it are pointed at the first item without a
+/// view name instead, or at the first item if every item has one.
+fn retarget_cameras(
+    targets: Query<(&QuickItemTarget, Option<&QuickItemView>)>,
+    mut cameras: Query<(Entity, &mut Camera, Option<&QmlView>)>,
+) {
+    if targets.is_empty() {
+        return;
+    }
+    let default_image = targets
+        .iter()
+        .find(|(_, view)| view.map_or(true, |view| view.name.is_empty()))
+        .or_else(|| targets.iter().next())
+        .map(|(target, _)| target.image.clone());
+
+    for (entity, mut camera, view) in &mut cameras {
+        let assigned = targets
+            .iter()
+            .find(|(_, item)| item.is_some_and(|item| item.camera == Some(entity)))
+            .or_else(|| {
+                let view = view.filter(|view| !view.0.is_empty())?;
+                targets
+                    .iter()
+                    .find(|(_, item)| item.is_some_and(|item| item.shows(&view.0)))
+            })
+            .map(|(target, _)| target.image.clone());
+
+        let image = match assigned {
+            Some(image) => image,
+            // Cameras of a view wait for its item to appear
+            None if view.is_some() => continue,
+            None if matches!(camera.target, RenderTarget::Window(WindowRef::Primary)) => {
+                match &default_image {
+                    Some(image) => image.clone(),
+                    None => continue,
+                }
+            }
+            None => continue,
+        };
+        if !matches!(&camera.target, RenderTarget::Image(current) if *current == image) {
+            camera.target = RenderTarget::Image(image);
+        }
+    }
+}
+
+/// Fit the cameras laid out in an item to their viewports, see
+/// [QuickItemViewport]
+///
+/// Cameras leaving a layout get the whole target back.
+fn layout_viewports(
+    targets: Query<(&QuickItemTarget, &QuickItemView)>,
+    mut cameras: Query<(Entity, &mut Camera, &QmlView)>,
+    mut laid_out: Local<Vec<Entity>>,
+) {
+    let previous = std::mem::take(&mut *laid_out);
+    for (entity, mut camera, view) in &mut cameras {
+        let slot = targets.iter().find_map(|(target, item)| {
+            if !matches!(&camera.target, RenderTarget::Image(image) if *image == target.image) {
+                return None;
+            }
+            let index = item
+                .viewports
+                .iter()
+                .position(|viewport| viewport.view == view.0)?;
+            Some((index, item.viewports[index].rect, target.size))
+        });
+        let Some((index, rect, size)) = slot else {
+            if previous.contains(&entity) {
+                camera.viewport = None;
+            }
+            continue;
+        };
+        laid_out.push(entity);
+
+        let size = size.as_vec2();
+        let min = (rect.min.clamp(Vec2::ZERO, Vec2::ONE) * size).round();
+        let max = (rect.max.clamp(Vec2::ZERO, Vec2::ONE) * size).round();
+        let viewport = Viewport {
+            physical_position: min.as_uvec2(),
+            physical_size: (max - min).max(Vec2::ONE).as_uvec2(),
+            ..default()
+        };
+        let current = camera
+            .viewport
+            .as_ref()
+            .map(|current| (current.physical_position, current.physical_size));
+        if current != Some((viewport.physical_position, viewport.physical_size)) {
+            camera.viewport = Some(viewport);
+        }
+        let order = index as isize;
+        if camera.order != order {
+            camera.order = order;
+        }
+        if index > 0 && !matches!(camera.clear_color, ClearColorConfig::None) {
+            camera.clear_color = ClearColorConfig::None;
+        }
+    }
+}
+
+/// Keep the render world readback or shared texture in step with the target
+fn sync_readbacks(
+    mut commands: Commands,
+    targets: Query<(Entity, &QuickItemTarget), Changed<QuickItemTarget>>,
+) {
+    for (entity, target) in &targets {
+        let mut entity = commands.entity(entity);
+        if target.backend == InteropBackend::Copy {
+            entity
+                .remove::<SharedTextureTarget>()
+                .insert(readback::FrameReadback {
+                    image: target.image.clone(),
+                    sink: target.sink.clone(),
+                });
+        } else {
+            entity
+                .remove::<readback::FrameReadback>()
+                .insert(SharedTextureTarget {
+                    image: target.image.clone(),
+                    backend: target.backend,
+                    slot: target.shared.clone(),
+                });
+        }
+    }
+}
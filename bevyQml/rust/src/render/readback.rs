@@ -0,0 +1,242 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Copies render target textures into mappable buffers and hands the pixels
+//! over to the Qt side once the GPU has finished with them.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    mpsc, Arc, Mutex,
+};
+
+use bevy::{
+    prelude::*,
+    render::{
+        extract_component::ExtractComponent,
+        render_asset::RenderAssets,
+        render_graph::{self, NodeRunError, RenderGraph, RenderGraphContext, RenderLabel},
+        render_resource::{
+            Buffer, BufferDescriptor, BufferUsages, Extent3d, ImageCopyBuffer, ImageDataLayout,
+            Maintain, MapMode,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::GpuImage,
+        Render, RenderApp, RenderSet,
+    },
+    utils::HashMap,
+};
+
+/// A single frame of tightly packed RGBA8 pixels
+#[derive(Clone, Debug, Default)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+#[derive(Default)]
+struct SinkInner {
+    frame: Mutex<Option<Frame>>,
+    generation: AtomicU64,
+}
+
+/// A single slot shared between the render world and Qt holding the latest frame
+///
+/// Older frames which were not picked up yet are replaced, so Qt always shows
+/// the most recent image and never queues up stale ones.
+#[derive(Clone, Default)]
+pub struct FrameSink(Arc<SinkInner>);
+
+impl FrameSink {
+    /// Replace the pending frame
+    pub fn publish(&self, frame: Frame) {
+        *self.0.frame.lock().unwrap() = Some(frame);
+        self.0.generation.fetch_add(1, Ordering::Release);
+    }
+
+    /// Take the pending frame if there is one
+    pub fn take(&self) -> Option<Frame> {
+        self.0.frame.lock().unwrap().take()
+    }
+
+    /// A copy of the pending frame, leaving it in place for other readers
+    pub fn latest(&self) -> Option<Frame> {
+        self.0.frame.lock().unwrap().clone()
+    }
+
+    /// How many frames have been published so far
+    pub fn generation(&self) -> u64 {
+        self.0.generation.load(Ordering::Acquire)
+    }
+}
+
+/// The render world side of a [super::QuickItemTarget]
+#[derive(Component, Clone, ExtractComponent)]
+pub(crate) struct FrameReadback {
+    pub image: Handle<Image>,
+    pub sink: FrameSink,
+}
+
+struct ReadbackBuffer {
+    buffer: Buffer,
+    size: UVec2,
+    padded_bytes_per_row: u32,
+}
+
+/// Buffers persist across frames, so they live in a resource rather than on
+/// the render entities which are cleared every frame
+#[derive(Resource, Default, Deref, DerefMut)]
+struct ReadbackBuffers(HashMap<AssetId<Image>, ReadbackBuffer>);
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct ReadbackLabel;
+
+pub(crate) struct ReadbackPlugin;
+
+impl Plugin for ReadbackPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<ReadbackBuffers>().add_systems(
+            Render,
+            (
+                prepare_buffers.in_set(RenderSet::PrepareResources),
+                publish_frames
+                    .after(RenderSet::Render)
+                    .before(RenderSet::Cleanup),
+            ),
+        );
+
+        let mut graph = render_app.world_mut().resource_mut::<RenderGraph>();
+        graph.add_node(ReadbackLabel, ReadbackNode);
+        graph.add_node_edge(bevy::render::graph::CameraDriverLabel, ReadbackLabel);
+    }
+}
+
+/// Create a buffer per target, recreating it whenever the target is resized
+fn prepare_buffers(
+    readbacks: Query<&FrameReadback>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    render_device: Res<RenderDevice>,
+    mut buffers: ResMut<ReadbackBuffers>,
+) {
+    buffers.retain(|id, _| readbacks.iter().any(|readback| readback.image.id() == *id));
+
+    for readback in &readbacks {
+        let Some(gpu_image) = gpu_images.get(&readback.image) else {
+            continue;
+        };
+
+        let id = readback.image.id();
+        if buffers
+            .get(&id)
+            .is_some_and(|buffer| buffer.size == gpu_image.size)
+        {
+            continue;
+        }
+
+        let padded_bytes_per_row =
+            RenderDevice::align_copy_bytes_per_row(gpu_image.size.x as usize * 4) as u32;
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("bevy_qml_readback"),
+            size: padded_bytes_per_row as u64 * gpu_image.size.y as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        buffers.insert(
+            id,
+            ReadbackBuffer {
+                buffer,
+                size: gpu_image.size,
+                padded_bytes_per_row,
+            },
+        );
+    }
+}
+
+/// Copies each target texture into its buffer after the cameras have rendered
+struct ReadbackNode;
+
+impl render_graph::Node for ReadbackNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let buffers = world.resource::<ReadbackBuffers>();
+        let gpu_images = world.resource::<RenderAssets<GpuImage>>();
+
+        for (id, readback) in buffers.iter() {
+            let Some(gpu_image) = gpu_images.get(*id) else {
+                continue;
+            };
+            // The image may have been resized this frame before the buffer caught up
+            if gpu_image.size != readback.size {
+                continue;
+            }
+
+            render_context.command_encoder().copy_texture_to_buffer(
+                gpu_image.texture.as_image_copy(),
+                ImageCopyBuffer {
+                    buffer: &readback.buffer,
+                    layout: ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(readback.padded_bytes_per_row),
+                        rows_per_image: None,
+                    },
+                },
+                Extent3d {
+                    width: readback.size.x,
+                    height: readback.size.y,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Map the buffers once the render graph has been submitted and publish the
+/// pixels without the row padding required by wgpu
+fn publish_frames(
+    readbacks: Query<&FrameReadback>,
+    buffers: Res<ReadbackBuffers>,
+    render_device: Res<RenderDevice>,
+) {
+    for readback in &readbacks {
+        let Some(buffer) = buffers.get(&readback.image.id()) else {
+            continue;
+        };
+
+        let slice = buffer.buffer.slice(..);
+        let (sender, receiver) = mpsc::sync_channel(1);
+        render_device.map_buffer(&slice, MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        render_device.poll(Maintain::wait()).panic_on_timeout();
+
+        if !matches!(receiver.recv(), Ok(Ok(()))) {
+            warn!("Failed to map the readback buffer of a QML render target");
+            continue;
+        }
+
+        let row_bytes = buffer.size.x as usize * 4;
+        let mut data = Vec::with_capacity(row_bytes * buffer.size.y as usize);
+        {
+            let mapped = slice.get_mapped_range();
+            for row in mapped.chunks(buffer.padded_bytes_per_row as usize) {
+                data.extend_from_slice(&row[..row_bytes]);
+            }
+        }
+        buffer.buffer.unmap();
+
+        readback.sink.publish(Frame {
+            width: buffer.size.x,
+            height: buffer.size.y,
+            data,
+        });
+    }
+}
@@ -0,0 +1,264 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! How the render target of a `BevyQuickItem` follows the size of the item.
+
+use bevy::prelude::*;
+
+/// How the size of a render target follows its item
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ResizeMode {
+    /// Render at the physical size of the item
+    #[default]
+    Stretch,
+    /// Render at a fixed size in pixels, scaled to fit the item
+    FixedResolution(UVec2),
+    /// Render at a multiple of the physical size of the item, which is
+    /// scaled down when shown
+    SuperSample(f32),
+}
+
+impl ResizeMode {
+    /// The size of the render target for an item of the given logical size,
+    /// shown with the device pixel ratio
+    pub fn target_size(self, logical_size: Vec2, scale_factor: f32) -> UVec2 {
+        let size = match self {
+            Self::Stretch => logical_size * scale_factor,
+            Self::FixedResolution(size) => size.as_vec2(),
+            Self::SuperSample(factor) => logical_size * scale_factor * factor.max(1.0),
+        };
+        size.round().max(Vec2::ONE).as_uvec2()
+    }
+
+    /// The scale factor of the render target, see
+    /// [super::QuickItemTarget::scale_factor]
+    ///
+    /// A fixed resolution does not depend on the screen, so it always has a
+    /// scale factor of one.
+    pub fn scale_factor(self, scale_factor: f32) -> f32 {
+        match self {
+            Self::Stretch => scale_factor,
+            Self::FixedResolution(_) => 1.0,
+            Self::SuperSample(factor) => scale_factor * factor.max(1.0),
+        }
+    }
+
+    /// The aspect ratio a fixed resolution keeps when it is scaled
+    pub fn aspect_ratio(self) -> Option<f32> {
+        match self {
+            Self::FixedResolution(size) if size.x > 0 && size.y > 0 => {
+                Some(size.x as f32 / size.y as f32)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The part of an item of the given size which shows the render target, with
+/// bars on two sides when its aspect ratio differs from the one given
+pub fn letterbox(size: Vec2, aspect_ratio: Option<f32>) -> Rect {
+    let Some(aspect_ratio) = aspect_ratio.filter(|ratio| ratio.is_finite() && *ratio > 0.0) else {
+        return Rect::from_corners(Vec2::ZERO, size);
+    };
+    if size.x <= 0.0 || size.y <= 0.0 {
+        return Rect::from_corners(Vec2::ZERO, size);
+    }
+    let fitted = if size.x / size.y > aspect_ratio {
+        Vec2::new(size.y * aspect_ratio, size.y)
+    } else {
+        Vec2::new(size.x, size.x / aspect_ratio)
+    };
+    Rect::from_center_size(size / 2.0, fitted)
+}
+
+/// Keeps the images of render targets after they were resized, so items
+/// switching back and forth between sizes reuse them instead of allocating
+/// new textures every time
+///
+/// Pooling is off with the default capacity of zero, and targets are
+/// resized in place then:
+///
+/// ```ignore
+/// app.insert_resource(RenderTargetPool::new(4));
+/// ```
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RenderTargetPool {
+    /// How many unused images are kept, the oldest are dropped first
+    pub capacity: usize,
+    images: Vec<(UVec2, Handle<Image>)>,
+}
+
+impl RenderTargetPool {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            images: Vec::new(),
+        }
+    }
+
+    /// How many unused images are kept right now
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// Drop every unused image
+    pub fn clear(&mut self) {
+        self.images.clear();
+    }
+
+    /// Take an image of that size out of the pool
+    pub(crate) fn take(&mut self, size: UVec2) -> Option<Handle<Image>> {
+        let index = self
+            .images
+            .iter()
+            .rposition(|(image_size, _)| *image_size == size)?;
+        Some(self.images.remove(index).1)
+    }
+
+    /// Keep an image no longer in use, if there is room
+    pub(crate) fn put(&mut self, size: UVec2, image: Handle<Image>) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.images.push((size, image));
+        let excess = self.images.len().saturating_sub(self.capacity);
+        self.images.drain(..excess);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(id: u128) -> Handle<Image> {
+        Handle::weak_from_u128(id)
+    }
+
+    #[test]
+    fn stretch_follows_the_scale_factor() {
+        let mode = ResizeMode::Stretch;
+        assert_eq!(
+            mode.target_size(Vec2::new(400.0, 300.0), 1.5),
+            UVec2::new(600, 450)
+        );
+        assert_eq!(
+            mode.target_size(Vec2::new(100.3, 50.7), 1.0),
+            UVec2::new(100, 51)
+        );
+        assert_eq!(mode.scale_factor(1.5), 1.5);
+        assert_eq!(mode.aspect_ratio(), None);
+    }
+
+    #[test]
+    fn fixed_resolution_ignores_the_item() {
+        let mode = ResizeMode::FixedResolution(UVec2::new(1280, 720));
+        assert_eq!(
+            mode.target_size(Vec2::new(400.0, 300.0), 2.0),
+            UVec2::new(1280, 720)
+        );
+        assert_eq!(mode.scale_factor(2.0), 1.0);
+        assert_eq!(mode.aspect_ratio(), Some(1280.0 / 720.0));
+        assert_eq!(
+            ResizeMode::FixedResolution(UVec2::new(0, 720)).aspect_ratio(),
+            None
+        );
+    }
+
+    #[test]
+    fn super_sample_never_renders_smaller() {
+        let mode = ResizeMode::SuperSample(2.0);
+        assert_eq!(
+            mode.target_size(Vec2::new(400.0, 300.0), 1.5),
+            UVec2::new(1200, 900)
+        );
+        assert_eq!(mode.scale_factor(1.5), 3.0);
+        let mode = ResizeMode::SuperSample(0.5);
+        assert_eq!(
+            mode.target_size(Vec2::new(400.0, 300.0), 1.0),
+            UVec2::new(400, 300)
+        );
+        assert_eq!(mode.scale_factor(1.0), 1.0);
+    }
+
+    #[test]
+    fn target_size_is_at_least_a_pixel() {
+        assert_eq!(ResizeMode::Stretch.target_size(Vec2::ZERO, 1.0), UVec2::ONE);
+        assert_eq!(
+            ResizeMode::FixedResolution(UVec2::ZERO).target_size(Vec2::ONE, 1.0),
+            UVec2::ONE
+        );
+    }
+
+    #[test]
+    fn letterbox_fills_without_aspect_ratio() {
+        let size = Vec2::new(400.0, 300.0);
+        let full = Rect::from_corners(Vec2::ZERO, size);
+        assert_eq!(letterbox(size, None), full);
+        assert_eq!(letterbox(size, Some(0.0)), full);
+        assert_eq!(letterbox(size, Some(f32::NAN)), full);
+        assert_eq!(letterbox(size, Some(f32::INFINITY)), full);
+        assert_eq!(
+            letterbox(Vec2::new(0.0, 300.0), Some(2.0)),
+            Rect::from_corners(Vec2::ZERO, Vec2::new(0.0, 300.0))
+        );
+    }
+
+    #[test]
+    fn letterbox_adds_bars() {
+        // Bars above and below a wide ratio
+        assert_eq!(
+            letterbox(Vec2::new(400.0, 300.0), Some(2.0)),
+            Rect::new(0.0, 50.0, 400.0, 250.0)
+        );
+        // Bars left and right of a tall ratio
+        assert_eq!(
+            letterbox(Vec2::new(400.0, 300.0), Some(1.0)),
+            Rect::new(50.0, 0.0, 350.0, 300.0)
+        );
+        assert_eq!(
+            letterbox(Vec2::new(400.0, 200.0), Some(2.0)),
+            Rect::new(0.0, 0.0, 400.0, 200.0)
+        );
+    }
+
+    #[test]
+    fn pool_is_off_by_default() {
+        let mut pool = RenderTargetPool::default();
+        pool.put(UVec2::ONE, image(1));
+        assert!(pool.is_empty());
+        assert_eq!(pool.take(UVec2::ONE), None);
+    }
+
+    #[test]
+    fn pool_reuses_images_by_size() {
+        let mut pool = RenderTargetPool::new(4);
+        pool.put(UVec2::new(800, 600), image(1));
+        pool.put(UVec2::new(1024, 768), image(2));
+        pool.put(UVec2::new(800, 600), image(3));
+        assert_eq!(pool.len(), 3);
+
+        // The most recent image of the size comes first
+        assert_eq!(pool.take(UVec2::new(800, 600)), Some(image(3)));
+        assert_eq!(pool.take(UVec2::new(800, 600)), Some(image(1)));
+        assert_eq!(pool.take(UVec2::new(800, 600)), None);
+        assert_eq!(pool.len(), 1);
+
+        pool.clear();
+        assert_eq!(pool.take(UVec2::new(1024, 768)), None);
+    }
+
+    #[test]
+    fn pool_drops_the_oldest_images() {
+        let mut pool = RenderTargetPool::new(2);
+        pool.put(UVec2::new(1, 1), image(1));
+        pool.put(UVec2::new(2, 2), image(2));
+        pool.put(UVec2::new(3, 3), image(3));
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.take(UVec2::new(1, 1)), None);
+        assert_eq!(pool.take(UVec2::new(2, 2)), Some(image(2)));
+        assert_eq!(pool.take(UVec2::new(3, 3)), Some(image(3)));
+    }
+}
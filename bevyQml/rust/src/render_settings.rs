@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! The graphics options of a camera as plain values, so QML can edit them,
+//! see [crate::cxxqt_bevy_render_settings] for the QML side.
+//!
+//! Most options are components of the camera, such as [BloomSettings],
+//! [Tonemapping] and [FogSettings], and are removed again when turned off.
+//! MSAA is the [Msaa] resource, which is shared by all cameras.
+
+use bevy::{
+    core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping, Skybox},
+    pbr::{FogFalloff, FogSettings},
+    prelude::*,
+    render::camera::Exposure,
+};
+
+use crate::asset;
+
+/// The graphics options of a camera
+#[derive(Clone, Debug, PartialEq)]
+pub struct QmlRenderSettings {
+    pub hdr: bool,
+    /// Samples per pixel, where 1 turns MSAA off
+    pub msaa: u32,
+    pub tonemapping: Tonemapping,
+    /// The exposure in EV100
+    pub exposure: f32,
+    /// The intensity of bloom, if the camera has it
+    pub bloom: Option<f32>,
+    pub fog: Option<QmlFog>,
+    pub skybox: Option<QmlSkybox>,
+    pub environment_map: Option<QmlEnvironmentMap>,
+}
+
+/// Distance fog
+#[derive(Clone, Debug, PartialEq)]
+pub struct QmlFog {
+    pub color: Color,
+    /// Where linear fog starts and where it hides everything, or [None] for
+    /// fog with another falloff, which is then kept
+    pub range: Option<(f32, f32)>,
+}
+
+/// A skybox, from a cubemap image
+#[derive(Clone, Debug, PartialEq)]
+pub struct QmlSkybox {
+    /// The URL the cubemap was loaded from
+    pub url: String,
+    /// The brightness in cd/m²
+    pub brightness: f32,
+}
+
+/// Image based lighting, from a diffuse and a specular cubemap
+#[derive(Clone, Debug, PartialEq)]
+pub struct QmlEnvironmentMap {
+    pub diffuse_url: String,
+    pub specular_url: String,
+    pub intensity: f32,
+}
+
+/// The names QML knows the tonemapping methods by
+const TONEMAPPING_NAMES: [(Tonemapping, &str); 8] = [
+    (Tonemapping::None, "none"),
+    (Tonemapping::Reinhard, "reinhard"),
+    (Tonemapping::ReinhardLuminance, "reinhardLuminance"),
+    (Tonemapping::AcesFitted, "acesFitted"),
+    (Tonemapping::AgX, "agx"),
+    (
+        Tonemapping::SomewhatBoringDisplayTransform,
+        "somewhatBoringDisplayTransform",
+    ),
+    (Tonemapping::TonyMcMapface, "tonyMcMapface"),
+    (Tonemapping::BlenderFilmic, "blenderFilmic"),
+];
+
+/// The name of a tonemapping method
+pub fn tonemapping_name(tonemapping: Tonemapping) -> &'static str {
+    TONEMAPPING_NAMES
+        .iter()
+        .find(|(other, _)| *other == tonemapping)
+        .map_or("none", |(_, name)| name)
+}
+
+/// The tonemapping method with a name, ignoring case
+pub fn tonemapping_from_name(name: &str) -> Option<Tonemapping> {
+    TONEMAPPING_NAMES
+        .iter()
+        .find(|(_, other)| other.eq_ignore_ascii_case(name))
+        .map(|(tonemapping, _)| *tonemapping)
+}
+
+/// The graphics options of a camera, or [None] if the entity is no camera
+pub fn read(world: &World, camera: Entity) -> Option<QmlRenderSettings> {
+    let entity = world.get_entity(camera)?;
+    let hdr = entity.get::<Camera>()?.hdr;
+    Some(QmlRenderSettings {
+        hdr,
+        msaa: world.get_resource::<Msaa>().map_or(1, Msaa::samples),
+        tonemapping: entity.get::<Tonemapping>().copied().unwrap_or_default(),
+        exposure: entity
+            .get::<Exposure>()
+            .map_or(Exposure::default().ev100, |exposure| exposure.ev100),
+        bloom: entity.get::<BloomSettings>().map(|bloom| bloom.intensity),
+        fog: entity.get::<FogSettings>().map(|fog| QmlFog {
+            color: fog.color,
+            range: match fog.falloff {
+                FogFalloff::Linear { start, end } => Some((start, end)),
+                _ => None,
+            },
+        }),
+        skybox: entity.get::<Skybox>().map(|skybox| QmlSkybox {
+            url: image_url(&skybox.image),
+            brightness: skybox.brightness,
+        }),
+        environment_map: entity
+            .get::<EnvironmentMapLight>()
+            .map(|light| QmlEnvironmentMap {
+                diffuse_url: image_url(&light.diffuse_map),
+                specular_url: image_url(&light.specular_map),
+                intensity: light.intensity,
+            }),
+    })
+}
+
+/// Change the graphics options of a camera, returns whether anything changed
+///
+/// Images are only loaded again when their URL changes.
+pub fn apply(world: &mut World, camera: Entity, settings: QmlRenderSettings) -> bool {
+    let Some(current) = read(world, camera) else {
+        return false;
+    };
+    if current == settings {
+        return false;
+    }
+    let Some(asset_server) = world.get_resource::<AssetServer>().cloned() else {
+        return false;
+    };
+
+    if current.msaa != settings.msaa {
+        world.insert_resource(match settings.msaa {
+            0 | 1 => Msaa::Off,
+            2 => Msaa::Sample2,
+            3..=4 => Msaa::Sample4,
+            _ => Msaa::Sample8,
+        });
+    }
+
+    let mut entity = world.entity_mut(camera);
+    if let Some(mut camera) = entity.get_mut::<Camera>() {
+        camera.hdr = settings.hdr;
+    }
+    entity.insert((
+        settings.tonemapping,
+        Exposure {
+            ev100: settings.exposure,
+        },
+    ));
+
+    match settings.bloom {
+        Some(intensity) => match entity.get_mut::<BloomSettings>() {
+            Some(mut bloom) => bloom.intensity = intensity,
+            None => {
+                entity.insert(BloomSettings {
+                    intensity,
+                    ..BloomSettings::NATURAL
+                });
+            }
+        },
+        None => {
+            entity.remove::<BloomSettings>();
+        }
+    }
+
+    match settings.fog {
+        Some(fog) => {
+            let mut current = entity.get::<FogSettings>().cloned().unwrap_or_default();
+            current.color = fog.color;
+            if let Some((start, end)) = fog.range {
+                current.falloff = FogFalloff::Linear { start, end };
+            }
+            entity.insert(current);
+        }
+        None => {
+            entity.remove::<FogSettings>();
+        }
+    }
+
+    match settings.skybox {
+        Some(skybox) if !skybox.url.is_empty() => {
+            let image = entity
+                .get::<Skybox>()
+                .map(|current| current.image.clone())
+                .filter(|image| image_url(image) == skybox.url)
+                .or_else(|| load_image(&asset_server, &skybox.url));
+            if let Some(image) = image {
+                entity.insert(Skybox {
+                    image,
+                    brightness: skybox.brightness,
+                });
+            }
+        }
+        _ => {
+            entity.remove::<Skybox>();
+        }
+    }
+
+    match settings.environment_map {
+        Some(map) if !map.diffuse_url.is_empty() && !map.specular_url.is_empty() => {
+            let current = entity.get::<EnvironmentMapLight>();
+            let diffuse_map = current
+                .map(|current| current.diffuse_map.clone())
+                .filter(|image| image_url(image) == map.diffuse_url)
+                .or_else(|| load_image(&asset_server, &map.diffuse_url));
+            let specular_map = current
+                .map(|current| current.specular_map.clone())
+                .filter(|image| image_url(image) == map.specular_url)
+                .or_else(|| load_image(&asset_server, &map.specular_url));
+            if let (Some(diffuse_map), Some(specular_map)) = (diffuse_map, specular_map) {
+                entity.insert(EnvironmentMapLight {
+                    diffuse_map,
+                    specular_map,
+                    intensity: map.intensity,
+                });
+            }
+        }
+        _ => {
+            entity.remove::<EnvironmentMapLight>();
+        }
+    }
+    true
+}
+
+/// The URL an image was loaded from, empty for images made in code
+fn image_url(image: &Handle<Image>) -> String {
+    image.path().map(ToString::to_string).unwrap_or_default()
+}
+
+fn load_image(asset_server: &AssetServer, url: &str) -> Option<Handle<Image>> {
+    match asset::resolve_url(url) {
+        Ok(path) => Some(asset_server.load(path)),
+        Err(error) => {
+            warn!("Cannot load the image {url:?} for a camera: {error}");
+            None
+        }
+    }
+}
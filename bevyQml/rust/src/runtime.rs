@@ -0,0 +1,1029 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Hosts the Bevy [App] inside the Qt event loop.
+//!
+//! [crate::plugin::BevyQmlPlugin] hands the app over to this module instead of
+//! running a loop of its own. A QTimer on the GUI thread then pumps the Bevy
+//! schedules, so the QGuiApplication event loop owns the frame tick and QML
+//! objects can reach the world directly from the GUI thread.
+//!
+//! A QCoreApplication is enough for headless apps without windows, such as
+//! simulations on a server or automated tests, see
+//! [crate::plugin::bevy_qml_headless_plugins]. The bridge objects then work
+//! as usual, only the parts needing Qt GUI, like `BevyQuickItem`, the
+//! clipboard and the palette, are left out. [has_gui] tells which kind of
+//! application runs.
+//!
+//! With [FramePacing::SceneGraph] the app is updated once per frame of the
+//! QML scene graph instead, from the `afterAnimating` signal of the window
+//! showing a `BevyQuickItem`, and the QTimer only takes over while no window
+//! renders.
+//!
+//! With [crate::redraw::UpdateMode::OnDemand] the app is only updated after
+//! [request_update] or when the previous update changed the scene, and the
+//! QTimer stops while nothing happens.
+//!
+//! While every window showing a `BevyQuickItem` is hidden or minimized, the
+//! [crate::redraw::HiddenWindowPolicy] decides whether the app keeps running,
+//! runs at a lower rate or pauses until a window is shown again.
+//!
+//! Independent apps can be hosted next to the main one with [install_world],
+//! e.g. for windows showing a world of their own. They are updated right
+//! after the main app and are reached by name with [with_world_in].
+//!
+//! Alternatively [spawn_threaded] runs the app on a thread of its own, so
+//! heavy simulations do not block the GUI thread and vice versa. The GUI
+//! thread then never touches the world: closures are sent to it through a
+//! lock-free channel with [send], and state is read from the triple buffered
+//! snapshots of [crate::snapshot] with [snapshot]. [with_world] and the QML
+//! elements built on it see no app in this mode.
+//!
+//! Panics while updating an app or running a closure with its world are
+//! caught by [crate::panic] rather than unwinding into Qt. The main app is
+//! then [is_degraded] and no longer updated until [recover] is called.
+//!
+//! When the GPU device of the main app is lost, see [crate::render::device],
+//! the app is torn down rather than updated against a device that no longer
+//! works. It is built again with a new device if [rebuild_with] told how, and
+//! the [generation] of the main app goes up either way, so the QML items
+//! start over with targets in the new app and tell the QML about the loss.
+//!
+//! The apps are torn down by [shutdown] when the Qt event loop is about to
+//! quit, while the windows and the graphics of Qt still exist: the open QML
+//! windows are closed, the commands queued by QML are applied, the GPU is
+//! waited for and the apps are dropped, after joining the thread of a
+//! threaded app.
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_runtime")]
+mod ffi {
+    unsafe extern "C++Qt" {
+        include!(<QtCore/QTimer>);
+        /// The timer which pumps the Bevy schedules
+        #[qobject]
+        type QTimer;
+
+        /// When the QTimer timeout occurs
+        #[qsignal]
+        fn timeout(self: Pin<&mut QTimer>);
+
+        fn start(self: Pin<&mut QTimer>, msec: i32);
+        fn stop(self: Pin<&mut QTimer>);
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/runtime.h");
+
+        #[doc(hidden)]
+        #[rust_name = "qtimer_new"]
+        fn qtimerNew() -> UniquePtr<QTimer>;
+
+        #[doc(hidden)]
+        #[rust_name = "has_gui_application"]
+        fn hasGuiApplication() -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "core_application_exit"]
+        fn coreApplicationExit(code: i32);
+
+        #[doc(hidden)]
+        #[rust_name = "shutdown_on_about_to_quit"]
+        fn shutdownOnAboutToQuit();
+    }
+
+    #[namespace = "bevyqml"]
+    extern "Rust" {
+        /// A window is about to render a frame, see [super::FramePacing]
+        #[cxx_name = "sceneGraphFrame"]
+        fn scene_graph_frame(window: usize);
+
+        #[cxx_name = "requestUpdate"]
+        fn request_update();
+
+        /// A window showing an item was shown, hidden or minimized
+        #[cxx_name = "windowVisibilityChanged"]
+        fn window_visibility_changed(window: usize, visible: bool);
+
+        #[cxx_name = "windowDestroyed"]
+        fn window_destroyed(window: usize);
+
+        /// The Qt event loop is about to quit
+        fn shutdown();
+    }
+}
+
+use std::{
+    any::{Any, TypeId},
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    app::AppExit,
+    ecs::event::Events,
+    prelude::*,
+    render::{render_resource::Maintain, renderer::RenderDevice},
+    window::RequestRedraw,
+};
+use crossbeam_channel::{Receiver, Sender};
+use cxx::UniquePtr;
+use cxx_qt::QMetaObjectConnectionGuard;
+
+use crate::{
+    batch, commands,
+    input::gamepad,
+    panic::{self, catch_panic},
+    redraw::{HiddenWindowPolicy, UpdateMode},
+    render::device,
+    snapshot::{self, SnapshotReader},
+    trace, window,
+};
+
+/// How many updates still run on demand after the scene last changed, so
+/// frames read back and pipelines compiled in the meantime show up
+const LINGER_UPDATES: u32 = 3;
+
+/// What drives the updates of the hosted app
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FramePacing {
+    /// Update at the tick interval of a QTimer, independent of rendering
+    #[default]
+    Timer,
+    /// Update exactly once per frame of the QML scene graph
+    ///
+    /// The first window to render paces the app, as long as it keeps
+    /// rendering. When no window has rendered for a few tick intervals,
+    /// e.g. as they are all hidden, the QTimer updates the app instead.
+    SceneGraph,
+}
+
+struct Host {
+    app: App,
+    timer: UniquePtr<ffi::QTimer>,
+    _timeout: QMetaObjectConnectionGuard,
+    pacing: FramePacing,
+    tick_interval: Duration,
+    /// The window pacing the updates, and when it last rendered
+    paced_by: Option<(usize, Instant)>,
+    /// Whether the app is updated on demand, as of the last update
+    on_demand: bool,
+    /// Updates left to run on demand without a request
+    linger: u32,
+    /// Whether nothing needs an update while updating on demand
+    idle: bool,
+    /// How often the timer polls the gamepads while idle, as of the last
+    /// update
+    idle_poll: Option<Duration>,
+    /// What to do while every window is hidden, as of the last update
+    hidden_policy: HiddenWindowPolicy,
+    /// The interval the timer runs at, [None] while it is stopped
+    timer_interval: Option<Duration>,
+    /// Whether an update panicked, which stops the updates until [recover]
+    degraded: bool,
+}
+
+impl Host {
+    /// Whether a window rendered recently enough to be pacing the app
+    fn is_paced(&self, now: Instant) -> bool {
+        self.pacing == FramePacing::SceneGraph
+            && self
+                .paced_by
+                .is_some_and(|(_, at)| now.duration_since(at) < self.tick_interval * 4)
+    }
+
+    fn wants_update(&self) -> bool {
+        !self.degraded && (!self.on_demand || REQUESTED.get() || self.linger > 0)
+    }
+
+    /// Start, stop or slow down the timer to match the demand for updates
+    /// and the visibility of the windows
+    fn reschedule(&mut self) {
+        let interval = if self.degraded {
+            None
+        } else if self.idle {
+            self.idle_poll
+        } else if all_windows_hidden() {
+            match self.hidden_policy {
+                HiddenWindowPolicy::KeepRunning => Some(self.tick_interval),
+                HiddenWindowPolicy::Throttle { interval } => Some(interval.max(self.tick_interval)),
+                HiddenWindowPolicy::Pause => None,
+            }
+        } else {
+            Some(self.tick_interval)
+        };
+        if interval == self.timer_interval {
+            return;
+        }
+
+        self.timer_interval = interval;
+        match interval {
+            Some(interval) => {
+                let msec = interval.as_millis().try_into().unwrap_or(i32::MAX);
+                self.timer.pin_mut().start(msec);
+            }
+            None => self.timer.pin_mut().stop(),
+        }
+    }
+}
+
+type Listener = Rc<RefCell<Option<Box<dyn FnMut()>>>>;
+
+thread_local! {
+    static HOST: RefCell<Option<Host>> = const { RefCell::new(None) };
+    static LISTENERS: RefCell<Vec<Listener>> = const { RefCell::new(Vec::new()) };
+    static WORLDS: RefCell<BTreeMap<String, App>> = const { RefCell::new(BTreeMap::new()) };
+    static REQUESTED: Cell<bool> = const { Cell::new(false) };
+    /// Whether each window showing an item is visible
+    static WINDOWS: RefCell<BTreeMap<usize, bool>> = const { RefCell::new(BTreeMap::new()) };
+    static THREADED: RefCell<Option<ThreadedHost>> = const { RefCell::new(None) };
+    /// The snapshot readers taken so far, by the type they read
+    static SNAPSHOTS: RefCell<BTreeMap<TypeId, Box<dyn Any>>> = const { RefCell::new(BTreeMap::new()) };
+    static REBUILD: RefCell<Option<Box<dyn FnMut() -> App>>> = const { RefCell::new(None) };
+    /// How often the main app was torn down after losing its device
+    static GENERATION: Cell<u64> = const { Cell::new(0) };
+    /// Why the main app last lost its device
+    static DEVICE_LOSS: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// A closure sent to an app running on a thread of its own
+type WorldCommand = Box<dyn FnOnce(&mut World) + Send>;
+
+/// The GUI thread side of an app running on a thread of its own
+struct ThreadedHost {
+    commands: Sender<WorldCommand>,
+    /// How many updates the app has finished
+    frames: Arc<AtomicU64>,
+    seen_frames: u64,
+    stop: Arc<AtomicBool>,
+    /// Set by the app thread when the app asked to exit
+    exit: Arc<Mutex<Option<AppExit>>>,
+    /// The panics caught on the app thread, until they are reported here
+    errors: Arc<Mutex<Vec<String>>>,
+    degraded: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    timer: UniquePtr<ffi::QTimer>,
+    _timeout: QMetaObjectConnectionGuard,
+}
+
+/// Take ownership of the app and start pumping it from the Qt event loop
+///
+/// This must be called from the GUI thread once the QCoreApplication exists.
+pub fn install(app: App, tick_interval: Duration, pacing: FramePacing) {
+    if is_running() {
+        warn!("A Bevy app is already hosted by the Qt event loop, ignoring the new one");
+        return;
+    }
+
+    ffi::shutdown_on_about_to_quit();
+    let mut timer = ffi::qtimer_new();
+    let timeout = timer.pin_mut().on_timeout(|_| timer_tick());
+    timer
+        .pin_mut()
+        .start(tick_interval.as_millis().try_into().unwrap_or(i32::MAX));
+
+    HOST.with(|host| {
+        *host.borrow_mut() = Some(Host {
+            app,
+            timer,
+            _timeout: timeout,
+            pacing,
+            tick_interval,
+            paced_by: None,
+            on_demand: false,
+            linger: 0,
+            idle: false,
+            idle_poll: None,
+            hidden_policy: HiddenWindowPolicy::default(),
+            timer_interval: Some(tick_interval),
+            degraded: false,
+        })
+    });
+}
+
+/// Whether the application is a QGuiApplication, rather than a headless
+/// QCoreApplication
+pub fn has_gui() -> bool {
+    ffi::has_gui_application()
+}
+
+/// Ask for an update of the app, which only matters while it is updated
+/// on demand
+///
+/// QML elements call this when they change the world or receive input.
+pub fn request_update() {
+    REQUESTED.set(true);
+    HOST.with(|host| {
+        let Ok(mut host) = host.try_borrow_mut() else {
+            return;
+        };
+        if let Some(host) = host.as_mut().filter(|host| host.idle) {
+            host.idle = false;
+            host.reschedule();
+        }
+    });
+}
+
+/// Whether there are windows showing items and none of them is visible
+fn all_windows_hidden() -> bool {
+    WINDOWS.with(|windows| {
+        let windows = windows.borrow();
+        !windows.is_empty() && windows.values().all(|visible| !visible)
+    })
+}
+
+fn window_visibility_changed(window: usize, visible: bool) {
+    let changed =
+        WINDOWS.with(|windows| windows.borrow_mut().insert(window, visible) != Some(visible));
+    if changed {
+        reschedule();
+    }
+}
+
+fn window_destroyed(window: usize) {
+    WINDOWS.with(|windows| windows.borrow_mut().remove(&window));
+    reschedule();
+}
+
+/// Reschedule the timer now, or after the update that is running
+fn reschedule() {
+    HOST.with(|host| {
+        if let Ok(mut host) = host.try_borrow_mut() {
+            if let Some(host) = host.as_mut() {
+                host.reschedule();
+            }
+        }
+    });
+}
+
+/// Whether an app is currently hosted by the Qt event loop
+pub fn is_running() -> bool {
+    HOST.with(|host| host.try_borrow().map_or(true, |host| host.is_some()))
+}
+
+/// Run the closure with the hosted app
+///
+/// Returns [None] if there is no app, if it is currently being updated, or
+/// if the closure panicked.
+pub fn with_app<R>(f: impl FnOnce(&mut App) -> R) -> Option<R> {
+    let result = HOST.with(|host| {
+        let mut host = host.try_borrow_mut().ok()?;
+        host.as_mut().map(|host| panic::catch(|| f(&mut host.app)))
+    })?;
+    // Reported once the app is released, so the listeners can reach it
+    result
+        .map_err(|message| {
+            panic::report_error(format!("QML using the Bevy world panicked: {message}"))
+        })
+        .ok()
+}
+
+/// Whether an update of the main app panicked
+///
+/// The app is no longer updated then, as its state may be inconsistent, but
+/// its world can still be read and fixed up before calling [recover].
+pub fn is_degraded() -> bool {
+    let hosted = HOST.with(|host| {
+        host.try_borrow()
+            .is_ok_and(|host| host.as_ref().is_some_and(|host| host.degraded))
+    });
+    hosted
+        || THREADED.with(|threaded| {
+            threaded.try_borrow().is_ok_and(|threaded| {
+                threaded
+                    .as_ref()
+                    .is_some_and(|threaded| threaded.degraded.load(Ordering::Acquire))
+            })
+        })
+}
+
+/// Resume updating the main app after a panic, see [is_degraded]
+pub fn recover() {
+    HOST.with(|host| {
+        if let Ok(mut host) = host.try_borrow_mut() {
+            if let Some(host) = host.as_mut().filter(|host| host.degraded) {
+                info!("Resuming the updates of the Bevy app");
+                host.degraded = false;
+                host.idle = false;
+                host.reschedule();
+            }
+        }
+    });
+    THREADED.with(|threaded| {
+        if let Ok(threaded) = threaded.try_borrow() {
+            if let Some(threaded) = threaded.as_ref() {
+                threaded.degraded.store(false, Ordering::Release);
+            }
+        }
+    });
+}
+
+/// Build the main app anew with the closure after its GPU device was lost
+///
+/// The closure builds the app just as it was built at first, it is then run
+/// and hosted in place of the lost one. Without it the app stays torn down.
+///
+/// ```ignore
+/// let build = || {
+///     let mut app = App::new();
+///     app.add_plugins((bevy_qml_default_plugins(), BevyQmlPlugin::default()));
+///     app
+/// };
+/// runtime::rebuild_with(build);
+/// build().run();
+/// ```
+pub fn rebuild_with(build: impl FnMut() -> App + 'static) {
+    REBUILD.with(|rebuild| *rebuild.borrow_mut() = Some(Box::new(build)));
+}
+
+/// How often the main app was torn down after losing its GPU device
+///
+/// Entities of the main app from an earlier generation are gone.
+pub fn generation() -> u64 {
+    GENERATION.get()
+}
+
+/// Why the main app last lost its GPU device, if it ever did
+pub fn device_loss() -> Option<String> {
+    DEVICE_LOSS.with(|loss| loss.borrow().clone())
+}
+
+/// Run the closure with the world of the hosted app
+///
+/// Returns [None] if there is no app, or if it is currently being updated.
+pub fn with_world<R>(f: impl FnOnce(&mut World) -> R) -> Option<R> {
+    with_app(|app| f(app.world_mut()))
+}
+
+/// Host an independent app under a name, next to the main app
+///
+/// The app must have finished adding its plugins. It is updated after the
+/// main app for as long as that runs, and replaces any app hosted under the
+/// same name before. The name must not be empty, which stands for the main
+/// app.
+pub fn install_world(name: impl Into<String>, app: App) {
+    let name = name.into();
+    if name.is_empty() {
+        warn!("Independent Bevy apps need a name, ignoring the app");
+        return;
+    }
+    let replaced = WORLDS.with(|worlds| worlds.borrow_mut().insert(name.clone(), app));
+    if replaced.is_some() {
+        info!("Replaced the Bevy app hosted as {name:?}");
+    }
+}
+
+/// Stop updating the independent app hosted under the name and hand it back
+pub fn uninstall_world(name: &str) -> Option<App> {
+    WORLDS.with(|worlds| worlds.try_borrow_mut().ok()?.remove(name))
+}
+
+/// The names of the independent apps, in alphabetical order
+pub fn world_names() -> Vec<String> {
+    WORLDS.with(|worlds| {
+        worlds
+            .try_borrow()
+            .map_or_else(|_| Vec::new(), |worlds| worlds.keys().cloned().collect())
+    })
+}
+
+/// Run the closure with the independent app of that name, or with the main
+/// app if the name is empty
+///
+/// Returns [None] if there is no such app, or if it is currently being updated.
+pub fn with_app_in<R>(name: &str, f: impl FnOnce(&mut App) -> R) -> Option<R> {
+    if name.is_empty() {
+        return with_app(f);
+    }
+    let result = WORLDS.with(|worlds| {
+        let mut worlds = worlds.try_borrow_mut().ok()?;
+        worlds.get_mut(name).map(|app| panic::catch(|| f(app)))
+    })?;
+    result
+        .map_err(|message| {
+            panic::report_error(format!(
+                "QML using the Bevy world {name:?} panicked: {message}"
+            ))
+        })
+        .ok()
+}
+
+/// Run the closure with the world of the app of that name, see [with_app_in]
+pub fn with_world_in<R>(name: &str, f: impl FnOnce(&mut World) -> R) -> Option<R> {
+    with_app_in(name, |app| f(app.world_mut()))
+}
+
+/// Build and run the app on a thread of its own
+///
+/// `build` runs on the new thread, as apps cannot move between threads, and
+/// should not add [crate::plugin::BevyQmlPlugin], whose runner hosts the app
+/// on the GUI thread. The app is then updated once per tick interval. Update
+/// listeners run on the GUI thread after every update, at most once per
+/// tick interval, and exiting the app quits the Qt event loop.
+///
+/// This must be called from the GUI thread once the QGuiApplication exists.
+pub fn spawn_threaded(build: impl FnOnce() -> App + Send + 'static, tick_interval: Duration) {
+    if is_running() || is_threaded() {
+        warn!("A Bevy app is already running, ignoring the new one");
+        return;
+    }
+
+    let (commands, receiver) = crossbeam_channel::unbounded();
+    let frames = Arc::new(AtomicU64::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+    let exit = Arc::new(Mutex::new(None));
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let degraded = Arc::new(AtomicBool::new(false));
+    let thread = {
+        let shared = ThreadShared {
+            frames: frames.clone(),
+            stop: stop.clone(),
+            exit: exit.clone(),
+            errors: errors.clone(),
+            degraded: degraded.clone(),
+        };
+        std::thread::Builder::new()
+            .name("bevy".into())
+            .spawn(move || {
+                let built = panic::catch(|| {
+                    let mut app = build();
+                    crate::plugin::finish_plugins(&mut app);
+                    app
+                });
+                match built {
+                    Ok(app) => run_threaded(app, receiver, tick_interval, &shared),
+                    Err(message) => {
+                        shared.error(format!("Building the Bevy app panicked: {message}"));
+                        *shared.exit.lock().unwrap() = Some(AppExit::error());
+                    }
+                }
+            })
+            .expect("Failed to spawn the Bevy thread")
+    };
+
+    ffi::shutdown_on_about_to_quit();
+    let mut timer = ffi::qtimer_new();
+    let timeout = timer.pin_mut().on_timeout(|_| poll_threaded());
+    timer
+        .pin_mut()
+        .start(tick_interval.as_millis().try_into().unwrap_or(i32::MAX));
+
+    THREADED.with(|threaded| {
+        *threaded.borrow_mut() = Some(ThreadedHost {
+            commands,
+            frames,
+            seen_frames: 0,
+            stop,
+            exit,
+            errors,
+            degraded,
+            thread: Some(thread),
+            timer,
+            _timeout: timeout,
+        })
+    });
+}
+
+/// Whether an app runs on a thread of its own, see [spawn_threaded]
+pub fn is_threaded() -> bool {
+    THREADED.with(|threaded| {
+        threaded
+            .try_borrow()
+            .map_or(true, |threaded| threaded.is_some())
+    })
+}
+
+/// Run the closure with the world of the app, wherever the app runs
+///
+/// The closure runs before the next update of an app on a thread of its own,
+/// and straight away otherwise. Returns whether there is an app to run it.
+pub fn send(f: impl FnOnce(&mut World) + Send + 'static) -> bool {
+    let queued = THREADED.with(|threaded| {
+        let threaded = threaded.try_borrow().ok()?;
+        let threaded = threaded.as_ref()?;
+        Some(threaded.commands.send(Box::new(f)).is_ok())
+    });
+    match queued {
+        Some(queued) => queued,
+        None => {
+            let ran = with_world(f).is_some();
+            request_update();
+            ran
+        }
+    }
+}
+
+/// Run the closure with the latest snapshot of type `T`
+///
+/// Returns [None] unless a [crate::snapshot::QmlSnapshotPlugin] publishes
+/// snapshots of that type. This never waits for the app, wherever it runs.
+pub fn snapshot<T: 'static, R>(f: impl FnOnce(&T) -> R) -> Option<R> {
+    SNAPSHOTS.with(|snapshots| {
+        let mut snapshots = snapshots.try_borrow_mut().ok()?;
+        let type_id = TypeId::of::<T>();
+        if !snapshots.contains_key(&type_id) {
+            let reader = snapshot::take_reader::<T>()?;
+            snapshots.insert(type_id, Box::new(reader));
+        }
+        let reader = snapshots
+            .get_mut(&type_id)?
+            .downcast_mut::<SnapshotReader<T>>()?;
+        reader.update();
+        Some(f(reader.read()))
+    })
+}
+
+/// Stop pumping and tear down the hosted apps
+///
+/// This runs when the Qt event loop is about to quit, and can be called
+/// earlier. Calling it again does nothing.
+pub fn shutdown() {
+    // Nothing is updated from here on
+    let host = HOST.with(|host| host.try_borrow_mut().ok()?.take());
+    let threaded = THREADED.with(|threaded| threaded.try_borrow_mut().ok()?.take());
+    let worlds = WORLDS.with(|worlds| std::mem::take(&mut *worlds.borrow_mut()));
+    if host.is_none() && threaded.is_none() && worlds.is_empty() {
+        return;
+    }
+
+    // The items of the other windows release their targets while the apps
+    // still exist
+    window::close_all();
+
+    if let Some(mut threaded) = threaded {
+        threaded.timer.pin_mut().stop();
+        threaded.stop.store(true, Ordering::Release);
+        if let Some(thread) = threaded.thread.take() {
+            if thread.join().is_err() {
+                error!("The Bevy thread panicked");
+            }
+        }
+    }
+    // Torn down outside of the borrows, as dropping an app may reach back in here
+    for (_, app) in worlds {
+        catch_panic("Tearing down a Bevy app", || tear_down(app));
+    }
+    if let Some(mut host) = host {
+        host.timer.pin_mut().stop();
+        catch_panic("Tearing down the Bevy app", || tear_down(host.app));
+    }
+
+    WINDOWS.with(|windows| windows.borrow_mut().clear());
+    SNAPSHOTS.with(|snapshots| snapshots.borrow_mut().clear());
+}
+
+/// Apply what QML queued and let the GPU finish, then drop the app
+///
+/// Dropping the app waits for a frame still being rendered, and destroys the
+/// render targets shared with Qt before its graphics go away. A lost device
+/// has nothing left to finish.
+fn tear_down(mut app: App) {
+    if app.world().contains_resource::<commands::QmlCommandQueue>() {
+        commands::apply_qml_commands(app.world_mut());
+    }
+    let device = app
+        .world()
+        .get_resource::<RenderDevice>()
+        .filter(|_| device::lost_reason(app.world()).is_none())
+        .cloned();
+    if let Some(device) = &device {
+        device.wgpu_device().poll(Maintain::Wait);
+    }
+    drop(app);
+    if let Some(device) = device {
+        device.wgpu_device().poll(Maintain::Wait);
+    }
+}
+
+/// Keeps a callback registered with [on_update] alive
+///
+/// The callback is removed when this is dropped.
+pub struct UpdateListener(Listener);
+
+impl Drop for UpdateListener {
+    fn drop(&mut self) {
+        if let Ok(mut callback) = self.0.try_borrow_mut() {
+            callback.take();
+        }
+    }
+}
+
+/// Register a callback which is run on the GUI thread after every update
+pub fn on_update(callback: impl FnMut() + 'static) -> UpdateListener {
+    let listener: Listener = Rc::new(RefCell::new(Some(Box::new(callback))));
+    LISTENERS.with(|listeners| listeners.borrow_mut().push(listener.clone()));
+    UpdateListener(listener)
+}
+
+fn timer_tick() {
+    let update_now = HOST.with(|host| {
+        host.try_borrow().is_ok_and(|host| {
+            host.as_ref()
+                .is_some_and(|host| host.wants_update() && !host.is_paced(Instant::now()))
+        })
+    });
+    if update_now {
+        update();
+        return;
+    }
+
+    // Input from gamepads does not go through the Qt event loop, so an idle
+    // app polls them to find out when to wake up
+    let woken = HOST.with(|host| {
+        host.try_borrow_mut().is_ok_and(|mut host| {
+            host.as_mut()
+                .is_some_and(|host| host.idle && gamepad::poll_idle(host.app.world_mut()))
+        })
+    });
+    if woken {
+        request_update();
+    }
+}
+
+fn scene_graph_frame(window: usize) {
+    trace::instant("scene graph frame");
+    let update_now = HOST.with(|host| {
+        let Ok(mut host) = host.try_borrow_mut() else {
+            return false;
+        };
+        let Some(host) = host.as_mut() else {
+            return false;
+        };
+        if host.pacing != FramePacing::SceneGraph || !host.wants_update() {
+            return false;
+        }
+
+        // Other windows only take over once the pacing one stops rendering,
+        // so several windows do not update the app more than once per frame
+        let now = Instant::now();
+        let driver = host.paced_by.map(|(driver, _)| driver);
+        if driver.is_some_and(|driver| driver != window) && host.is_paced(now) {
+            return false;
+        }
+        host.paced_by = Some((window, now));
+        true
+    });
+    if update_now {
+        update();
+    }
+}
+
+/// Update the app inside a span for [crate::trace], returning the message
+/// of its panic if it panicked
+fn update_app(app: &mut App) -> Result<(), String> {
+    let _span = info_span!("bevy app update").entered();
+    panic::catch(|| app.update())
+}
+
+fn update() {
+    let mut panicked = None;
+    let mut lost = None;
+    let exit = HOST.with(|host| match host.try_borrow_mut() {
+        Ok(mut host) => host.as_mut().and_then(|host| {
+            REQUESTED.set(false);
+            let updated = update_app(&mut host.app);
+            // Whatever failed once the device was lost failed because of it
+            lost = device::lost_reason(host.app.world());
+            if lost.is_some() {
+                return None;
+            }
+            if let Err(message) = updated {
+                host.degraded = true;
+                host.reschedule();
+                panicked = Some(message);
+                return None;
+            }
+            settle(host);
+            host.app.should_exit()
+        }),
+        // Re-entered from within an update, e.g. by a nested event loop
+        Err(_) => None,
+    });
+
+    if let Some(message) = panicked {
+        panic::report_error(format!(
+            "Updating the Bevy app panicked, it is paused until it recovers: {message}"
+        ));
+    }
+    if let Some(reason) = lost {
+        device_lost(reason);
+    }
+    update_worlds();
+    batch::flush();
+    notify_listeners();
+
+    if let Some(exit) = exit {
+        shutdown();
+        ffi::core_application_exit(match exit {
+            AppExit::Success => 0,
+            AppExit::Error(code) => code.get().into(),
+        });
+    }
+}
+
+/// Tear down the main app after its GPU device was lost, and build it again
+/// if [rebuild_with] told how
+fn device_lost(reason: String) {
+    let Some(mut host) = HOST.with(|host| host.borrow_mut().take()) else {
+        return;
+    };
+    error!("The GPU device of the Bevy app was lost, tearing the app down: {reason}");
+    host.timer.pin_mut().stop();
+    catch_panic("Tearing down the Bevy app", || tear_down(host.app));
+    DEVICE_LOSS.with(|loss| *loss.borrow_mut() = Some(reason));
+    GENERATION.set(GENERATION.get() + 1);
+
+    // Taken out while building, as the app may reach back in here
+    let Some(mut build) = REBUILD.with(|rebuild| rebuild.borrow_mut().take()) else {
+        return;
+    };
+    info!("Building the Bevy app again with a new GPU device");
+    if let Some(mut app) = catch_panic("Building the Bevy app", || build()) {
+        app.run();
+    }
+    REBUILD.with(|rebuild| {
+        rebuild.borrow_mut().get_or_insert(build);
+    });
+}
+
+/// Pick up the settings of the app after an update, and decide whether
+/// another update is needed when updating on demand
+fn settle(host: &mut Host) {
+    let world = host.app.world();
+    host.hidden_policy = world
+        .get_resource::<HiddenWindowPolicy>()
+        .copied()
+        .unwrap_or_default();
+    host.on_demand = world.get_resource::<UpdateMode>() == Some(&UpdateMode::OnDemand);
+    host.idle_poll = gamepad::idle_poll_interval(world);
+
+    if host.on_demand {
+        let redraw = world
+            .get_resource::<Events<RequestRedraw>>()
+            .is_some_and(|events| events.iter_current_update_events().next().is_some());
+        host.linger = if redraw {
+            LINGER_UPDATES
+        } else {
+            host.linger.saturating_sub(1)
+        };
+    }
+    host.idle = !host.wants_update();
+    host.reschedule();
+}
+
+/// The state the app thread shares with its [ThreadedHost]
+struct ThreadShared {
+    frames: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    exit: Arc<Mutex<Option<AppExit>>>,
+    errors: Arc<Mutex<Vec<String>>>,
+    degraded: Arc<AtomicBool>,
+}
+
+impl ThreadShared {
+    /// Log the error and hand it over to the GUI thread
+    fn error(&self, message: String) {
+        error!("{message}");
+        self.errors.lock().unwrap().push(message);
+    }
+
+    fn run_commands(&self, app: &mut App, commands: &Receiver<WorldCommand>) {
+        for command in commands.try_iter() {
+            if let Err(message) = panic::catch(|| command(app.world_mut())) {
+                self.error(format!(
+                    "A closure sent to the Bevy world panicked: {message}"
+                ));
+            }
+        }
+    }
+}
+
+/// The loop of the app thread
+fn run_threaded(
+    mut app: App,
+    commands: Receiver<WorldCommand>,
+    tick_interval: Duration,
+    shared: &ThreadShared,
+) {
+    while !shared.stop.load(Ordering::Acquire) {
+        let started = Instant::now();
+        shared.run_commands(&mut app, &commands);
+        if !shared.degraded.load(Ordering::Acquire) {
+            let updated = update_app(&mut app);
+            // The app is not built again on its thread, it stays paused
+            if let Some(reason) = device::lost_reason(app.world()) {
+                shared.degraded.store(true, Ordering::Release);
+                shared.error(format!("The GPU device of the Bevy app was lost: {reason}"));
+            } else {
+                match updated {
+                    Ok(()) => {
+                        shared.frames.fetch_add(1, Ordering::Release);
+                    }
+                    Err(message) => {
+                        shared.degraded.store(true, Ordering::Release);
+                        shared.error(format!("Updating the Bevy app panicked: {message}"));
+                    }
+                }
+            }
+        }
+
+        if let Some(code) = app.should_exit() {
+            *shared.exit.lock().unwrap() = Some(code);
+            break;
+        }
+        std::thread::sleep(tick_interval.saturating_sub(started.elapsed()));
+    }
+
+    // Closures sent before the shutdown still run
+    shared.run_commands(&mut app, &commands);
+    if let Err(message) = panic::catch(|| tear_down(app)) {
+        shared.error(format!("Tearing down the Bevy app panicked: {message}"));
+    }
+}
+
+/// Notify the listeners of new updates of the app thread, and quit once the
+/// app exited
+fn poll_threaded() {
+    let (updated, errors, exit) = THREADED.with(|threaded| {
+        let mut threaded = threaded.borrow_mut();
+        let Some(threaded) = threaded.as_mut() else {
+            return (false, Vec::new(), None);
+        };
+        let frames = threaded.frames.load(Ordering::Acquire);
+        let updated = frames != threaded.seen_frames;
+        threaded.seen_frames = frames;
+        let errors = std::mem::take(&mut *threaded.errors.lock().unwrap());
+        (updated, errors, threaded.exit.lock().unwrap().take())
+    });
+
+    // Already logged on the app thread
+    for message in errors {
+        panic::notify_error(&message);
+    }
+    if updated {
+        notify_listeners();
+    }
+    if let Some(exit) = exit {
+        shutdown();
+        ffi::core_application_exit(match exit {
+            AppExit::Success => 0,
+            AppExit::Error(code) => code.get().into(),
+        });
+    }
+}
+
+/// Update the independent apps, dropping those which asked to exit
+fn update_worlds() {
+    let mut panicked = Vec::new();
+    WORLDS.with(|worlds| {
+        let Ok(mut worlds) = worlds.try_borrow_mut() else {
+            return;
+        };
+        worlds.retain(|name, app| {
+            let updated = update_app(app);
+            if let Some(reason) = device::lost_reason(app.world()) {
+                panicked.push(format!(
+                    "The GPU device of the Bevy app hosted as {name:?} was lost, it was dropped: {reason}"
+                ));
+                return false;
+            }
+            if let Err(message) = updated {
+                panicked.push(format!(
+                    "Updating the Bevy app hosted as {name:?} panicked, it was dropped: {message}"
+                ));
+                return false;
+            }
+            let exit = app.should_exit();
+            if exit.is_some() {
+                info!("The Bevy app hosted as {name:?} exited");
+            }
+            exit.is_none()
+        });
+    });
+    for message in panicked {
+        panic::report_error(message);
+    }
+}
+
+fn notify_listeners() {
+    // Clone the list so callbacks can register new listeners while running
+    let listeners = LISTENERS.with(|listeners| {
+        let mut listeners = listeners.borrow_mut();
+        listeners.retain(|listener| listener.try_borrow().map_or(true, |cb| cb.is_some()));
+        listeners.clone()
+    });
+
+    for listener in listeners {
+        if let Ok(mut callback) = listener.try_borrow_mut() {
+            if let Some(callback) = callback.as_mut() {
+                catch_panic("An update listener", || callback());
+            }
+        }
+    }
+}
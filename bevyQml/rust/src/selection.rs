@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! The set of selected entities, shared by QML views and the viewport.
+//!
+//! [Selection] is the source of truth. Every selected entity carries a
+//! [Selected] component which follows the resource, and is outlined in the
+//! viewport according to [SelectionHighlight]. The `BevySelection` QML
+//! singleton mirrors the resource for item models and views.
+
+use bevy::{
+    ecs::entity::Entities, prelude::*, render::primitives::Aabb, transform::TransformSystem,
+};
+
+/// The selected entities, in the order they were selected
+#[derive(Resource, Clone, Debug, Default, Reflect)]
+#[reflect(Resource, Default)]
+pub struct Selection {
+    entities: Vec<Entity>,
+}
+
+impl Selection {
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.contains(&entity)
+    }
+
+    /// Add the entity to the selection, or make it the only selected entity
+    /// unless `extend` is set
+    pub fn select(&mut self, entity: Entity, extend: bool) {
+        if !extend {
+            self.entities.clear();
+        }
+        if !self.contains(entity) {
+            self.entities.push(entity);
+        }
+    }
+
+    pub fn deselect(&mut self, entity: Entity) {
+        self.entities.retain(|other| *other != entity);
+    }
+
+    pub fn toggle(&mut self, entity: Entity) {
+        if self.contains(entity) {
+            self.deselect(entity);
+        } else {
+            self.entities.push(entity);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entities.clear();
+    }
+
+    /// Replace the selection, dropping duplicates
+    pub fn set(&mut self, entities: impl IntoIterator<Item = Entity>) {
+        self.entities.clear();
+        for entity in entities {
+            if !self.contains(entity) {
+                self.entities.push(entity);
+            }
+        }
+    }
+}
+
+/// Update the selection for a click on an entity, or on nothing
+///
+/// With `extend` the clicked entity is toggled, otherwise it becomes the
+/// only selected entity. Clicking nothing clears the selection unless
+/// `extend` is set.
+pub fn select_clicked(world: &mut World, entity: Option<Entity>, extend: bool) {
+    let Some(mut selection) = world.get_resource_mut::<Selection>() else {
+        return;
+    };
+    match (entity, extend) {
+        (Some(entity), true) => selection.toggle(entity),
+        (Some(entity), false) => selection.select(entity, false),
+        (None, true) => {}
+        (None, false) => {
+            if !selection.entities().is_empty() {
+                selection.clear();
+            }
+        }
+    }
+}
+
+/// Marks an entity which is part of the [Selection]
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct Selected;
+
+/// How selected entities are outlined in the viewport
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct SelectionHighlight {
+    /// Draw the bounding box of every selected entity with gizmos
+    pub enabled: bool,
+    pub color: Color,
+}
+
+impl Default for SelectionHighlight {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            color: Color::srgb(1.0, 0.6, 0.1),
+        }
+    }
+}
+
+pub struct QmlSelectionPlugin;
+
+impl Plugin for QmlSelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Selection>()
+            .register_type::<Selected>()
+            .register_type::<SelectionHighlight>()
+            .init_resource::<Selection>()
+            .init_resource::<SelectionHighlight>()
+            .add_systems(
+                PostUpdate,
+                (
+                    sync_selected,
+                    draw_highlights
+                        .after(TransformSystem::TransformPropagate)
+                        .run_if(|highlight: Res<SelectionHighlight>| highlight.enabled),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Forget despawned entities and move the [Selected] markers along with the selection
+fn sync_selected(
+    mut commands: Commands,
+    mut selection: ResMut<Selection>,
+    selected: Query<Entity, With<Selected>>,
+    entities: &Entities,
+) {
+    if selection
+        .entities
+        .iter()
+        .any(|entity| !entities.contains(*entity))
+    {
+        selection
+            .entities
+            .retain(|entity| entities.contains(*entity));
+    }
+    if !selection.is_changed() {
+        return;
+    }
+
+    for entity in &selected {
+        if !selection.contains(entity) {
+            commands.entity(entity).remove::<Selected>();
+        }
+    }
+    for &entity in selection.entities() {
+        if !selected.contains(entity) {
+            if let Some(mut entity) = commands.get_entity(entity) {
+                entity.insert(Selected);
+            }
+        }
+    }
+}
+
+fn draw_highlights(
+    mut gizmos: Gizmos,
+    highlight: Res<SelectionHighlight>,
+    selected: Query<(&GlobalTransform, &Aabb), With<Selected>>,
+) {
+    for (transform, aabb) in &selected {
+        let bounds = Transform::from_translation(aabb.center.into())
+            .with_scale(Vec3::from(aabb.half_extents) * 2.0);
+        gizmos.cuboid(*transform * bounds, highlight.color);
+    }
+}
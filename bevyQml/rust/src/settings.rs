@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Resources which survive restarts by being stored in QSettings.
+//!
+//! [PersistentResource] loads a resource when it is added to the app and
+//! saves it when the app is torn down, so options edited from QML through a
+//! [crate::bridge::ResourceBridge] come back on the next start:
+//!
+//! ```ignore
+//! #[derive(Resource, Reflect, Default, Serialize, Deserialize)]
+//! #[reflect(Resource)]
+//! struct GraphicsOptions {
+//!     shadows: bool,
+//!     msaa_samples: u32,
+//! }
+//!
+//! app.add_plugins((
+//!     PersistentResource::<GraphicsOptions>::new("graphics"),
+//!     ResourceBridge::<GraphicsOptions>::default(),
+//! ));
+//! ```
+//!
+//! The values are stored as JSON under `bevy/<key>` in the settings of the
+//! organization and application names of the QCoreApplication, which should
+//! be set before the app is built.
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_settings")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/settings.h");
+
+        #[doc(hidden)]
+        #[rust_name = "settings_value"]
+        fn settingsValue(key: &QString) -> QString;
+
+        #[doc(hidden)]
+        #[rust_name = "set_settings_value"]
+        fn setSettingsValue(key: &QString, value: &QString);
+    }
+}
+
+use std::{cell::RefCell, marker::PhantomData};
+
+use bevy::{prelude::*, utils::HashMap};
+use cxx_qt_lib::QString;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Loads the resource `T` from QSettings and saves it back on shutdown
+///
+/// The resource starts out with its default value when nothing was stored
+/// yet, or when the stored value no longer fits `T`.
+pub struct PersistentResource<T> {
+    /// Where the resource is stored, below `bevy/`
+    pub key: String,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> PersistentResource<T> {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Plugin for PersistentResource<T>
+where
+    T: Resource + Serialize + DeserializeOwned + Default,
+{
+    fn build(&self, app: &mut App) {
+        let value = load::<T>(&self.key).unwrap_or_default();
+        let json = serde_json::to_string(&value).ok();
+        app.insert_resource(value)
+            .insert_resource(PersistedValue::<T> {
+                key: self.key.clone(),
+                json,
+                marker: PhantomData,
+            })
+            .add_systems(Last, track_value::<T>);
+    }
+}
+
+/// Read the resource stored under the key, [None] if there is none or it
+/// does not fit `T`
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let json = read(key);
+    if json.is_empty() {
+        return None;
+    }
+    serde_json::from_str(&json)
+        .map_err(|error| warn!("Ignoring the settings stored as {key:?}: {error}"))
+        .ok()
+}
+
+/// Store the resource under the key straight away
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    match serde_json::to_string(value) {
+        Ok(json) => write(key, &json),
+        Err(error) => warn!("Failed to store the settings {key:?}: {error}"),
+    }
+}
+
+fn read(key: &str) -> String {
+    if cfg!(test) {
+        return TEST_SETTINGS.with_borrow(|stored| stored.get(key).cloned().unwrap_or_default());
+    }
+    ffi::settings_value(&settings_key(key)).to_string()
+}
+
+fn write(key: &str, json: &str) {
+    if cfg!(test) {
+        TEST_SETTINGS.with_borrow_mut(|stored| stored.insert(key.to_owned(), json.to_owned()));
+        return;
+    }
+    ffi::set_settings_value(&settings_key(key), &QString::from(json));
+}
+
+fn settings_key(key: &str) -> QString {
+    QString::from(format!("bevy/{key}").as_str())
+}
+
+thread_local! {
+    /// Unit tests keep the settings of their thread here, rather than in
+    /// the settings of whoever runs them
+    static TEST_SETTINGS: RefCell<HashMap<String, String>> = Default::default();
+}
+
+/// The latest value of a [PersistentResource], which is written when the
+/// world is dropped
+#[derive(Resource)]
+struct PersistedValue<T> {
+    key: String,
+    json: Option<String>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Drop for PersistedValue<T> {
+    fn drop(&mut self) {
+        if let Some(json) = &self.json {
+            write(&self.key, json);
+        }
+    }
+}
+
+fn track_value<T: Resource + Serialize>(
+    value: Option<Res<T>>,
+    mut persisted: ResMut<PersistedValue<T>>,
+) {
+    let Some(value) = value.filter(|value| value.is_changed()) else {
+        return;
+    };
+    match serde_json::to_string(&*value) {
+        Ok(json) => persisted.json = Some(json),
+        Err(error) => warn!("Failed to store the settings {:?}: {error}", persisted.key),
+    }
+}
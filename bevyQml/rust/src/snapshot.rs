@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Lock-free snapshots of the world, for reading Bevy state on the GUI thread
+//! without reaching into the world.
+//!
+//! A [QmlSnapshotPlugin] extracts a value from the world at the end of every
+//! update and publishes it through a triple buffer. The GUI thread reads the
+//! latest published value with [crate::runtime::snapshot], which never
+//! waits for Bevy, also when the app runs on a thread of its own.
+//!
+//! ```ignore
+//! #[derive(Clone, Default)]
+//! struct Score(u32);
+//!
+//! app.add_plugins(QmlSnapshotPlugin::new(|world: &mut World| {
+//!     Score(world.resource::<Points>().total)
+//! }));
+//!
+//! let score = runtime::snapshot(|score: &Score| score.0);
+//! ```
+
+use std::{
+    any::{Any, TypeId},
+    cell::UnsafeCell,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+};
+
+use bevy::{prelude::*, utils::HashMap};
+
+/// Marks the back slot as holding a value the reader has not taken yet
+const FRESH: u8 = 0b100;
+const INDEX: u8 = 0b011;
+
+struct Shared<T> {
+    slots: [UnsafeCell<T>; 3],
+    /// The index of the slot owned by neither side, and [FRESH]
+    back: AtomicU8,
+}
+
+// Each slot is only ever accessed by the side owning it, and ownership
+// changes hands through the atomic swap of `back`
+unsafe impl<T: Send> Send for Shared<T> {}
+unsafe impl<T: Send> Sync for Shared<T> {}
+
+/// Create a triple buffer whose three slots start out as `initial`
+pub fn snapshot_buffer<T: Clone + Send>(initial: T) -> (SnapshotWriter<T>, SnapshotReader<T>) {
+    let shared = Arc::new(Shared {
+        slots: [
+            UnsafeCell::new(initial.clone()),
+            UnsafeCell::new(initial.clone()),
+            UnsafeCell::new(initial),
+        ],
+        back: AtomicU8::new(1),
+    });
+    (
+        SnapshotWriter {
+            shared: shared.clone(),
+            index: 0,
+        },
+        SnapshotReader { shared, index: 2 },
+    )
+}
+
+/// Publishes values to a [SnapshotReader] without waiting for it
+pub struct SnapshotWriter<T> {
+    shared: Arc<Shared<T>>,
+    index: u8,
+}
+
+impl<T> SnapshotWriter<T> {
+    pub fn write(&mut self, value: T) {
+        // SAFETY: the slot at `index` is owned by the writer
+        unsafe { *self.shared.slots[usize::from(self.index)].get() = value };
+        let previous = self.shared.back.swap(self.index | FRESH, Ordering::AcqRel);
+        self.index = previous & INDEX;
+    }
+
+    /// Whether the [SnapshotReader] was dropped, so nothing reads the values
+    pub fn is_orphaned(&self) -> bool {
+        Arc::strong_count(&self.shared) == 1
+    }
+}
+
+/// Reads the latest value of a [SnapshotWriter] without waiting for it
+pub struct SnapshotReader<T> {
+    shared: Arc<Shared<T>>,
+    index: u8,
+}
+
+impl<T> SnapshotReader<T> {
+    /// Take the latest published value, returns whether there was a new one
+    pub fn update(&mut self) -> bool {
+        if self.shared.back.load(Ordering::Relaxed) & FRESH == 0 {
+            return false;
+        }
+        let previous = self.shared.back.swap(self.index, Ordering::AcqRel);
+        self.index = previous & INDEX;
+        true
+    }
+
+    /// The value taken by the last [SnapshotReader::update]
+    pub fn read(&self) -> &T {
+        // SAFETY: the slot at `index` is owned by the reader
+        unsafe { &*self.shared.slots[usize::from(self.index)].get() }
+    }
+}
+
+/// Publishes a value extracted from the world at the end of every update
+pub struct QmlSnapshotPlugin<T> {
+    extract: fn(&mut World) -> T,
+}
+
+impl<T> QmlSnapshotPlugin<T> {
+    pub fn new(extract: fn(&mut World) -> T) -> Self {
+        Self { extract }
+    }
+}
+
+impl<T: Clone + Default + Send + Sync + 'static> Plugin for QmlSnapshotPlugin<T> {
+    fn build(&self, app: &mut App) {
+        let (writer, reader) = snapshot_buffer(T::default());
+        hand_over_reader(reader);
+        app.insert_resource(SnapshotPublisher {
+            writer,
+            extract: self.extract,
+        })
+        .add_systems(Last, publish_snapshot::<T>);
+    }
+}
+
+#[derive(Resource)]
+struct SnapshotPublisher<T> {
+    writer: SnapshotWriter<T>,
+    extract: fn(&mut World) -> T,
+}
+
+fn publish_snapshot<T: Send + Sync + 'static>(world: &mut World) {
+    world.resource_scope(|world, mut publisher: Mut<SnapshotPublisher<T>>| {
+        let value = (publisher.extract)(world);
+        publisher.writer.write(value);
+    });
+}
+
+type PendingReaders = Mutex<HashMap<TypeId, Box<dyn Any + Send>>>;
+
+/// Readers created by plugins on the Bevy thread, until the GUI thread
+/// takes them
+fn pending_readers() -> &'static PendingReaders {
+    static READERS: OnceLock<PendingReaders> = OnceLock::new();
+    READERS.get_or_init(Default::default)
+}
+
+fn hand_over_reader<T: Send + 'static>(reader: SnapshotReader<T>) {
+    pending_readers()
+        .lock()
+        .unwrap()
+        .insert(TypeId::of::<T>(), Box::new(reader));
+}
+
+/// Take the reader of the snapshots of type `T` handed over by its plugin
+pub(crate) fn take_reader<T: 'static>() -> Option<SnapshotReader<T>> {
+    let reader = pending_readers()
+        .lock()
+        .unwrap()
+        .remove(&TypeId::of::<T>())?;
+    reader.downcast().ok().map(|reader| *reader)
+}
@@ -0,0 +1,247 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Invokables doing work which takes longer than a frame, such as waiting
+//! for assets, batches of raycasts or pathfinding, without blocking the GUI
+//! thread.
+//!
+//! Such an invokable returns a task id from [next_task_id] straight away and
+//! hands an `async` block to [spawn], which runs it on Bevy's
+//! [AsyncComputeTaskPool]. The result is handed back to the QObject on the
+//! GUI thread, which emits a completion signal with the task id:
+//!
+//! ```ignore
+//! pub fn find_path(self: Pin<&mut Self>, from: u64, to: u64) -> u64 {
+//!     let id = task::next_task_id();
+//!     task::spawn(
+//!         self.qt_thread(),
+//!         async move {
+//!             let grid = task::world(|world| world.resource::<NavGrid>().clone()).await?;
+//!             Some(grid.find_path(from, to))
+//!         },
+//!         move |this, path| match path {
+//!             Some(path) => this.task_finished(id, path_to_qvariant(&path)),
+//!             None => this.task_failed(id, QString::from("The Bevy app is not running")),
+//!         },
+//!     );
+//!     id
+//! }
+//! ```
+//!
+//! The bridge objects name these signals `taskFinished(taskId, result)` and
+//! `taskFailed(taskId, error)`, which `BevyAsync.promise()` of the QML module
+//! turns into a JavaScript promise:
+//!
+//! ```qml
+//! BevyAsync.promise(BevyAssets, BevyAssets.loadAssetAsync(url, "scene"))
+//!     .then(handleId => spawner.scene = handleId)
+//!     .catch(error => console.warn(error))
+//! ```
+//!
+//! The task reaches the world of the app with [world] and [world_until],
+//! whose closures run at the start of the next update, wherever the app
+//! runs. An app updated on demand keeps updating while tasks are running, so
+//! they are not stuck waiting for an update. Only the app of
+//! [crate::plugin::BevyQmlPlugin] runs these closures.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, TaskPool},
+    window::RequestRedraw,
+};
+use cxx_qt::{CxxQtThread, Threading};
+
+use crate::runtime;
+
+/// A closure waiting for the world, returning whether it is done
+type WorldRequest = Box<dyn FnMut(&mut World) -> bool + Send>;
+
+static NEXT_TASK_ID: AtomicU64 = AtomicU64::new(1);
+static RUNNING: AtomicUsize = AtomicUsize::new(0);
+static WORLD_REQUESTS: Mutex<Vec<WorldRequest>> = Mutex::new(Vec::new());
+
+/// A new id for a task, starting at one so that zero can mean no task
+pub fn next_task_id() -> u64 {
+    NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// How many tasks started with [spawn] have not finished yet
+pub fn running_tasks() -> usize {
+    RUNNING.load(Ordering::Relaxed)
+}
+
+/// Run the future on the [AsyncComputeTaskPool], then `deliver` its output
+/// to the QObject on the GUI thread
+///
+/// Nothing is delivered if the QObject is destroyed in the meantime.
+pub fn spawn<T, R>(
+    qt_thread: CxxQtThread<T>,
+    future: impl Future<Output = R> + Send + 'static,
+    deliver: impl FnOnce(Pin<&mut T>, R) + Send + 'static,
+) where
+    T: Threading,
+    R: Send + 'static,
+{
+    let running = RunningTask::start();
+    AsyncComputeTaskPool::get_or_init(TaskPool::default)
+        .spawn(async move {
+            let output = future.await;
+            drop(running);
+            let _ = qt_thread.queue(move |qobject| deliver(qobject, output));
+        })
+        .detach();
+    runtime::request_update();
+}
+
+/// Run the closure with the world at the start of the next update
+///
+/// Resolves to [None] if the app is gone before that.
+pub fn world<R: Send + 'static>(
+    f: impl FnOnce(&mut World) -> R + Send + 'static,
+) -> WorldFuture<R> {
+    let mut f = Some(f);
+    world_until(move |world| f.take().map(|f| f(world)))
+}
+
+/// Run the closure with the world at the start of every update, until it
+/// returns something
+///
+/// Resolves to [None] if the app is gone before that.
+pub fn world_until<R: Send + 'static>(
+    mut f: impl FnMut(&mut World) -> Option<R> + Send + 'static,
+) -> WorldFuture<R> {
+    let shared = Arc::new(Mutex::new(Shared::default()));
+    let mut completer = Some(Completer(shared.clone()));
+    WORLD_REQUESTS.lock().unwrap().push(Box::new(move |world| {
+        let Some(output) = f(world) else {
+            return false;
+        };
+        if let Some(completer) = completer.take() {
+            completer.complete(output);
+        }
+        true
+    }));
+    WorldFuture(shared)
+}
+
+/// The output of a closure given to [world] or [world_until]
+#[must_use = "futures do nothing unless awaited"]
+pub struct WorldFuture<R>(Arc<Mutex<Shared<R>>>);
+
+impl<R> Future for WorldFuture<R> {
+    type Output = Option<R>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.0.lock().unwrap();
+        if let Some(output) = shared.output.take() {
+            Poll::Ready(Some(output))
+        } else if shared.closed {
+            Poll::Ready(None)
+        } else {
+            shared.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+struct Shared<R> {
+    output: Option<R>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+impl<R> Default for Shared<R> {
+    fn default() -> Self {
+        Self {
+            output: None,
+            closed: false,
+            waker: None,
+        }
+    }
+}
+
+/// Wakes the [WorldFuture] once the closure ran, or once it is dropped
+/// without running
+struct Completer<R>(Arc<Mutex<Shared<R>>>);
+
+impl<R> Completer<R> {
+    fn complete(self, output: R) {
+        self.0.lock().unwrap().output = Some(output);
+    }
+}
+
+impl<R> Drop for Completer<R> {
+    fn drop(&mut self) {
+        let mut shared = self.0.lock().unwrap();
+        shared.closed = true;
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Counts a task as running until it is dropped
+struct RunningTask;
+
+impl RunningTask {
+    fn start() -> Self {
+        RUNNING.fetch_add(1, Ordering::Relaxed);
+        Self
+    }
+}
+
+impl Drop for RunningTask {
+    fn drop(&mut self) {
+        RUNNING.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+pub struct QmlTaskPlugin;
+
+impl Plugin for QmlTaskPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldRequests>()
+            .add_systems(First, run_world_requests);
+    }
+}
+
+/// Drops the closures waiting for the world along with the world, which lets
+/// the tasks waiting for them go on
+#[derive(Resource, Default)]
+struct WorldRequests;
+
+impl Drop for WorldRequests {
+    fn drop(&mut self) {
+        let requests = std::mem::take(&mut *WORLD_REQUESTS.lock().unwrap());
+        drop(requests);
+    }
+}
+
+fn run_world_requests(world: &mut World) {
+    // Taken out, so that the closures can ask for the world again and
+    // those asking run again in the next update rather than this one
+    let requests = std::mem::take(&mut *WORLD_REQUESTS.lock().unwrap());
+    let mut waiting = Vec::new();
+    for mut request in requests {
+        let done = crate::panic::catch_panic("A closure of a task", || request(world));
+        if done == Some(false) {
+            waiting.push(request);
+        }
+    }
+    WORLD_REQUESTS.lock().unwrap().extend(waiting);
+
+    if running_tasks() > 0 {
+        if let Some(mut redraw) = world.get_resource_mut::<Events<RequestRedraw>>() {
+            redraw.send(RequestRedraw);
+        }
+    }
+}
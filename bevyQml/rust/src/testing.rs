@@ -0,0 +1,346 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Snapshot tests of QML and Bevy scenes, built with the `testing` feature.
+//!
+//! A test renders a scene for a number of frames, captures it with the
+//! `captureFrame` of a `BevyQuickItem` and compares the capture with a golden
+//! image, see [crate::cxxqt_bevy_golden_image] for the QML side and
+//! `tests/snapshot` for a test run by CTest. The app of a test should be
+//! built with [bevy_qml_test_plugins] and [QmlTestingPlugin], so that the
+//! frames do not depend on how fast the machine running it is. The frames
+//! rendered before the capture give assets time to load.
+//!
+//! Tests run without a display on the `offscreen` Qt platform. Bevy renders
+//! into textures either way, and needs a GPU adapter, which on CI machines
+//! is usually a software one such as Mesa's lavapipe.
+//!
+//! Golden images are written instead of compared while the
+//! `BEVYQML_UPDATE_GOLDENS` environment variable is set. Otherwise a missing
+//! golden image fails the comparison, so a test never passes without having
+//! compared anything. When a comparison fails, the capture and an image marking the differing pixels in red are
+//! saved next to the golden image, as `<name>.actual.png` and
+//! `<name>.diff.png`.
+
+use std::{path::Path, time::Duration};
+
+use bevy::{
+    app::PluginGroupBuilder,
+    prelude::*,
+    render::{settings::WgpuSettings, RenderPlugin},
+    time::TimeUpdateStrategy,
+};
+use cxx_qt_lib::QImage;
+
+use crate::{image, plugin::bevy_qml_default_plugins};
+
+/// The environment variable which makes [compare_with_golden] write golden
+/// images instead of comparing with them
+pub const UPDATE_GOLDENS_VAR: &str = "BEVYQML_UPDATE_GOLDENS";
+
+/// The plugins of [bevy_qml_default_plugins], with pipelines compiled before
+/// the first frame that uses them, so no frame is missing parts of the scene
+pub fn bevy_qml_test_plugins() -> PluginGroupBuilder {
+    bevy_qml_default_plugins().set(RenderPlugin {
+        render_creation: WgpuSettings::default().into(),
+        synchronous_pipeline_compilation: true,
+    })
+}
+
+/// Stops the clock of the app, so that animations stay where they start and
+/// a frame looks the same however many updates ran before it
+pub struct QmlTestingPlugin;
+
+impl Plugin for QmlTestingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+    }
+}
+
+/// How far a capture may be from its golden image and still match
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    /// How far any color channel of a pixel may be off, out of 255
+    pub channel: u8,
+    /// The fraction of pixels, between 0 and 1, which may be off by more
+    /// than [Tolerance::channel]
+    pub pixels: f32,
+}
+
+impl Default for Tolerance {
+    /// Small enough to catch changes to the scene, large enough for the
+    /// rounding of different GPUs
+    fn default() -> Self {
+        Self {
+            channel: 2,
+            pixels: 0.001,
+        }
+    }
+}
+
+/// How two images differ
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageDifference {
+    /// How many pixels are off by more than the tolerance of a channel
+    pub differing: usize,
+    /// How many pixels the images have
+    pub total: usize,
+    /// The largest difference of any channel
+    pub largest: u8,
+}
+
+impl ImageDifference {
+    /// The fraction of differing pixels
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.differing as f32 / self.total as f32
+        }
+    }
+
+    pub fn is_within(&self, tolerance: Tolerance) -> bool {
+        self.fraction() <= tolerance.pixels
+    }
+}
+
+/// Compare tightly packed RGBA8 pixels of images of the same size
+///
+/// Also returns an image marking the differing pixels in red on a dimmed
+/// copy of `actual`.
+pub fn compare_pixels(actual: &[u8], expected: &[u8], channel: u8) -> (ImageDifference, Vec<u8>) {
+    let mut difference = ImageDifference {
+        differing: 0,
+        total: actual.len().min(expected.len()) / 4,
+        largest: 0,
+    };
+    let mut diff = Vec::with_capacity(actual.len());
+    for (actual, expected) in actual.chunks_exact(4).zip(expected.chunks_exact(4)) {
+        let largest = actual
+            .iter()
+            .zip(expected)
+            .map(|(actual, expected)| actual.abs_diff(*expected))
+            .max()
+            .unwrap_or(0);
+        difference.largest = difference.largest.max(largest);
+        if largest > channel {
+            difference.differing += 1;
+            diff.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let gray = (actual[0] as u16 + actual[1] as u16 + actual[2] as u16) / 3;
+            let dimmed = (gray / 4) as u8;
+            diff.extend_from_slice(&[dimmed, dimmed, dimmed, 255]);
+        }
+    }
+    (difference, diff)
+}
+
+/// What [compare_with_golden] found
+#[derive(Clone, Debug, PartialEq)]
+pub enum GoldenOutcome {
+    /// The capture matches the golden image
+    Matches(ImageDifference),
+    /// The golden image was written from the capture
+    Updated,
+    /// There is no golden image to compare with
+    Missing,
+    /// The capture and the golden image differ in size
+    SizeDiffers {
+        actual: (u32, u32),
+        expected: (u32, u32),
+    },
+    /// The capture differs from the golden image by more than the tolerance
+    Differs(ImageDifference),
+    /// The capture is a null image, or a file could not be written
+    Failed(String),
+}
+
+impl GoldenOutcome {
+    /// Whether a test comparing with the golden image passes
+    pub fn passed(&self) -> bool {
+        matches!(self, Self::Matches(_) | Self::Updated)
+    }
+
+    /// A description for the log of a failing test
+    pub fn message(&self, golden: &str) -> String {
+        match self {
+            Self::Matches(_) => format!("{golden} matches"),
+            Self::Updated => format!("{golden} was updated"),
+            Self::Missing => format!(
+                "{golden} does not exist, run the test with {UPDATE_GOLDENS_VAR}=1 to write it"
+            ),
+            Self::SizeDiffers { actual, expected } => format!(
+                "the capture is {}x{} but {golden} is {}x{}",
+                actual.0, actual.1, expected.0, expected.1
+            ),
+            Self::Differs(difference) => format!(
+                "{} of {} pixels differ from {golden}, by up to {}",
+                difference.differing, difference.total, difference.largest
+            ),
+            Self::Failed(message) => message.clone(),
+        }
+    }
+}
+
+/// Compare a capture with the golden image at `golden`
+///
+/// Writes the golden image instead while [UPDATE_GOLDENS_VAR] is set. A
+/// capture which does not match is saved next to the golden image, along
+/// with the image from [compare_pixels].
+pub fn compare_with_golden(actual: &QImage, golden: &str, tolerance: Tolerance) -> GoldenOutcome {
+    let Some((width, height, actual_pixels)) = image::qimage_to_rgba8(actual) else {
+        return GoldenOutcome::Failed(String::from("the capture is a null image"));
+    };
+
+    if std::env::var_os(UPDATE_GOLDENS_VAR).is_some() {
+        if let Some(parent) = Path::new(golden).parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        return if image::save_qimage(actual, golden) {
+            GoldenOutcome::Updated
+        } else {
+            GoldenOutcome::Failed(format!("failed to write {golden}"))
+        };
+    }
+
+    let expected = image::load_qimage(golden).and_then(|image| image::qimage_to_rgba8(&image));
+    let Some((expected_width, expected_height, expected_pixels)) = expected else {
+        save_beside(golden, "actual", actual);
+        return GoldenOutcome::Missing;
+    };
+    if (width, height) != (expected_width, expected_height) {
+        save_beside(golden, "actual", actual);
+        return GoldenOutcome::SizeDiffers {
+            actual: (width, height),
+            expected: (expected_width, expected_height),
+        };
+    }
+
+    let (difference, diff) = compare_pixels(&actual_pixels, &expected_pixels, tolerance.channel);
+    if difference.is_within(tolerance) {
+        return GoldenOutcome::Matches(difference);
+    }
+    save_beside(golden, "actual", actual);
+    save_beside(
+        golden,
+        "diff",
+        &image::qimage_from_rgba8(width, height, &diff),
+    );
+    GoldenOutcome::Differs(difference)
+}
+
+/// Save an image next to the golden image, as `<name>.<kind>.png`
+fn save_beside(golden: &str, kind: &str, image: &QImage) {
+    let path = Path::new(golden).with_extension(format!("{kind}.png"));
+    let path = path.to_string_lossy();
+    if !image::save_qimage(image, &path) {
+        warn!("Failed to save {path}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixels(colors: &[[u8; 4]]) -> Vec<u8> {
+        colors.concat()
+    }
+
+    #[test]
+    fn identical_pixels_match() {
+        let image = pixels(&[[10, 20, 30, 255], [200, 100, 0, 255]]);
+        let (difference, diff) = compare_pixels(&image, &image, 0);
+        assert_eq!(
+            difference,
+            ImageDifference {
+                differing: 0,
+                total: 2,
+                largest: 0,
+            }
+        );
+        // A dimmed gray copy of the capture
+        assert_eq!(diff, pixels(&[[5, 5, 5, 255], [25, 25, 25, 255]]));
+    }
+
+    #[test]
+    fn marks_pixels_beyond_the_channel_tolerance() {
+        let actual = pixels(&[[10, 20, 30, 255], [100, 100, 100, 255], [0, 0, 0, 0]]);
+        let expected = pixels(&[[12, 20, 30, 255], [100, 100, 103, 255], [0, 0, 0, 9]]);
+        let (difference, diff) = compare_pixels(&actual, &expected, 2);
+        assert_eq!(
+            difference,
+            ImageDifference {
+                differing: 2,
+                total: 3,
+                largest: 9,
+            }
+        );
+        assert_eq!(
+            diff,
+            pixels(&[[5, 5, 5, 255], [255, 0, 0, 255], [255, 0, 0, 255]])
+        );
+    }
+
+    #[test]
+    fn compares_the_common_pixels() {
+        let actual = pixels(&[[0, 0, 0, 255], [0, 0, 0, 255]]);
+        let expected = pixels(&[[0, 0, 0, 255]]);
+        let (difference, diff) = compare_pixels(&actual, &expected, 0);
+        assert_eq!(difference.total, 1);
+        assert_eq!(diff.len(), 4);
+        let (difference, _) = compare_pixels(&[], &[], 0);
+        assert_eq!(difference.total, 0);
+    }
+
+    #[test]
+    fn fraction_of_differing_pixels() {
+        let difference = ImageDifference {
+            differing: 1,
+            total: 4,
+            largest: 255,
+        };
+        assert_eq!(difference.fraction(), 0.25);
+        let empty = ImageDifference {
+            differing: 0,
+            total: 0,
+            largest: 0,
+        };
+        assert_eq!(empty.fraction(), 0.0);
+    }
+
+    #[test]
+    fn within_the_pixel_tolerance() {
+        let difference = ImageDifference {
+            differing: 1,
+            total: 1000,
+            largest: 255,
+        };
+        assert!(difference.is_within(Tolerance::default()));
+        let difference = ImageDifference {
+            differing: 2,
+            ..difference
+        };
+        assert!(!difference.is_within(Tolerance::default()));
+        assert!(difference.is_within(Tolerance {
+            channel: 0,
+            pixels: 1.0,
+        }));
+        assert!(!difference.is_within(Tolerance {
+            channel: 255,
+            pixels: 0.0,
+        }));
+    }
+
+    #[test]
+    fn only_matches_and_updates_pass() {
+        let difference = ImageDifference {
+            differing: 0,
+            total: 1,
+            largest: 0,
+        };
+        assert!(GoldenOutcome::Matches(difference.clone()).passed());
+        assert!(GoldenOutcome::Updated.passed());
+        assert!(!GoldenOutcome::Missing.passed());
+        assert!(!GoldenOutcome::Differs(difference).passed());
+        assert!(!GoldenOutcome::Failed(String::new()).passed());
+    }
+}
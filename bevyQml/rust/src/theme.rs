@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Follows the theme of the Qt application in Bevy, so embedded views match
+//! the surrounding desktop.
+//!
+//! [QmlThemePlugin] keeps the [QtTheme] resource of the main app in line with
+//! the palette and the color scheme of the QGuiApplication, which are checked
+//! after every update, and keeps its default in headless apps. As far as [ThemeSync] asks for it, the [ClearColor]
+//! then follows the window color of the palette, and the [AmbientLight] the
+//! preset of the color scheme:
+//!
+//! ```ignore
+//! app.insert_resource(ThemeSync {
+//!     clear_color: false,
+//!     ..default()
+//! });
+//! ```
+//!
+//! Systems styling the scene further read [QtTheme] and run when it changed.
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_theme")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qcolor.h");
+        /// An alias to the QColor type
+        type QColor = cxx_qt_lib::QColor;
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/theme.h");
+
+        #[doc(hidden)]
+        #[rust_name = "palette_color"]
+        fn paletteColor(role: u8) -> QColor;
+
+        #[doc(hidden)]
+        #[rust_name = "color_scheme"]
+        fn colorScheme() -> u8;
+    }
+}
+
+use bevy::prelude::*;
+
+use crate::{
+    convert::FromQt,
+    runtime::{self, UpdateListener},
+};
+
+/// The values of QPalette::ColorRole
+mod role {
+    pub const WINDOW_TEXT: u8 = 0;
+    pub const BUTTON: u8 = 1;
+    pub const TEXT: u8 = 6;
+    pub const BUTTON_TEXT: u8 = 8;
+    pub const BASE: u8 = 9;
+    pub const WINDOW: u8 = 10;
+    pub const HIGHLIGHT: u8 = 12;
+    pub const HIGHLIGHTED_TEXT: u8 = 13;
+}
+
+/// Whether the application uses light text on dark backgrounds or the other
+/// way around
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum ColorScheme {
+    #[default]
+    Light,
+    Dark,
+}
+
+/// The palette and the color scheme of the Qt application, as of the last
+/// update
+///
+/// The colors are those of the active color group.
+#[derive(Resource, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Resource, Default)]
+pub struct QtTheme {
+    pub scheme: ColorScheme,
+    pub window: Color,
+    pub window_text: Color,
+    /// The background of text entry widgets and item views
+    pub base: Color,
+    pub text: Color,
+    pub button: Color,
+    pub button_text: Color,
+    pub highlight: Color,
+    pub highlighted_text: Color,
+}
+
+/// The light Fusion palette, until the palette of the application is known
+impl Default for QtTheme {
+    fn default() -> Self {
+        Self {
+            scheme: ColorScheme::Light,
+            window: Color::srgb_u8(0xef, 0xef, 0xef),
+            window_text: Color::BLACK,
+            base: Color::WHITE,
+            text: Color::BLACK,
+            button: Color::srgb_u8(0xef, 0xef, 0xef),
+            button_text: Color::BLACK,
+            highlight: Color::srgb_u8(0x30, 0x8c, 0xc6),
+            highlighted_text: Color::WHITE,
+        }
+    }
+}
+
+impl QtTheme {
+    /// Read the palette and the color scheme of the application
+    ///
+    /// This must be called from the GUI thread. When Qt does not know the
+    /// color scheme, the palette is dark if its text is lighter than its
+    /// windows.
+    pub fn from_application() -> Self {
+        let color = |role| Color::from_qt(&ffi::palette_color(role));
+        let window = color(role::WINDOW);
+        let window_text = color(role::WINDOW_TEXT);
+        let scheme = match ffi::color_scheme() {
+            1 => ColorScheme::Light,
+            2 => ColorScheme::Dark,
+            _ if window_text.luminance() > window.luminance() => ColorScheme::Dark,
+            _ => ColorScheme::Light,
+        };
+        Self {
+            scheme,
+            window,
+            window_text,
+            base: color(role::BASE),
+            text: color(role::TEXT),
+            button: color(role::BUTTON),
+            button_text: color(role::BUTTON_TEXT),
+            highlight: color(role::HIGHLIGHT),
+            highlighted_text: color(role::HIGHLIGHTED_TEXT),
+        }
+    }
+
+    pub fn is_dark(&self) -> bool {
+        self.scheme == ColorScheme::Dark
+    }
+}
+
+/// Which resources follow the [QtTheme]
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource, Default)]
+pub struct ThemeSync {
+    /// Clear to the window color of the palette
+    pub clear_color: bool,
+    /// Use the preset of the color scheme for the ambient light
+    pub ambient_light: bool,
+    pub light: AmbientLight,
+    pub dark: AmbientLight,
+}
+
+impl Default for ThemeSync {
+    fn default() -> Self {
+        Self {
+            clear_color: true,
+            ambient_light: true,
+            light: AmbientLight::default(),
+            dark: AmbientLight {
+                color: Color::srgb(0.8, 0.85, 1.0),
+                brightness: AmbientLight::default().brightness * 0.5,
+            },
+        }
+    }
+}
+
+pub struct QmlThemePlugin;
+
+impl Plugin for QmlThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ColorScheme>()
+            .register_type::<QtTheme>()
+            .register_type::<ThemeSync>()
+            .init_resource::<QtTheme>()
+            .init_resource::<ThemeSync>()
+            .insert_non_send_resource(QmlThemeListener(runtime::on_update(follow_theme)))
+            .add_systems(PreUpdate, apply_theme);
+    }
+}
+
+/// Reads the theme after every update for as long as the app exists
+struct QmlThemeListener(#[allow(dead_code)] UpdateListener);
+
+/// Store the theme of the application in the world when it changed
+fn follow_theme() {
+    // A QCoreApplication has no palette
+    if !runtime::has_gui() {
+        return;
+    }
+    let theme = QtTheme::from_application();
+    let changed = runtime::with_world(|world| {
+        let mut current = world.get_resource_mut::<QtTheme>()?;
+        let changed = *current != theme;
+        if changed {
+            *current = theme;
+        }
+        Some(changed)
+    })
+    .flatten();
+    // Shown right away, also when updating on demand
+    if changed == Some(true) {
+        runtime::request_update();
+    }
+}
+
+fn apply_theme(
+    theme: Res<QtTheme>,
+    sync: Res<ThemeSync>,
+    clear_color: Option<ResMut<ClearColor>>,
+    ambient_light: Option<ResMut<AmbientLight>>,
+) {
+    if !theme.is_changed() && !sync.is_changed() {
+        return;
+    }
+    if let Some(mut clear_color) = clear_color.filter(|_| sync.clear_color) {
+        clear_color.0 = theme.window;
+    }
+    if let Some(mut ambient_light) = ambient_light.filter(|_| sync.ambient_light) {
+        *ambient_light = match theme.scheme {
+            ColorScheme::Light => sync.light.clone(),
+            ColorScheme::Dark => sync.dark.clone(),
+        };
+    }
+}
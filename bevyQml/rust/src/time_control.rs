@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Pausing, stepping and slowing down the world from QML, see
+//! [crate::cxxqt_bevy_time] for the QML side.
+//!
+//! This drives [Time<Virtual>], so systems using [Time] in `Update` or
+//! [Time<Fixed>] in `FixedUpdate` follow it, while [Time<Real>] keeps going.
+
+use bevy::{prelude::*, time::TimeSystem};
+
+/// Steps asked for while virtual time is paused
+#[derive(Resource, Default)]
+pub struct QmlTimeSteps {
+    pending: u32,
+}
+
+pub struct QmlTimeControlPlugin;
+
+impl Plugin for QmlTimeControlPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QmlTimeSteps>()
+            .add_systems(First, apply_steps.after(TimeSystem));
+    }
+}
+
+/// Advance paused virtual time by one timestep of [Time<Fixed>] in the next
+/// update, so a single `FixedUpdate` runs as well
+///
+/// Steps asked for while time is running are dropped.
+pub fn step(world: &mut World) {
+    if world.resource::<Time<Virtual>>().is_paused() {
+        world.resource_mut::<QmlTimeSteps>().pending += 1;
+    }
+}
+
+fn apply_steps(
+    mut steps: ResMut<QmlTimeSteps>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut time: ResMut<Time>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    if steps.pending == 0 {
+        return;
+    }
+    steps.pending -= 1;
+    if !virtual_time.is_paused() {
+        steps.pending = 0;
+        return;
+    }
+    virtual_time.advance_by(fixed_time.timestep());
+    // Time was already copied from virtual time for this update
+    *time = virtual_time.as_generic();
+}
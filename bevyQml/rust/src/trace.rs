@@ -0,0 +1,263 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Captures tracing spans into Chrome trace files, which the CTF Visualizer
+//! of Qt Creator, Perfetto and `chrome://tracing` open.
+//!
+//! [TraceLayer] is part of [crate::log::qt_log_layer], so it is set up with
+//! the [LogPlugin] of [crate::plugin::bevy_qml_default_plugins]. Nothing is
+//! recorded outside of a capture, a span costs an atomic load then. Between
+//! [start_capture] and [stop_capture] every span which passes the filter of
+//! the plugin is recorded as it is exited, on the thread it ran on, and
+//! [instant] marks points in time.
+//!
+//! The runtime traces the updates of the apps and marks the frames of the
+//! scene graph, and the `BevyQuickItem`s trace syncing with the world and
+//! updating their paint nodes, which together cover the Qt side of a frame.
+//! Bevy adds spans for its systems and render passes when built with its
+//! `trace` feature. QML starts and stops captures with the `BevyTrace`
+//! singleton.
+//!
+//! [LogPlugin]: bevy::log::LogPlugin
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    log::tracing_subscriber::{layer::Context, registry::LookupSpan, Layer},
+    utils::tracing::{span, Subscriber},
+};
+use serde_json::json;
+
+/// How many events a capture keeps, later ones are dropped
+pub const CAPTURE_CAPACITY: usize = 1_000_000;
+
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+
+fn capture() -> &'static Mutex<Option<Capture>> {
+    static CAPTURE: OnceLock<Mutex<Option<Capture>>> = OnceLock::new();
+    CAPTURE.get_or_init(Default::default)
+}
+
+/// The events recorded since [start_capture]
+#[derive(Debug)]
+pub struct Capture {
+    started: Instant,
+    events: Vec<TraceEvent>,
+    /// The names of the threads the events ran on, by their trace ids
+    threads: HashMap<u64, String>,
+    /// How many events did not fit into [CAPTURE_CAPACITY]
+    dropped: usize,
+}
+
+#[derive(Clone, Debug)]
+struct TraceEvent {
+    name: &'static str,
+    category: &'static str,
+    /// Since the capture started
+    start: Duration,
+    /// [None] for instant events
+    duration: Option<Duration>,
+    thread: u64,
+}
+
+impl Capture {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            events: Vec::new(),
+            threads: HashMap::new(),
+            dropped: 0,
+        }
+    }
+
+    fn record(&mut self, event: TraceEvent) {
+        if self.events.len() == CAPTURE_CAPACITY {
+            self.dropped += 1;
+            return;
+        }
+        self.threads.entry(event.thread).or_insert_with(|| {
+            let current = thread::current();
+            current
+                .name()
+                .map_or_else(|| format!("{:?}", current.id()), str::to_string)
+        });
+        self.events.push(event);
+    }
+
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    /// How many events were dropped as the capture was full
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Write the events in the JSON object format of Chrome traces
+    pub fn write(&self, writer: impl Write) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        let pid = std::process::id();
+        writer.write_all(b"{\"displayTimeUnit\":\"ms\",\"traceEvents\":[")?;
+        let mut first = true;
+        let mut separate = |writer: &mut BufWriter<_>| {
+            let separator: &[u8] = if first { b"\n" } else { b",\n" };
+            first = false;
+            writer.write_all(separator)
+        };
+
+        for (thread, name) in &self.threads {
+            separate(&mut writer)?;
+            let event = json!({
+                "name": "thread_name",
+                "ph": "M",
+                "pid": pid,
+                "tid": thread,
+                "args": { "name": name },
+            });
+            serde_json::to_writer(&mut writer, &event)?;
+        }
+        for event in &self.events {
+            separate(&mut writer)?;
+            let timestamp = event.start.as_secs_f64() * 1e6;
+            let event = match event.duration {
+                Some(duration) => json!({
+                    "name": event.name,
+                    "cat": event.category,
+                    "ph": "X",
+                    "ts": timestamp,
+                    "dur": duration.as_secs_f64() * 1e6,
+                    "pid": pid,
+                    "tid": event.thread,
+                }),
+                None => json!({
+                    "name": event.name,
+                    "cat": event.category,
+                    "ph": "i",
+                    "s": "p",
+                    "ts": timestamp,
+                    "pid": pid,
+                    "tid": event.thread,
+                }),
+            };
+            serde_json::to_writer(&mut writer, &event)?;
+        }
+        writer.write_all(b"\n]}\n")?;
+        writer.flush()
+    }
+
+    /// Save the capture as a Chrome trace file
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        self.write(File::create(path)?)
+    }
+}
+
+/// Start recording spans, dropping those of a capture which was not stopped
+pub fn start_capture() {
+    *capture().lock().unwrap() = Some(Capture::new());
+    CAPTURING.store(true, Ordering::Release);
+}
+
+/// Stop recording spans, returns what was recorded if a capture was running
+pub fn stop_capture() -> Option<Capture> {
+    CAPTURING.store(false, Ordering::Release);
+    capture().lock().unwrap().take()
+}
+
+pub fn is_capturing() -> bool {
+    CAPTURING.load(Ordering::Acquire)
+}
+
+/// How long the running capture started ago
+pub fn capture_elapsed() -> Option<Duration> {
+    let capture = capture().lock().unwrap();
+    capture.as_ref().map(|capture| capture.started.elapsed())
+}
+
+/// How many events the running capture recorded so far
+pub fn captured_event_count() -> usize {
+    let capture = capture().lock().unwrap();
+    capture.as_ref().map_or(0, Capture::event_count)
+}
+
+/// Mark the current point in time in the running capture, e.g. the start
+/// of a frame
+pub fn instant(name: &'static str) {
+    if !is_capturing() {
+        return;
+    }
+    let now = Instant::now();
+    record(now, |capture| TraceEvent {
+        name,
+        category: "bevy_qml",
+        start: now.saturating_duration_since(capture.started),
+        duration: None,
+        thread: thread_id(),
+    });
+}
+
+fn record(at: Instant, event: impl FnOnce(&Capture) -> TraceEvent) {
+    let mut capture = capture().lock().unwrap();
+    // Anything which started before the capture is left out
+    let Some(capture) = capture.as_mut().filter(|capture| at >= capture.started) else {
+        return;
+    };
+    let event = event(capture);
+    capture.record(event);
+}
+
+/// A small id for the current thread, as Chrome traces want numbers
+fn thread_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    thread_local! {
+        static ID: u64 = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+    ID.with(|id| *id)
+}
+
+/// When the span was entered last, kept in its extensions
+struct Entered(Instant);
+
+/// The tracing layer recording spans into the running capture
+pub struct TraceLayer;
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for TraceLayer {
+    fn on_enter(&self, id: &span::Id, context: Context<'_, S>) {
+        if !is_capturing() {
+            return;
+        }
+        if let Some(span) = context.span(id) {
+            span.extensions_mut().replace(Entered(Instant::now()));
+        }
+    }
+
+    fn on_exit(&self, id: &span::Id, context: Context<'_, S>) {
+        let Some(span) = context.span(id) else {
+            return;
+        };
+        // Spans entered before the capture stopped are still taken out
+        let Some(Entered(entered)) = span.extensions_mut().remove::<Entered>() else {
+            return;
+        };
+        if !is_capturing() {
+            return;
+        }
+        let metadata = span.metadata();
+        record(entered, |capture| TraceEvent {
+            name: metadata.name(),
+            category: metadata.target(),
+            start: entered - capture.started,
+            duration: Some(entered.elapsed()),
+            thread: thread_id(),
+        });
+    }
+}
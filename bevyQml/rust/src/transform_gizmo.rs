@@ -0,0 +1,520 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Handles for moving, rotating and scaling the selected entity with the
+//! mouse, see [crate::cxxqt_bevy_transform_gizmo] for the QML side.
+//!
+//! While [TransformGizmo::enabled] is set, handles are drawn with gizmos
+//! around the entity selected last. A `BevyQuickItem` hands its left mouse
+//! button to the handles before anything else, so grabbing one neither
+//! changes the selection nor reaches camera controllers, and dragging it
+//! changes the [Transform] of the entity right away. Letting go sends a
+//! [TransformEdited] event.
+//!
+//! Handles keep the same size on screen, measured by the camera that was
+//! last used to grab one, or the active 3D camera with the highest order.
+
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::{picking, selection::Selection};
+
+/// What the handles change
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum TransformGizmoMode {
+    #[default]
+    Translate,
+    Rotate,
+    Scale,
+}
+
+/// Which axes the handles follow when translating or rotating
+///
+/// Scaling always follows the axes of the entity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum TransformGizmoSpace {
+    #[default]
+    World,
+    Local,
+}
+
+/// How the transform gizmo behaves
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource, Default)]
+pub struct TransformGizmo {
+    pub enabled: bool,
+    pub mode: TransformGizmoMode,
+    pub space: TransformGizmoSpace,
+    /// Round the changes to the steps below
+    pub snapping: bool,
+    /// The step of translations, in world units
+    pub translation_step: f32,
+    /// The step of rotations, in degrees
+    pub rotation_step: f32,
+    /// The step of scale factors
+    pub scale_step: f32,
+    /// The length of the handles, relative to their distance from the camera
+    pub size: f32,
+}
+
+impl Default for TransformGizmo {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: TransformGizmoMode::default(),
+            space: TransformGizmoSpace::default(),
+            snapping: false,
+            translation_step: 0.5,
+            rotation_step: 15.0,
+            scale_step: 0.1,
+            size: 0.15,
+        }
+    }
+}
+
+/// A handle of the transform gizmo was let go after changing the transform
+/// of an entity
+#[derive(Event, Clone, Copy, Debug)]
+pub struct TransformEdited {
+    pub entity: Entity,
+    /// The transform before the handle was grabbed
+    pub old_transform: Transform,
+    pub new_transform: Transform,
+}
+
+/// The handle being hovered or dragged
+#[derive(Resource, Default)]
+pub struct TransformGizmoState {
+    hovered: Option<Axis>,
+    drag: Option<Drag>,
+    camera: Option<Entity>,
+    /// Edits waiting for the `BevyTransformGizmo` singleton
+    pub(crate) edits: Vec<TransformEdited>,
+}
+
+impl TransformGizmoState {
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Axis::X => Color::srgb(0.9, 0.2, 0.2),
+            Axis::Y => Color::srgb(0.3, 0.8, 0.2),
+            Axis::Z => Color::srgb(0.2, 0.4, 0.9),
+        }
+    }
+}
+
+/// The color of the handle under the cursor or being dragged
+const ACTIVE_COLOR: Color = Color::srgb(1.0, 0.85, 0.1);
+
+/// How close the cursor has to be to a handle, relative to its length
+const GRAB_TOLERANCE: f32 = 0.08;
+
+/// How many edits are kept for QML, in case nothing takes them
+const RETAINED_EDITS: usize = 64;
+
+/// Where the handles are drawn
+struct Frame {
+    origin: Vec3,
+    axes: [Vec3; 3],
+    length: f32,
+}
+
+impl Frame {
+    fn new(
+        settings: &TransformGizmo,
+        mode: TransformGizmoMode,
+        target: &GlobalTransform,
+        camera: &GlobalTransform,
+    ) -> Self {
+        let (_, rotation, origin) = target.to_scale_rotation_translation();
+        let rotation =
+            if settings.space == TransformGizmoSpace::Local || mode == TransformGizmoMode::Scale {
+                rotation
+            } else {
+                Quat::IDENTITY
+            };
+        Self {
+            origin,
+            axes: [rotation * Vec3::X, rotation * Vec3::Y, rotation * Vec3::Z],
+            length: settings.size * camera.translation().distance(origin).max(0.01),
+        }
+    }
+
+    fn axis(&self, axis: Axis) -> Vec3 {
+        self.axes[axis.index()]
+    }
+
+    /// The handle under a ray, if any
+    fn hit(&self, mode: TransformGizmoMode, ray: Ray3d) -> Option<Axis> {
+        let tolerance = self.length * GRAB_TOLERANCE;
+        Axis::ALL
+            .into_iter()
+            .filter_map(|axis| {
+                let direction = self.axis(axis);
+                let miss = if mode == TransformGizmoMode::Rotate {
+                    let point = plane_hit(ray, self.origin, direction)?;
+                    (point.distance(self.origin) - self.length).abs()
+                } else {
+                    let (along_ray, along_axis) = closest_params(ray, self.origin, direction)?;
+                    if along_ray < 0.0 || !(0.0..=self.length * 1.1).contains(&along_axis) {
+                        return None;
+                    }
+                    ray.get_point(along_ray)
+                        .distance(self.origin + direction * along_axis)
+                };
+                (miss <= tolerance).then_some((axis, miss))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(axis, _)| axis)
+    }
+}
+
+/// A handle being dragged
+#[derive(Clone, Copy, Debug)]
+struct Drag {
+    entity: Entity,
+    camera: Entity,
+    mode: TransformGizmoMode,
+    axis: Axis,
+    origin: Vec3,
+    direction: Vec3,
+    /// Where the handle was grabbed, along the axis when translating or
+    /// scaling and on the plane of the axis when rotating
+    grabbed: Vec3,
+    parent: GlobalTransform,
+    start_global: Transform,
+    start_local: Transform,
+    current: Transform,
+}
+
+pub struct QmlTransformGizmoPlugin;
+
+impl Plugin for QmlTransformGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<TransformGizmo>()
+            .init_resource::<TransformGizmo>()
+            .init_resource::<TransformGizmoState>()
+            .add_event::<TransformEdited>()
+            .add_systems(
+                PostUpdate,
+                draw_handles
+                    .after(TransformSystem::TransformPropagate)
+                    .run_if(|settings: Res<TransformGizmo>| settings.enabled),
+            );
+    }
+}
+
+/// Grab the handle under a position of a render target image, in its
+/// physical pixels
+///
+/// Returns whether a handle was grabbed, in which case the mouse belongs to
+/// the gizmo until [release].
+pub fn press(world: &mut World, image: &Handle<Image>, position: Vec2) -> bool {
+    let Some((camera, ray)) = image_ray(world, image, position) else {
+        return false;
+    };
+    let Some(settings) = world.get_resource::<TransformGizmo>().cloned() else {
+        return false;
+    };
+    let Some(entity) = target(world, &settings) else {
+        return false;
+    };
+    let (Some(target_global), Some(camera_global), Some(&start_local)) = (
+        world.get::<GlobalTransform>(entity),
+        world.get::<GlobalTransform>(camera),
+        world.get::<Transform>(entity),
+    ) else {
+        return false;
+    };
+    let frame = Frame::new(&settings, settings.mode, target_global, camera_global);
+    let Some(axis) = frame.hit(settings.mode, ray) else {
+        return false;
+    };
+    let direction = frame.axis(axis);
+    let grabbed = if settings.mode == TransformGizmoMode::Rotate {
+        plane_hit(ray, frame.origin, direction)
+    } else {
+        closest_params(ray, frame.origin, direction)
+            .map(|(_, along_axis)| frame.origin + direction * along_axis)
+    };
+    let Some(grabbed) = grabbed else {
+        return false;
+    };
+    let start_global = target_global.compute_transform();
+    let parent = world
+        .get::<Parent>(entity)
+        .and_then(|parent| world.get::<GlobalTransform>(parent.get()))
+        .copied()
+        .unwrap_or_default();
+
+    let mut state = world.resource_mut::<TransformGizmoState>();
+    state.camera = Some(camera);
+    state.hovered = Some(axis);
+    state.drag = Some(Drag {
+        entity,
+        camera,
+        mode: settings.mode,
+        axis,
+        origin: frame.origin,
+        direction,
+        grabbed,
+        parent,
+        start_global,
+        start_local,
+        current: start_local,
+    });
+    true
+}
+
+/// Highlight the handle under a position of a render target image, in its
+/// physical pixels
+pub fn hover(world: &mut World, image: &Handle<Image>, position: Vec2) {
+    let settings = world.get_resource::<TransformGizmo>().cloned();
+    let hovered = settings
+        .filter(|settings| settings.enabled)
+        .and_then(|settings| {
+            let (camera, ray) = image_ray(world, image, position)?;
+            let entity = target(world, &settings)?;
+            let frame = Frame::new(
+                &settings,
+                settings.mode,
+                world.get::<GlobalTransform>(entity)?,
+                world.get::<GlobalTransform>(camera)?,
+            );
+            frame.hit(settings.mode, ray)
+        });
+    if let Some(mut state) = world.get_resource_mut::<TransformGizmoState>() {
+        if state.drag.is_none() && state.hovered != hovered {
+            state.hovered = hovered;
+        }
+    }
+}
+
+/// Move the grabbed handle to a position of a render target image, in its
+/// physical pixels
+///
+/// Returns false if no handle is grabbed.
+pub fn drag(world: &mut World, image: &Handle<Image>, position: Vec2) -> bool {
+    let Some(drag) = world
+        .get_resource::<TransformGizmoState>()
+        .and_then(|state| state.drag)
+    else {
+        return false;
+    };
+    if picking::target_camera(world, image).is_none() {
+        return true;
+    }
+    // The handle follows the cursor out of the viewport of its camera, when
+    // several cameras share the image
+    let Some(ray) = picking::viewport_ray(world, drag.camera, position) else {
+        return true;
+    };
+    let settings = world.resource::<TransformGizmo>().clone();
+    let Some(transform) = dragged_transform(&drag, &settings, ray) else {
+        return true;
+    };
+
+    if let Some(mut current) = world.get_mut::<Transform>(drag.entity) {
+        if *current != transform {
+            *current = transform;
+        }
+    }
+    if let Some(drag) = world.resource_mut::<TransformGizmoState>().drag.as_mut() {
+        drag.current = transform;
+    }
+    true
+}
+
+/// Let go of the grabbed handle
+///
+/// Returns false if no handle is grabbed.
+pub fn release(world: &mut World) -> bool {
+    let Some(drag) = world
+        .get_resource_mut::<TransformGizmoState>()
+        .and_then(|mut state| state.drag.take())
+    else {
+        return false;
+    };
+    if drag.current != drag.start_local {
+        let edited = TransformEdited {
+            entity: drag.entity,
+            old_transform: drag.start_local,
+            new_transform: drag.current,
+        };
+        let mut state = world.resource_mut::<TransformGizmoState>();
+        if state.edits.len() == RETAINED_EDITS {
+            state.edits.remove(0);
+        }
+        state.edits.push(edited);
+        world.send_event(edited);
+    }
+    true
+}
+
+/// The entity selected last, if it can be transformed
+fn target(world: &World, settings: &TransformGizmo) -> Option<Entity> {
+    if !settings.enabled {
+        return None;
+    }
+    let entity = *world.get_resource::<Selection>()?.entities().last()?;
+    world
+        .get::<Transform>(entity)
+        .and(world.get::<GlobalTransform>(entity))
+        .map(|_| entity)
+}
+
+fn image_ray(world: &mut World, image: &Handle<Image>, position: Vec2) -> Option<(Entity, Ray3d)> {
+    let camera = picking::camera_at(world, image, position)?;
+    let ray = picking::viewport_ray(world, camera, position)?;
+    Some((camera, ray))
+}
+
+/// The local transform of the entity for the handle dragged along the ray
+fn dragged_transform(drag: &Drag, settings: &TransformGizmo, ray: Ray3d) -> Option<Transform> {
+    let snap = |value: f32, step: f32| {
+        if settings.snapping && step > 0.0 {
+            (value / step).round() * step
+        } else {
+            value
+        }
+    };
+
+    match drag.mode {
+        TransformGizmoMode::Translate => {
+            let (_, along_axis) = closest_params(ray, drag.origin, drag.direction)?;
+            let grabbed = (drag.grabbed - drag.origin).dot(drag.direction);
+            let offset = snap(along_axis - grabbed, settings.translation_step);
+            let global = drag
+                .start_global
+                .with_translation(drag.start_global.translation + drag.direction * offset);
+            Some(GlobalTransform::from(global).reparented_to(&drag.parent))
+        }
+        TransformGizmoMode::Rotate => {
+            let point = plane_hit(ray, drag.origin, drag.direction)?;
+            let from = drag.grabbed - drag.origin;
+            let to = point - drag.origin;
+            let angle = from.cross(to).dot(drag.direction).atan2(from.dot(to));
+            let angle = snap(angle.to_degrees(), settings.rotation_step).to_radians();
+            let global = drag.start_global.with_rotation(
+                Quat::from_axis_angle(drag.direction, angle) * drag.start_global.rotation,
+            );
+            Some(GlobalTransform::from(global).reparented_to(&drag.parent))
+        }
+        TransformGizmoMode::Scale => {
+            let (_, along_axis) = closest_params(ray, drag.origin, drag.direction)?;
+            let grabbed = (drag.grabbed - drag.origin).dot(drag.direction);
+            if grabbed.abs() < f32::EPSILON {
+                return None;
+            }
+            let factor = snap(along_axis / grabbed, settings.scale_step);
+            let mut local = drag.start_local;
+            local.scale[drag.axis.index()] *= factor.max(0.01);
+            Some(local)
+        }
+    }
+}
+
+/// Where a ray crosses the plane through a point, unless it runs along it
+fn plane_hit(ray: Ray3d, point: Vec3, normal: Vec3) -> Option<Vec3> {
+    let facing = ray.direction.dot(normal);
+    if facing.abs() < 1e-4 {
+        return None;
+    }
+    let distance = (point - ray.origin).dot(normal) / facing;
+    (distance >= 0.0).then(|| ray.get_point(distance))
+}
+
+/// The parameters of the closest points of a ray and the line through a
+/// point along a unit direction, unless they are parallel
+fn closest_params(ray: Ray3d, point: Vec3, direction: Vec3) -> Option<(f32, f32)> {
+    let offset = ray.origin - point;
+    let alignment = ray.direction.dot(direction);
+    let denominator = 1.0 - alignment * alignment;
+    if denominator < 1e-6 {
+        return None;
+    }
+    let along_ray_offset = ray.direction.dot(offset);
+    let along_axis_offset = direction.dot(offset);
+    Some((
+        (alignment * along_axis_offset - along_ray_offset) / denominator,
+        (along_axis_offset - alignment * along_ray_offset) / denominator,
+    ))
+}
+
+fn draw_handles(
+    mut gizmos: Gizmos,
+    settings: Res<TransformGizmo>,
+    state: Res<TransformGizmoState>,
+    selection: Res<Selection>,
+    transforms: Query<&GlobalTransform, With<Transform>>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform), With<Camera3d>>,
+) {
+    let Some(target) = selection
+        .entities()
+        .last()
+        .and_then(|entity| transforms.get(*entity).ok())
+    else {
+        return;
+    };
+    let camera = state
+        .camera
+        .and_then(|camera| cameras.get(camera).ok())
+        .filter(|(_, camera, _)| camera.is_active)
+        .or_else(|| {
+            cameras
+                .iter()
+                .filter(|(_, camera, _)| camera.is_active)
+                .max_by_key(|(_, camera, _)| camera.order)
+        });
+    let Some((_, _, camera)) = camera else {
+        return;
+    };
+
+    let mode = state.drag.map_or(settings.mode, |drag| drag.mode);
+    let frame = Frame::new(&settings, mode, target, camera);
+    let active = state.drag.map(|drag| drag.axis).or(state.hovered);
+    for axis in Axis::ALL {
+        let color = if active == Some(axis) {
+            ACTIVE_COLOR
+        } else {
+            axis.color()
+        };
+        let direction = frame.axis(axis);
+        let end = frame.origin + direction * frame.length;
+        match mode {
+            TransformGizmoMode::Translate => {
+                gizmos.arrow(frame.origin, end, color);
+            }
+            TransformGizmoMode::Rotate => {
+                let normal = Dir3::new(direction).unwrap_or(Dir3::Y);
+                gizmos.circle(frame.origin, normal, frame.length, color);
+            }
+            TransformGizmoMode::Scale => {
+                let (_, rotation, _) = target.to_scale_rotation_translation();
+                gizmos.line(frame.origin, end, color);
+                gizmos.cuboid(
+                    Transform::from_translation(end)
+                        .with_rotation(rotation)
+                        .with_scale(Vec3::splat(frame.length * 0.1)),
+                    color,
+                );
+            }
+        }
+    }
+}
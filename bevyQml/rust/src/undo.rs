@@ -0,0 +1,945 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Undoable edits of the world for editor-style apps, following the
+//! semantics of QUndoStack. See [crate::cxxqt_bevy_undo_stack] for the QML
+//! side.
+//!
+//! An edit is an [EditCommand] which knows how to apply itself to the world
+//! and how to take itself back. [push] applies a command and puts it on the
+//! [QmlUndoStack], [undo] and [redo] move along the stack. Pushing after an
+//! undo drops the commands which could have been redone. Consecutive
+//! commands with the same [EditCommand::merge_id] may be merged into one,
+//! such as the steps of dragging a slider, unless the stack was marked clean
+//! in between. Commands pushed between [begin_macro] and [end_macro] are
+//! undone and redone together.
+//!
+//! ```ignore
+//! undo::push(world, SetTransform::new(world, entity, Transform::from_xyz(0.0, 1.0, 0.0)))?;
+//! undo::undo(world)?;
+//! ```
+//!
+//! [SetTransform], [SpawnEntity], [DespawnEntity] and [SetComponent] cover
+//! the usual edits. Edits made with the handles of the transform gizmo are
+//! recorded on the stack as they are let go.
+//!
+//! Commands refer to entities by their id. A despawned entity is spawned
+//! again with the same id when its despawn is undone, so the commands before
+//! it on the stack still find it.
+
+use std::any::{Any, TypeId};
+
+use bevy::{ecs::reflect::ReflectComponent, prelude::*};
+use cxx_qt_lib::QVariant;
+
+use crate::{
+    commands::{self, QmlCommand},
+    label::{EntityLabel, QmlLabel},
+    transform_gizmo::TransformEdited,
+};
+
+/// An undoable edit of the world, like a QUndoCommand
+pub trait EditCommand: AsAny + Send + Sync + 'static {
+    /// What the edit does, e.g. for "Undo Move Cube" in a menu
+    fn text(&self) -> String;
+
+    /// Make the edit, the first time when it is pushed and again on redo
+    fn apply(&mut self, world: &mut World) -> Result<(), String>;
+
+    /// Take the edit back
+    fn undo(&mut self, world: &mut World) -> Result<(), String>;
+
+    /// Commands with the same id may be merged with [EditCommand::merge_with]
+    fn merge_id(&self) -> Option<u32> {
+        None
+    }
+
+    /// Take over the result of the next command, which was already applied,
+    /// returning false to keep both
+    fn merge_with(&mut self, _next: &dyn EditCommand) -> bool {
+        false
+    }
+}
+
+/// Lets [EditCommand::merge_with] downcast the next command
+pub trait AsAny {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// The merge id of [SetTransform]
+pub const SET_TRANSFORM_MERGE_ID: u32 = 1;
+
+/// The merge id of [SetComponent]
+pub const SET_COMPONENT_MERGE_ID: u32 = 2;
+
+/// The commands which were pushed, and how far they are applied
+#[derive(Resource)]
+pub struct QmlUndoStack {
+    commands: Vec<Box<dyn EditCommand>>,
+    /// How many commands are applied, the next one to redo
+    index: usize,
+    /// The index at which the stack was marked clean, if it still exists
+    clean_index: Option<usize>,
+    /// How many commands are kept, 0 for no limit
+    undo_limit: usize,
+    /// The macros being recorded, innermost last
+    macros: Vec<MacroCommand>,
+}
+
+impl Default for QmlUndoStack {
+    /// An empty stack, which is clean
+    fn default() -> Self {
+        Self {
+            commands: Vec::new(),
+            index: 0,
+            clean_index: Some(0),
+            undo_limit: 0,
+            macros: Vec::new(),
+        }
+    }
+}
+
+impl QmlUndoStack {
+    pub fn can_undo(&self) -> bool {
+        self.macros.is_empty() && self.index > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.macros.is_empty() && self.index < self.commands.len()
+    }
+
+    /// The text of the command [undo] would take back
+    pub fn undo_text(&self) -> String {
+        self.index
+            .checked_sub(1)
+            .map(|index| self.text(index))
+            .unwrap_or_default()
+    }
+
+    /// The text of the command [redo] would apply
+    pub fn redo_text(&self) -> String {
+        self.text(self.index)
+    }
+
+    /// The text of the command at the index, empty if there is none
+    pub fn text(&self, index: usize) -> String {
+        self.commands
+            .get(index)
+            .map(|command| command.text())
+            .unwrap_or_default()
+    }
+
+    /// How many commands are on the stack
+    pub fn count(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// How many commands are applied
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Whether the world is as it was when the stack was marked clean
+    pub fn is_clean(&self) -> bool {
+        self.macros.is_empty() && self.clean_index == Some(self.index)
+    }
+
+    /// Mark the current state as clean, e.g. after saving
+    pub fn set_clean(&mut self) {
+        self.clean_index = Some(self.index);
+    }
+
+    /// Whether a macro is being recorded
+    pub fn is_recording_macro(&self) -> bool {
+        !self.macros.is_empty()
+    }
+
+    pub fn undo_limit(&self) -> usize {
+        self.undo_limit
+    }
+
+    /// Keep at most this many commands, dropping the oldest, 0 for no limit
+    ///
+    /// Only applied commands are dropped, commands which can be redone stay
+    /// on the stack until the next push drops them.
+    pub fn set_undo_limit(&mut self, limit: usize) {
+        self.undo_limit = limit;
+        self.enforce_limit();
+    }
+
+    /// Drop every command without undoing it, the state becomes clean
+    pub fn clear(&mut self) {
+        self.commands.clear();
+        self.macros.clear();
+        self.index = 0;
+        self.clean_index = Some(0);
+    }
+
+    /// Put a command which was already applied on the stack, or into the
+    /// macro being recorded
+    pub fn record(&mut self, command: impl EditCommand) {
+        self.record_boxed(Box::new(command));
+    }
+
+    fn record_boxed(&mut self, command: Box<dyn EditCommand>) {
+        if let Some(recording) = self.macros.last_mut() {
+            recording.add(command);
+            return;
+        }
+
+        // Pushing drops whatever could have been redone
+        self.commands.truncate(self.index);
+        if self.clean_index.is_some_and(|clean| clean > self.index) {
+            self.clean_index = None;
+        }
+
+        let mergeable = self.clean_index != Some(self.index);
+        if let Some(last) = self.commands.last_mut().filter(|_| mergeable) {
+            if merges(&**last, &*command) && last.merge_with(&*command) {
+                return;
+            }
+        }
+        self.commands.push(command);
+        self.index = self.commands.len();
+        self.enforce_limit();
+    }
+
+    fn enforce_limit(&mut self) {
+        if self.undo_limit == 0 || self.commands.len() <= self.undo_limit {
+            return;
+        }
+        // Never drop what could still be redone
+        let dropped = (self.commands.len() - self.undo_limit).min(self.index);
+        self.commands.drain(..dropped);
+        self.index -= dropped;
+        self.clean_index = self
+            .clean_index
+            .and_then(|clean| clean.checked_sub(dropped));
+    }
+}
+
+fn merges(last: &dyn EditCommand, next: &dyn EditCommand) -> bool {
+    last.merge_id().is_some() && last.merge_id() == next.merge_id()
+}
+
+/// Apply the command and put it on the [QmlUndoStack]
+///
+/// Nothing is put on the stack if applying fails.
+pub fn push(world: &mut World, mut command: impl EditCommand) -> Result<(), String> {
+    if !world.contains_resource::<QmlUndoStack>() {
+        return Err(String::from("the app has no QmlUndoPlugin"));
+    }
+    command.apply(world)?;
+    world.resource_mut::<QmlUndoStack>().record(command);
+    Ok(())
+}
+
+/// Take back the last applied command
+///
+/// The stack stays as it is if the command fails to undo.
+pub fn undo(world: &mut World) -> Result<(), String> {
+    step(world, true)
+}
+
+/// Apply the command after the last applied one again
+///
+/// The stack stays as it is if the command fails to apply.
+pub fn redo(world: &mut World) -> Result<(), String> {
+    step(world, false)
+}
+
+fn step(world: &mut World, back: bool) -> Result<(), String> {
+    if !world.contains_resource::<QmlUndoStack>() {
+        return Err(String::from("the app has no QmlUndoPlugin"));
+    }
+    world.resource_scope(|world, mut stack: Mut<QmlUndoStack>| {
+        if stack.is_recording_macro() {
+            return Err(String::from("a macro is being recorded"));
+        }
+        let stack = &mut *stack;
+        if back {
+            let Some(index) = stack.index.checked_sub(1) else {
+                return Ok(());
+            };
+            stack.commands[index].undo(world)?;
+            stack.index = index;
+        } else {
+            let Some(command) = stack.commands.get_mut(stack.index) else {
+                return Ok(());
+            };
+            command.apply(world)?;
+            stack.index += 1;
+        }
+        Ok(())
+    })
+}
+
+/// Start recording the commands pushed from now on as one, until
+/// [end_macro]
+///
+/// Macros may be nested. Undo and redo are not possible while recording.
+pub fn begin_macro(world: &mut World, text: impl Into<String>) {
+    if let Some(mut stack) = world.get_resource_mut::<QmlUndoStack>() {
+        stack.macros.push(MacroCommand::new(text));
+    }
+}
+
+/// Finish the macro started last, which goes on the stack unless it is
+/// empty
+pub fn end_macro(world: &mut World) {
+    let Some(mut stack) = world.get_resource_mut::<QmlUndoStack>() else {
+        return;
+    };
+    let Some(recorded) = stack.macros.pop() else {
+        warn!("end_macro was called without begin_macro");
+        return;
+    };
+    if !recorded.children.is_empty() {
+        stack.record(recorded);
+    }
+}
+
+/// Commands which are undone and redone together
+pub struct MacroCommand {
+    text: String,
+    children: Vec<Box<dyn EditCommand>>,
+}
+
+impl MacroCommand {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Add a command which was already applied
+    pub fn add(&mut self, command: Box<dyn EditCommand>) {
+        self.children.push(command);
+    }
+}
+
+impl EditCommand for MacroCommand {
+    fn text(&self) -> String {
+        self.text.clone()
+    }
+
+    fn apply(&mut self, world: &mut World) -> Result<(), String> {
+        self.children
+            .iter_mut()
+            .try_for_each(|command| command.apply(world))
+    }
+
+    fn undo(&mut self, world: &mut World) -> Result<(), String> {
+        self.children
+            .iter_mut()
+            .rev()
+            .try_for_each(|command| command.undo(world))
+    }
+}
+
+/// Change the [Transform] of an entity
+pub struct SetTransform {
+    entity: Entity,
+    text: String,
+    old: Option<Transform>,
+    new: Transform,
+    merging: bool,
+}
+
+impl SetTransform {
+    pub fn new(world: &World, entity: Entity, transform: Transform) -> Self {
+        Self {
+            entity,
+            text: format!("Move {}", EntityLabel::of(world, entity).display_name),
+            old: None,
+            new: transform,
+            merging: false,
+        }
+    }
+
+    /// A change which was already made, from `old` to the current transform
+    pub fn applied(entity: Entity, text: String, old: Transform, new: Transform) -> Self {
+        Self {
+            entity,
+            text,
+            old: Some(old),
+            new,
+            merging: false,
+        }
+    }
+
+    /// Merge with the following changes of the same entity which are
+    /// merging as well
+    pub fn merging(mut self) -> Self {
+        self.merging = true;
+        self
+    }
+
+    fn write(&self, world: &mut World, transform: Transform) -> Result<(), String> {
+        let mut current = world
+            .get_mut::<Transform>(self.entity)
+            .ok_or_else(|| format!("{:?} has no Transform", self.entity))?;
+        *current = transform;
+        Ok(())
+    }
+}
+
+impl EditCommand for SetTransform {
+    fn text(&self) -> String {
+        self.text.clone()
+    }
+
+    fn apply(&mut self, world: &mut World) -> Result<(), String> {
+        if self.old.is_none() {
+            self.old = world.get::<Transform>(self.entity).copied();
+        }
+        self.write(world, self.new)
+    }
+
+    fn undo(&mut self, world: &mut World) -> Result<(), String> {
+        let old = self.old.ok_or("the transform was never changed")?;
+        self.write(world, old)
+    }
+
+    fn merge_id(&self) -> Option<u32> {
+        self.merging.then_some(SET_TRANSFORM_MERGE_ID)
+    }
+
+    fn merge_with(&mut self, next: &dyn EditCommand) -> bool {
+        let Some(next) = next.as_any().downcast_ref::<Self>() else {
+            return false;
+        };
+        if next.entity != self.entity {
+            return false;
+        }
+        self.new = next.new;
+        true
+    }
+}
+
+/// Insert a reflected component, or overwrite what the value mentions of an
+/// existing one, see [crate::variant]
+///
+/// Undoing puts back the component as it was, or removes it if the entity
+/// had none.
+pub struct SetComponent {
+    entity: Entity,
+    type_name: String,
+    text: String,
+    /// The value as given, until the command was applied once
+    value: Option<QVariant>,
+    old: Option<Box<dyn Reflect>>,
+    new: Option<Box<dyn Reflect>>,
+    merging: bool,
+}
+
+impl SetComponent {
+    pub fn new(
+        world: &World,
+        entity: Entity,
+        type_name: impl Into<String>,
+        value: QVariant,
+    ) -> Self {
+        let type_name = type_name.into();
+        let short_name = type_name.rsplit("::").next().unwrap_or_default();
+        Self {
+            entity,
+            text: format!(
+                "Edit {short_name} of {}",
+                EntityLabel::of(world, entity).display_name
+            ),
+            type_name,
+            value: Some(value),
+            old: None,
+            new: None,
+            merging: false,
+        }
+    }
+
+    /// Merge with the following edits of the same component which are
+    /// merging as well
+    pub fn merging(mut self) -> Self {
+        self.merging = true;
+        self
+    }
+
+    fn restore(&self, world: &mut World, value: Option<&dyn Reflect>) -> Result<(), String> {
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+        let reflect = commands::lookup(&registry, &self.type_name)?
+            .data::<ReflectComponent>()
+            .ok_or_else(|| format!("{} does not reflect Component", self.type_name))?
+            .clone();
+        let mut entity = world
+            .get_entity_mut(self.entity)
+            .ok_or_else(|| format!("{:?} does not exist", self.entity))?;
+        match value {
+            Some(value) => reflect.insert(&mut entity, value, &registry),
+            None => reflect.remove(&mut entity),
+        }
+        Ok(())
+    }
+}
+
+impl EditCommand for SetComponent {
+    fn text(&self) -> String {
+        self.text.clone()
+    }
+
+    fn apply(&mut self, world: &mut World) -> Result<(), String> {
+        let Some(value) = self.value.take() else {
+            return self.restore(world, self.new.as_deref());
+        };
+        self.old = snapshot_component(world, self.entity, &self.type_name);
+        let applied = commands::apply_command(
+            world,
+            QmlCommand::SetComponent {
+                entity: self.entity,
+                type_name: self.type_name.clone(),
+                value: value.clone(),
+            },
+        );
+        if let Err(error) = applied {
+            self.value = Some(value);
+            return Err(error);
+        }
+        self.new = snapshot_component(world, self.entity, &self.type_name);
+        Ok(())
+    }
+
+    fn undo(&mut self, world: &mut World) -> Result<(), String> {
+        self.restore(world, self.old.as_deref())
+    }
+
+    fn merge_id(&self) -> Option<u32> {
+        self.merging.then_some(SET_COMPONENT_MERGE_ID)
+    }
+
+    fn merge_with(&mut self, next: &dyn EditCommand) -> bool {
+        let Some(next) = next.as_any().downcast_ref::<Self>() else {
+            return false;
+        };
+        if next.entity != self.entity || next.type_name != self.type_name {
+            return false;
+        }
+        self.new = next.new.as_ref().map(|value| value.clone_value());
+        true
+    }
+}
+
+fn snapshot_component(world: &World, entity: Entity, type_name: &str) -> Option<Box<dyn Reflect>> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let reflect = commands::lookup(&registry, type_name)
+        .ok()?
+        .data::<ReflectComponent>()?;
+    let value = reflect.reflect(world.get_entity(entity)?)?;
+    Some(value.clone_value())
+}
+
+/// Spawn an entity, given one which was reserved
+pub struct SpawnEntity {
+    entity: Entity,
+    components: EntitySnapshot,
+}
+
+impl SpawnEntity {
+    pub fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            components: EntitySnapshot::default(),
+        }
+    }
+}
+
+impl EditCommand for SpawnEntity {
+    fn text(&self) -> String {
+        String::from("Spawn entity")
+    }
+
+    fn apply(&mut self, world: &mut World) -> Result<(), String> {
+        self.components.restore(world, self.entity)
+    }
+
+    fn undo(&mut self, world: &mut World) -> Result<(), String> {
+        self.components = EntitySnapshot::capture(world, self.entity)?;
+        world.despawn(self.entity);
+        Ok(())
+    }
+}
+
+/// Despawn an entity, undone by spawning it again with the same id and its
+/// reflected components
+///
+/// Only the entity itself is despawned, not its children.
+pub struct DespawnEntity {
+    entity: Entity,
+    text: String,
+    components: EntitySnapshot,
+}
+
+impl DespawnEntity {
+    pub fn new(world: &World, entity: Entity) -> Self {
+        Self {
+            entity,
+            text: format!("Despawn {}", EntityLabel::of(world, entity).display_name),
+            components: EntitySnapshot::default(),
+        }
+    }
+}
+
+impl EditCommand for DespawnEntity {
+    fn text(&self) -> String {
+        self.text.clone()
+    }
+
+    fn apply(&mut self, world: &mut World) -> Result<(), String> {
+        self.components = EntitySnapshot::capture(world, self.entity)?;
+        world.despawn(self.entity);
+        Ok(())
+    }
+
+    fn undo(&mut self, world: &mut World) -> Result<(), String> {
+        self.components.restore(world, self.entity)
+    }
+}
+
+/// The reflected components of an entity
+#[derive(Default)]
+struct EntitySnapshot(Vec<(TypeId, Box<dyn Reflect>)>);
+
+impl EntitySnapshot {
+    fn capture(world: &World, entity: Entity) -> Result<Self, String> {
+        let entity_ref = world
+            .get_entity(entity)
+            .ok_or_else(|| format!("{entity:?} does not exist"))?;
+        let registry = world.resource::<AppTypeRegistry>().read();
+        let components = world
+            .inspect_entity(entity)
+            .into_iter()
+            .filter_map(|info| {
+                let type_id = info.type_id()?;
+                let reflect = registry.get_type_data::<ReflectComponent>(type_id)?;
+                Some((type_id, reflect.reflect(entity_ref)?.clone_value()))
+            })
+            .collect();
+        Ok(Self(components))
+    }
+
+    /// Spawn the entity with the same id if it does not exist, and insert
+    /// the components
+    fn restore(&self, world: &mut World, entity: Entity) -> Result<(), String> {
+        // Reserved entities only exist once the world has been flushed
+        world.flush();
+        world
+            .get_or_spawn(entity)
+            .ok_or_else(|| format!("{entity:?} is taken by another entity"))?;
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+        let mut entity_mut = world.entity_mut(entity);
+        for (type_id, value) in &self.0 {
+            if let Some(reflect) = registry.get_type_data::<ReflectComponent>(*type_id) {
+                reflect.insert(&mut entity_mut, &**value, &registry);
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct QmlUndoPlugin;
+
+impl Plugin for QmlUndoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QmlUndoStack>().add_systems(
+            Last,
+            record_gizmo_edits.run_if(resource_exists::<Events<TransformEdited>>),
+        );
+    }
+}
+
+/// Put the edits made with the handles of the transform gizmo on the stack
+fn record_gizmo_edits(
+    mut edits: EventReader<TransformEdited>,
+    labels: Query<(Option<&Name>, Option<&QmlLabel>)>,
+    mut stack: ResMut<QmlUndoStack>,
+) {
+    for edited in edits.read() {
+        let (name, label) = labels.get(edited.entity).unwrap_or_default();
+        let label = EntityLabel::new(edited.entity, name, label);
+        stack.record(SetTransform::applied(
+            edited.entity,
+            format!("Move {}", label.display_name),
+            edited.old_transform,
+            edited.new_transform,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Counter(i32);
+
+    /// Adds to the [Counter]
+    struct Add {
+        amount: i32,
+        merging: bool,
+    }
+
+    impl Add {
+        fn new(amount: i32) -> Self {
+            Self {
+                amount,
+                merging: false,
+            }
+        }
+
+        fn merging(amount: i32) -> Self {
+            Self {
+                amount,
+                merging: true,
+            }
+        }
+    }
+
+    impl EditCommand for Add {
+        fn text(&self) -> String {
+            format!("Add {}", self.amount)
+        }
+
+        fn apply(&mut self, world: &mut World) -> Result<(), String> {
+            world.resource_mut::<Counter>().0 += self.amount;
+            Ok(())
+        }
+
+        fn undo(&mut self, world: &mut World) -> Result<(), String> {
+            world.resource_mut::<Counter>().0 -= self.amount;
+            Ok(())
+        }
+
+        fn merge_id(&self) -> Option<u32> {
+            self.merging.then_some(100)
+        }
+
+        fn merge_with(&mut self, next: &dyn EditCommand) -> bool {
+            let Some(next) = next.as_any().downcast_ref::<Self>() else {
+                return false;
+            };
+            self.amount += next.amount;
+            true
+        }
+    }
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Counter>();
+        world.init_resource::<QmlUndoStack>();
+        world
+    }
+
+    fn counter(world: &World) -> i32 {
+        world.resource::<Counter>().0
+    }
+
+    fn stack(world: &World) -> &QmlUndoStack {
+        world.resource::<QmlUndoStack>()
+    }
+
+    #[test]
+    fn push_needs_the_plugin() {
+        let mut world = World::new();
+        world.init_resource::<Counter>();
+        assert!(push(&mut world, Add::new(1)).is_err());
+        assert_eq!(counter(&world), 0);
+        assert!(undo(&mut world).is_err());
+    }
+
+    #[test]
+    fn push_undo_redo() {
+        let mut world = world();
+        for amount in [1, 2, 3] {
+            push(&mut world, Add::new(amount)).unwrap();
+        }
+        assert_eq!(counter(&world), 6);
+        assert_eq!(stack(&world).count(), 3);
+        assert_eq!(stack(&world).undo_text(), "Add 3");
+        assert!(!stack(&world).can_redo());
+
+        undo(&mut world).unwrap();
+        assert_eq!(counter(&world), 3);
+        assert_eq!(stack(&world).index(), 2);
+        assert_eq!(stack(&world).redo_text(), "Add 3");
+
+        redo(&mut world).unwrap();
+        assert_eq!(counter(&world), 6);
+        redo(&mut world).unwrap();
+        assert_eq!(counter(&world), 6);
+
+        for _ in 0..4 {
+            undo(&mut world).unwrap();
+        }
+        assert_eq!(counter(&world), 0);
+        assert!(!stack(&world).can_undo());
+        assert_eq!(stack(&world).undo_text(), "");
+    }
+
+    #[test]
+    fn push_drops_redoable_commands() {
+        let mut world = world();
+        push(&mut world, Add::new(1)).unwrap();
+        push(&mut world, Add::new(2)).unwrap();
+        undo(&mut world).unwrap();
+        push(&mut world, Add::new(5)).unwrap();
+        assert_eq!(counter(&world), 6);
+        assert_eq!(stack(&world).count(), 2);
+        assert_eq!(stack(&world).text(1), "Add 5");
+        assert!(!stack(&world).can_redo());
+    }
+
+    #[test]
+    fn merges_consecutive_commands() {
+        let mut world = world();
+        push(&mut world, Add::merging(1)).unwrap();
+        push(&mut world, Add::merging(2)).unwrap();
+        assert_eq!(stack(&world).count(), 1);
+        assert_eq!(stack(&world).undo_text(), "Add 3");
+
+        // Commands without a merge id are kept apart
+        push(&mut world, Add::new(4)).unwrap();
+        push(&mut world, Add::merging(8)).unwrap();
+        assert_eq!(stack(&world).count(), 3);
+
+        undo(&mut world).unwrap();
+        undo(&mut world).unwrap();
+        undo(&mut world).unwrap();
+        assert_eq!(counter(&world), 0);
+    }
+
+    #[test]
+    fn does_not_merge_across_clean_state() {
+        let mut world = world();
+        push(&mut world, Add::merging(1)).unwrap();
+        world.resource_mut::<QmlUndoStack>().set_clean();
+        push(&mut world, Add::merging(2)).unwrap();
+        assert_eq!(stack(&world).count(), 2);
+        undo(&mut world).unwrap();
+        assert!(stack(&world).is_clean());
+        assert_eq!(counter(&world), 1);
+    }
+
+    #[test]
+    fn macros_are_undone_together() {
+        let mut world = world();
+        begin_macro(&mut world, "Add both");
+        push(&mut world, Add::new(1)).unwrap();
+        push(&mut world, Add::new(2)).unwrap();
+        assert!(stack(&world).is_recording_macro());
+        assert!(!stack(&world).can_undo());
+        assert!(undo(&mut world).is_err());
+        end_macro(&mut world);
+
+        assert_eq!(stack(&world).count(), 1);
+        assert_eq!(stack(&world).undo_text(), "Add both");
+        undo(&mut world).unwrap();
+        assert_eq!(counter(&world), 0);
+        redo(&mut world).unwrap();
+        assert_eq!(counter(&world), 3);
+    }
+
+    #[test]
+    fn nested_and_empty_macros() {
+        let mut world = world();
+        begin_macro(&mut world, "Outer");
+        push(&mut world, Add::new(1)).unwrap();
+        begin_macro(&mut world, "Inner");
+        push(&mut world, Add::new(2)).unwrap();
+        end_macro(&mut world);
+        end_macro(&mut world);
+        assert_eq!(stack(&world).count(), 1);
+        undo(&mut world).unwrap();
+        assert_eq!(counter(&world), 0);
+
+        begin_macro(&mut world, "Nothing");
+        end_macro(&mut world);
+        assert_eq!(stack(&world).count(), 1);
+        assert!(!stack(&world).is_recording_macro());
+    }
+
+    #[test]
+    fn tracks_the_clean_state() {
+        let mut world = world();
+        assert!(stack(&world).is_clean());
+        push(&mut world, Add::new(1)).unwrap();
+        assert!(!stack(&world).is_clean());
+        undo(&mut world).unwrap();
+        assert!(stack(&world).is_clean());
+
+        redo(&mut world).unwrap();
+        world.resource_mut::<QmlUndoStack>().set_clean();
+        undo(&mut world).unwrap();
+        assert!(!stack(&world).is_clean());
+        redo(&mut world).unwrap();
+        assert!(stack(&world).is_clean());
+
+        // The clean state is gone once it can no longer be redone
+        undo(&mut world).unwrap();
+        push(&mut world, Add::new(2)).unwrap();
+        undo(&mut world).unwrap();
+        redo(&mut world).unwrap();
+        assert!(!stack(&world).is_clean());
+
+        world.resource_mut::<QmlUndoStack>().clear();
+        assert!(stack(&world).is_clean());
+        assert_eq!(stack(&world).count(), 0);
+    }
+
+    #[test]
+    fn limit_drops_the_oldest_commands() {
+        let mut world = world();
+        world.resource_mut::<QmlUndoStack>().set_undo_limit(2);
+        for amount in [1, 2, 3] {
+            push(&mut world, Add::new(amount)).unwrap();
+        }
+        assert_eq!(counter(&world), 6);
+        assert_eq!(stack(&world).count(), 2);
+        assert_eq!(stack(&world).text(0), "Add 2");
+        for _ in 0..3 {
+            undo(&mut world).unwrap();
+        }
+        assert_eq!(counter(&world), 1);
+    }
+
+    #[test]
+    fn limit_keeps_redoable_commands() {
+        let mut world = world();
+        for amount in [1, 2, 3, 4] {
+            push(&mut world, Add::new(amount)).unwrap();
+        }
+        undo(&mut world).unwrap();
+        undo(&mut world).unwrap();
+        undo(&mut world).unwrap();
+        world.resource_mut::<QmlUndoStack>().set_clean();
+
+        world.resource_mut::<QmlUndoStack>().set_undo_limit(2);
+        assert_eq!(stack(&world).count(), 3);
+        assert_eq!(stack(&world).index(), 0);
+        assert!(stack(&world).is_clean());
+        for _ in 0..3 {
+            redo(&mut world).unwrap();
+        }
+        assert_eq!(counter(&world), 10);
+
+        // The next push trims the stack down to the limit
+        push(&mut world, Add::new(5)).unwrap();
+        assert_eq!(stack(&world).count(), 2);
+        assert_eq!(stack(&world).index(), 2);
+        assert!(!stack(&world).is_clean());
+    }
+}
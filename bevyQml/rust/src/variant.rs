@@ -0,0 +1,951 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Conversions between reflected Bevy values and QVariant.
+//!
+//! Values are converted by walking their reflected structure:
+//!
+//! | Rust | QML |
+//! |------|-----|
+//! | booleans, numbers, strings | `bool`, `real`, `int`, `string` |
+//! | [Vec2], [Vec3], [Vec4], [Quat] | `vector2d`, `vector3d`, `vector4d`, `quaternion` |
+//! | [Color] | `color` |
+//! | [Entity] | its bits, as with the rest of the crate |
+//! | structs, maps with string keys | objects |
+//! | lists, arrays, tuples, tuple structs | arrays |
+//! | tuple structs of a single field | the field |
+//! | enums without fields | the name of the variant |
+//! | [Option] | `null` or the value |
+//! | other enums | an object naming the variant with `variant`, plus its fields |
+//!
+//! Values nothing else applies to, such as handles, are left out when
+//! reading and refused when writing. Writing only touches what the variant
+//! mentions, so an object may set a few fields of a struct and leave the
+//! rest alone. Growing a list, adding to a map or switching an enum to a
+//! variant with fields needs new values, which [apply_variant_with] creates
+//! from the [ReflectDefault] of their type in the [TypeRegistry].
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_variant")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qvariant.h");
+        /// An alias to the QVariant type
+        type QVariant = cxx_qt_lib::QVariant;
+        include!("cxx-qt-lib/qmap.h");
+        /// An alias to the QVariantMap type
+        type QMap_QString_QVariant = cxx_qt_lib::QMap<cxx_qt_lib::QMapPair_QString_QVariant>;
+        include!("cxx-qt-lib/qlist.h");
+        /// An alias to the QVariantList type
+        type QList_QVariant = cxx_qt_lib::QList<cxx_qt_lib::QVariant>;
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/variant.h");
+
+        #[doc(hidden)]
+        #[rust_name = "qvariant_from_vector2d"]
+        fn qvariantFromVector2D(x: f32, y: f32) -> QVariant;
+
+        #[doc(hidden)]
+        #[rust_name = "qvariant_from_vector3d"]
+        fn qvariantFromVector3D(x: f32, y: f32, z: f32) -> QVariant;
+
+        #[doc(hidden)]
+        #[rust_name = "qvariant_from_vector4d"]
+        fn qvariantFromVector4D(x: f32, y: f32, z: f32, w: f32) -> QVariant;
+
+        #[doc(hidden)]
+        #[rust_name = "qvariant_from_quaternion"]
+        fn qvariantFromQuaternion(x: f32, y: f32, z: f32, w: f32) -> QVariant;
+
+        #[doc(hidden)]
+        #[rust_name = "qvariant_to_floats"]
+        fn qvariantToFloats(variant: &QVariant, out: &mut [f32]) -> usize;
+
+        #[doc(hidden)]
+        #[rust_name = "qvariant_from_map"]
+        fn qvariantFromMap(map: &QMap_QString_QVariant) -> QVariant;
+
+        #[doc(hidden)]
+        #[rust_name = "qvariant_from_list"]
+        fn qvariantFromList(list: &QList_QVariant) -> QVariant;
+
+        #[doc(hidden)]
+        #[rust_name = "qvariant_is_null"]
+        fn qvariantIsNull(variant: &QVariant) -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "qvariant_is_map"]
+        fn qvariantIsMap(variant: &QVariant) -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "qvariant_is_list"]
+        fn qvariantIsList(variant: &QVariant) -> bool;
+
+        #[doc(hidden)]
+        #[rust_name = "qvariant_to_map"]
+        fn qvariantToMap(variant: &QVariant) -> QMap_QString_QVariant;
+
+        #[doc(hidden)]
+        #[rust_name = "qvariant_to_list"]
+        fn qvariantToList(variant: &QVariant) -> QList_QVariant;
+    }
+}
+
+use std::{any::TypeId, borrow::Cow};
+
+use bevy::{
+    color::Color,
+    ecs::entity::Entity,
+    math::{Quat, Vec2, Vec3, Vec4},
+    prelude::ReflectDefault,
+    reflect::{
+        DynamicEnum, DynamicStruct, DynamicTuple, DynamicVariant, Enum, EnumInfo, Reflect,
+        ReflectMut, ReflectRef, TypeInfo, TypeRegistration, TypeRegistry, VariantInfo, VariantType,
+    },
+};
+pub use cxx_qt_lib::QVariant;
+use cxx_qt_lib::{QColor, QList, QMap, QMapPair_QString_QVariant, QString};
+
+use crate::convert::{FromQt, IntoQt};
+
+/// A Rust value which can be stored in a QVariant
+///
+/// Used by the code generated for `#[derive(QmlComponent)]`.
+pub trait QmlValue: Sized {
+    fn to_variant(&self) -> QVariant;
+
+    /// Returns [None] if the variant cannot be converted into this type
+    fn from_variant(variant: &QVariant) -> Option<Self>;
+}
+
+macro_rules! impl_qml_value {
+    ($($ty:ty),*) => {
+        $(
+            impl QmlValue for $ty {
+                fn to_variant(&self) -> QVariant {
+                    QVariant::from(self)
+                }
+
+                fn from_variant(variant: &QVariant) -> Option<Self> {
+                    variant.value::<$ty>()
+                }
+            }
+        )*
+    };
+}
+
+impl_qml_value!(bool, f32, f64, i8, i16, i32, i64, u8, u16, u32, u64, QString);
+
+impl QmlValue for String {
+    fn to_variant(&self) -> QVariant {
+        QVariant::from(&QString::from(self.as_str()))
+    }
+
+    fn from_variant(variant: &QVariant) -> Option<Self> {
+        variant.value::<QString>().map(|value| value.to_string())
+    }
+}
+
+/// Wrap a vector in a QVariant holding a QVector3D, a `vector3d` in QML
+pub fn vec3_to_variant(value: Vec3) -> QVariant {
+    ffi::qvariant_from_vector3d(value.x, value.y, value.z)
+}
+
+/// The entries of a variant holding a map, such as a JavaScript object
+/// passed from QML, empty for anything else
+pub fn map_entries(variant: &QVariant) -> Vec<(String, QVariant)> {
+    ffi::qvariant_to_map(variant)
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.clone()))
+        .collect()
+}
+
+/// The elements of a variant holding a list, such as a JavaScript array
+/// passed from QML, empty for anything else
+pub fn list_items(variant: &QVariant) -> Vec<QVariant> {
+    ffi::qvariant_to_list(variant).iter().cloned().collect()
+}
+
+/// Wrap entries in a QVariant holding a QVariantMap, an object in QML
+pub fn map_to_variant(entries: impl IntoIterator<Item = (String, QVariant)>) -> QVariant {
+    let mut map = QMap::<QMapPair_QString_QVariant>::default();
+    for (key, value) in entries {
+        map.insert(QString::from(key.as_str()), value);
+    }
+    ffi::qvariant_from_map(&map)
+}
+
+/// Wrap items in a QVariant holding a QVariantList, an array in QML
+pub fn list_to_variant(items: impl IntoIterator<Item = QVariant>) -> QVariant {
+    let mut list = QList::<QVariant>::default();
+    for item in items {
+        list.append(item);
+    }
+    ffi::qvariant_from_list(&list)
+}
+
+/// Convert a reflected value into a QVariant
+///
+/// Returns [None] if the value cannot be converted. Parts of a value that
+/// cannot be converted are left out.
+pub fn to_variant(value: &dyn Reflect) -> Option<QVariant> {
+    if let Some(variant) = plain_to_variant(value).or_else(|| special_to_variant(value)) {
+        return Some(variant);
+    }
+
+    match value.reflect_ref() {
+        ReflectRef::Struct(value) => {
+            Some(map_to_variant((0..value.field_len()).filter_map(|index| {
+                Some((
+                    value.name_at(index)?.to_owned(),
+                    to_variant(value.field_at(index)?)?,
+                ))
+            })))
+        }
+        ReflectRef::TupleStruct(value) if value.field_len() == 1 => to_variant(value.field(0)?),
+        ReflectRef::TupleStruct(value) => Some(list_to_variant(
+            value
+                .iter_fields()
+                .map(|field| to_variant(field).unwrap_or_default()),
+        )),
+        ReflectRef::Tuple(value) => Some(list_to_variant(
+            value
+                .iter_fields()
+                .map(|field| to_variant(field).unwrap_or_default()),
+        )),
+        ReflectRef::List(value) => Some(list_to_variant(
+            value
+                .iter()
+                .map(|item| to_variant(item).unwrap_or_default()),
+        )),
+        ReflectRef::Array(value) => Some(list_to_variant(
+            value
+                .iter()
+                .map(|item| to_variant(item).unwrap_or_default()),
+        )),
+        ReflectRef::Map(value) => {
+            Some(map_to_variant(value.iter().filter_map(|(key, value)| {
+                Some((key_to_string(key)?, to_variant(value)?))
+            })))
+        }
+        ReflectRef::Enum(value) => enum_to_variant(value),
+        _ => None,
+    }
+}
+
+fn plain_to_variant(value: &dyn Reflect) -> Option<QVariant> {
+    macro_rules! convert {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(value) = value.downcast_ref::<$ty>() {
+                    return Some(QVariant::from(value));
+                }
+            )*
+        };
+    }
+
+    convert!(bool, f32, f64, i8, i16, i32, i64, u8, u16, u32, u64);
+    if let Some(value) = value.downcast_ref::<usize>() {
+        return Some(QVariant::from(&(*value as u64)));
+    }
+    if let Some(value) = value.downcast_ref::<isize>() {
+        return Some(QVariant::from(&(*value as i64)));
+    }
+    if let Some(value) = value.downcast_ref::<String>() {
+        return Some(QVariant::from(&QString::from(value.as_str())));
+    }
+    if let Some(value) = value.downcast_ref::<Cow<'static, str>>() {
+        return Some(QVariant::from(&QString::from(value.as_ref())));
+    }
+    None
+}
+
+/// The values QML has a type of its own for
+fn special_to_variant(value: &dyn Reflect) -> Option<QVariant> {
+    if let Some(value) = value.downcast_ref::<Vec2>() {
+        return Some(ffi::qvariant_from_vector2d(value.x, value.y));
+    }
+    if let Some(value) = value.downcast_ref::<Vec3>() {
+        return Some(vec3_to_variant(*value));
+    }
+    if let Some(value) = value.downcast_ref::<Vec4>() {
+        return Some(ffi::qvariant_from_vector4d(
+            value.x, value.y, value.z, value.w,
+        ));
+    }
+    if let Some(value) = value.downcast_ref::<Quat>() {
+        return Some(ffi::qvariant_from_quaternion(
+            value.x, value.y, value.z, value.w,
+        ));
+    }
+    if let Some(value) = value.downcast_ref::<Color>() {
+        let color: QColor = (*value).into_qt();
+        return Some(QVariant::from(&color));
+    }
+    if let Some(value) = value.downcast_ref::<Entity>() {
+        return Some(QVariant::from(&value.to_bits()));
+    }
+    None
+}
+
+fn key_to_string(key: &dyn Reflect) -> Option<String> {
+    if let Some(key) = key.downcast_ref::<String>() {
+        return Some(key.clone());
+    }
+    plain_to_variant(key)
+        .and_then(|key| key.value::<QString>())
+        .map(|key| key.to_string())
+}
+
+fn enum_to_variant(value: &dyn Enum) -> Option<QVariant> {
+    let is_option = matches!(
+        value.get_represented_type_info(),
+        Some(TypeInfo::Enum(info)) if is_option(info)
+    );
+    match value.variant_type() {
+        VariantType::Unit if is_option => Some(QVariant::default()),
+        VariantType::Unit => Some(QVariant::from(&QString::from(value.variant_name()))),
+        VariantType::Tuple if is_option => to_variant(value.field_at(0)?),
+        variant_type => {
+            let fields = (0..value.field_len()).filter_map(|index| {
+                let name = match variant_type {
+                    VariantType::Struct => value.name_at(index)?.to_owned(),
+                    _ => index.to_string(),
+                };
+                Some((name, to_variant(value.field_at(index)?)?))
+            });
+            let name = QVariant::from(&QString::from(value.variant_name()));
+            Some(map_to_variant(
+                std::iter::once(("variant".to_owned(), name)).chain(fields),
+            ))
+        }
+    }
+}
+
+fn is_option(info: &EnumInfo) -> bool {
+    info.variant_len() == 2 && info.contains_variant("None") && info.contains_variant("Some")
+}
+
+/// Overwrite a reflected value with the contents of a QVariant
+///
+/// Returns false if the variant cannot be converted into the type of the
+/// value, which may be left partly changed then. Use [apply_variant_with] to
+/// learn why, or to grow lists and maps.
+pub fn apply_variant(target: &mut dyn Reflect, variant: &QVariant) -> bool {
+    apply(target, variant, None).is_ok()
+}
+
+/// Overwrite a reflected value with the contents of a QVariant, creating the
+/// values it needs from the types in the registry
+pub fn apply_variant_with(
+    target: &mut dyn Reflect,
+    variant: &QVariant,
+    registry: &TypeRegistry,
+) -> Result<(), String> {
+    apply(target, variant, Some(registry))
+}
+
+/// Create a value of a registered type from a QVariant
+///
+/// The value starts out as the [ReflectDefault] of the type, so the variant
+/// only has to mention what differs from it.
+pub fn from_variant(
+    registration: &TypeRegistration,
+    variant: &QVariant,
+    registry: &TypeRegistry,
+) -> Result<Box<dyn Reflect>, String> {
+    let mut value = new_value(registration.type_id(), Some(registry))?;
+    apply(&mut *value, variant, Some(registry))?;
+    Ok(value)
+}
+
+fn new_value(type_id: TypeId, registry: Option<&TypeRegistry>) -> Result<Box<dyn Reflect>, String> {
+    let registry = registry.ok_or("new values need the type registry")?;
+    let registration = registry
+        .get(type_id)
+        .ok_or("the type of a new value is not registered")?;
+    Ok(registration
+        .data::<ReflectDefault>()
+        .ok_or_else(|| {
+            format!(
+                "{} does not reflect Default",
+                registration.type_info().type_path()
+            )
+        })?
+        .default())
+}
+
+fn apply(
+    target: &mut dyn Reflect,
+    variant: &QVariant,
+    registry: Option<&TypeRegistry>,
+) -> Result<(), String> {
+    let mismatch = {
+        let type_info = target.get_represented_type_info();
+        move || {
+            let type_name =
+                type_info.map_or("the value", |info| info.type_path_table().short_path());
+            format!("{type_name} cannot be set to the given value")
+        }
+    };
+    if let Some(applied) = apply_plain(target, variant) {
+        return applied.then_some(()).ok_or_else(mismatch);
+    }
+    if apply_special(target, variant) {
+        return Ok(());
+    }
+    let is_map = ffi::qvariant_is_map(variant);
+    let is_list = ffi::qvariant_is_list(variant);
+
+    match target.reflect_mut() {
+        ReflectMut::Struct(target) if is_map => {
+            for (name, value) in map_entries(variant) {
+                let field = target
+                    .field_mut(&name)
+                    .ok_or_else(|| format!("there is no field {name}"))?;
+                apply(field, &value, registry).map_err(|error| format!("{name}: {error}"))?;
+            }
+        }
+        ReflectMut::TupleStruct(target) if target.field_len() == 1 => {
+            apply(target.field_mut(0).unwrap(), variant, registry)?;
+        }
+        ReflectMut::TupleStruct(target) if is_list => {
+            let items = list_items(variant);
+            if items.len() != target.field_len() {
+                return Err(format!("expected {} items", target.field_len()));
+            }
+            for (index, item) in items.iter().enumerate() {
+                apply(target.field_mut(index).unwrap(), item, registry)
+                    .map_err(|error| format!("[{index}]: {error}"))?;
+            }
+        }
+        ReflectMut::Tuple(target) if is_list => {
+            let items = list_items(variant);
+            if items.len() != target.field_len() {
+                return Err(format!("expected {} items", target.field_len()));
+            }
+            for (index, item) in items.iter().enumerate() {
+                apply(target.field_mut(index).unwrap(), item, registry)
+                    .map_err(|error| format!("[{index}]: {error}"))?;
+            }
+        }
+        ReflectMut::Array(target) if is_list => {
+            let items = list_items(variant);
+            if items.len() != target.len() {
+                return Err(format!("expected {} items", target.len()));
+            }
+            for (index, item) in items.iter().enumerate() {
+                apply(target.get_mut(index).unwrap(), item, registry)
+                    .map_err(|error| format!("[{index}]: {error}"))?;
+            }
+        }
+        ReflectMut::List(target) if is_list => {
+            let items = list_items(variant);
+            while target.len() > items.len() {
+                target.pop();
+            }
+            for (index, item) in items.iter().enumerate() {
+                let applied = if let Some(existing) = target.get_mut(index) {
+                    apply(existing, item, registry)
+                } else {
+                    let Some(TypeInfo::List(info)) = target.get_represented_type_info() else {
+                        return Err("the item type of the list is not known".to_owned());
+                    };
+                    new_value(info.item_type_id(), registry).and_then(|mut value| {
+                        apply(&mut *value, item, registry)?;
+                        target.push(value);
+                        Ok(())
+                    })
+                };
+                applied.map_err(|error| format!("[{index}]: {error}"))?;
+            }
+        }
+        ReflectMut::Map(target) if is_map => {
+            let Some(TypeInfo::Map(info)) = target.get_represented_type_info() else {
+                return Err("the value type of the map is not known".to_owned());
+            };
+            if !info.key_is::<String>() {
+                return Err("only maps with string keys can be set from QML".to_owned());
+            }
+            let entries = map_entries(variant);
+            let stale: Vec<String> = target
+                .iter()
+                .filter_map(|(key, _)| key.downcast_ref::<String>().cloned())
+                .filter(|key| !entries.iter().any(|(name, _)| name == key))
+                .collect();
+            for key in stale {
+                target.remove(&key);
+            }
+            for (key, value) in entries {
+                let applied = if let Some(existing) = target.get_mut(&key) {
+                    apply(existing, &value, registry)
+                } else {
+                    new_value(info.value_type_id(), registry).and_then(|mut new| {
+                        apply(&mut *new, &value, registry)?;
+                        target.insert_boxed(Box::new(key.clone()), new);
+                        Ok(())
+                    })
+                };
+                applied.map_err(|error| format!("{key}: {error}"))?;
+            }
+        }
+        ReflectMut::Enum(target) => apply_enum(target, variant, registry)?,
+        _ => return Err(mismatch()),
+    }
+    Ok(())
+}
+
+/// Returns [None] if the target is not a plain value
+fn apply_plain(target: &mut dyn Reflect, variant: &QVariant) -> Option<bool> {
+    macro_rules! convert {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(target) = target.downcast_mut::<$ty>() {
+                    return Some(variant.value::<$ty>().map(|value| *target = value).is_some());
+                }
+            )*
+        };
+    }
+
+    convert!(bool, f32, f64, i8, i16, i32, i64, u8, u16, u32, u64);
+    if let Some(target) = target.downcast_mut::<usize>() {
+        return Some(
+            variant
+                .value::<u64>()
+                .and_then(|value| value.try_into().ok())
+                .map(|value| *target = value)
+                .is_some(),
+        );
+    }
+    if let Some(target) = target.downcast_mut::<isize>() {
+        return Some(
+            variant
+                .value::<i64>()
+                .and_then(|value| value.try_into().ok())
+                .map(|value| *target = value)
+                .is_some(),
+        );
+    }
+    if let Some(target) = target.downcast_mut::<String>() {
+        return Some(
+            variant
+                .value::<QString>()
+                .map(|value| *target = value.to_string())
+                .is_some(),
+        );
+    }
+    if let Some(target) = target.downcast_mut::<Cow<'static, str>>() {
+        return Some(
+            variant
+                .value::<QString>()
+                .map(|value| *target = Cow::Owned(value.to_string()))
+                .is_some(),
+        );
+    }
+    None
+}
+
+/// Returns false if the target has no QML type of its own or the variant
+/// does not hold it, so an object of fields can still be applied to it
+fn apply_special(target: &mut dyn Reflect, variant: &QVariant) -> bool {
+    let mut components = [0.0; 4];
+    let count = ffi::qvariant_to_floats(variant, &mut components);
+    let [x, y, z, w] = components;
+    if let Some(target) = target.downcast_mut::<Vec2>() {
+        if count >= 2 {
+            *target = Vec2::new(x, y);
+        }
+        return count >= 2;
+    }
+    if let Some(target) = target.downcast_mut::<Vec3>() {
+        if count >= 3 {
+            *target = Vec3::new(x, y, z);
+        }
+        return count >= 3;
+    }
+    if let Some(target) = target.downcast_mut::<Vec4>() {
+        if count == 4 {
+            *target = Vec4::new(x, y, z, w);
+        }
+        return count == 4;
+    }
+    if let Some(target) = target.downcast_mut::<Quat>() {
+        if count == 4 {
+            *target = Quat::from_xyzw(x, y, z, w).normalize();
+        }
+        return count == 4;
+    }
+    if let Some(target) = target.downcast_mut::<Color>() {
+        if ffi::qvariant_is_map(variant) {
+            return false;
+        }
+        return variant
+            .value::<QColor>()
+            .filter(QColor::is_valid)
+            .map(|color| *target = Color::from_qt(&color))
+            .is_some();
+    }
+    if let Some(target) = target.downcast_mut::<Entity>() {
+        return variant
+            .value::<u64>()
+            .and_then(|bits| Entity::try_from_bits(bits).ok())
+            .map(|entity| *target = entity)
+            .is_some();
+    }
+    false
+}
+
+/// Apply a variant name, `null` for [None], a value for [Some] or an object
+/// naming the variant and its fields
+fn apply_enum(
+    target: &mut dyn Enum,
+    variant: &QVariant,
+    registry: Option<&TypeRegistry>,
+) -> Result<(), String> {
+    let Some(TypeInfo::Enum(info)) = target.get_represented_type_info() else {
+        return Err("the variants of the enum are not known".to_owned());
+    };
+    let (name, mut fields) = if ffi::qvariant_is_map(variant) {
+        let mut fields = map_entries(variant);
+        let name = match fields.iter().position(|(key, _)| key == "variant") {
+            Some(index) => fields
+                .remove(index)
+                .1
+                .value::<QString>()
+                .map(|name| name.to_string())
+                .ok_or("variant is not a string")?,
+            None => target.variant_name().to_owned(),
+        };
+        (name, fields)
+    } else if is_option(info) && ffi::qvariant_is_null(variant) {
+        ("None".to_owned(), Vec::new())
+    } else if is_option(info) {
+        ("Some".to_owned(), vec![("0".to_owned(), variant.clone())])
+    } else {
+        let name = variant
+            .value::<QString>()
+            .map(|name| name.to_string())
+            .ok_or("expected the name of a variant")?;
+        (name, Vec::new())
+    };
+
+    if name == target.variant_name() {
+        for (key, value) in fields {
+            let field = match target.variant_type() {
+                VariantType::Struct => target.field_mut(&key),
+                _ => key
+                    .parse()
+                    .ok()
+                    .and_then(|index| target.field_at_mut(index)),
+            }
+            .ok_or_else(|| format!("{name} has no field {key}"))?;
+            apply(field, &value, registry).map_err(|error| format!("{key}: {error}"))?;
+        }
+        return Ok(());
+    }
+
+    let mut take = |key: &str| {
+        let index = fields.iter().position(|(name, _)| name == key)?;
+        Some(fields.remove(index).1)
+    };
+    let new_field = |type_id, value: Option<QVariant>| {
+        let mut field = new_value(type_id, registry)?;
+        if let Some(value) = value {
+            apply(&mut *field, &value, registry)?;
+        }
+        Ok::<_, String>(field)
+    };
+    let dynamic = match info
+        .variant(&name)
+        .ok_or_else(|| format!("{} has no variant {name}", info.type_path()))?
+    {
+        VariantInfo::Unit(_) => DynamicVariant::Unit,
+        VariantInfo::Tuple(variant_info) => {
+            let mut tuple = DynamicTuple::default();
+            for (index, field) in variant_info.iter().enumerate() {
+                let value = take(&index.to_string());
+                tuple.insert_boxed(
+                    new_field(field.type_id(), value)
+                        .map_err(|error| format!("{index}: {error}"))?,
+                );
+            }
+            DynamicVariant::Tuple(tuple)
+        }
+        VariantInfo::Struct(variant_info) => {
+            let mut dynamic_struct = DynamicStruct::default();
+            for field in variant_info.iter() {
+                let value = take(field.name());
+                dynamic_struct.insert_boxed(
+                    field.name(),
+                    new_field(field.type_id(), value)
+                        .map_err(|error| format!("{}: {error}", field.name()))?,
+                );
+            }
+            DynamicVariant::Struct(dynamic_struct)
+        }
+    };
+    if let Some((key, _)) = fields.first() {
+        return Err(format!("{name} has no field {key}"));
+    }
+    target
+        .try_apply(&DynamicEnum::new(name, dynamic))
+        .map_err(|error| error.to_string())
+}
+
+/// The convertible fields of a reflected struct, in declaration order
+pub fn struct_fields(value: &dyn Reflect) -> Vec<(String, QVariant)> {
+    let ReflectRef::Struct(value) = value.reflect_ref() else {
+        return Vec::new();
+    };
+
+    (0..value.field_len())
+        .filter_map(|index| {
+            let name = value.name_at(index)?;
+            let field = to_variant(value.field_at(index)?)?;
+            Some((name.to_owned(), field))
+        })
+        .collect()
+}
+
+/// Overwrite a single field of a reflected struct
+///
+/// Returns false if there is no such field or the variant does not fit it.
+pub fn apply_struct_field(target: &mut dyn Reflect, name: &str, variant: &QVariant) -> bool {
+    let ReflectMut::Struct(target) = target.reflect_mut() else {
+        return false;
+    };
+    target
+        .field_mut(name)
+        .is_some_and(|field| apply_variant(field, variant))
+}
+
+/// Overwrite a single field of a reflected struct, creating the values it
+/// needs from the types in the registry
+pub fn apply_struct_field_with(
+    target: &mut dyn Reflect,
+    name: &str,
+    variant: &QVariant,
+    registry: &TypeRegistry,
+) -> Result<(), String> {
+    let ReflectMut::Struct(target) = target.reflect_mut() else {
+        return Err("not a struct".to_owned());
+    };
+    let field = target
+        .field_mut(name)
+        .ok_or_else(|| format!("there is no field {name}"))?;
+    apply_variant_with(field, variant, registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Default)]
+    struct Unit {
+        name: String,
+        health: f32,
+        position: Vec3,
+        target: Option<u32>,
+        mode: Mode,
+        waypoints: Vec<Waypoint>,
+    }
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Default)]
+    enum Mode {
+        #[default]
+        Idle,
+        Walking {
+            speed: f32,
+        },
+        Following(u32),
+    }
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Default)]
+    struct Waypoint {
+        position: Vec3,
+        wait: f32,
+    }
+
+    fn registry() -> TypeRegistry {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Unit>();
+        registry.register::<Mode>();
+        registry.register::<Waypoint>();
+        registry.register::<Option<u32>>();
+        registry.register::<Vec<Waypoint>>();
+        registry
+    }
+
+    fn round_trip<T: Reflect>(value: &T, registry: &TypeRegistry) -> T {
+        let variant = to_variant(value).unwrap();
+        let registration = registry.get(TypeId::of::<T>()).unwrap();
+        *from_variant(registration, &variant, registry)
+            .unwrap()
+            .downcast::<T>()
+            .unwrap()
+    }
+
+    #[test]
+    fn struct_round_trip() {
+        let registry = registry();
+        let unit = Unit {
+            name: "scout".to_owned(),
+            health: 0.75,
+            position: Vec3::new(1.0, 2.0, 3.0),
+            target: Some(4),
+            mode: Mode::Walking { speed: 1.5 },
+            waypoints: vec![
+                Waypoint {
+                    position: Vec3::X,
+                    wait: 2.0,
+                },
+                Waypoint {
+                    position: Vec3::Z,
+                    wait: 0.0,
+                },
+            ],
+        };
+        assert_eq!(round_trip(&unit, &registry), unit);
+        assert_eq!(round_trip(&Unit::default(), &registry), Unit::default());
+    }
+
+    #[test]
+    fn enum_round_trip() {
+        let registry = registry();
+        for mode in [Mode::Idle, Mode::Walking { speed: 3.0 }, Mode::Following(7)] {
+            assert_eq!(round_trip(&mode, &registry), mode);
+        }
+        assert_eq!(
+            to_variant(&Mode::Idle).unwrap().value::<QString>(),
+            Some(QString::from("Idle"))
+        );
+    }
+
+    #[test]
+    fn from_variant_starts_from_default() {
+        let registry = registry();
+        let registration = registry.get(TypeId::of::<Unit>()).unwrap();
+        let variant = map_to_variant([("health".to_owned(), QVariant::from(&0.5_f32))]);
+        let unit = from_variant(registration, &variant, &registry).unwrap();
+        assert_eq!(
+            unit.downcast_ref::<Unit>(),
+            Some(&Unit {
+                health: 0.5,
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn reports_unknown_fields() {
+        let registry = registry();
+        let mut unit = Unit::default();
+        let variant = map_to_variant([("armor".to_owned(), QVariant::from(&1.0_f32))]);
+        assert_eq!(
+            apply_variant_with(&mut unit, &variant, &registry),
+            Err("there is no field armor".to_owned())
+        );
+
+        let variant = map_to_variant([(
+            "mode".to_owned(),
+            map_to_variant([
+                (
+                    "variant".to_owned(),
+                    QVariant::from(&QString::from("Walking")),
+                ),
+                ("pace".to_owned(), QVariant::from(&1.0_f32)),
+            ]),
+        )]);
+        assert_eq!(
+            apply_variant_with(&mut unit, &variant, &registry),
+            Err("mode: Walking has no field pace".to_owned())
+        );
+
+        let variant =
+            map_to_variant([("mode".to_owned(), QVariant::from(&QString::from("Running")))]);
+        assert!(apply_variant_with(&mut unit, &variant, &registry)
+            .unwrap_err()
+            .ends_with("has no variant Running"));
+    }
+
+    #[test]
+    fn reports_wrong_types() {
+        let registry = registry();
+        let mut unit = Unit::default();
+        let variant = map_to_variant([(
+            "position".to_owned(),
+            QVariant::from(&QString::from("here")),
+        )]);
+        assert_eq!(
+            apply_variant_with(&mut unit, &variant, &registry),
+            Err("position: Vec3 cannot be set to the given value".to_owned())
+        );
+        assert!(!apply_variant(&mut unit, &variant));
+        assert_eq!(unit.position, Vec3::ZERO);
+    }
+
+    #[test]
+    fn applies_nested_values() {
+        let registry = registry();
+        let mut unit = Unit {
+            name: "scout".to_owned(),
+            waypoints: vec![Waypoint {
+                position: Vec3::X,
+                wait: 1.0,
+            }],
+            ..Default::default()
+        };
+        // Changes the wait of the first waypoint and adds a second one
+        let variant = map_to_variant([(
+            "waypoints".to_owned(),
+            list_to_variant([
+                map_to_variant([("wait".to_owned(), QVariant::from(&2.0_f32))]),
+                map_to_variant([("position".to_owned(), vec3_to_variant(Vec3::Y))]),
+            ]),
+        )]);
+        apply_variant_with(&mut unit, &variant, &registry).unwrap();
+        assert_eq!(
+            unit.waypoints,
+            vec![
+                Waypoint {
+                    position: Vec3::X,
+                    wait: 2.0,
+                },
+                Waypoint {
+                    position: Vec3::Y,
+                    wait: 0.0,
+                },
+            ]
+        );
+        assert_eq!(unit.name, "scout");
+
+        // Errors name the path to the value
+        let variant = map_to_variant([(
+            "waypoints".to_owned(),
+            list_to_variant([
+                map_to_variant([("wait".to_owned(), QVariant::from(&1.0_f32))]),
+                map_to_variant([("speed".to_owned(), QVariant::from(&1.0_f32))]),
+            ]),
+        )]);
+        assert_eq!(
+            apply_variant_with(&mut unit, &variant, &registry),
+            Err("waypoints: [1]: there is no field speed".to_owned())
+        );
+    }
+
+    #[test]
+    fn growing_a_list_needs_the_registry() {
+        let mut unit = Unit::default();
+        let variant = map_to_variant([(
+            "waypoints".to_owned(),
+            list_to_variant([map_to_variant([])]),
+        )]);
+        assert!(!apply_variant(&mut unit, &variant));
+        assert!(apply_variant_with(&mut unit, &variant, &registry()).is_ok());
+        assert_eq!(unit.waypoints, vec![Waypoint::default()]);
+    }
+}
@@ -0,0 +1,255 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Debug renderings of the scene, picked with the `viewMode` of a
+//! `BevyQuickItem`.
+//!
+//! Bevy draws wireframes and bounding boxes for the whole world rather than
+//! for a camera, so while any item asks for such a mode every view shows it.
+//! `Normals` draws the vertex normals of the visible meshes as lines.
+//! `Uvs` and `Overdraw` swap the [StandardMaterial] of every mesh for a
+//! checker pattern laid out by its texture coordinates, or for a dim color
+//! which adds up where surfaces overlap, and give the materials back once no
+//! item asks for them any more. Items asking for both get the first one.
+
+use bevy::{
+    gizmos::aabb::AabbGizmoConfigGroup,
+    pbr::wireframe::{WireframeConfig, WireframePlugin},
+    prelude::*,
+    render::{
+        mesh::VertexAttributeValues,
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+/// How a [crate::render::QuickItemTarget] shows the scene
+///
+/// `BevyQuickItem` keeps this in step with its `viewMode`.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default)]
+pub enum QmlViewMode {
+    /// The materials as they are
+    #[default]
+    Shaded,
+    /// The edges of the triangles on top of the materials
+    Wireframe,
+    /// The vertex normals as lines, colored by their direction
+    Normals,
+    /// A checker pattern laid out by the texture coordinates
+    Uvs,
+    /// A dim color which grows brighter the more surfaces overlap
+    Overdraw,
+    /// The bounding boxes of the meshes on top of the materials
+    Aabbs,
+}
+
+/// How long the lines of [QmlViewMode::Normals] are
+const NORMAL_LENGTH: f32 = 0.1;
+
+/// The most normals drawn in an update, so dense meshes stay responsive
+const MAX_NORMALS: usize = 20_000;
+
+/// How many squares the checker of [QmlViewMode::Uvs] has along each side
+const CHECKER_SQUARES: u32 = 8;
+
+/// The size of the checker image in pixels
+const CHECKER_SIZE: u32 = 256;
+
+pub struct QmlViewModePlugin;
+
+impl Plugin for QmlViewModePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<WireframePlugin>() {
+            app.add_plugins(WireframePlugin);
+        }
+        app.register_type::<QmlViewMode>()
+            .add_systems(PostUpdate, (apply_view_modes, draw_normals));
+    }
+}
+
+/// The material a mesh had before a view mode swapped it
+#[derive(Component)]
+struct ShadedMaterial(Handle<StandardMaterial>);
+
+/// The materials [QmlViewMode::Uvs] and [QmlViewMode::Overdraw] swap in
+struct DebugMaterials {
+    uvs: Handle<StandardMaterial>,
+    overdraw: Handle<StandardMaterial>,
+}
+
+impl DebugMaterials {
+    fn new(images: &mut Assets<Image>, materials: &mut Assets<StandardMaterial>) -> Self {
+        Self {
+            uvs: materials.add(StandardMaterial {
+                base_color_texture: Some(images.add(checker_image())),
+                unlit: true,
+                ..default()
+            }),
+            overdraw: materials.add(StandardMaterial {
+                base_color: Color::srgb(0.25, 0.1, 0.04),
+                alpha_mode: AlphaMode::Add,
+                unlit: true,
+                cull_mode: None,
+                ..default()
+            }),
+        }
+    }
+
+    fn contains(&self, material: &Handle<StandardMaterial>) -> bool {
+        *material == self.uvs || *material == self.overdraw
+    }
+}
+
+/// What the view modes last turned on, so settings made by the app itself
+/// are left alone otherwise
+#[derive(Default)]
+struct GlobalModes {
+    wireframe: bool,
+    aabbs: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_view_modes(
+    mut commands: Commands,
+    modes: Query<&QmlViewMode>,
+    wireframe_config: Option<ResMut<WireframeConfig>>,
+    mut gizmo_config: ResMut<GizmoConfigStore>,
+    mut global: Local<GlobalModes>,
+    mut debug_materials: Local<Option<DebugMaterials>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    meshes: Query<(Entity, &Handle<StandardMaterial>, Option<&ShadedMaterial>), With<Handle<Mesh>>>,
+) {
+    let wants = |mode| modes.iter().any(|other| *other == mode);
+
+    let wireframe = wants(QmlViewMode::Wireframe);
+    if wireframe != global.wireframe {
+        global.wireframe = wireframe;
+        if let Some(mut config) = wireframe_config {
+            config.global = wireframe;
+        }
+    }
+    let aabbs = wants(QmlViewMode::Aabbs);
+    if aabbs != global.aabbs {
+        global.aabbs = aabbs;
+        gizmo_config.config_mut::<AabbGizmoConfigGroup>().1.draw_all = aabbs;
+    }
+
+    let swapped = modes
+        .iter()
+        .find(|mode| matches!(mode, QmlViewMode::Uvs | QmlViewMode::Overdraw));
+    match swapped {
+        Some(mode) => {
+            let debug = debug_materials
+                .get_or_insert_with(|| DebugMaterials::new(&mut images, &mut materials));
+            let wanted = if *mode == QmlViewMode::Uvs {
+                &debug.uvs
+            } else {
+                &debug.overdraw
+            };
+            for (entity, material, _) in &meshes {
+                if material == wanted {
+                    continue;
+                }
+                let mut entity = commands.entity(entity);
+                // Anything but a debug material is the mesh's own, also when
+                // the app changed it in the meantime
+                if !debug.contains(material) {
+                    entity.insert(ShadedMaterial(material.clone()));
+                }
+                entity.insert(wanted.clone());
+            }
+        }
+        None => {
+            for (entity, material, shaded) in &meshes {
+                let Some(ShadedMaterial(shaded)) = shaded else {
+                    continue;
+                };
+                let mut entity = commands.entity(entity);
+                let is_debug = debug_materials
+                    .as_ref()
+                    .is_some_and(|debug| debug.contains(material));
+                if is_debug {
+                    entity.insert(shaded.clone());
+                }
+                entity.remove::<ShadedMaterial>();
+            }
+        }
+    }
+}
+
+fn draw_normals(
+    mut gizmos: Gizmos,
+    modes: Query<&QmlViewMode>,
+    meshes: Res<Assets<Mesh>>,
+    entities: Query<(&Handle<Mesh>, &GlobalTransform, &ViewVisibility)>,
+) {
+    if !modes.iter().any(|mode| *mode == QmlViewMode::Normals) {
+        return;
+    }
+
+    let mut drawn = 0;
+    for (mesh, transform, visibility) in &entities {
+        // Meshes only kept in the render world have no vertices here
+        let Some(mesh) = meshes.get(mesh).filter(|_| visibility.get()) else {
+            continue;
+        };
+        let (Some(positions), Some(normals)) = (
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+                .and_then(VertexAttributeValues::as_float3),
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+                .and_then(VertexAttributeValues::as_float3),
+        ) else {
+            continue;
+        };
+
+        let normal_matrix = Mat3::from(transform.affine().matrix3).inverse().transpose();
+        for (position, normal) in positions.iter().zip(normals) {
+            let normal = Vec3::from(*normal);
+            let start = transform.transform_point(Vec3::from(*position));
+            let direction = (normal_matrix * normal).normalize_or_zero();
+            let color = normal * 0.5 + 0.5;
+            gizmos.line(
+                start,
+                start + direction * NORMAL_LENGTH,
+                Color::srgb(color.x, color.y, color.z),
+            );
+            drawn += 1;
+            if drawn >= MAX_NORMALS {
+                return;
+            }
+        }
+    }
+}
+
+/// Squares alternating between light and dark, tinted red along U and green
+/// along V
+fn checker_image() -> Image {
+    let square = CHECKER_SIZE / CHECKER_SQUARES;
+    let mut data = Vec::with_capacity((CHECKER_SIZE * CHECKER_SIZE * 4) as usize);
+    for y in 0..CHECKER_SIZE {
+        for x in 0..CHECKER_SIZE {
+            let light = (x / square + y / square) % 2 == 0;
+            let shade = if light { 1.0 } else { 0.55 };
+            let u = x as f32 / CHECKER_SIZE as f32;
+            let v = y as f32 / CHECKER_SIZE as f32;
+            data.extend_from_slice(&[
+                ((0.3 + 0.7 * u) * shade * 255.0) as u8,
+                ((0.3 + 0.7 * v) * shade * 255.0) as u8,
+                (0.5 * shade * 255.0) as u8,
+                255,
+            ]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: CHECKER_SIZE,
+            height: CHECKER_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
@@ -0,0 +1,274 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Additional QML windows, for editor style workflows with several windows.
+//!
+//! The [QmlWindowRegistry] of the main app lists the windows which should be
+//! open. Every window loads its QML file in an engine of its own and is shown
+//! as a top level window after the next update. A window either shows more
+//! cameras into the main world, or the world of an independent app hosted
+//! with [crate::plugin::QmlWorldPlugin]:
+//!
+//! ```ignore
+//! fn open_preview(mut windows: ResMut<QmlWindowRegistry>) {
+//!     windows.open("preview", "qrc:/qml/Preview.qml", "preview");
+//! }
+//! ```
+//!
+//! The QML of the window sees the name of its world as `bevyWorld`, which
+//! its views pass on with `BevyQuickItem { world: bevyWorld }`. The other
+//! QML elements always use the main world. Windows closed by the user are
+//! marked as closed in the registry.
+
+#[cxx_qt::bridge(cxx_file_stem = "rust_cxx_qt_window")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qstring.h");
+        /// An alias to the QString type
+        type QString = cxx_qt_lib::QString;
+        include!("cxx-qt-lib/qurl.h");
+        /// An alias to the QUrl type
+        type QUrl = cxx_qt_lib::QUrl;
+    }
+
+    #[namespace = "bevyqml"]
+    unsafe extern "C++" {
+        include!("bevyqml/window.h");
+        /// Shows a QML file as a top level window
+        type QmlWindowHost;
+
+        #[cxx_name = "errorString"]
+        fn error_string(self: &QmlWindowHost) -> QString;
+
+        #[cxx_name = "isVisible"]
+        fn is_visible(self: &QmlWindowHost) -> bool;
+
+        #[cxx_name = "setTitle"]
+        fn set_title(self: Pin<&mut QmlWindowHost>, title: &QString);
+
+        fn raise(self: Pin<&mut QmlWindowHost>);
+
+        #[doc(hidden)]
+        #[rust_name = "qml_window_host_new"]
+        fn qmlWindowHostNew(
+            source: &QUrl,
+            name: &QString,
+            world: &QString,
+            title: &QString,
+        ) -> UniquePtr<QmlWindowHost>;
+    }
+}
+
+use std::cell::RefCell;
+
+use bevy::{ecs::component::Tick, prelude::*, utils::HashMap};
+use cxx::UniquePtr;
+use cxx_qt_lib::{QString, QUrl};
+
+use crate::runtime::{self, UpdateListener};
+
+/// A QML window listed in the [QmlWindowRegistry]
+#[derive(Clone, Debug, Default, PartialEq, Reflect)]
+pub struct QmlWindow {
+    /// Identifies the window, and is passed to its QML as `bevyWindow`
+    pub name: String,
+    /// The URL of the QML file, whose root is a Window or an Item
+    pub source: String,
+    /// Replaces the title set in QML, unless empty
+    pub title: String,
+    /// The independent app the window shows, empty for the main world
+    pub world: String,
+    pub open: bool,
+}
+
+/// The QML windows opened besides the main one
+#[derive(Resource, Clone, Debug, Default, Reflect)]
+#[reflect(Resource, Default)]
+pub struct QmlWindowRegistry {
+    windows: Vec<QmlWindow>,
+    /// Windows to bring to the front after the next update
+    #[reflect(ignore)]
+    raise: Vec<String>,
+}
+
+impl QmlWindowRegistry {
+    pub fn windows(&self) -> &[QmlWindow] {
+        &self.windows
+    }
+
+    pub fn get(&self, name: &str) -> Option<&QmlWindow> {
+        self.windows.iter().find(|window| window.name == name)
+    }
+
+    pub fn is_open(&self, name: &str) -> bool {
+        self.get(name).is_some_and(|window| window.open)
+    }
+
+    /// Open a window showing the world of that name, or the main world if it
+    /// is empty
+    ///
+    /// A window which is already open is brought to the front, and reloaded
+    /// if the source or world changed.
+    pub fn open(
+        &mut self,
+        name: impl Into<String>,
+        source: impl Into<String>,
+        world: impl Into<String>,
+    ) {
+        let name = name.into();
+        let (source, world) = (source.into(), world.into());
+        match self.windows.iter_mut().find(|window| window.name == name) {
+            Some(window) => {
+                window.source = source;
+                window.world = world;
+                window.open = true;
+            }
+            None => self.windows.push(QmlWindow {
+                name: name.clone(),
+                source,
+                world,
+                open: true,
+                ..default()
+            }),
+        }
+        self.raise.push(name);
+    }
+
+    pub fn set_title(&mut self, name: &str, title: impl Into<String>) {
+        if let Some(window) = self.windows.iter_mut().find(|window| window.name == name) {
+            window.title = title.into();
+        }
+    }
+
+    /// Close the window, it stays listed so it can be opened again
+    pub fn close(&mut self, name: &str) {
+        if let Some(window) = self.windows.iter_mut().find(|window| window.name == name) {
+            window.open = false;
+        }
+    }
+
+    /// Close the window and forget about it
+    pub fn remove(&mut self, name: &str) {
+        self.windows.retain(|window| window.name != name);
+    }
+}
+
+pub struct QmlWindowPlugin;
+
+impl Plugin for QmlWindowPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<QmlWindow>()
+            .register_type::<QmlWindowRegistry>()
+            .init_resource::<QmlWindowRegistry>()
+            .insert_non_send_resource(QmlWindowListener(runtime::on_update(sync_windows)));
+    }
+}
+
+/// Opens and closes the windows after every update for as long as the app exists
+struct QmlWindowListener(#[allow(dead_code)] UpdateListener);
+
+struct Host {
+    window: QmlWindow,
+    host: UniquePtr<ffi::QmlWindowHost>,
+}
+
+thread_local! {
+    static HOSTS: RefCell<HashMap<String, Host>> = RefCell::new(HashMap::default());
+    static SEEN: RefCell<Option<Tick>> = const { RefCell::new(None) };
+}
+
+/// Follow the [QmlWindowRegistry], and mark the windows closed by the user
+fn sync_windows() {
+    let seen = SEEN.with(|seen| *seen.borrow());
+    let registry = runtime::with_world(|world| {
+        let mut registry = world.get_resource_mut::<QmlWindowRegistry>()?;
+        let changed = registry.last_changed();
+        let raise = std::mem::take(&mut registry.bypass_change_detection().raise);
+        Some((changed, registry.windows.clone(), raise))
+    })
+    .flatten();
+    let Some((changed, windows, raise)) = registry else {
+        return;
+    };
+
+    let closed = HOSTS.with(|hosts| {
+        let mut hosts = hosts.borrow_mut();
+        if seen != Some(changed) {
+            open_windows(&mut hosts, &windows);
+        }
+        for name in raise {
+            if let Some(host) = hosts.get_mut(&name) {
+                host.host.pin_mut().raise();
+            }
+        }
+
+        let closed: Vec<String> = hosts
+            .iter()
+            .filter(|(_, host)| !host.host.is_visible())
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in &closed {
+            hosts.remove(name);
+        }
+        closed
+    });
+    SEEN.with(|seen| *seen.borrow_mut() = Some(changed));
+
+    if !closed.is_empty() {
+        runtime::with_world(|world| {
+            let mut registry = world.resource_mut::<QmlWindowRegistry>();
+            for name in &closed {
+                registry.close(name);
+            }
+        });
+    }
+}
+
+/// Close every window, when tearing down the app
+pub(crate) fn close_all() {
+    let hosts = HOSTS.with(|hosts| std::mem::take(&mut *hosts.borrow_mut()));
+    drop(hosts);
+    SEEN.with(|seen| seen.borrow_mut().take());
+}
+
+/// Create the hosts of open windows and drop those of closed ones
+fn open_windows(hosts: &mut HashMap<String, Host>, windows: &[QmlWindow]) {
+    hosts.retain(|name, host| {
+        windows.iter().any(|window| {
+            window.open
+                && window.name == *name
+                && window.source == host.window.source
+                && window.world == host.window.world
+        })
+    });
+
+    for window in windows.iter().filter(|window| window.open) {
+        if let Some(host) = hosts.get_mut(&window.name) {
+            if host.window.title != window.title && !window.title.is_empty() {
+                host.host
+                    .pin_mut()
+                    .set_title(&QString::from(window.title.as_str()));
+            }
+            host.window = window.clone();
+            continue;
+        }
+
+        let host = ffi::qml_window_host_new(
+            &QUrl::from(window.source.as_str()),
+            &QString::from(window.name.as_str()),
+            &QString::from(window.world.as_str()),
+            &QString::from(window.title.as_str()),
+        );
+        let error = host.error_string().to_string();
+        if !error.is_empty() {
+            warn!("Failed to open the QML window {:?}: {error}", window.name);
+        }
+        // Failed windows are not visible, so they are marked closed
+        hosts.insert(
+            window.name.clone(),
+            Host {
+                window: window.clone(),
+                host,
+            },
+        );
+    }
+}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+#include <QtCore/QtGlobal>
+#include <QtQml/QQmlEngine>
+#include <QtQuickTest/quicktest.h>
+
+class Setup : public QObject
+{
+  Q_OBJECT
+
+public:
+  // Runs before the application is created, so the tests need no display
+  // unless a platform is asked for
+  Setup()
+  {
+    if (!qEnvironmentVariableIsSet("QT_QPA_PLATFORM")) {
+      qputenv("QT_QPA_PLATFORM", "offscreen");
+    }
+  }
+};
+
+QUICK_TEST_MAIN_WITH_SETUP(module, Setup)
+
+#include "tst_module.moc"
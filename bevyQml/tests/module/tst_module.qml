@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+import QtQuick 2.12
+import QtTest 1.12
+
+import BevyQml 1.0
+
+// Imports the module from outside qml/, so its QML files are only found
+// through the qmldir embedded in the library
+TestCase {
+    name: "ModuleTests"
+
+    function test_qml_file_types_data() {
+        return [
+            { tag: "BevyStatsOverlay" },
+            { tag: "EntityOverlay" },
+            { tag: "EntitySelectionModel" },
+            { tag: "BevyMiniView" },
+        ];
+    }
+
+    // Compiling a component resolves the type without creating it
+    function test_qml_file_types(data) {
+        const component = Qt.createQmlObject(
+            "import QtQuick 2.12; import BevyQml 1.0; Component { " + data.tag + " {} }",
+            this, data.tag);
+        verify(component, data.tag + " is not a type of BevyQml");
+        compare(component.status, Component.Ready, component.errorString());
+        component.destroy();
+    }
+
+    function test_scripts() {
+        compare(typeof BevyAsync.promise, "function");
+    }
+
+    function test_rust_types() {
+        const object = Qt.createQmlObject(
+            "import BevyQml 1.0; MyObject {}", this, "MyObject");
+        verify(object);
+        object.destroy();
+    }
+}
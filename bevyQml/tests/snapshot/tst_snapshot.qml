@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+import QtQuick 2.12
+import QtTest 1.12
+
+import BevyQml 1.0
+
+// Renders the demo scene and compares it with the images in goldens/, which
+// are written by running the test with BEVYQML_UPDATE_GOLDENS=1. A missing
+// golden image fails the test like one which differs.
+Item {
+    width: 320
+    height: 240
+
+    BevyTestApp {
+        id: app
+    }
+
+    BevyQuickItem {
+        id: item
+        anchors.fill: parent
+    }
+
+    GoldenImage {
+        id: golden
+        directory: Qt.resolvedUrl("goldens")
+    }
+
+    SignalSpy {
+        id: updates
+        target: item
+        signalName: "sceneUpdated"
+    }
+
+    SignalSpy {
+        id: captured
+        target: item
+        signalName: "frameCaptured"
+    }
+
+    TestCase {
+        name: "SnapshotTests"
+        when: windowShown
+
+        // Render the number of frames, so assets can load, then compare the
+        // next one with the golden image
+        function compareAfter(frames, name) {
+            updates.clear();
+            tryVerify(() => updates.count >= frames, 10000);
+            captured.clear();
+            item.captureFrame("");
+            captured.wait(10000);
+            verify(golden.compare(captured.signalArguments[0][0], name), golden.message);
+        }
+
+        function test_app_is_running() {
+            verify(app.running);
+        }
+
+        function test_demo_scene() {
+            compareAfter(10, "demo_scene");
+        }
+
+        function test_view_modes_data() {
+            return [
+                { tag: "wireframe", mode: BevyQuickItem.Wireframe },
+                { tag: "normals", mode: BevyQuickItem.Normals },
+                { tag: "uvs", mode: BevyQuickItem.Uvs },
+            ];
+        }
+
+        function test_view_modes(data) {
+            item.viewMode = data.mode;
+            compareAfter(3, "view_mode_" + data.tag);
+            item.viewMode = BevyQuickItem.Shaded;
+        }
+    }
+}